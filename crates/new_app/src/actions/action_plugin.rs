@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+use new_core::action::{ActionHistory, ActionInvoked, ActionRegistry, CommandPalette};
+use new_core::keymap::{Keymap, KeymapEditor};
+
+use crate::actions::keymap_file::{load_keymap, save_keymap};
+use crate::actions::shortcuts::{PendingChord, action_shortcuts, record_key_strokes, record_recent_actions};
+
+pub struct ActionPlugin;
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionRegistry>()
+            .init_resource::<ActionHistory>()
+            .init_resource::<CommandPalette>()
+            .init_resource::<Keymap>()
+            .init_resource::<KeymapEditor>()
+            .init_resource::<PendingChord>()
+            .add_message::<ActionInvoked>()
+            .add_systems(Startup, load_keymap)
+            .add_systems(
+                Update,
+                (record_key_strokes, action_shortcuts, record_recent_actions, save_keymap).chain(),
+            );
+    }
+}
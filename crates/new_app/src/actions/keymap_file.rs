@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use new_core::action::ActionRegistry;
+use new_core::keymap::{InputContext, KeyBinding, KeyStroke, Keymap, KeymapPreset, Navigation};
+
+use crate::preferences::user_files;
+
+const KEYMAP_FILE: &str = "keymap.toml";
+
+// Only what the user changed is written, as text like "Ctrl+K Ctrl+S", so new default
+// bindings still reach them
+#[derive(Serialize, Deserialize, Default)]
+struct KeymapFile {
+    preset: String,
+    #[serde(default)]
+    navigation: BTreeMap<String, String>,
+    #[serde(default)]
+    actions: BTreeMap<String, Vec<BindingEntry>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BindingEntry {
+    keys: String,
+    #[serde(default)]
+    context: String,
+}
+
+// After every plugin has registered its actions, so rebound ones can be found
+pub fn load_keymap(registry: Res<ActionRegistry>, mut keymap: ResMut<Keymap>) {
+    let Some(file) = user_files::read::<KeymapFile>(KEYMAP_FILE) else {
+        return;
+    };
+
+    keymap.set_preset(KeymapPreset::from_str(&file.preset).unwrap_or_default());
+    for (name, keys) in &file.navigation {
+        match (Navigation::from_str(name), KeyStroke::parse(keys)) {
+            (Ok(navigation), Some(stroke)) => {
+                keymap.custom_navigation.insert(navigation, stroke);
+            }
+            _ => warn!("Keymap: {name} = {keys} is not a navigation binding"),
+        }
+    }
+    for (id, entries) in &file.actions {
+        let Some(def) = registry.actions.iter().find(|def| def.id.0 == id) else {
+            warn!("Keymap: no action {id}, its bindings are dropped");
+            continue;
+        };
+        let bindings = entries
+            .iter()
+            .filter_map(|entry| {
+                let context = InputContext::from_str(&entry.context).unwrap_or_default();
+                let binding = KeyBinding::parse(&entry.keys, context);
+                if binding.is_none() {
+                    warn!("Keymap: {} for {id} is not a key binding", entry.keys);
+                }
+                binding
+            })
+            .collect();
+        keymap.custom.insert(def.id, bindings);
+    }
+}
+
+// Every edit is written straight away
+pub fn save_keymap(keymap: Res<Keymap>) {
+    if keymap.is_added() || !keymap.is_changed() {
+        return;
+    }
+    let file = KeymapFile {
+        preset: keymap.preset.to_string(),
+        navigation: keymap
+            .custom_navigation
+            .iter()
+            .map(|(navigation, stroke)| (navigation.to_string(), stroke.to_string()))
+            .collect(),
+        actions: keymap
+            .custom
+            .iter()
+            .map(|(id, bindings)| {
+                let entries = bindings
+                    .iter()
+                    .map(|binding| BindingEntry {
+                        keys: binding.to_string(),
+                        context: binding.context.to_string(),
+                    })
+                    .collect();
+                (id.0.to_owned(), entries)
+            })
+            .collect(),
+    };
+
+    user_files::write(KEYMAP_FILE, &file);
+}
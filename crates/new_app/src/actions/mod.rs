@@ -0,0 +1,3 @@
+pub mod action_plugin;
+pub mod keymap_file;
+pub mod shortcuts;
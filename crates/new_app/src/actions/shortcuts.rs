@@ -0,0 +1,135 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::input::EguiWantsInput;
+
+use new_core::VisibleViewports;
+use new_core::action::{ActionHistory, ActionInvoked, ActionRegistry, COMMAND_PALETTE};
+use new_core::keymap::{CHORD_TIMEOUT, InputButton, InputContext, KeyLookup, KeyStroke, Keymap, KeymapEditor};
+
+// Strokes of a chord pressed so far
+#[derive(Resource, Default, Debug)]
+pub struct PendingChord {
+    strokes: Vec<KeyStroke>,
+    since: f32,
+}
+
+#[derive(SystemParam)]
+pub struct StrokeInput<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    egui_input: Res<'w, EguiWantsInput>,
+    window: Option<Single<'w, 's, &'static Window, With<PrimaryWindow>>>,
+    visible_viewports: Res<'w, VisibleViewports>,
+}
+
+impl StrokeInput<'_, '_> {
+    fn over_viewport(&self) -> bool {
+        self.window
+            .as_ref()
+            .and_then(|window| window.cursor_position())
+            .and_then(|cursor| self.visible_viewports.pane_at(cursor))
+            .is_some()
+    }
+
+    // Most specific first. Typing into a field shuts out everything else.
+    fn contexts(&self) -> Vec<InputContext> {
+        if self.egui_input.wants_keyboard_input() {
+            vec![InputContext::TextField]
+        } else if self.over_viewport() {
+            vec![InputContext::Viewport, InputContext::Global]
+        } else {
+            vec![InputContext::Global]
+        }
+    }
+
+    // Keys and mouse buttons pressed this frame, with the modifiers held. The left mouse
+    // button is never one, it picks and selects.
+    fn strokes(&self, with_mouse: bool) -> Vec<KeyStroke> {
+        let keys = self
+            .keys
+            .get_just_pressed()
+            .filter(|&&key| !KeyStroke::is_modifier(key))
+            .map(|&key| InputButton::Key(key));
+        let mouse = self
+            .mouse
+            .get_just_pressed()
+            .filter(move |&&button| with_mouse && button != MouseButton::Left)
+            .map(|&button| InputButton::Mouse(button));
+        keys.chain(mouse)
+            .map(|button| KeyStroke::pressed(button, &self.keys))
+            .collect()
+    }
+}
+
+// Runs the action bound to the strokes pressed, waiting for the rest of a chord when they
+// start one
+pub fn action_shortcuts(
+    input: StrokeInput,
+    registry: Res<ActionRegistry>,
+    keymap: Res<Keymap>,
+    editor: Res<KeymapEditor>,
+    time: Res<Time>,
+    mut pending: ResMut<PendingChord>,
+    mut invoked: MessageWriter<ActionInvoked>,
+) {
+    let now = time.elapsed_secs();
+    if editor.recording.is_some() || now - pending.since > CHORD_TIMEOUT {
+        pending.strokes.clear();
+    }
+    if editor.recording.is_some() {
+        return;
+    }
+
+    let contexts = input.contexts();
+    // Clicks elsewhere belong to the UI
+    for stroke in input.strokes(input.over_viewport()) {
+        let mut strokes = std::mem::take(&mut pending.strokes);
+        strokes.push(stroke);
+        let mut lookup = keymap.lookup(&registry, &strokes, &contexts);
+        // A stroke that does not go on with the chord may still start something by itself
+        if lookup == KeyLookup::None && strokes.len() > 1 {
+            strokes = vec![stroke];
+            lookup = keymap.lookup(&registry, &strokes, &contexts);
+        }
+
+        match lookup {
+            KeyLookup::Action(id) => {
+                invoked.write(ActionInvoked::new(id));
+            }
+            KeyLookup::Pending => {
+                pending.strokes = strokes;
+                pending.since = now;
+            }
+            KeyLookup::None => {}
+        }
+    }
+}
+
+// Strokes pressed while the keymap pane records a binding go to it instead
+pub fn record_key_strokes(input: StrokeInput, mut editor: ResMut<KeymapEditor>) {
+    if editor.recording.is_none() {
+        return;
+    }
+    let strokes = input.strokes(true);
+    if !strokes.is_empty() {
+        editor.recorded.extend(strokes);
+    }
+}
+
+// Kept once the action runs, not when it only opens the palette to ask for its argument
+pub fn record_recent_actions(
+    mut invoked: MessageReader<ActionInvoked>,
+    registry: Res<ActionRegistry>,
+    mut history: ResMut<ActionHistory>,
+) {
+    for action in invoked.read() {
+        let Some(def) = registry.get(action.id) else {
+            continue;
+        };
+        let waiting = def.prompt.is_some() && action.arg.is_none();
+        if action.id != COMMAND_PALETTE && !waiting {
+            history.record(action.id);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use bevy::math::Vec3A;
+use bevy::math::bounding::{Aabb3d, BoundingVolume, IntersectsVolume};
+
+// Sort-and-sweep along X.
+// Boxes are grown by `reach` so clearance rules see pairs that are close but not touching.
+// `accept` decides whether a candidate pair is worth a narrow phase test.
+pub fn sweep_and_prune(
+    boxes: &[Aabb3d],
+    reach: f32,
+    mut accept: impl FnMut(usize, usize) -> bool,
+) -> Vec<(usize, usize)> {
+    let pad = Vec3A::splat(reach * 0.5);
+    let grown: Vec<Aabb3d> = boxes.iter().map(|aabb| aabb.grow(pad)).collect();
+
+    let mut order: Vec<usize> = (0..grown.len()).collect();
+    order.sort_by(|&a, &b| grown[a].min.x.total_cmp(&grown[b].min.x));
+
+    let mut active: Vec<usize> = Vec::new();
+    let mut pairs = Vec::new();
+
+    for &current in &order {
+        let start_x = grown[current].min.x;
+        active.retain(|&other| grown[other].max.x >= start_x);
+
+        for &other in &active {
+            if grown[current].intersects(&grown[other]) && accept(other, current) {
+                pairs.push((other, current));
+            }
+        }
+
+        active.push(current);
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::math::Vec3;
+
+    fn unit(x: f32) -> Aabb3d {
+        Aabb3d::from_min_max(Vec3::new(x, 0.0, 0.0), Vec3::new(x + 1.0, 1.0, 1.0))
+    }
+
+    fn sorted(mut pairs: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        for pair in &mut pairs {
+            *pair = (pair.0.min(pair.1), pair.0.max(pair.1));
+        }
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn only_overlapping_boxes_pair_up() {
+        let boxes = [unit(3.0), unit(0.0), unit(0.5), unit(1.2)];
+        assert_eq!(sorted(sweep_and_prune(&boxes, 0.0, |_, _| true)), vec![(1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn reach_brings_in_boxes_close_by() {
+        let boxes = [unit(0.0), unit(1.1), unit(2.5)];
+        assert_eq!(sorted(sweep_and_prune(&boxes, 0.0, |_, _| true)), vec![]);
+        assert_eq!(sorted(sweep_and_prune(&boxes, 0.2, |_, _| true)), vec![(0, 1)]);
+        assert_eq!(sorted(sweep_and_prune(&boxes, 0.5, |_, _| true)), vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn rejected_pairs_are_left_out() {
+        let boxes = [unit(0.0), unit(0.5), unit(0.8)];
+        let pairs = sweep_and_prune(&boxes, 0.0, |a, b| a != 0 && b != 0);
+        assert_eq!(sorted(pairs), vec![(1, 2)]);
+    }
+}
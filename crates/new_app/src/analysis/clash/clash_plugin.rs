@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+
+use new_core::clash::{ClashCommand, ClashResults, ClashReview, ClashRules};
+
+use crate::analysis::clash::detection::{
+    ClashIndex,
+    detect_clashes,
+    mark_changed_elements,
+    rebuild_clash_shapes,
+};
+use crate::analysis::clash::review::{
+    apply_clash_commands,
+    remove_outline_for_clash,
+    setup_clash_highlight_material,
+    spawn_outline_for_clash,
+};
+
+pub struct ClashPlugin;
+
+impl Plugin for ClashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClashRules>()
+            .init_resource::<ClashResults>()
+            .init_resource::<ClashIndex>()
+            .init_resource::<ClashReview>()
+            .add_message::<ClashCommand>()
+            .add_systems(Startup, setup_clash_highlight_material)
+            .add_systems(
+                Update,
+                (
+                    apply_clash_commands,
+                    spawn_outline_for_clash,
+                    remove_outline_for_clash,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    mark_changed_elements,
+                    rebuild_clash_shapes,
+                    detect_clashes,
+                )
+                    .chain()
+                    .after(TransformSystems::Propagate),
+            );
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use bevy::tasks::ComputeTaskPool;
+
+use new_core::clash::{Clash, ClashKey, ClashRule, ClashRules, ClashResults, ClashStatus, ClashTest};
+use new_core::element::ElementHeader;
+
+use super::broad_phase::sweep_and_prune;
+use super::narrow_phase::{NarrowHit, clearance_clash, hard_clash};
+use super::shape::ClashShape;
+
+// Jobs handed to a single worker. Small enough to spread a few hundred pairs over all cores.
+const PAIRS_PER_TASK: usize = 32;
+// Meshes turned into shapes by a single worker. Each one is a full pass over its vertices.
+const SHAPES_PER_TASK: usize = 8;
+
+type ChangedElements = (
+    With<ElementHeader>,
+    Or<(Changed<GlobalTransform>, Changed<Mesh3d>, Changed<ElementHeader>)>,
+);
+
+#[derive(Resource, Default)]
+pub struct ClashIndex {
+    pub shapes: HashMap<Entity, ClashShape>,
+    // Entities whose shape must be rebuilt and whose pairs must be tested again
+    pub dirty: HashSet<Entity>,
+    // Entities that need their pairs tested again, shape already up to date or gone
+    pub retest: HashSet<Entity>,
+    // Entities whose geometry changed since the last detection, their reviews no longer hold
+    pub reshaped: HashSet<Entity>,
+}
+
+pub fn mark_changed_elements(
+    mut index: ResMut<ClashIndex>,
+    rules: Res<ClashRules>,
+    changed: Query<Entity, ChangedElements>,
+    elements: Query<(Entity, &Mesh3d), With<ElementHeader>>,
+    mut mesh_events: MessageReader<AssetEvent<Mesh>>,
+    mut removed: RemovedComponents<ElementHeader>,
+) {
+    index.dirty.extend(changed.iter());
+
+    let edited_meshes: HashSet<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    if !edited_meshes.is_empty() {
+        for (entity, mesh) in &elements {
+            if edited_meshes.contains(&mesh.id()) {
+                index.dirty.insert(entity);
+            }
+        }
+    }
+
+    for entity in removed.read() {
+        index.shapes.remove(&entity);
+        index.reshaped.insert(entity);
+        index.dirty.remove(&entity);
+        index.retest.insert(entity);
+    }
+
+    // Rule edits invalidate every pair
+    if rules.is_changed() {
+        let all: Vec<Entity> = index.shapes.keys().copied().collect();
+        index.retest.extend(all);
+    }
+}
+
+pub fn rebuild_clash_shapes(
+    mut index: ResMut<ClashIndex>,
+    meshes: Res<Assets<Mesh>>,
+    elements: Query<(&ElementHeader, &Mesh3d, &GlobalTransform)>,
+) {
+    if index.dirty.is_empty() {
+        return;
+    }
+
+    let jobs: Vec<(Entity, &ElementHeader, &Mesh, GlobalTransform)> = index
+        .dirty
+        .iter()
+        .filter_map(|&entity| {
+            let (header, mesh, transform) = elements.get(entity).ok()?;
+            Some((entity, header, meshes.get(&mesh.0)?, *transform))
+        })
+        .collect();
+
+    let rebuilt: Vec<(Entity, ClashShape)> = ComputeTaskPool::get()
+        .scope(|scope| {
+            for chunk in jobs.chunks(SHAPES_PER_TASK) {
+                scope.spawn(async move {
+                    chunk
+                        .iter()
+                        .filter_map(|(entity, header, mesh, transform)| {
+                            ClashShape::from_mesh(header, mesh, transform)
+                                .map(|shape| (*entity, shape))
+                        })
+                        .collect::<Vec<_>>()
+                });
+            }
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+
+    // Anything that failed to rebuild (mesh not loaded yet, not a triangle list) drops out
+    // of the index until its mesh shows up through an asset event.
+    let dirty: Vec<Entity> = index.dirty.drain().collect();
+    let mut previous: HashMap<Entity, ClashShape> = HashMap::new();
+    for entity in &dirty {
+        if let Some(shape) = index.shapes.remove(entity) {
+            previous.insert(*entity, shape);
+        }
+    }
+    for (entity, shape) in rebuilt {
+        // Header edits rebuild the shape too, only moved or reshaped geometry counts
+        if previous.get(&entity).is_none_or(|old| old.triangles != shape.triangles) {
+            index.reshaped.insert(entity);
+        }
+        index.shapes.insert(entity, shape);
+    }
+    index.retest.extend(dirty);
+}
+
+pub fn detect_clashes(
+    mut index: ResMut<ClashIndex>,
+    rules: Res<ClashRules>,
+    mut results: ResMut<ClashResults>,
+) {
+    if index.retest.is_empty() {
+        return;
+    }
+
+    let retest: HashSet<Entity> = index.retest.drain().collect();
+    let reshaped: HashSet<Entity> = index.reshaped.drain().collect();
+
+    let entities: Vec<Entity> = index.shapes.keys().copied().collect();
+    let shapes: Vec<&ClashShape> = entities.iter().map(|entity| &index.shapes[entity]).collect();
+    let boxes: Vec<_> = shapes.iter().map(|shape| shape.aabb).collect();
+
+    let candidates = sweep_and_prune(&boxes, rules.max_reach(), |i, j| {
+        (retest.contains(&entities[i]) || retest.contains(&entities[j]))
+            && rules.matching(shapes[i].kind, shapes[j].kind).next().is_some()
+    });
+
+    let jobs: Vec<(usize, usize, &ClashRule)> = candidates
+        .into_iter()
+        .flat_map(|(i, j)| {
+            rules
+                .matching(shapes[i].kind, shapes[j].kind)
+                .map(move |rule| (i, j, rule))
+        })
+        .collect();
+
+    let found: HashMap<ClashKey, Clash> = ComputeTaskPool::get()
+        .scope(|scope| {
+            for chunk in jobs.chunks(PAIRS_PER_TASK) {
+                let entities = &entities;
+                let shapes = &shapes;
+                scope.spawn(async move {
+                    chunk
+                        .iter()
+                        .filter_map(|&(i, j, rule)| {
+                            let hit = test_pair(shapes[i], shapes[j], rule)?;
+                            let key = ClashKey::new(rule.id, entities[i], entities[j]);
+                            let (a, b) = if key.a == entities[i] { (i, j) } else { (j, i) };
+
+                            Some(Clash {
+                                key,
+                                a_id: shapes[a].id,
+                                b_id: shapes[b].id,
+                                level_id: shapes[a].level_id.or(shapes[b].level_id),
+                                test: rule.test,
+                                point: hit.point,
+                                distance: hit.distance,
+                                status: ClashStatus::New,
+                                reviewed: false,
+                                assignee: None,
+                                comments: Vec::new(),
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                });
+            }
+        })
+        .into_iter()
+        .flatten()
+        .map(|clash| (clash.key, clash))
+        .collect();
+
+    // Reviews stand until one of the two elements changes shape or moves
+    let review_holds = |clash: &mut Clash| {
+        if clash.reviewed && (reshaped.contains(&clash.key.a) || reshaped.contains(&clash.key.b)) {
+            clash.reviewed = false;
+        }
+        clash.reviewed
+    };
+
+    // Pairs that were looked at again but did not come back are resolved
+    for clash in results.clashes.values_mut() {
+        let retested = retest.contains(&clash.key.a) || retest.contains(&clash.key.b);
+        if retested && !found.contains_key(&clash.key) && !review_holds(clash) {
+            clash.status = ClashStatus::Resolved;
+        }
+    }
+
+    for (key, clash) in found {
+        results
+            .clashes
+            .entry(key)
+            .and_modify(|existing| {
+                if !review_holds(existing) {
+                    existing.status = match existing.status {
+                        ClashStatus::Resolved => ClashStatus::New,
+                        _ => ClashStatus::Active,
+                    };
+                }
+                existing.point = clash.point;
+                existing.distance = clash.distance;
+            })
+            .or_insert(clash);
+    }
+}
+
+fn test_pair(a: &ClashShape, b: &ClashShape, rule: &ClashRule) -> Option<NarrowHit> {
+    match rule.test {
+        ClashTest::Hard => hard_clash(a, b, rule.tolerance),
+        ClashTest::Clearance => clearance_clash(a, b, rule.tolerance),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::tasks::TaskPool;
+    use new_core::elements::ElementKind;
+
+    use crate::analysis::clash::shape::tests::cuboid;
+
+    fn scene(shapes: &[ClashShape]) -> (World, Vec<Entity>) {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+
+        let mut rules = ClashRules { rules: Vec::new() };
+        rules.add("Duct vs Wall", ElementKind::DuctSegment, ElementKind::Wall, ClashTest::Hard, 0.0);
+
+        let mut world = World::new();
+        let entities: Vec<Entity> = shapes.iter().map(|_| world.spawn_empty().id()).collect();
+        let mut index = ClashIndex::default();
+        for (entity, shape) in entities.iter().zip(shapes) {
+            index.shapes.insert(*entity, shape.clone());
+            index.retest.insert(*entity);
+        }
+        world.insert_resource(index);
+        world.insert_resource(rules);
+        world.insert_resource(ClashResults::default());
+        (world, entities)
+    }
+
+    fn detect(world: &mut World) {
+        world.run_system_once(detect_clashes).expect("detection runs");
+    }
+
+    // Clashes still standing, by the ids of the two elements, with their depth in mm
+    fn standing(world: &World) -> Vec<(i64, i64, i32)> {
+        let mut clashes: Vec<(i64, i64, i32)> = world
+            .resource::<ClashResults>()
+            .clashes
+            .values()
+            .filter(|clash| clash.status != ClashStatus::Resolved)
+            .map(|clash| {
+                let (a, b) = (clash.a_id.0.min(clash.b_id.0), clash.a_id.0.max(clash.b_id.0));
+                (a, b, (clash.distance * 1000.0).round() as i32)
+            })
+            .collect();
+        clashes.sort();
+        clashes
+    }
+
+    fn shapes(duct_x: f32) -> Vec<ClashShape> {
+        vec![
+            cuboid(1, ElementKind::Wall, Vec3::new(0.2, 3.0, 10.0), Transform::IDENTITY),
+            cuboid(2, ElementKind::DuctSegment, Vec3::splat(0.4), Transform::from_xyz(duct_x, 1.0, 0.0)),
+            cuboid(3, ElementKind::DuctSegment, Vec3::splat(0.4), Transform::from_xyz(0.25, -1.0, 2.0)),
+        ]
+    }
+
+    #[test]
+    fn retesting_what_moved_matches_a_full_run() {
+        let (mut world, entities) = scene(&shapes(2.0));
+        detect(&mut world);
+        assert_eq!(standing(&world), vec![(1, 3, 50)]);
+
+        // The first duct moves into the wall, only it is tested again
+        let moved = shapes(0.1).swap_remove(1);
+        let mut index = world.resource_mut::<ClashIndex>();
+        index.shapes.insert(entities[1], moved);
+        index.retest.insert(entities[1]);
+        index.reshaped.insert(entities[1]);
+        detect(&mut world);
+
+        let (mut full, _) = scene(&shapes(0.1));
+        detect(&mut full);
+        assert_eq!(standing(&world), standing(&full));
+        assert_eq!(standing(&world), vec![(1, 2, 200), (1, 3, 50)]);
+    }
+
+    #[test]
+    fn pairs_that_part_are_resolved() {
+        let (mut world, entities) = scene(&shapes(0.1));
+        detect(&mut world);
+
+        let moved = shapes(2.0).swap_remove(1);
+        let mut index = world.resource_mut::<ClashIndex>();
+        index.shapes.insert(entities[1], moved);
+        index.retest.insert(entities[1]);
+        detect(&mut world);
+
+        let results = world.resource::<ClashResults>();
+        assert_eq!(results.count(ClashStatus::Resolved), 1);
+        assert_eq!(standing(&world), vec![(1, 3, 50)]);
+    }
+}
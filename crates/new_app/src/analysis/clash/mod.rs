@@ -0,0 +1,6 @@
+pub mod broad_phase;
+pub mod clash_plugin;
+pub mod detection;
+pub mod narrow_phase;
+pub mod review;
+pub mod shape;
@@ -0,0 +1,438 @@
+use bevy::math::Vec3A;
+use bevy::math::bounding::{Aabb3d, BoundingVolume, IntersectsVolume};
+use bevy::prelude::*;
+
+use super::shape::{ClashShape, Triangle, aabb_overlap};
+
+// Distances under this are touching, meters. About as fine as f32 resolves at building scale.
+const TOLERANCE: f32 = 1e-5;
+// Cosine above which two face normals are taken for one direction
+const SAME_DIRECTION: f32 = 0.9999;
+
+#[derive(Clone, Copy, Debug)]
+pub struct NarrowHit {
+    pub point: Vec3,
+    // Penetration for hard clashes, gap for clearance clashes
+    pub distance: f32,
+}
+
+// Meshes intersect or one is inside the other.
+// Penetration is the shortest push along the faces in contact, or along the axes, that pulls the
+// two apart. That is exact for convex elements whichever way they run. The overlapping bounds
+// only rule pairs out early: their thinnest side is never less than the penetration.
+pub fn hard_clash(a: &ClashShape, b: &ClashShape, tolerance: f32) -> Option<NarrowHit> {
+    let region = aabb_overlap(&a.aabb, &b.aabb)?;
+    let size = region.max - region.min;
+    if size.min_element() <= tolerance {
+        return None;
+    }
+
+    let tris_a = a.triangles_in(&region);
+    let tris_b = b.triangles_in(&region);
+
+    let mut hits = Vec::new();
+    let mut normals = Vec::new();
+    for (tri_a, bounds_a) in &tris_a {
+        for (tri_b, bounds_b) in &tris_b {
+            if !bounds_a.intersects(bounds_b) {
+                continue;
+            }
+
+            if let Some(point) = triangles_intersect(tri_a, tri_b) {
+                hits.push(point);
+                normals.extend([triangle_normal(tri_a), triangle_normal(tri_b)]);
+            }
+        }
+    }
+
+    let point = if !hits.is_empty() {
+        hits.iter().copied().sum::<Vec3>() / hits.len() as f32
+    } else {
+        let contained = a.triangles.first().is_some_and(|tri| contains_point(b, tri[0]))
+            || b.triangles.first().is_some_and(|tri| contains_point(a, tri[0]));
+        if !contained {
+            return None;
+        }
+        // Nothing touches, any face of either may be the way out
+        normals.extend(a.triangles.iter().chain(b.triangles.iter()).map(triangle_normal));
+        Vec3::from(region.center())
+    };
+
+    let penetration = penetration(a, b, &normals);
+    (penetration > tolerance).then_some(NarrowHit { point, distance: penetration })
+}
+
+// Least overlap of the two shapes along the axes and the given directions
+fn penetration(a: &ClashShape, b: &ClashShape, normals: &[Vec3]) -> f32 {
+    let mut directions = vec![Vec3::X, Vec3::Y, Vec3::Z];
+    for normal in normals {
+        let Some(normal) = normal.try_normalize() else {
+            continue;
+        };
+        if directions.iter().all(|direction| direction.dot(normal).abs() < SAME_DIRECTION) {
+            directions.push(normal);
+        }
+    }
+
+    directions
+        .iter()
+        .map(|&direction| {
+            let (min_a, max_a) = extent(a, direction);
+            let (min_b, max_b) = extent(b, direction);
+            max_a.min(max_b) - min_a.max(min_b)
+        })
+        .fold(f32::MAX, f32::min)
+        .max(0.0)
+}
+
+fn extent(shape: &ClashShape, direction: Vec3) -> (f32, f32) {
+    shape
+        .triangles
+        .iter()
+        .flatten()
+        .map(|point| point.dot(direction))
+        .fold((f32::MAX, f32::MIN), |(min, max), along| (min.min(along), max.max(along)))
+}
+
+fn triangle_normal(tri: &Triangle) -> Vec3 {
+    (tri[1] - tri[0]).cross(tri[2] - tri[0])
+}
+
+// Meshes come closer than the tolerance. Touching and intersecting count as a gap of zero.
+pub fn clearance_clash(a: &ClashShape, b: &ClashShape, tolerance: f32) -> Option<NarrowHit> {
+    let pad = Vec3A::splat(tolerance * 0.5);
+    let region = aabb_overlap(&a.aabb.grow(pad), &b.aabb.grow(pad))?;
+
+    // Faces across a gap lie outside the shared box, up to the tolerance away from it
+    let reach = region.grow(pad);
+    let tris_a = a.triangles_in(&reach);
+    let tris_b = b.triangles_in(&reach);
+
+    let mut best: Option<(f32, Vec3, Vec3)> = None;
+    for (tri_a, bounds_a) in &tris_a {
+        for (tri_b, bounds_b) in &tris_b {
+            if !bounds_a.grow(pad).intersects(&bounds_b.grow(pad)) {
+                continue;
+            }
+
+            let (distance, on_a, on_b) = triangle_distance(tri_a, tri_b);
+            if distance <= TOLERANCE {
+                return Some(NarrowHit { point: on_a, distance: 0.0 });
+            }
+
+            if best.is_none_or(|(current, _, _)| distance < current) {
+                best = Some((distance, on_a, on_b));
+            }
+        }
+    }
+
+    let (distance, on_a, on_b) = best?;
+    (distance < tolerance).then(|| NarrowHit {
+        point: (on_a + on_b) * 0.5,
+        distance,
+    })
+}
+
+// Non-coplanar triangles intersect when an edge of one pierces the other
+pub fn triangles_intersect(a: &Triangle, b: &Triangle) -> Option<Vec3> {
+    for i in 0..3 {
+        if let Some(point) = segment_triangle(a[i], a[(i + 1) % 3], b) {
+            return Some(point);
+        }
+        if let Some(point) = segment_triangle(b[i], b[(i + 1) % 3], a) {
+            return Some(point);
+        }
+    }
+    None
+}
+
+// Moller-Trumbore limited to the segment p..q
+pub fn segment_triangle(p: Vec3, q: Vec3, tri: &Triangle) -> Option<Vec3> {
+    let dir = q - p;
+    let e1 = tri[1] - tri[0];
+    let e2 = tri[2] - tri[0];
+
+    let h = dir.cross(e2);
+    let det = e1.dot(h);
+    // Parallel to the plane, relative to the lengths so small and large triangles alike pass
+    if det.abs() <= f32::EPSILON * dir.length() * e1.length() * e2.length() {
+        return None;
+    }
+
+    let inv = 1.0 / det;
+    let s = p - tri[0];
+    let u = inv * s.dot(h);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let qv = s.cross(e1);
+    let v = inv * dir.dot(qv);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = inv * e2.dot(qv);
+    (0.0..=1.0).contains(&t).then(|| p + dir * t)
+}
+
+// Ray parity test. Only meaningful for closed meshes.
+pub fn contains_point(shape: &ClashShape, point: Vec3) -> bool {
+    if !shape.aabb.closest_point(point).abs_diff_eq(point.into(), TOLERANCE) {
+        return false;
+    }
+
+    // Slightly skewed so the ray does not run along edges of axis aligned boxes
+    let length = (shape.aabb.max - shape.aabb.min).length() * 2.0 + 1.0;
+    let end = point + Vec3::new(1.0, 0.000_37, 0.000_91).normalize() * length;
+    let ray_bounds = Aabb3d::from_min_max(point.min(end), point.max(end));
+
+    let crossings = shape
+        .triangles_in(&ray_bounds)
+        .iter()
+        .filter(|(tri, _)| segment_triangle(point, end, tri).is_some())
+        .count();
+
+    crossings % 2 == 1
+}
+
+// Returns the distance and the closest point on each triangle
+pub fn triangle_distance(a: &Triangle, b: &Triangle) -> (f32, Vec3, Vec3) {
+    if let Some(point) = triangles_intersect(a, b) {
+        return (0.0, point, point);
+    }
+
+    let mut best = (f32::MAX, Vec3::ZERO, Vec3::ZERO);
+    let mut consider = |p: Vec3, q: Vec3| {
+        let distance = p.distance(q);
+        if distance < best.0 {
+            best = (distance, p, q);
+        }
+    };
+
+    for i in 0..3 {
+        consider(a[i], closest_point_on_triangle(a[i], b));
+        consider(closest_point_on_triangle(b[i], a), b[i]);
+
+        for j in 0..3 {
+            let (p, q) = closest_points_on_segments(a[i], a[(i + 1) % 3], b[j], b[(j + 1) % 3]);
+            consider(p, q);
+        }
+    }
+
+    best
+}
+
+// Ericson, Real-Time Collision Detection 5.1.5
+pub fn closest_point_on_triangle(p: Vec3, tri: &Triangle) -> Vec3 {
+    let [a, b, c] = *tri;
+    let ab = b - a;
+    let ac = c - a;
+
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+// Ericson, Real-Time Collision Detection 5.1.9
+pub fn closest_points_on_segments(p1: Vec3, q1: Vec3, p2: Vec3, q2: Vec3) -> (Vec3, Vec3) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    // Segments shorter than the tolerance are points
+    let short = TOLERANCE * TOLERANCE;
+    if a <= short && e <= short {
+        return (p1, p2);
+    }
+
+    let (s, t) = if a <= short {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= short {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denom = a * e - b * b;
+            let mut s = if denom != 0.0 {
+                ((b * f - c * e) / denom).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let mut t = (b * s + f) / e;
+
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::clash::shape::tests::cuboid;
+    use new_core::elements::ElementKind;
+
+    fn flat(z: f32) -> Triangle {
+        [Vec3::new(0.0, 0.0, z), Vec3::new(1.0, 0.0, z), Vec3::new(0.0, 1.0, z)]
+    }
+
+    fn close(value: f32, expected: f32) -> bool {
+        (value - expected).abs() < 1e-4
+    }
+
+    fn at(x: f32, y: f32, z: f32) -> Transform {
+        Transform::from_xyz(x, y, z)
+    }
+
+    #[test]
+    fn crossing_triangles_intersect() {
+        let upright = [Vec3::new(0.2, 0.2, -1.0), Vec3::new(0.2, 0.2, 1.0), Vec3::new(0.3, 0.25, 0.0)];
+        let point = triangles_intersect(&flat(0.0), &upright).expect("pierced");
+        assert!(close(point.z, 0.0));
+        assert_eq!(triangle_distance(&flat(0.0), &upright).0, 0.0);
+    }
+
+    #[test]
+    fn coplanar_triangles_do_not_pierce_but_touch() {
+        let shifted = [Vec3::new(0.2, 0.2, 0.0), Vec3::new(1.2, 0.2, 0.0), Vec3::new(0.2, 1.2, 0.0)];
+        assert!(triangles_intersect(&flat(0.0), &shifted).is_none());
+        assert!(close(triangle_distance(&flat(0.0), &shifted).0, 0.0));
+    }
+
+    #[test]
+    fn triangles_touching_at_an_edge_intersect() {
+        // Standing on the flat one, its lower edge lying across it
+        let standing = [Vec3::new(0.1, 0.1, 0.0), Vec3::new(0.4, 0.1, 0.0), Vec3::new(0.25, 0.1, 1.0)];
+        assert!(triangles_intersect(&flat(0.0), &standing).is_some());
+        assert_eq!(triangle_distance(&flat(0.0), &standing).0, 0.0);
+    }
+
+    #[test]
+    fn disjoint_triangles_keep_their_gap() {
+        assert!(triangles_intersect(&flat(0.0), &flat(0.5)).is_none());
+        let (distance, on_a, on_b) = triangle_distance(&flat(0.0), &flat(0.5));
+        assert!(close(distance, 0.5));
+        assert!(close(on_b.z - on_a.z, 0.5));
+
+        let beside = [Vec3::new(2.0, 0.0, 0.0), Vec3::new(3.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 0.0)];
+        assert!(close(triangle_distance(&flat(0.0), &beside).0, 1.0));
+    }
+
+    #[test]
+    fn points_inside_a_closed_box_are_contained() {
+        let shape = cuboid(1, ElementKind::Wall, Vec3::ONE, at(0.0, 0.0, 0.0));
+        assert!(contains_point(&shape, Vec3::ZERO));
+        assert!(contains_point(&shape, Vec3::new(0.45, -0.45, 0.3)));
+        assert!(!contains_point(&shape, Vec3::new(0.6, 0.0, 0.0)));
+        assert!(!contains_point(&shape, Vec3::new(5.0, 5.0, 5.0)));
+    }
+
+    #[test]
+    fn a_box_inside_another_is_a_hard_clash() {
+        let outer = cuboid(1, ElementKind::Wall, Vec3::splat(2.0), at(0.0, 0.0, 0.0));
+        let inner = cuboid(2, ElementKind::PipeSegment, Vec3::splat(0.2), at(0.1, 0.2, 0.0));
+        let hit = hard_clash(&outer, &inner, 0.0).expect("contained");
+        assert!(close(hit.distance, 0.2));
+        assert!(hit.point.distance(Vec3::new(0.1, 0.2, 0.0)) < 1e-4);
+    }
+
+    #[test]
+    fn hard_clashes_need_more_than_the_tolerance() {
+        let wall = cuboid(1, ElementKind::Wall, Vec3::ONE, at(0.0, 0.0, 0.0));
+        // Runs 20 mm into the wall
+        let duct = cuboid(2, ElementKind::DuctSegment, Vec3::new(1.0, 0.4, 0.4), at(0.98, 0.0, 0.0));
+
+        let hit = hard_clash(&wall, &duct, 0.019).expect("past the tolerance");
+        assert!(close(hit.distance, 0.02));
+        assert!(hard_clash(&wall, &duct, 0.021).is_none());
+
+        let apart = cuboid(3, ElementKind::DuctSegment, Vec3::ONE, at(1.5, 0.0, 0.0));
+        assert!(hard_clash(&wall, &apart, 0.0).is_none());
+    }
+
+    #[test]
+    fn a_diagonal_graze_is_as_deep_as_the_graze() {
+        let column = cuboid(1, ElementKind::Column, Vec3::new(0.4, 3.0, 0.4), at(0.0, 0.0, 0.0));
+
+        // Brace across the plan at 45°, its side face 1 mm past the corner of the column
+        let rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_4);
+        let side = rotation * Vec3::Z;
+        let corner = side.dot(Vec3::new(0.2, 0.0, 0.2));
+        let center = side * (corner - 0.001 + 0.1);
+        let brace = cuboid(
+            2,
+            ElementKind::Beam,
+            Vec3::new(4.0, 0.2, 0.2),
+            Transform::from_translation(center).with_rotation(rotation),
+        );
+
+        // The bounds overlap by 200 mm
+        let bounds = aabb_overlap(&column.aabb, &brace.aabb).expect("overlapping bounds");
+        assert!((bounds.max - bounds.min).min_element() > 0.19);
+
+        let hit = hard_clash(&column, &brace, 0.0).expect("grazing");
+        assert!((hit.distance - 0.001).abs() < 2e-4, "{} deep", hit.distance);
+        assert!(hard_clash(&column, &brace, 0.01).is_none());
+    }
+
+    #[test]
+    fn clearance_clashes_report_the_gap() {
+        let tray = cuboid(1, ElementKind::CableCarrierSegment, Vec3::ONE, at(0.0, 0.0, 0.0));
+        let pipe = cuboid(2, ElementKind::PipeSegment, Vec3::ONE, at(0.0, 1.1, 0.0));
+
+        let hit = clearance_clash(&tray, &pipe, 0.15).expect("too close");
+        assert!(close(hit.distance, 0.1));
+        assert!(clearance_clash(&tray, &pipe, 0.05).is_none());
+
+        let touching = cuboid(3, ElementKind::PipeSegment, Vec3::ONE, at(0.0, 1.0, 0.0));
+        assert_eq!(clearance_clash(&tray, &touching, 0.15).map(|hit| hit.distance), Some(0.0));
+    }
+}
@@ -0,0 +1,153 @@
+use bevy::camera::Projection;
+use bevy::math::bounding::BoundingVolume;
+use bevy::pbr::MeshMaterial3d;
+use bevy::prelude::*;
+use bevy::render::render_resource::Face;
+
+use new_core::clash::{ClashCommand, ClashResults};
+use new_core::display::{DisplayOverrides, DisplaySource, ElementAppearance};
+use new_core::element::ElementHeader;
+use new_core::{ActiveViewport, GameViewportCamera, VisibleViewports};
+
+use crate::analysis::clash::detection::ClashIndex;
+use crate::camera::controls::ViewportOrbitCamera;
+use crate::editor::display::resolve::write_override;
+
+// Element that is part of the clash being reviewed
+#[derive(Component)]
+pub struct ClashHighlight;
+
+#[derive(Component)]
+pub struct ClashHighlightOutline;
+
+#[derive(Resource, Clone)]
+pub struct ClashHighlightMaterial(pub Handle<StandardMaterial>);
+
+pub fn setup_clash_highlight_material(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.95, 0.2, 0.15),
+        unlit: true,
+        cull_mode: Some(Face::Front),
+        depth_bias: 1.0,
+        ..Default::default()
+    });
+
+    commands.insert_resource(ClashHighlightMaterial(material));
+}
+
+pub fn apply_clash_commands(
+    mut commands: Commands,
+    mut messages: MessageReader<ClashCommand>,
+    results: Res<ClashResults>,
+    index: Res<ClashIndex>,
+    active_viewport: Res<ActiveViewport>,
+    visible_viewports: Res<VisibleViewports>,
+    mut cameras: Query<(&GameViewportCamera, &Projection, &mut Transform, &mut ViewportOrbitCamera)>,
+    mut elements: Query<(Entity, Option<&mut DisplayOverrides>), With<ElementHeader>>,
+    highlighted: Query<Entity, With<ClashHighlight>>,
+) {
+    for command in messages.read() {
+        // Every command starts from a clean model
+        for entity in &highlighted {
+            commands.entity(entity).remove::<ClashHighlight>();
+        }
+        for (entity, overrides) in &mut elements {
+            write_override(&mut commands, entity, overrides, DisplaySource::ClashIsolation, None);
+        }
+
+        let ClashCommand::Focus(key) = *command else {
+            continue;
+        };
+
+        let Some(clash) = results.clashes.get(&key) else {
+            continue;
+        };
+
+        for (entity, overrides) in &mut elements {
+            if key.involves(entity) {
+                commands.entity(entity).insert(ClashHighlight);
+                continue;
+            }
+
+            write_override(
+                &mut commands,
+                entity,
+                overrides,
+                DisplaySource::ClashIsolation,
+                Some(ElementAppearance::Hidden),
+            );
+        }
+
+        let bounds = [key.a, key.b]
+            .iter()
+            .filter_map(|entity| index.shapes.get(entity).map(|shape| shape.aabb))
+            .reduce(|acc, aabb| acc.merge(&aabb));
+
+        let (center, radius) = match bounds {
+            Some(aabb) => (Vec3::from(aabb.center()), Vec3::from(aabb.half_size()).length()),
+            None => (clash.point, 1.0),
+        };
+
+        let Some(pane_id) = active_viewport
+            .pane_id
+            .filter(|pane_id| visible_viewports.rects.contains_key(pane_id))
+            .or_else(|| visible_viewports.rects.keys().min().copied())
+        else {
+            continue;
+        };
+
+        for (tag, projection, mut transform, mut orbit) in &mut cameras {
+            if tag.pane_id != pane_id {
+                continue;
+            }
+
+            let half_fov = match projection {
+                Projection::Perspective(perspective) => perspective.fov * 0.5,
+                _ => 30f32.to_radians(),
+            };
+
+            orbit.pivot = center;
+            orbit.distance = (radius.max(0.1) / half_fov.sin() * 1.2)
+                .clamp(orbit.min_distance, orbit.max_distance);
+            orbit.apply_to_transform(&mut transform);
+        }
+    }
+}
+
+pub fn spawn_outline_for_clash(
+    mut commands: Commands,
+    material: Res<ClashHighlightMaterial>,
+    highlighted: Query<(Entity, &Mesh3d), Added<ClashHighlight>>,
+) {
+    for (entity, mesh) in &highlighted {
+        commands.entity(entity).with_children(|child| {
+            child.spawn((
+                Name::new("ClashHighlightOutline"),
+                Mesh3d(mesh.0.clone()),
+                MeshMaterial3d(material.0.clone()),
+                Transform::from_scale(Vec3::splat(1.05)),
+                ClashHighlightOutline,
+            ));
+        });
+    }
+}
+
+pub fn remove_outline_for_clash(
+    mut commands: Commands,
+    mut removed: RemovedComponents<ClashHighlight>,
+    children: Query<&Children>,
+    outlines: Query<(), With<ClashHighlightOutline>>,
+) {
+    for entity in removed.read() {
+        if let Ok(kids) = children.get(entity) {
+            for child in kids.iter() {
+                if outlines.contains(child) {
+                    commands.entity(child).despawn();
+                }
+            }
+        }
+    }
+}
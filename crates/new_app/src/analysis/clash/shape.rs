@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use bevy::math::bounding::{Aabb3d, BoundingVolume, IntersectsVolume};
+use bevy::mesh::{PrimitiveTopology, VertexAttributeValues};
+use bevy::prelude::*;
+
+use new_core::element::{ElementHeader, ElementId};
+use new_core::elements::ElementKind;
+
+pub type Triangle = [Vec3; 3];
+
+// World space snapshot of an element used by the clash engine.
+// Triangles are behind an Arc so a detection pass can share them across threads.
+#[derive(Clone, Debug)]
+pub struct ClashShape {
+    pub id: ElementId,
+    pub kind: ElementKind,
+    pub level_id: Option<ElementId>,
+    pub aabb: Aabb3d,
+    pub triangles: Arc<Vec<Triangle>>,
+}
+
+impl ClashShape {
+    pub fn from_mesh(
+        header: &ElementHeader,
+        mesh: &Mesh,
+        transform: &GlobalTransform,
+    ) -> Option<Self> {
+        let triangles = world_triangles(mesh, transform)?;
+        let aabb = triangles_aabb(&triangles)?;
+
+        Some(Self {
+            id: header.id,
+            kind: header.kind,
+            level_id: header.level_id,
+            aabb,
+            triangles: Arc::new(triangles),
+        })
+    }
+
+    // Triangles that touch the given box
+    pub fn triangles_in(&self, region: &Aabb3d) -> Vec<(Triangle, Aabb3d)> {
+        self.triangles
+            .iter()
+            .filter_map(|tri| {
+                let bounds = triangle_aabb(tri);
+                bounds.intersects(region).then_some((*tri, bounds))
+            })
+            .collect()
+    }
+}
+
+pub fn world_triangles(mesh: &Mesh, transform: &GlobalTransform) -> Option<Vec<Triangle>> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+
+    let Ok(VertexAttributeValues::Float32x3(positions)) =
+        mesh.try_attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+
+    let affine = transform.affine();
+    let points: Vec<Vec3> = positions
+        .iter()
+        .map(|p| affine.transform_point3(Vec3::from_array(*p)))
+        .collect();
+
+    let triangles = match mesh.try_indices_option().ok()? {
+        Some(indices) => {
+            let indices: Vec<usize> = indices.iter().collect();
+            indices
+                .chunks_exact(3)
+                .filter(|t| t.iter().all(|&i| i < points.len()))
+                .map(|t| [points[t[0]], points[t[1]], points[t[2]]])
+                .collect()
+        }
+        None => points.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect(),
+    };
+
+    Some(triangles)
+}
+
+pub fn triangle_aabb(tri: &Triangle) -> Aabb3d {
+    Aabb3d::from_min_max(tri[0].min(tri[1]).min(tri[2]), tri[0].max(tri[1]).max(tri[2]))
+}
+
+pub fn triangles_aabb(triangles: &[Triangle]) -> Option<Aabb3d> {
+    triangles
+        .iter()
+        .map(triangle_aabb)
+        .reduce(|acc, bounds| acc.merge(&bounds))
+}
+
+// Box shared by both inputs, None when they are apart
+pub fn aabb_overlap(a: &Aabb3d, b: &Aabb3d) -> Option<Aabb3d> {
+    let min = a.min.max(b.min);
+    let max = a.max.min(b.max);
+
+    min.cmple(max).all().then_some(Aabb3d { min, max })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    // Closed box of the given size, turned and then moved into place
+    pub fn cuboid(id: i64, kind: ElementKind, size: Vec3, transform: Transform) -> ClashShape {
+        let mesh = Mesh::from(Cuboid::from_size(size));
+        let header = ElementHeader {
+            id: ElementId(id),
+            name: None,
+            kind,
+            kind_type: None,
+            spec_id: None,
+            level_id: None,
+            params: Default::default(),
+        };
+        ClashShape::from_mesh(&header, &mesh, &GlobalTransform::from(transform)).expect("triangle list")
+    }
+
+    #[test]
+    fn overlap_is_none_once_the_boxes_part() {
+        let a = Aabb3d::from_min_max(Vec3::ZERO, Vec3::ONE);
+        let b = Aabb3d::from_min_max(Vec3::splat(0.5), Vec3::splat(2.0));
+        let c = Aabb3d::from_min_max(Vec3::new(1.5, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+
+        let shared = aabb_overlap(&a, &b).expect("overlapping");
+        assert_eq!(Vec3::from(shared.min), Vec3::splat(0.5));
+        assert_eq!(Vec3::from(shared.max), Vec3::ONE);
+        assert!(aabb_overlap(&a, &c).is_none());
+    }
+}
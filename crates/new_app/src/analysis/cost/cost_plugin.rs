@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+
+use new_core::cost::{CostEstimate, CostSchedule, CostView};
+
+use crate::analysis::cost::estimate::{
+    PendingTakeoff,
+    mark_changed_for_takeoff,
+    reprice_estimate,
+    update_takeoff,
+};
+
+pub struct CostPlugin;
+
+impl Plugin for CostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CostSchedule>()
+            .init_resource::<CostEstimate>()
+            .init_resource::<CostView>()
+            .init_resource::<PendingTakeoff>()
+            .add_systems(
+                PostUpdate,
+                (
+                    mark_changed_for_takeoff,
+                    reprice_estimate,
+                    update_takeoff,
+                )
+                    .chain()
+                    .after(TransformSystems::Propagate),
+            );
+    }
+}
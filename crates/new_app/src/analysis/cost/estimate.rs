@@ -0,0 +1,125 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use new_core::cost::{CostEstimate, CostLine, CostSchedule};
+use new_core::element::ElementHeader;
+use new_core::mep::MepSegment;
+use new_core::profile::Framing;
+use new_core::room::Room;
+
+use super::quantities::{measure, placement};
+
+type ChangedElements = (
+    With<ElementHeader>,
+    Or<(
+        Changed<GlobalTransform>,
+        Changed<Mesh3d>,
+        Changed<ElementHeader>,
+        Changed<Framing>,
+        Changed<MepSegment>,
+        Changed<Room>,
+    )>,
+);
+
+type MeasuredElements<'a> = (
+    &'a ElementHeader,
+    &'a Mesh3d,
+    &'a GlobalTransform,
+    Option<&'a Framing>,
+    Option<&'a MepSegment>,
+    Option<&'a Room>,
+);
+
+// Elements whose quantities must be measured again
+#[derive(Resource, Default)]
+pub struct PendingTakeoff {
+    pub dirty: HashSet<Entity>,
+}
+
+pub fn mark_changed_for_takeoff(
+    mut pending: ResMut<PendingTakeoff>,
+    mut estimate: ResMut<CostEstimate>,
+    changed: Query<Entity, ChangedElements>,
+    elements: Query<(Entity, &Mesh3d), With<ElementHeader>>,
+    mut mesh_events: MessageReader<AssetEvent<Mesh>>,
+    mut removed: RemovedComponents<ElementHeader>,
+) {
+    pending.dirty.extend(changed.iter());
+
+    let edited_meshes: HashSet<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    if !edited_meshes.is_empty() {
+        for (entity, mesh) in &elements {
+            if edited_meshes.contains(&mesh.id()) {
+                pending.dirty.insert(entity);
+            }
+        }
+    }
+
+    for entity in removed.read() {
+        pending.dirty.remove(&entity);
+        estimate.lines.remove(&entity);
+    }
+}
+
+pub fn update_takeoff(
+    mut pending: ResMut<PendingTakeoff>,
+    mut estimate: ResMut<CostEstimate>,
+    schedule: Res<CostSchedule>,
+    meshes: Res<Assets<Mesh>>,
+    elements: Query<MeasuredElements>,
+) {
+    if pending.dirty.is_empty() {
+        return;
+    }
+
+    // Meshes that are not loaded yet drop out until their asset event marks them again
+    let dirty: Vec<Entity> = pending.dirty.drain().collect();
+    for entity in dirty {
+        let Ok((header, mesh, transform, framing, segment, room)) = elements.get(entity) else {
+            continue;
+        };
+        let placement = placement(framing, segment, room);
+        let Some(quantities) = meshes.get(&mesh.0).and_then(|mesh| measure(mesh, transform, &placement)) else {
+            estimate.lines.remove(&entity);
+            continue;
+        };
+
+        let mut line = CostLine {
+            element: header.id,
+            kind: header.kind,
+            spec_id: header.spec_id,
+            level_id: header.level_id,
+            quantities,
+            item: None,
+            quantity: 0.0,
+            cost: 0.0,
+        };
+        line.price(&schedule, header);
+        estimate.lines.insert(entity, line);
+    }
+}
+
+// Rate or assignment edits only need new prices, the quantities still hold
+pub fn reprice_estimate(
+    schedule: Res<CostSchedule>,
+    mut estimate: ResMut<CostEstimate>,
+    headers: Query<&ElementHeader>,
+) {
+    if !schedule.is_changed() {
+        return;
+    }
+
+    for (entity, line) in estimate.lines.iter_mut() {
+        if let Ok(header) = headers.get(*entity) {
+            line.price(&schedule, header);
+        }
+    }
+}
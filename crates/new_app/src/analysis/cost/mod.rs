@@ -0,0 +1,3 @@
+pub mod cost_plugin;
+pub mod estimate;
+pub mod quantities;
@@ -0,0 +1,224 @@
+use bevy::mesh::VertexAttributeValues;
+use bevy::prelude::*;
+
+use new_core::cost::Quantities;
+use new_core::mep::MepSegment;
+use new_core::placement::{Curve3, Placement, Point3, Profile3};
+use new_core::profile::Framing;
+use new_core::room::Room;
+
+use crate::analysis::clash::shape::world_triangles;
+
+// The axis or outline an element is drawn from, where it has one
+pub fn placement(
+    framing: Option<&Framing>,
+    segment: Option<&MepSegment>,
+    room: Option<&Room>,
+) -> Placement {
+    if let Some(framing) = framing.filter(|framing| framing.axis.is_valid()) {
+        return Placement::Curve(framing.axis.clone());
+    }
+    if let Some(segment) = segment {
+        return Placement::Curve(Curve3::line(Point3::default(), Point3::from_vec3(segment.end)));
+    }
+    if let Some(room) = room.filter(|room| room.profile.outline().is_some()) {
+        return Placement::Profile(room.profile.clone());
+    }
+    Placement::None
+}
+
+// Quantities of one element in world units. Length runs along the axis and area lies inside
+// the outline of elements placed by one. The rest are measured by the sides of their mesh box.
+pub fn measure(mesh: &Mesh, transform: &GlobalTransform, placement: &Placement) -> Option<Quantities> {
+    let triangles = world_triangles(mesh, transform)?;
+
+    // Divergence theorem, sum of signed tetrahedra against the origin
+    let volume = triangles
+        .iter()
+        .map(|[a, b, c]| a.dot(b.cross(*c)) as f64)
+        .sum::<f64>()
+        .abs()
+        / 6.0;
+
+    // Sides of the element box along its own axes, so a rotated duct keeps its length
+    let Ok(VertexAttributeValues::Float32x3(positions)) =
+        mesh.try_attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let (min, max) = positions.iter().map(|p| Vec3::from_array(*p)).fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), p| (min.min(p), max.max(p)),
+    );
+    if min.cmpgt(max).any() {
+        return None;
+    }
+
+    let (scale, _, _) = transform.to_scale_rotation_translation();
+    let mut sides = ((max - min) * scale.abs()).to_array();
+    sides.sort_by(|a, b| b.total_cmp(a));
+
+    let (length, area) = match placement {
+        // A swept section, taking the wide side of the mesh across the start of the run
+        Placement::Curve(axis) => {
+            let points = world_points(axis, transform);
+            let length = curve_length(&points);
+            let along = points.windows(2).find_map(|pair| (pair[1] - pair[0]).try_normalize());
+            let width = along.map_or(sides[1], |along| section_width(&triangles, along));
+            (length, length * width as f64)
+        }
+        // Around the outline, and inside it less the holes
+        Placement::Profile(profile) => match profile.outline() {
+            Some(outline) => (
+                curve_length(&closed(world_points(outline, transform))),
+                profile_area(profile, transform),
+            ),
+            None => (sides[0] as f64, (sides[0] * sides[1]) as f64),
+        },
+        Placement::None | Placement::Pose(_) => (sides[0] as f64, (sides[0] * sides[1]) as f64),
+    };
+
+    Some(Quantities {
+        count: 1.0,
+        length,
+        area,
+        volume,
+    })
+}
+
+// Widest the mesh gets square to the run, measured upright and level
+fn section_width(triangles: &[[Vec3; 3]], along: Vec3) -> f32 {
+    let across = along.cross(Vec3::Y).try_normalize().unwrap_or(Vec3::X);
+    let up = across.cross(along).normalize();
+    [across, up]
+        .iter()
+        .map(|&direction| {
+            let (min, max) = triangles
+                .iter()
+                .flatten()
+                .map(|point| point.dot(direction))
+                .fold((f32::MAX, f32::MIN), |(min, max), at| (min.min(at), max.max(at)));
+            max - min
+        })
+        .fold(0.0, f32::max)
+}
+
+fn world_points(curve: &Curve3, transform: &GlobalTransform) -> Vec<Vec3> {
+    curve.points.iter().map(|point| transform.transform_point(point.to_vec3())).collect()
+}
+
+fn closed(mut points: Vec<Vec3>) -> Vec<Vec3> {
+    if let Some(first) = points.first().copied() {
+        points.push(first);
+    }
+    points
+}
+
+fn curve_length(points: &[Vec3]) -> f64 {
+    points.windows(2).map(|pair| f64::from(pair[0].distance(pair[1]))).sum()
+}
+
+// Newell's method, so the outline may lie in any plane
+fn loop_area(points: &[Vec3]) -> f64 {
+    let normal: Vec3 = (0..points.len())
+        .map(|i| points[i].cross(points[(i + 1) % points.len()]))
+        .sum();
+    f64::from(normal.length()) / 2.0
+}
+
+fn profile_area(profile: &Profile3, transform: &GlobalTransform) -> f64 {
+    let outline = profile.outline().map_or(0.0, |outline| loop_area(&world_points(outline, transform)));
+    let holes: f64 = profile.holes().iter().map(|hole| loop_area(&world_points(hole, transform))).sum();
+    (outline - holes).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use new_core::mep::SectionSize;
+
+    fn close(value: f64, expected: f64) -> bool {
+        (value - expected).abs() < 1e-4
+    }
+
+    fn curve(points: &[Vec3]) -> Curve3 {
+        Curve3 {
+            points: points.iter().copied().map(Point3::from_vec3).collect(),
+        }
+    }
+
+    #[test]
+    fn elements_without_an_axis_are_measured_by_their_box() {
+        let mesh = Mesh::from(Cuboid::new(4.0, 3.0, 0.2));
+        let quantities = measure(&mesh, &GlobalTransform::IDENTITY, &Placement::None).expect("measured");
+        assert_eq!(quantities.count, 1.0);
+        assert!(close(quantities.length, 4.0));
+        assert!(close(quantities.area, 12.0));
+        assert!(close(quantities.volume, 2.4));
+    }
+
+    #[test]
+    fn a_sloped_beam_is_as_long_as_its_axis() {
+        // 0.3 deep and 0.2 wide, rising 3 m over 4 m. Its box is 4.2 by 3.2.
+        let slope = Quat::from_rotation_z((3.0f32).atan2(4.0));
+        let mesh = Mesh::from(Cuboid::new(5.0, 0.3, 0.2)).rotated_by(slope);
+        let axis = curve(&[Vec3::new(-2.0, -1.5, 0.0), Vec3::new(2.0, 1.5, 0.0)]);
+        let transform = GlobalTransform::from_xyz(10.0, 2.0, 5.0);
+
+        let boxed = measure(&mesh, &transform, &Placement::None).expect("measured");
+        assert!(boxed.length > 4.1 && boxed.area > 13.0);
+
+        let quantities = measure(&mesh, &transform, &Placement::Curve(axis)).expect("measured");
+        assert!(close(quantities.length, 5.0));
+        assert!(close(quantities.area, 5.0 * 0.3));
+    }
+
+    #[test]
+    fn axes_scale_with_the_element() {
+        let mesh = Mesh::from(Cuboid::new(1.0, 0.2, 0.2));
+        let axis = curve(&[Vec3::ZERO, Vec3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 1.0)]);
+        let transform = GlobalTransform::from(Transform::from_scale(Vec3::splat(2.0)));
+
+        let quantities = measure(&mesh, &transform, &Placement::Curve(axis)).expect("measured");
+        assert!(close(quantities.length, 4.0));
+    }
+
+    #[test]
+    fn an_outline_is_measured_less_its_holes() {
+        // L-shaped floor, 6 by 6 less a 3 by 3 corner, with a column 0.4 square through it
+        let outline = curve(&[
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(6.0, 0.0, 0.0),
+            Vec3::new(6.0, 0.0, -3.0),
+            Vec3::new(3.0, 0.0, -3.0),
+            Vec3::new(3.0, 0.0, -6.0),
+            Vec3::new(0.0, 0.0, -6.0),
+        ]);
+        let column = curve(&[
+            Vec3::new(1.0, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, -1.4),
+            Vec3::new(1.4, 0.0, -1.4),
+            Vec3::new(1.4, 0.0, -1.0),
+        ]);
+        let profile = Profile3::new(vec![outline, column]);
+        let mesh = Mesh::from(Cuboid::new(6.0, 0.25, 6.0));
+
+        let quantities =
+            measure(&mesh, &GlobalTransform::IDENTITY, &Placement::Profile(profile)).expect("measured");
+        assert!(close(quantities.area, 27.0 - 0.16));
+        assert!(close(quantities.length, 24.0));
+    }
+
+    #[test]
+    fn runs_and_framing_give_their_axis() {
+        let segment = MepSegment {
+            size: SectionSize::Round { diameter: 0.2 },
+            end: Vec3::new(3.0, 0.0, 4.0),
+        };
+        let Placement::Curve(axis) = placement(None, Some(&segment), None) else {
+            panic!("a run is placed by its axis");
+        };
+        assert_eq!(axis.end().map(Point3::to_vec3), Some(Vec3::new(3.0, 0.0, 4.0)));
+        assert!(matches!(placement(None, None, None), Placement::None));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod clash;
+pub mod cost;
@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+use new_core::cad::{CadCommand, CadExchange, Underlays};
+
+use crate::cad::commands::run_cad_commands;
+use crate::cad::underlay_mesh::sync_underlay_meshes;
+
+pub struct CadPlugin;
+
+impl Plugin for CadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Underlays>()
+            .init_resource::<CadExchange>()
+            .add_message::<CadCommand>()
+            .add_systems(Update, (run_cad_commands, sync_underlay_meshes).chain());
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use std::path::Path;
+
+use new_core::cad::{CadCommand, CadExchange, Underlay, Underlays};
+use new_core::drawing::ViewKind;
+use new_core::element::ElementHeader;
+use new_core::phase::ElementPhasing;
+
+use crate::analysis::clash::shape::world_triangles;
+use crate::cad::dxf_read::read_dxf;
+use crate::cad::dxf_write::plan_dxf;
+use crate::geometry::projection::{ProjectedElement, project_view};
+
+pub fn run_cad_commands(
+    mut commands: MessageReader<CadCommand>,
+    mut exchange: ResMut<CadExchange>,
+    mut underlays: ResMut<Underlays>,
+    meshes: Res<Assets<Mesh>>,
+    elements: Query<(&ElementHeader, &Mesh3d, &GlobalTransform, Option<&ElementPhasing>)>,
+) {
+    for command in commands.read() {
+        let status = match command {
+            CadCommand::Import {
+                path,
+                units,
+                level_id,
+                elevation,
+            } => match read_dxf(path, units.unwrap_or_default()) {
+                Ok((drawing, used)) => {
+                    let name = path
+                        .file_stem()
+                        .map_or("Underlay".to_owned(), |stem| stem.to_string_lossy().into_owned());
+                    let status = format!(
+                        "Imported {} entities on {} layers from {} ({used})",
+                        drawing.items.len(),
+                        drawing.layers.len(),
+                        path.display()
+                    );
+                    underlays.add(Underlay::new(name, path.clone(), *level_id, *elevation, drawing));
+                    status
+                }
+                Err(error) => format!("Importing {} failed: {error}", path.display()),
+            },
+            CadCommand::Remove(id) => {
+                underlays.remove(*id);
+                continue;
+            }
+            CadCommand::ExportPlan {
+                path,
+                cut_height,
+                phase,
+            } => {
+                let shapes: Vec<_> = elements
+                    .iter()
+                    .filter_map(|(header, mesh, transform, phasing)| {
+                        let triangles = world_triangles(meshes.get(&mesh.0)?, transform)?;
+                        Some((triangles, header.kind, phasing.copied().unwrap_or_default()))
+                    })
+                    .collect();
+                let projected: Vec<ProjectedElement> = shapes
+                    .iter()
+                    .map(|(triangles, kind, phasing)| ProjectedElement {
+                        triangles,
+                        kind: *kind,
+                        style: phase.style(phasing),
+                    })
+                    .collect();
+
+                match export_plan(&projected, *cut_height, path) {
+                    Ok(lines) => format!("Exported {lines} lines to {}", path.display()),
+                    Err(error) => format!("Exporting to {} failed: {error}", path.display()),
+                }
+            }
+        };
+
+        info!("{status}");
+        exchange.status = Some(status);
+    }
+}
+
+// Writes the plan cut at the height, returns the polyline count
+fn export_plan(elements: &[ProjectedElement], cut_height: f32, path: &Path) -> std::io::Result<usize> {
+    let drawing = project_view(elements, &ViewKind::Plan { cut_height }.frame());
+    std::fs::write(path, plan_dxf(&drawing))?;
+    Ok(drawing.polylines.len())
+}
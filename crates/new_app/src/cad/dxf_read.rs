@@ -0,0 +1,632 @@
+use bevy::math::Affine2;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::fmt;
+use std::path::Path;
+
+use new_core::cad::{CadDrawing, CadEntity, CadItem, CadLayer, CadUnits, arc_points};
+
+// Blocks inside blocks deeper than this are taken as a cycle
+const MAX_NESTING: usize = 16;
+
+#[derive(Debug)]
+pub enum DxfError {
+    Io(std::io::Error),
+    // Group codes and values have to come in pairs of lines
+    Syntax { line: usize, message: String },
+    Empty,
+}
+
+impl fmt::Display for DxfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DxfError::Io(error) => write!(f, "{error}"),
+            DxfError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            DxfError::Empty => write!(f, "no drawable entities"),
+        }
+    }
+}
+
+impl From<std::io::Error> for DxfError {
+    fn from(error: std::io::Error) -> Self {
+        DxfError::Io(error)
+    }
+}
+
+// One entity or table entry, everything from its 0 group to the next
+#[derive(Debug, Default)]
+struct Record {
+    kind: String,
+    pairs: Vec<(i32, String)>,
+}
+
+impl Record {
+    fn text(&self, code: i32) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn number(&self, code: i32) -> Option<f32> {
+        self.text(code)?.trim().parse().ok()
+    }
+
+    fn float(&self, code: i32, default: f32) -> f32 {
+        self.number(code).unwrap_or(default)
+    }
+
+    fn point(&self, x: i32) -> Vec2 {
+        Vec2::new(self.float(x, 0.0), self.float(x + 10, 0.0))
+    }
+
+    fn layer(&self) -> &str {
+        self.text(8).unwrap_or("0")
+    }
+
+    // Object coordinate system to plan, from the extrusion direction by the arbitrary axis
+    // algorithm. Entities drawn from below or on a tilted plane land mirrored or squashed.
+    fn ocs(&self) -> Affine2 {
+        let normal = Vec3::new(self.float(210, 0.0), self.float(220, 0.0), self.float(230, 1.0));
+        let Some(normal) = normal.try_normalize().filter(|normal| *normal != Vec3::Z) else {
+            return Affine2::IDENTITY;
+        };
+
+        let x_axis = if normal.x.abs() < 1.0 / 64.0 && normal.y.abs() < 1.0 / 64.0 {
+            Vec3::Y.cross(normal)
+        } else {
+            Vec3::Z.cross(normal)
+        }
+        .normalize();
+        let y_axis = normal.cross(x_axis);
+
+        // Elevation moves the plane along the normal, which only shows in plan when it is tilted
+        let elevation = self.float(30, self.float(38, 0.0));
+        Affine2::from_mat2_translation(
+            Mat2::from_cols(x_axis.truncate(), y_axis.truncate()),
+            normal.truncate() * elevation,
+        )
+    }
+}
+
+struct Block {
+    base: Vec2,
+    records: Vec<Record>,
+}
+
+// Reads a DXF into plan meters. Units come from the file header, then the fallback.
+// Returns the units that were used.
+pub fn read_dxf(path: &Path, fallback: CadUnits) -> Result<(CadDrawing, CadUnits), DxfError> {
+    let bytes = std::fs::read(path)?;
+    // Older files are code page text, anything outside ASCII is only ever in labels
+    parse_dxf(&String::from_utf8_lossy(&bytes), fallback)
+}
+
+fn parse_dxf(text: &str, fallback: CadUnits) -> Result<(CadDrawing, CadUnits), DxfError> {
+    let records = records(text)?;
+
+    let mut units = None;
+    let mut layers: Vec<CadLayer> = Vec::new();
+    let mut blocks: HashMap<String, Block> = HashMap::new();
+    let mut entities: Vec<Record> = Vec::new();
+
+    let mut section = String::new();
+    let mut current_block: Option<(String, Block)> = None;
+    for record in records {
+        match record.kind.as_str() {
+            "SECTION" => {
+                section = record.text(2).unwrap_or_default().to_owned();
+                // Header variables all sit in the section record
+                if section == "HEADER"
+                    && let Some(code) = header_value(&record, "$INSUNITS")
+                {
+                    units = code.trim().parse().ok().and_then(CadUnits::from_insunits);
+                }
+                continue;
+            }
+            "ENDSEC" => {
+                section.clear();
+                continue;
+            }
+            _ => {}
+        }
+
+        match section.as_str() {
+            "TABLES" if record.kind == "LAYER" => {
+                let color = record.number(62).unwrap_or(7.0) as i32;
+                let frozen = record.number(70).unwrap_or(0.0) as i32 & 1 != 0;
+                layers.push(CadLayer {
+                    name: record.text(2).unwrap_or("0").to_owned(),
+                    color: aci_color(color.abs()),
+                    visible: color >= 0 && !frozen,
+                });
+            }
+            "BLOCKS" => match record.kind.as_str() {
+                "BLOCK" => {
+                    let name = record.text(2).unwrap_or_default().to_owned();
+                    current_block = Some((name, Block { base: record.point(10), records: Vec::new() }));
+                }
+                "ENDBLK" => {
+                    if let Some((name, block)) = current_block.take() {
+                        blocks.insert(name, block);
+                    }
+                }
+                _ => {
+                    if let Some((_, block)) = &mut current_block {
+                        block.records.push(record);
+                    }
+                }
+            },
+            "ENTITIES" => entities.push(record),
+            _ => {}
+        }
+    }
+
+    let units = units.unwrap_or(fallback);
+    let mut drawing = CadDrawing {
+        layers,
+        items: Vec::new(),
+    };
+    let to_meters = Affine2::from_scale(Vec2::splat(units.meters()));
+    add_records(&mut drawing, &blocks, &entities, to_meters, None, 0);
+
+    if drawing.items.is_empty() {
+        return Err(DxfError::Empty);
+    }
+    Ok((drawing, units))
+}
+
+fn records(text: &str) -> Result<Vec<Record>, DxfError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut records = Vec::new();
+    let mut current: Option<Record> = None;
+
+    for (index, pair) in lines.chunks(2).enumerate() {
+        let line = index * 2 + 1;
+        let [code, value] = pair else {
+            if pair[0].trim().is_empty() {
+                break;
+            }
+            return Err(DxfError::Syntax {
+                line,
+                message: "group code without a value".to_owned(),
+            });
+        };
+        let code: i32 = code.trim().parse().map_err(|_| DxfError::Syntax {
+            line,
+            message: format!("\"{}\" is not a group code", code.trim()),
+        })?;
+        let value = value.trim_end_matches('\r');
+
+        if code == 0 {
+            if value.trim() == "EOF" {
+                break;
+            }
+            records.extend(current.take());
+            current = Some(Record {
+                kind: value.trim().to_owned(),
+                pairs: Vec::new(),
+            });
+        } else if let Some(record) = &mut current {
+            record.pairs.push((code, value.to_owned()));
+        }
+    }
+    records.extend(current);
+
+    Ok(records)
+}
+
+// A header variable is a 9 group with its name followed by its value
+fn header_value<'a>(record: &'a Record, name: &str) -> Option<&'a str> {
+    let start = record
+        .pairs
+        .iter()
+        .position(|(code, value)| *code == 9 && value.trim() == name)?;
+    record.pairs[start + 1..]
+        .iter()
+        .take_while(|(code, _)| *code != 9)
+        .map(|(_, value)| value.as_str())
+        .next()
+}
+
+// Adds entity records under the transform. Layer 0 inside a block takes the layer of its insert.
+fn add_records(
+    drawing: &mut CadDrawing,
+    blocks: &HashMap<String, Block>,
+    records: &[Record],
+    transform: Affine2,
+    insert_layer: Option<&str>,
+    depth: usize,
+) {
+    let mut index = 0;
+    while index < records.len() {
+        let record = &records[index];
+        index += 1;
+
+        let layer = match (record.layer(), insert_layer) {
+            ("0", Some(outer)) => outer,
+            (own, _) => own,
+        };
+
+        // Lines and 3D polylines are in world coordinates, everything else in its own
+        let flags = record.float(70, 0.0) as i32;
+        let ocs = match record.kind.as_str() {
+            "LINE" => Affine2::IDENTITY,
+            "POLYLINE" if flags & (8 | 16 | 64) != 0 => Affine2::IDENTITY,
+            _ => record.ocs(),
+        };
+
+        let entity = match record.kind.as_str() {
+            "LINE" => Some(CadEntity::Line {
+                a: record.point(10),
+                b: record.point(11),
+            }),
+            "LWPOLYLINE" => Some(lwpolyline(record)),
+            "POLYLINE" => {
+                // Vertices follow as their own records up to SEQEND
+                let start = index;
+                while index < records.len() && records[index].kind == "VERTEX" {
+                    index += 1;
+                }
+                let vertices = &records[start..index];
+                if records.get(index).is_some_and(|record| record.kind == "SEQEND") {
+                    index += 1;
+                }
+                let closed = flags & 1 != 0;
+                Some(polyline(
+                    vertices.iter().map(|vertex| (vertex.point(10), vertex.float(42, 0.0))),
+                    closed,
+                ))
+            }
+            "ARC" => Some(CadEntity::Arc {
+                center: record.point(10),
+                radius: record.float(40, 0.0),
+                start: record.float(50, 0.0).to_radians(),
+                end: record.float(51, 360.0).to_radians(),
+            }),
+            "CIRCLE" => Some(CadEntity::Circle {
+                center: record.point(10),
+                radius: record.float(40, 0.0),
+            }),
+            "TEXT" => Some(CadEntity::Text {
+                position: record.point(10),
+                height: record.float(40, 1.0),
+                rotation: record.float(50, 0.0).to_radians(),
+                text: record.text(1).unwrap_or_default().to_owned(),
+            }),
+            "INSERT" => {
+                let Some(block) = record.text(2).and_then(|name| blocks.get(name)) else {
+                    continue;
+                };
+                if depth >= MAX_NESTING {
+                    continue;
+                }
+                // Column and row arrays are not expanded, only the first copy is drawn
+                let placed = transform
+                    * ocs
+                    * Affine2::from_scale_angle_translation(
+                        Vec2::new(record.float(41, 1.0), record.float(42, 1.0)),
+                        record.float(50, 0.0).to_radians(),
+                        record.point(10),
+                    )
+                    * Affine2::from_translation(-block.base);
+                add_records(drawing, blocks, &block.records, placed, Some(layer), depth + 1);
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(entity) = entity {
+            let layer = drawing.layer_index(layer);
+            drawing.items.push(CadItem {
+                layer,
+                entity: transformed(entity, transform * ocs),
+            });
+        }
+    }
+}
+
+fn lwpolyline(record: &Record) -> CadEntity {
+    let mut vertices: Vec<(Vec2, f32)> = Vec::new();
+    for (code, value) in &record.pairs {
+        let value: f32 = value.trim().parse().unwrap_or(0.0);
+        match code {
+            10 => vertices.push((Vec2::new(value, 0.0), 0.0)),
+            20 => {
+                if let Some(vertex) = vertices.last_mut() {
+                    vertex.0.y = value;
+                }
+            }
+            42 => {
+                if let Some(vertex) = vertices.last_mut() {
+                    vertex.1 = value;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let closed = record.float(70, 0.0) as i32 & 1 != 0;
+    polyline(vertices.into_iter(), closed)
+}
+
+// Bulges turn into arc points, a closed outline ends where it started
+fn polyline(vertices: impl Iterator<Item = (Vec2, f32)>, closed: bool) -> CadEntity {
+    let vertices: Vec<(Vec2, f32)> = vertices.collect();
+    let mut points = Vec::new();
+
+    let count = vertices.len();
+    let spans = if closed { count } else { count.saturating_sub(1) };
+    if let Some(&(first, _)) = vertices.first() {
+        points.push(first);
+    }
+    for i in 0..spans {
+        let (from, bulge) = vertices[i];
+        let (to, _) = vertices[(i + 1) % count];
+        if bulge.abs() > 1.0e-6 {
+            points.extend(bulge_points(from, to, bulge).into_iter().skip(1));
+        } else {
+            points.push(to);
+        }
+    }
+
+    CadEntity::Polyline {
+        points,
+        closed: false,
+    }
+}
+
+// Bulge is the tangent of a quarter of the included angle, positive turns counter clockwise
+fn bulge_points(from: Vec2, to: Vec2, bulge: f32) -> Vec<Vec2> {
+    let chord = to - from;
+    let left = chord.perp();
+    let center = from.midpoint(to) + left * ((1.0 - bulge * bulge) / (4.0 * bulge));
+
+    let radius = center.distance(from);
+    let start = (from - center).to_angle();
+    let sweep = 4.0 * bulge.atan();
+
+    if sweep > 0.0 {
+        arc_points(center, radius, start, start + sweep)
+    } else {
+        let mut points = arc_points(center, radius, start + sweep, start);
+        points.reverse();
+        points
+    }
+}
+
+// Arcs and text keep their shape under a uniform, unmirrored transform, anything else is
+// drawn as a polyline
+fn transformed(entity: CadEntity, transform: Affine2) -> CadEntity {
+    let point = |p: Vec2| transform.transform_point2(p);
+    let x_axis = transform.matrix2.x_axis;
+    let y_axis = transform.matrix2.y_axis;
+    let scale = x_axis.length();
+    let rotation = x_axis.to_angle();
+    let conformal = (y_axis.length() - scale).abs() < 1.0e-4 * scale.max(1.0)
+        && transform.matrix2.determinant() > 0.0;
+
+    match entity {
+        CadEntity::Line { a, b } => CadEntity::Line { a: point(a), b: point(b) },
+        CadEntity::Polyline { points, closed } => CadEntity::Polyline {
+            points: points.into_iter().map(point).collect(),
+            closed,
+        },
+        CadEntity::Arc { center, radius, start, end } if conformal => CadEntity::Arc {
+            center: point(center),
+            radius: radius * scale,
+            start: start + rotation,
+            end: end + rotation,
+        },
+        CadEntity::Arc { center, radius, start, end } => CadEntity::Polyline {
+            points: arc_points(center, radius, start, end).into_iter().map(point).collect(),
+            closed: false,
+        },
+        CadEntity::Circle { center, radius } if conformal => CadEntity::Circle {
+            center: point(center),
+            radius: radius * scale,
+        },
+        CadEntity::Circle { center, radius } => CadEntity::Polyline {
+            points: arc_points(center, radius, 0.0, TAU).into_iter().map(point).collect(),
+            closed: false,
+        },
+        CadEntity::Text { position, height, rotation: angle, text } => CadEntity::Text {
+            position: point(position),
+            height: height * y_axis.length(),
+            rotation: angle + rotation,
+            text,
+        },
+    }
+}
+
+// The first AutoCAD index colors, the rest fall back to gray
+fn aci_color(index: i32) -> [u8; 3] {
+    match index {
+        1 => [255, 0, 0],
+        2 => [255, 255, 0],
+        3 => [0, 255, 0],
+        4 => [0, 255, 255],
+        5 => [0, 0, 255],
+        6 => [255, 0, 255],
+        8 => [128, 128, 128],
+        9 => [192, 192, 192],
+        7 | 0 | 256 => [255, 255, 255],
+        _ => [160, 160, 160],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(value: Vec2, expected: Vec2) -> bool {
+        value.distance(expected) < 1e-4
+    }
+
+    // A file in meters from group code and value pairs, blocks and entities each in their section
+    fn dxf(blocks: &[(i32, &str)], entities: &[(i32, &str)]) -> String {
+        let section = |name: &str, pairs: &[(i32, &str)]| {
+            let mut text = format!("0\nSECTION\n2\n{name}\n");
+            for (code, value) in pairs {
+                text += &format!("{code}\n{value}\n");
+            }
+            text + "0\nENDSEC\n"
+        };
+        [
+            section("HEADER", &[(9, "$INSUNITS"), (70, "6")]),
+            section("BLOCKS", blocks),
+            section("ENTITIES", entities),
+            "0\nEOF\n".to_owned(),
+        ]
+        .concat()
+    }
+
+    fn read(text: &str) -> CadDrawing {
+        let (drawing, units) = parse_dxf(text, CadUnits::Millimeters).expect("read");
+        assert_eq!(units, CadUnits::Meters);
+        drawing
+    }
+
+    #[test]
+    fn a_bulge_turns_into_an_arc() {
+        // Half circle from (0, 0) to (2, 0), a bulge of 1 turns counter clockwise so it dips below
+        let text = dxf(
+            &[],
+            &[
+                (0, "LWPOLYLINE"),
+                (8, "Walls"),
+                (90, "3"),
+                (70, "0"),
+                (10, "0.0"),
+                (20, "0.0"),
+                (42, "1.0"),
+                (10, "2.0"),
+                (20, "0.0"),
+                (10, "2.0"),
+                (20, "3.0"),
+            ],
+        );
+        let drawing = read(&text);
+        assert_eq!(drawing.items.len(), 1);
+        assert_eq!(drawing.layers[drawing.items[0].layer].name, "Walls");
+
+        let CadEntity::Polyline { points, .. } = &drawing.items[0].entity else {
+            panic!("a polyline");
+        };
+        assert!(points.len() > 4);
+        assert!(close(points[0], Vec2::ZERO));
+        assert!(close(points[points.len() - 2], Vec2::new(2.0, 0.0)));
+        assert!(close(points[points.len() - 1], Vec2::new(2.0, 3.0)));
+
+        let arc = &points[..points.len() - 1];
+        assert!(arc.iter().all(|point| (point.distance(Vec2::new(1.0, 0.0)) - 1.0).abs() < 1e-4));
+        assert!(arc.iter().all(|point| point.y < 1e-4));
+        assert!(arc.iter().any(|point| close(*point, Vec2::new(1.0, -1.0))));
+    }
+
+    #[test]
+    fn inserts_nest_with_their_rotation_and_scale() {
+        // INNER holds a unit line and a circle on layer 0. OUTER, based at (1, 0), holds INNER
+        // turned 90 degrees at twice the size. The drawing places OUTER turned 90 degrees at
+        // three times the size on layer Doors.
+        let blocks = [
+            (0, "BLOCK"),
+            (2, "INNER"),
+            (10, "0.0"),
+            (20, "0.0"),
+            (0, "LINE"),
+            (8, "0"),
+            (10, "0.0"),
+            (20, "0.0"),
+            (11, "1.0"),
+            (21, "0.0"),
+            (0, "CIRCLE"),
+            (8, "0"),
+            (10, "0.0"),
+            (20, "0.0"),
+            (40, "0.5"),
+            (0, "ENDBLK"),
+            (0, "BLOCK"),
+            (2, "OUTER"),
+            (10, "1.0"),
+            (20, "0.0"),
+            (0, "INSERT"),
+            (8, "0"),
+            (2, "INNER"),
+            (10, "1.0"),
+            (20, "0.0"),
+            (41, "2.0"),
+            (42, "2.0"),
+            (50, "90.0"),
+            (0, "ENDBLK"),
+        ];
+        let entities = [
+            (0, "INSERT"),
+            (8, "Doors"),
+            (2, "OUTER"),
+            (10, "10.0"),
+            (20, "5.0"),
+            (41, "3.0"),
+            (42, "3.0"),
+            (50, "90.0"),
+        ];
+        let drawing = read(&dxf(&blocks, &entities));
+        assert_eq!(drawing.items.len(), 2);
+        assert!(drawing.items.iter().all(|item| drawing.layers[item.layer].name == "Doors"));
+
+        // (1, 0) in INNER is (1, 2) in OUTER, (0, 2) from its base, six long and turned to -x
+        let CadEntity::Line { a, b } = drawing.items[0].entity else {
+            panic!("a line");
+        };
+        assert!(close(a, Vec2::new(10.0, 5.0)));
+        assert!(close(b, Vec2::new(4.0, 5.0)));
+
+        let CadEntity::Circle { center, radius } = drawing.items[1].entity else {
+            panic!("a circle keeps its shape under a uniform scale");
+        };
+        assert!(close(center, Vec2::new(10.0, 5.0)));
+        assert!((radius - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn an_extrusion_down_the_z_axis_mirrors_the_entity() {
+        // Drawn from below, an arc around (2, 3) lands around (-2, 3) in plan and runs the
+        // other way. Lines are in world coordinates and stay put.
+        let text = dxf(
+            &[],
+            &[
+                (0, "ARC"),
+                (10, "2.0"),
+                (20, "3.0"),
+                (40, "1.0"),
+                (50, "0.0"),
+                (51, "90.0"),
+                (210, "0.0"),
+                (220, "0.0"),
+                (230, "-1.0"),
+                (0, "LINE"),
+                (10, "0.0"),
+                (20, "0.0"),
+                (11, "1.0"),
+                (21, "0.0"),
+                (210, "0.0"),
+                (220, "0.0"),
+                (230, "-1.0"),
+            ],
+        );
+        let drawing = read(&text);
+
+        let CadEntity::Polyline { points, .. } = &drawing.items[0].entity else {
+            panic!("a mirrored arc is drawn as a polyline");
+        };
+        assert!(close(points[0], Vec2::new(-3.0, 3.0)));
+        assert!(close(points[points.len() - 1], Vec2::new(-2.0, 4.0)));
+        assert!(points.iter().all(|point| (point.distance(Vec2::new(-2.0, 3.0)) - 1.0).abs() < 1e-4));
+
+        let CadEntity::Line { a, b } = drawing.items[1].entity else {
+            panic!("a line");
+        };
+        assert!(close(a, Vec2::ZERO) && close(b, Vec2::X));
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use new_core::drawing::{Drawing, LineKind, LineStyle};
+
+// Index color for existing work in a phased view
+const HALFTONE_COLOR: u8 = 8;
+
+// Layer of a line, one per element kind with its cut and outline lines apart so they can get their own pen
+pub fn layer_name(style: &LineStyle) -> String {
+    match style.kind {
+        LineKind::Cut => format!("{}-Cut", style.element),
+        LineKind::Silhouette => format!("{}-Outline", style.element),
+        LineKind::Crease | LineKind::Projection => style.element.to_string(),
+        LineKind::Grid => "Grid".to_owned(),
+    }
+}
+
+// Cut layers in white for the heavy pen, outlines in yellow for the medium one, the rest in blue
+fn layer_color(kind: LineKind) -> &'static str {
+    match kind {
+        LineKind::Cut => "7",
+        LineKind::Silhouette => "2",
+        LineKind::Crease | LineKind::Projection | LineKind::Grid => "5",
+    }
+}
+
+// R12 DXF in meters, the oldest flavor every CAD package still reads
+pub fn plan_dxf(drawing: &Drawing) -> String {
+    let mut out = String::new();
+    let mut pair = |code: i32, value: &str| {
+        let _ = writeln!(out, "{code:>3}\n{value}");
+    };
+
+    pair(0, "SECTION");
+    pair(2, "HEADER");
+    pair(9, "$ACADVER");
+    pair(1, "AC1009");
+    pair(9, "$INSUNITS");
+    pair(70, "6");
+    pair(0, "ENDSEC");
+
+    pair(0, "SECTION");
+    pair(2, "TABLES");
+
+    pair(0, "TABLE");
+    pair(2, "LTYPE");
+    pair(70, "2");
+    for (name, description, pattern) in [
+        ("CONTINUOUS", "Solid line", &[][..]),
+        ("DASHED", "__ __ __", &[0.2, -0.1][..]),
+    ] {
+        pair(0, "LTYPE");
+        pair(2, name);
+        pair(70, "0");
+        pair(3, description);
+        pair(72, "65");
+        pair(73, &pattern.len().to_string());
+        pair(40, &pattern.iter().fold(0.0, |total: f32, dash: &f32| total + dash.abs()).to_string());
+        for dash in pattern {
+            pair(49, &dash.to_string());
+        }
+    }
+    pair(0, "ENDTAB");
+
+    let layers: BTreeSet<(String, &str)> = drawing
+        .polylines
+        .iter()
+        .map(|polyline| (layer_name(&polyline.style), layer_color(polyline.style.kind)))
+        .collect();
+    pair(0, "TABLE");
+    pair(2, "LAYER");
+    pair(70, &layers.len().to_string());
+    for (name, color) in &layers {
+        pair(0, "LAYER");
+        pair(2, name);
+        pair(70, "0");
+        pair(62, color);
+        pair(6, "CONTINUOUS");
+    }
+    pair(0, "ENDTAB");
+    pair(0, "ENDSEC");
+
+    pair(0, "SECTION");
+    pair(2, "ENTITIES");
+    for polyline in &drawing.polylines {
+        let layer = layer_name(&polyline.style);
+        let common = |pair: &mut dyn FnMut(i32, &str)| {
+            pair(8, &layer);
+            if polyline.style.dashed {
+                pair(6, "DASHED");
+            }
+            if polyline.style.halftone {
+                pair(62, &HALFTONE_COLOR.to_string());
+            }
+        };
+
+        let points = &polyline.points;
+        if let [a, b] = points.as_slice() {
+            pair(0, "LINE");
+            common(&mut pair);
+            for (code, point) in [(10, a), (11, b)] {
+                pair(code, &point.x.to_string());
+                pair(code + 10, &point.y.to_string());
+                pair(code + 20, "0.0");
+            }
+            continue;
+        }
+
+        let closed = points.len() > 3 && points.first() == points.last();
+        let points = if closed { &points[..points.len() - 1] } else { &points[..] };
+
+        pair(0, "POLYLINE");
+        common(&mut pair);
+        pair(66, "1");
+        pair(70, if closed { "1" } else { "0" });
+        pair(10, "0.0");
+        pair(20, "0.0");
+        pair(30, "0.0");
+        for point in points {
+            pair(0, "VERTEX");
+            pair(8, &layer);
+            pair(10, &point.x.to_string());
+            pair(20, &point.y.to_string());
+            pair(30, "0.0");
+        }
+        pair(0, "SEQEND");
+        pair(8, &layer);
+    }
+    pair(0, "ENDSEC");
+    pair(0, "EOF");
+
+    out
+}
@@ -0,0 +1,5 @@
+pub mod cad_plugin;
+pub mod commands;
+pub mod dxf_read;
+pub mod dxf_write;
+pub mod underlay_mesh;
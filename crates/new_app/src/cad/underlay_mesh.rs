@@ -0,0 +1,103 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::visibility::RenderLayers;
+use bevy::mesh::PrimitiveTopology;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use new_core::cad::{Underlay, UnderlayId, Underlays};
+
+// Linework of one underlay layer. Not Selectable, so picking passes through it.
+#[derive(Component)]
+pub struct UnderlayLayerMesh {
+    pub underlay: UnderlayId,
+    pub layer: usize,
+}
+
+// Keeps one line mesh per underlay layer, toggling visibility instead of rebuilding
+pub fn sync_underlay_meshes(
+    mut commands: Commands,
+    underlays: Res<Underlays>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut spawned: Query<(Entity, &UnderlayLayerMesh, &mut Visibility, &mut Transform)>,
+) {
+    if !underlays.is_changed() {
+        return;
+    }
+
+    let mut present = HashSet::new();
+    for (entity, tag, mut visibility, mut transform) in &mut spawned {
+        let found = underlays
+            .underlays
+            .iter()
+            .find(|underlay| underlay.id == tag.underlay)
+            .and_then(|underlay| Some((underlay, underlay.drawing.layers.get(tag.layer)?)));
+
+        let Some((underlay, layer)) = found else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        *visibility = if underlay.visible && layer.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        transform.translation.y = underlay.elevation;
+        present.insert((tag.underlay, tag.layer));
+    }
+
+    for underlay in &underlays.underlays {
+        for (index, layer) in underlay.drawing.layers.iter().enumerate() {
+            if present.contains(&(underlay.id, index)) {
+                continue;
+            }
+            let Some(mesh) = layer_mesh(underlay, index) else {
+                continue;
+            };
+
+            let [r, g, b] = layer.color;
+            commands.spawn((
+                Name::new(format!("Underlay {} {}", underlay.name, layer.name)),
+                Mesh3d(meshes.add(mesh)),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Color::srgb_u8(r, g, b),
+                    unlit: true,
+                    ..default()
+                })),
+                Transform::from_xyz(0.0, underlay.elevation, 0.0),
+                if underlay.visible && layer.visible {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                },
+                RenderLayers::layer(0),
+                UnderlayLayerMesh {
+                    underlay: underlay.id,
+                    layer: index,
+                },
+            ));
+        }
+    }
+}
+
+fn layer_mesh(underlay: &Underlay, layer: usize) -> Option<Mesh> {
+    let positions: Vec<[f32; 3]> = underlay
+        .drawing
+        .items
+        .iter()
+        .filter(|item| item.layer == layer)
+        .flat_map(|item| item.entity.segments())
+        .flat_map(|(a, b)| [a, b])
+        .map(|point| [point.x, 0.0, -point.y])
+        .collect();
+
+    if positions.is_empty() {
+        return None;
+    }
+
+    Some(
+        Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::RENDER_WORLD)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions),
+    )
+}
@@ -4,10 +4,14 @@ use bevy_egui::{
     EguiStartupSet
 };
 
+use new_core::ActiveViewport;
+use new_core::action::ActionAppExt;
+
 use crate::camera::{
-    controls::viewport_camera_controls_system,
+    controls::{track_active_viewport, viewport_camera_controls_system},
     setup_egui::setup_egui_camera,
     setup_scene::setup_scene,
+    view_actions::{run_view_actions, view_actions},
     viewport::sync_viewport_cameras
 };
 
@@ -15,11 +19,15 @@ pub struct AppCameraPlugin;
 
 impl Plugin for AppCameraPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveViewport>();
+        for action in view_actions() {
+            app.register_action(action);
+        }
         app.add_systems(
             Startup,
             (setup_egui_camera, setup_scene).before(EguiStartupSet::InitContexts),
         )
-        .add_systems(Update, viewport_camera_controls_system)
+        .add_systems(Update, (track_active_viewport, viewport_camera_controls_system, run_view_actions))
         .add_systems(PostUpdate, 
             sync_viewport_cameras.after(EguiPostUpdateSet::EndPass),
         );
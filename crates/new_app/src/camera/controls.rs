@@ -1,3 +1,4 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::input::mouse::{
     AccumulatedMouseMotion,
@@ -5,7 +6,9 @@ use bevy::input::mouse::{
 };
 use bevy::window::PrimaryWindow;
 
-use new_core::{GameViewportCamera, VisibleViewports};
+use new_core::keymap::{Keymap, Navigation};
+use new_core::preferences::Preferences;
+use new_core::{ActiveViewport, GameViewportCamera, VisibleViewports};
 
 #[derive(Component, Debug, Clone)]
 pub struct ViewportOrbitCamera {
@@ -14,10 +17,6 @@ pub struct ViewportOrbitCamera {
     pub pitch: f32,
     pub distance: f32,
 
-    pub orbit_sensitivity: f32,
-    pub pan_sensitivity: f32,
-    pub zoom_sensitivity: f32,
-    
     pub min_distance: f32,
     pub max_distance: f32,
 }
@@ -30,10 +29,6 @@ impl Default for ViewportOrbitCamera {
             pitch: -0.6,
             distance: 6.0,
 
-            orbit_sensitivity: 0.005,
-            pan_sensitivity: 0.002,
-            zoom_sensitivity: 0.1,
-
             min_distance: 0.05,
             max_distance: 100_000.0,
         }
@@ -67,12 +62,44 @@ impl ViewportOrbitCamera {
     }
 }
 
+// Rates at a speed of 1 in the preferences
+const ORBIT_RATE: f32 = 0.005;
+const PAN_RATE: f32 = 0.002;
+const ZOOM_RATE: f32 = 0.1;
+
+// Buttons held to orbit and pan as the keymap has them, at the user's speeds
+#[derive(SystemParam)]
+pub struct NavigationInput<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+    keymap: Res<'w, Keymap>,
+    preferences: Res<'w, Preferences>,
+}
+
+impl NavigationInput<'_> {
+    fn held(&self, navigation: Navigation) -> bool {
+        self.keymap.navigation(navigation).is_held(&self.keys, &self.mouse_buttons)
+    }
+
+    fn orbit_rate(&self) -> f32 {
+        ORBIT_RATE * self.preferences.navigation.orbit_speed
+    }
+
+    fn pan_rate(&self) -> f32 {
+        PAN_RATE * self.preferences.navigation.pan_speed
+    }
+
+    // Scroll up zooms in unless the user turned it around
+    fn zoom_rate(&self) -> f32 {
+        let rate = ZOOM_RATE * self.preferences.navigation.zoom_speed;
+        if self.preferences.navigation.invert_zoom { -rate } else { rate }
+    }
+}
 
 pub fn viewport_camera_controls_system(
     visible_viewports: Res<VisibleViewports>,
     window: Single<&Window, With<PrimaryWindow>>,
-    keys: Res<ButtonInput<KeyCode>>,
-    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    navigation: NavigationInput,
     mouse_motion: Res<AccumulatedMouseMotion>,
     mouse_scroll: Res<AccumulatedMouseScroll>,
     mut cameras: Query<(&GameViewportCamera, &mut Transform, &mut ViewportOrbitCamera)>
@@ -85,9 +112,8 @@ pub fn viewport_camera_controls_system(
         return;
     };
 
-    let shift = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
-    let orbiting = mouse_buttons.pressed(MouseButton::Middle) && !shift;
-    let panning = mouse_buttons.pressed(MouseButton::Middle) && shift;
+    let orbiting = navigation.held(Navigation::Orbit);
+    let panning = navigation.held(Navigation::Pan);
 
     let moved = mouse_motion.delta != Vec2::ZERO;
     let scrolled = mouse_scroll.delta.y != 0.0;
@@ -102,7 +128,7 @@ pub fn viewport_camera_controls_system(
         }
 
         if scrolled {
-            let zoom_factor = (1.0 - mouse_scroll.delta.y * orbit.zoom_sensitivity).max(0.01);
+            let zoom_factor = (1.0 - mouse_scroll.delta.y * navigation.zoom_rate()).max(0.01);
             orbit.distance = (orbit.distance * zoom_factor).clamp(orbit.min_distance, orbit.max_distance);
         }
 
@@ -110,8 +136,8 @@ pub fn viewport_camera_controls_system(
             let delta = mouse_motion.delta;
 
             if orbiting {
-                orbit.yaw -= delta.x * orbit.orbit_sensitivity;
-                orbit.pitch -= delta.y * orbit.orbit_sensitivity;
+                orbit.yaw -= delta.x * navigation.orbit_rate();
+                orbit.pitch -= delta.y * navigation.orbit_rate();
 
                 let limit = 1.54; // about 88 degrees
                 orbit.pitch = orbit.pitch.clamp(-limit, limit);
@@ -122,7 +148,7 @@ pub fn viewport_camera_controls_system(
                 let right = rotation * Vec3::X;
                 let up = rotation * Vec3::Y;
 
-                let pan_scale = orbit.distance * orbit.pan_sensitivity;
+                let pan_scale = orbit.distance * navigation.pan_rate();
                 orbit.pivot += (-right * delta.x + up * delta.y) * pan_scale;
             }
         }
@@ -132,6 +158,30 @@ pub fn viewport_camera_controls_system(
     }
 }
 
+// Remember the viewport the user last clicked or scrolled in
+pub fn track_active_viewport(
+    visible_viewports: Res<VisibleViewports>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    mut active: ResMut<ActiveViewport>,
+) {
+    let interacted = mouse_buttons.get_just_pressed().next().is_some() || mouse_scroll.delta.y != 0.0;
+    if !interacted {
+        return;
+    }
+
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    if let Some(pane_id) = find_viewport_under_cursor(&visible_viewports, cursor)
+        && active.pane_id != Some(pane_id)
+    {
+        active.pane_id = Some(pane_id);
+    }
+}
+
 fn find_viewport_under_cursor(
     visible_viewports: &VisibleViewports,
     cursor: Vec2
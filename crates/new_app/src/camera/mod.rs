@@ -2,4 +2,5 @@ pub mod camera_plugin;
 pub mod setup_egui;
 pub mod setup_scene;
 pub mod viewport;
-pub mod controls;
\ No newline at end of file
+pub mod controls;
+pub mod view_actions;
\ No newline at end of file
@@ -1,14 +1,17 @@
 use bevy::camera::visibility::RenderLayers;
 use bevy::prelude::*;
-use new_core::element::{ElementHeader, ElementId};
+use new_core::element::{ElementHeader, ElementId, ElementIdAllocator, ElementParams};
 use new_core::elements::{ElementKind, ElementKindType};
-use new_core::elements::element_kindtype_enums::DuctSegmentType;
+use new_core::elements::element_kindtype_enums::{DistributionSystemEnum, DuctSegmentType};
+use new_core::mep::{MepDomain, MepSegment, Ports, SectionSize};
 use crate::editor::selection::picking::Selectable;
+use crate::mep::mep_mesh::segment_mesh;
 
 pub fn setup_scene(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut ids: ResMut<ElementIdAllocator>,
 ) {                                                                                                                                                                                                                                                                                                                                                                                                                                             
     commands.spawn((
 
@@ -21,9 +24,16 @@ pub fn setup_scene(
         Selectable,
     ));
 
+    // Meshed here as well as by the MEP plugin, which headless publishing runs without
+    let duct = MepSegment {
+        size: SectionSize::Round { diameter: 0.315 },
+        end: Vec3::new(2.5, 0.0, 0.0),
+    };
+    let duct_mesh = segment_mesh(&duct).unwrap_or_else(|| Sphere::new(0.8).mesh().uv(32, 18));
+
     commands.spawn((
                 ElementHeader {
-            id: ElementId(1),
+            id: ids.allocate(),
             name: Some("new item".to_owned()),
             kind: ElementKind::DuctSegment,
             kind_type: Some(ElementKindType::DuctSegment(
@@ -31,13 +41,20 @@ pub fn setup_scene(
             )),
             spec_id: Some(ElementId(12)),
             level_id: Some(ElementId(12)),
+            params: ElementParams::new(),
+        },
+        Ports {
+            domain: MepDomain::Duct,
+            system: DistributionSystemEnum::VENTILATION,
+            ports: duct.ports(),
         },
-        Mesh3d(meshes.add(Sphere::new(0.8).mesh().uv(32, 18))),
+        duct,
+        Mesh3d(meshes.add(duct_mesh)),
         MeshMaterial3d(materials.add(StandardMaterial {
             base_color: Color::srgb(0.25, 0.55, 0.95),
             ..default()
         })),
-        Transform::from_xyz(-1.25, 0.8, 0.0),
+        Transform::from_xyz(-3.0, 0.8, 0.0),
         RenderLayers::layer(0),
         Selectable,
     ));
@@ -0,0 +1,55 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use bevy::prelude::*;
+
+use new_core::action::{ActionDef, ActionId, ActionInvoked};
+use new_core::keymap::{InputContext, KeyBinding};
+use new_core::{ActiveViewport, GameViewportCamera, VisibleViewports};
+
+use crate::camera::controls::ViewportOrbitCamera;
+
+// Straight down looks along the up vector, which the orbit cannot turn about
+const TOP_PITCH: f32 = -FRAC_PI_2 + 0.001;
+
+// Standard views, yaw and pitch around the current pivot. Keys as on a numpad, with the
+// cursor over a viewport.
+const VIEWS: [(ActionId, &str, KeyCode, f32, f32); 4] = [
+    (ActionId("view.top"), "Top View", KeyCode::Numpad7, 0.0, TOP_PITCH),
+    (ActionId("view.front"), "Front View", KeyCode::Numpad1, 0.0, 0.0),
+    (ActionId("view.side"), "Side View", KeyCode::Numpad3, FRAC_PI_2, 0.0),
+    (ActionId("view.isometric"), "Isometric View", KeyCode::Numpad5, FRAC_PI_4, -0.6),
+];
+
+pub fn view_actions() -> impl Iterator<Item = ActionDef> {
+    VIEWS.iter().map(|&(id, label, key, ..)| ActionDef {
+        id,
+        label,
+        menu: "View",
+        bindings: vec![KeyBinding::key(key).in_context(InputContext::Viewport)],
+        prompt: None,
+    })
+}
+
+pub fn run_view_actions(
+    mut invoked: MessageReader<ActionInvoked>,
+    active: Res<ActiveViewport>,
+    visible_viewports: Res<VisibleViewports>,
+    mut cameras: Query<(&GameViewportCamera, &mut Transform, &mut ViewportOrbitCamera)>,
+) {
+    for action in invoked.read() {
+        let Some(&(.., yaw, pitch)) = VIEWS.iter().find(|(id, ..)| *id == action.id) else {
+            continue;
+        };
+        let Some(pane_id) = active.target(&visible_viewports) else {
+            continue;
+        };
+
+        for (tag, mut transform, mut orbit) in &mut cameras {
+            if tag.pane_id == pane_id {
+                orbit.yaw = yaw;
+                orbit.pitch = pitch;
+                orbit.apply_to_transform(&mut transform);
+            }
+        }
+    }
+}
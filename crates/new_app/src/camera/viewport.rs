@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use new_core::{GameViewportCamera, VisibleViewports};
 
 use crate::camera::controls::ViewportOrbitCamera;
+use crate::grid::view_grid::grid_layer;
 
 pub fn sync_viewport_cameras(
     visible_viewports: Res<VisibleViewports>,
@@ -123,7 +124,8 @@ fn spawn_viewport_camera(
             ..default()
         }),
         Transform::from_xyz(4.0, 3.0, 6.0).looking_at(Vec3::new(0.0, 0.7, 0.0), Vec3::Y),
-        RenderLayers::layer(0),
+        // The model and this view's own grid
+        RenderLayers::from_layers(&[0, grid_layer(pane_id)]),
         GameViewportCamera { pane_id },
         ViewportOrbitCamera::from_eye_and_target(eye, target),
     ));
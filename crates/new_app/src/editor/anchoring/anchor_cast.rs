@@ -0,0 +1,285 @@
+use bevy::ecs::system::SystemParam;
+use bevy::mesh::{Indices, VertexAttributeValues};
+use bevy::picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings, RayCastVisibility, RayMeshHit};
+use bevy::prelude::*;
+
+use new_core::GameViewportCamera;
+use new_core::anchor::{Anchor, AnchorStrategy, Attachment};
+use new_core::cad::Underlays;
+use new_core::element::{ElementHeader, ElementIndex};
+use new_core::grid::{ViewGrid, ViewGrids};
+use new_core::structural_grid::{StructuralGrid, plan_to_world, world_to_plan};
+
+// Underlay points closer than this to a ground point take it, meters
+const SNAP_RADIUS: f32 = 0.25;
+// Start of a drop above the point it falls from, so the surface it stands on is found again
+const DROP_LIFT: f32 = 0.01;
+// Deepest host an embedded element is centered in, meters
+const MAX_EMBED_DEPTH: f32 = 2.0;
+
+// Where an element goes and what holds it there
+pub struct Anchored {
+    pub transform: Transform,
+    pub anchor: Anchor,
+    // Landed on a host or on an underlay point rather than free on the ground
+    pub snapped: bool,
+}
+
+type HostElement<'a> = (&'a ElementHeader, &'a GlobalTransform, &'a Mesh3d);
+
+// Casts against the elements of the model to anchor point based elements
+#[derive(SystemParam)]
+pub struct AnchorCast<'w, 's> {
+    ray_cast: MeshRayCast<'w, 's>,
+    hosts: Query<'w, 's, HostElement<'static>>,
+    index: Res<'w, ElementIndex>,
+    underlays: Res<'w, Underlays>,
+    grids: Res<'w, ViewGrids>,
+    structural: Query<'w, 's, &'static StructuralGrid>,
+}
+
+impl AnchorCast<'_, '_> {
+    // Anchors along the ray with the strategy. Strategies that need a host fall back to the
+    // ground plane when nothing is hit, except embedding which needs a wall or slab.
+    pub fn anchor(&mut self, strategy: AnchorStrategy, ray: Ray3d, exclude: Option<Entity>) -> Option<Anchored> {
+        self.anchor_over(strategy, ray, exclude, &ViewGrid::default())
+    }
+
+    // Same from a viewport, falling back to its work plane and snapping to its grid
+    pub fn anchor_in_view(&mut self, pane_id: u32, strategy: AnchorStrategy, ray: Ray3d) -> Option<Anchored> {
+        let grid = self.grids.get(pane_id);
+        self.anchor_over(strategy, ray, None, &grid)
+    }
+
+    // Where the ray meets the work plane of the view, for showing what could not be anchored
+    pub fn work_plane_point(&self, pane_id: u32, ray: Ray3d) -> Option<Vec3> {
+        self.grids.get(pane_id).plane().intersect(ray)
+    }
+
+    fn anchor_over(
+        &mut self,
+        strategy: AnchorStrategy,
+        ray: Ray3d,
+        exclude: Option<Entity>,
+        grid: &ViewGrid,
+    ) -> Option<Anchored> {
+        match strategy {
+            AnchorStrategy::Float => self.ground(ray, strategy, grid),
+            AnchorStrategy::RayCast => match self.first_hit(ray, exclude, None) {
+                Some((host, hit)) => self.on_surface(strategy, host, hit.point, surface_normal(&hit)),
+                None => self.ground(ray, strategy, grid),
+            },
+            AnchorStrategy::Gravity => {
+                let start = match self.first_hit(ray, exclude, None) {
+                    Some((_, hit)) => hit.point,
+                    None => self.ground(ray, strategy, grid)?.transform.translation,
+                };
+                Some(self.drop(start, exclude, grid))
+            }
+            AnchorStrategy::Embedded => {
+                let (host, hit) = self.first_hit(ray, exclude, None)?;
+                let (header, ..) = self.hosts.get(host).ok()?;
+                if !AnchorStrategy::can_embed(header.kind) {
+                    return None;
+                }
+                let normal = surface_normal(&hit);
+                let middle = self
+                    .far_side(host, hit.point, normal)
+                    .map_or(hit.point, |far| (hit.point + far) / 2.0);
+                self.on_surface(strategy, host, middle, normal)
+            }
+            AnchorStrategy::Manifold => match self.first_hit(ray, exclude, None) {
+                Some((host, hit)) => {
+                    let triangle = hit.triangle_index?;
+                    let transform = Transform::from_translation(hit.point).with_rotation(upright(surface_normal(&hit)));
+                    Some(Anchored {
+                        transform,
+                        anchor: Anchor {
+                            strategy,
+                            host: self.index.id(host),
+                            attachment: Some(Attachment::Face {
+                                triangle,
+                                barycentric: hit.barycentric_coords,
+                            }),
+                        },
+                        snapped: true,
+                    })
+                }
+                None => self.ground(ray, strategy, grid),
+            },
+        }
+    }
+
+    // Follows the host after it moved or changed shape. None when the host is gone or the
+    // element was never attached.
+    pub fn follow(&mut self, anchor: &Anchor, element: Entity) -> Option<Anchored> {
+        let host = self.index.entity(anchor.host?)?;
+        let (_, host_transform, mesh) = self.hosts.get(host).ok()?;
+
+        match anchor.attachment? {
+            Attachment::Pose(local) => {
+                let transform = Transform::from_matrix(host_transform.to_matrix() * local.to_matrix());
+                if anchor.strategy != AnchorStrategy::Gravity {
+                    return Some(Anchored {
+                        transform,
+                        anchor: *anchor,
+                        snapped: true,
+                    });
+                }
+                // Lands on whatever is below now, the old host may have moved away. Stays
+                // upright, only turning with the host about the vertical.
+                let dropped = self.drop(transform.translation, Some(element), &ViewGrid::default());
+                let (yaw, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+                Some(Anchored {
+                    transform: dropped.transform.with_rotation(Quat::from_rotation_y(yaw)),
+                    ..dropped
+                })
+            }
+            Attachment::Face { triangle, barycentric } => {
+                let mesh = self.ray_cast.meshes.get(&mesh.0)?;
+                let [a, b, c] = triangle_vertices(mesh, triangle)?.map(|vertex| host_transform.transform_point(vertex));
+                let normal = (b - a).cross(c - a).try_normalize()?;
+                let point = a * barycentric.x + b * barycentric.y + c * barycentric.z;
+                Some(Anchored {
+                    transform: Transform::from_translation(point).with_rotation(upright(normal)),
+                    anchor: *anchor,
+                    snapped: true,
+                })
+            }
+        }
+    }
+
+    // Anchors again from where the element stands, casting along its own down axis
+    pub fn settle(&mut self, strategy: AnchorStrategy, transform: &Transform, element: Entity) -> Option<Anchored> {
+        let down = transform.rotation * Vec3::NEG_Y;
+        let ray = Ray3d::new(transform.translation - down * DROP_LIFT, Dir3::new(down).ok()?);
+        let mut anchored = self.anchor(strategy, ray, Some(element))?;
+        if strategy == AnchorStrategy::Float {
+            anchored.transform = *transform;
+        }
+        Some(anchored)
+    }
+
+    // Point and normal of the first element face along the ray
+    pub fn face(&mut self, ray: Ray3d) -> Option<(Vec3, Vec3)> {
+        let (_, hit) = self.first_hit(ray, None, None)?;
+        Some((hit.point, surface_normal(&hit)))
+    }
+
+    fn first_hit(&mut self, ray: Ray3d, exclude: Option<Entity>, only: Option<Entity>) -> Option<(Entity, RayMeshHit)> {
+        let hosts = &self.hosts;
+        let filter = |entity: Entity| {
+            Some(entity) != exclude && only.is_none_or(|only| only == entity) && hosts.contains(entity)
+        };
+        let settings = MeshRayCastSettings::default()
+            .with_visibility(RayCastVisibility::Visible)
+            .with_filter(&filter);
+        self.ray_cast.cast_ray(ray, &settings).first().cloned()
+    }
+
+    // Back faces are not hit, so the far side is found from beyond it looking back
+    fn far_side(&mut self, host: Entity, point: Vec3, normal: Vec3) -> Option<Vec3> {
+        let ray = Ray3d::new(point - normal * MAX_EMBED_DEPTH, Dir3::new(normal).ok()?);
+        let (_, hit) = self.first_hit(ray, None, Some(host))?;
+        (hit.distance < MAX_EMBED_DEPTH - 1.0e-4).then_some(hit.point)
+    }
+
+    // Falls onto the work plane when nothing is below, or onto the ground when the plane is
+    // not below either
+    fn drop(&mut self, start: Vec3, exclude: Option<Entity>, grid: &ViewGrid) -> Anchored {
+        let ray = Ray3d::new(start + Vec3::Y * DROP_LIFT, Dir3::NEG_Y);
+        match self.first_hit(ray, exclude, None) {
+            Some((host, hit)) => self
+                .on_surface(AnchorStrategy::Gravity, host, hit.point, Vec3::Y)
+                .unwrap_or_else(|| self.floating(AnchorStrategy::Gravity, hit.point, true)),
+            None => {
+                let ground = grid.plane().intersect(ray).unwrap_or(Vec3::new(start.x, 0.0, start.z));
+                self.floating(AnchorStrategy::Gravity, ground, false)
+            }
+        }
+    }
+
+    // Pose kept in the host frame
+    fn on_surface(&self, strategy: AnchorStrategy, host: Entity, point: Vec3, normal: Vec3) -> Option<Anchored> {
+        let (_, host_transform, _) = self.hosts.get(host).ok()?;
+        let transform = Transform::from_translation(point).with_rotation(upright(normal));
+        let local = Transform::from_matrix(host_transform.to_matrix().inverse() * transform.to_matrix());
+        Some(Anchored {
+            transform,
+            anchor: Anchor {
+                strategy,
+                host: self.index.id(host),
+                attachment: Some(Attachment::Pose(local)),
+            },
+            snapped: true,
+        })
+    }
+
+    // Underlay points win over the grids, they are what is being traced. Structural grid
+    // intersections come next, then the working grid. Underlays and structural grids are in
+    // plan, so they only snap on level work planes.
+    fn ground(&self, ray: Ray3d, strategy: AnchorStrategy, grid: &ViewGrid) -> Option<Anchored> {
+        let plane = grid.plane();
+        let point = plane.intersect(ray)?;
+        if plane.normal == Vec3::Y {
+            // A horizontal face is not the level, even at its height
+            let level_id = grid.level_id.filter(|_| grid.face.is_none());
+            if let Some(snap) = self.underlays.snap(point, level_id, SNAP_RADIUS) {
+                return Some(self.floating(strategy, snap, true));
+            }
+            if let Some(intersection) = self
+                .structural
+                .iter()
+                .find_map(|structural| structural.snap(world_to_plan(point), SNAP_RADIUS))
+            {
+                return Some(self.floating(strategy, plan_to_world(intersection.point, point.y), true));
+            }
+        }
+        let point = if grid.snap { grid.snap_point(point) } else { point };
+        Some(self.floating(strategy, point, false))
+    }
+
+    fn floating(&self, strategy: AnchorStrategy, point: Vec3, snapped: bool) -> Anchored {
+        Anchored {
+            transform: Transform::from_translation(point),
+            anchor: Anchor::unattached(strategy),
+            snapped,
+        }
+    }
+}
+
+// Element up along the surface normal, so fixtures hang from ceilings and stand out of walls
+fn upright(normal: Vec3) -> Quat {
+    Quat::from_rotation_arc(Vec3::Y, normal)
+}
+
+fn surface_normal(hit: &RayMeshHit) -> Vec3 {
+    hit.normal.try_normalize().unwrap_or(Vec3::Y)
+}
+
+fn triangle_vertices(mesh: &Mesh, triangle: usize) -> Option<[Vec3; 3]> {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        return None;
+    };
+    let corners = [triangle * 3, triangle * 3 + 1, triangle * 3 + 2];
+    let corners = match mesh.indices() {
+        Some(Indices::U16(indices)) => corners.map(|corner| indices.get(corner).map(|index| *index as usize)),
+        Some(Indices::U32(indices)) => corners.map(|corner| indices.get(corner).map(|index| *index as usize)),
+        None => corners.map(Some),
+    };
+    let [a, b, c] = corners.map(|corner| corner.and_then(|index| positions.get(index)).map(|p| Vec3::from_array(*p)));
+    Some([a?, b?, c?])
+}
+
+// Ray through the cursor of one viewport pane
+#[derive(SystemParam)]
+pub struct ViewportRay<'w, 's> {
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform, &'static GameViewportCamera)>,
+}
+
+impl ViewportRay<'_, '_> {
+    pub fn ray(&self, pane_id: u32, cursor: Vec2) -> Option<Ray3d> {
+        let (camera, transform, _) = self.cameras.iter().find(|(.., tag)| tag.pane_id == pane_id)?;
+        camera.viewport_to_world(transform, cursor).ok()
+    }
+}
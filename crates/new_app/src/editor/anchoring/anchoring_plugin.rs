@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+
+use new_core::anchor::PlacementSettings;
+
+use crate::editor::anchoring::follow_hosts::{follow_hosts, settle_anchors};
+
+pub struct AnchoringPlugin;
+
+impl Plugin for AnchoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlacementSettings>()
+            .add_systems(Update, (settle_anchors, follow_hosts).chain());
+    }
+}
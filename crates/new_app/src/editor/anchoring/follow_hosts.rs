@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use new_core::anchor::{Anchor, AnchorStrategy};
+use new_core::element::{ElementHeader, ElementId};
+
+use crate::editor::anchoring::anchor_cast::{AnchorCast, Anchored};
+
+// Elements given a new strategy, or placed where nothing held them, look for a host from
+// where they stand
+pub fn settle_anchors(
+    mut changed: Query<(Entity, &mut Anchor, &mut Transform), Changed<Anchor>>,
+    mut cast: AnchorCast,
+) {
+    for (entity, mut anchor, mut transform) in &mut changed {
+        if anchor.attachment.is_some() || anchor.strategy == AnchorStrategy::Float {
+            continue;
+        }
+        if let Some(anchored) = cast.settle(anchor.strategy, &transform, entity) {
+            apply(anchored, &mut anchor, &mut transform);
+        }
+    }
+}
+
+type MovedHosts = Or<(Changed<GlobalTransform>, Changed<Mesh3d>)>;
+
+// Elements whose host moved or changed shape since the last frame go with it
+pub fn follow_hosts(
+    changed_hosts: Query<&ElementHeader, MovedHosts>,
+    mut anchored: Query<(Entity, &mut Anchor, &mut Transform)>,
+    mut cast: AnchorCast,
+) {
+    let moved: HashSet<ElementId> = changed_hosts.iter().map(|header| header.id).collect();
+    if moved.is_empty() {
+        return;
+    }
+
+    for (entity, mut anchor, mut transform) in &mut anchored {
+        if !anchor.host.is_some_and(|host| moved.contains(&host)) {
+            continue;
+        }
+        if let Some(followed) = cast.follow(&anchor, entity) {
+            apply(followed, &mut anchor, &mut transform);
+        }
+    }
+}
+
+// Keeps the element's own scale, and leaves untouched what did not change so nothing hosted
+// on it moves for no reason
+fn apply(anchored: Anchored, anchor: &mut Mut<Anchor>, transform: &mut Mut<Transform>) {
+    let Anchored {
+        transform: pose,
+        anchor: new_anchor,
+        ..
+    } = anchored;
+
+    if transform.translation != pose.translation || transform.rotation != pose.rotation {
+        transform.translation = pose.translation;
+        transform.rotation = pose.rotation;
+    }
+    if **anchor != new_anchor {
+        **anchor = new_anchor;
+    }
+}
@@ -0,0 +1,3 @@
+pub mod anchor_cast;
+pub mod anchoring_plugin;
+pub mod follow_hosts;
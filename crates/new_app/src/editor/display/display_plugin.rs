@@ -0,0 +1,16 @@
+use bevy::camera::visibility::VisibilitySystems;
+use bevy::prelude::*;
+
+use crate::editor::display::resolve::{resolve_display_overrides, setup_display_materials};
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_display_materials)
+            .add_systems(
+                PostUpdate,
+                resolve_display_overrides.before(VisibilitySystems::VisibilityPropagate),
+            );
+    }
+}
@@ -0,0 +1,2 @@
+pub mod display_plugin;
+pub mod resolve;
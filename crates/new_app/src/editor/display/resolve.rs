@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use bevy::pbr::MeshMaterial3d;
+use bevy::prelude::*;
+
+use new_core::display::{DisplayOverrides, DisplaySource, ElementAppearance};
+
+// How the element looked before the first override, put back once the stack is empty
+#[derive(Component, Clone)]
+pub struct DisplayBase {
+    pub visibility: Visibility,
+    pub material: Option<Handle<StandardMaterial>>,
+}
+
+#[derive(Resource)]
+pub struct DisplayMaterials {
+    pub ghost: Handle<StandardMaterial>,
+    pub tints: HashMap<[u8; 4], Handle<StandardMaterial>>,
+}
+
+pub fn setup_display_materials(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let ghost = materials.add(StandardMaterial {
+        base_color: Color::srgba(0.65, 0.72, 0.8, 0.2),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..Default::default()
+    });
+
+    commands.insert_resource(DisplayMaterials {
+        ghost,
+        tints: HashMap::new(),
+    });
+}
+
+type OverriddenElements<'a> = (
+    Entity,
+    &'a DisplayOverrides,
+    &'a mut Visibility,
+    Option<&'a mut MeshMaterial3d<StandardMaterial>>,
+    Option<&'a DisplayBase>,
+);
+
+pub fn resolve_display_overrides(
+    mut commands: Commands,
+    mut display_materials: ResMut<DisplayMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut elements: Query<OverriddenElements, Changed<DisplayOverrides>>,
+) {
+    for (entity, overrides, mut visibility, material, base) in &mut elements {
+        let base = match base {
+            Some(base) => base.clone(),
+            None => {
+                let base = DisplayBase {
+                    visibility: *visibility,
+                    material: material.as_ref().map(|m| m.0.clone()),
+                };
+                commands.entity(entity).insert(base.clone());
+                base
+            }
+        };
+
+        if overrides.is_empty() {
+            visibility.set_if_neq(base.visibility);
+            match (material, base.material) {
+                (Some(mut material), Some(handle)) => {
+                    material.set_if_neq(MeshMaterial3d(handle));
+                }
+                // Drawn by its parts again
+                (Some(_), None) => {
+                    commands.entity(entity).remove::<MeshMaterial3d<StandardMaterial>>();
+                }
+                _ => {}
+            }
+            // A source may have written to the stack again since, later in this frame
+            commands.entity(entity).queue(|mut entity: EntityWorldMut| {
+                if entity.get::<DisplayOverrides>().is_some_and(DisplayOverrides::is_empty) {
+                    entity.remove::<(DisplayOverrides, DisplayBase)>();
+                }
+            });
+            continue;
+        }
+
+        let appearance = overrides.resolve();
+
+        visibility.set_if_neq(match appearance {
+            ElementAppearance::Hidden => Visibility::Hidden,
+            _ => base.visibility,
+        });
+
+        let target = match appearance {
+            ElementAppearance::Normal | ElementAppearance::Hidden => base.material,
+            ElementAppearance::Ghost => Some(display_materials.ghost.clone()),
+            ElementAppearance::Tint(color) => Some(
+                display_materials
+                    .tints
+                    .entry(color.to_srgba().to_u8_array())
+                    .or_insert_with(|| {
+                        materials.add(StandardMaterial {
+                            base_color: color,
+                            ..Default::default()
+                        })
+                    })
+                    .clone(),
+            ),
+        };
+
+        match (material, target) {
+            (Some(mut material), Some(handle)) => {
+                material.set_if_neq(MeshMaterial3d(handle));
+            }
+            // An asset drawn by its parts takes the override on its whole mesh, the parts
+            // stay hidden while it has a material
+            (None, Some(handle)) => {
+                commands.entity(entity).insert(MeshMaterial3d(handle));
+            }
+            (Some(_), None) => {
+                commands.entity(entity).remove::<MeshMaterial3d<StandardMaterial>>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+// Sets one source on an element, or clears it with None. Only touches the stack when the
+// value actually changes so the resolver does not run for every element every frame.
+// Elements without a stack get one merged in when commands apply, so sources writing to the
+// same element in one frame all keep their entry.
+pub fn write_override(
+    commands: &mut Commands,
+    entity: Entity,
+    overrides: Option<Mut<DisplayOverrides>>,
+    source: DisplaySource,
+    appearance: Option<ElementAppearance>,
+) {
+    let current = overrides.as_ref().and_then(|overrides| overrides.get(source));
+    if current == appearance {
+        return;
+    }
+
+    match (overrides, appearance) {
+        (Some(mut overrides), Some(appearance)) => overrides.set(source, appearance),
+        (Some(mut overrides), None) => overrides.clear(source),
+        (None, Some(appearance)) => {
+            commands
+                .entity(entity)
+                .entry::<DisplayOverrides>()
+                .or_default()
+                .and_modify(move |mut overrides| overrides.set(source, appearance));
+        }
+        (None, None) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::schedule::Schedule;
+
+    // A source that puts the same appearance on every element
+    fn write(
+        source: DisplaySource,
+        appearance: ElementAppearance,
+    ) -> impl FnMut(Commands, Query<(Entity, Option<&mut DisplayOverrides>)>) {
+        move |mut commands, mut elements| {
+            for (entity, overrides) in &mut elements {
+                write_override(&mut commands, entity, overrides, source, Some(appearance));
+            }
+        }
+    }
+
+    #[test]
+    fn sources_writing_in_one_frame_keep_both_entries() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((
+            write(DisplaySource::Timeline, ElementAppearance::Ghost),
+            write(DisplaySource::ClashIsolation, ElementAppearance::Hidden),
+        ));
+        schedule.run(&mut world);
+
+        let overrides = world.get::<DisplayOverrides>(entity).expect("overrides");
+        assert_eq!(overrides.get(DisplaySource::Timeline), Some(ElementAppearance::Ghost));
+        assert_eq!(overrides.get(DisplaySource::ClashIsolation), Some(ElementAppearance::Hidden));
+        assert_eq!(overrides.resolve(), ElementAppearance::Hidden);
+    }
+
+    #[test]
+    fn an_emptied_stack_written_again_in_the_same_frame_stays() {
+        let mut world = World::new();
+        world.insert_resource(DisplayMaterials {
+            ghost: Handle::default(),
+            tints: HashMap::new(),
+        });
+        world.init_resource::<Assets<StandardMaterial>>();
+        let entity = world.spawn((DisplayOverrides::default(), Visibility::Visible)).id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (resolve_display_overrides, write(DisplaySource::Timeline, ElementAppearance::Ghost)).chain(),
+        );
+        // Chained systems apply commands in between, keep the resolver's removal for the end
+        schedule.set_build_settings(bevy::ecs::schedule::ScheduleBuildSettings {
+            auto_insert_apply_deferred: false,
+            ..Default::default()
+        });
+        schedule.run(&mut world);
+
+        let overrides = world.get::<DisplayOverrides>(entity).expect("overrides");
+        assert_eq!(overrides.get(DisplaySource::Timeline), Some(ElementAppearance::Ghost));
+    }
+}
@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+
+use new_core::element::{ElementHeader, ElementIdAllocator, ElementIndex};
+
+pub fn index_elements(
+    mut index: ResMut<ElementIndex>,
+    mut ids: ResMut<ElementIdAllocator>,
+    changed: Query<(Entity, &ElementHeader), Changed<ElementHeader>>,
+) {
+    for (entity, header) in &changed {
+        // Header may have been given a new id
+        if let Some(old) = index.ids.insert(entity, header.id)
+            && old != header.id
+            && index.entities.get(&old) == Some(&entity)
+        {
+            index.entities.remove(&old);
+        }
+
+        index.entities.insert(header.id, entity);
+        ids.reserve(header.id);
+    }
+}
+
+pub fn unindex_removed_elements(
+    mut index: ResMut<ElementIndex>,
+    mut removed: RemovedComponents<ElementHeader>,
+) {
+    for entity in removed.read() {
+        if let Some(id) = index.ids.remove(&entity)
+            && index.entities.get(&id) == Some(&entity)
+        {
+            index.entities.remove(&id);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+
+use new_core::element::{ElementIdAllocator, ElementIndex};
+use new_core::inspector::{ElementEdit, InspectedElement};
+
+use crate::editor::elements::element_index::{index_elements, unindex_removed_elements};
+use crate::editor::elements::inspect::{apply_element_edits, sync_inspected_element};
+
+pub struct ElementsPlugin;
+
+impl Plugin for ElementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ElementIdAllocator>()
+            .init_resource::<ElementIndex>()
+            .init_resource::<InspectedElement>()
+            .add_message::<ElementEdit>()
+            .add_systems(PreUpdate, (index_elements, unindex_removed_elements))
+            .add_systems(Update, (apply_element_edits, sync_inspected_element).chain());
+    }
+}
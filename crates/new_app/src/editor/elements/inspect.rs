@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+
+use new_core::anchor::Anchor;
+use new_core::element::{ElementHeader, ElementIndex, ParamKey, ParamValue};
+use new_core::inspector::{ElementEdit, InspectedElement, InspectedRoom, InspectedSystem, PortConnection};
+use new_core::mep::Ports;
+use new_core::mep_system::MepAnalysis;
+use new_core::phase::ElementPhasing;
+use new_core::room::{NUMBER_PARAM, Room};
+use new_core::structural_grid::{GridPoint, GridReference, StructuralGrid};
+
+use crate::editor::selection::picking::SelectionState;
+
+type InspectableElement<'a> = (
+    &'a ElementHeader,
+    Option<&'a ElementPhasing>,
+    Option<&'a Anchor>,
+    Option<&'a GridReference>,
+    Option<&'a Ports>,
+    Option<&'a Room>,
+);
+
+type EditedRoom<'a> = (&'a mut ElementHeader, &'a mut Room);
+
+pub fn sync_inspected_element(
+    selection: Res<SelectionState>,
+    elements: Query<InspectableElement>,
+    index: Res<ElementIndex>,
+    analysis: Res<MepAnalysis>,
+    mut inspected: ResMut<InspectedElement>,
+) {
+    let entity = selection.current.filter(|entity| elements.contains(*entity));
+
+    match entity.and_then(|entity| elements.get(entity).ok()) {
+        Some((header, phasing, anchor, grid_reference, ports, room)) => {
+            inspected.entity = entity;
+            inspected.header = Some(header.clone());
+            inspected.phasing = phasing.copied().unwrap_or_default();
+            inspected.anchor = anchor.copied();
+            inspected.grid_reference = grid_reference.cloned();
+            inspected.connections = ports.map_or_else(Vec::new, |ports| {
+                let named = |entity| elements.get(entity).ok().map(|(header, ..)| header.name.clone());
+                ports
+                    .ports
+                    .iter()
+                    .enumerate()
+                    .map(|(port, at)| PortConnection {
+                        label: ports.label(port),
+                        size: at.size,
+                        flow: at.flow,
+                        design_flow: at.design_flow,
+                        connected: at.connected.map(|other| {
+                            let name = index.entity(other.element).and_then(named).flatten();
+                            (other.element, name)
+                        }),
+                    })
+                    .collect()
+            });
+            inspected.system = ports.map(|ports| InspectedSystem {
+                domain: ports.domain,
+                system: ports.system,
+                flow: analysis.flows.get(&header.id).copied(),
+                issues: analysis
+                    .issues_for(header.id)
+                    .map(|issue| match issue.port {
+                        Some(port) => format!("{}: {}", ports.label(port), issue.message),
+                        None => issue.message.clone(),
+                    })
+                    .collect(),
+            });
+            inspected.room = room.map(|room| InspectedRoom {
+                number: header
+                    .params
+                    .get(&ParamKey::new(NUMBER_PARAM))
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                height: room.height,
+                enclosed: room.enclosed,
+            });
+        }
+        None if inspected.entity.is_some() => *inspected = InspectedElement::default(),
+        None => {}
+    }
+}
+
+pub fn apply_element_edits(
+    mut commands: Commands,
+    mut edits: MessageReader<ElementEdit>,
+    grids: Query<(&ElementHeader, &StructuralGrid)>,
+    mut ports: Query<(&ElementHeader, &mut Ports)>,
+    mut rooms: Query<EditedRoom, (Without<Ports>, Without<StructuralGrid>)>,
+    analysis: Res<MepAnalysis>,
+) {
+    for edit in edits.read() {
+        match edit {
+            ElementEdit::SetPhasing(entity, phasing) => {
+                if let Ok(mut element) = commands.get_entity(*entity) {
+                    element.insert(*phasing);
+                }
+            }
+            ElementEdit::SetAnchor(entity, strategy) => {
+                if let Ok(mut element) = commands.get_entity(*entity) {
+                    element.insert(Anchor::unattached(*strategy));
+                }
+            }
+            ElementEdit::SetGridReference(entity, at, towards) => {
+                let Ok(mut element) = commands.get_entity(*entity) else {
+                    continue;
+                };
+                let Some(at) = at else {
+                    element.remove::<GridReference>();
+                    continue;
+                };
+                // Points naming axes no grid has are dropped rather than kept dangling
+                let on_grid = |point: &GridPoint| {
+                    grids
+                        .iter()
+                        .find(|(_, grid)| grid.point(point).is_some())
+                        .map(|(header, _)| header.id)
+                };
+                let Some(grid) = on_grid(at) else {
+                    continue;
+                };
+                let towards = towards.clone().filter(|towards| on_grid(towards) == Some(grid));
+                element.insert(GridReference {
+                    grid,
+                    at: at.clone(),
+                    towards,
+                });
+            }
+            ElementEdit::SetSystem(entity, system) => {
+                let Ok((header, _)) = ports.get(*entity) else {
+                    continue;
+                };
+                // The whole network changes over, elements the analysis has not seen yet on their own
+                let network = analysis
+                    .network_of(header.id)
+                    .map_or_else(|| vec![header.id], |network| network.elements.clone());
+                for (header, mut element) in &mut ports {
+                    if network.contains(&header.id) && element.system != *system {
+                        element.system = *system;
+                    }
+                }
+            }
+            ElementEdit::SetDesignFlow(entity, port, flow) => {
+                if let Ok((_, mut element)) = ports.get_mut(*entity)
+                    && let Some(port) = element.ports.get_mut(*port)
+                {
+                    port.design_flow = flow.map(|flow| flow.max(0.0));
+                }
+            }
+            ElementEdit::SetRoomNumber(entity, number) => {
+                let Ok((mut header, _)) = rooms.get_mut(*entity) else {
+                    continue;
+                };
+                let key = ParamKey::new(NUMBER_PARAM);
+                match number.trim() {
+                    "" => header.params.remove(&key),
+                    number => header.params.insert(key, ParamValue::Text(number.to_owned())),
+                };
+            }
+            ElementEdit::SetRoomName(entity, name) => {
+                if let Ok((mut header, _)) = rooms.get_mut(*entity) {
+                    let name = name.trim();
+                    header.name = (!name.is_empty()).then(|| name.to_owned());
+                }
+            }
+            ElementEdit::SetRoomHeight(entity, height) => {
+                if let Ok((_, mut room)) = rooms.get_mut(*entity) {
+                    room.height = height.max(0.1);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod element_index;
+pub mod elements_plugin;
+pub mod inspect;
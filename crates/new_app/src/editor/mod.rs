@@ -1 +1,5 @@
+pub mod anchoring;
+pub mod display;
+pub mod elements;
+pub mod phasing;
 pub mod selection;
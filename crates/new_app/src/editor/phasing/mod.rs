@@ -0,0 +1,4 @@
+pub mod phasing_plugin;
+pub mod proxies;
+pub mod view_actions;
+pub mod view_filter;
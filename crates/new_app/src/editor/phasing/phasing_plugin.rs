@@ -0,0 +1,35 @@
+use bevy::camera::visibility::VisibilitySystems;
+use bevy::prelude::*;
+
+use new_core::action::ActionAppExt;
+use new_core::phase::ViewPhases;
+
+use crate::editor::phasing::proxies::{
+    setup_phase_materials,
+    spawn_phase_proxies,
+    sync_phase_proxy_meshes,
+};
+use crate::editor::phasing::view_actions::{run_view_phase_actions, view_phase_actions};
+use crate::editor::phasing::view_filter::filter_visible_by_phase;
+
+pub struct PhasingPlugin;
+
+impl Plugin for PhasingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewPhases>();
+        for action in view_phase_actions() {
+            app.register_action(action);
+        }
+        app.add_systems(Startup, setup_phase_materials)
+            .add_systems(
+                Update,
+                (spawn_phase_proxies, sync_phase_proxy_meshes, run_view_phase_actions),
+            )
+            .add_systems(
+                PostUpdate,
+                filter_visible_by_phase
+                    .after(VisibilitySystems::CheckVisibility)
+                    .before(VisibilitySystems::MarkNewlyHiddenEntitiesInvisible),
+            );
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::math::Affine2;
+use bevy::pbr::MeshMaterial3d;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use new_core::element::ElementHeader;
+use new_core::phase::PhaseStyle;
+
+// Copy of an element drawn with a phase style. Views pick either the element or one of
+// its proxies, so the same element can look different in two viewports.
+#[derive(Component, Clone, Copy)]
+pub struct PhaseProxy(pub PhaseStyle);
+
+#[derive(Component)]
+pub struct PhaseProxies {
+    pub halftone: Entity,
+    pub demolished: Entity,
+}
+
+#[derive(Resource)]
+pub struct PhaseMaterials {
+    pub halftone: Handle<StandardMaterial>,
+    pub demolished: Handle<StandardMaterial>,
+}
+
+pub fn setup_phase_materials(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let halftone = materials.add(StandardMaterial {
+        base_color: Color::srgba(0.78, 0.78, 0.8, 0.45),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..Default::default()
+    });
+
+    let demolished = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.9, 0.15, 0.1),
+        base_color_texture: Some(images.add(hatch_image())),
+        uv_transform: Affine2::from_scale(Vec2::splat(6.0)),
+        alpha_mode: AlphaMode::Mask(0.5),
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..Default::default()
+    });
+
+    commands.insert_resource(PhaseMaterials {
+        halftone,
+        demolished,
+    });
+}
+
+// Diagonal stripes, the see through gaps give demolished work its dashed look
+fn hatch_image() -> Image {
+    const SIZE: u32 = 16;
+
+    let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let alpha = if (x + y) % 8 < 4 { 255 } else { 0 };
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..Default::default()
+    });
+    image
+}
+
+type UnproxiedElements = (With<ElementHeader>, Without<PhaseProxies>);
+
+pub fn spawn_phase_proxies(
+    mut commands: Commands,
+    materials: Res<PhaseMaterials>,
+    elements: Query<(Entity, &Mesh3d), UnproxiedElements>,
+) {
+    for (entity, mesh) in &elements {
+        let mut spawn = |style: PhaseStyle, material: &Handle<StandardMaterial>| {
+            commands
+                .spawn((
+                    Name::new("PhaseProxy"),
+                    Mesh3d(mesh.0.clone()),
+                    MeshMaterial3d(material.clone()),
+                    Transform::IDENTITY,
+                    PhaseProxy(style),
+                    ChildOf(entity),
+                ))
+                .id()
+        };
+
+        let proxies = PhaseProxies {
+            halftone: spawn(PhaseStyle::Halftone, &materials.halftone),
+            demolished: spawn(PhaseStyle::Demolished, &materials.demolished),
+        };
+        commands.entity(entity).insert(proxies);
+    }
+}
+
+pub fn sync_phase_proxy_meshes(
+    elements: Query<(&Mesh3d, &PhaseProxies), Changed<Mesh3d>>,
+    mut proxies: Query<&mut Mesh3d, (With<PhaseProxy>, Without<PhaseProxies>)>,
+) {
+    for (mesh, element_proxies) in &elements {
+        for proxy in [element_proxies.halftone, element_proxies.demolished] {
+            if let Ok(mut proxy_mesh) = proxies.get_mut(proxy) {
+                proxy_mesh.set_if_neq(mesh.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+use new_core::action::{ActionDef, ActionId, ActionInvoked, ActionPrompt};
+use new_core::phase::{Phase, PhaseFilter, ViewPhases};
+use new_core::{ActiveViewport, VisibleViewports};
+
+pub const VIEW_PHASE: ActionId = ActionId("view.phase");
+pub const VIEW_PHASE_FILTER: ActionId = ActionId("view.phase_filter");
+
+pub fn view_phase_actions() -> [ActionDef; 2] {
+    [
+        ActionDef {
+            id: VIEW_PHASE,
+            label: "Set View Phase",
+            menu: "View",
+            bindings: Vec::new(),
+            prompt: Some(ActionPrompt {
+                label: "Phase",
+                choices: Phase::iter().map(|phase| phase.to_string()).collect(),
+            }),
+        },
+        ActionDef {
+            id: VIEW_PHASE_FILTER,
+            label: "Set Phase Filter",
+            menu: "View",
+            bindings: Vec::new(),
+            prompt: Some(ActionPrompt {
+                label: "Filter",
+                choices: PhaseFilter::iter().map(|filter| filter.to_string()).collect(),
+            }),
+        },
+    ]
+}
+
+// Phase and filter of the viewport the user is working in
+pub fn run_view_phase_actions(
+    mut invoked: MessageReader<ActionInvoked>,
+    active: Res<ActiveViewport>,
+    visible_viewports: Res<VisibleViewports>,
+    mut view_phases: ResMut<ViewPhases>,
+) {
+    for action in invoked.read() {
+        let Some(arg) = &action.arg else {
+            continue;
+        };
+        let Some(pane_id) = active.target(&visible_viewports) else {
+            continue;
+        };
+        let mut view = view_phases.get(pane_id);
+
+        if action.id == VIEW_PHASE
+            && let Some(phase) = Phase::iter().find(|phase| phase.to_string() == *arg)
+        {
+            view.phase = phase;
+        } else if action.id == VIEW_PHASE_FILTER
+            && let Some(filter) = PhaseFilter::iter().find(|filter| filter.to_string() == *arg)
+        {
+            view.filter = filter;
+        } else {
+            continue;
+        }
+        view_phases.views.insert(pane_id, view);
+    }
+}
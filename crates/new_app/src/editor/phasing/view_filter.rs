@@ -0,0 +1,63 @@
+use bevy::camera::visibility::VisibleEntities;
+use bevy::prelude::*;
+
+use new_core::GameViewportCamera;
+use new_core::asset_library::InstancePart;
+use new_core::element::ElementHeader;
+use new_core::phase::{ElementPhasing, PhaseStyle, ViewPhases};
+
+use super::proxies::PhaseProxy;
+
+// Phasing and whether the element draws itself rather than through instance parts
+type PhasedElement<'a> = (Option<&'a ElementPhasing>, Has<MeshMaterial3d<StandardMaterial>>);
+
+// Runs right after bevy decided what each camera sees and takes out what the view's
+// phase filter does not show. Styled elements are swapped for their proxy.
+pub fn filter_visible_by_phase(
+    view_phases: Res<ViewPhases>,
+    mut cameras: Query<(&mut VisibleEntities, Option<&GameViewportCamera>)>,
+    elements: Query<PhasedElement, With<ElementHeader>>,
+    proxies: Query<&PhaseProxy>,
+    parts: Query<(), With<InstancePart>>,
+    parents: Query<&ChildOf>,
+) {
+    for (mut visible, tag) in &mut cameras {
+        let view = tag.map(|tag| view_phases.get(tag.pane_id));
+
+        for entities in visible.entities.values_mut() {
+            entities.retain(|&entity| {
+                let owner = if elements.contains(entity) {
+                    entity
+                } else if let Ok(child_of) = parents.get(entity)
+                    && elements.contains(child_of.parent())
+                {
+                    child_of.parent()
+                } else {
+                    return true;
+                };
+
+                let proxy = proxies.get(entity).ok().map(|proxy| proxy.0);
+
+                // Cameras that are not viewports never draw proxies
+                let Some(view) = view else {
+                    return proxy.is_none();
+                };
+
+                let (phasing, own_material) = elements
+                    .get(owner)
+                    .map_or((None, false), |(phasing, own_material)| (phasing.copied(), own_material));
+                let style = view.style(&phasing.unwrap_or_default());
+
+                match proxy {
+                    Some(proxy_style) => proxy_style == style,
+                    // The element itself and the parts it is drawn by only draw unstyled,
+                    // anything else hanging off it (outlines, highlights) follows the element
+                    // being there at all. Parts give way to a display override on the element.
+                    None if entity == owner => style == PhaseStyle::Normal,
+                    None if parts.contains(entity) => style == PhaseStyle::Normal && !own_material,
+                    None => style != PhaseStyle::Hidden,
+                }
+            });
+        }
+    }
+}
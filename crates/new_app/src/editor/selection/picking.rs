@@ -5,8 +5,12 @@ use bevy::picking::mesh_picking::ray_cast::{
 use bevy::window::PrimaryWindow;
 
 use bevy_egui::egui;
+use new_core::action::{ActionId, ActionInvoked};
 use new_core::{GameViewportCamera, VisibleViewports};
 
+pub const DESELECT_ALL: ActionId = ActionId("edit.deselect_all");
+pub const DELETE_SELECTED: ActionId = ActionId("edit.delete");
+
 #[derive(Resource, Default)]
 pub struct SelectionState {
     pub current: Option<Entity>
@@ -75,7 +79,33 @@ pub fn select_with_left_click(
 
 }
 
-fn set_selection(
+pub fn run_selection_actions(
+    mut invoked: MessageReader<ActionInvoked>,
+    mut selection: ResMut<SelectionState>,
+    selected: Query<Entity, With<Selected>>,
+    mut commands: Commands,
+) {
+    let mut deselect = false;
+    let mut delete = false;
+    for action in invoked.read() {
+        deselect |= action.id == DESELECT_ALL;
+        delete |= action.id == DELETE_SELECTED;
+    }
+    if !deselect && !delete {
+        return;
+    }
+
+    selection.current = None;
+    for entity in &selected {
+        if delete {
+            commands.entity(entity).despawn();
+        } else {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+}
+
+pub fn set_selection(
     selection: &mut SelectionState,
     new_entity: Option<Entity>,
     exists: &Query<(), ()>,
@@ -1,24 +1,44 @@
 use bevy::prelude::*;
 
+use new_core::action::{ActionAppExt, ActionDef};
+use new_core::keymap::KeyBinding;
+
 use crate::editor::selection::{
     highlight::{
         remove_outline_for_deselected,
         setup_outline_material,
         spawn_outline_for_selected
     },
-    picking::{SelectionState, select_with_left_click},
+    picking::{DELETE_SELECTED, DESELECT_ALL, SelectionState, run_selection_actions, select_with_left_click},
 };
+use crate::tools::framework::activation::no_tool_active;
 
 pub struct SelectionPlugin;
 
 impl Plugin for SelectionPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SelectionState>()
+            .register_action(ActionDef {
+                id: DESELECT_ALL,
+                label: "Deselect All",
+                menu: "Edit",
+                bindings: vec![KeyBinding::alt(KeyCode::KeyA)],
+                prompt: None,
+            })
+            .register_action(ActionDef {
+                id: DELETE_SELECTED,
+                label: "Delete",
+                menu: "Edit",
+                bindings: vec![KeyBinding::key(KeyCode::Delete)],
+                prompt: None,
+            })
             .add_systems(Startup, setup_outline_material)
             .add_systems(
                 Update,
                 (
-                    select_with_left_click,
+                    // Clicks belong to the running tool
+                    select_with_left_click.run_if(no_tool_active),
+                    run_selection_actions,
                     spawn_outline_for_selected,
                     remove_outline_for_deselected,
                 ),
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use new_core::drawing::{LineKind, ViewFrame};
+
+use crate::analysis::clash::shape::Triangle;
+
+// Vertices closer than 0.1 mm are one vertex, primitive meshes split them at every hard edge
+const WELD: f32 = 1.0e4;
+// Faces meeting at a sharper angle than about 20 degrees leave a visible edge
+const CREASE_COS: f32 = 0.94;
+// Faces closer than about 0.01 degrees to the view direction are seen edge on
+const EDGE_ON: f32 = 1.0e-4;
+
+pub type Segment = (Vec3, Vec3);
+
+// Edges worth drawing for one element, seen along direction, with the kind of line they make.
+// A silhouette where the surface turns away from the viewer, a crease where the faces meet at
+// an angle, and a projection along open borders.
+pub fn feature_edges(triangles: &[Triangle], direction: Vec3) -> Vec<(Segment, LineKind)> {
+    let mut points: Vec<Vec3> = Vec::new();
+    let mut welded: HashMap<[i64; 3], usize> = HashMap::new();
+    let mut weld = |point: Vec3| {
+        let key = (point * WELD).round().as_i64vec3().to_array();
+        *welded.entry(key).or_insert_with(|| {
+            points.push(point);
+            points.len() - 1
+        })
+    };
+
+    let mut edges: HashMap<(usize, usize), Vec<Vec3>> = HashMap::new();
+    for triangle in triangles {
+        let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+        let Some(normal) = normal.try_normalize() else {
+            continue;
+        };
+
+        let corners = triangle.map(&mut weld);
+        for i in 0..3 {
+            let (a, b) = (corners[i], corners[(i + 1) % 3]);
+            if a != b {
+                edges.entry((a.min(b), a.max(b))).or_default().push(normal);
+            }
+        }
+    }
+
+    // Faces seen edge on count as turned away, a box in plan is outlined by its top face
+    let facing = |normal: &Vec3| normal.dot(direction) < -EDGE_ON;
+
+    edges
+        .into_iter()
+        .filter_map(|((a, b), normals)| {
+            let kind = match normals.as_slice() {
+                [n0, n1] if facing(n0) != facing(n1) => LineKind::Silhouette,
+                [n0, n1] if n0.dot(*n1) < CREASE_COS => LineKind::Crease,
+                [_, _] => return None,
+                // Open border or non manifold
+                _ => LineKind::Projection,
+            };
+            Some(((points[a], points[b]), kind))
+        })
+        .collect()
+}
+
+// Keeps the part of the edge beyond the cut plane
+pub fn clip_to_cut(a: Vec3, b: Vec3, frame: &ViewFrame) -> Option<Segment> {
+    let Some(cut) = frame.cut else {
+        return Some((a, b));
+    };
+
+    let (da, db) = (frame.depth(a) - cut, frame.depth(b) - cut);
+    match (da >= 0.0, db >= 0.0) {
+        (true, true) => Some((a, b)),
+        (false, false) => None,
+        (true, false) => Some((a, a.lerp(b, da / (da - db)))),
+        (false, true) => Some((a.lerp(b, da / (da - db)), b)),
+    }
+}
+
+// The part of the triangle beyond the cut plane, as up to two triangles
+pub fn clip_triangle_to_cut(triangle: &Triangle, frame: &ViewFrame) -> Vec<Triangle> {
+    let Some(cut) = frame.cut else {
+        return vec![*triangle];
+    };
+
+    let depths = triangle.map(|point| frame.depth(point) - cut);
+    if depths.iter().all(|&depth| depth >= 0.0) {
+        return vec![*triangle];
+    }
+
+    let mut polygon: Vec<Vec3> = Vec::with_capacity(4);
+    for i in 0..3 {
+        let j = (i + 1) % 3;
+        let (di, dj) = (depths[i], depths[j]);
+        if di >= 0.0 {
+            polygon.push(triangle[i]);
+        }
+        if (di >= 0.0) != (dj >= 0.0) {
+            polygon.push(triangle[i].lerp(triangle[j], di / (di - dj)));
+        }
+    }
+
+    (1..polygon.len().saturating_sub(1))
+        .map(|i| [polygon[0], polygon[i], polygon[i + 1]])
+        .collect()
+}
+
+// Where the triangles cross the cut plane
+pub fn cut_segments(triangles: &[Triangle], frame: &ViewFrame) -> Vec<Segment> {
+    let Some(cut) = frame.cut else {
+        return Vec::new();
+    };
+
+    triangles
+        .iter()
+        .filter_map(|triangle| {
+            let depths = triangle.map(|point| frame.depth(point) - cut);
+
+            let mut crossings = (0..3).filter_map(|i| {
+                let j = (i + 1) % 3;
+                let (di, dj) = (depths[i], depths[j]);
+                ((di < 0.0) != (dj < 0.0)).then(|| triangle[i].lerp(triangle[j], di / (di - dj)))
+            });
+
+            Some((crossings.next()?, crossings.next()?))
+        })
+        .collect()
+}
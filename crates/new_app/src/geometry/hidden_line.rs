@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+
+use new_core::drawing::ViewFrame;
+
+use crate::analysis::clash::shape::Triangle;
+use crate::geometry::edges::{Segment, clip_triangle_to_cut};
+
+// A line has to be this far behind a face to count as hidden, so edges on the face itself stay
+const DEPTH_TOLERANCE: f32 = 1.0e-3;
+// Lines this close to the outline of a face count as inside it, hidden copies of a visible edge go
+const OUTLINE_TOLERANCE: f32 = 1.0e-5;
+// Faces thinner than this on paper are seen edge on and hide nothing
+const MIN_AREA: f32 = 1.0e-10;
+const MAX_CELLS: usize = 256;
+
+// A face in view coordinates with a plane for its depth
+struct Occluder {
+    corners: [Vec2; 3],
+    // Depth at corners[0] and how it changes across the paper
+    depth: f32,
+    gradient: Vec2,
+    min_depth: f32,
+}
+
+impl Occluder {
+    fn new(triangle: &Triangle, frame: &ViewFrame) -> Option<Self> {
+        let corners = triangle.map(|point| frame.project(point));
+        let depths = triangle.map(|point| frame.depth(point));
+
+        let (e1, e2) = (corners[1] - corners[0], corners[2] - corners[0]);
+        let area = e1.perp_dot(e2);
+        if area.abs() < MIN_AREA {
+            return None;
+        }
+
+        // Solve depth(p) = depth + gradient . (p - corners[0]) through all three corners
+        let (d1, d2) = (depths[1] - depths[0], depths[2] - depths[0]);
+        let gradient = Vec2::new(d1 * e2.y - d2 * e1.y, d2 * e1.x - d1 * e2.x) / area;
+
+        Some(Self {
+            corners,
+            depth: depths[0],
+            gradient,
+            min_depth: depths[0].min(depths[1]).min(depths[2]),
+        })
+    }
+
+    fn depth_at(&self, point: Vec2) -> f32 {
+        self.depth + self.gradient.dot(point - self.corners[0])
+    }
+
+    fn bounds(&self) -> Rect {
+        let [a, b, c] = self.corners;
+        Rect::from_corners(a.min(b).min(c), a.max(b).max(c))
+    }
+
+    // Stretch of the segment, as a range of t, that lies inside the face and behind it
+    fn hides(&self, a: Vec2, b: Vec2, depth_a: f32, depth_b: f32) -> Option<(f32, f32)> {
+        let mut range = (0.0_f32, 1.0_f32);
+
+        // Keeps the part of t in 0..1 where start + t * (end - start) >= 0
+        let mut keep = |start: f32, end: f32| {
+            let slope = end - start;
+            if slope.abs() < f32::EPSILON {
+                if start < 0.0 {
+                    range = (1.0, 0.0);
+                }
+                return;
+            }
+            let t = -start / slope;
+            if slope > 0.0 {
+                range.0 = range.0.max(t);
+            } else {
+                range.1 = range.1.min(t);
+            }
+        };
+
+        let winding = (self.corners[1] - self.corners[0])
+            .perp_dot(self.corners[2] - self.corners[0])
+            .signum();
+        for i in 0..3 {
+            let (p, q) = (self.corners[i], self.corners[(i + 1) % 3]);
+            let along = (q - p).try_normalize()?;
+            let side = |point: Vec2| along.perp_dot(point - p) * winding + OUTLINE_TOLERANCE;
+            keep(side(a), side(b));
+        }
+
+        let behind = |point: Vec2, depth: f32| depth - self.depth_at(point) - DEPTH_TOLERANCE;
+        keep(behind(a, depth_a), behind(b, depth_b));
+
+        (range.0 < range.1).then_some(range)
+    }
+}
+
+// Every face that can hide a line in one view, bucketed on a grid over the paper
+pub struct Occluders {
+    faces: Vec<Occluder>,
+    origin: Vec2,
+    cell: Vec2,
+    // Cells along each axis
+    side: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl Occluders {
+    // Faces nearer than the cut plane are cut away and hide nothing
+    pub fn new<'a>(triangles: impl IntoIterator<Item = &'a Triangle>, frame: &ViewFrame) -> Self {
+        let faces: Vec<Occluder> = triangles
+            .into_iter()
+            .flat_map(|triangle| clip_triangle_to_cut(triangle, frame))
+            .filter_map(|triangle| Occluder::new(&triangle, frame))
+            .collect();
+
+        let bounds = faces
+            .iter()
+            .map(Occluder::bounds)
+            .reduce(|a, b| a.union(b))
+            .unwrap_or_default();
+
+        let side = ((faces.len() as f32).sqrt().ceil() as usize).clamp(1, MAX_CELLS);
+        let cell = (bounds.size() / side as f32).max(Vec2::splat(f32::EPSILON));
+
+        let mut occluders = Self {
+            faces: Vec::new(),
+            origin: bounds.min,
+            cell,
+            side,
+            cells: vec![Vec::new(); side * side],
+        };
+        for (index, face) in faces.iter().enumerate() {
+            for cell in occluders.cells_over(face.bounds()) {
+                occluders.cells[cell].push(index);
+            }
+        }
+        occluders.faces = faces;
+        occluders
+    }
+
+    fn cells_over(&self, rect: Rect) -> Vec<usize> {
+        let last = self.side - 1;
+        let to_cell = |point: Vec2| {
+            let cell = ((point - self.origin) / self.cell).floor().max(Vec2::ZERO);
+            ((cell.x as usize).min(last), (cell.y as usize).min(last))
+        };
+        let (x0, y0) = to_cell(rect.min);
+        let (x1, y1) = to_cell(rect.max);
+
+        (y0..=y1)
+            .flat_map(|y| (x0..=x1).map(move |x| y * self.side + x))
+            .collect()
+    }
+
+    // The pieces of a model space segment no face covers
+    pub fn visible_parts(&self, a: Vec3, b: Vec3, frame: &ViewFrame) -> Vec<Segment> {
+        let (pa, pb) = (frame.project(a), frame.project(b));
+        let (da, db) = (frame.depth(a), frame.depth(b));
+        let far = da.max(db);
+
+        let mut candidates: Vec<usize> = self
+            .cells_over(Rect::from_corners(pa, pb))
+            .into_iter()
+            .flat_map(|cell| self.cells[cell].iter().copied())
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut hidden: Vec<(f32, f32)> = candidates
+            .into_iter()
+            .map(|index| &self.faces[index])
+            .filter(|face| face.min_depth < far)
+            .filter_map(|face| face.hides(pa, pb, da, db))
+            .collect();
+        hidden.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+        let mut parts = Vec::new();
+        let mut start = 0.0;
+        for (from, to) in hidden {
+            if from > start {
+                parts.push((a.lerp(b, start), a.lerp(b, from)));
+            }
+            start = f32::max(start, to);
+        }
+        if start < 1.0 {
+            parts.push((a.lerp(b, start), b));
+        }
+        parts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use new_core::drawing::{Compass, ViewKind};
+
+    // Square face 2 by 2 facing the viewer in a south elevation, at the given depth
+    fn square(z: f32) -> Vec<Triangle> {
+        let [a, b, c, d] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(x, y)| Vec3::new(x, y, z));
+        vec![[a, b, c], [a, c, d]]
+    }
+
+    fn lengths(parts: &[Segment]) -> Vec<f32> {
+        parts.iter().map(|(a, b)| (a.distance(*b) * 1e4).round() / 1e4).collect()
+    }
+
+    #[test]
+    fn a_face_hides_what_is_behind_it() {
+        let frame = ViewKind::Elevation { side: Compass::South }.frame();
+        let occluders = Occluders::new(&square(0.0), &frame);
+
+        // Behind, only the ends past the face are seen
+        let parts = occluders.visible_parts(Vec3::new(-2.0, 0.0, -1.0), Vec3::new(2.0, 0.0, -1.0), &frame);
+        assert_eq!(lengths(&parts), [1.0, 1.0]);
+        assert!(parts[0].1.x <= -1.0 + 1e-4 && parts[1].0.x >= 1.0 - 1e-4);
+
+        // In front or on the face itself it is seen whole
+        for z in [1.0, 0.0] {
+            let parts = occluders.visible_parts(Vec3::new(-2.0, 0.0, z), Vec3::new(2.0, 0.0, z), &frame);
+            assert_eq!(lengths(&parts), [4.0]);
+        }
+
+        // Passing through the face, the part behind it goes
+        let parts = occluders.visible_parts(Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.5, -1.0), &frame);
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].1.z > -1e-2);
+    }
+
+    #[test]
+    fn faces_cut_away_hide_nothing() {
+        // A section looking north cut 0.5 in, the face at 0 lies before the cut
+        let frame = ViewKind::Section {
+            facing: Compass::North,
+            offset: 0.5,
+        }
+        .frame();
+        let occluders = Occluders::new(&square(0.0), &frame);
+
+        let parts = occluders.visible_parts(Vec3::new(-2.0, 0.0, -1.0), Vec3::new(2.0, 0.0, -1.0), &frame);
+        assert_eq!(lengths(&parts), [4.0]);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod edges;
+pub mod hidden_line;
+pub mod polyline;
+pub mod projection;
+pub mod sweep;
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use new_core::drawing::{DrawingPolyline, LineStyle};
+
+// Ends closer than 0.01 mm join up
+const JOIN: f32 = 1.0e5;
+
+fn key(point: Vec2) -> [i64; 2] {
+    (point * JOIN).round().as_i64vec2().to_array()
+}
+
+// Joins segments that share an end and a style into polylines
+pub fn chain_segments(segments: &[(Vec2, Vec2, LineStyle)]) -> Vec<DrawingPolyline> {
+    let mut ends: HashMap<([i64; 2], LineStyle), Vec<usize>> = HashMap::new();
+    for (index, (a, b, style)) in segments.iter().enumerate() {
+        ends.entry((key(*a), *style)).or_default().push(index);
+        ends.entry((key(*b), *style)).or_default().push(index);
+    }
+
+    let mut used = vec![false; segments.len()];
+    // Follows unused segments from the point, returns the points passed
+    let walk = |mut point: Vec2, style: LineStyle, used: &mut Vec<bool>| {
+        let mut points = Vec::new();
+        while let Some(&next) = ends
+            .get(&(key(point), style))
+            .and_then(|list| list.iter().find(|&&index| !used[index]))
+        {
+            used[next] = true;
+            let (a, b, _) = segments[next];
+            point = if key(a) == key(point) { b } else { a };
+            points.push(point);
+        }
+        points
+    };
+
+    let mut polylines = Vec::new();
+    for (index, &(a, b, style)) in segments.iter().enumerate() {
+        if used[index] {
+            continue;
+        }
+        used[index] = true;
+
+        let forward = walk(b, style, &mut used);
+        let backward = walk(a, style, &mut used);
+
+        let points: Vec<Vec2> = backward
+            .into_iter()
+            .rev()
+            .chain([a, b])
+            .chain(forward)
+            .collect();
+        polylines.push(DrawingPolyline { points, style });
+    }
+    polylines
+}
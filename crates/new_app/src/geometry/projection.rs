@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+
+use new_core::drawing::{Drawing, LineKind, LineStyle, ViewFrame};
+use new_core::elements::ElementKind;
+use new_core::phase::PhaseStyle;
+
+use crate::analysis::clash::shape::Triangle;
+use crate::geometry::edges::{clip_to_cut, cut_segments, feature_edges};
+use crate::geometry::hidden_line::Occluders;
+use crate::geometry::polyline::chain_segments;
+
+// Edges shorter than this on paper are seen end on
+const MIN_LENGTH: f32 = 1.0e-5;
+
+// One element as the projection sees it, world space triangles and how its phase draws it
+pub struct ProjectedElement<'a> {
+    pub triangles: &'a [Triangle],
+    pub kind: ElementKind,
+    pub style: PhaseStyle,
+}
+
+// Hidden line drawing of the elements for an orthographic view.
+// Every face beyond the cut hides what is behind it, cut lines lie on the cut plane and are
+// always seen. Runs on the CPU only so it works without a GPU.
+pub fn project_view(elements: &[ProjectedElement], frame: &ViewFrame) -> Drawing {
+    let shown: Vec<&ProjectedElement> = elements
+        .iter()
+        .filter(|element| element.style != PhaseStyle::Hidden)
+        .collect();
+
+    let occluders = Occluders::new(shown.iter().flat_map(|element| element.triangles), frame);
+
+    let mut segments: Vec<(Vec2, Vec2, LineStyle)> = Vec::new();
+    for element in shown {
+        let style = |kind: LineKind| LineStyle {
+            kind,
+            element: element.kind,
+            halftone: element.style == PhaseStyle::Halftone,
+            dashed: element.style == PhaseStyle::Demolished,
+        };
+
+        let mut push = |(a, b): (Vec3, Vec3), kind: LineKind| {
+            let (a, b) = (frame.project(a), frame.project(b));
+            if a.distance_squared(b) > MIN_LENGTH * MIN_LENGTH {
+                segments.push((a, b, style(kind)));
+            }
+        };
+
+        for ((a, b), kind) in feature_edges(element.triangles, frame.direction) {
+            let Some((a, b)) = clip_to_cut(a, b, frame) else {
+                continue;
+            };
+            for part in occluders.visible_parts(a, b, frame) {
+                push(part, kind);
+            }
+        }
+
+        for segment in cut_segments(element.triangles, frame) {
+            push(segment, LineKind::Cut);
+        }
+    }
+
+    Drawing {
+        polylines: chain_segments(&segments),
+        annotations: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use new_core::drawing::{Compass, ViewKind};
+
+    use crate::analysis::clash::shape::world_triangles;
+
+    fn close(value: f32, expected: f32) -> bool {
+        (value - expected).abs() < 1e-4
+    }
+
+    fn cuboid(size: Vec3, at: Vec3) -> Vec<Triangle> {
+        world_triangles(&Mesh::from(Cuboid::from_size(size)), &GlobalTransform::from_translation(at))
+            .expect("triangles")
+    }
+
+    fn element(triangles: &[Triangle], kind: ElementKind) -> ProjectedElement<'_> {
+        ProjectedElement {
+            triangles,
+            kind,
+            style: PhaseStyle::Normal,
+        }
+    }
+
+    // Length drawn on paper for the element and line kind
+    fn drawn(drawing: &Drawing, element: ElementKind, kind: LineKind) -> f32 {
+        drawing
+            .polylines
+            .iter()
+            .filter(|polyline| polyline.style.element == element && polyline.style.kind == kind)
+            .flat_map(|polyline| polyline.segments())
+            .map(|(a, b)| a.distance(b))
+            .sum()
+    }
+
+    #[test]
+    fn a_box_face_on_is_drawn_by_its_outline() {
+        // The edges running away from the viewer are seen end on, the back face hides behind the front
+        let block = cuboid(Vec3::new(2.0, 1.0, 3.0), Vec3::ZERO);
+        let frame = ViewKind::Elevation { side: Compass::South }.frame();
+        let drawing = project_view(&[element(&block, ElementKind::Wall)], &frame);
+
+        assert!(close(drawn(&drawing, ElementKind::Wall, LineKind::Silhouette), 6.0));
+        assert!(close(drawn(&drawing, ElementKind::Wall, LineKind::Crease), 0.0));
+        assert_eq!(drawing.polylines.len(), 1);
+    }
+
+    #[test]
+    fn a_box_seen_from_a_corner_shows_its_near_creases_only() {
+        let direction = Vec3::NEG_ONE.normalize();
+        let up = Vec3::Y.reject_from(direction).normalize();
+        let frame = ViewFrame {
+            right: direction.cross(up),
+            up,
+            direction,
+            cut: None,
+        };
+        let block = cuboid(Vec3::ONE, Vec3::ZERO);
+        let drawing = project_view(&[element(&block, ElementKind::Column)], &frame);
+
+        // Every edge of a unit cube is this long on paper seen along its diagonal
+        let edge = (2.0f32 / 3.0).sqrt();
+        // Six edges turn the outline
+        assert!(close(drawn(&drawing, ElementKind::Column, LineKind::Silhouette), 6.0 * edge));
+        // Three creases meet at the near corner. The three at the far corner lie right behind
+        // those and are hidden, bar a sliver within the depth tolerance where they reach the outline.
+        let creases = drawn(&drawing, ElementKind::Column, LineKind::Crease);
+        assert!((creases - 3.0 * edge).abs() < 1e-2);
+    }
+
+    #[test]
+    fn a_box_behind_another_is_hidden_where_they_overlap() {
+        let front = cuboid(Vec3::new(2.0, 2.0, 1.0), Vec3::ZERO);
+        let frame = ViewKind::Elevation { side: Compass::South }.frame();
+
+        // Wholly behind, nothing of it is drawn
+        let behind = cuboid(Vec3::ONE, Vec3::new(0.0, 0.0, -3.0));
+        let drawing = project_view(
+            &[element(&front, ElementKind::Wall), element(&behind, ElementKind::Column)],
+            &frame,
+        );
+        assert!(close(drawn(&drawing, ElementKind::Wall, LineKind::Silhouette), 8.0));
+        assert!(drawing.polylines.iter().all(|polyline| polyline.style.element == ElementKind::Wall));
+
+        // Sticking out 1.5 to the right, its top and bottom show past the front box and its
+        // left side is hidden
+        let beside = cuboid(Vec3::new(2.0, 1.0, 1.0), Vec3::new(1.5, 0.0, -3.0));
+        let drawing = project_view(
+            &[element(&front, ElementKind::Wall), element(&beside, ElementKind::Column)],
+            &frame,
+        );
+        assert!(close(drawn(&drawing, ElementKind::Wall, LineKind::Silhouette), 8.0));
+        assert!(close(drawn(&drawing, ElementKind::Column, LineKind::Silhouette), 1.5 + 1.5 + 1.0));
+        assert!(close(drawn(&drawing, ElementKind::Column, LineKind::Crease), 0.0));
+        let left = drawing
+            .polylines
+            .iter()
+            .filter(|polyline| polyline.style.element == ElementKind::Column)
+            .flat_map(|polyline| polyline.points.clone())
+            .any(|point| point.x < 0.99);
+        assert!(!left);
+    }
+
+    #[test]
+    fn demolished_work_is_dashed_and_hidden_work_is_left_out() {
+        let block = cuboid(Vec3::ONE, Vec3::ZERO);
+        let frame = ViewKind::Elevation { side: Compass::South }.frame();
+        let elements = [
+            ProjectedElement {
+                triangles: &block,
+                kind: ElementKind::Wall,
+                style: PhaseStyle::Demolished,
+            },
+            ProjectedElement {
+                triangles: &block,
+                kind: ElementKind::Column,
+                style: PhaseStyle::Hidden,
+            },
+        ];
+        let drawing = project_view(&elements, &frame);
+
+        assert!(!drawing.polylines.is_empty());
+        assert!(drawing.polylines.iter().all(|polyline| polyline.style.dashed));
+        assert!(drawing.polylines.iter().all(|polyline| polyline.style.element == ElementKind::Wall));
+    }
+}
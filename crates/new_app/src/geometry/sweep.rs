@@ -0,0 +1,125 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::PrimitiveTopology;
+use bevy::prelude::*;
+
+use new_core::profile::ProfileShape;
+
+// Segments closer to vertical than this, as the sine of their tilt, are set out like columns
+const VERTICAL: f32 = 0.999;
+
+// Prisms of profiles swept along polylines, gathered into one mesh
+#[derive(Default)]
+pub struct Sweep {
+    positions: Vec<[f32; 3]>,
+}
+
+impl Sweep {
+    // One prism per segment of the axis. Depth goes up the section where the segment allows
+    // it, along north for vertical ones, and width across it. Lift moves the section up.
+    pub fn add(&mut self, shape: &ProfileShape, axis: &[Vec3], lift: f32) {
+        let outline = shape.outline();
+        let hole = shape.hole();
+        let caps = match &hole {
+            Some(_) => Vec::new(),
+            None => triangulate(&outline),
+        };
+
+        let positions = &mut self.positions;
+        let mut triangle = |a: Vec3, b: Vec3, c: Vec3| positions.extend([a, b, c].map(|p| p.to_array()));
+
+        for pair in axis.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let Some(along) = (end - start).try_normalize() else {
+                continue;
+            };
+            let up = if along.y.abs() > VERTICAL {
+                Vec3::NEG_Z
+            } else {
+                Vec3::Y.reject_from_normalized(along).normalize()
+            };
+            let across = up.cross(along);
+            let place = |point: Vec2, base: Vec3| base + across * point.x + up * (point.y + lift);
+
+            // Sides face out of a counterclockwise outline and into the hole
+            for (ring, inward) in std::iter::once((&outline, false)).chain(hole.iter().map(|hole| (hole, true))) {
+                for (index, &a) in ring.iter().enumerate() {
+                    let b = ring[(index + 1) % ring.len()];
+                    let (a0, b0, a1, b1) = (place(a, start), place(b, start), place(a, end), place(b, end));
+                    if inward {
+                        triangle(b0, a0, a1);
+                        triangle(b0, a1, b1);
+                    } else {
+                        triangle(a0, b0, b1);
+                        triangle(a0, b1, a1);
+                    }
+                }
+            }
+
+            // Ends face back along the axis at the start and forward at the end
+            let mut cap = |a: Vec2, b: Vec2, c: Vec2| {
+                triangle(place(a, end), place(b, end), place(c, end));
+                triangle(place(c, start), place(b, start), place(a, start));
+            };
+            match &hole {
+                Some(hole) => {
+                    for index in 0..outline.len() {
+                        let next = (index + 1) % outline.len();
+                        cap(outline[index], outline[next], hole[next]);
+                        cap(outline[index], hole[next], hole[index]);
+                    }
+                }
+                None => {
+                    for [a, b, c] in &caps {
+                        cap(outline[*a], outline[*b], outline[*c]);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn mesh(self) -> Option<Mesh> {
+        if self.positions.is_empty() {
+            return None;
+        }
+        Some(
+            Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+                .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+                .with_computed_flat_normals(),
+        )
+    }
+}
+
+// Ear clipping of a simple counterclockwise polygon, indices into it
+pub fn triangulate(points: &[Vec2]) -> Vec<[usize; 3]> {
+    let mut left: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::new();
+
+    while left.len() > 3 {
+        let count = left.len();
+        let ear = (0..count).find(|&i| {
+            let (a, b, c) = (left[(i + count - 1) % count], left[i], left[(i + 1) % count]);
+            let (pa, pb, pc) = (points[a], points[b], points[c]);
+            // Convex corner with no other corner inside the triangle it cuts off
+            (pb - pa).perp_dot(pc - pb) > 0.0
+                && left
+                    .iter()
+                    .filter(|&&other| other != a && other != b && other != c)
+                    .all(|&other| !inside(points[other], pa, pb, pc))
+        });
+        // Degenerate outlines are left with what was clipped so far
+        let Some(i) = ear else {
+            return triangles;
+        };
+        triangles.push([left[(i + count - 1) % count], left[i], left[(i + 1) % count]]);
+        left.remove(i);
+    }
+    if let [a, b, c] = left[..] {
+        triangles.push([a, b, c]);
+    }
+    triangles
+}
+
+fn inside(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    let side = |from: Vec2, to: Vec2| (to - from).perp_dot(point - from) >= 0.0;
+    side(a, b) && side(b, c) && side(c, a)
+}
@@ -0,0 +1,103 @@
+// Working grid of one viewport, ported from mn_app's world_grid.wgsl. Draws on the work plane
+// given by origin, u and v instead of the world floor, with major lines every major_every
+// minor ones. Lines packed too close on screen fade out, the app picks the step so the
+// finest lines shown stay apart where the view looks.
+#import bevy_pbr::forward_io::VertexOutput
+#import bevy_pbr::mesh_bindings::mesh
+#import bevy_pbr::mesh_view_bindings as view_bindings
+
+struct GridParams {
+    origin: vec3<f32>,
+    step: f32,
+    u: vec3<f32>,
+    major_every: f32,
+    v: vec3<f32>,
+    fade_distance: f32,
+    line_px: f32,
+    axis_px: f32,
+    _pad: vec2<f32>,
+};
+
+struct MaterialBindings {
+    material: u32,
+};
+
+#ifdef BINDLESS
+@group(#{MATERIAL_BIND_GROUP}) @binding(0)  var<storage> materials: array<MaterialBindings>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(10) var<storage> params_array: binding_array<GridParams>;
+#else
+@group(#{MATERIAL_BIND_GROUP}) @binding(0)  var<uniform> params: GridParams;
+#endif
+
+fn get_params(in: VertexOutput) -> GridParams {
+#ifdef BINDLESS
+    let slot = mesh[in.instance_index].material_and_lightmap_bind_group_slot & 0xffffu;
+    return params_array[materials[slot].material];
+#else
+    return params;
+#endif
+}
+
+// Pixel constant line thickness, 1 on a line of the spacing and 0 between
+fn grid_line_px(coord: f32, spacing: f32, thickness_px: f32) -> f32 {
+    let u = coord / spacing;
+    let f = fract(u);
+    let d = min(f, 1.0 - f);
+    let du = fwidth(u);
+    let t = thickness_px * du;
+    return 1.0 - smoothstep(t, t + du, d);
+}
+
+fn grid_lines(p: vec2<f32>, spacing: f32, thickness_px: f32) -> f32 {
+    return max(grid_line_px(p.x, spacing, thickness_px), grid_line_px(p.y, spacing, thickness_px));
+}
+
+// 1 while lines of the spacing are far apart on screen, 0 once they would run together
+fn density_fade(p: vec2<f32>, spacing: f32) -> f32 {
+    let per_pixel = max(fwidth(p.x), fwidth(p.y)) / spacing;
+    return 1.0 - smoothstep(0.08, 0.25, per_pixel);
+}
+
+fn axis_line(coord: f32, thickness_px: f32) -> f32 {
+    let d = fwidth(coord);
+    return 1.0 - smoothstep(thickness_px * d, thickness_px * d + d, abs(coord));
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let params = get_params(in);
+
+    // Position in the plane, along u and v from its origin
+    let offset = in.world_position.xyz - params.origin;
+    let p = vec2<f32>(dot(offset, params.u), dot(offset, params.v));
+
+    let major_spacing = params.step * params.major_every;
+    let minor = grid_lines(p, params.step, params.line_px) * density_fade(p, params.step);
+    let major = grid_lines(p, major_spacing, params.line_px * 1.5) * density_fade(p, major_spacing);
+
+    // Lines through the origin, red along u and blue along v like the world X and Z axes
+    let u_axis = axis_line(p.y, params.axis_px);
+    let v_axis = axis_line(p.x, params.axis_px);
+
+    // Fade from the camera, so the grid stays visible wherever the view is
+    let camera = view_bindings::view.world_from_view[3].xyz;
+    let r = length(in.world_position.xyz - camera);
+    let fade = 1.0 - smoothstep(params.fade_distance * 0.7, params.fade_distance, r);
+
+    let minor_col = vec3<f32>(0.7, 0.7, 0.7);
+    let major_col = vec3<f32>(0.85, 0.85, 0.85);
+    let u_col = vec3<f32>(0.95, 0.25, 0.25);
+    let v_col = vec3<f32>(0.25, 0.45, 0.95);
+
+    let w_minor = minor * 0.18;
+    let w_major = major * 0.4;
+    let w_u = u_axis * 0.9;
+    let w_v = v_axis * 0.9;
+    let total = w_minor + w_major + w_u + w_v;
+
+    let alpha = clamp(total * fade, 0.0, 1.0);
+    let rgb = (minor_col * w_minor + major_col * w_major + u_col * w_u + v_col * w_v)
+        / clamp(total, 0.0001, 10.0);
+
+    return vec4<f32>(rgb, alpha);
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use new_core::action::{ActionDef, ActionId, ActionInvoked};
+use new_core::grid::ViewGrids;
+use new_core::keymap::KeyBinding;
+use new_core::{ActiveViewport, VisibleViewports};
+
+pub const SHOW_GRID: ActionId = ActionId("view.show_grid");
+pub const SNAP_TO_GRID: ActionId = ActionId("view.snap_to_grid");
+pub const LEVEL_WORK_PLANE: ActionId = ActionId("view.level_work_plane");
+
+// F7 and F9 toggle grid and snap in AutoCAD too
+pub fn grid_actions() -> [ActionDef; 3] {
+    [
+        ActionDef {
+            id: SHOW_GRID,
+            label: "Show Grid",
+            menu: "View",
+            bindings: vec![KeyBinding::key(KeyCode::F7)],
+            prompt: None,
+        },
+        ActionDef {
+            id: SNAP_TO_GRID,
+            label: "Snap to Grid",
+            menu: "View",
+            bindings: vec![KeyBinding::key(KeyCode::F9)],
+            prompt: None,
+        },
+        ActionDef {
+            id: LEVEL_WORK_PLANE,
+            label: "Work Plane on Level",
+            menu: "View",
+            bindings: Vec::new(),
+            prompt: None,
+        },
+    ]
+}
+
+// Grid of the viewport the user is working in
+pub fn run_grid_actions(
+    mut invoked: MessageReader<ActionInvoked>,
+    active: Res<ActiveViewport>,
+    visible_viewports: Res<VisibleViewports>,
+    mut grids: ResMut<ViewGrids>,
+) {
+    for action in invoked.read() {
+        if ![SHOW_GRID, SNAP_TO_GRID, LEVEL_WORK_PLANE].contains(&action.id) {
+            continue;
+        }
+        let Some(pane_id) = active.target(&visible_viewports) else {
+            continue;
+        };
+        let mut grid = grids.get(pane_id);
+
+        match action.id {
+            SHOW_GRID => grid.visible = !grid.visible,
+            SNAP_TO_GRID => grid.snap = !grid.snap,
+            _ => grid.face = None,
+        }
+        grids.views.insert(pane_id, grid);
+    }
+}
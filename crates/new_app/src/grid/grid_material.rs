@@ -0,0 +1,84 @@
+use bevy::{
+    mesh::MeshVertexBufferLayoutRef,
+    pbr::{Material, MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    reflect::TypePath,
+    render::render_resource::{AsBindGroup, RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError},
+    shader::ShaderRef,
+};
+
+use new_core::grid::ViewGrid;
+
+pub const GRID_SHADER: &str = "embedded://new_app/grid/grid.wgsl";
+
+// Lines stay this wide however far away they are, pixels
+const LINE_PX: f32 = 0.5;
+const AXIS_PX: f32 = 1.0;
+
+// Uniforms of the grid shader, ported from mn_app::world_grid::GridParams with the plane and
+// the subdivision of the view added
+#[derive(ShaderType, Clone, Copy, Debug, PartialEq)]
+pub struct GridParams {
+    pub origin: Vec3,
+    // Finest spacing shown, metres
+    pub step: f32,
+    pub u: Vec3,
+    pub major_every: f32,
+    pub v: Vec3,
+    // Lines fade out towards this distance from the camera, metres
+    pub fade_distance: f32,
+    pub line_px: f32,
+    pub axis_px: f32,
+    pub _pad: Vec2,
+}
+
+impl GridParams {
+    pub fn new(grid: &ViewGrid, fade_distance: f32) -> Self {
+        let plane = grid.plane();
+        Self {
+            origin: plane.origin,
+            step: grid.step,
+            u: plane.u,
+            major_every: grid.major_every.max(1) as f32,
+            v: plane.v(),
+            fade_distance,
+            line_px: LINE_PX,
+            axis_px: AXIS_PX,
+            _pad: Vec2::ZERO,
+        }
+    }
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+#[uniform(0, GridParams, binding_array(10))]
+#[bindless(limit(64))]
+pub struct GridMaterial {
+    pub params: GridParams,
+}
+
+impl From<&GridMaterial> for GridParams {
+    fn from(material: &GridMaterial) -> Self {
+        material.params
+    }
+}
+
+impl Material for GridMaterial {
+    fn fragment_shader() -> ShaderRef {
+        GRID_SHADER.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+
+    // Seen from below as well, a work plane on a wall is looked at from either side
+    fn specialize(
+        _pipeline: &MaterialPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = None;
+        Ok(())
+    }
+}
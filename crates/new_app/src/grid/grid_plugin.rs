@@ -0,0 +1,51 @@
+use bevy::asset::embedded_asset;
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+
+use new_core::action::ActionAppExt;
+use new_core::grid::{PICK_WORK_PLANE, ViewGrids};
+use new_core::tool::ToolDef;
+
+use crate::grid::grid_actions::{grid_actions, run_grid_actions};
+use crate::grid::grid_material::GridMaterial;
+use crate::grid::view_grid::{
+    GridMesh, follow_grid_preferences, follow_levels, place_view_grids, spawn_view_grids,
+};
+use crate::grid::work_plane::pick_work_plane;
+use crate::tools::framework::activation::tool_active;
+use crate::tools::framework::tool_plugin::{ToolAppExt, ToolSystems};
+use crate::tools::ghost::preview::GhostSystems;
+
+pub struct GridPlugin;
+
+impl Plugin for GridPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "grid.wgsl");
+
+        app.add_plugins(MaterialPlugin::<GridMaterial>::default())
+            .init_resource::<ViewGrids>()
+            .init_resource::<GridMesh>();
+        for action in grid_actions() {
+            app.register_action(action);
+        }
+        app.register_tool(
+            ToolDef {
+                id: PICK_WORK_PLANE,
+                label: "Work Plane",
+                tooltip: "Click a face to lay the grid of that view on it, or empty space to put it back on its level",
+                group: "View",
+                shortcut: None,
+            },
+            Vec::new(),
+        )
+        .add_systems(
+            Update,
+            (
+                (follow_grid_preferences, run_grid_actions, follow_levels, spawn_view_grids).before(GhostSystems),
+                pick_work_plane.after(ToolSystems).run_if(tool_active(PICK_WORK_PLANE)),
+            ),
+        )
+        // After the cameras moved for this frame, in time to be propagated and culled with them
+        .add_systems(PostUpdate, place_view_grids.before(TransformSystems::Propagate));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod grid_actions;
+pub mod grid_material;
+pub mod grid_plugin;
+pub mod view_grid;
+pub mod work_plane;
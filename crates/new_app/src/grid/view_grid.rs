@@ -0,0 +1,181 @@
+use bevy::camera::visibility::RenderLayers;
+use bevy::light::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use new_core::GameViewportCamera;
+use new_core::element::ElementIndex;
+use new_core::grid::{ViewGrid, ViewGrids, WorkPlane};
+use new_core::preferences::Preferences;
+
+use crate::grid::grid_material::{GridMaterial, GridParams};
+
+// Each viewport sees only its own grid, on a layer of its own above the model and egui layers
+const FIRST_GRID_LAYER: usize = 8;
+// Lifted off the plane towards the camera, so the floor or face it lies on does not fight it
+const PLANE_LIFT: f32 = 0.001;
+// Size of the grid around the camera in far planes. Up to √2 keeps its corners inside the far plane.
+const REACH: f32 = 1.3;
+// Lines fade out at this many times the distance the view looks at
+const FADE_DISTANCES: f32 = 20.0;
+// Closest the view may look at, so the step does not collapse with the camera on the plane
+const MIN_LOOK_DISTANCE: f32 = 0.01;
+
+pub fn grid_layer(pane_id: u32) -> usize {
+    FIRST_GRID_LAYER + pane_id as usize
+}
+
+#[derive(Component)]
+pub struct ViewGridMesh {
+    pub pane_id: u32,
+}
+
+// Unit square every grid scales to its view
+#[derive(Resource)]
+pub struct GridMesh(Handle<Mesh>);
+
+impl FromWorld for GridMesh {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource_mut::<Assets<Mesh>>().add(Plane3d::default()))
+    }
+}
+
+// New views start from the grid preferences
+pub fn follow_grid_preferences(preferences: Res<Preferences>, mut grids: ResMut<ViewGrids>) {
+    if preferences.is_changed() {
+        grids.defaults = ViewGrid::from_prefs(&preferences.grid);
+    }
+}
+
+// Grids on a level go up and down with it
+pub fn follow_levels(mut grids: ResMut<ViewGrids>, index: Res<ElementIndex>, levels: Query<&GlobalTransform>) {
+    for grid in grids.views.values_mut() {
+        let Some(level) = grid.level_id.and_then(|id| index.entity(id)) else {
+            continue;
+        };
+        if let Ok(transform) = levels.get(level) {
+            grid.elevation = transform.translation().y;
+        }
+    }
+}
+
+// A grid for every viewport camera, hidden until it is placed. The view keeps the grid
+// preferences it was first shown with.
+pub fn spawn_view_grids(
+    mut commands: Commands,
+    mut grids: ResMut<ViewGrids>,
+    mesh: Res<GridMesh>,
+    mut materials: ResMut<Assets<GridMaterial>>,
+    cameras: Query<&GameViewportCamera>,
+    shown: Query<&ViewGridMesh>,
+) {
+    let shown: HashSet<u32> = shown.iter().map(|grid| grid.pane_id).collect();
+
+    for &GameViewportCamera { pane_id } in &cameras {
+        if shown.contains(&pane_id) {
+            continue;
+        }
+        let defaults = grids.defaults;
+        let grid = *grids.views.entry(pane_id).or_insert(defaults);
+
+        commands.spawn((
+            Name::new(format!("ViewGrid({pane_id})")),
+            ViewGridMesh { pane_id },
+            Mesh3d(mesh.0.clone()),
+            MeshMaterial3d(materials.add(GridMaterial {
+                params: GridParams::new(&grid, 0.0),
+            })),
+            Transform::default(),
+            Visibility::Hidden,
+            RenderLayers::layer(grid_layer(pane_id)),
+            NotShadowCaster,
+            NotShadowReceiver,
+        ));
+    }
+}
+
+// Viewport cameras are never parented, their transform is where they are
+type ViewCamera<'a> = (&'a GameViewportCamera, &'a Camera, &'a Transform, &'a Projection);
+type GridEntity<'a> = (
+    &'a ViewGridMesh,
+    &'a mut Transform,
+    &'a mut Visibility,
+    &'a MeshMaterial3d<GridMaterial>,
+);
+
+// Lays each grid on its work plane under its camera, subdivided for how far the view looks
+pub fn place_view_grids(
+    mut grids: ResMut<ViewGrids>,
+    mut materials: ResMut<Assets<GridMaterial>>,
+    cameras: Query<ViewCamera, Without<ViewGridMesh>>,
+    mut shown: Query<GridEntity>,
+) {
+    for (view, mut transform, mut visibility, material) in &mut shown {
+        let camera = cameras
+            .iter()
+            .find(|(tag, camera, ..)| tag.pane_id == view.pane_id && camera.is_active);
+        let (Some((_, camera, camera_transform, projection)), Some(grid)) =
+            (camera, grids.views.get_mut(&view.pane_id))
+        else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        if !grid.visible {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+
+        let plane = grid.plane();
+        let far = projection_far(projection);
+        let distance = look_distance(&plane, camera_transform, far);
+        if let Some(metres_per_pixel) = metres_per_pixel(camera, projection, distance) {
+            let step = grid.step_at(metres_per_pixel);
+            if grid.step != step {
+                grid.step = step;
+            }
+        }
+
+        let params = GridParams::new(grid, (distance * FADE_DISTANCES).min(far));
+        if materials.get(&material.0).is_some_and(|current| current.params != params)
+            && let Some(current) = materials.get_mut(&material.0)
+        {
+            current.params = params;
+        }
+
+        let eye = camera_transform.translation;
+        let height = plane.height(eye);
+        transform.translation = eye - plane.normal * height + plane.normal * PLANE_LIFT.copysign(height);
+        transform.rotation = plane.rotation();
+        transform.scale = Vec3::splat((far * REACH).max(10.0));
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+// Where the middle of the view meets the plane, or straight down onto it when the view looks
+// past the plane
+fn look_distance(plane: &WorkPlane, camera_transform: &Transform, far: f32) -> f32 {
+    let eye = camera_transform.translation;
+    let height = plane.height(eye).abs();
+    let ahead = plane
+        .intersect(Ray3d::new(eye, camera_transform.forward()))
+        .map(|point| point.distance(eye));
+
+    ahead.unwrap_or(height).min(far).max(MIN_LOOK_DISTANCE)
+}
+
+fn metres_per_pixel(camera: &Camera, projection: &Projection, distance: f32) -> Option<f32> {
+    let pixels = camera.logical_viewport_size()?.y;
+    match projection {
+        Projection::Perspective(perspective) => Some(2.0 * distance * (perspective.fov / 2.0).tan() / pixels),
+        Projection::Orthographic(orthographic) => Some(orthographic.area.height() / pixels),
+        _ => None,
+    }
+}
+
+fn projection_far(projection: &Projection) -> f32 {
+    match projection {
+        Projection::Perspective(perspective) => perspective.far,
+        Projection::Orthographic(orthographic) => orthographic.far,
+        _ => 10_000.0,
+    }
+}
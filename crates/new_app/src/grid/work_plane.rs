@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+
+use new_core::grid::{PICK_WORK_PLANE, ViewGrids, WorkPlane};
+use new_core::tool::{ToolCommand, ToolEvent};
+
+use crate::editor::anchoring::anchor_cast::{AnchorCast, ViewportRay};
+
+// A click on a face puts the grid of that view on it, a click on nothing back on its level.
+// One pick and the tool is done.
+pub fn pick_work_plane(
+    mut events: MessageReader<ToolEvent>,
+    viewport: ViewportRay,
+    // Casting reads the grids to fall back on, picking writes them
+    mut params: ParamSet<(AnchorCast, ResMut<ViewGrids>)>,
+    mut tool_commands: MessageWriter<ToolCommand>,
+) {
+    for event in events.read() {
+        let ToolEvent::Picked { tool, pane_id, cursor } = *event else {
+            continue;
+        };
+        if tool != PICK_WORK_PLANE {
+            continue;
+        }
+        let Some(ray) = viewport.ray(pane_id, cursor) else {
+            continue;
+        };
+
+        let face = params
+            .p0()
+            .face(ray)
+            .and_then(|(point, normal)| WorkPlane::from_face(point, normal));
+        let mut grids = params.p1();
+        let mut grid = grids.get(pane_id);
+        grid.face = face;
+        grids.views.insert(pane_id, grid);
+        tool_commands.write(ToolCommand::Exit);
+    }
+}
@@ -0,0 +1,151 @@
+use bevy::asset::uuid::Uuid;
+use bevy::mesh::Indices;
+use bevy::prelude::*;
+
+use new_core::asset_library::Thumbnail;
+use new_core::drawing::ViewFrame;
+use new_core::element::{ElementHeader, ElementId, ElementParams};
+use new_core::elements::ElementKind;
+use new_core::phase::PhaseStyle;
+
+use crate::analysis::clash::shape::world_triangles;
+use crate::geometry::projection::{ProjectedElement, project_view};
+use crate::models::gltf_read::ImportedScene;
+use crate::models::gltf_write::{ExportedElement, ExportedPart, model_glb};
+
+// The geometry drawn with one material
+pub struct AssetPart {
+    pub mesh: Mesh,
+    pub material: StandardMaterial,
+}
+
+// Every instance of an asset is a single element, drawn by a part per material
+pub struct AssetGeometry {
+    pub parts: Vec<AssetPart>,
+    // From the extras of the first node that has them
+    pub kind: Option<ElementKind>,
+    pub params: ElementParams,
+}
+
+impl AssetGeometry {
+    // All parts in one mesh, what the element is measured, picked and drawn on sheets by
+    pub fn merged(&self) -> Option<Mesh> {
+        merge_meshes(self.parts.iter().map(|part| part.mesh.clone()).collect())
+    }
+}
+
+// Bakes every node transform in and merges the primitives that share a material.
+// Textures are dropped.
+pub fn merge_scene(scene: ImportedScene) -> Option<AssetGeometry> {
+    let (kind, params) = scene
+        .meshes
+        .iter()
+        .find(|imported| imported.kind.is_some())
+        .map_or((None, ElementParams::new()), |imported| (imported.kind, imported.params.clone()));
+
+    // In the order the materials are first used, the glTF default material is None
+    let mut groups: Vec<(Option<usize>, Vec<Mesh>)> = Vec::new();
+    for imported in scene.meshes {
+        let mesh = imported.mesh.transformed_by(imported.transform);
+        match groups.iter_mut().find(|(material, _)| *material == imported.material) {
+            Some((_, meshes)) => meshes.push(mesh),
+            None => groups.push((imported.material, vec![mesh])),
+        }
+    }
+
+    let parts = groups
+        .into_iter()
+        .map(|(material, meshes)| {
+            Some(AssetPart {
+                mesh: merge_meshes(meshes)?,
+                material: material
+                    .and_then(|index| scene.materials.get(index))
+                    .map(|imported| imported.material.clone())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if parts.is_empty() {
+        return None;
+    }
+
+    Some(AssetGeometry { parts, kind, params })
+}
+
+fn merge_meshes(meshes: Vec<Mesh>) -> Option<Mesh> {
+    // Merging needs the same attributes on every mesh
+    let with_uvs = meshes.iter().all(|mesh| mesh.contains_attribute(Mesh::ATTRIBUTE_UV_0));
+
+    let mut merged: Option<Mesh> = None;
+    for mut mesh in meshes {
+        if !with_uvs {
+            mesh.remove_attribute(Mesh::ATTRIBUTE_UV_0);
+        }
+        if mesh.indices().is_none() {
+            mesh.insert_indices(Indices::U32((0..mesh.count_vertices() as u32).collect()));
+        }
+
+        match &mut merged {
+            Some(merged) => merged.merge(&mesh).ok()?,
+            None => merged = Some(mesh),
+        }
+    }
+    merged
+}
+
+// Seen from above the front right corner like the default 3d view
+pub fn thumbnail(mesh: &Mesh) -> Thumbnail {
+    let Some(triangles) = world_triangles(mesh, &GlobalTransform::IDENTITY) else {
+        return Thumbnail::default();
+    };
+
+    let direction = Vec3::new(-1.0, -1.0, -1.0).normalize();
+    let right = direction.cross(Vec3::Y).normalize();
+    let frame = ViewFrame {
+        right,
+        up: right.cross(direction),
+        direction,
+        cut: None,
+    };
+    let element = ProjectedElement {
+        triangles: &triangles,
+        kind: ElementKind::BuildingElementProxy,
+        style: PhaseStyle::Normal,
+    };
+    Thumbnail::fit(&project_view(&[element], &frame))
+}
+
+// Stored geometry as one node with a primitive per part, the kind and default parameters go
+// along in the node extras
+pub fn asset_glb(name: &str, kind: ElementKind, geometry: &AssetGeometry) -> Result<Vec<u8>, gltf::Error> {
+    let header = ElementHeader {
+        id: ElementId(0),
+        name: Some(name.to_owned()),
+        kind,
+        kind_type: None,
+        spec_id: None,
+        level_id: None,
+        params: geometry.params.clone(),
+    };
+    let parts: Vec<ExportedPart> = geometry
+        .parts
+        .iter()
+        .enumerate()
+        .map(|(index, part)| ExportedPart {
+            mesh: &part.mesh,
+            // Materials are shared by id, every part keeps its own
+            material: Some((
+                AssetId::Uuid {
+                    uuid: Uuid::from_u128(index as u128),
+                },
+                &part.material,
+            )),
+            texture: None,
+        })
+        .collect();
+    model_glb(&[ExportedElement {
+        header: &header,
+        parts,
+        transform: GlobalTransform::IDENTITY,
+    }])
+}
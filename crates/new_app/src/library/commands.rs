@@ -0,0 +1,326 @@
+use bevy::camera::visibility::RenderLayers;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+use new_core::anchor::PlacementSettings;
+use new_core::asset_library::{
+    ASSET_PAGE, AssetBrowser, AssetCommand, AssetInstance, AssetResults, InstancePart, LibraryAssetId,
+};
+use new_core::element::{ElementHeader, ElementIdAllocator, ElementParams};
+use new_core::elements::ElementKind;
+use new_core::model_exchange::ImportAs;
+use new_core::phase::ElementPhasing;
+use new_db::library::{LibraryDb, NewAsset, asset_categories, asset_definition, delete_asset, insert_asset, search_assets};
+
+use crate::editor::anchoring::anchor_cast::{AnchorCast, ViewportRay};
+use crate::editor::selection::picking::{Selectable, Selected};
+use crate::library::asset_geometry::{AssetGeometry, AssetPart, asset_glb, merge_scene, thumbnail};
+use crate::models::gltf_read::{read_gltf, read_gltf_slice};
+
+// Assets placed this session, instances share the meshes and materials
+#[derive(Resource, Default)]
+pub struct LoadedAssets {
+    assets: HashMap<LibraryAssetId, LoadedAsset>,
+}
+
+#[derive(Clone)]
+struct LoadedAsset {
+    name: String,
+    kind: ElementKind,
+    params: ElementParams,
+    // Whole asset, the same as the only part when there is one
+    mesh: Handle<Mesh>,
+    parts: Vec<(Handle<Mesh>, Handle<StandardMaterial>)>,
+}
+
+type SelectedElement<'a> = (
+    &'a ElementHeader,
+    &'a Mesh3d,
+    Option<&'a MeshMaterial3d<StandardMaterial>>,
+    Option<&'a Children>,
+    &'a GlobalTransform,
+);
+
+// What a selected element is drawn with, to add it to the library
+#[derive(SystemParam)]
+pub struct SelectedGeometry<'w, 's> {
+    meshes: Res<'w, Assets<Mesh>>,
+    materials: Res<'w, Assets<StandardMaterial>>,
+    parts: Query<'w, 's, (&'static Mesh3d, &'static MeshMaterial3d<StandardMaterial>), With<InstancePart>>,
+}
+
+impl SelectedGeometry<'_, '_> {
+    // The instance parts of an asset with several materials, otherwise the element itself
+    fn parts(
+        &self,
+        mesh: &Mesh3d,
+        material: Option<&MeshMaterial3d<StandardMaterial>>,
+        children: Option<&Children>,
+    ) -> Vec<AssetPart> {
+        let part = |mesh: &Mesh3d, material: Option<&MeshMaterial3d<StandardMaterial>>| {
+            Some(AssetPart {
+                mesh: self.meshes.get(&mesh.0)?.clone(),
+                material: material
+                    .and_then(|material| self.materials.get(&material.0))
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+        };
+
+        let parts: Vec<AssetPart> = children
+            .into_iter()
+            .flatten()
+            .filter_map(|child| self.parts.get(*child).ok())
+            .filter_map(|(mesh, material)| part(mesh, Some(material)))
+            .collect();
+        if parts.is_empty() {
+            part(mesh, material).into_iter().collect()
+        } else {
+            parts
+        }
+    }
+}
+
+// Everything that writes to the library. Results are searched again afterwards because the
+// status changes the browser.
+pub fn run_library_commands(
+    mut asset_commands: MessageReader<AssetCommand>,
+    mut browser: ResMut<AssetBrowser>,
+    mut loaded: ResMut<LoadedAssets>,
+    db: NonSend<LibraryDb>,
+    geometry: SelectedGeometry,
+    selected: Query<SelectedElement, With<Selected>>,
+) {
+    for command in asset_commands.read() {
+        let status = match command {
+            AssetCommand::AddSelection { category } => {
+                let mut added = 0;
+                for (header, mesh, material, children, transform) in &selected {
+                    let parts = geometry.parts(mesh, material, children);
+                    if parts.is_empty() {
+                        continue;
+                    }
+                    // Keep how it is turned and scaled, the asset origin is the element origin
+                    let (scale, rotation, _) = transform.to_scale_rotation_translation();
+                    let turned = Transform {
+                        rotation,
+                        scale,
+                        ..default()
+                    };
+                    let geometry = AssetGeometry {
+                        parts: parts
+                            .into_iter()
+                            .map(|part| AssetPart {
+                                mesh: part.mesh.transformed_by(turned),
+                                ..part
+                            })
+                            .collect(),
+                        kind: Some(header.kind),
+                        params: header.params.clone(),
+                    };
+                    let name = header.name.clone().unwrap_or_else(|| header.kind.to_string());
+
+                    match add_asset(&db, &name, category, header.kind, &geometry) {
+                        Ok(_) => added += 1,
+                        Err(error) => warn!("Adding {name} to the library failed: {error}"),
+                    }
+                }
+                match added {
+                    0 => "Select elements with a mesh to add them".to_owned(),
+                    _ => format!("Added {added} assets to {category}"),
+                }
+            }
+            AssetCommand::ImportGltf {
+                path,
+                category,
+                import_as,
+            } => match import_asset(&db, path, category, *import_as) {
+                Ok(name) => format!("Added {name} to {category}"),
+                Err(error) => format!("Importing {} failed: {error}", path.display()),
+            },
+            AssetCommand::Delete(id) => {
+                loaded.assets.remove(id);
+                match delete_asset(&db, *id) {
+                    Ok(()) => "Deleted the asset".to_owned(),
+                    Err(error) => format!("Deleting the asset failed: {error}"),
+                }
+            }
+            AssetCommand::Place { .. } => continue,
+        };
+
+        info!("{status}");
+        browser.status = Some(status);
+    }
+}
+
+fn add_asset(
+    db: &LibraryDb,
+    name: &str,
+    category: &str,
+    kind: ElementKind,
+    geometry: &AssetGeometry,
+) -> Result<LibraryAssetId, String> {
+    let glb = asset_glb(name, kind, geometry).map_err(|error| error.to_string())?;
+    let merged = geometry.merged().ok_or("the parts could not be merged")?;
+    insert_asset(
+        db,
+        &NewAsset {
+            name,
+            category,
+            kind,
+            thumbnail: &thumbnail(&merged),
+            geometry: &glb,
+        },
+    )
+    .map_err(|error| error.to_string())
+}
+
+// The whole scene as one asset named after the file, returns the name
+fn import_asset(db: &LibraryDb, path: &Path, category: &str, import_as: ImportAs) -> Result<String, String> {
+    let scene = read_gltf(path).map_err(|error| error.to_string())?;
+    let geometry = merge_scene(scene).ok_or("the meshes could not be merged")?;
+    let name = path
+        .file_stem()
+        .map_or("Asset".to_owned(), |stem| stem.to_string_lossy().into_owned());
+
+    add_asset(db, &name, category, geometry.kind.unwrap_or(import_as.kind()), &geometry)?;
+    Ok(name)
+}
+
+pub fn search_library(
+    browser: Res<AssetBrowser>,
+    mut results: ResMut<AssetResults>,
+    db: NonSend<LibraryDb>,
+) {
+    if !browser.is_changed() {
+        return;
+    }
+
+    let category = browser.category.as_deref();
+    let found = asset_categories(&db)
+        .and_then(|categories| Ok((categories, search_assets(&db, &browser.search, category, ASSET_PAGE)?)));
+    match found {
+        Ok((categories, (assets, total))) => {
+            *results = AssetResults {
+                categories,
+                assets,
+                total,
+            };
+        }
+        Err(error) => warn!("Searching the asset library failed: {error}"),
+    }
+}
+
+#[derive(SystemParam)]
+pub struct AssetStore<'w> {
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    loaded: ResMut<'w, LoadedAssets>,
+}
+
+impl AssetStore<'_> {
+    // Reads the geometry on first use
+    fn load(&mut self, db: &LibraryDb, id: LibraryAssetId) -> Result<&LoadedAsset, String> {
+        if !self.loaded.assets.contains_key(&id) {
+            let definition = asset_definition(db, id)
+                .map_err(|error| error.to_string())?
+                .ok_or("the asset is no longer in the library")?;
+            let scene = read_gltf_slice(&definition.geometry, Path::new(".")).map_err(|error| error.to_string())?;
+            let geometry = merge_scene(scene).ok_or("the asset geometry could not be read")?;
+            let merged = geometry.merged().ok_or("the asset geometry could not be read")?;
+
+            let parts: Vec<_> = geometry
+                .parts
+                .into_iter()
+                .map(|part| (self.meshes.add(part.mesh), self.materials.add(part.material)))
+                .collect();
+            let mesh = match parts.as_slice() {
+                [(mesh, _)] => mesh.clone(),
+                _ => self.meshes.add(merged),
+            };
+            let asset = LoadedAsset {
+                name: definition.name,
+                kind: definition.kind,
+                params: geometry.params,
+                mesh,
+                parts,
+            };
+            self.loaded.assets.insert(id, asset);
+        }
+        Ok(&self.loaded.assets[&id])
+    }
+}
+
+// Instances are anchored with the strategy chosen for placing
+pub fn place_assets(
+    mut asset_commands: MessageReader<AssetCommand>,
+    mut commands: Commands,
+    mut ids: ResMut<ElementIdAllocator>,
+    // Loading adds meshes, casting reads them
+    mut params: ParamSet<(AssetStore, AnchorCast)>,
+    viewport: ViewportRay,
+    settings: Res<PlacementSettings>,
+    db: NonSend<LibraryDb>,
+) {
+    for command in asset_commands.read() {
+        let AssetCommand::Place { asset, pane_id, cursor } = command else {
+            continue;
+        };
+        let Some(ray) = viewport.ray(*pane_id, *cursor) else {
+            continue;
+        };
+
+        let loaded = match params.p0().load(&db, *asset) {
+            Ok(loaded) => loaded.clone(),
+            Err(error) => {
+                warn!("Placing asset {} failed: {error}", asset.0);
+                continue;
+            }
+        };
+        let Some(anchored) = params.p1().anchor_in_view(*pane_id, settings.strategy, ray) else {
+            warn!("Nothing under the cursor to anchor to with {}", settings.strategy);
+            continue;
+        };
+
+        let mut instance = commands.spawn((
+            ElementHeader {
+                id: ids.allocate(),
+                name: Some(loaded.name),
+                kind: loaded.kind,
+                kind_type: None,
+                spec_id: None,
+                level_id: None,
+                params: loaded.params,
+            },
+            AssetInstance(*asset),
+            Mesh3d(loaded.mesh),
+            anchored.transform,
+            anchored.anchor,
+            RenderLayers::layer(0),
+            Selectable,
+            ElementPhasing::default(),
+        ));
+
+        // A single material goes on the element, several are drawn by the parts
+        match loaded.parts.as_slice() {
+            [(_, material)] => {
+                instance.insert(MeshMaterial3d(material.clone()));
+            }
+            parts => {
+                instance.with_children(|instance| {
+                    for (mesh, material) in parts {
+                        instance.spawn((
+                            Name::new("InstancePart"),
+                            Mesh3d(mesh.clone()),
+                            MeshMaterial3d(material.clone()),
+                            RenderLayers::layer(0),
+                            InstancePart,
+                        ));
+                    }
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+use new_core::asset_library::{AssetBrowser, AssetCommand, AssetResults};
+
+use crate::library::commands::{LoadedAssets, place_assets, run_library_commands, search_library};
+
+pub struct LibraryPlugin;
+
+impl Plugin for LibraryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetBrowser>()
+            .init_resource::<AssetResults>()
+            .init_resource::<LoadedAssets>()
+            .add_message::<AssetCommand>()
+            .add_systems(Update, (run_library_commands, search_library, place_assets).chain());
+    }
+}
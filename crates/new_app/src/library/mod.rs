@@ -0,0 +1,3 @@
+pub mod asset_geometry;
+pub mod commands;
+pub mod library_plugin;
@@ -1,22 +1,80 @@
 use bevy::prelude::*;
 use bevy::winit::WinitSettings;
 use bevy_egui::EguiPlugin;
+pub mod actions;
+pub mod analysis;
+pub mod cad;
 pub mod camera;
 pub mod editor;
+pub mod geometry;
+pub mod grid;
+pub mod library;
+pub mod mep;
+pub mod models;
+pub mod preferences;
+pub mod rooms;
+pub mod schedules;
+pub mod sequence;
+pub mod sheets;
+pub mod structure;
 pub mod tools;
 
+use crate::actions::action_plugin;
+use crate::analysis::clash::clash_plugin;
+use crate::analysis::cost::cost_plugin;
+use crate::cad::cad_plugin;
+use crate::editor::anchoring::anchoring_plugin;
+use crate::editor::display::display_plugin;
+use crate::editor::elements::elements_plugin;
+use crate::editor::phasing::phasing_plugin;
 use crate::editor::selection::selection_plugin;
+use crate::grid::grid_plugin;
+use crate::library::library_plugin;
+use crate::mep::mep_plugin;
+use crate::models::model_plugin;
+use crate::preferences::preferences_plugin;
+use crate::rooms::room_plugin;
+use crate::schedules::schedule_plugin;
+use crate::sequence::sequence_plugin;
+use crate::sheets::{headless, sheet_plugin};
+use crate::structure::structure_plugin;
 use crate::tools::debug::debug_plugin;
+use crate::tools::framework::tool_plugin;
+use crate::tools::ghost::ghost_plugin;
+
+fn main() -> AppExit {
+    if let Some(args) = headless::publish_args() {
+        return headless::run(args);
+    }
 
-fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .insert_resource(WinitSettings::game()) // ← continuous rendering; no stale frames
         .add_plugins(EguiPlugin::default())
         .add_plugins(new_db::DbPlugin)
+        .add_plugins(preferences_plugin::PreferencesPlugin)
+        .add_plugins(action_plugin::ActionPlugin)
         .add_plugins(camera::camera_plugin::AppCameraPlugin)
         .add_plugins(new_ui::UIPlugin)
+        .add_plugins(elements_plugin::ElementsPlugin)
+        .add_plugins(display_plugin::DisplayPlugin)
+        .add_plugins(phasing_plugin::PhasingPlugin)
         .add_plugins(selection_plugin::SelectionPlugin)
+        .add_plugins(anchoring_plugin::AnchoringPlugin)
+        .add_plugins(tool_plugin::ToolPlugin)
+        .add_plugins(ghost_plugin::GhostPlugin)
+        .add_plugins(grid_plugin::GridPlugin)
+        .add_plugins(structure_plugin::StructurePlugin)
+        .add_plugins(mep_plugin::MepPlugin)
+        .add_plugins(room_plugin::RoomPlugin)
         .add_plugins(debug_plugin::DebugPlugin)
-        .run();
+        .add_plugins(clash_plugin::ClashPlugin)
+        .add_plugins(cost_plugin::CostPlugin)
+        .add_plugins(sequence_plugin::SequencePlugin)
+        .add_plugins(schedule_plugin::SchedulePlugin)
+        .add_plugins(sheet_plugin::SheetPlugin)
+        .add_plugins(cad_plugin::CadPlugin)
+        .add_plugins(model_plugin::ModelPlugin)
+        .add_plugins(library_plugin::LibraryPlugin)
+        .run()
 }
\ No newline at end of file
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use new_core::element::{ElementHeader, ElementIndex};
+use new_core::mep::{MepFitting, MepSegment, Ports};
+use new_core::mep_system::{MepAnalysis, SystemCommand, SystemNode};
+
+use crate::editor::selection::picking::{SelectionState, set_selection};
+
+type SystemElement<'a> = (&'a ElementHeader, &'a Ports, Option<&'a MepSegment>, Option<&'a MepFitting>);
+type ReshapedRun = Or<(Changed<Ports>, Changed<MepSegment>)>;
+
+// Works the systems out again whenever a run, a connection or a design flow changes
+pub fn analyse_systems(
+    changed: Query<(), ReshapedRun>,
+    mut removed: RemovedComponents<Ports>,
+    elements: Query<SystemElement>,
+    mut analysis: ResMut<MepAnalysis>,
+) {
+    let removed = removed.read().count() > 0;
+    if changed.is_empty() && !removed {
+        return;
+    }
+
+    let nodes: Vec<SystemNode> = elements
+        .iter()
+        .map(|(header, ports, segment, fitting)| SystemNode {
+            id: header.id,
+            domain: ports.domain,
+            system: ports.system,
+            ports: ports.ports.clone(),
+            length: segment.map(|segment| segment.end.length()),
+            fitting: fitting.map(|fitting| fitting.kind),
+        })
+        .collect();
+    *analysis = MepAnalysis::analyse(&nodes);
+}
+
+pub fn apply_system_commands(
+    mut system_commands: MessageReader<SystemCommand>,
+    index: Res<ElementIndex>,
+    mut selection: ResMut<SelectionState>,
+    exists: Query<(), ()>,
+    mut commands: Commands,
+) {
+    for command in system_commands.read() {
+        let SystemCommand::Select(id) = *command;
+        if let Some(entity) = index.entity(id) {
+            set_selection(&mut selection, Some(entity), &exists, &mut commands);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use new_core::mep::{FittingKind, MepFitting, MepSegment, Ports, SectionSize};
+use new_core::profile::ProfileShape;
+
+use crate::geometry::sweep::Sweep;
+
+type ReshapedFitting = Or<(Changed<MepFitting>, Changed<Ports>)>;
+
+// Meshes segments and fittings that were routed or changed, fittings again when their ports move
+pub fn sweep_mep(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    segments: Query<(Entity, &MepSegment), Changed<MepSegment>>,
+    fittings: Query<(Entity, &MepFitting, &Ports), ReshapedFitting>,
+) {
+    let meshed = segments
+        .iter()
+        .map(|(entity, segment)| (entity, segment_mesh(segment)))
+        .chain(fittings.iter().map(|(entity, fitting, ports)| (entity, fitting_mesh(fitting, ports))));
+    for (entity, mesh) in meshed {
+        if let Some(mesh) = mesh {
+            commands.entity(entity).insert(Mesh3d(meshes.add(mesh)));
+        }
+    }
+}
+
+// Outside of the section, walls are not modelled
+fn section(size: &SectionSize) -> ProfileShape {
+    match *size {
+        SectionSize::Round { diameter } => ProfileShape::Circle { diameter },
+        SectionSize::Rectangular { width, height } => ProfileShape::Rectangle { depth: height, width },
+    }
+}
+
+pub fn segment_mesh(segment: &MepSegment) -> Option<Mesh> {
+    let mut sweep = Sweep::default();
+    sweep.add(&section(&segment.size), &[Vec3::ZERO, segment.end], 0.0);
+    sweep.mesh()
+}
+
+// A stub from the origin out to each port in that port's size
+pub fn fitting_mesh(fitting: &MepFitting, ports: &Ports) -> Option<Mesh> {
+    let mut sweep = Sweep::default();
+    for port in &ports.ports {
+        // Elbows and tees reach back past the origin so the stubs close the outside corner
+        let back = match fitting.kind {
+            FittingKind::Transition => 0.0,
+            FittingKind::Elbow | FittingKind::Tee => port.size.extent() / 2.0,
+        };
+        sweep.add(&section(&port.size), &[-port.direction * back, port.position], 0.0);
+    }
+    sweep.mesh()
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use new_core::mep::{CABLE_TRAY_TOOL, DUCT_TOOL, MepDomain, PIPE_TOOL};
+use new_core::mep_system::{MepAnalysis, SystemCommand};
+use new_core::tool::ToolDef;
+
+use crate::mep::mep_analysis::{analyse_systems, apply_system_commands};
+use crate::mep::mep_mesh::sweep_mep;
+use crate::mep::route_tool::{MepMaterials, route_options, route_runs, routing_tool_active};
+use crate::tools::framework::tool_plugin::{ToolAppExt, ToolSystems};
+
+pub struct MepPlugin;
+
+impl Plugin for MepPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_tool(
+            ToolDef {
+                id: DUCT_TOOL,
+                label: "Duct",
+                tooltip: "Click along a duct run and press Enter, fittings go in at the bends and joins",
+                group: "MEP",
+                shortcut: None,
+            },
+            route_options(MepDomain::Duct),
+        )
+        .register_tool(
+            ToolDef {
+                id: PIPE_TOOL,
+                label: "Pipe",
+                tooltip: "Click along a pipe run and press Enter, fittings go in at the bends and joins",
+                group: "MEP",
+                shortcut: None,
+            },
+            route_options(MepDomain::Pipe),
+        )
+        .register_tool(
+            ToolDef {
+                id: CABLE_TRAY_TOOL,
+                label: "Cable Tray",
+                tooltip: "Click along a cable tray run and press Enter, fittings go in at the bends and joins",
+                group: "MEP",
+                shortcut: None,
+            },
+            route_options(MepDomain::CableCarrier),
+        )
+        .init_resource::<MepMaterials>()
+        .init_resource::<MepAnalysis>()
+        .add_message::<SystemCommand>()
+        .add_systems(
+            Update,
+            (
+                route_runs.run_if(routing_tool_active).after(ToolSystems),
+                (sweep_mep, analyse_systems),
+                apply_system_commands,
+            )
+                .chain(),
+        );
+    }
+}
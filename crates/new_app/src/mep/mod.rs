@@ -0,0 +1,4 @@
+pub mod mep_analysis;
+pub mod mep_mesh;
+pub mod mep_plugin;
+pub mod route_tool;
@@ -0,0 +1,881 @@
+use bevy::camera::visibility::RenderLayers;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+use new_core::anchor::AnchorStrategy;
+use new_core::element::{ElementHeader, ElementId, ElementIdAllocator, ElementParams, ParamKey, ParamValue};
+use new_core::elements::element_kindtype_enums::DistributionSystemEnum;
+use new_core::mep::{
+    FittingKind, FlowDirection, JOIN_RADIUS, MepDomain, MepFitting, MepSegment, Port, PortRef, Ports, SIZE_PARAM,
+    SectionSize,
+};
+use new_core::mep_system::system_name;
+use new_core::phase::{ElementPhasing, ViewPhases};
+use new_core::tool::{ActiveTool, ToolEvent, ToolId, ToolInputs, ToolOption, ToolOptions, ToolValue};
+use new_core::units::Measure;
+
+use crate::editor::anchoring::anchor_cast::{AnchorCast, ViewportRay};
+use crate::editor::selection::picking::Selectable;
+
+pub const SIZE_OPTION: &str = "Size";
+pub const ELEVATION_OPTION: &str = "Elevation";
+pub const SYSTEM_OPTION: &str = "System";
+
+// Points closer than this are one, and a point this close to a centerline is on it
+const ON_RUN: f32 = 1.0e-3;
+// Fittings take no more than this share of the segments either side of them
+const MAX_TAKE_OFF: f32 = 0.45;
+
+type MepElement<'a> = (
+    Entity,
+    &'a ElementHeader,
+    &'a GlobalTransform,
+    &'a mut Ports,
+    Option<&'a mut MepSegment>,
+);
+
+pub fn route_options(domain: MepDomain) -> Vec<ToolOption> {
+    let sizes = domain.sizes();
+    vec![
+        ToolOption {
+            key: SIZE_OPTION,
+            value: ToolValue::Choice {
+                choices: sizes.iter().map(|size| (*size).to_owned()).collect(),
+                selected: sizes.iter().position(|size| *size == domain.default_size()).unwrap_or(0),
+            },
+        },
+        ToolOption {
+            key: ELEVATION_OPTION,
+            value: ToolValue::Number {
+                value: domain.default_elevation(),
+                measure: Measure::Length,
+            },
+        },
+        ToolOption {
+            key: SYSTEM_OPTION,
+            value: ToolValue::Choice {
+                choices: domain.systems().iter().map(|system| system_name(*system)).collect(),
+                selected: 0,
+            },
+        },
+    ]
+}
+
+pub fn routing_tool_active(active: Res<ActiveTool>) -> bool {
+    active.id.and_then(routed).is_some()
+}
+
+fn routed(tool: ToolId) -> Option<MepDomain> {
+    MepDomain::iter().find(|domain| domain.tool() == tool)
+}
+
+#[derive(Resource)]
+pub struct MepMaterials {
+    duct: Handle<StandardMaterial>,
+    pipe: Handle<StandardMaterial>,
+    cable_tray: Handle<StandardMaterial>,
+}
+
+impl MepMaterials {
+    pub fn get(&self, domain: MepDomain) -> &Handle<StandardMaterial> {
+        match domain {
+            MepDomain::Duct => &self.duct,
+            MepDomain::Pipe => &self.pipe,
+            MepDomain::CableCarrier => &self.cable_tray,
+        }
+    }
+}
+
+impl FromWorld for MepMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let mut metal = |base_color: Color| {
+            materials.add(StandardMaterial {
+                base_color,
+                metallic: 0.5,
+                perceptual_roughness: 0.6,
+                ..default()
+            })
+        };
+        Self {
+            duct: metal(Color::srgb(0.25, 0.55, 0.95)),
+            pipe: metal(Color::srgb(0.3, 0.7, 0.4)),
+            cable_tray: metal(Color::srgb(0.85, 0.7, 0.25)),
+        }
+    }
+}
+
+enum Body {
+    Segment(MepSegment),
+    Fitting(FittingKind),
+}
+
+// Element of a run before it is spawned, ports in its own space
+struct Piece {
+    id: ElementId,
+    name: String,
+    origin: Vec3,
+    body: Body,
+    ports: Vec<Port>,
+}
+
+impl Piece {
+    fn segment(id: ElementId, name: String, start: Vec3, end: Vec3, size: SectionSize) -> Self {
+        let segment = MepSegment { size, end: end - start };
+        Self {
+            id,
+            name,
+            origin: start,
+            ports: segment.ports(),
+            body: Body::Segment(segment),
+        }
+    }
+}
+
+// Spawns routed elements in the phase of the view they are drawn in
+#[derive(SystemParam)]
+pub struct MepSpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    ids: ResMut<'w, ElementIdAllocator>,
+    materials: Res<'w, MepMaterials>,
+    view_phases: Res<'w, ViewPhases>,
+}
+
+impl MepSpawner<'_, '_> {
+    // Ids come first so the pieces of a run can be linked before any is spawned
+    fn allocate(&mut self) -> ElementId {
+        self.ids.allocate()
+    }
+
+    fn spawn(&mut self, pane_id: u32, domain: MepDomain, system: DistributionSystemEnum, piece: Piece) {
+        let (kind, kind_type) = match piece.body {
+            Body::Segment(_) => (domain.segment_kind(), domain.segment_type()),
+            Body::Fitting(fitting) => (domain.fitting_kind(), domain.fitting_type(fitting)),
+        };
+        let mut params = ElementParams::new();
+        if let Some(port) = piece.ports.first() {
+            params.insert(ParamKey::new(SIZE_PARAM), ParamValue::Text(port.size.to_string()));
+        }
+
+        let mut element = self.commands.spawn((
+            ElementHeader {
+                id: piece.id,
+                name: Some(piece.name),
+                kind,
+                kind_type: Some(kind_type),
+                spec_id: None,
+                level_id: None,
+                params,
+            },
+            Ports {
+                domain,
+                system,
+                ports: piece.ports,
+            },
+            MeshMaterial3d(self.materials.get(domain).clone()),
+            Transform::from_translation(piece.origin),
+            Visibility::default(),
+            RenderLayers::layer(0),
+            Selectable,
+            ElementPhasing {
+                created: self.view_phases.get(pane_id).phase,
+                demolished: None,
+            },
+        ));
+        match piece.body {
+            Body::Segment(segment) => element.insert(segment),
+            Body::Fitting(kind) => element.insert(MepFitting { kind }),
+        };
+    }
+}
+
+// What an end of a run was drawn onto
+enum Join {
+    Open,
+    // Open port of an element, by its index
+    Port(Entity, usize),
+    // Somewhere along a segment, away from its ends
+    Segment(Entity),
+}
+
+// Clicks along the centerline, confirmed to finish. Clicks near an open port or a segment of the
+// same kind join it, anywhere else the centerline runs the Elevation above the picked point.
+pub fn route_runs(
+    mut events: MessageReader<ToolEvent>,
+    viewport: ViewportRay,
+    mut cast: AnchorCast,
+    mut inputs: ResMut<ToolInputs>,
+    options: Res<ToolOptions>,
+    mut elements: Query<MepElement>,
+    mut spawner: MepSpawner,
+) {
+    for event in events.read() {
+        match event {
+            ToolEvent::Picked { tool, pane_id, cursor } => {
+                let (Some(domain), Some(ray)) = (routed(*tool), viewport.ray(*pane_id, *cursor)) else {
+                    continue;
+                };
+                let point = match snap_to_run(ray, domain, &elements) {
+                    Some(point) => point,
+                    None => {
+                        let Some(anchored) = cast.anchor_in_view(*pane_id, AnchorStrategy::Float, ray) else {
+                            continue;
+                        };
+                        let elevation = options.number(*tool, ELEVATION_OPTION).unwrap_or(domain.default_elevation());
+                        anchored.transform.translation + Vec3::Y * elevation
+                    }
+                };
+                inputs.push(*pane_id, point);
+            }
+            ToolEvent::Confirmed { tool, pane_id, points } => {
+                let Some(domain) = routed(*tool) else {
+                    continue;
+                };
+                let Some(size) = options.choice(*tool, SIZE_OPTION).and_then(SectionSize::parse) else {
+                    continue;
+                };
+                let points = corners(points);
+                if points.len() < 2 {
+                    warn!("A run needs two points or more");
+                    continue;
+                }
+                let chosen = options.choice(*tool, SYSTEM_OPTION);
+                let system = domain
+                    .systems()
+                    .iter()
+                    .copied()
+                    .find(|system| Some(system_name(*system).as_str()) == chosen)
+                    .unwrap_or(domain.systems()[0]);
+                let mut routing = Routing {
+                    domain,
+                    system,
+                    size,
+                    pieces: Vec::new(),
+                    links: Vec::new(),
+                };
+                routing.route(&points, &mut elements, &mut spawner);
+                routing.finish(*pane_id, &mut elements, &mut spawner);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Picked points without repeats or points partway along a straight
+fn corners(points: &[Vec3]) -> Vec<Vec3> {
+    let mut points = points.to_vec();
+    points.dedup_by(|a, b| a.distance_squared(*b) < ON_RUN * ON_RUN);
+    let mut index = 1;
+    while index + 1 < points.len() {
+        let (before, after) = (points[index] - points[index - 1], points[index + 1] - points[index]);
+        if before.normalize().dot(after.normalize()) > 1.0 - ON_RUN {
+            points.remove(index);
+        } else {
+            index += 1;
+        }
+    }
+    points
+}
+
+// Open port or centerline of the domain the ray passes closest to, nearest the eye first
+fn snap_to_run(ray: Ray3d, domain: MepDomain, elements: &Query<MepElement>) -> Option<Vec3> {
+    let seen = |point: Vec3| {
+        let along = (point - ray.origin).dot(*ray.direction);
+        let miss = (point - ray.origin).reject_from_normalized(*ray.direction).length();
+        (along > 0.0 && miss < JOIN_RADIUS).then_some((along, point))
+    };
+    let nearest = |hits: &mut dyn Iterator<Item = (f32, Vec3)>| hits.min_by(|a, b| a.0.total_cmp(&b.0)).map(|(_, point)| point);
+    let domain_elements = || elements.iter().filter(move |(.., ports, _)| ports.domain == domain);
+
+    let open_ports = domain_elements().flat_map(|(_, _, transform, ports, _)| {
+        let origin = transform.translation();
+        ports
+            .ports
+            .iter()
+            .filter(|port| port.connected.is_none())
+            .map(move |port| origin + port.position)
+    });
+    if let Some(point) = nearest(&mut open_ports.filter_map(seen)) {
+        return Some(point);
+    }
+
+    // Closest point of each centerline to the ray, kept off the ends where the ports are
+    let centerlines = domain_elements().filter_map(|(_, _, transform, _, segment)| {
+        let segment = segment?;
+        let length = segment.end.length();
+        if length <= 2.0 * JOIN_RADIUS {
+            return None;
+        }
+        let start = transform.translation();
+        let along = segment.end / length;
+        let between = ray.direction.dot(along);
+        let square = 1.0 - between * between;
+        if square < ON_RUN {
+            return None;
+        }
+        let offset = ray.origin - start;
+        let at = (offset.dot(along) - between * offset.dot(*ray.direction)) / square;
+        Some(start + along * at.clamp(JOIN_RADIUS, length - JOIN_RADIUS))
+    });
+    nearest(&mut centerlines.filter_map(seen))
+}
+
+// What a confirmed end point lies on, snapped picks land exactly on it
+fn join_at(point: Vec3, domain: MepDomain, elements: &Query<MepElement>) -> Join {
+    let domain_elements = || elements.iter().filter(move |(.., ports, _)| ports.domain == domain);
+    for (entity, _, transform, ports, _) in domain_elements() {
+        let origin = transform.translation();
+        let open = ports
+            .ports
+            .iter()
+            .position(|port| port.connected.is_none() && (origin + port.position).distance(point) < ON_RUN);
+        if let Some(index) = open {
+            return Join::Port(entity, index);
+        }
+    }
+    for (entity, _, transform, _, segment) in domain_elements() {
+        let Some(segment) = segment else {
+            continue;
+        };
+        let length = segment.end.length();
+        let offset = point - transform.translation();
+        let along = offset.dot(segment.end) / length.max(ON_RUN);
+        if along > ON_RUN && along < length - ON_RUN && offset.reject_from(segment.end).length() < ON_RUN {
+            return Join::Segment(entity);
+        }
+    }
+    Join::Open
+}
+
+// First or last point of a run and what it lies on
+struct RunEnd {
+    join: Join,
+    at: Vec3,
+    // Along the run away from the point
+    towards: Vec3,
+    // Flow enters the run here
+    inlet: bool,
+    // Port of the run's segment at the point
+    port: PortRef,
+    // Most of the segment a fitting may take
+    room: f32,
+}
+
+// Pieces of one run and the ports to link once they all have ids
+struct Routing {
+    domain: MepDomain,
+    // Taken from what the run joins, the tool's choice otherwise
+    system: DistributionSystemEnum,
+    size: SectionSize,
+    pieces: Vec<Piece>,
+    links: Vec<(PortRef, PortRef)>,
+}
+
+impl Routing {
+    fn route(&mut self, points: &[Vec3], elements: &mut Query<MepElement>, spawner: &mut MepSpawner) {
+        let mut points = points.to_vec();
+        let last = points.len() - 1;
+        let mut start = join_at(points[0], self.domain, elements);
+        let mut end = join_at(points[last], self.domain, elements);
+        // Flow runs from the first point to the last unless that goes against the ports the run
+        // joins, as when it is drawn from an inlet or onto an outlet
+        let forward = usize::from(against(&start, true, elements)) + usize::from(against(&end, false, elements));
+        let backward = usize::from(against(&end, true, elements)) + usize::from(against(&start, false, elements));
+        if backward < forward {
+            points.reverse();
+            std::mem::swap(&mut start, &mut end);
+        }
+        // Splitting a segment twice from one run is left to a second run
+        if let (Join::Segment(a), Join::Segment(b)) = (&start, &end)
+            && a == b
+        {
+            end = Join::Open;
+        }
+        for join in [&start, &end] {
+            if let Join::Port(entity, _) | Join::Segment(entity) = *join
+                && let Ok((.., ports, _)) = elements.get(entity)
+            {
+                self.system = ports.system;
+            }
+        }
+
+        let legs: Vec<(Vec3, Vec3)> = points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+        let lengths: Vec<f32> = legs.iter().map(|(a, b)| a.distance(*b)).collect();
+        let ids: Vec<ElementId> = legs.iter().map(|_| spawner.allocate()).collect();
+        // Left off each leg at its start and end for the fittings there
+        let mut trims = vec![(0.0, 0.0); legs.len()];
+
+        for corner in 1..last {
+            let take_off = self
+                .size
+                .extent()
+                .min(MAX_TAKE_OFF * lengths[corner - 1])
+                .min(MAX_TAKE_OFF * lengths[corner]);
+            let back = (points[corner - 1] - points[corner]).normalize();
+            let ahead = (points[corner + 1] - points[corner]).normalize();
+            let elbow = spawner.allocate();
+            self.pieces.push(Piece {
+                id: elbow,
+                name: format!("{} {}", FittingKind::Elbow, self.size),
+                origin: points[corner],
+                body: Body::Fitting(FittingKind::Elbow),
+                ports: vec![
+                    Port::new(back * take_off, back, FlowDirection::Sink, self.size),
+                    Port::new(ahead * take_off, ahead, FlowDirection::Source, self.size),
+                ],
+            });
+            trims[corner - 1].1 = take_off;
+            trims[corner].0 = take_off;
+            self.links.push((port(ids[corner - 1], 1), port(elbow, 0)));
+            self.links.push((port(elbow, 1), port(ids[corner], 0)));
+        }
+
+        let final_leg = legs.len() - 1;
+        let ends = [
+            RunEnd {
+                join: start,
+                at: points[0],
+                towards: (points[1] - points[0]).normalize(),
+                inlet: true,
+                port: port(ids[0], 0),
+                room: MAX_TAKE_OFF * lengths[0],
+            },
+            RunEnd {
+                join: end,
+                at: points[last],
+                towards: (points[last - 1] - points[last]).normalize(),
+                inlet: false,
+                port: port(ids[final_leg], 1),
+                room: MAX_TAKE_OFF * lengths[final_leg],
+            },
+        ];
+        let [start, end] = ends.map(|end| self.attach(end, elements, spawner));
+        trims[0].0 = start;
+        trims[final_leg].1 = end;
+
+        let name = format!("{} {}", self.domain.segment_kind(), self.size);
+        for (index, (a, b)) in legs.into_iter().enumerate() {
+            let along = (b - a).normalize();
+            let (from, to) = (a + along * trims[index].0, b - along * trims[index].1);
+            self.pieces.push(Piece::segment(ids[index], name.clone(), from, to, self.size));
+        }
+    }
+
+    // Joins an end of the run to what it was drawn onto. Returns how much of the run the
+    // fitting put there takes.
+    fn attach(&mut self, end: RunEnd, elements: &mut Query<MepElement>, spawner: &mut MepSpawner) -> f32 {
+        let RunEnd {
+            join,
+            at,
+            towards,
+            inlet,
+            port: run_port,
+            room,
+        } = end;
+        match join {
+            Join::Open => 0.0,
+            Join::Port(entity, index) => {
+                let Ok((_, header, _, ports, _)) = elements.get(entity) else {
+                    return 0.0;
+                };
+                let existing = port(header.id, index);
+                let other = ports.ports[index].size;
+                if other == self.size {
+                    self.links.push((run_port, existing));
+                    return 0.0;
+                }
+
+                // Sizes meet halfway along a transition as long as the larger of the two. Flow
+                // goes through it the way the existing port has it.
+                let length = other.extent().max(self.size.extent()).min(room);
+                let half = towards * length / 2.0;
+                let (outer, inner) = match ports.ports[index].flow {
+                    FlowDirection::Source => (FlowDirection::Sink, FlowDirection::Source),
+                    FlowDirection::Sink => (FlowDirection::Source, FlowDirection::Sink),
+                    _ if inlet => (FlowDirection::Sink, FlowDirection::Source),
+                    _ => (FlowDirection::Source, FlowDirection::Sink),
+                };
+                let transition = spawner.allocate();
+                self.pieces.push(Piece {
+                    id: transition,
+                    name: format!("{} {other}/{}", FittingKind::Transition, self.size),
+                    origin: at + half,
+                    body: Body::Fitting(FittingKind::Transition),
+                    ports: vec![
+                        Port::new(-half, -towards, outer, other),
+                        Port::new(half, towards, inner, self.size),
+                    ],
+                });
+                self.links.push((existing, port(transition, 0)));
+                self.links.push((port(transition, 1), run_port));
+                length
+            }
+            Join::Segment(entity) => {
+                let Ok((_, header, transform, mut ports, Some(mut segment))) = elements.get_mut(entity) else {
+                    return 0.0;
+                };
+                let start = transform.translation();
+                let end = start + segment.end;
+                let along = segment.end.normalize();
+                let main = segment.size;
+                let take_off = main
+                    .extent()
+                    .max(self.size.extent())
+                    .min(room)
+                    .min(MAX_TAKE_OFF * at.distance(start))
+                    .min(MAX_TAKE_OFF * at.distance(end));
+
+                // The segment stops short of the tee and a new one carries on from it, taking
+                // over whatever the far end was connected to
+                let (tee, rest) = (spawner.allocate(), spawner.allocate());
+                // Connections and design flows stay with the ends they were set on
+                let [inlet_end, outlet_end] = [0, 1].map(|index| {
+                    let port = &ports.ports[index];
+                    (port.connected, port.design_flow)
+                });
+                *segment = MepSegment {
+                    size: main,
+                    end: at - along * take_off - start,
+                };
+                ports.ports = segment.ports();
+                (ports.ports[0].connected, ports.ports[0].design_flow) = inlet_end;
+
+                let branch = if inlet {
+                    FlowDirection::Source
+                } else {
+                    FlowDirection::Sink
+                };
+                self.pieces.push(Piece {
+                    id: tee,
+                    name: format!("{} {main}", FittingKind::Tee),
+                    origin: at,
+                    body: Body::Fitting(FittingKind::Tee),
+                    ports: vec![
+                        Port::new(-along * take_off, -along, FlowDirection::Sink, main),
+                        Port::new(along * take_off, along, FlowDirection::Source, main),
+                        Port::new(towards * take_off, towards, branch, self.size),
+                    ],
+                });
+                let name = header.name.clone().unwrap_or_else(|| format!("{} {main}", header.kind));
+                let mut rest_piece = Piece::segment(rest, name, at + along * take_off, end, main);
+                rest_piece.ports[1].design_flow = outlet_end.1;
+                self.pieces.push(rest_piece);
+
+                self.links.push((port(header.id, 1), port(tee, 0)));
+                self.links.push((port(tee, 1), port(rest, 0)));
+                self.links.push((port(tee, 2), run_port));
+                if let Some(outlet) = outlet_end.0 {
+                    self.links.push((port(rest, 1), outlet));
+                }
+                take_off
+            }
+        }
+    }
+
+    // Links the ports both ways and spawns the pieces
+    fn finish(mut self, pane_id: u32, elements: &mut Query<MepElement>, spawner: &mut MepSpawner) {
+        for (a, b) in std::mem::take(&mut self.links) {
+            self.connect(a, b, elements);
+            self.connect(b, a, elements);
+        }
+        for piece in self.pieces {
+            spawner.spawn(pane_id, self.domain, self.system, piece);
+        }
+    }
+
+    // Points one port at another, on a piece of the run or an element already there
+    fn connect(&mut self, from: PortRef, to: PortRef, elements: &mut Query<MepElement>) {
+        if let Some(piece) = self.pieces.iter_mut().find(|piece| piece.id == from.element) {
+            if let Some(port) = piece.ports.get_mut(from.port) {
+                port.connected = Some(to);
+            }
+            return;
+        }
+        if let Some((.., mut ports, _)) = elements.iter_mut().find(|(_, header, ..)| header.id == from.element)
+            && let Some(port) = ports.ports.get_mut(from.port)
+        {
+            port.connected = Some(to);
+        }
+    }
+}
+
+// Whether an end of the run on the join would meet its port flow against flow, an inlet of
+// the run on an inlet or an outlet on an outlet
+fn against(join: &Join, inlet: bool, elements: &Query<MepElement>) -> bool {
+    let Join::Port(entity, index) = *join else {
+        return false;
+    };
+    let Ok((.., ports, _)) = elements.get(entity) else {
+        return false;
+    };
+    match ports.ports[index].flow {
+        FlowDirection::Sink => inlet,
+        FlowDirection::Source => !inlet,
+        _ => false,
+    }
+}
+
+fn port(element: ElementId, port: usize) -> PortRef {
+    PortRef { element, port }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    const DUCT: SectionSize = SectionSize::Round { diameter: 0.2 };
+    const MAIN: SectionSize = SectionSize::Round { diameter: 0.3 };
+
+    fn close(value: Vec3, expected: Vec3) -> bool {
+        value.distance(expected) < 1e-4
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<MepMaterials>();
+        world.init_resource::<ViewPhases>();
+        world.init_resource::<ElementIdAllocator>();
+        world
+    }
+
+    // A duct already in the model, open at both ends
+    fn existing(world: &mut World, start: Vec3, end: Vec3, size: SectionSize) -> ElementId {
+        let id = world.resource_mut::<ElementIdAllocator>().allocate();
+        let segment = MepSegment { size, end: end - start };
+        world.spawn((
+            ElementHeader {
+                id,
+                name: Some("Main".to_owned()),
+                kind: MepDomain::Duct.segment_kind(),
+                kind_type: None,
+                spec_id: None,
+                level_id: None,
+                params: ElementParams::new(),
+            },
+            Ports {
+                domain: MepDomain::Duct,
+                system: DistributionSystemEnum::EXHAUST,
+                ports: segment.ports(),
+            },
+            segment,
+            Transform::from_translation(start),
+            GlobalTransform::from_translation(start),
+        ));
+        id
+    }
+
+    fn route(world: &mut World, points: &[Vec3], size: SectionSize) {
+        fn run(
+            In((points, size)): In<(Vec<Vec3>, SectionSize)>,
+            mut elements: Query<MepElement>,
+            mut spawner: MepSpawner,
+        ) {
+            let mut routing = Routing {
+                domain: MepDomain::Duct,
+                system: MepDomain::Duct.systems()[0],
+                size,
+                pieces: Vec::new(),
+                links: Vec::new(),
+            };
+            routing.route(&points, &mut elements, &mut spawner);
+            routing.finish(0, &mut elements, &mut spawner);
+        }
+        world.run_system_once_with(run, (points.to_vec(), size)).expect("routed");
+    }
+
+    // Every element after routing, origin and ports, with the fitting kind for fittings
+    struct Routed {
+        id: ElementId,
+        origin: Vec3,
+        ports: Ports,
+        fitting: Option<FittingKind>,
+        segment: Option<MepSegment>,
+    }
+
+    fn routed(world: &mut World) -> Vec<Routed> {
+        let mut query = world.query::<(
+            &ElementHeader,
+            &Transform,
+            &Ports,
+            Option<&MepFitting>,
+            Option<&MepSegment>,
+        )>();
+        let mut elements: Vec<Routed> = query
+            .iter(world)
+            .map(|(header, transform, ports, fitting, segment)| Routed {
+                id: header.id,
+                origin: transform.translation,
+                ports: ports.clone(),
+                fitting: fitting.map(|fitting| fitting.kind),
+                segment: segment.copied(),
+            })
+            .collect();
+        elements.sort_by_key(|element| element.id.0);
+        elements
+    }
+
+    fn fittings(elements: &[Routed]) -> Vec<&Routed> {
+        elements.iter().filter(|element| element.fitting.is_some()).collect()
+    }
+
+    fn port_at(elements: &[Routed], to: PortRef) -> (&Routed, &Port) {
+        let element = elements.iter().find(|element| element.id == to.element).expect("linked element");
+        (element, &element.ports.ports[to.port])
+    }
+
+    // Linked ports point back at each other, sit in the same place facing apart, and pass the
+    // flow from an outlet to an inlet
+    fn assert_linked(elements: &[Routed]) {
+        for element in elements {
+            for (index, port) in element.ports.ports.iter().enumerate() {
+                let Some(to) = port.connected else {
+                    continue;
+                };
+                let (other, other_port) = port_at(elements, to);
+                assert_eq!(other_port.connected, Some(PortRef { element: element.id, port: index }));
+                assert!(close(element.origin + port.position, other.origin + other_port.position));
+                assert!(close(port.direction, -other_port.direction));
+                assert_eq!(port.flow.opposite(), other_port.flow);
+                assert_eq!(port.size, other_port.size);
+            }
+        }
+    }
+
+    #[test]
+    fn a_corner_gets_an_elbow() {
+        let mut world = world();
+        let points = [Vec3::new(0.0, 3.0, 0.0), Vec3::new(4.0, 3.0, 0.0), Vec3::new(4.0, 3.0, 4.0)];
+        route(&mut world, &points, DUCT);
+        let elements = routed(&mut world);
+
+        assert_eq!(elements.len(), 3);
+        let [elbow] = fittings(&elements)[..] else {
+            panic!("one fitting");
+        };
+        assert_eq!(elbow.fitting, Some(FittingKind::Elbow));
+        assert!(close(elbow.origin, points[1]));
+
+        // The run comes in from -x and leaves along +z, each leg stopping the size short of it
+        let [inlet, outlet] = &elbow.ports.ports[..] else {
+            panic!("two ports");
+        };
+        assert_eq!((inlet.flow, outlet.flow), (FlowDirection::Sink, FlowDirection::Source));
+        assert!(close(inlet.position, Vec3::new(-0.2, 0.0, 0.0)));
+        assert!(close(outlet.position, Vec3::new(0.0, 0.0, 0.2)));
+        assert!(inlet.connected.is_some() && outlet.connected.is_some());
+        assert_linked(&elements);
+
+        let lengths: Vec<f32> = elements
+            .iter()
+            .filter_map(|element| element.segment)
+            .map(|segment| segment.end.length())
+            .collect();
+        assert_eq!(lengths.len(), 2);
+        assert!(lengths.iter().all(|length| (length - 3.8).abs() < 1e-4));
+    }
+
+    #[test]
+    fn points_along_a_straight_are_not_corners() {
+        let points = [Vec3::ZERO, Vec3::X, Vec3::X * 2.0, Vec3::X * 2.0, Vec3::new(2.0, 0.0, 1.0)];
+        assert_eq!(corners(&points), [Vec3::ZERO, Vec3::X * 2.0, Vec3::new(2.0, 0.0, 1.0)]);
+    }
+
+    #[test]
+    fn a_run_of_the_same_size_carries_straight_on() {
+        let mut world = world();
+        let main = existing(&mut world, Vec3::ZERO, Vec3::new(4.0, 0.0, 0.0), DUCT);
+        route(&mut world, &[Vec3::new(4.0, 0.0, 0.0), Vec3::new(8.0, 0.0, 0.0)], DUCT);
+        let elements = routed(&mut world);
+
+        // No fitting, the new segment's inlet meets the outlet it was drawn from
+        assert_eq!(elements.len(), 2);
+        assert!(fittings(&elements).is_empty());
+        assert_linked(&elements);
+        let run = &elements[1];
+        assert_eq!(run.ports.ports[0].connected, Some(port(main, 1)));
+        assert!(close(run.origin, Vec3::new(4.0, 0.0, 0.0)));
+        // And joins the system of what it was drawn from
+        assert_eq!(run.ports.system, DistributionSystemEnum::EXHAUST);
+    }
+
+    #[test]
+    fn a_run_drawn_from_an_inlet_is_turned_to_flow_into_it() {
+        let mut world = world();
+        let main = existing(&mut world, Vec3::ZERO, Vec3::new(4.0, 0.0, 0.0), DUCT);
+        route(&mut world, &[Vec3::ZERO, Vec3::new(-3.0, 0.0, 0.0)], DUCT);
+        let elements = routed(&mut world);
+
+        assert_linked(&elements);
+        let run = &elements[1];
+        // Drawn away from the inlet, the run starts at the far point and ends on the inlet
+        assert!(close(run.origin, Vec3::new(-3.0, 0.0, 0.0)));
+        assert_eq!(run.ports.ports[1].connected, Some(port(main, 0)));
+        assert_eq!(run.ports.ports[1].flow, FlowDirection::Source);
+    }
+
+    #[test]
+    fn a_change_of_size_gets_a_transition() {
+        let mut world = world();
+        let main = existing(&mut world, Vec3::ZERO, Vec3::new(4.0, 0.0, 0.0), MAIN);
+        route(&mut world, &[Vec3::new(4.0, 0.0, 0.0), Vec3::new(8.0, 0.0, 0.0)], DUCT);
+        let elements = routed(&mut world);
+
+        assert_eq!(elements.len(), 3);
+        let [transition] = fittings(&elements)[..] else {
+            panic!("one fitting");
+        };
+        assert_eq!(transition.fitting, Some(FittingKind::Transition));
+        // As long as the larger size, the run starts where it ends
+        assert!(close(transition.origin, Vec3::new(4.15, 0.0, 0.0)));
+
+        let [large, small] = &transition.ports.ports[..] else {
+            panic!("two ports");
+        };
+        assert_eq!((large.size, small.size), (MAIN, DUCT));
+        // Flow carries on the way the outlet it was drawn from sends it
+        assert_eq!((large.flow, small.flow), (FlowDirection::Sink, FlowDirection::Source));
+        assert_eq!(large.connected, Some(port(main, 1)));
+        assert_linked(&elements);
+
+        let run = elements.iter().find(|element| element.id != main && element.segment.is_some()).expect("run");
+        assert!(close(run.origin, Vec3::new(4.3, 0.0, 0.0)));
+        assert!(close(run.origin + run.segment.expect("segment").end, Vec3::new(8.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn a_run_onto_the_side_of_a_segment_gets_a_tee() {
+        let mut world = world();
+        let main = existing(&mut world, Vec3::ZERO, Vec3::new(6.0, 0.0, 0.0), MAIN);
+        route(&mut world, &[Vec3::new(3.0, 0.0, 4.0), Vec3::new(3.0, 0.0, 0.0)], DUCT);
+        let elements = routed(&mut world);
+
+        // The main stops short of the tee and a new segment carries on past it
+        assert_eq!(elements.len(), 4);
+        let [tee] = fittings(&elements)[..] else {
+            panic!("one fitting");
+        };
+        assert_eq!(tee.fitting, Some(FittingKind::Tee));
+        assert!(close(tee.origin, Vec3::new(3.0, 0.0, 0.0)));
+
+        let [through_in, through_out, branch] = &tee.ports.ports[..] else {
+            panic!("three ports");
+        };
+        assert_eq!(
+            (through_in.flow, through_out.flow, branch.flow),
+            (FlowDirection::Sink, FlowDirection::Source, FlowDirection::Sink)
+        );
+        assert_eq!((through_in.size, branch.size), (MAIN, DUCT));
+        assert_eq!(through_in.connected, Some(port(main, 1)));
+        assert!(close(branch.direction, Vec3::Z));
+        assert_linked(&elements);
+
+        let shortened = elements.iter().find(|element| element.id == main).expect("main");
+        assert!(close(shortened.segment.expect("segment").end, Vec3::new(2.7, 0.0, 0.0)));
+        let rest = port_at(&elements, through_out.connected.expect("carried on")).0;
+        assert!(close(rest.origin, Vec3::new(3.3, 0.0, 0.0)));
+        assert!(close(rest.origin + rest.segment.expect("segment").end, Vec3::new(6.0, 0.0, 0.0)));
+    }
+}
@@ -0,0 +1,281 @@
+use bevy::camera::visibility::RenderLayers;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+
+use new_core::action::{ActionDef, ActionId, ActionInvoked, ActionPrompt};
+use new_core::asset_library::InstancePart;
+use new_core::element::{ElementHeader, ElementIdAllocator};
+use new_core::elements::element_kindtype_enums::BuildingElementProxyType;
+use new_core::elements::{ElementKind, ElementKindType};
+use new_core::model_exchange::{ExportScope, ImportAs, ModelCommand, ModelExchange};
+use new_core::phase::ElementPhasing;
+use new_core::sheet::SheetSet;
+use new_core::structural_grid::StructuralGrid;
+
+use crate::editor::selection::picking::{Selectable, Selected};
+use crate::models::gltf_read::{GltfError, read_gltf};
+use crate::analysis::clash::shape::world_triangles;
+use crate::models::gltf_write::{ExportedElement, ExportedPart, model_glb};
+use crate::models::ifc_write::{IfcElement, IfcGrid, model_ifc};
+
+pub const IMPORT_GLTF: ActionId = ActionId("file.import_gltf");
+pub const EXPORT_GLB: ActionId = ActionId("file.export_glb");
+pub const EXPORT_IFC: ActionId = ActionId("file.export_ifc");
+
+pub fn model_actions() -> [ActionDef; 3] {
+    let path = |label| {
+        Some(ActionPrompt {
+            label,
+            choices: Vec::new(),
+        })
+    };
+    [
+        ActionDef {
+            id: IMPORT_GLTF,
+            label: "Import glTF",
+            menu: "File",
+            bindings: Vec::new(),
+            prompt: path(".gltf or .glb file"),
+        },
+        ActionDef {
+            id: EXPORT_GLB,
+            label: "Export Model as GLB",
+            menu: "File",
+            bindings: Vec::new(),
+            prompt: path(".glb file"),
+        },
+        ActionDef {
+            id: EXPORT_IFC,
+            label: "Export Model as IFC",
+            menu: "File",
+            bindings: Vec::new(),
+            prompt: path(".ifc file"),
+        },
+    ]
+}
+
+// Same as the Models pane, with the import and export settings chosen there
+pub fn run_model_actions(
+    mut invoked: MessageReader<ActionInvoked>,
+    exchange: Res<ModelExchange>,
+    mut model_commands: MessageWriter<ModelCommand>,
+) {
+    for action in invoked.read() {
+        let Some(path) = action.arg.as_deref().map(str::trim).filter(|path| !path.is_empty()) else {
+            continue;
+        };
+        let path = PathBuf::from(path);
+        if action.id == IMPORT_GLTF {
+            model_commands.write(ModelCommand::ImportGltf {
+                path,
+                import_as: exchange.import_as,
+            });
+        } else if action.id == EXPORT_GLB {
+            model_commands.write(ModelCommand::ExportGlb {
+                path,
+                scope: exchange.scope,
+            });
+        } else if action.id == EXPORT_IFC {
+            model_commands.write(ModelCommand::ExportIfc {
+                path,
+                scope: exchange.scope,
+            });
+        }
+    }
+}
+
+type ExportableElement<'a> = (
+    &'a ElementHeader,
+    &'a Mesh3d,
+    Option<&'a MeshMaterial3d<StandardMaterial>>,
+    &'a GlobalTransform,
+    Option<&'a Children>,
+    Has<Selected>,
+);
+
+type ExportablePart<'a> = (&'a Mesh3d, &'a MeshMaterial3d<StandardMaterial>);
+
+// What export reads besides the element meshes, the parts of instances for GLB and the grids
+// and project for IFC
+#[derive(SystemParam)]
+pub struct ExportableModel<'w, 's> {
+    parts: Query<'w, 's, ExportablePart<'static>, With<InstancePart>>,
+    grids: Query<'w, 's, ExportableGrid<'static>>,
+    sheets: Res<'w, SheetSet>,
+}
+
+#[derive(SystemParam)]
+pub struct ModelAssets<'w> {
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    images: ResMut<'w, Assets<Image>>,
+}
+
+type ExportableGrid<'a> = (&'a ElementHeader, &'a StructuralGrid, Has<Selected>);
+
+pub fn run_model_commands(
+    mut model_commands: MessageReader<ModelCommand>,
+    mut commands: Commands,
+    mut exchange: ResMut<ModelExchange>,
+    mut ids: ResMut<ElementIdAllocator>,
+    mut assets: ModelAssets,
+    elements: Query<ExportableElement>,
+    exportable: ExportableModel,
+) {
+    for command in model_commands.read() {
+        let status = match command {
+            ModelCommand::ImportGltf { path, import_as } => {
+                match import_gltf(path, *import_as, &mut commands, &mut ids, &mut assets) {
+                    Ok(count) => format!("Imported {count} elements from {}", path.display()),
+                    Err(error) => format!("Importing {} failed: {error}", path.display()),
+                }
+            }
+            ModelCommand::ExportGlb { path, scope } => {
+                let exported: Vec<ExportedElement> = elements
+                    .iter()
+                    .filter(|(.., selected)| *scope == ExportScope::Model || *selected)
+                    .filter_map(|(header, mesh, material, transform, children, _)| {
+                        let part = |mesh: &Mesh3d, material: Option<&MeshMaterial3d<StandardMaterial>>| {
+                            let material = material
+                                .and_then(|material| Some((material.id(), assets.materials.get(&material.0)?)));
+                            let texture = material
+                                .and_then(|(_, material)| material.base_color_texture.as_ref())
+                                .and_then(|image| assets.images.get(image));
+                            Some(ExportedPart {
+                                mesh: assets.meshes.get(&mesh.0)?,
+                                material,
+                                texture,
+                            })
+                        };
+
+                        // Instances in several materials are drawn by their parts, the element
+                        // mesh has them all merged without a material
+                        let drawn: Vec<ExportedPart> = children
+                            .into_iter()
+                            .flat_map(|children| exportable.parts.iter_many(children))
+                            .filter_map(|(mesh, material)| part(mesh, Some(material)))
+                            .collect();
+                        let parts = if drawn.is_empty() {
+                            vec![part(mesh, material)?]
+                        } else {
+                            drawn
+                        };
+
+                        Some(ExportedElement {
+                            header,
+                            parts,
+                            transform: *transform,
+                        })
+                    })
+                    .collect();
+
+                match export_glb(&exported, path) {
+                    Ok(()) => format!("Exported {} elements to {}", exported.len(), path.display()),
+                    Err(error) => format!("Exporting to {} failed: {error}", path.display()),
+                }
+            }
+            ModelCommand::ExportIfc { path, scope } => {
+                let in_scope = |selected: bool| *scope == ExportScope::Model || selected;
+                let exported: Vec<IfcElement> = elements
+                    .iter()
+                    .filter(|(.., selected)| in_scope(*selected))
+                    .filter_map(|(header, mesh, _, transform, ..)| {
+                        Some(IfcElement {
+                            header,
+                            triangles: world_triangles(assets.meshes.get(&mesh.0)?, transform)?,
+                        })
+                    })
+                    .collect();
+                let grids: Vec<IfcGrid> = exportable
+                    .grids
+                    .iter()
+                    .filter(|(.., selected)| in_scope(*selected))
+                    .map(|(header, grid, _)| IfcGrid { header, grid })
+                    .collect();
+
+                match export_ifc(&exportable.sheets.project, &exported, &grids, path) {
+                    Ok(()) => format!(
+                        "Exported {} elements and {} grids to {}",
+                        exported.len(),
+                        grids.len(),
+                        path.display()
+                    ),
+                    Err(error) => format!("Exporting to {} failed: {error}", path.display()),
+                }
+            }
+        };
+
+        info!("{status}");
+        exchange.status = Some(status);
+    }
+}
+
+// Spawns every mesh as an element, returns how many
+pub fn import_gltf(
+    path: &Path,
+    import_as: ImportAs,
+    commands: &mut Commands,
+    ids: &mut ElementIdAllocator,
+    assets: &mut ModelAssets,
+) -> Result<usize, GltfError> {
+    let scene = read_gltf(path)?;
+
+    let materials: Vec<Handle<StandardMaterial>> = scene
+        .materials
+        .into_iter()
+        .map(|imported| {
+            let base_color_texture = imported.base_color_texture.map(|image| assets.images.add(image));
+            assets.materials.add(StandardMaterial {
+                base_color_texture,
+                ..imported.material
+            })
+        })
+        .collect();
+    let default_material = assets.materials.add(StandardMaterial::default());
+
+    let count = scene.meshes.len();
+    for imported in scene.meshes {
+        // Kinds from our own exports survive the round trip, anything else takes the chosen one
+        let kind = imported.kind.unwrap_or(import_as.kind());
+        let kind_type = imported.kind_type.or_else(|| {
+            (kind == ElementKind::BuildingElementProxy)
+                .then_some(ElementKindType::BuildingElementProxy(BuildingElementProxyType::NOTDEFINED))
+        });
+        let material = imported
+            .material
+            .and_then(|index| materials.get(index))
+            .unwrap_or(&default_material)
+            .clone();
+
+        commands.spawn((
+            ElementHeader {
+                id: ids.allocate(),
+                name: Some(imported.name),
+                kind,
+                kind_type,
+                spec_id: None,
+                level_id: None,
+                params: imported.params,
+            },
+            Mesh3d(assets.meshes.add(imported.mesh)),
+            MeshMaterial3d(material),
+            imported.transform,
+            RenderLayers::layer(0),
+            Selectable,
+            ElementPhasing::default(),
+        ));
+    }
+
+    Ok(count)
+}
+
+fn export_glb(elements: &[ExportedElement], path: &Path) -> std::io::Result<()> {
+    let glb = model_glb(elements).map_err(std::io::Error::other)?;
+    std::fs::write(path, glb)
+}
+
+fn export_ifc(project: &str, elements: &[IfcElement], grids: &[IfcGrid], path: &Path) -> std::io::Result<()> {
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    std::fs::write(path, model_ifc(project, file_name, elements, grids))
+}
@@ -0,0 +1,278 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use bevy::asset::RenderAssetUsages;
+use bevy::image::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+use gltf::json::Value;
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+
+use new_core::element::{ElementParams, ParamKey, ParamValue};
+use new_core::elements::{ElementKind, ElementKindType};
+use new_core::model_exchange::{EXTRAS_KIND, EXTRAS_KIND_TYPE, EXTRAS_PARAMS, kind_from_name};
+
+#[derive(Debug)]
+pub enum GltfError {
+    Io(std::io::Error),
+    Gltf(gltf::Error),
+    Buffer(String),
+    Empty,
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfError::Io(error) => write!(f, "{error}"),
+            GltfError::Gltf(error) => write!(f, "{error}"),
+            GltfError::Buffer(message) => write!(f, "{message}"),
+            GltfError::Empty => write!(f, "no triangle meshes in the scene"),
+        }
+    }
+}
+
+impl From<std::io::Error> for GltfError {
+    fn from(error: std::io::Error) -> Self {
+        GltfError::Io(error)
+    }
+}
+
+impl From<gltf::Error> for GltfError {
+    fn from(error: gltf::Error) -> Self {
+        GltfError::Gltf(error)
+    }
+}
+
+pub struct ImportedMaterial {
+    pub material: StandardMaterial,
+    pub base_color_texture: Option<Image>,
+}
+
+// One mesh primitive placed in the scene
+pub struct ImportedMesh {
+    pub name: String,
+    pub mesh: Mesh,
+    // Index into the imported materials, None for the glTF default material
+    pub material: Option<usize>,
+    pub transform: Transform,
+    // From the node extras when the file came from us
+    pub kind: Option<ElementKind>,
+    pub kind_type: Option<ElementKindType>,
+    pub params: ElementParams,
+}
+
+pub struct ImportedScene {
+    pub materials: Vec<ImportedMaterial>,
+    pub meshes: Vec<ImportedMesh>,
+}
+
+pub fn read_gltf(path: &Path) -> Result<ImportedScene, GltfError> {
+    let folder = path.parent().unwrap_or(Path::new("."));
+    read_gltf_slice(&std::fs::read(path)?, folder)
+}
+
+// glTF is Y up in meters like the model, so node transforms carry over as they are.
+// Buffers given by a relative uri are looked up in the folder.
+pub fn read_gltf_slice(bytes: &[u8], folder: &Path) -> Result<ImportedScene, GltfError> {
+    let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(bytes)?;
+
+    let buffers = document
+        .buffers()
+        .map(|buffer| match buffer.source() {
+            gltf::buffer::Source::Bin => blob
+                .clone()
+                .ok_or_else(|| GltfError::Buffer("binary chunk missing".to_owned())),
+            gltf::buffer::Source::Uri(uri) => read_uri(folder, uri),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let materials = document
+        .materials()
+        .map(|material| read_material(&material, folder, &buffers))
+        .collect();
+
+    let mut meshes = Vec::new();
+    let scene = document.default_scene().or_else(|| document.scenes().next());
+    for node in scene.iter().flat_map(|scene| scene.nodes()) {
+        read_node(&node, Mat4::IDENTITY, &buffers, &mut meshes);
+    }
+
+    if meshes.is_empty() {
+        return Err(GltfError::Empty);
+    }
+    Ok(ImportedScene { materials, meshes })
+}
+
+// Embedded base64 data or a file next to the glTF
+fn read_uri(folder: &Path, uri: &str) -> Result<Vec<u8>, GltfError> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, encoded) = data
+            .split_once(";base64,")
+            .ok_or_else(|| GltfError::Buffer("only base64 data uris are supported".to_owned()))?;
+        return STANDARD
+            .decode(encoded)
+            .map_err(|error| GltfError::Buffer(error.to_string()));
+    }
+
+    let file = uri.replace("%20", " ");
+    Ok(std::fs::read(folder.join(file))?)
+}
+
+fn read_node(node: &gltf::Node, parent: Mat4, buffers: &[Vec<u8>], meshes: &mut Vec<ImportedMesh>) {
+    let world = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        let (kind, kind_type, params) = read_extras(node.extras());
+        let name = node
+            .name()
+            .or(mesh.name())
+            .map_or_else(|| format!("Mesh {}", mesh.index()), str::to_owned);
+        let count = mesh.primitives().len();
+
+        for (index, primitive) in mesh.primitives().enumerate() {
+            let Some(bevy_mesh) = read_primitive(&primitive, buffers) else {
+                continue;
+            };
+            meshes.push(ImportedMesh {
+                name: if count > 1 { format!("{name} {}", index + 1) } else { name.clone() },
+                mesh: bevy_mesh,
+                material: primitive.material().index(),
+                transform: Transform::from_matrix(world),
+                kind,
+                kind_type,
+                params: params.clone(),
+            });
+        }
+    }
+
+    for child in node.children() {
+        read_node(&child, world, buffers, meshes);
+    }
+}
+
+// Triangle lists only, points, lines and strips are skipped
+fn read_primitive(primitive: &gltf::Primitive, buffers: &[Vec<u8>]) -> Option<Mesh> {
+    if primitive.mode() != gltf::mesh::Mode::Triangles {
+        return None;
+    }
+
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+    let positions: Vec<[f32; 3]> = reader.read_positions()?.collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    if let Some(uvs) = reader.read_tex_coords(0) {
+        let uvs: Vec<[f32; 2]> = uvs.into_f32().collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    }
+    if let Some(indices) = reader.read_indices() {
+        mesh.insert_indices(Indices::U32(indices.into_u32().collect()));
+    }
+    match reader.read_normals() {
+        Some(normals) => mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals.collect::<Vec<_>>()),
+        None => mesh.compute_normals(),
+    }
+
+    Some(mesh)
+}
+
+fn read_material(material: &gltf::Material, folder: &Path, buffers: &[Vec<u8>]) -> ImportedMaterial {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    let [er, eg, eb] = material.emissive_factor();
+
+    let alpha_mode = match material.alpha_mode() {
+        gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+        gltf::material::AlphaMode::Mask => AlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5)),
+        gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+    };
+
+    // A texture that can't be read leaves the flat color
+    let base_color_texture = pbr.base_color_texture().and_then(|info| {
+        let (bytes, image_type) = match info.texture().source().source() {
+            gltf::image::Source::View { view, mime_type } => {
+                let buffer = buffers.get(view.buffer().index())?;
+                let bytes = buffer.get(view.offset()..view.offset() + view.length())?.to_vec();
+                (bytes, ImageType::MimeType(mime_type))
+            }
+            gltf::image::Source::Uri { uri, mime_type } => {
+                let image_type = match mime_type {
+                    Some(mime_type) => ImageType::MimeType(mime_type),
+                    None => ImageType::Extension(Path::new(uri).extension()?.to_str()?),
+                };
+                (read_uri(folder, uri).ok()?, image_type)
+            }
+        };
+        Image::from_buffer(
+            &bytes,
+            image_type,
+            CompressedImageFormats::NONE,
+            true,
+            ImageSampler::Default,
+            RenderAssetUsages::default(),
+        )
+        .ok()
+    });
+
+    ImportedMaterial {
+        material: StandardMaterial {
+            base_color: Color::linear_rgba(r, g, b, a),
+            metallic: pbr.metallic_factor(),
+            perceptual_roughness: pbr.roughness_factor(),
+            emissive: LinearRgba::rgb(er, eg, eb),
+            alpha_mode,
+            double_sided: material.double_sided(),
+            cull_mode: if material.double_sided() {
+                None
+            } else {
+                StandardMaterial::default().cull_mode
+            },
+            ..default()
+        },
+        base_color_texture,
+    }
+}
+
+fn read_extras(extras: &gltf::json::Extras) -> (Option<ElementKind>, Option<ElementKindType>, ElementParams) {
+    let Some(Value::Object(extras)) = extras
+        .as_ref()
+        .and_then(|raw| gltf::json::deserialize::from_str(raw.get()).ok())
+    else {
+        return (None, None, ElementParams::new());
+    };
+
+    let kind = extras.get(EXTRAS_KIND).and_then(Value::as_str).and_then(kind_from_name);
+    let kind_type = extras
+        .get(EXTRAS_KIND_TYPE)
+        .and_then(Value::as_str)
+        .and_then(kind_type_from_name);
+
+    let params = extras
+        .get(EXTRAS_PARAMS)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                Value::Bool(value) => ParamValue::Bool(*value),
+                Value::Number(number) => match number.as_i64() {
+                    Some(value) => ParamValue::Int(value),
+                    None => ParamValue::Float(number.as_f64()?),
+                },
+                Value::String(text) => ParamValue::Text(text.clone()),
+                _ => return None,
+            };
+            Some((ParamKey::new(key), value))
+        })
+        .collect();
+
+    (kind, kind_type, params)
+}
+
+// Written as the kind with its predefined type, e.g. DuctSegment(RIGIDSEGMENT)
+fn kind_type_from_name(name: &str) -> Option<ElementKindType> {
+    let (kind, predefined) = name.strip_suffix(')')?.split_once('(')?;
+    let tagged: Value = [(kind.to_owned(), Value::from(predefined))].into_iter().collect();
+    ElementKindType::deserialize(tagged).ok()
+}
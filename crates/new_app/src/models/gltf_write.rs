@@ -0,0 +1,386 @@
+use bevy::mesh::{PrimitiveTopology, VertexAttributeValues};
+use bevy::prelude::*;
+use gltf::binary::{Glb, Header};
+use gltf::json::accessor::{ComponentType, GenericComponentType, Type};
+use gltf::json::buffer::Target;
+use gltf::json::extras::RawValue;
+use gltf::json::image::MimeType;
+use gltf::json::material::{AlphaCutoff, AlphaMode as GltfAlphaMode, EmissiveFactor, PbrBaseColorFactor, StrengthFactor};
+use gltf::json::mesh::{Mode, Semantic};
+use gltf::json::scene::UnitQuaternion;
+use gltf::json::validation::{Checked, USize64};
+use gltf::json::{self, Index, Value};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Cursor;
+
+use new_core::element::{ElementHeader, ParamValue};
+use new_core::model_exchange::{EXTRAS_ELEMENT_ID, EXTRAS_KIND, EXTRAS_KIND_TYPE, EXTRAS_PARAMS};
+
+// One element as it goes into the file
+pub struct ExportedElement<'a> {
+    pub header: &'a ElementHeader,
+    // A primitive each in the element mesh
+    pub parts: Vec<ExportedPart<'a>>,
+    pub transform: GlobalTransform,
+}
+
+// Geometry of an element drawn with one material, in element space
+pub struct ExportedPart<'a> {
+    pub mesh: &'a Mesh,
+    pub material: Option<(AssetId<StandardMaterial>, &'a StandardMaterial)>,
+    // Base color texture of the material, embedded as a PNG
+    pub texture: Option<&'a Image>,
+}
+
+// Root json and the binary chunk it points into
+struct GlbBuilder {
+    root: json::Root,
+    buffer: Index<json::Buffer>,
+    bin: Vec<u8>,
+}
+
+impl GlbBuilder {
+    fn new() -> Self {
+        let mut root = json::Root {
+            asset: json::Asset {
+                generator: Some("Monolith".to_owned()),
+                ..default()
+            },
+            ..default()
+        };
+        let buffer = root.push(json::Buffer {
+            byte_length: USize64(0),
+            name: None,
+            uri: None,
+            extensions: None,
+            extras: None,
+        });
+
+        Self {
+            root,
+            buffer,
+            bin: Vec::new(),
+        }
+    }
+
+    // Appends the data to the binary chunk and describes it with a view
+    fn view(&mut self, bytes: &[u8], target: Option<Target>) -> Index<json::buffer::View> {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+        let view = self.root.push(json::buffer::View {
+            buffer: self.buffer,
+            byte_length: USize64(bytes.len() as u64),
+            byte_offset: Some(USize64(self.bin.len() as u64)),
+            byte_stride: None,
+            name: None,
+            target: target.map(Checked::Valid),
+            extensions: None,
+            extras: None,
+        });
+        self.bin.extend_from_slice(bytes);
+        view
+    }
+
+    // Same with an accessor on top
+    fn accessor(
+        &mut self,
+        bytes: &[u8],
+        count: usize,
+        (component, type_): (ComponentType, Type),
+        target: Target,
+        bounds: Option<([f32; 3], [f32; 3])>,
+    ) -> Index<json::Accessor> {
+        let view = self.view(bytes, Some(target));
+
+        let bound = |values: [f32; 3]| Value::from(values.to_vec());
+        self.root.push(json::Accessor {
+            buffer_view: Some(view),
+            byte_offset: None,
+            count: USize64(count as u64),
+            component_type: Checked::Valid(GenericComponentType(component)),
+            extensions: None,
+            extras: None,
+            type_: Checked::Valid(type_),
+            min: bounds.map(|(min, _)| bound(min)),
+            max: bounds.map(|(_, max)| bound(max)),
+            name: None,
+            normalized: false,
+            sparse: None,
+        })
+    }
+
+    // The image as a PNG in the binary chunk, None for a format that can't be converted
+    fn texture(&mut self, image: &Image) -> Option<Index<json::Texture>> {
+        let mut png = Vec::new();
+        image
+            .clone()
+            .try_into_dynamic()
+            .ok()?
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .ok()?;
+
+        let view = self.view(&png, None);
+        let source = self.root.push(json::Image {
+            buffer_view: Some(view),
+            mime_type: Some(MimeType("image/png".to_owned())),
+            name: None,
+            uri: None,
+            extensions: None,
+            extras: None,
+        });
+        Some(self.root.push(json::Texture {
+            name: None,
+            sampler: None,
+            source,
+            extensions: None,
+            extras: None,
+        }))
+    }
+
+    fn primitive(&mut self, mesh: &Mesh) -> Option<json::mesh::Primitive> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            return None;
+        };
+        if positions.is_empty() {
+            return None;
+        }
+
+        // Viewers need the bounds of the positions
+        let (min, max) = positions.iter().fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), p| {
+            (
+                Vec3::from_array(min).min(Vec3::from_array(*p)).to_array(),
+                Vec3::from_array(max).max(Vec3::from_array(*p)).to_array(),
+            )
+        });
+
+        let vec3 = (ComponentType::F32, Type::Vec3);
+        let mut attributes = BTreeMap::new();
+        let position = self.accessor(&floats(positions), positions.len(), vec3, Target::ArrayBuffer, Some((min, max)));
+        attributes.insert(Checked::Valid(Semantic::Positions), position);
+
+        if let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            let normal = self.accessor(&floats(normals), normals.len(), vec3, Target::ArrayBuffer, None);
+            attributes.insert(Checked::Valid(Semantic::Normals), normal);
+        }
+        if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            let vec2 = (ComponentType::F32, Type::Vec2);
+            let uv = self.accessor(&floats(uvs), uvs.len(), vec2, Target::ArrayBuffer, None);
+            attributes.insert(Checked::Valid(Semantic::TexCoords(0)), uv);
+        }
+
+        let indices = mesh.indices().map(|indices| {
+            let values: Vec<u32> = indices.iter().map(|index| index as u32).collect();
+            let bytes: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+            let scalar = (ComponentType::U32, Type::Scalar);
+            self.accessor(&bytes, values.len(), scalar, Target::ElementArrayBuffer, None)
+        });
+
+        Some(json::mesh::Primitive {
+            attributes,
+            extensions: None,
+            extras: None,
+            indices,
+            material: None,
+            mode: Checked::Valid(Mode::Triangles),
+            targets: None,
+        })
+    }
+}
+
+fn floats<const N: usize>(values: &[[f32; N]]) -> Vec<u8> {
+    values.iter().flatten().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+// A node per element in a single scene, all geometry in the binary chunk
+pub fn model_glb(elements: &[ExportedElement]) -> Result<Vec<u8>, gltf::Error> {
+    let mut builder = GlbBuilder::new();
+    let mut materials: HashMap<AssetId<StandardMaterial>, Index<json::Material>> = HashMap::new();
+    let mut nodes = Vec::new();
+
+    for element in elements {
+        let mut primitives = Vec::new();
+        for part in &element.parts {
+            let Some(primitive) = builder.primitive(part.mesh) else {
+                continue;
+            };
+            let material = part.material.map(|(id, material)| match materials.get(&id) {
+                Some(index) => *index,
+                None => {
+                    let texture = part.texture.and_then(|image| builder.texture(image));
+                    let index = builder.root.push(write_material(material, texture));
+                    materials.insert(id, index);
+                    index
+                }
+            });
+            primitives.push(json::mesh::Primitive { material, ..primitive });
+        }
+        if primitives.is_empty() {
+            continue;
+        }
+
+        let root = &mut builder.root;
+        let name = element.header.name.clone();
+        let mesh = root.push(json::Mesh {
+            extensions: None,
+            extras: None,
+            name: name.clone(),
+            primitives,
+            weights: None,
+        });
+
+        let (scale, rotation, translation) = element.transform.to_scale_rotation_translation();
+        nodes.push(root.push(json::Node {
+            mesh: Some(mesh),
+            name,
+            translation: Some(translation.to_array()),
+            rotation: Some(UnitQuaternion(rotation.to_array())),
+            scale: Some(scale.to_array()),
+            extras: element_extras(element.header),
+            ..default()
+        }));
+    }
+
+    let GlbBuilder { mut root, buffer, bin } = builder;
+    root.buffers[buffer.value()].byte_length = USize64(bin.len() as u64);
+    let scene = root.push(json::Scene {
+        extensions: None,
+        extras: None,
+        name: None,
+        nodes,
+    });
+    root.scene = Some(scene);
+
+    let json = json::serialize::to_vec(&root)?;
+    Glb {
+        header: Header {
+            magic: *b"glTF",
+            version: 2,
+            // Worked out by the writer
+            length: 0,
+        },
+        json: Cow::Owned(json),
+        bin: Some(Cow::Owned(bin)),
+    }
+    .to_vec()
+}
+
+fn write_material(material: &StandardMaterial, texture: Option<Index<json::Texture>>) -> json::Material {
+    let base = material.base_color.to_linear();
+    let emissive = material.emissive;
+
+    let (alpha_mode, alpha_cutoff) = match material.alpha_mode {
+        AlphaMode::Opaque => (GltfAlphaMode::Opaque, None),
+        AlphaMode::Mask(cutoff) => (GltfAlphaMode::Mask, Some(AlphaCutoff(cutoff))),
+        _ => (GltfAlphaMode::Blend, None),
+    };
+
+    json::Material {
+        alpha_cutoff,
+        alpha_mode: Checked::Valid(alpha_mode),
+        double_sided: material.double_sided,
+        pbr_metallic_roughness: json::material::PbrMetallicRoughness {
+            base_color_factor: PbrBaseColorFactor(base.to_f32_array()),
+            base_color_texture: texture.map(|index| json::texture::Info {
+                index,
+                tex_coord: 0,
+                extensions: None,
+                extras: None,
+            }),
+            metallic_factor: StrengthFactor(material.metallic),
+            roughness_factor: StrengthFactor(material.perceptual_roughness),
+            ..default()
+        },
+        emissive_factor: EmissiveFactor([emissive.red, emissive.green, emissive.blue].map(|c| c.clamp(0.0, 1.0))),
+        ..default()
+    }
+}
+
+// Id, kind and parameters so the element can be found again in a viewer or a round trip
+fn element_extras(header: &ElementHeader) -> json::Extras {
+    let params: Value = header
+        .params
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                ParamValue::Bool(value) => Value::from(*value),
+                ParamValue::Int(value) => Value::from(*value),
+                ParamValue::Float(value) => Value::from(*value),
+                ParamValue::Text(_) | ParamValue::ElemenentRef(_) => Value::from(value.to_string()),
+            };
+            (key.0.clone(), value)
+        })
+        .collect();
+
+    let mut extras: Vec<(String, Value)> = vec![
+        (EXTRAS_ELEMENT_ID.to_owned(), Value::from(header.id.0)),
+        (EXTRAS_KIND.to_owned(), Value::from(header.kind.to_string())),
+        (EXTRAS_PARAMS.to_owned(), params),
+    ];
+    // Predefined type with its kind, e.g. DuctSegment(RIGIDSEGMENT)
+    if let Some(kind_type) = &header.kind_type {
+        extras.push((EXTRAS_KIND_TYPE.to_owned(), Value::from(format!("{kind_type:?}"))));
+    }
+
+    let extras: Value = extras.into_iter().collect();
+    RawValue::from_string(extras.to_string()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::gltf_read::read_gltf_slice;
+    use bevy::asset::uuid::Uuid;
+    use new_core::element::{ElementId, ElementParams};
+    use new_core::elements::ElementKind;
+    use std::path::Path;
+
+    #[test]
+    fn an_element_in_two_materials_keeps_both() {
+        let header = ElementHeader {
+            id: ElementId(7),
+            name: Some("Door".to_owned()),
+            kind: ElementKind::Door,
+            kind_type: None,
+            spec_id: None,
+            level_id: None,
+            params: ElementParams::new(),
+        };
+        let (leaf, handle) = (Mesh::from(Cuboid::new(0.9, 2.1, 0.05)), Mesh::from(Sphere::new(0.03)));
+        let (oak, steel) = (
+            StandardMaterial::from_color(Color::srgb(0.6, 0.4, 0.2)),
+            StandardMaterial::from_color(Color::srgb(0.7, 0.7, 0.7)),
+        );
+        let material = |index: u128, material| Some((AssetId::Uuid { uuid: Uuid::from_u128(index) }, material));
+        let element = ExportedElement {
+            header: &header,
+            parts: vec![
+                ExportedPart {
+                    mesh: &leaf,
+                    material: material(1, &oak),
+                    texture: None,
+                },
+                ExportedPart {
+                    mesh: &handle,
+                    material: material(2, &steel),
+                    texture: None,
+                },
+            ],
+            transform: GlobalTransform::from_xyz(3.0, 0.0, 1.0),
+        };
+
+        let glb = model_glb(&[element]).expect("written");
+        let scene = read_gltf_slice(&glb, Path::new(".")).expect("read back");
+
+        assert_eq!(scene.materials.len(), 2);
+        let materials: Vec<Option<usize>> = scene.meshes.iter().map(|mesh| mesh.material).collect();
+        assert_eq!(materials, [Some(0), Some(1)]);
+        assert!(scene.meshes.iter().all(|mesh| mesh.kind == Some(ElementKind::Door)));
+        assert!(scene.meshes.iter().all(|mesh| mesh.transform.translation == Vec3::new(3.0, 0.0, 1.0)));
+        assert_eq!(scene.meshes[1].mesh.count_vertices(), handle.count_vertices());
+    }
+}
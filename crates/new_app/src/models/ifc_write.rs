@@ -0,0 +1,339 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use new_core::element::ElementHeader;
+use new_core::elements::ElementKindType;
+use new_core::sequence::Date;
+use new_core::structural_grid::{AxisFamily, GridAxis, StructuralGrid};
+
+use crate::analysis::clash::shape::Triangle;
+
+// Characters of the compressed GUID, IFC's own base 64
+const GUID_CHARACTERS: &[u8; 64] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_$";
+// Points closer than this share one entry of a face set, meters
+const WELD_TOLERANCE: f32 = 1.0e-5;
+// 64 bit FNV-1a
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// One element as it goes into the file, triangles in world space
+pub struct IfcElement<'a> {
+    pub header: &'a ElementHeader,
+    pub triangles: Vec<Triangle>,
+}
+
+pub struct IfcGrid<'a> {
+    pub header: &'a ElementHeader,
+    pub grid: &'a StructuralGrid,
+}
+
+// Entity instances of the DATA section, numbered as they are added
+struct StepWriter {
+    data: String,
+    count: usize,
+}
+
+impl StepWriter {
+    fn add(&mut self, entity: impl AsRef<str>) -> usize {
+        self.count += 1;
+        let _ = writeln!(self.data, "#{}={};", self.count, entity.as_ref());
+        self.count
+    }
+}
+
+// 128 bits from the seed, written in 22 characters the way IFC compresses GUIDs. The same
+// element gets the same id in every export, so a viewer can match two exports up. FNV is
+// used because it is fixed, unlike the standard library's hasher.
+fn global_id(seed: &str) -> String {
+    let half = |salt: u8| {
+        std::iter::once(salt)
+            .chain(seed.bytes())
+            .fold(FNV_OFFSET, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME))
+    };
+    let bits = (u128::from(half(0)) << 64) | u128::from(half(1));
+
+    let mut id = String::with_capacity(22);
+    id.push(GUID_CHARACTERS[(bits >> 126) as usize] as char);
+    for index in (0..21).rev() {
+        id.push(GUID_CHARACTERS[((bits >> (index * 6)) & 63) as usize] as char);
+    }
+    id
+}
+
+// Quoted STEP string, anything past ASCII as \X2\ or \X4\ hex
+fn text(value: &str) -> String {
+    let mut out = String::from("'");
+    for character in value.chars() {
+        match character {
+            '\'' => out.push_str("''"),
+            '\\' => out.push_str("\\\\"),
+            ' '..='~' => out.push(character),
+            _ if u32::from(character) <= 0xFFFF => {
+                let _ = write!(out, "\\X2\\{:04X}\\X0\\", u32::from(character));
+            }
+            _ => {
+                let _ = write!(out, "\\X4\\{:08X}\\X0\\", u32::from(character));
+            }
+        }
+    }
+    out.push('\'');
+    out
+}
+
+// STEP reals always carry the decimal point
+fn real(value: f32) -> String {
+    let value = if value.abs() < 1.0e-9 { 0.0 } else { value };
+    let fixed = format!("{value:.6}");
+    fixed.trim_end_matches('0').to_owned()
+}
+
+fn references(ids: &[usize]) -> String {
+    let ids: Vec<String> = ids.iter().map(|id| format!("#{id}")).collect();
+    format!("({})", ids.join(","))
+}
+
+// Model space is Y up with north at -Z, IFC is Z up with north at +Y
+fn ifc_point(point: Vec3) -> [f32; 3] {
+    [point.x, -point.z, point.y]
+}
+
+// IFC4 STEP file of the elements as proxies with triangulated bodies, and of the structural
+// grids as IfcGrid with their lettered axes as U and numbered ones as V. Everything goes on
+// one building storey at zero.
+pub fn model_ifc(project: &str, file_name: &str, elements: &[IfcElement], grids: &[IfcGrid]) -> String {
+    let mut step = StepWriter {
+        data: String::new(),
+        count: 0,
+    };
+
+    let origin = step.add("IFCCARTESIANPOINT((0.,0.,0.))");
+    let world = step.add(format!("IFCAXIS2PLACEMENT3D(#{origin},$,$)"));
+    let context = step.add(format!(
+        "IFCGEOMETRICREPRESENTATIONCONTEXT($,'Model',3,1.E-05,#{world},$)"
+    ));
+    let body = step.add(format!(
+        "IFCGEOMETRICREPRESENTATIONSUBCONTEXT('Body','Model',*,*,*,*,#{context},$,.MODEL_VIEW.,$)"
+    ));
+    let footprint = step.add(format!(
+        "IFCGEOMETRICREPRESENTATIONSUBCONTEXT('FootPrint','Model',*,*,*,*,#{context},$,.MODEL_VIEW.,$)"
+    ));
+
+    let units = [
+        ".LENGTHUNIT.,$,.METRE.",
+        ".AREAUNIT.,$,.SQUARE_METRE.",
+        ".VOLUMEUNIT.,$,.CUBIC_METRE.",
+        ".PLANEANGLEUNIT.,$,.RADIAN.",
+    ]
+    .map(|unit| step.add(format!("IFCSIUNIT(*,{unit})")));
+    let units = step.add(format!("IFCUNITASSIGNMENT({})", references(&units)));
+
+    let project = step.add(format!(
+        "IFCPROJECT('{}',$,{},$,$,$,$,(#{context}),#{units})",
+        global_id(&format!("project/{project}")),
+        text(project),
+    ));
+
+    let site_placement = step.add(format!("IFCLOCALPLACEMENT($,#{world})"));
+    let site = step.add(format!(
+        "IFCSITE('{}',$,'Site',$,$,#{site_placement},$,$,.ELEMENT.,$,$,$,$,$)",
+        global_id("site")
+    ));
+    let building_placement = step.add(format!("IFCLOCALPLACEMENT(#{site_placement},#{world})"));
+    let building = step.add(format!(
+        "IFCBUILDING('{}',$,'Building',$,$,#{building_placement},$,$,.ELEMENT.,$,$,$)",
+        global_id("building")
+    ));
+    let storey_placement = step.add(format!("IFCLOCALPLACEMENT(#{building_placement},#{world})"));
+    let storey = step.add(format!(
+        "IFCBUILDINGSTOREY('{}',$,'Level 0',$,$,#{storey_placement},$,$,.ELEMENT.,0.)",
+        global_id("storey")
+    ));
+
+    for (name, whole, part) in [
+        ("project", project, site),
+        ("site", site, building),
+        ("building", building, storey),
+    ] {
+        step.add(format!(
+            "IFCRELAGGREGATES('{}',$,$,$,#{whole},(#{part}))",
+            global_id(&format!("aggregates/{name}"))
+        ));
+    }
+
+    let mut contained = Vec::new();
+    for element in elements {
+        let Some(shape) = face_set(&mut step, &element.triangles) else {
+            continue;
+        };
+        let representation = step.add(format!(
+            "IFCSHAPEREPRESENTATION(#{body},'Body','Tessellation',(#{shape}))"
+        ));
+        let product = step.add(format!("IFCPRODUCTDEFINITIONSHAPE($,$,(#{representation}))"));
+        let placement = step.add(format!("IFCLOCALPLACEMENT(#{storey_placement},#{world})"));
+
+        let header = element.header;
+        let name = header.name.clone().unwrap_or_else(|| header.kind.to_string());
+        contained.push(step.add(format!(
+            "IFCBUILDINGELEMENTPROXY('{}',$,{},$,{},#{placement},#{product},'{}',.NOTDEFINED.)",
+            global_id(&format!("element/{}", header.id.0)),
+            text(&name),
+            text(&header.kind.to_string()),
+            header.id.0,
+        )));
+    }
+
+    for grid in grids {
+        match grid_entity(&mut step, grid, footprint, storey_placement) {
+            Some(id) => contained.push(id),
+            None => warn!(
+                "Grid {} needs lettered and numbered axes to be written as IfcGrid",
+                grid.header.id.0
+            ),
+        }
+    }
+
+    if !contained.is_empty() {
+        step.add(format!(
+            "IFCRELCONTAINEDINSPATIALSTRUCTURE('{}',$,$,$,{},#{storey})",
+            global_id("contained"),
+            references(&contained)
+        ));
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "ISO-10303-21;");
+    let _ = writeln!(out, "HEADER;");
+    let _ = writeln!(out, "FILE_DESCRIPTION(('ViewDefinition [ReferenceView_V1.2]'),'2;1');");
+    let _ = writeln!(
+        out,
+        "FILE_NAME({},'{}T00:00:00',(''),(''),'Monolith','Monolith','');",
+        text(file_name),
+        Date::today()
+    );
+    let _ = writeln!(out, "FILE_SCHEMA(('IFC4'));");
+    let _ = writeln!(out, "ENDSEC;");
+    let _ = writeln!(out, "DATA;");
+    out.push_str(&step.data);
+    let _ = writeln!(out, "ENDSEC;");
+    let _ = writeln!(out, "END-ISO-10303-21;");
+    out
+}
+
+// Triangles welded into a face set, None when there are none
+fn face_set(step: &mut StepWriter, triangles: &[Triangle]) -> Option<usize> {
+    if triangles.is_empty() {
+        return None;
+    }
+
+    let key = |point: Vec3| (point / WELD_TOLERANCE).round().as_ivec3().to_array();
+    let mut welded: HashMap<[i32; 3], usize> = HashMap::new();
+    let mut points: Vec<String> = Vec::new();
+    let mut faces: Vec<String> = Vec::new();
+    for triangle in triangles {
+        let corners = triangle.map(|corner| {
+            *welded.entry(key(corner)).or_insert_with(|| {
+                let [x, y, z] = ifc_point(corner);
+                points.push(format!("({},{},{})", real(x), real(y), real(z)));
+                points.len()
+            })
+        });
+        // Slivers that welded into a line or a point are left out
+        if corners[0] != corners[1] && corners[1] != corners[2] && corners[0] != corners[2] {
+            faces.push(format!("({},{},{})", corners[0], corners[1], corners[2]));
+        }
+    }
+    if faces.is_empty() {
+        return None;
+    }
+
+    let list = step.add(format!("IFCCARTESIANPOINTLIST3D(({}))", points.join(",")));
+    Some(step.add(format!(
+        "IFCTRIANGULATEDFACESET(#{list},$,$,({}),$)",
+        faces.join(",")
+    )))
+}
+
+// IfcGrid needs axes both ways, None when one family is empty
+fn grid_entity(step: &mut StepWriter, grid: &IfcGrid, footprint: usize, storey_placement: usize) -> Option<usize> {
+    let family = |family: AxisFamily| -> Vec<&GridAxis> { grid.grid.family(family).collect() };
+    let (u_axes, v_axes) = (family(AxisFamily::Letters), family(AxisFamily::Numbers));
+    if u_axes.is_empty() || v_axes.is_empty() {
+        return None;
+    }
+
+    let mut curves = Vec::new();
+    let mut axis_entities = |step: &mut StepWriter, axes: &[&GridAxis]| -> Vec<usize> {
+        axes.iter()
+            .map(|axis| {
+                let [start, end] = [axis.start, axis.end]
+                    .map(|point| step.add(format!("IFCCARTESIANPOINT(({},{}))", real(point.x), real(point.y))));
+                let curve = step.add(format!("IFCPOLYLINE((#{start},#{end}))"));
+                curves.push(curve);
+                step.add(format!("IFCGRIDAXIS({},#{curve},.T.)", text(&axis.tag)))
+            })
+            .collect()
+    };
+    let u_axes = axis_entities(step, &u_axes);
+    let v_axes = axis_entities(step, &v_axes);
+
+    let curve_set = step.add(format!("IFCGEOMETRICCURVESET({})", references(&curves)));
+    let representation = step.add(format!(
+        "IFCSHAPEREPRESENTATION(#{footprint},'FootPrint','GeometricCurveSet',(#{curve_set}))"
+    ));
+    let product = step.add(format!("IFCPRODUCTDEFINITIONSHAPE($,$,(#{representation}))"));
+
+    // Axes lie in plan at the bottom of the grid
+    let origin = step.add(format!("IFCCARTESIANPOINT((0.,0.,{}))", real(grid.grid.bottom)));
+    let axis_placement = step.add(format!("IFCAXIS2PLACEMENT3D(#{origin},$,$)"));
+    let placement = step.add(format!("IFCLOCALPLACEMENT(#{storey_placement},#{axis_placement})"));
+
+    let header = grid.header;
+    let predefined = match header.kind_type {
+        Some(ElementKindType::Grid(grid_type)) => format!(".{grid_type:?}."),
+        _ => ".NOTDEFINED.".to_owned(),
+    };
+    Some(step.add(format!(
+        "IFCGRID('{}',$,{},$,$,#{placement},#{product},{},{},$,{predefined})",
+        global_id(&format!("element/{}", header.id.0)),
+        text(header.name.as_deref().unwrap_or("Grid")),
+        references(&u_axes),
+        references(&v_axes),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_ids_are_compressed_guids() {
+        for seed in ["", "element 1", "element 2", "grid A", "Ünïcode ✓", &"long ".repeat(200)] {
+            let id = global_id(seed);
+            assert_eq!(id.len(), 22, "{seed}");
+            assert!(id.bytes().all(|byte| GUID_CHARACTERS.contains(&byte)), "{id}");
+            // The first character only carries the top two bits
+            assert!(('0'..='3').contains(&id.chars().next().unwrap_or_default()), "{id}");
+        }
+    }
+
+    #[test]
+    fn a_seed_always_gives_the_same_id() {
+        assert_eq!(global_id("element 42"), global_id(&format!("element {}", 42)));
+        assert_ne!(global_id("element 42"), global_id("element 43"));
+        // Fixed across runs and builds, so exports made apart can be matched up
+        assert_eq!(global_id("element 42"), "1R86D7BNLi5U$8KXTWIHrU");
+    }
+
+    #[test]
+    fn text_is_escaped_for_step() {
+        assert_eq!(text("Level 1"), "'Level 1'");
+        assert_eq!(text("Tom's"), "'Tom''s'");
+        assert_eq!(text("C:\\models\\a.ifc"), "'C:\\\\models\\\\a.ifc'");
+        assert_eq!(text("\"quoted\""), "'\"quoted\"'");
+        assert_eq!(text("Straße"), "'Stra\\X2\\00DF\\X0\\e'");
+        assert_eq!(text("2 m²"), "'2 m\\X2\\00B2\\X0\\'");
+        assert_eq!(text("🏠"), "'\\X4\\0001F3E0\\X0\\'");
+        assert_eq!(text("a\nb"), "'a\\X2\\000A\\X0\\b'");
+    }
+}
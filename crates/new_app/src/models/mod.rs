@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod gltf_read;
+pub mod gltf_write;
+pub mod ifc_write;
+pub mod model_plugin;
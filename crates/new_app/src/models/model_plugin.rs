@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+
+use new_core::action::ActionAppExt;
+use new_core::model_exchange::{ModelCommand, ModelExchange};
+
+use crate::models::commands::{model_actions, run_model_actions, run_model_commands};
+
+pub struct ModelPlugin;
+
+impl Plugin for ModelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ModelExchange>().add_message::<ModelCommand>();
+        for action in model_actions() {
+            app.register_action(action);
+        }
+        app.add_systems(Update, (run_model_actions, run_model_commands).chain());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod preferences_plugin;
+pub mod user_files;
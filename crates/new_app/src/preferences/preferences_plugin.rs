@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+
+use new_core::preferences::{PREFERENCES_FILE, Preferences};
+use new_core::units::ProjectUnits;
+
+use crate::preferences::user_files;
+
+pub struct PreferencesPlugin;
+
+impl Plugin for PreferencesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Preferences>()
+            .init_resource::<ProjectUnits>()
+            .add_systems(PreStartup, (load_preferences, start_project_units).chain())
+            .add_systems(Last, save_preferences);
+    }
+}
+
+// Before anything is set up from them
+fn load_preferences(mut preferences: ResMut<Preferences>) {
+    if let Some(mut loaded) = user_files::read::<Preferences>(PREFERENCES_FILE) {
+        loaded.clamp();
+        *preferences = loaded;
+    }
+}
+
+// Projects are not saved with their own units yet, so each session starts from the defaults
+fn start_project_units(preferences: Res<Preferences>, mut units: ResMut<ProjectUnits>) {
+    *units = preferences.units;
+}
+
+// Seconds without edits before they are written, so dragging a value saves it once
+const SAVE_DELAY: f32 = 1.0;
+
+// Written once the edits settle, or when the app closes before they do
+fn save_preferences(
+    preferences: Res<Preferences>,
+    time: Res<Time>,
+    mut exit: MessageReader<AppExit>,
+    mut idle: Local<Option<f32>>,
+) {
+    if preferences.is_changed() && !preferences.is_added() {
+        *idle = Some(0.0);
+    }
+    let Some(seconds) = idle.as_mut() else {
+        return;
+    };
+    *seconds += time.delta_secs();
+    if *seconds >= SAVE_DELAY || exit.read().count() > 0 {
+        user_files::write(PREFERENCES_FILE, &*preferences);
+        *idle = None;
+    }
+}
@@ -0,0 +1,39 @@
+use std::fs;
+
+use bevy::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use new_core::user_config_path;
+
+// Settings file in the user's config folder. None when there is none yet, or it cannot be
+// read, which leaves the defaults in place.
+pub fn read<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let path = user_config_path(file_name)?;
+    let text = fs::read_to_string(&path).ok()?;
+    match toml::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            warn!("{} could not be read, using defaults: {error}", path.display());
+            None
+        }
+    }
+}
+
+pub fn write<T: Serialize>(file_name: &str, value: &T) {
+    let Some(path) = user_config_path(file_name) else {
+        return;
+    };
+
+    let written = toml::to_string(value)
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+            }
+            fs::write(&path, text).map_err(|error| error.to_string())
+        });
+    if let Err(error) = written {
+        error!("Saving {} failed: {error}", path.display());
+    }
+}
@@ -0,0 +1,67 @@
+use bevy::camera::primitives::MeshAabb;
+use bevy::prelude::*;
+
+use new_core::element::{ElementHeader, ParamKey, ParamValue};
+use new_core::elements::ElementKind;
+use new_core::room::{FROM_ROOM_PARAM, Room, TO_ROOM_PARAM, room_label};
+
+// Rooms are looked for this far either side of a door, meters
+const DOOR_REACH: f32 = 0.6;
+
+type PlacedRoom<'a> = (Ref<'a, ElementHeader>, &'a GlobalTransform, Ref<'a, Room>);
+type Door<'a> = (&'a mut ElementHeader, Ref<'a, GlobalTransform>, Option<&'a Mesh3d>);
+
+// Doors name the rooms either side of them for the door schedule. The side along the door's
+// local axis is To Room, a door with a room on one side only leads into it.
+pub fn name_door_rooms(
+    rooms: Query<PlacedRoom>,
+    mut removed: RemovedComponents<Room>,
+    mut doors: Query<Door, Without<Room>>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    let rooms_changed = removed.read().count() > 0
+        || rooms.iter().any(|(header, _, room)| header.is_changed() || room.is_changed());
+
+    let room_at = |point: Vec3| {
+        rooms.iter().find_map(|(header, transform, room)| {
+            let local = transform.affine().inverse().transform_point3(point);
+            let on_floor = (0.0..=room.height).contains(&local.y) && room.contains(Vec2::new(local.x, -local.z));
+            on_floor.then(|| room_label(&header))
+        })
+    };
+
+    for (mut header, transform, mesh) in &mut doors {
+        if header.kind != ElementKind::Door || (!rooms_changed && !transform.is_changed()) {
+            continue;
+        }
+        let center = mesh
+            .and_then(|mesh| meshes.get(&mesh.0))
+            .and_then(MeshAabb::compute_aabb)
+            .map_or(transform.translation(), |aabb| transform.transform_point(aabb.center.into()));
+
+        // Across the door finds rooms, along it runs into the wall it stands in
+        let sides = [transform.right(), transform.back()].map(|axis| {
+            let axis = axis.with_y(0.0).normalize_or_zero();
+            [room_at(center - axis * DOOR_REACH), room_at(center + axis * DOOR_REACH)]
+        });
+        let [from, to] = sides
+            .into_iter()
+            .max_by_key(|sides| sides.iter().flatten().count())
+            .unwrap_or_default();
+        let (from, to) = match (from, to) {
+            (Some(from), None) => (None, Some(from)),
+            sides => sides,
+        };
+
+        for (key, room) in [(FROM_ROOM_PARAM, from), (TO_ROOM_PARAM, to)] {
+            let key = ParamKey::new(key);
+            let value = room.map(ParamValue::Text);
+            if header.params.get(&key) != value.as_ref() {
+                match value {
+                    Some(value) => header.params.insert(key, value),
+                    None => header.params.remove(&key),
+                };
+            }
+        }
+    }
+}
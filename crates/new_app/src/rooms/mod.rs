@@ -0,0 +1,5 @@
+pub mod door_rooms;
+pub mod room_bounds;
+pub mod room_mesh;
+pub mod room_plugin;
+pub mod room_tool;
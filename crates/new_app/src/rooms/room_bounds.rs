@@ -0,0 +1,106 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use new_core::element::ElementHeader;
+use new_core::room::{CUT_HEIGHT, Room, RoomOutline, bounds_rooms};
+use new_core::structural_grid::world_to_plan;
+
+use crate::analysis::clash::shape::{Triangle, world_triangles};
+
+type BoundingElement<'a> = (&'a ElementHeader, &'a Mesh3d, &'a GlobalTransform);
+
+// Walls and what else closes rooms off, cut through at a height to find room boundaries in
+#[derive(SystemParam)]
+pub struct RoomBounds<'w, 's> {
+    elements: Query<'w, 's, BoundingElement<'static>>,
+    meshes: Res<'w, Assets<Mesh>>,
+}
+
+impl RoomBounds<'_, '_> {
+    // Faces of everything bounding rooms where the plane at the elevation cuts it, in plan
+    pub fn cut(&self, elevation: f32) -> Vec<(Vec2, Vec2)> {
+        let mut faces = Vec::new();
+        for (header, mesh, transform) in &self.elements {
+            if !bounds_rooms(header.kind) {
+                continue;
+            }
+            let Some(triangles) = self.meshes.get(&mesh.0).and_then(|mesh| world_triangles(mesh, transform)) else {
+                continue;
+            };
+            faces.extend(triangles.iter().filter_map(|triangle| cut_triangle(triangle, elevation)));
+        }
+        faces
+    }
+
+    // Boundary around a point on the floor
+    pub fn outline(&self, floor: Vec3) -> Option<RoomOutline> {
+        RoomOutline::find(&self.cut(floor.y + CUT_HEIGHT), world_to_plan(floor))
+    }
+}
+
+// Where the plane crosses the triangle, running counterclockwise around the solid as seen from
+// above. Meshes are taken to be wound with their faces out.
+fn cut_triangle(triangle: &Triangle, elevation: f32) -> Option<(Vec2, Vec2)> {
+    let depths = triangle.map(|point| point.y - elevation);
+    let mut crossings = Vec::with_capacity(2);
+    for i in 0..3 {
+        let j = (i + 1) % 3;
+        let (a, b) = (depths[i], depths[j]);
+        if (a >= 0.0) != (b >= 0.0) {
+            crossings.push(world_to_plan(triangle[i].lerp(triangle[j], a / (a - b))));
+        }
+    }
+    let [a, b] = crossings[..] else {
+        return None;
+    };
+
+    let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+    let out = world_to_plan(normal);
+    let along = b - a;
+    if along.length_squared() <= f32::EPSILON {
+        return None;
+    }
+    // Out of the solid is to the right of the face
+    if Vec2::new(along.y, -along.x).dot(out) >= 0.0 {
+        Some((a, b))
+    } else {
+        Some((b, a))
+    }
+}
+
+type BoundedRoom<'a> = (Ref<'a, GlobalTransform>, &'a mut Room);
+type Reshaped = Or<(Changed<GlobalTransform>, Changed<Mesh3d>)>;
+
+// Rooms find their boundary again when what bounds them moves, or they are moved themselves.
+// Rooms the walls no longer close in keep the boundary they had.
+pub fn bound_rooms(
+    bounds: RoomBounds,
+    changed: Query<&ElementHeader, Reshaped>,
+    mut removed: RemovedComponents<Mesh3d>,
+    mut rooms: Query<BoundedRoom>,
+) {
+    let bounds_changed = removed.read().count() > 0 || changed.iter().any(|header| bounds_rooms(header.kind));
+
+    // Rooms on one floor share the cut
+    let mut cuts: HashMap<i32, Vec<(Vec2, Vec2)>> = HashMap::new();
+    for (transform, mut room) in &mut rooms {
+        if !bounds_changed && !transform.is_changed() {
+            continue;
+        }
+        let floor = transform.translation();
+        let elevation = floor.y + CUT_HEIGHT;
+        let faces = cuts
+            .entry((elevation * 1000.0).round() as i32)
+            .or_insert_with(|| bounds.cut(elevation));
+
+        let found = match RoomOutline::find(faces, world_to_plan(floor)) {
+            Some(outline) => outline.room(&transform, room.height),
+            None => Room {
+                enclosed: false,
+                ..room.clone()
+            },
+        };
+        room.set_if_neq(found);
+    }
+}
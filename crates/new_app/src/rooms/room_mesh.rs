@@ -0,0 +1,50 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::PrimitiveTopology;
+use bevy::prelude::*;
+
+use new_core::element::ElementHeader;
+use new_core::room::{Room, plan};
+
+use crate::geometry::sweep::triangulate;
+
+// Floors are drawn this far above the room's origin so they do not fight with slabs, meters
+const FLOOR_LIFT: f32 = 0.01;
+
+// Floor of rooms that were placed or found a new boundary. What stands free in the room covers
+// the holes in it.
+pub fn room_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    rooms: Query<(Entity, &Room), Changed<Room>>,
+) {
+    for (entity, room) in &rooms {
+        let Some(mesh) = floor_mesh(room) else {
+            continue;
+        };
+        commands.entity(entity).insert(Mesh3d(meshes.add(mesh)));
+    }
+}
+
+fn floor_mesh(room: &Room) -> Option<Mesh> {
+    let outline = room.profile.outline()?;
+    let positions: Vec<[f32; 3]> = triangulate(&plan(outline))
+        .iter()
+        .flatten()
+        .map(|&index| (outline.points[index].to_vec3() + Vec3::Y * FLOOR_LIFT).to_array())
+        .collect();
+    if positions.is_empty() {
+        return None;
+    }
+    Some(
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_computed_flat_normals(),
+    )
+}
+
+// Areas and volume go into the parameters, where schedules and area reports pick them up
+pub fn measure_rooms(mut rooms: Query<(&Room, &mut ElementHeader), Changed<Room>>) {
+    for (room, mut header) in &mut rooms {
+        room.write_params(&mut header);
+    }
+}
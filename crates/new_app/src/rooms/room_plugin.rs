@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+
+use new_core::room::ROOM_TOOL;
+use new_core::tool::ToolDef;
+
+use crate::rooms::door_rooms::name_door_rooms;
+use crate::rooms::room_bounds::bound_rooms;
+use crate::rooms::room_mesh::{measure_rooms, room_meshes};
+use crate::rooms::room_tool::{RoomMaterials, place_room, room_options};
+use crate::tools::framework::activation::tool_active;
+use crate::tools::framework::tool_plugin::{ToolAppExt, ToolSystems};
+
+pub struct RoomPlugin;
+
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_tool(
+            ToolDef {
+                id: ROOM_TOOL,
+                label: "Room",
+                tooltip: "Click on the floor between walls to make a room of what they close in",
+                group: "Architecture",
+                shortcut: None,
+            },
+            room_options(),
+        )
+        .init_resource::<RoomMaterials>()
+        .add_systems(
+            Update,
+            (
+                place_room.run_if(tool_active(ROOM_TOOL)).after(ToolSystems),
+                bound_rooms,
+                (room_meshes, measure_rooms),
+                name_door_rooms,
+            )
+                .chain(),
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::camera::visibility::RenderLayers;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use new_core::anchor::AnchorStrategy;
+use new_core::element::{ElementHeader, ElementId, ElementIdAllocator, ElementParams, ParamKey, ParamValue};
+use new_core::elements::element_kindtype_enums::SpaceType;
+use new_core::elements::{ElementKind, ElementKindType};
+use new_core::phase::{ElementPhasing, ViewPhases};
+use new_core::room::{NUMBER_PARAM, ROOM_TOOL, RoomOutline, next_room_number};
+use new_core::tool::{ToolEvent, ToolOption, ToolOptions, ToolValue};
+use new_core::units::Measure;
+
+use crate::editor::anchoring::anchor_cast::{AnchorCast, ViewportRay};
+use crate::editor::selection::picking::Selectable;
+use crate::rooms::room_bounds::RoomBounds;
+
+pub const HEIGHT_OPTION: &str = "Height";
+pub const TYPE_OPTION: &str = "Type";
+
+// Floor to ceiling of new rooms, meters
+const DEFAULT_HEIGHT: f32 = 2.7;
+// Levels this close below the floor clicked still count as the one it is on
+const LEVEL_TOLERANCE: f32 = 0.01;
+
+const TYPES: [&str; 3] = ["Internal", "External", "Parking"];
+
+pub fn room_options() -> Vec<ToolOption> {
+    vec![
+        ToolOption {
+            key: HEIGHT_OPTION,
+            value: ToolValue::Number {
+                value: DEFAULT_HEIGHT,
+                measure: Measure::Length,
+            },
+        },
+        ToolOption {
+            key: TYPE_OPTION,
+            value: ToolValue::Choice {
+                choices: TYPES.map(str::to_owned).to_vec(),
+                selected: 0,
+            },
+        },
+    ]
+}
+
+#[derive(Resource)]
+pub struct RoomMaterials {
+    floor: Handle<StandardMaterial>,
+}
+
+impl FromWorld for RoomMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self {
+            floor: materials.add(StandardMaterial {
+                base_color: Color::srgba(0.35, 0.6, 0.95, 0.3),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            }),
+        }
+    }
+}
+
+pub struct NewRoom {
+    // Where it was clicked, the room's origin
+    pub floor: Vec3,
+    pub level_id: Option<ElementId>,
+    pub number: String,
+    pub space_type: SpaceType,
+    pub height: f32,
+    pub outline: RoomOutline,
+}
+
+// Spawns rooms in the phase of the view they are placed in
+#[derive(SystemParam)]
+pub struct RoomSpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    ids: ResMut<'w, ElementIdAllocator>,
+    materials: Res<'w, RoomMaterials>,
+    view_phases: Res<'w, ViewPhases>,
+}
+
+impl RoomSpawner<'_, '_> {
+    pub fn spawn(&mut self, pane_id: u32, new: NewRoom) {
+        let placed = GlobalTransform::from_translation(new.floor);
+        let room = new.outline.room(&placed, new.height);
+
+        let mut header = ElementHeader {
+            id: self.ids.allocate(),
+            name: Some("Room".to_owned()),
+            kind: ElementKind::Space,
+            kind_type: Some(ElementKindType::Space(new.space_type)),
+            spec_id: None,
+            level_id: new.level_id,
+            params: ElementParams::from([(ParamKey::new(NUMBER_PARAM), ParamValue::Text(new.number))]),
+        };
+        room.write_params(&mut header);
+
+        self.commands.spawn((
+            header,
+            room,
+            MeshMaterial3d(self.materials.floor.clone()),
+            Transform::from_translation(new.floor),
+            Visibility::default(),
+            RenderLayers::layer(0),
+            Selectable,
+            ElementPhasing {
+                created: self.view_phases.get(pane_id).phase,
+                demolished: None,
+            },
+        ));
+    }
+}
+
+// A click on the floor between walls makes a room of what they close in, numbered on from the
+// rooms already on that level
+pub fn place_room(
+    mut events: MessageReader<ToolEvent>,
+    viewport: ViewportRay,
+    mut cast: AnchorCast,
+    options: Res<ToolOptions>,
+    elements: Query<(&ElementHeader, &GlobalTransform)>,
+    bounds: RoomBounds,
+    mut spawner: RoomSpawner,
+) {
+    for event in events.read() {
+        let ToolEvent::Picked { tool, pane_id, cursor } = *event else {
+            continue;
+        };
+        if tool != ROOM_TOOL {
+            continue;
+        }
+        let Some(ray) = viewport.ray(pane_id, cursor) else {
+            continue;
+        };
+        let Some(anchored) = cast.anchor_in_view(pane_id, AnchorStrategy::Float, ray) else {
+            continue;
+        };
+        let floor = anchored.transform.translation;
+
+        let Some(outline) = bounds.outline(floor) else {
+            warn!("No walls close in the point clicked");
+            continue;
+        };
+
+        let mut levels: Vec<(ElementId, f32)> = elements
+            .iter()
+            .filter(|(header, _)| header.kind == ElementKind::BuildingStorey)
+            .map(|(header, transform)| (header.id, transform.translation().y))
+            .collect();
+        levels.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let level = levels.iter().rposition(|(_, elevation)| *elevation <= floor.y + LEVEL_TOLERANCE);
+        let level_id = level.map(|level| levels[level].0);
+
+        let taken: Vec<String> = elements
+            .iter()
+            .filter(|(header, _)| header.kind == ElementKind::Space && header.level_id == level_id)
+            .filter_map(|(header, _)| header.params.get(&ParamKey::new(NUMBER_PARAM)).map(ToString::to_string))
+            .collect();
+
+        let space_type = match options.choice(ROOM_TOOL, TYPE_OPTION) {
+            Some("External") => SpaceType::EXTERNAL,
+            Some("Parking") => SpaceType::PARKING,
+            _ => SpaceType::INTERNAL,
+        };
+        spawner.spawn(
+            pane_id,
+            NewRoom {
+                floor,
+                level_id,
+                number: next_room_number(taken.iter().map(String::as_str), level.unwrap_or(0)),
+                space_type,
+                height: options.number(ROOM_TOOL, HEIGHT_OPTION).unwrap_or(DEFAULT_HEIGHT).max(0.1),
+                outline,
+            },
+        );
+    }
+}
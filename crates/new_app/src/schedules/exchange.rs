@@ -0,0 +1,280 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::Path;
+
+use new_core::element::{ElementHeader, ElementId};
+use new_core::exchange::{ExchangeCommand, ImportPreview, Sheet, SheetExchange};
+use new_core::schedule::{ScheduleTables, Schedules};
+
+use crate::schedules::sheet_io::{read_sheet, write_sheet};
+
+pub fn run_exchange_commands(
+    mut commands: MessageReader<ExchangeCommand>,
+    mut exchange: ResMut<SheetExchange>,
+    schedules: Res<Schedules>,
+    tables: Res<ScheduleTables>,
+    mut headers: Query<(Entity, &mut ElementHeader)>,
+) {
+    for command in commands.read() {
+        let status = match command {
+            ExchangeCommand::ExportSchedule { schedule, path } => {
+                let Some(definition) = schedules.get(*schedule) else {
+                    continue;
+                };
+                let rows = tables.rows.get(schedule).map(Vec::as_slice).unwrap_or_default();
+                let sheet = Sheet::from_schedule(definition, rows);
+                export(&sheet, path)
+            }
+            ExchangeCommand::ExportParameters { path } => {
+                let sheet = Sheet::parameters(headers.iter().map(|(_, header)| header), &tables.model_params);
+                export(&sheet, path)
+            }
+            ExchangeCommand::Import { schedule, path } => match read_sheet(path) {
+                Ok(sheet) => {
+                    let columns = schedule
+                        .and_then(|id| schedules.get(id))
+                        .map(|definition| definition.columns.as_slice())
+                        .unwrap_or_default();
+                    let elements: HashMap<ElementId, (Entity, &ElementHeader)> = headers
+                        .iter()
+                        .map(|(entity, header)| (header.id, (entity, header)))
+                        .collect();
+
+                    let preview =
+                        ImportPreview::build(path.clone(), &sheet, columns, &tables.model_params, &elements);
+                    let status = format!(
+                        "Read {} rows from {}: {} changes, {} errors",
+                        preview.rows,
+                        path.display(),
+                        preview.changes.len(),
+                        preview.errors()
+                    );
+                    exchange.preview = Some(preview);
+                    status
+                }
+                Err(error) => format!("Import of {} failed: {error}", path.display()),
+            },
+            ExchangeCommand::ApplyImport => match exchange.preview.take() {
+                Some(preview) => apply_import(&preview, &mut headers),
+                None => continue,
+            },
+            ExchangeCommand::DiscardImport => {
+                exchange.preview = None;
+                "Import discarded".to_owned()
+            }
+        };
+
+        info!("{status}");
+        exchange.status = Some(status);
+    }
+}
+
+fn export(sheet: &Sheet, path: &Path) -> String {
+    match write_sheet(sheet, path) {
+        Ok(()) => format!("Exported {} rows to {}", sheet.rows.len(), path.display()),
+        Err(error) => format!("Export to {} failed: {error}", path.display()),
+    }
+}
+
+// All or nothing. Changes are written to copies first and only stored once every one of them took,
+// an element edited since the preview was built cancels the whole import.
+fn apply_import(preview: &ImportPreview, headers: &mut Query<(Entity, &mut ElementHeader)>) -> String {
+    if !preview.can_apply() {
+        return "Nothing to apply".to_owned();
+    }
+
+    let mut staged: HashMap<Entity, ElementHeader> = HashMap::new();
+
+    for change in &preview.changes {
+        let header = match staged.entry(change.entity) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let Ok((_, header)) = headers.get(change.entity) else {
+                    return format!("Import cancelled, element #{} no longer exists", change.element.0);
+                };
+                entry.insert(header.clone())
+            }
+        };
+
+        if change.field.read(header) != change.old {
+            return format!(
+                "Import cancelled, element #{} changed since the preview, import again",
+                change.element.0
+            );
+        }
+        if !change.field.write(header, change.new.clone()) {
+            return format!(
+                "Import cancelled, {} of element #{} can not take {:?}",
+                change.field.label(),
+                change.element.0,
+                change.new
+            );
+        }
+    }
+
+    let elements = staged.len();
+    for (entity, staged) in staged {
+        if let Ok((_, mut header)) = headers.get_mut(entity) {
+            *header = staged;
+        }
+    }
+
+    format!("Applied {} changes to {elements} elements", preview.changes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use new_core::element::{ParamKey, ParamType, ParamValue};
+    use new_core::elements::ElementKind;
+    use new_core::exchange::{IssueLevel, SheetText};
+    use new_core::schedule::ScheduleField;
+
+    fn width() -> ParamKey {
+        ParamKey::new("Width")
+    }
+
+    fn wall(id: i64, width_value: f64) -> ElementHeader {
+        ElementHeader {
+            id: ElementId(id),
+            name: Some(format!("Wall {id}")),
+            kind: ElementKind::Wall,
+            kind_type: None,
+            spec_id: None,
+            level_id: None,
+            params: BTreeMap::from([(width(), ParamValue::Float(width_value))]),
+        }
+    }
+
+    fn model_params() -> BTreeMap<ParamKey, ParamType> {
+        BTreeMap::from([(width(), ParamType::Float)])
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.spawn(wall(1, 0.2));
+        world.spawn(wall(2, 0.3));
+        world
+    }
+
+    fn preview(world: &mut World, sheet: &SheetText) -> ImportPreview {
+        let mut headers = world.query::<(Entity, &ElementHeader)>();
+        let elements: HashMap<ElementId, (Entity, &ElementHeader)> =
+            headers.iter(world).map(|(entity, header)| (header.id, (entity, header))).collect();
+        ImportPreview::build(PathBuf::from("walls.csv"), sheet, &[], &model_params(), &elements)
+    }
+
+    fn apply(world: &mut World, preview: ImportPreview) -> String {
+        world
+            .run_system_once(move |mut headers: Query<(Entity, &mut ElementHeader)>| {
+                apply_import(&preview, &mut headers)
+            })
+            .expect("apply runs")
+    }
+
+    fn widths(world: &mut World) -> Vec<(i64, Option<ParamValue>)> {
+        let mut headers = world.query::<&ElementHeader>();
+        let mut widths: Vec<(i64, Option<ParamValue>)> = headers
+            .iter(world)
+            .map(|header| (header.id.0, header.params.get(&width()).cloned()))
+            .collect();
+        widths.sort_by_key(|(id, _)| *id);
+        widths
+    }
+
+    // Exports the parameters to CSV, sets a cell of the element's row and reads the file back
+    fn edited_export(world: &mut World, name: &str, edits: &[(i64, &str)]) -> SheetText {
+        let mut headers = world.query::<&ElementHeader>();
+        let sheet = Sheet::parameters(headers.iter(world), &model_params());
+        let column = sheet.headings.iter().position(|heading| heading == "Width").expect("width column");
+
+        let path = std::env::temp_dir().join(format!("{}-{name}.csv", std::process::id()));
+        write_sheet(&sheet, &path).expect("exported");
+
+        let mut exported = read_sheet(&path).expect("read back");
+        for (id, text) in edits {
+            let row = exported.rows.iter_mut().find(|row| row[0] == id.to_string()).expect("row");
+            row[column] = (*text).to_owned();
+        }
+        let mut writer = csv::Writer::from_path(&path).expect("saved");
+        writer.write_record(&exported.headings).expect("saved");
+        for row in &exported.rows {
+            writer.write_record(row).expect("saved");
+        }
+        writer.flush().expect("saved");
+
+        let imported = read_sheet(&path).expect("imported");
+        std::fs::remove_file(&path).ok();
+        imported
+    }
+
+    #[test]
+    fn an_edited_cell_comes_back_as_the_only_change() {
+        let mut world = world();
+        let sheet = edited_export(&mut world, "edited", &[(2, "0.25")]);
+        let preview = preview(&mut world, &sheet);
+
+        assert_eq!(preview.rows, 2);
+        assert_eq!(preview.unchanged, 1);
+        assert!(preview.issues.is_empty(), "{:?}", preview.issues);
+        assert_eq!(preview.changes.len(), 1);
+        let change = &preview.changes[0];
+        assert_eq!(change.element, ElementId(2));
+        assert_eq!(change.field, ScheduleField::Param(width(), ParamType::Float));
+        assert_eq!(change.old, Some(ParamValue::Float(0.3)));
+        assert_eq!(change.new, Some(ParamValue::Float(0.25)));
+
+        assert!(preview.can_apply());
+        assert_eq!(apply(&mut world, preview), "Applied 1 changes to 1 elements");
+        assert_eq!(
+            widths(&mut world),
+            vec![(1, Some(ParamValue::Float(0.2))), (2, Some(ParamValue::Float(0.25)))]
+        );
+    }
+
+    #[test]
+    fn a_value_of_the_wrong_type_rejects_the_whole_import() {
+        let mut world = world();
+        let sheet = edited_export(&mut world, "mismatch", &[(1, "wide"), (2, "0.25")]);
+        let preview = preview(&mut world, &sheet);
+
+        assert_eq!(preview.errors(), 1);
+        let issue = &preview.issues[0];
+        assert_eq!(issue.level, IssueLevel::Error);
+        assert_eq!((issue.row, issue.column.as_deref()), (Some(2), Some("Width")));
+        assert_eq!(preview.changes.len(), 1);
+        assert!(!preview.can_apply());
+
+        assert_eq!(apply(&mut world, preview), "Nothing to apply");
+        assert_eq!(
+            widths(&mut world),
+            vec![(1, Some(ParamValue::Float(0.2))), (2, Some(ParamValue::Float(0.3)))]
+        );
+    }
+
+    #[test]
+    fn an_element_edited_after_the_preview_cancels_every_change() {
+        let mut world = world();
+        let sheet = edited_export(&mut world, "stale", &[(1, "0.15"), (2, "0.25")]);
+        let preview = preview(&mut world, &sheet);
+        assert_eq!(preview.changes.len(), 2);
+
+        let mut headers = world.query::<&mut ElementHeader>();
+        for mut header in headers.iter_mut(&mut world) {
+            if header.id == ElementId(2) {
+                header.params.insert(width(), ParamValue::Float(0.4));
+            }
+        }
+
+        assert!(apply(&mut world, preview).starts_with("Import cancelled, element #2 changed"));
+        assert_eq!(
+            widths(&mut world),
+            vec![(1, Some(ParamValue::Float(0.2))), (2, Some(ParamValue::Float(0.4)))]
+        );
+    }
+}
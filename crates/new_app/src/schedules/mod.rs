@@ -0,0 +1,4 @@
+pub mod exchange;
+pub mod schedule_plugin;
+pub mod sheet_io;
+pub mod tables;
\ No newline at end of file
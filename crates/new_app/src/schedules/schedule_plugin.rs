@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+use new_core::exchange::{ExchangeCommand, SheetExchange};
+use new_core::schedule::{ScheduleEdit, ScheduleTables, Schedules};
+
+use crate::schedules::exchange::run_exchange_commands;
+use crate::schedules::tables::{apply_schedule_edits, refresh_schedule_tables};
+
+pub struct SchedulePlugin;
+
+impl Plugin for SchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Schedules>()
+            .init_resource::<ScheduleTables>()
+            .init_resource::<SheetExchange>()
+            .add_message::<ScheduleEdit>()
+            .add_message::<ExchangeCommand>()
+            .add_systems(
+                Update,
+                (run_exchange_commands, apply_schedule_edits, refresh_schedule_tables).chain(),
+            );
+    }
+}
@@ -0,0 +1,172 @@
+use calamine::{Reader, open_workbook_auto};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::fmt;
+use std::path::Path;
+
+use new_core::element::ParamValue;
+use new_core::exchange::{Sheet, SheetFormat, SheetText};
+
+#[derive(Debug)]
+pub enum SheetError {
+    UnknownFormat,
+    Empty,
+    Io(std::io::Error),
+    Csv(csv::Error),
+    XlsxWrite(XlsxError),
+    XlsxRead(calamine::Error),
+}
+
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SheetError::UnknownFormat => write!(f, "unknown file type, use .csv or .xlsx"),
+            SheetError::Empty => write!(f, "the sheet is empty"),
+            SheetError::Io(error) => write!(f, "{error}"),
+            SheetError::Csv(error) => write!(f, "{error}"),
+            SheetError::XlsxWrite(error) => write!(f, "{error}"),
+            SheetError::XlsxRead(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for SheetError {
+    fn from(error: std::io::Error) -> Self {
+        SheetError::Io(error)
+    }
+}
+
+impl From<csv::Error> for SheetError {
+    fn from(error: csv::Error) -> Self {
+        SheetError::Csv(error)
+    }
+}
+
+impl From<XlsxError> for SheetError {
+    fn from(error: XlsxError) -> Self {
+        SheetError::XlsxWrite(error)
+    }
+}
+
+impl From<calamine::Error> for SheetError {
+    fn from(error: calamine::Error) -> Self {
+        SheetError::XlsxRead(error)
+    }
+}
+
+pub fn write_sheet(sheet: &Sheet, path: &Path) -> Result<(), SheetError> {
+    match SheetFormat::from_path(path).ok_or(SheetError::UnknownFormat)? {
+        SheetFormat::Csv => write_csv(sheet, path),
+        SheetFormat::Xlsx => write_xlsx(sheet, path),
+    }
+}
+
+pub fn read_sheet(path: &Path) -> Result<SheetText, SheetError> {
+    let sheet = match SheetFormat::from_path(path).ok_or(SheetError::UnknownFormat)? {
+        SheetFormat::Csv => read_csv(path)?,
+        SheetFormat::Xlsx => read_xlsx(path)?,
+    };
+
+    if sheet.headings.is_empty() {
+        return Err(SheetError::Empty);
+    }
+    Ok(sheet)
+}
+
+fn write_csv(sheet: &Sheet, path: &Path) -> Result<(), SheetError> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(&sheet.headings)?;
+    for row in &sheet.rows {
+        writer.write_record(row.iter().map(|cell| cell.as_ref().map_or(String::new(), ToString::to_string)))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_csv(path: &Path) -> Result<SheetText, SheetError> {
+    let text = std::fs::read_to_string(path)?;
+
+    // Excel saves with semicolons where the comma is the decimal separator
+    let first_line = text.lines().next().unwrap_or_default();
+    let delimiter = if first_line.matches(';').count() > first_line.matches(',').count() {
+        b';'
+    } else {
+        b','
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let headings = reader.headers()?.iter().map(str::to_owned).collect();
+    let rows = reader
+        .records()
+        .map(|record| record.map(|record| record.iter().map(str::to_owned).collect()))
+        .collect::<Result<_, _>>()?;
+
+    Ok(SheetText { headings, rows })
+}
+
+fn write_xlsx(sheet: &Sheet, path: &Path) -> Result<(), SheetError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+
+    // Sheet names are capped at 31 characters and some are reserved
+    let name: String = sheet
+        .name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect();
+    if !name.trim().is_empty() {
+        worksheet.set_name(name)?;
+    }
+
+    let bold = Format::new().set_bold();
+    for (column, heading) in sheet.headings.iter().enumerate() {
+        worksheet.write_string_with_format(0, column as u16, heading, &bold)?;
+    }
+
+    for (index, row) in sheet.rows.iter().enumerate() {
+        let row_number = index as u32 + 1;
+        for (column, cell) in row.iter().enumerate() {
+            let column = column as u16;
+            match cell {
+                None => {}
+                Some(ParamValue::Bool(value)) => {
+                    worksheet.write_boolean(row_number, column, *value)?;
+                }
+                Some(ParamValue::Int(value)) => {
+                    worksheet.write_number(row_number, column, *value as f64)?;
+                }
+                Some(ParamValue::Float(value)) => {
+                    worksheet.write_number(row_number, column, *value)?;
+                }
+                Some(value) => {
+                    worksheet.write_string(row_number, column, value.to_string())?;
+                }
+            }
+        }
+    }
+
+    worksheet.set_freeze_panes(1, 1)?;
+    worksheet.autofit();
+    workbook.save(path)?;
+    Ok(())
+}
+
+// Reads the first worksheet
+fn read_xlsx(path: &Path) -> Result<SheetText, SheetError> {
+    let mut workbook = open_workbook_auto(path)?;
+    let range = workbook.worksheet_range_at(0).ok_or(SheetError::Empty)??;
+
+    let mut rows = range
+        .rows()
+        .map(|row| row.iter().map(ToString::to_string).collect::<Vec<String>>());
+
+    let headings = rows.next().ok_or(SheetError::Empty)?;
+    Ok(SheetText {
+        headings,
+        rows: rows.collect(),
+    })
+}
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use new_core::element::ElementHeader;
+use new_core::schedule::{ScheduleEdit, ScheduleTables, Schedules};
+
+// Rebuilds the rows of every schedule when elements or definitions change
+pub fn refresh_schedule_tables(
+    schedules: Res<Schedules>,
+    mut tables: ResMut<ScheduleTables>,
+    changed: Query<(), Changed<ElementHeader>>,
+    mut removed: RemovedComponents<ElementHeader>,
+    elements: Query<(Entity, &ElementHeader)>,
+) {
+    let removed_any = removed.read().count() > 0;
+    if !schedules.is_changed() && changed.is_empty() && !removed_any {
+        return;
+    }
+
+    tables.rows.clear();
+    tables.model_params.clear();
+
+    for (_, header) in &elements {
+        for (key, value) in &header.params {
+            tables.model_params.insert(key.clone(), value.param_type());
+        }
+    }
+
+    for definition in &schedules.definitions {
+        let rows = elements
+            .iter()
+            .filter(|(_, header)| definition.includes(header))
+            .map(|(entity, header)| definition.row(entity, header))
+            .collect();
+        tables.rows.insert(definition.id, rows);
+    }
+}
+
+pub fn apply_schedule_edits(
+    mut edits: MessageReader<ScheduleEdit>,
+    mut headers: Query<&mut ElementHeader>,
+) {
+    for edit in edits.read() {
+        let Ok(mut header) = headers.get_mut(edit.entity) else {
+            continue;
+        };
+
+        if !edit.field.write(&mut header, edit.value.clone()) {
+            warn!("Schedule edit rejected for {:?}: {:?}", edit.field, edit.value);
+        }
+    }
+}
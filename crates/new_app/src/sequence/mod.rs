@@ -0,0 +1,2 @@
+pub mod playback;
+pub mod sequence_plugin;
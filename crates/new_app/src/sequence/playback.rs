@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+use new_core::display::{DisplayOverrides, DisplaySource};
+use new_core::element::ElementHeader;
+use new_core::sequence::{ConstructionSequence, ConstructionState, Timeline, TimelineCommand};
+
+use crate::editor::display::resolve::write_override;
+use crate::editor::selection::picking::SelectionState;
+
+// Moves the playhead while playing, stops at the end of the sequence
+pub fn advance_playhead(
+    time: Res<Time>,
+    sequence: Res<ConstructionSequence>,
+    mut timeline: ResMut<Timeline>,
+    mut carry: Local<f32>,
+) {
+    if !timeline.playing {
+        *carry = 0.0;
+        return;
+    }
+
+    let Some((_, end)) = sequence.span() else {
+        timeline.playing = false;
+        return;
+    };
+
+    *carry += time.delta_secs() * timeline.days_per_second;
+    let days = carry.floor();
+    if days < 1.0 {
+        return;
+    }
+    *carry -= days;
+
+    timeline.playhead = timeline.playhead.add_days(days as i32).min(end);
+    if timeline.playhead == end {
+        timeline.playing = false;
+    }
+}
+
+pub fn apply_timeline_commands(
+    mut messages: MessageReader<TimelineCommand>,
+    selection: Res<SelectionState>,
+    headers: Query<&ElementHeader>,
+    mut sequence: ResMut<ConstructionSequence>,
+) {
+    for command in messages.read() {
+        let Some(id) = selection
+            .current
+            .and_then(|entity| headers.get(entity).ok())
+            .map(|header| header.id)
+        else {
+            continue;
+        };
+
+        match *command {
+            TimelineCommand::LinkSelection(task) => {
+                if let Some(task) = sequence.get_mut(task)
+                    && !task.elements.contains(&id)
+                {
+                    task.elements.push(id);
+                }
+            }
+            TimelineCommand::UnlinkSelection(task) => {
+                if let Some(task) = sequence.get_mut(task) {
+                    task.elements.retain(|element| *element != id);
+                }
+            }
+        }
+    }
+}
+
+// Puts every element in the state it has on the playhead day
+pub fn apply_construction_state(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    sequence: Res<ConstructionSequence>,
+    added: Query<(), Added<ElementHeader>>,
+    mut elements: Query<(Entity, &ElementHeader, Option<&mut DisplayOverrides>)>,
+) {
+    if !timeline.is_changed() && !sequence.is_changed() && added.is_empty() {
+        return;
+    }
+
+    let tasks = sequence.tasks_by_element();
+
+    for (entity, header, overrides) in &mut elements {
+        let appearance = timeline.enabled.then(|| {
+            let state = ConstructionState::at(
+                tasks.get(&header.id).into_iter().flatten().copied(),
+                timeline.playhead,
+            );
+            timeline.display.appearance(state)
+        });
+
+        write_override(&mut commands, entity, overrides, DisplaySource::Timeline, appearance);
+    }
+}
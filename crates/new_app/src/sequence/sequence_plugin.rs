@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+use new_core::sequence::{ConstructionSequence, Timeline, TimelineCommand};
+
+use crate::sequence::playback::{
+    advance_playhead,
+    apply_construction_state,
+    apply_timeline_commands,
+};
+
+pub struct SequencePlugin;
+
+impl Plugin for SequencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConstructionSequence>()
+            .init_resource::<Timeline>()
+            .add_message::<TimelineCommand>()
+            .add_systems(
+                Update,
+                (
+                    apply_timeline_commands,
+                    advance_playhead,
+                    apply_construction_state,
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use new_core::element::ElementHeader;
+use new_core::phase::ElementPhasing;
+use new_core::sheet::{SheetDrawings, SheetSet};
+
+use crate::analysis::clash::shape::world_triangles;
+use crate::geometry::projection::{ProjectedElement, project_view};
+use crate::structure::grid_drawing::DrawnGrids;
+
+type ChangedDrawnElements = (
+    With<ElementHeader>,
+    Or<(Changed<GlobalTransform>, Changed<Mesh3d>, Changed<ElementPhasing>)>,
+);
+
+// Redraws every placed view when sheets, drawn elements or grids change
+pub fn refresh_sheet_drawings(
+    sheets: Res<SheetSet>,
+    mut drawings: ResMut<SheetDrawings>,
+    meshes: Res<Assets<Mesh>>,
+    changed: Query<(), ChangedDrawnElements>,
+    mut removed: RemovedComponents<ElementHeader>,
+    elements: Query<(&ElementHeader, &Mesh3d, &GlobalTransform, Option<&ElementPhasing>)>,
+    mut grids: DrawnGrids,
+) {
+    let removed_any = removed.read().count() > 0;
+    let grids_changed = grids.changed();
+    if !sheets.is_changed() && changed.is_empty() && !removed_any && !grids_changed {
+        return;
+    }
+
+    // World triangles once, shared by all views
+    let shapes: Vec<_> = elements
+        .iter()
+        .filter_map(|(header, mesh, transform, phasing)| {
+            let triangles = world_triangles(meshes.get(&mesh.0)?, transform)?;
+            Some((triangles, header.kind, phasing.copied().unwrap_or_default()))
+        })
+        .collect();
+
+    drawings.drawings.clear();
+    for sheet in &sheets.sheets {
+        let views = sheet
+            .placements
+            .iter()
+            .map(|placement| {
+                let elements: Vec<ProjectedElement> = shapes
+                    .iter()
+                    .map(|(triangles, kind, phasing)| ProjectedElement {
+                        triangles,
+                        kind: *kind,
+                        style: placement.view.phase.style(phasing),
+                    })
+                    .collect();
+                let mut drawing = project_view(&elements, &placement.view.kind.frame());
+                grids.draw(&placement.view.kind, &mut drawing);
+                drawing
+            })
+            .collect();
+        drawings.drawings.insert(sheet.id, views);
+    }
+}
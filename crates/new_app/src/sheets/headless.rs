@@ -0,0 +1,163 @@
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::prelude::*;
+use bevy::render::RenderPlugin;
+use bevy::render::settings::WgpuSettings;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use std::path::PathBuf;
+
+use new_core::element::ElementIdAllocator;
+use new_core::model_exchange::ImportAs;
+use new_core::sheet::{SheetDrawings, SheetSet};
+
+use crate::camera::setup_scene::setup_scene;
+use crate::models::commands::{ModelAssets, import_gltf};
+use crate::sheets::publish::publish_sheets;
+use crate::sheets::sheet_file::read_sheet_set;
+use crate::sheets::sheet_plugin::SheetPlugin;
+
+pub struct PublishArgs {
+    pub output: PathBuf,
+    // Sheet set saved from the sheets pane, nothing is published without one
+    pub sheets: Option<PathBuf>,
+    // .gltf or .glb drawn on the sheets, the demo scene without one
+    pub model: Option<PathBuf>,
+}
+
+// `new_app --publish out.pdf --sheets sheets.toml [model.glb]` writes every sheet of the set and
+// quits, no window or GPU needed
+pub fn publish_args() -> Option<PublishArgs> {
+    let mut output = None;
+    let mut sheets = None;
+    let mut model = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--publish" {
+            output = args.next().map(PathBuf::from);
+        } else if arg == "--sheets" {
+            sheets = args.next().map(PathBuf::from);
+        } else if !arg.starts_with("--") {
+            model = Some(PathBuf::from(arg));
+        }
+    }
+    Some(PublishArgs {
+        output: output?,
+        sheets,
+        model,
+    })
+}
+
+#[derive(Resource)]
+struct PublishTarget(PathBuf);
+
+#[derive(Resource)]
+struct PublishModel(PathBuf);
+
+#[derive(Resource)]
+struct PublishSheets(Option<PathBuf>);
+
+pub fn run(args: PublishArgs) -> AppExit {
+    let mut app = App::new();
+    app
+        .add_plugins(
+            DefaultPlugins
+                .build()
+                .disable::<WinitPlugin>()
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                })
+                .set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                }),
+        )
+        .add_plugins(ScheduleRunnerPlugin::default())
+        .add_plugins(SheetPlugin)
+        .init_resource::<ElementIdAllocator>()
+        .insert_resource(PublishTarget(args.output))
+        .insert_resource(PublishSheets(args.sheets))
+        .add_systems(Startup, load_sheets)
+        .add_systems(Last, publish_and_exit);
+
+    match args.model {
+        Some(model) => app
+            .insert_resource(PublishModel(model))
+            .add_systems(Startup, load_model),
+        None => app.add_systems(Startup, setup_scene),
+    };
+
+    app.run()
+}
+
+// The placeholder sheets the app starts with are never published
+fn load_sheets(sheets: Res<PublishSheets>, mut commands: Commands, mut exit: MessageWriter<AppExit>) {
+    let Some(path) = &sheets.0 else {
+        error!("Nothing to publish, give the sheet set saved from the sheets pane with --sheets sheets.toml");
+        exit.write(AppExit::error());
+        return;
+    };
+
+    match read_sheet_set(path) {
+        Ok(set) if set.sheets.is_empty() => {
+            error!("{} has no sheets", path.display());
+            exit.write(AppExit::error());
+        }
+        Ok(set) => {
+            info!("Read {} sheets from {}", set.sheets.len(), path.display());
+            commands.insert_resource(set);
+        }
+        Err(error) => {
+            error!("Reading {} failed: {error}", path.display());
+            exit.write(AppExit::error());
+        }
+    }
+}
+
+// Spawned at startup like the demo scene, so the first frame lays the sheets out with it
+fn load_model(
+    model: Res<PublishModel>,
+    mut commands: Commands,
+    mut ids: ResMut<ElementIdAllocator>,
+    mut assets: ModelAssets,
+    mut exit: MessageWriter<AppExit>,
+) {
+    match import_gltf(&model.0, ImportAs::default(), &mut commands, &mut ids, &mut assets) {
+        Ok(count) => info!("Loaded {count} elements from {}", model.0.display()),
+        Err(error) => {
+            error!("Loading {} failed: {error}", model.0.display());
+            exit.write(AppExit::error());
+        }
+    }
+}
+
+fn publish_and_exit(
+    mut frames: Local<u32>,
+    target: Res<PublishTarget>,
+    sheets: Res<SheetSet>,
+    drawings: Res<SheetDrawings>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    // Transforms and linework are ready after the first full frame
+    *frames += 1;
+    if *frames < 2 {
+        return;
+    }
+
+    let ids: Vec<_> = sheets.sheets.iter().map(|sheet| sheet.id).collect();
+    match publish_sheets(&sheets, &drawings, &ids, &target.0) {
+        Ok(pages) => {
+            info!("Published {pages} sheets to {}", target.0.display());
+            exit.write(AppExit::Success);
+        }
+        Err(error) => {
+            error!("Publishing to {} failed: {error}", target.0.display());
+            exit.write(AppExit::error());
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod drawings;
+pub mod headless;
+pub mod pdf;
+pub mod publish;
+pub mod sheet_file;
+pub mod sheet_plugin;
\ No newline at end of file
@@ -0,0 +1,111 @@
+use pdf_writer::types::{LineCapStyle, LineJoinStyle};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+use new_core::sheet::{SheetGraphics, SheetLine};
+
+// PDF user space is 1/72 inch
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+// Helvetica cap height as a share of the font size
+const CAP_HEIGHT: f32 = 0.718;
+const FONT: Name = Name(b"F1");
+
+// One page per sheet, vector linework and the standard Helvetica so nothing has to be embedded
+pub fn sheets_pdf(title: &str, pages: &[SheetGraphics]) -> Vec<u8> {
+    let mut pdf = Pdf::new();
+
+    let catalog_id = Ref::new(1);
+    let tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+    let info_id = Ref::new(4);
+    let page_ids: Vec<Ref> = (0..pages.len() as i32).map(|i| Ref::new(5 + i * 2)).collect();
+
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id)
+        .kids(page_ids.iter().copied())
+        .count(pages.len() as i32);
+
+    for (graphics, &page_id) in pages.iter().zip(&page_ids) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let size = graphics.size * POINTS_PER_MM;
+
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, size.x, size.y))
+            .parent(tree_id)
+            .contents(content_id);
+        page.resources().fonts().pair(FONT, font_id);
+        page.finish();
+
+        pdf.stream(content_id, &page_content(graphics));
+    }
+
+    pdf.type1_font(font_id)
+        .base_font(Name(b"Helvetica"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.document_info(info_id)
+        .title(TextStr(title))
+        .producer(TextStr("Monolith"));
+
+    pdf.finish()
+}
+
+fn page_content(graphics: &SheetGraphics) -> Vec<u8> {
+    let mut content = Content::new();
+
+    // Draw in millimeters
+    content.transform([POINTS_PER_MM, 0.0, 0.0, POINTS_PER_MM, 0.0, 0.0]);
+    content.set_line_cap(LineCapStyle::RoundCap);
+    content.set_line_join(LineJoinStyle::RoundJoin);
+
+    // One stroke per pen
+    let mut lines: Vec<&SheetLine> = graphics.lines.iter().collect();
+    lines.sort_by(|a, b| pen(a).partial_cmp(&pen(b)).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut current = None;
+    for line in lines {
+        if current != Some(pen(line)) {
+            if current.is_some() {
+                content.stroke();
+            }
+            content.set_line_width(line.weight.mm());
+            content.set_stroke_gray(line.gray);
+            if line.dashed {
+                content.set_dash_pattern([2.0, 1.0], 0.0);
+            } else {
+                content.set_dash_pattern([], 0.0);
+            }
+            current = Some(pen(line));
+        }
+        content.move_to(line.a.x, line.a.y);
+        content.line_to(line.b.x, line.b.y);
+    }
+    if current.is_some() {
+        content.stroke();
+    }
+
+    content.set_fill_gray(0.0);
+    for label in &graphics.labels {
+        let text = win_ansi(&label.text);
+        content
+            .begin_text()
+            .set_font(FONT, label.height / CAP_HEIGHT)
+            .set_text_matrix([1.0, 0.0, 0.0, 1.0, label.position.x, label.position.y])
+            .show(Str(&text))
+            .end_text();
+    }
+
+    content.finish()
+}
+
+fn pen(line: &SheetLine) -> (f32, f32, bool) {
+    (line.weight.mm(), line.gray, line.dashed)
+}
+
+// The standard fonts only know WinAnsi, Latin-1 maps straight onto it
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            code @ (0x20..=0x7e | 0xa0..=0xff) => code as u8,
+            _ => b'?',
+        })
+        .collect()
+}
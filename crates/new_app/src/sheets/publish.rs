@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use std::path::Path;
+
+use new_core::sheet::{SheetCommand, SheetDrawings, SheetId, SheetPublish, SheetSet, compose};
+
+use crate::sheets::pdf::sheets_pdf;
+use crate::sheets::sheet_file::{read_sheet_set, write_sheet_set};
+
+pub fn run_sheet_commands(
+    mut commands: MessageReader<SheetCommand>,
+    mut publish: ResMut<SheetPublish>,
+    mut sheets: ResMut<SheetSet>,
+    drawings: Res<SheetDrawings>,
+) {
+    for command in commands.read() {
+        let status = match command {
+            SheetCommand::Publish { sheets: ids, path } => match publish_sheets(&sheets, &drawings, ids, path) {
+                Ok(pages) => format!("Published {pages} sheets to {}", path.display()),
+                Err(error) => format!("Publishing to {} failed: {error}", path.display()),
+            },
+            SheetCommand::Save { path } => match write_sheet_set(&sheets, path) {
+                Ok(()) => format!("Saved {} sheets to {}", sheets.sheets.len(), path.display()),
+                Err(error) => format!("Saving to {} failed: {error}", path.display()),
+            },
+            SheetCommand::Open { path } => match read_sheet_set(path) {
+                Ok(set) => {
+                    *sheets = set;
+                    format!("Opened {} sheets from {}", sheets.sheets.len(), path.display())
+                }
+                Err(error) => format!("Opening {} failed: {error}", path.display()),
+            },
+        };
+
+        info!("{status}");
+        publish.status = Some(status);
+    }
+}
+
+// Writes the sheets in set order, returns the page count
+pub fn publish_sheets(
+    set: &SheetSet,
+    drawings: &SheetDrawings,
+    ids: &[SheetId],
+    path: &Path,
+) -> std::io::Result<usize> {
+    let pages: Vec<_> = set
+        .sheets
+        .iter()
+        .filter(|sheet| ids.contains(&sheet.id))
+        .map(|sheet| {
+            let views = drawings.drawings.get(&sheet.id).map(Vec::as_slice).unwrap_or_default();
+            compose(set, sheet, views)
+        })
+        .collect();
+
+    std::fs::write(path, sheets_pdf(&set.project, &pages))?;
+    Ok(pages.len())
+}
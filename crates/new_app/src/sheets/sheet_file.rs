@@ -0,0 +1,376 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+use bevy::math::Vec2;
+use new_core::drawing::{DrawingView, ViewKind};
+use new_core::phase::{Phase, PhaseFilter, ViewPhase};
+use new_core::sequence::Date;
+use new_core::sheet::{Orientation, PaperSize, Revision, Sheet, SheetId, SheetNote, SheetSet, ViewPlacement};
+
+// Sheet set kept next to the model as TOML, so sheets can be published without the ui.
+// Names are written the way the sheet pane shows them, like "A3" or "New Construction".
+#[derive(Serialize, Deserialize)]
+struct SheetSetFile {
+    project: String,
+    #[serde(default)]
+    client: String,
+    #[serde(default)]
+    drawn_by: String,
+    #[serde(default)]
+    checked_by: String,
+    #[serde(default)]
+    sheets: Vec<SheetEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SheetEntry {
+    number: String,
+    name: String,
+    paper: String,
+    orientation: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    views: Vec<ViewEntry>,
+    #[serde(default)]
+    notes: Vec<NoteEntry>,
+    #[serde(default)]
+    revisions: Vec<RevisionEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ViewEntry {
+    name: String,
+    #[serde(flatten)]
+    kind: KindEntry,
+    #[serde(default = "default_phase")]
+    phase: String,
+    #[serde(default = "default_filter")]
+    filter: String,
+    scale: u32,
+    center: [f32; 2],
+}
+
+fn default_phase() -> String {
+    ViewPhase::default().phase.to_string()
+}
+
+fn default_filter() -> String {
+    ViewPhase::default().filter.to_string()
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind")]
+enum KindEntry {
+    Plan { cut_height: f32 },
+    Section { facing: String, offset: f32 },
+    Elevation { side: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct NoteEntry {
+    text: String,
+    position: [f32; 2],
+    height: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RevisionEntry {
+    number: u32,
+    date: String,
+    description: String,
+    #[serde(default)]
+    by: String,
+}
+
+#[derive(Debug)]
+pub enum SheetFileError {
+    Io(std::io::Error),
+    Read(toml::de::Error),
+    Write(toml::ser::Error),
+    // A name the sheet set does not know, what it is for and the name
+    Unknown(&'static str, String),
+}
+
+impl fmt::Display for SheetFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SheetFileError::Io(error) => write!(f, "{error}"),
+            SheetFileError::Read(error) => write!(f, "{error}"),
+            SheetFileError::Write(error) => write!(f, "{error}"),
+            SheetFileError::Unknown(what, name) => write!(f, "'{name}' is not a {what}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for SheetFileError {
+    fn from(error: std::io::Error) -> Self {
+        SheetFileError::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for SheetFileError {
+    fn from(error: toml::de::Error) -> Self {
+        SheetFileError::Read(error)
+    }
+}
+
+impl From<toml::ser::Error> for SheetFileError {
+    fn from(error: toml::ser::Error) -> Self {
+        SheetFileError::Write(error)
+    }
+}
+
+pub fn write_sheet_set(set: &SheetSet, path: &Path) -> Result<(), SheetFileError> {
+    std::fs::write(path, sheet_set_text(set)?)?;
+    Ok(())
+}
+
+// The first sheet is made active
+pub fn read_sheet_set(path: &Path) -> Result<SheetSet, SheetFileError> {
+    parse_sheet_set(&std::fs::read_to_string(path)?)
+}
+
+fn sheet_set_text(set: &SheetSet) -> Result<String, SheetFileError> {
+    let file = SheetSetFile {
+        project: set.project.clone(),
+        client: set.client.clone(),
+        drawn_by: set.drawn_by.clone(),
+        checked_by: set.checked_by.clone(),
+        sheets: set
+            .sheets
+            .iter()
+            .map(|sheet| SheetEntry {
+                number: sheet.number.clone(),
+                name: sheet.name.clone(),
+                paper: sheet.paper.to_string(),
+                orientation: sheet.orientation.to_string(),
+                status: sheet.status.clone(),
+                views: sheet
+                    .placements
+                    .iter()
+                    .map(|placement| ViewEntry {
+                        name: placement.view.name.clone(),
+                        kind: match placement.view.kind {
+                            ViewKind::Plan { cut_height } => KindEntry::Plan { cut_height },
+                            ViewKind::Section { facing, offset } => KindEntry::Section {
+                                facing: facing.to_string(),
+                                offset,
+                            },
+                            ViewKind::Elevation { side } => KindEntry::Elevation { side: side.to_string() },
+                        },
+                        phase: placement.view.phase.phase.to_string(),
+                        filter: placement.view.phase.filter.to_string(),
+                        scale: placement.scale,
+                        center: placement.center.to_array(),
+                    })
+                    .collect(),
+                notes: sheet
+                    .notes
+                    .iter()
+                    .map(|note| NoteEntry {
+                        text: note.text.clone(),
+                        position: note.position.to_array(),
+                        height: note.height,
+                    })
+                    .collect(),
+                revisions: sheet
+                    .revisions
+                    .iter()
+                    .map(|revision| RevisionEntry {
+                        number: revision.number,
+                        date: revision.date.to_string(),
+                        description: revision.description.clone(),
+                        by: revision.by.clone(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+    Ok(toml::to_string(&file)?)
+}
+
+fn parse_sheet_set(text: &str) -> Result<SheetSet, SheetFileError> {
+    let file: SheetSetFile = toml::from_str(text)?;
+
+    let mut sheets = Vec::new();
+    for (index, entry) in file.sheets.into_iter().enumerate() {
+        let mut placements = Vec::new();
+        for view in entry.views {
+            let kind = match view.kind {
+                KindEntry::Plan { cut_height } => ViewKind::Plan { cut_height },
+                KindEntry::Section { facing, offset } => ViewKind::Section {
+                    facing: named("compass direction", &facing)?,
+                    offset,
+                },
+                KindEntry::Elevation { side } => ViewKind::Elevation {
+                    side: named("compass direction", &side)?,
+                },
+            };
+            placements.push(ViewPlacement {
+                view: DrawingView {
+                    name: view.name,
+                    kind,
+                    phase: ViewPhase {
+                        phase: named::<Phase>("phase", &view.phase)?,
+                        filter: named::<PhaseFilter>("phase filter", &view.filter)?,
+                    },
+                },
+                scale: view.scale.max(1),
+                center: Vec2::from_array(view.center),
+            });
+        }
+
+        let revisions = entry
+            .revisions
+            .into_iter()
+            .map(|revision| {
+                Ok(Revision {
+                    number: revision.number,
+                    date: Date::parse(&revision.date).ok_or(SheetFileError::Unknown("date", revision.date))?,
+                    description: revision.description,
+                    by: revision.by,
+                })
+            })
+            .collect::<Result<_, SheetFileError>>()?;
+
+        sheets.push(Sheet {
+            id: SheetId(index as u32 + 1),
+            number: entry.number,
+            name: entry.name,
+            paper: named::<PaperSize>("paper size", &entry.paper)?,
+            orientation: named::<Orientation>("orientation", &entry.orientation)?,
+            status: entry.status,
+            placements,
+            notes: entry
+                .notes
+                .into_iter()
+                .map(|note| SheetNote {
+                    text: note.text,
+                    position: Vec2::from_array(note.position),
+                    height: note.height,
+                })
+                .collect(),
+            revisions,
+        });
+    }
+
+    Ok(SheetSet {
+        project: file.project,
+        client: file.client,
+        drawn_by: file.drawn_by,
+        checked_by: file.checked_by,
+        active: sheets.first().map(|sheet| sheet.id),
+        sheets,
+    })
+}
+
+fn named<T: IntoEnumIterator + fmt::Display>(what: &'static str, name: &str) -> Result<T, SheetFileError> {
+    T::iter()
+        .find(|value| value.to_string().eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| SheetFileError::Unknown(what, name.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use new_core::drawing::Compass;
+
+    #[test]
+    fn a_sheet_set_reads_back_what_it_wrote() {
+        let mut set = SheetSet {
+            client: "Client \"A\"".to_owned(),
+            ..SheetSet::default()
+        };
+        let plans = set.sheets[0].id;
+        if let Some(sheet) = set.get_mut(plans) {
+            sheet.paper = PaperSize::A1;
+            sheet.orientation = Orientation::Portrait;
+            sheet.notes.push(SheetNote {
+                text: "All dimensions in mm\nDo not scale".to_owned(),
+                position: Vec2::new(30.0, 60.0),
+                height: 2.5,
+            });
+            sheet.revisions.push(Revision {
+                number: 1,
+                date: Date::from_ymd(2026, 10, 19),
+                description: "For comment".to_owned(),
+                by: "JS".to_owned(),
+            });
+        }
+
+        let text = sheet_set_text(&set).expect("written");
+        assert_eq!(parse_sheet_set(&text).expect("read back"), set);
+    }
+
+    #[test]
+    fn unknown_names_are_refused() {
+        let text = r#"
+            project = "Tower"
+
+            [[sheets]]
+            number = "S-101"
+            name = "Framing"
+            paper = "B7"
+            orientation = "Landscape"
+        "#;
+        let error = parse_sheet_set(text).expect_err("no such paper");
+        assert_eq!(error.to_string(), "'B7' is not a paper size");
+    }
+
+    #[test]
+    fn a_hand_written_set_reads_in_order() {
+        let text = r#"
+            project = "Tower"
+
+            [[sheets]]
+            number = "S-101"
+            name = "Framing"
+            paper = "a1"
+            orientation = "Landscape"
+
+            [[sheets.views]]
+            name = "Level 2"
+            kind = "Plan"
+            cut_height = 4.2
+            phase = "New Construction"
+            filter = "Show All"
+            scale = 100
+            center = [400.0, 300.0]
+
+            [[sheets]]
+            number = "S-201"
+            name = "Sections"
+            paper = "A1"
+            orientation = "Landscape"
+
+            [[sheets.views]]
+            name = "Section B"
+            kind = "Section"
+            facing = "North"
+            offset = 12.5
+            phase = "Existing"
+            filter = "Show All"
+            scale = 50
+            center = [300.0, 300.0]
+        "#;
+        let set = parse_sheet_set(text).expect("read");
+
+        assert_eq!(set.project, "Tower");
+        assert_eq!(set.active, Some(SheetId(1)));
+        let numbers: Vec<&str> = set.sheets.iter().map(|sheet| sheet.number.as_str()).collect();
+        assert_eq!(numbers, ["S-101", "S-201"]);
+        assert_eq!(set.sheets[0].paper, PaperSize::A1);
+        assert_eq!(set.sheets[0].placements[0].view.kind, ViewKind::Plan { cut_height: 4.2 });
+        assert_eq!(
+            set.sheets[1].placements[0].view.kind,
+            ViewKind::Section {
+                facing: Compass::North,
+                offset: 12.5
+            }
+        );
+        assert_eq!(set.sheets[1].placements[0].view.phase.phase, Phase::Existing);
+    }
+}
@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystems;
+
+use new_core::sheet::{SheetCommand, SheetDrawings, SheetPublish, SheetSet};
+use new_core::units::ProjectUnits;
+
+use crate::sheets::drawings::refresh_sheet_drawings;
+use crate::sheets::publish::run_sheet_commands;
+
+pub struct SheetPlugin;
+
+impl Plugin for SheetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SheetSet>()
+            .init_resource::<SheetDrawings>()
+            .init_resource::<SheetPublish>()
+            // Grid dimensions are written in them, publishing without a window needs them too
+            .init_resource::<ProjectUnits>()
+            .add_message::<SheetCommand>()
+            .add_systems(Update, run_sheet_commands)
+            .add_systems(
+                PostUpdate,
+                refresh_sheet_drawings.after(TransformSystems::Propagate),
+            );
+    }
+}
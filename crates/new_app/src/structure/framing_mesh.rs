@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+
+use new_core::profile::{Framing, Justification, ProfileShape, profile};
+
+use crate::geometry::sweep::Sweep;
+
+// Sweeps the profile of framing that was placed or changed along its axis
+pub fn sweep_framing(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    framing: Query<(Entity, &Framing), Changed<Framing>>,
+) {
+    for (entity, framing) in &framing {
+        let Some(section) = profile(&framing.profile) else {
+            warn!("No profile called {}", framing.profile);
+            continue;
+        };
+        let axis: Vec<Vec3> = framing.axis.points.iter().map(|point| point.to_vec3()).collect();
+        let Some(mesh) = framing_mesh(&section.shape, &axis, framing.justification) else {
+            continue;
+        };
+        commands.entity(entity).insert(Mesh3d(meshes.add(mesh)));
+    }
+}
+
+// Profile along the axis, hung below it for top justified framing
+pub fn framing_mesh(shape: &ProfileShape, axis: &[Vec3], justification: Justification) -> Option<Mesh> {
+    let lift = match justification {
+        Justification::Center => 0.0,
+        Justification::Top => -shape.size().y / 2.0,
+    };
+    let mut sweep = Sweep::default();
+    sweep.add(shape, axis, lift);
+    sweep.mesh()
+}
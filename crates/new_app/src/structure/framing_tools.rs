@@ -0,0 +1,304 @@
+use bevy::camera::visibility::RenderLayers;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use new_core::anchor::AnchorStrategy;
+use new_core::element::{ElementHeader, ElementId, ElementIdAllocator};
+use new_core::elements::element_kindtype_enums::{BeamType, ColumnType, MemberType};
+use new_core::elements::{ElementKind, ElementKindType};
+use new_core::phase::{ElementPhasing, ViewPhases};
+use new_core::placement::{Curve3, Point3};
+use new_core::profile::{BEAM_TOOL, COLUMN_TOOL, ColumnTop, Framing, Justification, PROFILES, SectionProfile, profile};
+use new_core::structural_grid::{GridReference, StructuralGrid, world_to_plan};
+use new_core::tool::{ToolEvent, ToolId, ToolInputs, ToolOption, ToolOptions, ToolValue};
+use new_core::units::Measure;
+
+use crate::editor::anchoring::anchor_cast::{AnchorCast, ViewportRay};
+use crate::editor::selection::picking::Selectable;
+
+pub const PROFILE_OPTION: &str = "Profile";
+pub const HEIGHT_OPTION: &str = "Height";
+pub const MEMBER_OPTION: &str = "Member";
+pub const TOP_OPTION: &str = "Top";
+
+// Columns this tall unless they go up to the level above, meters
+const DEFAULT_HEIGHT: f32 = 3.0;
+// Levels closer than this above the base don't count as the next one
+const LEVEL_TOLERANCE: f32 = 0.01;
+// A column this close to a grid intersection is set out from it
+const ON_GRID: f32 = 1.0e-3;
+
+const MEMBERS: [&str; 3] = ["Beam", "Joist", "Brace"];
+const LEVEL_ABOVE: &str = "Level Above";
+const TOPS: [&str; 2] = [LEVEL_ABOVE, "Height"];
+
+// Profile choice with every catalog size, starting at the given one
+pub fn profile_option(selected: &str) -> ToolOption {
+    ToolOption {
+        key: PROFILE_OPTION,
+        value: ToolValue::Choice {
+            choices: PROFILES.iter().map(|profile| profile.name.to_owned()).collect(),
+            selected: PROFILES.iter().position(|profile| profile.name == selected).unwrap_or(0),
+        },
+    }
+}
+
+pub fn column_options() -> Vec<ToolOption> {
+    vec![
+        profile_option("HEB 240"),
+        ToolOption {
+            key: TOP_OPTION,
+            value: ToolValue::Choice {
+                choices: TOPS.map(str::to_owned).to_vec(),
+                selected: 0,
+            },
+        },
+        ToolOption {
+            key: HEIGHT_OPTION,
+            value: ToolValue::Number {
+                value: DEFAULT_HEIGHT,
+                measure: Measure::Length,
+            },
+        },
+    ]
+}
+
+pub fn beam_options() -> Vec<ToolOption> {
+    vec![
+        profile_option("IPE 300"),
+        ToolOption {
+            key: MEMBER_OPTION,
+            value: ToolValue::Choice {
+                choices: MEMBERS.map(str::to_owned).to_vec(),
+                selected: 0,
+            },
+        },
+    ]
+}
+
+#[derive(Resource)]
+pub struct FramingMaterials {
+    steel: Handle<StandardMaterial>,
+    concrete: Handle<StandardMaterial>,
+}
+
+impl FromWorld for FramingMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self {
+            steel: materials.add(StandardMaterial {
+                base_color: Color::srgb(0.45, 0.5, 0.58),
+                metallic: 0.6,
+                perceptual_roughness: 0.5,
+                ..default()
+            }),
+            concrete: materials.add(StandardMaterial {
+                base_color: Color::srgb(0.72, 0.71, 0.68),
+                perceptual_roughness: 0.9,
+                ..default()
+            }),
+        }
+    }
+}
+
+// Element to make from a profile, the mesh is swept from it afterwards
+pub struct NewFraming {
+    pub kind: ElementKind,
+    pub kind_type: ElementKindType,
+    pub profile: &'static SectionProfile,
+    // World space, the element's origin goes on the first point
+    pub axis: Vec<Vec3>,
+    pub justification: Justification,
+    pub grid_reference: Option<GridReference>,
+    // Level it stands on, and for columns what holds their top
+    pub level_id: Option<ElementId>,
+    pub top: Option<ColumnTop>,
+}
+
+// Spawns framing elements in the phase of the view they are drawn in
+#[derive(SystemParam)]
+pub struct FramingSpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    ids: ResMut<'w, ElementIdAllocator>,
+    materials: Res<'w, FramingMaterials>,
+    view_phases: Res<'w, ViewPhases>,
+}
+
+impl FramingSpawner<'_, '_> {
+    pub fn spawn(&mut self, pane_id: u32, framing: NewFraming) {
+        let Some(&origin) = framing.axis.first() else {
+            return;
+        };
+        let material = if framing.profile.steel {
+            &self.materials.steel
+        } else {
+            &self.materials.concrete
+        };
+
+        let mut element = self.commands.spawn((
+            ElementHeader {
+                id: self.ids.allocate(),
+                name: Some(format!("{} {}", framing.kind, framing.profile.name)),
+                kind: framing.kind,
+                kind_type: Some(framing.kind_type),
+                spec_id: None,
+                level_id: framing.level_id,
+                params: framing.profile.parameters(),
+            },
+            Framing {
+                profile: framing.profile.name.to_owned(),
+                axis: Curve3 {
+                    points: framing.axis.iter().map(|point| Point3::from_vec3(*point - origin)).collect(),
+                },
+                justification: framing.justification,
+            },
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(origin),
+            Visibility::default(),
+            RenderLayers::layer(0),
+            Selectable,
+            ElementPhasing {
+                created: self.view_phases.get(pane_id).phase,
+                demolished: None,
+            },
+        ));
+        if let Some(reference) = framing.grid_reference {
+            element.insert(reference);
+        }
+        if let Some(top) = framing.top {
+            element.insert(top);
+        }
+    }
+}
+
+fn chosen_profile(options: &ToolOptions, tool: ToolId) -> Option<&'static SectionProfile> {
+    profile(options.choice(tool, PROFILE_OPTION)?)
+}
+
+// A click puts a column on the point, on the level at or below it. It goes up to the level
+// above when the Top option asks for that and there is one, otherwise as high as the Height
+// option. Points on a grid intersection tie the column to it.
+pub fn place_column(
+    mut events: MessageReader<ToolEvent>,
+    viewport: ViewportRay,
+    mut cast: AnchorCast,
+    options: Res<ToolOptions>,
+    elements: Query<(&ElementHeader, &GlobalTransform)>,
+    grids: Query<(&ElementHeader, &StructuralGrid)>,
+    mut spawner: FramingSpawner,
+) {
+    for event in events.read() {
+        let ToolEvent::Picked { tool, pane_id, cursor } = *event else {
+            continue;
+        };
+        if tool != COLUMN_TOOL {
+            continue;
+        }
+        let (Some(ray), Some(section)) = (viewport.ray(pane_id, cursor), chosen_profile(&options, COLUMN_TOOL)) else {
+            continue;
+        };
+        let Some(anchored) = cast.anchor_in_view(pane_id, AnchorStrategy::Float, ray) else {
+            continue;
+        };
+        let base = anchored.transform.translation;
+
+        let mut levels: Vec<(ElementId, f32)> = elements
+            .iter()
+            .filter(|(header, _)| header.kind == ElementKind::BuildingStorey)
+            .map(|(header, transform)| (header.id, transform.translation().y))
+            .collect();
+        levels.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let level_id = levels
+            .iter()
+            .rfind(|(_, elevation)| *elevation <= base.y + LEVEL_TOLERANCE)
+            .map(|(id, _)| *id);
+        let level_above = levels.iter().find(|(_, elevation)| *elevation > base.y + LEVEL_TOLERANCE);
+
+        let height = options.number(COLUMN_TOOL, HEIGHT_OPTION).unwrap_or(DEFAULT_HEIGHT).max(0.1);
+        let (top, top_elevation) = match (options.choice(COLUMN_TOOL, TOP_OPTION), level_above) {
+            (Some(LEVEL_ABOVE), Some(&(id, elevation))) => (ColumnTop::Level(id), elevation),
+            (Some(LEVEL_ABOVE), None) => {
+                info!("No level above the column, it takes the Height option");
+                (ColumnTop::Height(height), base.y + height)
+            }
+            _ => (ColumnTop::Height(height), base.y + height),
+        };
+
+        let grid_reference = grids.iter().find_map(|(header, grid)| {
+            let intersection = grid.snap(world_to_plan(base), ON_GRID)?;
+            Some(GridReference {
+                grid: header.id,
+                at: intersection.at,
+                towards: None,
+            })
+        });
+
+        spawner.spawn(
+            pane_id,
+            NewFraming {
+                kind: ElementKind::Column,
+                kind_type: ElementKindType::Column(ColumnType::COLUMN),
+                profile: section,
+                axis: vec![base, Vec3::new(base.x, top_elevation, base.z)],
+                justification: Justification::Center,
+                grid_reference,
+                level_id,
+                top: Some(top),
+            },
+        );
+    }
+}
+
+// Clicks along the beam, confirmed to finish. Its top runs through the points.
+pub fn place_beam(
+    mut events: MessageReader<ToolEvent>,
+    viewport: ViewportRay,
+    mut cast: AnchorCast,
+    mut inputs: ResMut<ToolInputs>,
+    options: Res<ToolOptions>,
+    mut spawner: FramingSpawner,
+) {
+    for event in events.read() {
+        match event {
+            ToolEvent::Picked { tool, pane_id, cursor } if *tool == BEAM_TOOL => {
+                let Some(ray) = viewport.ray(*pane_id, *cursor) else {
+                    continue;
+                };
+                if let Some(anchored) = cast.anchor_in_view(*pane_id, AnchorStrategy::Float, ray) {
+                    inputs.push(*pane_id, anchored.transform.translation);
+                }
+            }
+            ToolEvent::Confirmed { tool, pane_id, points } if *tool == BEAM_TOOL => {
+                let Some(section) = chosen_profile(&options, BEAM_TOOL) else {
+                    continue;
+                };
+                let mut axis = points.clone();
+                axis.dedup_by(|a, b| a.distance_squared(*b) < ON_GRID * ON_GRID);
+                if axis.len() < 2 {
+                    warn!("A beam needs two points or more");
+                    continue;
+                }
+
+                let (kind, kind_type) = match options.choice(BEAM_TOOL, MEMBER_OPTION) {
+                    Some("Joist") => (ElementKind::Beam, ElementKindType::Beam(BeamType::JOIST)),
+                    Some("Brace") => (ElementKind::Member, ElementKindType::Member(MemberType::BRACE)),
+                    _ => (ElementKind::Beam, ElementKindType::Beam(BeamType::BEAM)),
+                };
+                spawner.spawn(
+                    *pane_id,
+                    NewFraming {
+                        kind,
+                        kind_type,
+                        profile: section,
+                        axis,
+                        justification: Justification::Top,
+                        grid_reference: None,
+                        level_id: None,
+                        top: None,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+}
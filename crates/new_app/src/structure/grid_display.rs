@@ -0,0 +1,130 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::visibility::RenderLayers;
+use bevy::mesh::PrimitiveTopology;
+use bevy::prelude::*;
+
+use new_core::element::ElementHeader;
+use new_core::elements::ElementKind;
+use new_core::structural_grid::{StructuralGrid, plan_to_world};
+
+// Bubbles in the model, meters. On paper they keep their own size.
+const BUBBLE_RADIUS: f32 = 0.4;
+const BUBBLE_SEGMENTS: usize = 24;
+// Grids reach from just below the lowest level to well above the highest
+const BELOW_LEVELS: f32 = 1.0;
+const ABOVE_LEVELS: f32 = 4.0;
+
+// Linework of one grid, a child of the grid element. Not Selectable and not an element, so
+// picking and anchoring pass through it.
+#[derive(Component)]
+pub struct GridLines;
+
+#[derive(Resource)]
+pub struct GridLineMaterial(pub Handle<StandardMaterial>);
+
+impl FromWorld for GridLineMaterial {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+            base_color: Color::srgb(0.85, 0.3, 0.25),
+            unlit: true,
+            ..default()
+        }))
+    }
+}
+
+// Rebuilds the lines of grids whose axes or heights changed
+pub fn show_grid_lines(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    material: Res<GridLineMaterial>,
+    grids: Query<(Entity, &StructuralGrid, Option<&Children>), Changed<StructuralGrid>>,
+    shown: Query<&Mesh3d, With<GridLines>>,
+) {
+    for (entity, grid, children) in &grids {
+        let mesh = meshes.add(grid_mesh(grid));
+
+        let existing = children.and_then(|children| children.iter().find(|child| shown.contains(*child)));
+        match existing {
+            Some(lines) => {
+                commands.entity(lines).insert(Mesh3d(mesh));
+            }
+            None => {
+                commands.spawn((
+                    Name::new("Grid Lines"),
+                    GridLines,
+                    Mesh3d(mesh),
+                    MeshMaterial3d(material.0.clone()),
+                    Transform::default(),
+                    Visibility::Inherited,
+                    RenderLayers::layer(0),
+                    ChildOf(entity),
+                ));
+            }
+        }
+    }
+}
+
+// Each axis along the bottom and the top with its ends joined, and a bubble past either end
+// at the top
+fn grid_mesh(grid: &StructuralGrid) -> Mesh {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut line = |a: Vec3, b: Vec3| positions.extend([a.to_array(), b.to_array()]);
+
+    for axis in &grid.axes {
+        let Some(direction) = axis.direction() else {
+            continue;
+        };
+        for height in [grid.bottom, grid.top] {
+            line(plan_to_world(axis.start, height), plan_to_world(axis.end, height));
+        }
+        for (end, outward) in [(axis.start, -direction), (axis.end, direction)] {
+            line(plan_to_world(end, grid.bottom), plan_to_world(end, grid.top));
+
+            let center = end + outward * BUBBLE_RADIUS;
+            let point = |index: usize| {
+                let angle = index as f32 / BUBBLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                plan_to_world(center + Vec2::from_angle(angle) * BUBBLE_RADIUS, grid.top)
+            };
+            for index in 0..BUBBLE_SEGMENTS {
+                line(point(index), point(index + 1));
+            }
+        }
+    }
+
+    Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+}
+
+// Keeps every grid spanning the levels of the model as they are added, moved and removed.
+// Grids keep their heights while the model has no levels.
+pub fn span_levels(
+    mut grids: Query<&mut StructuralGrid>,
+    levels: Query<(&ElementHeader, &GlobalTransform)>,
+    moved: Query<&ElementHeader, Changed<GlobalTransform>>,
+    added: Query<(), Added<StructuralGrid>>,
+    mut removed: RemovedComponents<ElementHeader>,
+) {
+    let levels_moved = moved.iter().any(|header| header.kind == ElementKind::BuildingStorey);
+    // Which elements they were is gone with them, so any removal counts
+    let removals = removed.read().count() > 0;
+    if !levels_moved && !removals && added.is_empty() {
+        return;
+    }
+    let heights = levels
+        .iter()
+        .filter(|(header, _)| header.kind == ElementKind::BuildingStorey)
+        .map(|(_, transform)| transform.translation().y);
+    let Some((lowest, highest)) = heights.fold(None, |span: Option<(f32, f32)>, height| {
+        Some(span.map_or((height, height), |(low, high)| (low.min(height), high.max(height))))
+    }) else {
+        return;
+    };
+
+    let (bottom, top) = (lowest - BELOW_LEVELS, highest + ABOVE_LEVELS);
+    for mut grid in &mut grids {
+        if grid.bottom != bottom || grid.top != top {
+            grid.bottom = bottom;
+            grid.top = top;
+        }
+    }
+}
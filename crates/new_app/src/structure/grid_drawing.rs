@@ -0,0 +1,128 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use new_core::drawing::{Annotation, Drawing, DrawingPolyline, LineKind, LineStyle, ViewKind};
+use new_core::elements::ElementKind;
+use new_core::structural_grid::{StructuralGrid, plan_to_world};
+use new_core::units::{Measure, ProjectUnits};
+
+// Axes further off square to an elevation than this, as a sine, are seen at a slant and left out
+const SQUARE_TOLERANCE: f32 = 0.01;
+// Axes closer than this along an elevation are the same line, meters
+const SAME_LINE: f32 = 1.0e-3;
+
+const GRID_STYLE: LineStyle = LineStyle {
+    kind: LineKind::Grid,
+    element: ElementKind::Grid,
+    halftone: false,
+    dashed: true,
+};
+
+// Structural grids as sheets draw them, with dimensions in the project units
+#[derive(SystemParam)]
+pub struct DrawnGrids<'w, 's> {
+    grids: Query<'w, 's, &'static StructuralGrid>,
+    changed: Query<'w, 's, (), Changed<StructuralGrid>>,
+    removed: RemovedComponents<'w, 's, StructuralGrid>,
+    units: Res<'w, ProjectUnits>,
+}
+
+impl DrawnGrids<'_, '_> {
+    pub fn changed(&mut self) -> bool {
+        let removed_any = self.removed.read().count() > 0;
+        removed_any || !self.changed.is_empty() || self.units.is_changed()
+    }
+
+    pub fn draw(&self, kind: &ViewKind, drawing: &mut Drawing) {
+        for grid in &self.grids {
+            match *kind {
+                ViewKind::Plan { cut_height } => self.plan(grid, cut_height, drawing),
+                _ => self.elevation(grid, kind, drawing),
+            }
+        }
+    }
+
+    // Every axis the cut passes through, bubbled at both ends, with the spacing of neighbouring
+    // axes dimensioned off their start ends
+    fn plan(&self, grid: &StructuralGrid, cut_height: f32, drawing: &mut Drawing) {
+        if !(grid.bottom..=grid.top).contains(&cut_height) {
+            return;
+        }
+
+        for axis in &grid.axes {
+            let Some(direction) = axis.direction() else {
+                continue;
+            };
+            drawing.polylines.push(DrawingPolyline {
+                points: vec![axis.start, axis.end],
+                style: GRID_STYLE,
+            });
+            for (end, outward) in [(axis.start, -direction), (axis.end, direction)] {
+                drawing.annotations.push(Annotation::Bubble {
+                    end,
+                    outward,
+                    tag: axis.tag.clone(),
+                });
+            }
+        }
+
+        for dimension in grid.dimensions() {
+            let (Some(from), Some(to)) = (grid.axis(&dimension.from), grid.axis(&dimension.to)) else {
+                continue;
+            };
+            let (Some(direction), Some(across)) = (from.direction(), to.direction()) else {
+                continue;
+            };
+            // Square across to the second axis, whatever length it is drawn at
+            let a = from.start;
+            let b = a - across.perp() * to.offset(a);
+            drawing.annotations.push(Annotation::Dimension {
+                a,
+                b,
+                outward: -direction,
+                text: self.units.format(Measure::Length, f64::from(dimension.distance)),
+            });
+        }
+    }
+
+    // Axes running away from the viewer stand up as lines from the bottom of the grid to its
+    // top, bubbled at the top and dimensioned along it
+    fn elevation(&self, grid: &StructuralGrid, kind: &ViewKind, drawing: &mut Drawing) {
+        let frame = kind.frame();
+        let mut standing: Vec<(f32, &str)> = grid
+            .axes
+            .iter()
+            .filter_map(|axis| {
+                let direction = plan_to_world(axis.direction()?, 0.0);
+                (direction.dot(frame.right).abs() < SQUARE_TOLERANCE)
+                    .then(|| (frame.project(plan_to_world(axis.start, 0.0)).x, axis.tag.as_str()))
+            })
+            .collect();
+        standing.sort_by(|a, b| a.0.total_cmp(&b.0));
+        standing.dedup_by(|a, b| (a.0 - b.0).abs() < SAME_LINE);
+
+        let (bottom, top) = (grid.bottom, grid.top);
+        for &(x, tag) in &standing {
+            let top = Vec2::new(x, top);
+            drawing.polylines.push(DrawingPolyline {
+                points: vec![Vec2::new(x, bottom), top],
+                style: GRID_STYLE,
+            });
+            drawing.annotations.push(Annotation::Bubble {
+                end: top,
+                outward: Vec2::Y,
+                tag: tag.to_owned(),
+            });
+        }
+
+        for pair in standing.windows(2) {
+            let distance = pair[1].0 - pair[0].0;
+            drawing.annotations.push(Annotation::Dimension {
+                a: Vec2::new(pair[0].0, top),
+                b: Vec2::new(pair[1].0, top),
+                outward: Vec2::Y,
+                text: self.units.format(Measure::Length, f64::from(distance)),
+            });
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+
+use new_core::element::ElementHeader;
+use new_core::structural_grid::{GridReference, StructuralGrid, plan_to_world};
+
+// Elements set out from a grid stand on their intersection at the height they have, and turn
+// their local +X towards the second one when they have it. Runs when a grid or a reference
+// changes, so moving an axis carries everything on it along.
+pub fn follow_grid_references(
+    grids: Query<(&ElementHeader, &StructuralGrid)>,
+    changed_grids: Query<(), Changed<StructuralGrid>>,
+    mut elements: Query<(Ref<GridReference>, &mut Transform)>,
+) {
+    let grids_changed = !changed_grids.is_empty();
+
+    for (reference, mut transform) in &mut elements {
+        if !grids_changed && !reference.is_changed() {
+            continue;
+        }
+        let Some((_, grid)) = grids.iter().find(|(header, _)| header.id == reference.grid) else {
+            continue;
+        };
+        let Some(at) = grid.point(&reference.at) else {
+            continue;
+        };
+
+        let mut placed = *transform;
+        placed.translation = plan_to_world(at, transform.translation.y);
+        if let Some(towards) = reference.towards.as_ref().and_then(|towards| grid.point(towards))
+            && let Some(direction) = (towards - at).try_normalize()
+        {
+            placed.rotation = Quat::from_rotation_y(direction.y.atan2(direction.x));
+        }
+        transform.set_if_neq(placed);
+    }
+}
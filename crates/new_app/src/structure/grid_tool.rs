@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+
+use new_core::anchor::AnchorStrategy;
+use new_core::element::{ElementHeader, ElementIdAllocator, ElementParams};
+use new_core::elements::element_kindtype_enums::GridType;
+use new_core::elements::{ElementKind, ElementKindType};
+use new_core::structural_grid::{AxisFamily, GRID_LINE_TOOL, StructuralGrid, world_to_plan};
+use new_core::tool::{ToolEvent, ToolInputs, ToolOptions, ToolValue};
+
+use crate::editor::anchoring::anchor_cast::{AnchorCast, ViewportRay};
+
+// Tool option choosing what the next axis is called
+pub const LABELS_OPTION: &str = "Labels";
+// Tool option choosing the grid axes go on, listing the grids of the model after NEW_GRID
+pub const GRID_OPTION: &str = "Grid";
+pub const NEW_GRID: &str = "New Grid";
+
+fn labels(options: &ToolOptions) -> Option<AxisFamily> {
+    match options.choice(GRID_LINE_TOOL, LABELS_OPTION)? {
+        "Letters" => Some(AxisFamily::Letters),
+        "Numbers" => Some(AxisFamily::Numbers),
+        _ => None,
+    }
+}
+
+type GridEntry<'a> = (Entity, &'a ElementHeader);
+type EditedGrid = (Entity, &'static ElementHeader, &'static mut StructuralGrid);
+
+// Grids by id, in the order the Grid option lists them
+fn grid_names<'a>(grids: impl Iterator<Item = GridEntry<'a>>) -> Vec<(Entity, String)> {
+    let mut grids: Vec<GridEntry> = grids.collect();
+    grids.sort_by_key(|(_, header)| header.id);
+    grids
+        .into_iter()
+        .map(|(entity, header)| (entity, grid_name(header)))
+        .collect()
+}
+
+fn grid_name(header: &ElementHeader) -> String {
+    header.name.clone().unwrap_or_else(|| format!("Grid {}", header.id.0))
+}
+
+fn grid_choice(options: &mut ToolOptions) -> Option<(&mut Vec<String>, &mut usize)> {
+    let option = options
+        .tools
+        .get_mut(&GRID_LINE_TOOL)?
+        .iter_mut()
+        .find(|option| option.key == GRID_OPTION)?;
+    match &mut option.value {
+        ToolValue::Choice { choices, selected } => Some((choices, selected)),
+        _ => None,
+    }
+}
+
+// Keeps the Grid option listing the grids of the model. The chosen one stays chosen by name,
+// and one that is gone falls back to the last grid.
+pub fn list_grids(grids: Query<(Entity, &ElementHeader), With<StructuralGrid>>, mut options: ResMut<ToolOptions>) {
+    let mut listed = vec![NEW_GRID.to_owned()];
+    listed.extend(grid_names(grids.iter()).into_iter().map(|(_, name)| name));
+
+    let current = match options.get(GRID_LINE_TOOL, GRID_OPTION) {
+        Some(ToolValue::Choice { choices, selected }) => {
+            if *choices == listed {
+                return;
+            }
+            // A model loaded with grids carries on with them
+            let chosen = choices.get(*selected).filter(|_| choices.len() > 1);
+            chosen.and_then(|chosen| listed.iter().position(|name| name == chosen))
+        }
+        _ => return,
+    };
+    if let Some((choices, selected)) = grid_choice(&mut options) {
+        *selected = current.unwrap_or(listed.len() - 1);
+        *choices = listed;
+    }
+}
+
+// First click starts an axis and the second ends it. Axes go on the grid chosen in the Grid
+// option. New Grid makes one with the axis and chooses it for the axes after.
+pub fn draw_grid_line(
+    mut commands: Commands,
+    mut ids: ResMut<ElementIdAllocator>,
+    mut events: MessageReader<ToolEvent>,
+    mut inputs: ResMut<ToolInputs>,
+    mut options: ResMut<ToolOptions>,
+    viewport: ViewportRay,
+    // Casting snaps to the axes already there, drawing adds to them
+    mut params: ParamSet<(AnchorCast, Query<EditedGrid>)>,
+) {
+    for event in events.read() {
+        let ToolEvent::Picked { tool, pane_id, cursor } = *event else {
+            continue;
+        };
+        if tool != GRID_LINE_TOOL {
+            continue;
+        }
+        let Some(ray) = viewport.ray(pane_id, cursor) else {
+            continue;
+        };
+        let Some(anchored) = params.p0().anchor_in_view(pane_id, AnchorStrategy::Float, ray) else {
+            continue;
+        };
+        let point = anchored.transform.translation;
+
+        let Some(&start) = inputs.points(pane_id).first() else {
+            inputs.push(pane_id, point);
+            continue;
+        };
+        inputs.panes.remove(&pane_id);
+
+        let (start, end) = (world_to_plan(start), world_to_plan(point));
+        let mut grids = params.p1();
+        let chosen = match options.get(GRID_LINE_TOOL, GRID_OPTION) {
+            Some(ToolValue::Choice { selected, .. }) if *selected > 0 => {
+                let names = grid_names(grids.iter().map(|(entity, header, _)| (entity, header)));
+                names.get(*selected - 1).map(|(entity, _)| *entity)
+            }
+            _ => None,
+        };
+        if let Some((_, _, mut grid)) = chosen.and_then(|entity| grids.get_mut(entity).ok()) {
+            match grid.add_axis(start, end, labels(&options)) {
+                Some(axis) => info!("Grid axis {}", axis.tag),
+                None => warn!("Grid axis needs two different points"),
+            }
+            continue;
+        }
+
+        let mut grid = StructuralGrid::default();
+        if grid.add_axis(start, end, labels(&options)).is_none() {
+            warn!("Grid axis needs two different points");
+            continue;
+        }
+        // Named apart from the others so the Grid option tells them apart
+        let names = grid_names(grids.iter().map(|(entity, header, _)| (entity, header)));
+        let name = (1..)
+            .map(|number| format!("Grid {number}"))
+            .find(|name| names.iter().all(|(_, other)| other != name))
+            .unwrap_or_default();
+        if let Some((choices, selected)) = grid_choice(&mut options) {
+            choices.push(name.clone());
+            *selected = choices.len() - 1;
+        }
+        commands.spawn((
+            ElementHeader {
+                id: ids.allocate(),
+                name: Some(name),
+                kind: ElementKind::Grid,
+                kind_type: Some(ElementKindType::Grid(GridType::RECTANGULAR)),
+                spec_id: None,
+                level_id: None,
+                params: ElementParams::new(),
+            },
+            grid,
+            Transform::default(),
+            Visibility::default(),
+        ));
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use new_core::element::{ElementHeader, ElementId};
+use new_core::elements::ElementKind;
+use new_core::placement::Point3;
+use new_core::profile::{ColumnTop, Framing};
+
+// Shortest column a top level may pull down to, meters
+const MIN_HEIGHT: f32 = 0.1;
+
+// Columns ride on the level they were placed on and reach up to their top level, so moving a
+// level carries the columns on it and stretches the ones standing below it
+pub fn follow_levels(
+    levels: Query<(&ElementHeader, &GlobalTransform)>,
+    moved: Query<&ElementHeader, Changed<GlobalTransform>>,
+    mut columns: Query<(&ElementHeader, &ColumnTop, &mut Transform, &mut Framing)>,
+    // Heights the levels had when last seen, to move columns by as much as their level moved
+    mut known: Local<HashMap<ElementId, f32>>,
+) {
+    if !moved.iter().any(|header| header.kind == ElementKind::BuildingStorey) {
+        return;
+    }
+    let elevations: HashMap<ElementId, f32> = levels
+        .iter()
+        .filter(|(header, _)| header.kind == ElementKind::BuildingStorey)
+        .map(|(header, transform)| (header.id, transform.translation().y))
+        .collect();
+    let shifts: HashMap<ElementId, f32> = elevations
+        .iter()
+        .filter_map(|(id, elevation)| {
+            let before = known.insert(*id, *elevation)?;
+            (before != *elevation).then_some((*id, elevation - before))
+        })
+        .collect();
+    if shifts.is_empty() {
+        return;
+    }
+
+    for (header, top, mut transform, mut framing) in &mut columns {
+        let shift = header.level_id.and_then(|level| shifts.get(&level));
+        if let Some(shift) = shift {
+            transform.translation.y += shift;
+        }
+        let ColumnTop::Level(top_level) = *top else {
+            continue;
+        };
+        if shift.is_none() && !shifts.contains_key(&top_level) {
+            continue;
+        }
+        let Some(elevation) = elevations.get(&top_level) else {
+            continue;
+        };
+        let height = (elevation - transform.translation.y).max(MIN_HEIGHT);
+        let end = Point3::from_vec3(Vec3::Y * height);
+        if framing.axis.points.last() != Some(&end) {
+            let points = &mut framing.axis.points;
+            points.truncate(1);
+            points.push(end);
+        }
+    }
+}
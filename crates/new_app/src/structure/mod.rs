@@ -0,0 +1,8 @@
+pub mod framing_mesh;
+pub mod framing_tools;
+pub mod grid_display;
+pub mod grid_drawing;
+pub mod grid_references;
+pub mod grid_tool;
+pub mod level_constraints;
+pub mod structure_plugin;
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+
+use new_core::profile::{BEAM_TOOL, COLUMN_TOOL};
+use new_core::structural_grid::GRID_LINE_TOOL;
+use new_core::tool::{ToolDef, ToolOption, ToolValue};
+
+use crate::structure::framing_mesh::sweep_framing;
+use crate::structure::framing_tools::{FramingMaterials, beam_options, column_options, place_beam, place_column};
+use crate::structure::grid_display::{GridLineMaterial, show_grid_lines, span_levels};
+use crate::structure::grid_references::follow_grid_references;
+use crate::structure::grid_tool::{GRID_OPTION, LABELS_OPTION, NEW_GRID, draw_grid_line, list_grids};
+use crate::structure::level_constraints::follow_levels;
+use crate::tools::framework::activation::tool_active;
+use crate::tools::framework::tool_plugin::{ToolAppExt, ToolSystems};
+
+pub struct StructurePlugin;
+
+impl Plugin for StructurePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_tool(
+            ToolDef {
+                id: GRID_LINE_TOOL,
+                label: "Grid Line",
+                tooltip: "Click the two ends of a structural grid axis, named after the axes before it",
+                group: "Structure",
+                shortcut: None,
+            },
+            vec![
+                ToolOption {
+                    key: GRID_OPTION,
+                    value: ToolValue::Choice {
+                        choices: vec![NEW_GRID.to_owned()],
+                        selected: 0,
+                    },
+                },
+                ToolOption {
+                    key: LABELS_OPTION,
+                    value: ToolValue::Choice {
+                        choices: vec!["Auto".to_owned(), "Letters".to_owned(), "Numbers".to_owned()],
+                        selected: 0,
+                    },
+                },
+            ],
+        )
+        .register_tool(
+            ToolDef {
+                id: COLUMN_TOOL,
+                label: "Column",
+                tooltip: "Click a point or a grid intersection to put a column there, up to the level above or a height",
+                group: "Structure",
+                shortcut: None,
+            },
+            column_options(),
+        )
+        .register_tool(
+            ToolDef {
+                id: BEAM_TOOL,
+                label: "Beam",
+                tooltip: "Click along the top of a beam and press Enter to finish it",
+                group: "Structure",
+                shortcut: None,
+            },
+            beam_options(),
+        )
+        .init_resource::<GridLineMaterial>()
+        .init_resource::<FramingMaterials>()
+        .add_systems(
+            Update,
+            (
+                (
+                    draw_grid_line.run_if(tool_active(GRID_LINE_TOOL)),
+                    place_column.run_if(tool_active(COLUMN_TOOL)),
+                    place_beam.run_if(tool_active(BEAM_TOOL)),
+                )
+                    .after(ToolSystems),
+                (span_levels, list_grids),
+                (follow_grid_references, follow_levels),
+                (show_grid_lines, sweep_framing),
+            )
+                .chain(),
+        );
+    }
+}
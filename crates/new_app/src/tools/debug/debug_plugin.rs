@@ -1,14 +1,41 @@
 use bevy::prelude::*;
 
-use crate::tools::debug::object_place::place_object_here;
+use new_core::tool::{ToolDef, ToolOption, ToolValue};
+use new_core::units::Measure;
+
+use crate::tools::debug::object_place::{DebugObject, PLACE_OBJECT, place_object_here, preview_object};
+use crate::tools::framework::activation::tool_active;
+use crate::tools::framework::tool_plugin::{ToolAppExt, ToolSystems};
+use crate::tools::ghost::preview::GhostSystems;
 
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.register_tool(
+            ToolDef {
+                id: PLACE_OBJECT,
+                label: "Place Object",
+                tooltip: "Place a test object, anchored with the placement strategy",
+                group: "Debug",
+                shortcut: Some(KeyCode::KeyP),
+            },
+            vec![ToolOption {
+                key: "Radius",
+                value: ToolValue::Number {
+                    value: 0.3,
+                    measure: Measure::Length,
+                },
+            }],
+        )
+        .init_resource::<DebugObject>()
+        .add_systems(
             Update,
-            place_object_here
+            (
+                preview_object.after(ToolSystems).before(GhostSystems),
+                place_object_here.after(GhostSystems),
+            )
+                .run_if(tool_active(PLACE_OBJECT)),
         );
     }
-}
\ No newline at end of file
+}
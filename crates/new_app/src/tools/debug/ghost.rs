@@ -1,17 +0,0 @@
-use bevy::prelude::*;
-
-pub fn spawn_ghosting(
-    mut commands: Commands,
-) {
-
-}
-
-pub fn update_ghosting(
-    mut commands: Commands,
-    
-) {
-
-
-}
-
-
@@ -1,3 +1,3 @@
+
 pub mod object_place;
-pub mod debug_plugin;
-pub mod ghost;
\ No newline at end of file
+pub mod debug_plugin;
\ No newline at end of file
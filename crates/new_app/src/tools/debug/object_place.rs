@@ -1,67 +1,100 @@
 use bevy::camera::visibility::RenderLayers;
 use bevy::prelude::*;
-use bevy::input::mouse::MouseButton;
-use bevy::window::PrimaryWindow;
-use new_core::{VisibleViewports};
-use new_core::element::{ElementHeader, ElementId};
+use new_core::element::{ElementHeader, ElementId, ElementIdAllocator, ElementParams};
 use new_core::elements::{ElementKind, ElementKindType};
-use bevy_egui::egui;
 use new_core::elements::element_kindtype_enums::DuctSegmentType;
+use new_core::anchor::PlacementSettings;
+use new_core::phase::{ElementPhasing, ViewPhases};
+use new_core::tool::{ToolEvent, ToolId, ToolOptions};
 use crate::editor::selection::picking::Selectable;
+use crate::tools::ghost::preview::{GhostPlacement, GhostPreview, GhostShape};
 
-use new_core::{GameViewportCamera};
+pub const PLACE_OBJECT: ToolId = ToolId("debug.place_object");
+const DEFAULT_RADIUS: f32 = 0.3;
 
+// Mesh and material every debug object of the current radius shares
+#[derive(Resource)]
+pub struct DebugObject {
+    radius: f32,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn object_mesh(radius: f32) -> Mesh {
+    Sphere::new(radius).mesh().uv(32, 18)
+}
+
+impl FromWorld for DebugObject {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(object_mesh(DEFAULT_RADIUS));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Color::srgb(0.25, 0.55, 0.95),
+                ..default()
+            });
+        Self {
+            radius: DEFAULT_RADIUS,
+            mesh,
+            material,
+        }
+    }
+}
+
+// Shows where the object would go while the tool runs
+pub fn preview_object(
+    options: Res<ToolOptions>,
+    mut object: ResMut<DebugObject>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    settings: Res<PlacementSettings>,
+    mut preview: ResMut<GhostPreview>,
+) {
+    let radius = options
+        .number(PLACE_OBJECT, "Radius")
+        .unwrap_or(DEFAULT_RADIUS)
+        .max(0.01);
+    if radius != object.radius {
+        object.radius = radius;
+        object.mesh = meshes.add(object_mesh(radius));
+    }
+
+    preview.set_if_neq(GhostPreview(Some(GhostShape {
+        owner: PLACE_OBJECT.0,
+        mesh: object.mesh.clone(),
+        kind: ElementKind::DuctSegment,
+        strategy: settings.strategy,
+    })));
+}
 
 pub fn place_object_here(
     mut commands: Commands,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    visible_viewports: Res<VisibleViewports>,
-    elements: Query<(), With<ElementHeader>>,
-    mouse: Res<ButtonInput<MouseButton>>,
-    window: Single<&Window, With<PrimaryWindow>>,
-    keys: Res<ButtonInput<KeyCode>>,
-    cameras: Query<(&Camera, &GlobalTransform, &GameViewportCamera)>,
-    mut mesh_params: ParamSet<(MeshRayCast, ResMut<Assets<Mesh>>)>
+    mut ids: ResMut<ElementIdAllocator>,
+    object: Res<DebugObject>,
+    view_phases: Res<ViewPhases>,
+    placement: Res<GhostPlacement>,
+    mut events: MessageReader<ToolEvent>,
 ) {
-    let ctrl = keys.pressed(KeyCode::ControlLeft);
-    if !(ctrl && mouse.just_pressed(MouseButton::Left)) {
+    let picked = events.read().any(|event| {
+        matches!(event, ToolEvent::Picked { tool, pane_id, .. }
+            if *tool == PLACE_OBJECT && placement.pane_id == Some(*pane_id))
+    });
+    if !picked {
         return;
     }
 
-    let Some(cursor) = window.cursor_position() else {
-        return;
-    };
-
-    let Some(pane_id) = find_viewport_under_cursor(&visible_viewports, cursor) else {
+    let (Some(pane_id), Some(anchored)) = (placement.pane_id, &placement.anchored) else {
         return;
     };
-
-    let Some((camera, camera_transform)) = cameras
-        .iter()
-        .find(|(_,_,tag)| tag.pane_id == pane_id)
-        .map(|(cam, tf,_)| (cam, tf))
-        else {
-            return;
-        };  
-
-    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else {
-      return;  
-    };
-
-
-    let Some(some_place) = ray.plane_intersection_point(
-        Vec3::ZERO,                    // point on plane
-        InfinitePlane3d::new(Vec3::Y), // XY plane, normal points up Z
-    ) else {
+    if !placement.state.is_valid() {
+        warn!("Cannot place here: {:?}", placement.state);
         return;
-    };
-
+    }
 
-    // let mut mesh = mesh_params.p1();
-    
     commands.spawn((
         ElementHeader {
-            id: ElementId(1),
+            id: ids.allocate(),
             name: Some("new item".to_owned()),
             kind: ElementKind::DuctSegment,
             kind_type: Some(ElementKindType::DuctSegment(
@@ -69,33 +102,20 @@ pub fn place_object_here(
             )),
             spec_id: Some(ElementId(12)),
             level_id: Some(ElementId(12)),
+            params: ElementParams::new(),
         },
-        Mesh3d(mesh_params.p1().add(Sphere::new(0.3).mesh().uv(32, 18))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(0.25, 0.55, 0.95),
-            ..default()
-        })),
-        Transform::from_translation(some_place),
-        RenderLayers::layer(0), 
+        Mesh3d(object.mesh.clone()),
+        MeshMaterial3d(object.material.clone()),
+        anchored.transform,
+        anchored.anchor,
+        RenderLayers::layer(0),
         Selectable,
+        // New work belongs to the phase of the view it is drawn in
+        ElementPhasing {
+            created: view_phases.get(pane_id).phase,
+            demolished: None,
+        },
     ));
 
-
-    
-    println!("Hit entity: {:?}", some_place);
+    debug!("Placed at {:?}", anchored.transform.translation);
 }
-
-
-
-fn find_viewport_under_cursor(
-    visible_viewports: &VisibleViewports,
-    cursor: Vec2
-) -> Option<u32>{
-    let cursor_pos: egui::Pos2 = (cursor.x, cursor.y).into();
-    for (&pane_id, rect) in &visible_viewports.rects {
-        if rect.contains(cursor_pos) {
-            return Some(pane_id);
-        }
-    }
-    None
-}
\ No newline at end of file
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+
+use new_core::action::ActionInvoked;
+use new_core::tool::{
+    ActiveTool, CANCEL_TOOL, CONFIRM_TOOL, EXIT_TOOL, ToolCommand, ToolEvent, ToolId, ToolInputs, ToolRegistry,
+    ToolStep,
+};
+
+use crate::tools::ghost::preview::GhostPreview;
+
+// Every tool is an action named after it
+pub fn run_tool_actions(
+    mut invoked: MessageReader<ActionInvoked>,
+    registry: Res<ToolRegistry>,
+    mut tool_commands: MessageWriter<ToolCommand>,
+) {
+    for action in invoked.read() {
+        if action.id == EXIT_TOOL {
+            tool_commands.write(ToolCommand::Exit);
+        } else if action.id == CANCEL_TOOL {
+            tool_commands.write(ToolCommand::Cancel);
+        } else if action.id == CONFIRM_TOOL {
+            tool_commands.write(ToolCommand::Confirm);
+        } else if let Some(tool) = registry.get(ToolId(action.id.0)) {
+            tool_commands.write(ToolCommand::Activate(tool.id));
+        }
+    }
+}
+
+pub fn apply_tool_commands(
+    mut tool_commands: MessageReader<ToolCommand>,
+    mut active: ResMut<ActiveTool>,
+    mut inputs: ResMut<ToolInputs>,
+    mut events: MessageWriter<ToolEvent>,
+    mut ghost: ResMut<GhostPreview>,
+    registry: Res<ToolRegistry>,
+) {
+    for command in tool_commands.read() {
+        match *command {
+            ToolCommand::Activate(id) => {
+                if registry.get(id).is_none() {
+                    warn!("No tool registered as {id}");
+                    continue;
+                }
+                if active.id != Some(id) {
+                    switch(Some(id), &mut active, &mut inputs, &mut events, &mut ghost);
+                }
+            }
+            ToolCommand::Exit => switch(None, &mut active, &mut inputs, &mut events, &mut ghost),
+            ToolCommand::Cancel => {
+                let Some(tool) = active.id else {
+                    continue;
+                };
+                let picking: Vec<u32> = inputs.picking().collect();
+                if picking.is_empty() {
+                    switch(None, &mut active, &mut inputs, &mut events, &mut ghost);
+                    continue;
+                }
+                for pane_id in picking {
+                    inputs.panes.remove(&pane_id);
+                    events.write(ToolEvent::Cancelled { tool, pane_id });
+                }
+            }
+            ToolCommand::Confirm => {
+                let Some(tool) = active.id else {
+                    continue;
+                };
+                let picking: Vec<u32> = inputs.picking().collect();
+                for pane_id in picking {
+                    if let Some(ToolStep::Picking { points }) = inputs.panes.remove(&pane_id) {
+                        events.write(ToolEvent::Confirmed { tool, pane_id, points });
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Picks in progress belong to the old tool and go with it, and so does its ghost
+fn switch(
+    next: Option<ToolId>,
+    active: &mut ActiveTool,
+    inputs: &mut ToolInputs,
+    events: &mut MessageWriter<ToolEvent>,
+    ghost: &mut GhostPreview,
+) {
+    if let Some(tool) = active.id {
+        events.write(ToolEvent::Exited(tool));
+    }
+    inputs.panes.clear();
+    ghost.0 = None;
+
+    active.previous = active.id;
+    active.id = next;
+    if let Some(tool) = next {
+        info!("Tool: {tool}");
+        events.write(ToolEvent::Entered(tool));
+    }
+}
+
+pub fn tool_active(id: ToolId) -> impl Fn(Res<ActiveTool>) -> bool + Clone {
+    move |active: Res<ActiveTool>| active.id == Some(id)
+}
+
+pub fn no_tool_active(active: Res<ActiveTool>) -> bool {
+    active.id.is_none()
+}
@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use new_core::VisibleViewports;
+use new_core::tool::{ActiveTool, ToolEvent};
+
+// Left clicks in a viewport go to the running tool, tagged with the pane so each viewport
+// keeps its own picks. Clicks on the UI drawn over a viewport are not picks.
+pub fn pick_in_viewports(
+    active: Res<ActiveTool>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    visible_viewports: Res<VisibleViewports>,
+    mut events: MessageWriter<ToolEvent>,
+) {
+    let Some(tool) = active.id else {
+        return;
+    };
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let Some(pane_id) = visible_viewports.click_target(cursor) else {
+        return;
+    };
+
+    events.write(ToolEvent::Picked { tool, pane_id, cursor });
+}
@@ -0,0 +1,3 @@
+pub mod activation;
+pub mod input;
+pub mod tool_plugin;
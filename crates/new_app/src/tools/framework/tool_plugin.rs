@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+use new_core::action::{ActionAppExt, ActionDef};
+use new_core::keymap::KeyBinding;
+use new_core::tool::{
+    ActiveTool, CANCEL_TOOL, CONFIRM_TOOL, EXIT_TOOL, ToolCommand, ToolDef, ToolEvent, ToolInputs, ToolOption,
+    ToolOptions, ToolRegistry,
+};
+
+use crate::tools::framework::activation::{apply_tool_commands, run_tool_actions};
+use crate::tools::framework::input::pick_in_viewports;
+
+// Switching tools and routing input runs in here. Tools read their events after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ToolSystems;
+
+pub struct ToolPlugin;
+
+impl Plugin for ToolPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ToolRegistry>()
+            .init_resource::<ToolOptions>()
+            .init_resource::<ActiveTool>()
+            .init_resource::<ToolInputs>()
+            .add_message::<ToolCommand>()
+            .add_message::<ToolEvent>()
+            .register_action(ActionDef {
+                id: EXIT_TOOL,
+                label: "Exit Tool",
+                menu: "Tools",
+                bindings: Vec::new(),
+                prompt: None,
+            })
+            // Drops the picks in progress, a second press leaves the tool
+            .register_action(ActionDef {
+                id: CANCEL_TOOL,
+                label: "Cancel",
+                menu: "Tools",
+                bindings: vec![KeyBinding::key(KeyCode::Escape)],
+                prompt: None,
+            })
+            .register_action(ActionDef {
+                id: CONFIRM_TOOL,
+                label: "Confirm Points",
+                menu: "Tools",
+                bindings: vec![KeyBinding::key(KeyCode::Enter), KeyBinding::key(KeyCode::NumpadEnter)],
+                prompt: None,
+            })
+            .add_systems(
+                Update,
+                (run_tool_actions, apply_tool_commands, pick_in_viewports)
+                    .chain()
+                    .in_set(ToolSystems),
+            );
+    }
+}
+
+// Tool plugins put themselves on the toolbar and in the Tools menu with this when they are built
+pub trait ToolAppExt {
+    fn register_tool(&mut self, def: ToolDef, options: Vec<ToolOption>) -> &mut Self;
+}
+
+impl ToolAppExt for App {
+    fn register_tool(&mut self, def: ToolDef, options: Vec<ToolOption>) -> &mut Self {
+        self.init_resource::<ToolRegistry>()
+            .init_resource::<ToolOptions>()
+            .register_action(ActionDef {
+                id: def.id.action(),
+                label: def.label,
+                menu: "Tools",
+                bindings: def.shortcut.map(KeyBinding::key).into_iter().collect(),
+                prompt: None,
+            });
+        let id = def.id;
+        self.world_mut().resource_mut::<ToolRegistry>().register(def);
+        self.world_mut().resource_mut::<ToolOptions>().tools.insert(id, options);
+        self
+    }
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+
+use crate::tools::ghost::preview::{GhostAssets, GhostPlacement, GhostPreview, GhostSystems, follow_cursor, sync_ghost};
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostAssets>()
+            .init_resource::<GhostPreview>()
+            .init_resource::<GhostPlacement>()
+            .add_systems(Update, (sync_ghost, follow_cursor).chain().in_set(GhostSystems));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod ghost_plugin;
+pub mod preview;
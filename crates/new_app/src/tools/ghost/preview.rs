@@ -0,0 +1,261 @@
+use std::sync::Arc;
+
+use bevy::camera::visibility::RenderLayers;
+use bevy::light::NotShadowCaster;
+use bevy::math::bounding::IntersectsVolume;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use new_core::VisibleViewports;
+use new_core::anchor::AnchorStrategy;
+use new_core::element::ElementId;
+use new_core::elements::ElementKind;
+
+use crate::analysis::clash::detection::ClashIndex;
+use crate::analysis::clash::narrow_phase::hard_clash;
+use crate::analysis::clash::shape::{ClashShape, triangles_aabb, world_triangles};
+use crate::editor::anchoring::anchor_cast::{AnchorCast, Anchored, ViewportRay};
+
+// Overlap a ghost may have with its neighbours before it counts as a clash, meters
+const CLASH_TOLERANCE: f32 = 0.005;
+
+// Everything that follows the cursor runs in here. Tools set the preview before and place
+// from the result after.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GhostSystems;
+
+// What the running tool previews under the cursor. Tools set it while they run and clear it
+// when they stop, the ghost is despawned as soon as it is None or owned by another tool.
+#[derive(Resource, Default, PartialEq)]
+pub struct GhostPreview(pub Option<GhostShape>);
+
+#[derive(Clone, PartialEq)]
+pub struct GhostShape {
+    // Tool showing the ghost
+    pub owner: &'static str,
+    pub mesh: Handle<Mesh>,
+    pub kind: ElementKind,
+    pub strategy: AnchorStrategy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GhostState {
+    #[default]
+    Free,
+    // On a host or an underlay point
+    Snapped,
+    // The strategy found nothing to anchor to, like embedding with no wall under the cursor
+    NoHost,
+    // Would run into this element
+    Clash(ElementId),
+}
+
+impl GhostState {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, GhostState::Free | GhostState::Snapped)
+    }
+}
+
+// Where the ghost stands this frame. Tools place from here so elements land where previewed.
+#[derive(Resource, Default)]
+pub struct GhostPlacement {
+    pub pane_id: Option<u32>,
+    pub anchored: Option<Anchored>,
+    pub state: GhostState,
+}
+
+#[derive(Component)]
+pub struct Ghost {
+    owner: &'static str,
+}
+
+#[derive(Resource)]
+pub struct GhostAssets {
+    free: Handle<StandardMaterial>,
+    snapped: Handle<StandardMaterial>,
+    invalid: Handle<StandardMaterial>,
+}
+
+impl FromWorld for GhostAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let mut tint = |color: Color| {
+            materials.add(StandardMaterial {
+                base_color: color,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })
+        };
+
+        Self {
+            free: tint(Color::srgba(0.6, 0.8, 1.0, 0.35)),
+            snapped: tint(Color::srgba(0.45, 0.9, 0.55, 0.4)),
+            invalid: tint(Color::srgba(1.0, 0.3, 0.3, 0.45)),
+        }
+    }
+}
+
+impl GhostAssets {
+    fn material(&self, state: GhostState) -> Handle<StandardMaterial> {
+        match state {
+            GhostState::Free => self.free.clone(),
+            GhostState::Snapped => self.snapped.clone(),
+            GhostState::NoHost | GhostState::Clash(_) => self.invalid.clone(),
+        }
+    }
+}
+
+// Keeps exactly one ghost for the current preview
+pub fn sync_ghost(
+    mut commands: Commands,
+    preview: Res<GhostPreview>,
+    assets: Res<GhostAssets>,
+    mut placement: ResMut<GhostPlacement>,
+    mut ghosts: Query<(Entity, &Ghost, &mut Mesh3d)>,
+) {
+    let mut shown = false;
+    for (entity, ghost, mut mesh) in &mut ghosts {
+        match &preview.0 {
+            Some(shape) if shape.owner == ghost.owner && !shown => {
+                if mesh.0 != shape.mesh {
+                    mesh.0 = shape.mesh.clone();
+                }
+                shown = true;
+            }
+            _ => commands.entity(entity).despawn(),
+        }
+    }
+
+    let Some(shape) = &preview.0 else {
+        *placement = GhostPlacement::default();
+        return;
+    };
+    if !shown {
+        commands.spawn((
+            Name::new(format!("Ghost ({})", shape.owner)),
+            Ghost { owner: shape.owner },
+            Mesh3d(shape.mesh.clone()),
+            MeshMaterial3d(assets.material(GhostState::Free)),
+            Transform::default(),
+            Visibility::Hidden,
+            RenderLayers::layer(0),
+            NotShadowCaster,
+        ));
+    }
+}
+
+// Ray through the cursor in whichever viewport it is over
+#[derive(SystemParam)]
+pub struct GhostCursor<'w, 's> {
+    window: Single<'w, 's, &'static Window, With<PrimaryWindow>>,
+    visible_viewports: Res<'w, VisibleViewports>,
+    viewport: ViewportRay<'w, 's>,
+}
+
+impl GhostCursor<'_, '_> {
+    fn ray(&self) -> Option<(u32, Ray3d)> {
+        let cursor = self.window.cursor_position()?;
+        let pane_id = self.visible_viewports.pane_at(cursor)?;
+        Some((pane_id, self.viewport.ray(pane_id, cursor)?))
+    }
+}
+
+type GhostEntity<'a> = (
+    &'a mut Transform,
+    &'a mut Visibility,
+    &'a mut MeshMaterial3d<StandardMaterial>,
+);
+
+// Anchors the ghost under the cursor like the element would be, then tints it by how it landed
+pub fn follow_cursor(
+    preview: Res<GhostPreview>,
+    mut placement: ResMut<GhostPlacement>,
+    assets: Res<GhostAssets>,
+    cursor: GhostCursor,
+    mut cast: AnchorCast,
+    clashes: GhostClashes,
+    mut ghosts: Query<GhostEntity, With<Ghost>>,
+) {
+    let Some(shape) = &preview.0 else {
+        return;
+    };
+    let Some((pane_id, ray)) = cursor.ray() else {
+        *placement = GhostPlacement::default();
+        for (_, mut visibility, _) in &mut ghosts {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+        return;
+    };
+
+    let anchored = cast.anchor_in_view(pane_id, shape.strategy, ray);
+    // With nothing to anchor to the ghost still shows where the cursor points, in red
+    let transform = match &anchored {
+        Some(anchored) => Some(anchored.transform),
+        None => cast.work_plane_point(pane_id, ray).map(Transform::from_translation),
+    };
+
+    let state = match &anchored {
+        None => GhostState::NoHost,
+        Some(anchored) => match clashes.first(shape, anchored) {
+            Some(id) => GhostState::Clash(id),
+            None if anchored.snapped => GhostState::Snapped,
+            None => GhostState::Free,
+        },
+    };
+
+    for (mut ghost_transform, mut visibility, mut material) in &mut ghosts {
+        match transform {
+            Some(transform) => {
+                ghost_transform.translation = transform.translation;
+                ghost_transform.rotation = transform.rotation;
+                visibility.set_if_neq(Visibility::Visible);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+        let tint = assets.material(state);
+        if material.0 != tint {
+            material.0 = tint;
+        }
+    }
+
+    *placement = GhostPlacement {
+        pane_id: Some(pane_id),
+        anchored,
+        state,
+    };
+}
+
+// Tests the ghost against the shapes the clash engine keeps up to date
+#[derive(SystemParam)]
+pub struct GhostClashes<'w> {
+    index: Res<'w, ClashIndex>,
+    meshes: Res<'w, Assets<Mesh>>,
+}
+
+impl GhostClashes<'_> {
+    // First element the ghost runs into. The host is left out, standing on it or in it is the point.
+    fn first(&self, shape: &GhostShape, anchored: &Anchored) -> Option<ElementId> {
+        let mesh = self.meshes.get(&shape.mesh)?;
+        let triangles = world_triangles(mesh, &GlobalTransform::from(anchored.transform))?;
+        let aabb = triangles_aabb(&triangles)?;
+        // Not an element yet, so it has no id of its own
+        let ghost = ClashShape {
+            id: ElementId(0),
+            kind: shape.kind,
+            level_id: None,
+            aabb,
+            triangles: Arc::new(triangles),
+        };
+
+        self.index
+            .shapes
+            .values()
+            .filter(|other| Some(other.id) != anchored.anchor.host && other.aabb.intersects(&ghost.aabb))
+            .find(|other| hard_clash(&ghost, other, CLASH_TOLERANCE).is_some())
+            .map(|other| other.id)
+    }
+}
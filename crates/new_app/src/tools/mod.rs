@@ -1,3 +1,5 @@
 pub mod architecture;
 pub mod modify;
-pub mod debug;
\ No newline at end of file
+pub mod debug;
+pub mod framework;
+pub mod ghost;
\ No newline at end of file
@@ -0,0 +1,211 @@
+// File: action.rs
+// Desc: Everything the user can ask the app to do, defined once. Menus, the command palette
+//       and the keymap are all drawn from the registry and all end in an ActionInvoked,
+//       which the plugin owning the action carries out.
+
+use bevy::prelude::*;
+use std::fmt;
+
+use crate::keymap::KeyBinding;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ActionId(pub &'static str);
+
+impl fmt::Display for ActionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+// Opens the palette itself, never kept as recently used
+pub const COMMAND_PALETTE: ActionId = ActionId("edit.command_palette");
+
+// Asked for before an action runs. With no choices any text is taken.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionPrompt {
+    pub label: &'static str,
+    pub choices: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ActionDef {
+    pub id: ActionId,
+    pub label: &'static str,
+    // Menu it is listed under, also shown next to it in the palette
+    pub menu: &'static str,
+    // Its own bindings, the keymap may replace them
+    pub bindings: Vec<KeyBinding>,
+    pub prompt: Option<ActionPrompt>,
+}
+
+const MENU_ORDER: [&str; 5] = ["File", "Edit", "View", "Window", "Tools"];
+
+#[derive(Resource, Default)]
+pub struct ActionRegistry {
+    pub actions: Vec<ActionDef>,
+}
+
+impl ActionRegistry {
+    // An action registered twice replaces the first one in place
+    pub fn register(&mut self, def: ActionDef) {
+        match self.actions.iter_mut().find(|action| action.id == def.id) {
+            Some(action) => *action = def,
+            None => self.actions.push(def),
+        }
+    }
+
+    pub fn get(&self, id: ActionId) -> Option<&ActionDef> {
+        self.actions.iter().find(|action| action.id == id)
+    }
+
+    // Menus in menu bar order, ones it does not know go last in the order they were registered
+    pub fn menus(&self) -> Vec<&'static str> {
+        let mut menus = Vec::new();
+        for action in &self.actions {
+            if !menus.contains(&action.menu) {
+                menus.push(action.menu);
+            }
+        }
+        menus.sort_by_key(|menu| MENU_ORDER.iter().position(|known| known == menu).unwrap_or(MENU_ORDER.len()));
+        menus
+    }
+}
+
+// Plugins add their actions with this when they are built
+pub trait ActionAppExt {
+    fn register_action(&mut self, def: ActionDef) -> &mut Self;
+}
+
+impl ActionAppExt for App {
+    fn register_action(&mut self, def: ActionDef) -> &mut Self {
+        self.init_resource::<ActionRegistry>();
+        self.world_mut().resource_mut::<ActionRegistry>().register(def);
+        self
+    }
+}
+
+// The argument is the answer to the prompt. An action that needs one and arrives without it
+// opens the palette to ask.
+#[derive(Message, Clone, Debug, PartialEq)]
+pub struct ActionInvoked {
+    pub id: ActionId,
+    pub arg: Option<String>,
+}
+
+impl ActionInvoked {
+    pub fn new(id: ActionId) -> Self {
+        Self { id, arg: None }
+    }
+}
+
+const RECENT_ACTIONS: usize = 10;
+
+// Most recent first
+#[derive(Resource, Default, Debug)]
+pub struct ActionHistory {
+    pub recent: Vec<ActionId>,
+}
+
+impl ActionHistory {
+    pub fn record(&mut self, id: ActionId) {
+        self.recent.retain(|recent| *recent != id);
+        self.recent.insert(0, id);
+        self.recent.truncate(RECENT_ACTIONS);
+    }
+
+    pub fn rank(&self, id: ActionId) -> Option<usize> {
+        self.recent.iter().position(|recent| *recent == id)
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct CommandPalette {
+    pub open: bool,
+    pub query: String,
+    // Row under the keyboard cursor
+    pub selected: usize,
+    // Action whose prompt is being answered
+    pub prompting: Option<ActionId>,
+}
+
+impl CommandPalette {
+    pub fn show(&mut self, prompting: Option<ActionId>) {
+        *self = Self {
+            open: true,
+            prompting,
+            ..default()
+        };
+    }
+}
+
+// The query's characters in order anywhere in the text, ignoring case. Runs of matched
+// characters and matches at the start of words score higher. None when it does not match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase) {
+        let found = (next..text.len()).find(|&i| text[i] == wanted)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        score -= (found - next).min(3) as i32;
+        previous = Some(found);
+        next = found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_needs_every_character_in_order() {
+        assert!(fuzzy_score("wall", "Wall Tool").is_some());
+        assert!(fuzzy_score("wt", "Wall Tool").is_some());
+        assert_eq!(fuzzy_score("tw", "Wall Tool"), None);
+        assert_eq!(fuzzy_score("walls", "Wall Tool"), None);
+    }
+
+    #[test]
+    fn fuzzy_score_ignores_case_and_spaces() {
+        assert_eq!(fuzzy_score("WALL TOOL", "wall tool"), fuzzy_score("walltool", "Wall Tool"));
+        assert_eq!(fuzzy_score("", "Wall Tool"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_runs_and_word_starts() {
+        let run = fuzzy_score("ex", "Export").unwrap();
+        let apart = fuzzy_score("ex", "Edit Box").unwrap();
+        assert!(run > apart);
+
+        let word_starts = fuzzy_score("ei", "Export IFC").unwrap();
+        let inside = fuzzy_score("ei", "Deselect Item").unwrap();
+        assert!(word_starts > inside);
+    }
+
+    #[test]
+    fn history_keeps_the_most_recent_first_without_repeats() {
+        let mut history = ActionHistory::default();
+        for id in ["a", "b", "a"] {
+            history.record(ActionId(id));
+        }
+        assert_eq!(history.recent, vec![ActionId("a"), ActionId("b")]);
+        assert_eq!(history.rank(ActionId("b")), Some(1));
+
+        for id in ["c", "d", "e", "f", "g", "h", "i", "j", "k", "l"] {
+            history.record(ActionId(id));
+        }
+        assert_eq!(history.recent.len(), RECENT_ACTIONS);
+        assert_eq!(history.rank(ActionId("l")), Some(0));
+        assert_eq!(history.rank(ActionId("a")), None);
+    }
+}
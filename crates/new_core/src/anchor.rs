@@ -0,0 +1,82 @@
+// File: anchor.rs
+// Desc: How point based elements sit on the model. The strategy is kept on each element with
+//       where it landed on its host, so it can be anchored again when the host moves.
+
+use bevy::prelude::*;
+use strum_macros::{Display, EnumIter};
+
+use crate::element::ElementId;
+use crate::elements::ElementKind;
+
+#[derive(EnumIter, Display, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum AnchorStrategy {
+    #[strum(to_string = "Float")]
+    Float,
+    #[default]
+    #[strum(to_string = "Raycast")]
+    RayCast,
+    #[strum(to_string = "Embedded")]
+    Embedded,
+    #[strum(to_string = "Gravity")]
+    Gravity,
+    #[strum(to_string = "Manifold")]
+    Manifold,
+}
+
+impl AnchorStrategy {
+    pub fn description(&self) -> &'static str {
+        match self {
+            AnchorStrategy::Float => "Stays where it is put on the ground plane, never follows anything",
+            AnchorStrategy::RayCast => "Sits on the surface under the cursor, turned to face out of it",
+            AnchorStrategy::Embedded => "Sits inside the wall or slab under the cursor, halfway through it",
+            AnchorStrategy::Gravity => "Drops upright onto the nearest surface below",
+            AnchorStrategy::Manifold => "Sticks to the face under the cursor, even when the host shape is edited",
+        }
+    }
+
+    // Elements that can be built into
+    pub fn can_embed(kind: ElementKind) -> bool {
+        matches!(
+            kind,
+            ElementKind::Wall
+                | ElementKind::CurtainWall
+                | ElementKind::Slab
+                | ElementKind::Roof
+                | ElementKind::Covering
+        )
+    }
+}
+
+// Where on the host the element landed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Attachment {
+    // Pose in the host's own frame, follows the host as one piece
+    Pose(Transform),
+    // Point on one triangle of the host mesh, follows the face when the shape changes too
+    Face { triangle: usize, barycentric: Vec3 },
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Anchor {
+    pub strategy: AnchorStrategy,
+    // None when floating or when nothing was hit
+    pub host: Option<ElementId>,
+    pub attachment: Option<Attachment>,
+}
+
+impl Anchor {
+    // Not attached yet, anchored from where the element stands
+    pub fn unattached(strategy: AnchorStrategy) -> Self {
+        Self {
+            strategy,
+            host: None,
+            attachment: None,
+        }
+    }
+}
+
+// Strategy new elements are placed with
+#[derive(Resource, Default, Debug)]
+pub struct PlacementSettings {
+    pub strategy: AnchorStrategy,
+}
@@ -0,0 +1,130 @@
+// File: asset_library.rs
+// Desc: Reusable element definitions. Geometry and default parameters per kind live in the
+//       library database, the browser only holds the rows of the current search.
+
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+use crate::drawing::Drawing;
+use crate::elements::ElementKind;
+use crate::model_exchange::ImportAs;
+
+// Rows shown for one search, narrow it down to find the rest
+pub const ASSET_PAGE: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LibraryAssetId(pub i64);
+
+// Line drawing of an asset in a unit square, y up
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Thumbnail {
+    pub polylines: Vec<Vec<Vec2>>,
+}
+
+impl Thumbnail {
+    // Scales the drawing into the unit square keeping its proportions
+    pub fn fit(drawing: &Drawing) -> Self {
+        let Some(bounds) = drawing.bounds() else {
+            return Self::default();
+        };
+        let size = bounds.size().max_element().max(f32::EPSILON);
+        let offset = (Vec2::splat(size) - bounds.size()) / 2.0;
+
+        let polylines = drawing
+            .polylines
+            .iter()
+            .map(|polyline| {
+                polyline
+                    .points
+                    .iter()
+                    .map(|point| (*point - bounds.min + offset) / size)
+                    .collect()
+            })
+            .collect();
+        Self { polylines }
+    }
+
+    // Point count then x y pairs per polyline, little endian
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for polyline in &self.polylines {
+            bytes.extend_from_slice(&(polyline.len() as u32).to_le_bytes());
+            for point in polyline {
+                bytes.extend_from_slice(&point.x.to_le_bytes());
+                bytes.extend_from_slice(&point.y.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    // A truncated blob keeps the polylines read before the damage
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut words = bytes
+            .chunks_exact(4)
+            .map(|chunk| [chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let mut polylines = Vec::new();
+
+        while let Some(count) = words.next() {
+            let points: Vec<Vec2> = (0..u32::from_le_bytes(count))
+                .map_while(|_| {
+                    let x = f32::from_le_bytes(words.next()?);
+                    let y = f32::from_le_bytes(words.next()?);
+                    Some(Vec2::new(x, y))
+                })
+                .collect();
+            polylines.push(points);
+        }
+        Self { polylines }
+    }
+}
+
+// One row of the browser, geometry stays in the database until an instance is placed
+#[derive(Clone, Debug)]
+pub struct LibraryAssetSummary {
+    pub id: LibraryAssetId,
+    pub name: String,
+    pub category: String,
+    pub kind: ElementKind,
+    pub thumbnail: Thumbnail,
+}
+
+// What the browser asks for, edited by the pane
+#[derive(Resource, Default, Debug)]
+pub struct AssetBrowser {
+    pub search: String,
+    pub category: Option<String>,
+    // Category new assets are filed under
+    pub new_category: String,
+    pub import_path: String,
+    pub import_as: ImportAs,
+    pub status: Option<String>,
+}
+
+// Result of the last search, written by the app
+#[derive(Resource, Default, Debug)]
+pub struct AssetResults {
+    pub categories: Vec<String>,
+    pub assets: Vec<LibraryAssetSummary>,
+    // Matches in the library, more than shown when over a page
+    pub total: usize,
+}
+
+// Placed from the library, instances of one asset share its mesh
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AssetInstance(pub LibraryAssetId);
+
+// One material of an asset with several, drawn as a child of the instance. The instance
+// keeps the whole mesh without a material for picking and measuring.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstancePart;
+
+#[derive(Message, Debug, Clone)]
+pub enum AssetCommand {
+    // Every selected element becomes an asset, located at its own origin
+    AddSelection { category: String },
+    // The whole scene becomes one asset
+    ImportGltf { path: PathBuf, category: String, import_as: ImportAs },
+    Delete(LibraryAssetId),
+    // Dropped on a viewport, cursor in window coordinates
+    Place { asset: LibraryAssetId, pane_id: u32, cursor: Vec2 },
+}
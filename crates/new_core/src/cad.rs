@@ -0,0 +1,337 @@
+// File: cad.rs
+// Desc: 2D CAD drawings brought in from DXF as underlays. An underlay lies flat at a level's
+//       elevation, can't be selected or edited, and offers its points to snapping.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::path::PathBuf;
+use strum_macros::{Display, EnumIter};
+
+use crate::element::ElementId;
+use crate::phase::ViewPhase;
+
+// Arcs and circles are drawn with a segment per this many radians
+const ARC_STEP: f32 = TAU / 64.0;
+// Snap points are binned in plan cells this size, meters
+const SNAP_CELL: f32 = 1.0;
+// Metres an underlay may sit off a work plane and still be traced on it
+const ELEVATION_TOLERANCE: f32 = 1e-3;
+
+// Drawing units of a DXF, $INSUNITS when the file has it
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CadUnits {
+    #[default]
+    #[strum(to_string = "Millimeters")]
+    Millimeters,
+    #[strum(to_string = "Centimeters")]
+    Centimeters,
+    #[strum(to_string = "Meters")]
+    Meters,
+    #[strum(to_string = "Inches")]
+    Inches,
+    #[strum(to_string = "Feet")]
+    Feet,
+}
+
+impl CadUnits {
+    pub fn meters(&self) -> f32 {
+        match self {
+            CadUnits::Millimeters => 0.001,
+            CadUnits::Centimeters => 0.01,
+            CadUnits::Meters => 1.0,
+            CadUnits::Inches => 0.0254,
+            CadUnits::Feet => 0.3048,
+        }
+    }
+
+    pub fn from_insunits(code: i32) -> Option<Self> {
+        match code {
+            1 => Some(CadUnits::Inches),
+            2 => Some(CadUnits::Feet),
+            4 => Some(CadUnits::Millimeters),
+            5 => Some(CadUnits::Centimeters),
+            6 => Some(CadUnits::Meters),
+            _ => None,
+        }
+    }
+}
+
+// Plan coordinates in meters, x east and y north
+#[derive(Clone, Debug, PartialEq)]
+pub enum CadEntity {
+    Line { a: Vec2, b: Vec2 },
+    Polyline { points: Vec<Vec2>, closed: bool },
+    // Counter clockwise from start to end, radians
+    Arc { center: Vec2, radius: f32, start: f32, end: f32 },
+    Circle { center: Vec2, radius: f32 },
+    Text { position: Vec2, height: f32, rotation: f32, text: String },
+}
+
+impl CadEntity {
+    // Straight pieces that draw the entity, text draws its baseline
+    pub fn segments(&self) -> Vec<(Vec2, Vec2)> {
+        let chain = |points: &[Vec2]| -> Vec<(Vec2, Vec2)> {
+            points.windows(2).map(|pair| (pair[0], pair[1])).collect()
+        };
+
+        match self {
+            CadEntity::Line { a, b } => vec![(*a, *b)],
+            CadEntity::Polyline { points, closed } => {
+                let mut segments = chain(points);
+                if *closed && points.len() > 2 {
+                    segments.push((points[points.len() - 1], points[0]));
+                }
+                segments
+            }
+            CadEntity::Arc { center, radius, start, end } => {
+                chain(&arc_points(*center, *radius, *start, *end))
+            }
+            CadEntity::Circle { center, radius } => chain(&arc_points(*center, *radius, 0.0, TAU)),
+            CadEntity::Text { position, height, rotation, text } => {
+                // Roughly the width of the text in a plain font
+                let width = 0.6 * height * text.chars().count() as f32;
+                vec![(*position, *position + Vec2::from_angle(*rotation) * width)]
+            }
+        }
+    }
+
+    // Ends, midpoints and centers
+    pub fn snap_points(&self) -> Vec<Vec2> {
+        match self {
+            CadEntity::Line { a, b } => vec![*a, *b, a.midpoint(*b)],
+            CadEntity::Polyline { .. } => self
+                .segments()
+                .into_iter()
+                .flat_map(|(a, b)| [a, a.midpoint(b), b])
+                .collect(),
+            CadEntity::Arc { center, radius, start, end } => vec![
+                *center,
+                *center + Vec2::from_angle(*start) * *radius,
+                *center + Vec2::from_angle(*end) * *radius,
+            ],
+            CadEntity::Circle { center, radius } => {
+                let mut points = vec![*center];
+                points.extend([Vec2::X, Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y].map(|axis| *center + axis * *radius));
+                points
+            }
+            CadEntity::Text { position, .. } => vec![*position],
+        }
+    }
+}
+
+pub fn arc_points(center: Vec2, radius: f32, start: f32, end: f32) -> Vec<Vec2> {
+    let mut sweep = (end - start).rem_euclid(TAU);
+    if sweep == 0.0 {
+        sweep = TAU;
+    }
+    let steps = ((sweep / ARC_STEP).ceil() as usize).max(1);
+    (0..=steps)
+        .map(|i| center + Vec2::from_angle(start + sweep * i as f32 / steps as f32) * radius)
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CadLayer {
+    pub name: String,
+    pub color: [u8; 3],
+    pub visible: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CadItem {
+    pub layer: usize,
+    pub entity: CadEntity,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CadDrawing {
+    pub layers: Vec<CadLayer>,
+    pub items: Vec<CadItem>,
+}
+
+impl CadDrawing {
+    pub fn layer_index(&mut self, name: &str) -> usize {
+        if let Some(index) = self.layers.iter().position(|layer| layer.name == name) {
+            return index;
+        }
+        self.layers.push(CadLayer {
+            name: name.to_owned(),
+            color: [255, 255, 255],
+            visible: true,
+        });
+        self.layers.len() - 1
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UnderlayId(pub u32);
+
+// Snap points of a drawing binned by plan cell, with the layer each one came from.
+// Built once at load, the drawing itself never changes, only which layers are shown.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SnapGrid {
+    cells: HashMap<IVec2, Vec<(Vec2, usize)>>,
+}
+
+impl SnapGrid {
+    pub fn new(drawing: &CadDrawing) -> Self {
+        let mut cells: HashMap<IVec2, Vec<(Vec2, usize)>> = HashMap::new();
+        for item in &drawing.items {
+            for point in item.entity.snap_points() {
+                cells.entry(cell(point)).or_default().push((point, item.layer));
+            }
+        }
+        Self { cells }
+    }
+
+    // Points within radius of the target, walking only the cells the radius reaches
+    pub fn near(&self, target: Vec2, radius: f32) -> impl Iterator<Item = (Vec2, usize)> + '_ {
+        let (min, max) = (cell(target - radius), cell(target + radius));
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .copied()
+            .filter(move |(point, _)| point.distance_squared(target) <= radius * radius)
+    }
+}
+
+fn cell(point: Vec2) -> IVec2 {
+    (point / SNAP_CELL).floor().as_ivec2()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Underlay {
+    pub id: UnderlayId,
+    pub name: String,
+    pub source: PathBuf,
+    pub level_id: Option<ElementId>,
+    // Height of the level it lies on
+    pub elevation: f32,
+    pub visible: bool,
+    pub drawing: CadDrawing,
+    snaps: SnapGrid,
+}
+
+impl Underlay {
+    pub fn new(
+        name: String,
+        source: PathBuf,
+        level_id: Option<ElementId>,
+        elevation: f32,
+        drawing: CadDrawing,
+    ) -> Self {
+        Self {
+            id: UnderlayId(0),
+            name,
+            source,
+            level_id,
+            elevation,
+            visible: true,
+            snaps: SnapGrid::new(&drawing),
+            drawing,
+        }
+    }
+
+    // On the level when both know theirs, otherwise at the same height
+    pub fn lies_on(&self, level_id: Option<ElementId>, elevation: f32) -> bool {
+        match (self.level_id, level_id) {
+            (Some(own), Some(level)) => own == level,
+            _ => (self.elevation - elevation).abs() < ELEVATION_TOLERANCE,
+        }
+    }
+
+    // Plan point to model space, north is -Z
+    pub fn to_world(&self, point: Vec2) -> Vec3 {
+        Vec3::new(point.x, self.elevation, -point.y)
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct Underlays {
+    pub underlays: Vec<Underlay>,
+    next_id: u32,
+}
+
+impl Underlays {
+    pub fn add(&mut self, mut underlay: Underlay) -> UnderlayId {
+        self.next_id += 1;
+        underlay.id = UnderlayId(self.next_id);
+        self.underlays.push(underlay);
+        UnderlayId(self.next_id)
+    }
+
+    pub fn remove(&mut self, id: UnderlayId) {
+        self.underlays.retain(|underlay| underlay.id != id);
+    }
+
+    pub fn get_mut(&mut self, id: UnderlayId) -> Option<&mut Underlay> {
+        self.underlays.iter_mut().find(|underlay| underlay.id == id)
+    }
+
+    // Nearest point of a shown layer within radius of the model space point, measured in plan.
+    // Only underlays on the level, or at the height of the point without one, are traced.
+    pub fn snap(&self, point: Vec3, level_id: Option<ElementId>, radius: f32) -> Option<Vec3> {
+        let target = Vec2::new(point.x, -point.z);
+
+        self.underlays
+            .iter()
+            .filter(|underlay| underlay.visible && underlay.lies_on(level_id, point.y))
+            .flat_map(|underlay| {
+                underlay
+                    .snaps
+                    .near(target, radius)
+                    .filter(|(_, layer)| underlay.drawing.layers[*layer].visible)
+                    .map(|(snap, _)| (snap.distance_squared(target), underlay.to_world(snap)))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, snap)| snap)
+    }
+}
+
+// What the CAD pane is working on
+#[derive(Resource, Debug)]
+pub struct CadExchange {
+    pub import_path: String,
+    // None reads the units from the file
+    pub units: Option<CadUnits>,
+    pub level_id: Option<ElementId>,
+    pub elevation: f32,
+    pub export_path: String,
+    pub cut_height: f32,
+    pub phase: ViewPhase,
+    pub status: Option<String>,
+}
+
+impl Default for CadExchange {
+    fn default() -> Self {
+        Self {
+            import_path: String::new(),
+            units: None,
+            level_id: None,
+            elevation: 0.0,
+            export_path: String::new(),
+            cut_height: 1.2,
+            phase: ViewPhase::default(),
+            status: None,
+        }
+    }
+}
+
+#[derive(Message, Debug, Clone)]
+pub enum CadCommand {
+    Import {
+        path: PathBuf,
+        units: Option<CadUnits>,
+        level_id: Option<ElementId>,
+        elevation: f32,
+    },
+    Remove(UnderlayId),
+    // Plan of the model cut at the height, one layer per element kind
+    ExportPlan {
+        path: PathBuf,
+        cut_height: f32,
+        phase: ViewPhase,
+    },
+}
@@ -0,0 +1,251 @@
+// File: clash.rs
+// Desc: Clash rules and clash results shared between the engine and the ui
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use strum_macros::{Display, EnumIter};
+
+use crate::element::ElementId;
+use crate::elements::ElementKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClashRuleId(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClashTest {
+    // Geometry intersects. Tolerance is the penetration that is still accepted.
+    Hard,
+    // Geometry is closer than the tolerance without touching.
+    Clearance,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClashRule {
+    pub id: ClashRuleId,
+    pub name: String,
+    pub kind_a: ElementKind,
+    pub kind_b: ElementKind,
+    pub test: ClashTest,
+    pub tolerance: f32,
+    pub enabled: bool,
+}
+
+impl ClashRule {
+    pub fn matches(&self, a: ElementKind, b: ElementKind) -> bool {
+        self.enabled
+            && ((self.kind_a == a && self.kind_b == b) || (self.kind_a == b && self.kind_b == a))
+    }
+
+    // How far element bounds must be grown so the broad phase does not miss a pair
+    pub fn reach(&self) -> f32 {
+        match self.test {
+            ClashTest::Hard => 0.0,
+            ClashTest::Clearance => self.tolerance.max(0.0),
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct ClashRules {
+    pub rules: Vec<ClashRule>,
+}
+
+impl ClashRules {
+    pub fn get(&self, id: ClashRuleId) -> Option<&ClashRule> {
+        self.rules.iter().find(|rule| rule.id == id)
+    }
+
+    pub fn matching(&self, a: ElementKind, b: ElementKind) -> impl Iterator<Item = &ClashRule> {
+        self.rules.iter().filter(move |rule| rule.matches(a, b))
+    }
+
+    pub fn max_reach(&self) -> f32 {
+        self.rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .map(ClashRule::reach)
+            .fold(0.0, f32::max)
+    }
+
+    pub fn add(
+        &mut self,
+        name: &str,
+        kind_a: ElementKind,
+        kind_b: ElementKind,
+        test: ClashTest,
+        tolerance: f32,
+    ) -> ClashRuleId {
+        let id = ClashRuleId(self.rules.iter().map(|rule| rule.id.0 + 1).max().unwrap_or(1));
+        self.rules.push(ClashRule {
+            id,
+            name: name.to_owned(),
+            kind_a,
+            kind_b,
+            test,
+            tolerance,
+            enabled: true,
+        });
+        id
+    }
+}
+
+impl Default for ClashRules {
+    fn default() -> Self {
+        let mut rules = Self { rules: Vec::new() };
+
+        // Services against structure
+        for structure in [ElementKind::Beam, ElementKind::Column, ElementKind::Slab, ElementKind::Wall] {
+            for service in [ElementKind::DuctSegment, ElementKind::PipeSegment, ElementKind::CableCarrierSegment] {
+                rules.add(
+                    &format!("{service} vs {structure}"),
+                    service,
+                    structure,
+                    ClashTest::Hard,
+                    0.01,
+                );
+            }
+        }
+
+        // Services against services
+        rules.add("Duct vs Pipe", ElementKind::DuctSegment, ElementKind::PipeSegment, ClashTest::Hard, 0.0);
+        rules.add("Duct vs Duct", ElementKind::DuctSegment, ElementKind::DuctSegment, ClashTest::Hard, 0.0);
+        rules.add("Pipe vs Pipe", ElementKind::PipeSegment, ElementKind::PipeSegment, ClashTest::Hard, 0.0);
+        rules.add(
+            "Cable Tray Clearance",
+            ElementKind::CableCarrierSegment,
+            ElementKind::PipeSegment,
+            ClashTest::Clearance,
+            0.15,
+        );
+
+        rules
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClashStatus {
+    New,
+    Active,
+    Resolved,
+}
+
+// Pair is always stored with the lower entity first so a->b and b->a map to the same clash
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClashKey {
+    pub rule: ClashRuleId,
+    pub a: Entity,
+    pub b: Entity,
+}
+
+impl ClashKey {
+    pub fn new(rule: ClashRuleId, a: Entity, b: Entity) -> Self {
+        if a <= b {
+            Self { rule, a, b }
+        } else {
+            Self { rule, a: b, b: a }
+        }
+    }
+
+    pub fn involves(&self, entity: Entity) -> bool {
+        self.a == entity || self.b == entity
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ClashComment {
+    pub author: String,
+    pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Clash {
+    pub key: ClashKey,
+    pub a_id: ElementId,
+    pub b_id: ElementId,
+    pub level_id: Option<ElementId>,
+    pub test: ClashTest,
+    // Representative world position of the clash
+    pub point: Vec3,
+    // Penetration for hard clashes, remaining gap for clearance clashes
+    pub distance: f32,
+    pub status: ClashStatus,
+    // Status was set by a reviewer. Kept through retests until either element changes shape.
+    pub reviewed: bool,
+
+    // Review
+    pub assignee: Option<String>,
+    pub comments: Vec<ClashComment>,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct ClashResults {
+    pub clashes: HashMap<ClashKey, Clash>,
+}
+
+impl ClashResults {
+    pub fn for_entity(&self, entity: Entity) -> impl Iterator<Item = &Clash> {
+        self.clashes.values().filter(move |clash| clash.key.involves(entity))
+    }
+
+    pub fn count(&self, status: ClashStatus) -> usize {
+        self.clashes.values().filter(|clash| clash.status == status).count()
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ClashGrouping {
+    #[default]
+    #[strum(to_string = "Rule")]
+    Rule,
+    #[strum(to_string = "Level")]
+    Level,
+    #[strum(to_string = "Element Pair")]
+    ElementPair,
+}
+
+// Review pane state. Lives here so the engine can see which clash is being looked at.
+#[derive(Resource, Debug)]
+pub struct ClashReview {
+    pub grouping: ClashGrouping,
+    pub show_new: bool,
+    pub show_active: bool,
+    pub show_resolved: bool,
+    pub selected: Option<ClashKey>,
+    pub reviewer: String,
+    pub comment_draft: String,
+}
+
+impl Default for ClashReview {
+    fn default() -> Self {
+        let reviewer = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "Reviewer".to_owned());
+
+        Self {
+            grouping: ClashGrouping::Rule,
+            show_new: true,
+            show_active: true,
+            show_resolved: false,
+            selected: None,
+            reviewer,
+            comment_draft: String::new(),
+        }
+    }
+}
+
+impl ClashReview {
+    pub fn shows(&self, status: ClashStatus) -> bool {
+        match status {
+            ClashStatus::New => self.show_new,
+            ClashStatus::Active => self.show_active,
+            ClashStatus::Resolved => self.show_resolved,
+        }
+    }
+}
+
+#[derive(Message, Debug, Clone, Copy)]
+pub enum ClashCommand {
+    // Zoom the active viewport to the clash and isolate both elements
+    Focus(ClashKey),
+    ClearIsolation,
+}
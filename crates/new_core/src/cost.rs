@@ -0,0 +1,269 @@
+// File: cost.rs
+// Desc: 5D costing. Cost items with unit rates, per element quantities and the running estimate.
+
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use strum_macros::{Display, EnumIter};
+
+use crate::element::{ElementHeader, ElementId};
+use crate::elements::ElementKind;
+use crate::elements::element_kindtype_enums::CostScheduleType;
+use crate::units::Measure;
+
+// What a unit rate is paid for
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum QuantityBasis {
+    #[default]
+    #[strum(to_string = "Count")]
+    Count,
+    #[strum(to_string = "Length")]
+    Length,
+    #[strum(to_string = "Area")]
+    Area,
+    #[strum(to_string = "Volume")]
+    Volume,
+}
+
+impl QuantityBasis {
+    // None for a count
+    pub fn measure(&self) -> Option<Measure> {
+        match self {
+            QuantityBasis::Count => None,
+            QuantityBasis::Length => Some(Measure::Length),
+            QuantityBasis::Area => Some(Measure::Area),
+            QuantityBasis::Volume => Some(Measure::Volume),
+        }
+    }
+}
+
+// Measured from the element geometry in meters.
+// Length runs along the axis of framing and runs, area is the axis times the section's wide side.
+// Elements with an outline, rooms, take the length around it and the area inside less holes.
+// Anything else falls back on its mesh box: the longest side, and the two longest multiplied.
+// Volume is the enclosed mesh volume.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Quantities {
+    pub count: f64,
+    pub length: f64,
+    pub area: f64,
+    pub volume: f64,
+}
+
+impl Quantities {
+    pub fn get(&self, basis: QuantityBasis) -> f64 {
+        match basis {
+            QuantityBasis::Count => self.count,
+            QuantityBasis::Length => self.length,
+            QuantityBasis::Area => self.area,
+            QuantityBasis::Volume => self.volume,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CostItemId(pub u32);
+
+// IfcCostItem
+#[derive(Clone, Debug)]
+pub struct CostItem {
+    pub id: CostItemId,
+    pub code: String,
+    pub description: String,
+    pub basis: QuantityBasis,
+    pub unit_rate: f64,
+    // Priced elements. A spec match wins over a kind match.
+    pub spec: Option<ElementId>,
+    pub kind: Option<ElementKind>,
+}
+
+// IfcCostSchedule
+#[derive(Resource, Clone, Debug)]
+pub struct CostSchedule {
+    pub name: String,
+    pub schedule_type: CostScheduleType,
+    pub currency: String,
+    pub items: Vec<CostItem>,
+}
+
+impl CostSchedule {
+    pub fn get(&self, id: CostItemId) -> Option<&CostItem> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    pub fn item_for(&self, header: &ElementHeader) -> Option<&CostItem> {
+        header
+            .spec_id
+            .and_then(|spec| self.items.iter().find(|item| item.spec == Some(spec)))
+            .or_else(|| {
+                self.items
+                    .iter()
+                    .find(|item| item.spec.is_none() && item.kind == Some(header.kind))
+            })
+    }
+
+    pub fn add(
+        &mut self,
+        code: &str,
+        description: &str,
+        basis: QuantityBasis,
+        unit_rate: f64,
+        kind: Option<ElementKind>,
+    ) -> CostItemId {
+        let id = CostItemId(self.items.iter().map(|item| item.id.0 + 1).max().unwrap_or(1));
+        self.items.push(CostItem {
+            id,
+            code: code.to_owned(),
+            description: description.to_owned(),
+            basis,
+            unit_rate,
+            spec: None,
+            kind,
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: CostItemId) {
+        self.items.retain(|item| item.id != id);
+    }
+}
+
+impl Default for CostSchedule {
+    fn default() -> Self {
+        let mut schedule = Self {
+            name: "Estimate".to_owned(),
+            schedule_type: CostScheduleType::ESTIMATE,
+            currency: "EUR".to_owned(),
+            items: Vec::new(),
+        };
+
+        use ElementKind as K;
+        use QuantityBasis as Q;
+
+        schedule.add("23.31", "Ductwork", Q::Length, 85.0, Some(K::DuctSegment));
+        schedule.add("22.11", "Pipework", Q::Length, 60.0, Some(K::PipeSegment));
+        schedule.add("26.05", "Cable tray", Q::Length, 40.0, Some(K::CableCarrierSegment));
+        schedule.add("03.30", "Concrete slab", Q::Volume, 180.0, Some(K::Slab));
+        schedule.add("04.20", "Walls", Q::Area, 95.0, Some(K::Wall));
+        schedule.add("03.31", "Concrete columns", Q::Volume, 240.0, Some(K::Column));
+        schedule.add("05.12", "Steel beams", Q::Length, 150.0, Some(K::Beam));
+
+        schedule
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CostLine {
+    pub element: ElementId,
+    pub kind: ElementKind,
+    pub spec_id: Option<ElementId>,
+    pub level_id: Option<ElementId>,
+    pub quantities: Quantities,
+    pub item: Option<CostItemId>,
+    // Quantity on the item basis and its price, zero when the element is not priced
+    pub quantity: f64,
+    pub cost: f64,
+}
+
+impl CostLine {
+    // Looks the item up again, rates or assignments may have changed
+    pub fn price(&mut self, schedule: &CostSchedule, header: &ElementHeader) {
+        match schedule.item_for(header) {
+            Some(item) => {
+                self.item = Some(item.id);
+                self.quantity = self.quantities.get(item.basis);
+                self.cost = self.quantity * item.unit_rate;
+            }
+            None => {
+                self.item = None;
+                self.quantity = 0.0;
+                self.cost = 0.0;
+            }
+        }
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CostGrouping {
+    #[default]
+    #[strum(to_string = "Cost Code")]
+    CostCode,
+    #[strum(to_string = "Level")]
+    Level,
+    #[strum(to_string = "Kind")]
+    Kind,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CostRollup {
+    pub elements: usize,
+    pub quantity: f64,
+    pub basis: Option<QuantityBasis>,
+    // Lines priced on different bases, the quantity sum means nothing
+    pub mixed: bool,
+    pub cost: f64,
+}
+
+impl CostRollup {
+    pub fn quantity(&self) -> Option<(f64, QuantityBasis)> {
+        if self.mixed {
+            return None;
+        }
+        self.basis.map(|basis| (self.quantity, basis))
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct CostEstimate {
+    pub lines: HashMap<Entity, CostLine>,
+}
+
+impl CostEstimate {
+    pub fn total(&self) -> f64 {
+        self.lines.values().map(|line| line.cost).sum()
+    }
+
+    pub fn rollup(
+        &self,
+        schedule: &CostSchedule,
+        grouping: CostGrouping,
+    ) -> BTreeMap<String, CostRollup> {
+        let mut groups: BTreeMap<String, CostRollup> = BTreeMap::new();
+
+        for line in self.lines.values() {
+            let item = line.item.and_then(|id| schedule.get(id));
+
+            let group = match grouping {
+                CostGrouping::CostCode => match item {
+                    Some(item) => format!("{} {}", item.code, item.description),
+                    None => "Unpriced".to_owned(),
+                },
+                CostGrouping::Level => match line.level_id {
+                    Some(level) => format!("Level #{}", level.0),
+                    None => "No Level".to_owned(),
+                },
+                CostGrouping::Kind => line.kind.to_string(),
+            };
+
+            let basis = item.map(|item| item.basis);
+            let rollup = groups.entry(group).or_default();
+
+            if rollup.elements == 0 {
+                rollup.basis = basis;
+            } else if rollup.basis != basis {
+                rollup.mixed = true;
+            }
+
+            rollup.elements += 1;
+            rollup.quantity += line.quantity;
+            rollup.cost += line.cost;
+        }
+
+        groups
+    }
+}
+
+// Cost pane state
+#[derive(Resource, Default, Debug)]
+pub struct CostView {
+    pub grouping: CostGrouping,
+}
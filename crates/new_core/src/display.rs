@@ -0,0 +1,69 @@
+// File: display.rs
+// Desc: Per element display overrides. Features that hide, ghost or tint elements
+//       write their own entry and a single resolver turns the stack into what is drawn.
+
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ElementAppearance {
+    Normal,
+    Ghost,
+    Tint(Color),
+    Hidden,
+}
+
+impl ElementAppearance {
+    // Stronger overrides win when several sources disagree
+    pub fn strength(&self) -> u8 {
+        match self {
+            ElementAppearance::Normal => 0,
+            ElementAppearance::Tint(_) => 1,
+            ElementAppearance::Ghost => 2,
+            ElementAppearance::Hidden => 3,
+        }
+    }
+}
+
+// Who asked for an override. Order breaks ties between equally strong overrides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DisplaySource {
+    Timeline,
+    ClashIsolation,
+}
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct DisplayOverrides {
+    entries: Vec<(DisplaySource, ElementAppearance)>,
+}
+
+impl DisplayOverrides {
+    pub fn get(&self, source: DisplaySource) -> Option<ElementAppearance> {
+        self.entries
+            .iter()
+            .find(|(s, _)| *s == source)
+            .map(|(_, appearance)| *appearance)
+    }
+
+    pub fn set(&mut self, source: DisplaySource, appearance: ElementAppearance) {
+        match self.entries.iter_mut().find(|(s, _)| *s == source) {
+            Some(entry) => entry.1 = appearance,
+            None => self.entries.push((source, appearance)),
+        }
+    }
+
+    pub fn clear(&mut self, source: DisplaySource) {
+        self.entries.retain(|(s, _)| *s != source);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn resolve(&self) -> ElementAppearance {
+        self.entries
+            .iter()
+            .max_by_key(|(source, appearance)| (appearance.strength(), *source))
+            .map(|(_, appearance)| *appearance)
+            .unwrap_or(ElementAppearance::Normal)
+    }
+}
@@ -0,0 +1,203 @@
+// File: drawing.rs
+// Desc: 2D drawings of the model. A view projects the elements onto a plane as vector linework,
+//       plans and sections also cut them.
+
+use bevy::prelude::*;
+use strum_macros::{Display, EnumIter};
+
+use crate::elements::ElementKind;
+use crate::phase::ViewPhase;
+
+// North is -Z and east is +X, up is +Y
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Compass {
+    #[default]
+    #[strum(to_string = "North")]
+    North,
+    #[strum(to_string = "East")]
+    East,
+    #[strum(to_string = "South")]
+    South,
+    #[strum(to_string = "West")]
+    West,
+}
+
+impl Compass {
+    pub fn direction(&self) -> Vec3 {
+        match self {
+            Compass::North => Vec3::NEG_Z,
+            Compass::East => Vec3::X,
+            Compass::South => Vec3::Z,
+            Compass::West => Vec3::NEG_X,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ViewKind {
+    // Looking down, cut at the height above zero
+    Plan { cut_height: f32 },
+    // Looking towards facing, cut where the distance along facing equals offset
+    Section { facing: Compass, offset: f32 },
+    // The face of the model on the given side, seen from outside
+    Elevation { side: Compass },
+}
+
+impl ViewKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ViewKind::Plan { .. } => "Plan",
+            ViewKind::Section { .. } => "Section",
+            ViewKind::Elevation { .. } => "Elevation",
+        }
+    }
+
+    pub fn frame(&self) -> ViewFrame {
+        let (direction, up, cut) = match *self {
+            ViewKind::Plan { cut_height } => (Vec3::NEG_Y, Vec3::NEG_Z, Some(-cut_height)),
+            ViewKind::Section { facing, offset } => (facing.direction(), Vec3::Y, Some(offset)),
+            ViewKind::Elevation { side } => (-side.direction(), Vec3::Y, None),
+        };
+
+        ViewFrame {
+            right: direction.cross(up),
+            up,
+            direction,
+            cut,
+        }
+    }
+}
+
+// Paper axes and viewing direction of a view in model space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewFrame {
+    pub right: Vec3,
+    pub up: Vec3,
+    pub direction: Vec3,
+    // Depth of the cut plane, everything nearer is cut away
+    pub cut: Option<f32>,
+}
+
+impl ViewFrame {
+    pub fn project(&self, point: Vec3) -> Vec2 {
+        Vec2::new(point.dot(self.right), point.dot(self.up))
+    }
+
+    pub fn depth(&self, point: Vec3) -> f32 {
+        point.dot(self.direction)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawingView {
+    pub name: String,
+    pub kind: ViewKind,
+    pub phase: ViewPhase,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LineKind {
+    // Where the cut plane goes through material
+    Cut,
+    // Outline where the surface turns away from the viewer
+    Silhouette,
+    // Where two faces meet at an angle
+    Crease,
+    // Other edges seen beyond the cut or in elevation, open borders of a surface
+    Projection,
+    // Axes of a structural grid
+    Grid,
+}
+
+// Pen widths in millimeters on paper, the ISO 128 series
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LineWeight {
+    #[strum(to_string = "0.13")]
+    ExtraFine,
+    #[strum(to_string = "0.18")]
+    Fine,
+    #[default]
+    #[strum(to_string = "0.25")]
+    Thin,
+    #[strum(to_string = "0.35")]
+    Medium,
+    #[strum(to_string = "0.50")]
+    Thick,
+    #[strum(to_string = "0.70")]
+    Heavy,
+}
+
+impl LineWeight {
+    pub fn mm(&self) -> f32 {
+        match self {
+            LineWeight::ExtraFine => 0.13,
+            LineWeight::Fine => 0.18,
+            LineWeight::Thin => 0.25,
+            LineWeight::Medium => 0.35,
+            LineWeight::Thick => 0.5,
+            LineWeight::Heavy => 0.7,
+        }
+    }
+}
+
+impl LineKind {
+    pub fn weight(&self) -> LineWeight {
+        match self {
+            LineKind::Cut => LineWeight::Thick,
+            LineKind::Silhouette => LineWeight::Medium,
+            LineKind::Crease => LineWeight::Fine,
+            LineKind::Projection => LineWeight::Fine,
+            LineKind::Grid => LineWeight::ExtraFine,
+        }
+    }
+}
+
+// Tag carried by every line so an exporter can pick a pen or a layer for it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LineStyle {
+    pub kind: LineKind,
+    // Element the line was drawn from
+    pub element: ElementKind,
+    // Existing work in a phased view
+    pub halftone: bool,
+    // Demolished work in a phased view
+    pub dashed: bool,
+}
+
+// Connected run of points in view coordinates, meters
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawingPolyline {
+    pub points: Vec<Vec2>,
+    pub style: LineStyle,
+}
+
+impl DrawingPolyline {
+    pub fn segments(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.points.windows(2).map(|pair| (pair[0], pair[1]))
+    }
+}
+
+// Marks drawn at a fixed size on paper whatever the scale, placed in view coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub enum Annotation {
+    // Circle with the axis tag, sitting past the end of the axis
+    Bubble { end: Vec2, outward: Vec2, tag: String },
+    // Distance between two points, drawn beside them on the outward side
+    Dimension { a: Vec2, b: Vec2, outward: Vec2, text: String },
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Drawing {
+    pub polylines: Vec<DrawingPolyline>,
+    pub annotations: Vec<Annotation>,
+}
+
+impl Drawing {
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut points = self.polylines.iter().flat_map(|line| line.points.iter().copied());
+        let first = points.next()?;
+        Some(points.fold(Rect::from_corners(first, first), |rect, point| {
+            rect.union_point(point)
+        }))
+    }
+}
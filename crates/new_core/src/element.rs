@@ -1,5 +1,6 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use  bevy::prelude::*;
+use strum_macros::{Display, EnumIter};
 
 use crate::elements::{
     ElementKind,
@@ -8,7 +9,7 @@ use crate::elements::{
 
 // The id of each element
 // Later will be revised to use timestampt bit + node + seq
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ElementId(pub i64);
 
 // Parameters implementation.
@@ -27,6 +28,74 @@ pub enum ParamValue {
 
 pub type ElementParams = BTreeMap<ParamKey, ParamValue>;
 
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParamType {
+    Bool,
+    Int,
+    Float,
+    Text,
+    #[strum(to_string = "Element")]
+    ElementRef,
+}
+
+impl ParamKey {
+    pub fn new(key: &str) -> Self {
+        Self(key.to_owned())
+    }
+}
+
+impl ParamValue {
+    pub fn param_type(&self) -> ParamType {
+        match self {
+            ParamValue::Bool(_) => ParamType::Bool,
+            ParamValue::Int(_) => ParamType::Int,
+            ParamValue::Float(_) => ParamType::Float,
+            ParamValue::Text(_) => ParamType::Text,
+            ParamValue::ElemenentRef(_) => ParamType::ElementRef,
+        }
+    }
+
+    // Reads a value typed by a user or a spreadsheet, None when it does not fit the type
+    pub fn parse(param_type: ParamType, text: &str) -> Option<Self> {
+        let text = text.trim();
+        match param_type {
+            ParamType::Bool => match text.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Some(ParamValue::Bool(true)),
+                "false" | "no" | "0" => Some(ParamValue::Bool(false)),
+                _ => None,
+            },
+            ParamType::Int => text.parse().ok().map(ParamValue::Int),
+            ParamType::Float => text.parse().ok().map(ParamValue::Float),
+            ParamType::Text => Some(ParamValue::Text(text.to_owned())),
+            ParamType::ElementRef => text
+                .trim_start_matches('#')
+                .parse()
+                .ok()
+                .map(|id| ParamValue::ElemenentRef(ElementId(id))),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ParamValue::Int(value) => Some(*value as f64),
+            ParamValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamValue::Bool(value) => write!(f, "{}", if *value { "Yes" } else { "No" }),
+            ParamValue::Int(value) => write!(f, "{value}"),
+            ParamValue::Float(value) => write!(f, "{value}"),
+            ParamValue::Text(value) => write!(f, "{value}"),
+            ParamValue::ElemenentRef(id) => write!(f, "#{}", id.0),
+        }
+    }
+}
+
 // Element base class
 #[derive(Component, Clone, Debug)]
 pub struct ElementHeader {
@@ -36,6 +105,47 @@ pub struct ElementHeader {
     pub kind_type: Option<ElementKindType>,
     pub spec_id: Option<ElementId>,
     pub level_id: Option<ElementId>,
-    // pub params: ElementParams
+    pub params: ElementParams,
 }
 
+// Hands out ids for elements created in this session
+#[derive(Resource, Debug)]
+pub struct ElementIdAllocator {
+    next: i64,
+}
+
+impl Default for ElementIdAllocator {
+    fn default() -> Self {
+        Self { next: 1 }
+    }
+}
+
+impl ElementIdAllocator {
+    pub fn allocate(&mut self) -> ElementId {
+        let id = ElementId(self.next);
+        self.next += 1;
+        id
+    }
+
+    // Make sure ids coming from outside (db, import) are never handed out again
+    pub fn reserve(&mut self, id: ElementId) {
+        self.next = self.next.max(id.0 + 1);
+    }
+}
+
+// Lookup from element id to the entity that currently holds it
+#[derive(Resource, Default, Debug)]
+pub struct ElementIndex {
+    pub entities: HashMap<ElementId, Entity>,
+    pub ids: HashMap<Entity, ElementId>,
+}
+
+impl ElementIndex {
+    pub fn entity(&self, id: ElementId) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+
+    pub fn id(&self, entity: Entity) -> Option<ElementId> {
+        self.ids.get(&entity).copied()
+    }
+}
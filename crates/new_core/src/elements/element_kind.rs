@@ -11,9 +11,9 @@
 // - Prefer specific variants over generic fallback variants when authoring.
 // - Generic/deprecated variants are kept mainly for import compatibility.
 
-use strum_macros::Display;
+use strum_macros::{Display, EnumIter};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display, EnumIter)]
 pub enum ElementKind {
     // Annotation
     Annotation, // IfcAnnotation
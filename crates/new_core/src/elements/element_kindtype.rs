@@ -1,11 +1,12 @@
 // File: element_kindtype.rs
 // Desc: Contains enum wrapper for the entire ElementKindType
 
+use serde::{Deserialize, Serialize};
 use strum_macros::Display;
 
 use crate::elements::element_kindtype_enums::*;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
 pub enum ElementKindType {
     ActionRequest(ActionRequestType),
     Actuator(ActuatorType),
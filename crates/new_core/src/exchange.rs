@@ -0,0 +1,371 @@
+// File: exchange.rs
+// Desc: Spreadsheet round trip. Schedules and element parameters go out as sheets,
+//       edited sheets come back as a checked list of changes that is applied all at once.
+
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use strum_macros::{Display, EnumIter};
+
+use crate::element::{ElementHeader, ElementId, ParamKey, ParamType, ParamValue};
+use crate::schedule::{ScheduleColumn, ScheduleDefinition, ScheduleField, ScheduleId, ScheduleRow};
+
+// First column of every exported sheet, rows are matched back to elements by it
+pub const ELEMENT_ID_HEADING: &str = "ElementId";
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SheetFormat {
+    #[strum(to_string = "CSV")]
+    Csv,
+    #[strum(to_string = "XLSX")]
+    Xlsx,
+}
+
+impl SheetFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" | "txt" => Some(SheetFormat::Csv),
+            "xlsx" | "xlsm" => Some(SheetFormat::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+// Typed table on its way out, numbers stay numbers in XLSX
+#[derive(Clone, Debug, Default)]
+pub struct Sheet {
+    pub name: String,
+    pub headings: Vec<String>,
+    pub rows: Vec<Vec<Option<ParamValue>>>,
+}
+
+impl Sheet {
+    // Rows in the order the schedule shows them
+    pub fn from_schedule(definition: &ScheduleDefinition, rows: &[ScheduleRow]) -> Self {
+        let headings = std::iter::once(ELEMENT_ID_HEADING.to_owned())
+            .chain(definition.columns.iter().map(|column| column.heading.clone()))
+            .collect();
+
+        let rows = definition
+            .arrange(rows)
+            .iter()
+            .flat_map(|group| &group.rows)
+            .map(|row| {
+                std::iter::once(Some(ParamValue::Int(row.element.0)))
+                    .chain(row.cells.iter().cloned())
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            name: definition.name.clone(),
+            headings,
+            rows,
+        }
+    }
+
+    // Every element with every parameter found in the model
+    pub fn parameters<'a>(
+        elements: impl IntoIterator<Item = &'a ElementHeader>,
+        model_params: &BTreeMap<ParamKey, ParamType>,
+    ) -> Self {
+        let fields: Vec<ScheduleField> = [ScheduleField::Kind, ScheduleField::KindType, ScheduleField::Name]
+            .into_iter()
+            .chain(
+                model_params
+                    .iter()
+                    .map(|(key, param_type)| ScheduleField::Param(key.clone(), *param_type)),
+            )
+            .collect();
+
+        let mut elements: Vec<&ElementHeader> = elements.into_iter().collect();
+        elements.sort_by_key(|header| header.id);
+
+        Self {
+            name: "Parameters".to_owned(),
+            headings: std::iter::once(ELEMENT_ID_HEADING.to_owned())
+                .chain(fields.iter().map(ScheduleField::label))
+                .collect(),
+            rows: elements
+                .into_iter()
+                .map(|header| {
+                    std::iter::once(Some(ParamValue::Int(header.id.0)))
+                        .chain(fields.iter().map(|field| field.read(header)))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+// Table as read back from a file, cells untyped until they are checked against their field
+#[derive(Clone, Debug, Default)]
+pub struct SheetText {
+    pub headings: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueLevel {
+    Warning,
+    // Blocks the apply
+    Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct ImportIssue {
+    pub level: IssueLevel,
+    // Spreadsheet row number, the heading row is 1
+    pub row: Option<usize>,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct ImportChange {
+    pub entity: Entity,
+    pub element: ElementId,
+    pub field: ScheduleField,
+    pub old: Option<ParamValue>,
+    pub new: Option<ParamValue>,
+}
+
+// What an import would do, shown to the user before anything is written
+#[derive(Clone, Debug, Default)]
+pub struct ImportPreview {
+    pub source: PathBuf,
+    pub rows: usize,
+    pub unchanged: usize,
+    pub changes: Vec<ImportChange>,
+    pub issues: Vec<ImportIssue>,
+}
+
+impl ImportPreview {
+    // Columns are matched by the schedule headings first, then by field and parameter names.
+    // Unknown headings become new text parameters.
+    pub fn build(
+        source: PathBuf,
+        sheet: &SheetText,
+        columns: &[ScheduleColumn],
+        model_params: &BTreeMap<ParamKey, ParamType>,
+        elements: &HashMap<ElementId, (Entity, &ElementHeader)>,
+    ) -> Self {
+        let mut preview = Self {
+            source,
+            rows: sheet.rows.len(),
+            ..default()
+        };
+
+        let headings: Vec<&str> = sheet
+            .headings
+            .iter()
+            .map(|heading| heading.trim().trim_start_matches('\u{feff}'))
+            .collect();
+
+        let Some(id_column) = headings
+            .iter()
+            .position(|heading| heading.eq_ignore_ascii_case(ELEMENT_ID_HEADING))
+        else {
+            preview.issue(IssueLevel::Error, None, None, format!("No {ELEMENT_ID_HEADING} column"));
+            return preview;
+        };
+
+        let mut fields: Vec<(usize, &str, ScheduleField)> = Vec::new();
+        for (index, heading) in headings.iter().enumerate() {
+            if index == id_column || heading.is_empty() {
+                continue;
+            }
+            if fields.iter().any(|(_, other, _)| other.eq_ignore_ascii_case(heading)) {
+                preview.issue(IssueLevel::Error, None, Some(heading), "Duplicate column".to_owned());
+                continue;
+            }
+
+            let field = import_field(heading, columns, model_params).unwrap_or_else(|| {
+                preview.issue(
+                    IssueLevel::Warning,
+                    None,
+                    Some(heading),
+                    "Unknown column, imported as a new text parameter".to_owned(),
+                );
+                ScheduleField::Param(ParamKey::new(heading), ParamType::Text)
+            });
+            fields.push((index, heading, field));
+        }
+
+        let mut seen: HashMap<ElementId, usize> = HashMap::new();
+        let mut read_only_edits: Vec<&str> = Vec::new();
+
+        for (index, cells) in sheet.rows.iter().enumerate() {
+            let row = index + 2;
+            let cell = |column: usize| cells.get(column).map_or("", |cell| cell.trim());
+
+            if cells.iter().all(|cell| cell.trim().is_empty()) {
+                continue;
+            }
+
+            let Some(element) = ParamValue::parse(ParamType::Int, cell(id_column)).and_then(|id| match id {
+                ParamValue::Int(id) => Some(ElementId(id)),
+                _ => None,
+            }) else {
+                preview.issue(
+                    IssueLevel::Error,
+                    Some(row),
+                    Some(ELEMENT_ID_HEADING),
+                    format!("'{}' is not an element id", cell(id_column)),
+                );
+                continue;
+            };
+
+            if let Some(first) = seen.insert(element, row) {
+                preview.issue(
+                    IssueLevel::Error,
+                    Some(row),
+                    Some(ELEMENT_ID_HEADING),
+                    format!("Element #{} already on row {first}", element.0),
+                );
+                continue;
+            }
+
+            let Some(&(entity, header)) = elements.get(&element) else {
+                preview.issue(
+                    IssueLevel::Error,
+                    Some(row),
+                    Some(ELEMENT_ID_HEADING),
+                    format!("No element #{} in the model", element.0),
+                );
+                continue;
+            };
+
+            let mut changed = false;
+            for (column, heading, field) in &fields {
+                let text = cell(*column);
+                let old = field.read(header);
+
+                let new = if text.is_empty() {
+                    None
+                } else {
+                    match ParamValue::parse(field.value_type(), text) {
+                        Some(value) => Some(value),
+                        None => {
+                            preview.issue(
+                                IssueLevel::Error,
+                                Some(row),
+                                Some(heading),
+                                format!("'{text}' is not a {}", field.value_type()),
+                            );
+                            continue;
+                        }
+                    }
+                };
+
+                if new == old {
+                    continue;
+                }
+
+                // Kind, level and the like are exported for context only
+                if !field.editable() {
+                    if !read_only_edits.contains(heading) {
+                        read_only_edits.push(heading);
+                    }
+                    continue;
+                }
+
+                changed = true;
+                preview.changes.push(ImportChange {
+                    entity,
+                    element,
+                    field: field.clone(),
+                    old,
+                    new,
+                });
+            }
+
+            if !changed {
+                preview.unchanged += 1;
+            }
+        }
+
+        for heading in read_only_edits {
+            preview.issue(
+                IssueLevel::Warning,
+                None,
+                Some(heading),
+                "Read only, edits in this column are ignored".to_owned(),
+            );
+        }
+
+        preview
+    }
+
+    pub fn errors(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.level == IssueLevel::Error)
+            .count()
+    }
+
+    pub fn can_apply(&self) -> bool {
+        self.errors() == 0 && !self.changes.is_empty()
+    }
+
+    fn issue(&mut self, level: IssueLevel, row: Option<usize>, column: Option<&str>, message: String) {
+        self.issues.push(ImportIssue {
+            level,
+            row,
+            column: column.map(str::to_owned),
+            message,
+        });
+    }
+}
+
+fn import_field(
+    heading: &str,
+    columns: &[ScheduleColumn],
+    model_params: &BTreeMap<ParamKey, ParamType>,
+) -> Option<ScheduleField> {
+    if let Some(column) = columns
+        .iter()
+        .find(|column| column.heading.eq_ignore_ascii_case(heading))
+    {
+        return Some(column.field.clone());
+    }
+
+    let fixed = [
+        ScheduleField::Id,
+        ScheduleField::Name,
+        ScheduleField::Kind,
+        ScheduleField::KindType,
+        ScheduleField::Level,
+        ScheduleField::Spec,
+    ];
+    if let Some(field) = fixed
+        .into_iter()
+        .find(|field| field.label().eq_ignore_ascii_case(heading))
+    {
+        return Some(field);
+    }
+
+    model_params
+        .iter()
+        .find(|(key, _)| key.0.eq_ignore_ascii_case(heading))
+        .map(|(key, param_type)| ScheduleField::Param(key.clone(), *param_type))
+}
+
+// Export path, pending import and the outcome of the last operation
+#[derive(Resource, Default, Debug)]
+pub struct SheetExchange {
+    pub path: String,
+    pub preview: Option<ImportPreview>,
+    pub status: Option<String>,
+}
+
+#[derive(Message, Debug, Clone)]
+pub enum ExchangeCommand {
+    ExportSchedule { schedule: ScheduleId, path: PathBuf },
+    ExportParameters { path: PathBuf },
+    // Headings are matched against the columns of the given schedule
+    Import { schedule: Option<ScheduleId>, path: PathBuf },
+    ApplyImport,
+    DiscardImport,
+}
@@ -0,0 +1,154 @@
+// File: grid.rs
+// Desc: Working grid of each viewport. A view draws its grid on a work plane, either level
+//       with the floor it works on or through a face picked in the model, and can pull
+//       points that land on nothing onto the grid. Lines thin out as the view zooms away.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::element::ElementId;
+use crate::preferences::GridPrefs;
+use crate::tool::ToolId;
+
+// Tool that lays the grid of a view on a face picked in it
+pub const PICK_WORK_PLANE: ToolId = ToolId("view.pick_work_plane");
+
+// Closest two lines of the finest subdivision shown may come, pixels
+pub const MIN_LINE_GAP_PX: f32 = 8.0;
+// Subdivisions the grid may coarsen by before it stops, so a far zoom can't loop forever
+const MAX_COARSENING: u32 = 12;
+
+// Plane a view draws its grid on, lines run along u and across it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorkPlane {
+    pub origin: Vec3,
+    pub normal: Vec3,
+    pub u: Vec3,
+}
+
+impl WorkPlane {
+    pub const GROUND: Self = Self::level(0.0);
+
+    pub const fn level(elevation: f32) -> Self {
+        Self {
+            origin: Vec3::new(0.0, elevation, 0.0),
+            normal: Vec3::Y,
+            u: Vec3::X,
+        }
+    }
+
+    // Plane through a face, lines square to the world where the face allows it
+    pub fn from_face(point: Vec3, normal: Vec3) -> Option<Self> {
+        let normal = normal.try_normalize()?;
+        let u = [Vec3::X, Vec3::NEG_Z]
+            .into_iter()
+            .find_map(|axis| axis.reject_from_normalized(normal).try_normalize())?;
+        Some(Self { origin: point, normal, u })
+    }
+
+    // Across the lines along u, right handed with the normal
+    pub fn v(&self) -> Vec3 {
+        self.u.cross(self.normal)
+    }
+
+    // Turns local x and z onto the line directions and y onto the normal
+    pub fn rotation(&self) -> Quat {
+        Quat::from_mat3(&Mat3::from_cols(self.u, self.normal, self.v()))
+    }
+
+    pub fn intersect(&self, ray: Ray3d) -> Option<Vec3> {
+        let normal = Dir3::new(self.normal).ok()?;
+        ray.plane_intersection_point(self.origin, InfinitePlane3d { normal })
+    }
+
+    // Distance of a point off the plane, positive on the side the normal points to
+    pub fn height(&self, point: Vec3) -> f32 {
+        (point - self.origin).dot(self.normal)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewGrid {
+    pub visible: bool,
+    // Metres between the finest lines
+    pub spacing: f32,
+    // Minor lines per major line, also what the lines coarsen by when zooming out
+    pub major_every: u32,
+    // Points landing on nothing go to the nearest line crossing
+    pub snap: bool,
+    // Level the view works on. The grid lies at its height, and moves with it when it has one.
+    pub level_id: Option<ElementId>,
+    pub elevation: f32,
+    // Picked face the grid lies on instead of the level
+    pub face: Option<WorkPlane>,
+    // Finest spacing shown at the current zoom, kept up to date by the app
+    pub step: f32,
+}
+
+impl Default for ViewGrid {
+    fn default() -> Self {
+        Self::from_prefs(&GridPrefs::default())
+    }
+}
+
+impl ViewGrid {
+    pub fn from_prefs(prefs: &GridPrefs) -> Self {
+        Self {
+            visible: prefs.visible,
+            spacing: prefs.spacing,
+            major_every: prefs.major_every,
+            snap: prefs.snap,
+            level_id: None,
+            elevation: 0.0,
+            face: None,
+            step: prefs.spacing,
+        }
+    }
+
+    pub fn plane(&self) -> WorkPlane {
+        self.face.unwrap_or(WorkPlane::level(self.elevation))
+    }
+
+    // Finest subdivision whose lines stay apart at this zoom. Zooming out drops the minor lines
+    // first, the major lines then become the minor ones of a coarser grid.
+    pub fn step_at(&self, metres_per_pixel: f32) -> f32 {
+        let factor = self.major_every.max(2) as f32;
+        let mut step = self.spacing;
+        if !(metres_per_pixel > 0.0 && metres_per_pixel.is_finite()) {
+            return step;
+        }
+        for _ in 0..MAX_COARSENING {
+            if step / metres_per_pixel >= MIN_LINE_GAP_PX {
+                break;
+            }
+            step *= factor;
+        }
+        step
+    }
+
+    // Nearest line crossing of the shown subdivision, keeping the height off the plane
+    pub fn snap_point(&self, point: Vec3) -> Vec3 {
+        let plane = self.plane();
+        let offset = point - plane.origin;
+        let round = |along: f32| (along / self.step).round() * self.step;
+
+        plane.origin
+            + plane.u * round(offset.dot(plane.u))
+            + plane.v() * round(offset.dot(plane.v()))
+            + plane.normal * plane.height(point)
+    }
+}
+
+// Grid of each viewport pane, keyed by pane id
+#[derive(Resource, Default, Debug)]
+pub struct ViewGrids {
+    pub views: HashMap<u32, ViewGrid>,
+    // What a view starts with the first time it is shown, from the preferences
+    pub defaults: ViewGrid,
+}
+
+impl ViewGrids {
+    pub fn get(&self, pane_id: u32) -> ViewGrid {
+        self.views.get(&pane_id).copied().unwrap_or(self.defaults)
+    }
+}
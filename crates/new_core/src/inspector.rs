@@ -0,0 +1,74 @@
+// File: inspector.rs
+// Desc: Selected element as the properties pane sees it. The app fills the snapshot,
+//       the pane sends its edits back as messages.
+
+use bevy::prelude::*;
+
+use crate::anchor::{Anchor, AnchorStrategy};
+use crate::element::{ElementHeader, ElementId};
+use crate::elements::element_kindtype_enums::DistributionSystemEnum;
+use crate::mep::{FlowDirection, MepDomain, SectionSize};
+use crate::mep_system::ElementFlow;
+use crate::phase::ElementPhasing;
+use crate::structural_grid::{GridPoint, GridReference};
+
+#[derive(Resource, Default, Debug)]
+pub struct InspectedElement {
+    pub entity: Option<Entity>,
+    pub header: Option<ElementHeader>,
+    pub phasing: ElementPhasing,
+    pub anchor: Option<Anchor>,
+    pub grid_reference: Option<GridReference>,
+    pub connections: Vec<PortConnection>,
+    pub system: Option<InspectedSystem>,
+    pub room: Option<InspectedRoom>,
+}
+
+// Port of the inspected element and what is on the other side of it
+#[derive(Clone, Debug)]
+pub struct PortConnection {
+    // Port 1 (Inlet)
+    pub label: String,
+    pub size: SectionSize,
+    pub flow: FlowDirection,
+    pub design_flow: Option<f32>,
+    // Id and name of the element it connects to
+    pub connected: Option<(ElementId, Option<String>)>,
+}
+
+// Distribution system the inspected element is part of and how it does there
+#[derive(Clone, Debug)]
+pub struct InspectedSystem {
+    pub domain: MepDomain,
+    pub system: DistributionSystemEnum,
+    // Only for systems that carry a flow
+    pub flow: Option<ElementFlow>,
+    pub issues: Vec<String>,
+}
+
+// Room as it is numbered and scheduled, its areas are in the parameters
+#[derive(Clone, Debug)]
+pub struct InspectedRoom {
+    pub number: String,
+    pub height: f32,
+    pub enclosed: bool,
+}
+
+#[derive(Message, Debug, Clone)]
+pub enum ElementEdit {
+    SetPhasing(Entity, ElementPhasing),
+    // Anchored again from where it stands
+    SetAnchor(Entity, AnchorStrategy),
+    // Intersection to stand on and the one to face, on whichever grid has those axes. None
+    // lets go of the grid where the element stands.
+    SetGridReference(Entity, Option<GridPoint>, Option<GridPoint>),
+    // For every element connected to it
+    SetSystem(Entity, DistributionSystemEnum),
+    // Flow drawn off an open outlet, by port index, m³/s
+    SetDesignFlow(Entity, usize, Option<f32>),
+    // Number and name rooms are scheduled and tagged by, empty clears them
+    SetRoomNumber(Entity, String),
+    SetRoomName(Entity, String),
+    // Floor to ceiling, meters
+    SetRoomHeight(Entity, f32),
+}
@@ -0,0 +1,581 @@
+// File: keymap.rs
+// Desc: Which keys and mouse buttons run which action. Actions bring their own bindings, a
+//       preset swaps some of them for the ones another program uses, and whatever the user
+//       rebinds wins over both. A binding applies in one input context and may be a chord of
+//       several strokes, like 3 then D. Viewport navigation is held rather than pressed, so
+//       it is bound here too instead of being an action.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use strum_macros::{Display, EnumIter, EnumString};
+
+use crate::action::{ActionDef, ActionId, ActionRegistry, COMMAND_PALETTE};
+
+// Seconds between the strokes of a chord before it is given up
+pub const CHORD_TIMEOUT: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputButton {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+// Where the cursor and the keyboard focus are decides which bindings apply. While the cursor
+// is over a viewport its bindings come before the global ones on the same keys.
+#[derive(EnumIter, EnumString, Display, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum InputContext {
+    // Anywhere, except while typing into a field
+    #[default]
+    Global,
+    Viewport,
+    // Only while typing into a field
+    #[strum(to_string = "Text Field")]
+    TextField,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyStroke {
+    pub button: InputButton,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyStroke {
+    pub const fn new(button: InputButton) -> Self {
+        Self {
+            button,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub const fn key(key: KeyCode) -> Self {
+        Self::new(InputButton::Key(key))
+    }
+
+    pub const fn mouse(button: MouseButton) -> Self {
+        Self::new(InputButton::Mouse(button))
+    }
+
+    pub const fn with_ctrl(self) -> Self {
+        Self { ctrl: true, ..self }
+    }
+
+    pub const fn with_shift(self) -> Self {
+        Self { shift: true, ..self }
+    }
+
+    pub const fn with_alt(self) -> Self {
+        Self { alt: true, ..self }
+    }
+
+    // The button with whichever modifiers are down right now
+    pub fn pressed(button: InputButton, keys: &ButtonInput<KeyCode>) -> Self {
+        Self {
+            button,
+            ctrl: keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+            shift: keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            alt: keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        }
+    }
+
+    // Button down with exactly these modifiers, for bindings that are held
+    pub fn is_held(&self, keys: &ButtonInput<KeyCode>, mouse: &ButtonInput<MouseButton>) -> bool {
+        let down = match self.button {
+            InputButton::Key(key) => keys.pressed(key),
+            InputButton::Mouse(button) => mouse.pressed(button),
+        };
+        down && Self::pressed(self.button, keys) == *self
+    }
+
+    // Modifiers only change other strokes, they are never one on their own
+    pub fn is_modifier(key: KeyCode) -> bool {
+        matches!(
+            key,
+            KeyCode::ControlLeft
+                | KeyCode::ControlRight
+                | KeyCode::ShiftLeft
+                | KeyCode::ShiftRight
+                | KeyCode::AltLeft
+                | KeyCode::AltRight
+                | KeyCode::SuperLeft
+                | KeyCode::SuperRight
+        )
+    }
+
+    // Reads what Display writes, like Ctrl+Shift+P or Shift+MouseMiddle
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let mut stroke = Self::new(parse_button(parts.pop()?)?);
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => stroke.ctrl = true,
+                "shift" => stroke.shift = true,
+                "alt" => stroke.alt = true,
+                _ => return None,
+            }
+        }
+        Some(stroke)
+    }
+}
+
+// Written the way it is printed on the keys, like Ctrl+Shift+P
+impl fmt::Display for KeyStroke {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.shift {
+            f.write_str("Shift+")?;
+        }
+        if self.alt {
+            f.write_str("Alt+")?;
+        }
+        match self.button {
+            InputButton::Key(key) => f.write_str(&key_name(key)),
+            InputButton::Mouse(MouseButton::Other(index)) => write!(f, "Mouse{index}"),
+            InputButton::Mouse(button) => write!(f, "Mouse{button:?}"),
+        }
+    }
+}
+
+// KeyW prints as W and Digit3 as 3
+fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_owned()
+}
+
+fn parse_button(name: &str) -> Option<InputButton> {
+    if let Some(mouse) = name.strip_prefix("Mouse") {
+        let button = match mouse {
+            "Left" => MouseButton::Left,
+            "Right" => MouseButton::Right,
+            "Middle" => MouseButton::Middle,
+            "Back" => MouseButton::Back,
+            "Forward" => MouseButton::Forward,
+            index => MouseButton::Other(index.parse().ok()?),
+        };
+        return Some(InputButton::Mouse(button));
+    }
+    BINDABLE_KEYS
+        .iter()
+        .find(|&&key| key_name(key).eq_ignore_ascii_case(name))
+        .map(|&key| InputButton::Key(key))
+}
+
+// Keys a binding can be read back with from the keymap file
+const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF,
+    KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL,
+    KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR,
+    KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX,
+    KeyCode::KeyY, KeyCode::KeyZ,
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+    KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::Numpad0, KeyCode::Numpad1, KeyCode::Numpad2, KeyCode::Numpad3, KeyCode::Numpad4,
+    KeyCode::Numpad5, KeyCode::Numpad6, KeyCode::Numpad7, KeyCode::Numpad8, KeyCode::Numpad9,
+    KeyCode::NumpadAdd, KeyCode::NumpadSubtract, KeyCode::NumpadMultiply, KeyCode::NumpadDivide,
+    KeyCode::NumpadDecimal, KeyCode::NumpadEnter,
+    KeyCode::Escape, KeyCode::Enter, KeyCode::Space, KeyCode::Tab, KeyCode::Backspace,
+    KeyCode::Delete, KeyCode::Insert, KeyCode::Home, KeyCode::End, KeyCode::PageUp,
+    KeyCode::PageDown, KeyCode::ArrowUp, KeyCode::ArrowDown, KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::Minus, KeyCode::Equal, KeyCode::BracketLeft, KeyCode::BracketRight,
+    KeyCode::Backslash, KeyCode::Semicolon, KeyCode::Quote, KeyCode::Comma, KeyCode::Period,
+    KeyCode::Slash, KeyCode::Backquote,
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub strokes: Vec<KeyStroke>,
+    pub context: InputContext,
+}
+
+impl KeyBinding {
+    pub fn stroke(stroke: KeyStroke) -> Self {
+        Self::chord([stroke])
+    }
+
+    pub fn chord(strokes: impl IntoIterator<Item = KeyStroke>) -> Self {
+        Self {
+            strokes: strokes.into_iter().collect(),
+            context: InputContext::Global,
+        }
+    }
+
+    pub fn key(key: KeyCode) -> Self {
+        Self::stroke(KeyStroke::key(key))
+    }
+
+    pub fn ctrl(key: KeyCode) -> Self {
+        Self::stroke(KeyStroke::key(key).with_ctrl())
+    }
+
+    pub fn alt(key: KeyCode) -> Self {
+        Self::stroke(KeyStroke::key(key).with_alt())
+    }
+
+    pub fn in_context(self, context: InputContext) -> Self {
+        Self { context, ..self }
+    }
+
+    // Either one fires on the other's strokes, or waits for more when it should have fired
+    pub fn clashes_with(&self, other: &KeyBinding) -> bool {
+        self.context == other.context
+            && (self.strokes.starts_with(&other.strokes) || other.strokes.starts_with(&self.strokes))
+    }
+
+    // Strokes separated by spaces, like Ctrl+K Ctrl+S. The context is kept apart.
+    pub fn parse(text: &str, context: InputContext) -> Option<Self> {
+        let strokes = text
+            .split_whitespace()
+            .map(KeyStroke::parse)
+            .collect::<Option<Vec<_>>>()?;
+        if strokes.is_empty() {
+            return None;
+        }
+        Some(Self { strokes, context })
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, stroke) in self.strokes.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{stroke}")?;
+        }
+        Ok(())
+    }
+}
+
+// Held in a viewport to move its camera
+#[derive(EnumIter, EnumString, Display, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Navigation {
+    Orbit,
+    Pan,
+}
+
+#[derive(EnumIter, EnumString, Display, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum KeymapPreset {
+    #[default]
+    Default,
+    Revit,
+    Blender,
+    #[strum(to_string = "AutoCAD")]
+    AutoCad,
+}
+
+impl KeymapPreset {
+    pub fn navigation(self, navigation: Navigation) -> KeyStroke {
+        let middle = KeyStroke::mouse(MouseButton::Middle);
+        // Revit and AutoCAD pan on the bare wheel button and orbit with Shift held
+        let orbit_with_shift = matches!(self, Self::Revit | Self::AutoCad);
+        match (navigation, orbit_with_shift) {
+            (Navigation::Orbit, false) | (Navigation::Pan, true) => middle,
+            (Navigation::Orbit, true) | (Navigation::Pan, false) => middle.with_shift(),
+        }
+    }
+
+    // Bindings that replace the actions' own. Ones for actions that are not registered are
+    // left alone.
+    pub fn bindings(self) -> Vec<(ActionId, Vec<KeyBinding>)> {
+        let viewport = |binding: KeyBinding| binding.in_context(InputContext::Viewport);
+        let typed = |first: KeyCode, second: KeyCode| {
+            viewport(KeyBinding::chord([KeyStroke::key(first), KeyStroke::key(second)]))
+        };
+        let undo = (ActionId("edit.undo"), vec![KeyBinding::ctrl(KeyCode::KeyZ)]);
+        match self {
+            Self::Default => Vec::new(),
+            // Two letter chords as typed in Revit, 3D for the default 3D view
+            Self::Revit => vec![
+                (ActionId("view.isometric"), vec![typed(KeyCode::Digit3, KeyCode::KeyD)]),
+                (ActionId("structure.grid_line"), vec![typed(KeyCode::KeyG, KeyCode::KeyR)]),
+                (ActionId("structure.column"), vec![typed(KeyCode::KeyC, KeyCode::KeyL)]),
+                (ActionId("structure.beam"), vec![typed(KeyCode::KeyB, KeyCode::KeyM)]),
+                (ActionId("architecture.room"), vec![typed(KeyCode::KeyR, KeyCode::KeyM)]),
+                (ActionId("mep.duct"), vec![typed(KeyCode::KeyD, KeyCode::KeyT)]),
+                (ActionId("mep.pipe"), vec![typed(KeyCode::KeyP, KeyCode::KeyI)]),
+                (ActionId("mep.cable_tray"), vec![typed(KeyCode::KeyC, KeyCode::KeyT)]),
+                undo,
+                (ActionId("edit.redo"), vec![KeyBinding::ctrl(KeyCode::KeyY)]),
+                (
+                    ActionId("edit.delete"),
+                    vec![KeyBinding::key(KeyCode::Delete), typed(KeyCode::KeyD, KeyCode::KeyE)],
+                ),
+            ],
+            // Blender's numpad views are the default ones already
+            Self::Blender => vec![
+                (COMMAND_PALETTE, vec![KeyBinding::key(KeyCode::F3)]),
+                undo,
+                (
+                    ActionId("edit.redo"),
+                    vec![KeyBinding::stroke(KeyStroke::key(KeyCode::KeyZ).with_ctrl().with_shift())],
+                ),
+                (
+                    ActionId("edit.delete"),
+                    vec![viewport(KeyBinding::key(KeyCode::KeyX)), KeyBinding::key(KeyCode::Delete)],
+                ),
+                (ActionId("edit.deselect_all"), vec![KeyBinding::alt(KeyCode::KeyA)]),
+            ],
+            // Space repeats the last command in AutoCAD, F2 is where its command history is.
+            // F7 and F9 toggle the grid and snapping to it.
+            Self::AutoCad => vec![
+                (COMMAND_PALETTE, vec![KeyBinding::key(KeyCode::F2), KeyBinding::ctrl(KeyCode::KeyP)]),
+                undo,
+                (ActionId("edit.redo"), vec![KeyBinding::ctrl(KeyCode::KeyY)]),
+                (ActionId("edit.delete"), vec![KeyBinding::key(KeyCode::Delete)]),
+                (ActionId("view.show_grid"), vec![KeyBinding::key(KeyCode::F7)]),
+                (ActionId("view.snap_to_grid"), vec![KeyBinding::key(KeyCode::F9)]),
+            ],
+        }
+    }
+}
+
+// What a binding is for, an action or a way to navigate
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyTarget {
+    Action(ActionId),
+    Navigation(Navigation),
+}
+
+// Two targets on the same keys, or one waiting for more strokes the other fires on
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyConflict {
+    pub first: KeyTarget,
+    pub second: KeyTarget,
+    pub binding: KeyBinding,
+}
+
+impl KeyConflict {
+    pub fn involves(&self, target: KeyTarget) -> bool {
+        self.first == target || self.second == target
+    }
+}
+
+// What the strokes pressed so far lead to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyLookup {
+    Action(ActionId),
+    // Start of a chord, wait for the next stroke
+    Pending,
+    None,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct Keymap {
+    pub preset: KeymapPreset,
+    preset_bindings: HashMap<ActionId, Vec<KeyBinding>>,
+    // Actions the user rebound. An empty list leaves the action without keys.
+    pub custom: HashMap<ActionId, Vec<KeyBinding>>,
+    pub custom_navigation: HashMap<Navigation, KeyStroke>,
+}
+
+impl Keymap {
+    // Rebinding by the user is kept across presets
+    pub fn set_preset(&mut self, preset: KeymapPreset) {
+        self.preset = preset;
+        self.preset_bindings = preset.bindings().into_iter().collect();
+    }
+
+    pub fn bindings<'a>(&'a self, def: &'a ActionDef) -> &'a [KeyBinding] {
+        self.custom
+            .get(&def.id)
+            .or_else(|| self.preset_bindings.get(&def.id))
+            .unwrap_or(&def.bindings)
+    }
+
+    pub fn navigation(&self, navigation: Navigation) -> KeyStroke {
+        self.custom_navigation
+            .get(&navigation)
+            .copied()
+            .unwrap_or_else(|| self.preset.navigation(navigation))
+    }
+
+    pub fn is_custom(&self, target: KeyTarget) -> bool {
+        match target {
+            KeyTarget::Action(id) => self.custom.contains_key(&id),
+            KeyTarget::Navigation(navigation) => self.custom_navigation.contains_key(&navigation),
+        }
+    }
+
+    // Back to the preset's binding, or the action's own
+    pub fn reset(&mut self, target: KeyTarget) {
+        match target {
+            KeyTarget::Action(id) => {
+                self.custom.remove(&id);
+            }
+            KeyTarget::Navigation(navigation) => {
+                self.custom_navigation.remove(&navigation);
+            }
+        }
+    }
+
+    // Contexts in the order they are tried, the most specific first
+    pub fn lookup(&self, registry: &ActionRegistry, strokes: &[KeyStroke], contexts: &[InputContext]) -> KeyLookup {
+        let mut pending = false;
+        for &context in contexts {
+            for def in &registry.actions {
+                for binding in self.bindings(def).iter().filter(|binding| binding.context == context) {
+                    if binding.strokes == strokes {
+                        return KeyLookup::Action(def.id);
+                    }
+                    pending |= binding.strokes.starts_with(strokes);
+                }
+            }
+        }
+        if pending { KeyLookup::Pending } else { KeyLookup::None }
+    }
+
+    pub fn conflicts(&self, registry: &ActionRegistry) -> Vec<KeyConflict> {
+        let mut bound: Vec<(KeyTarget, KeyBinding)> = registry
+            .actions
+            .iter()
+            .flat_map(|def| {
+                self.bindings(def)
+                    .iter()
+                    .map(|binding| (KeyTarget::Action(def.id), binding.clone()))
+            })
+            .collect();
+        for navigation in [Navigation::Orbit, Navigation::Pan] {
+            let binding = KeyBinding::stroke(self.navigation(navigation)).in_context(InputContext::Viewport);
+            bound.push((KeyTarget::Navigation(navigation), binding));
+        }
+
+        let mut conflicts = Vec::new();
+        for (index, (first, binding)) in bound.iter().enumerate() {
+            for (second, other) in &bound[index + 1..] {
+                if first != second && binding.clashes_with(other) {
+                    // The shorter one is what the user has to press to hit it
+                    let shorter = if binding.strokes.len() <= other.strokes.len() { binding } else { other };
+                    conflicts.push(KeyConflict {
+                        first: *first,
+                        second: *second,
+                        binding: shorter.clone(),
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+// State of the keymap pane. While recording, strokes go to the binding being made instead of
+// running anything.
+#[derive(Resource, Default, Debug)]
+pub struct KeymapEditor {
+    pub filter: String,
+    pub recording: Option<KeyTarget>,
+    pub recorded: Vec<KeyStroke>,
+    pub context: InputContext,
+}
+
+impl KeymapEditor {
+    pub fn record(&mut self, target: KeyTarget, context: InputContext) {
+        self.recording = Some(target);
+        self.recorded.clear();
+        self.context = context;
+    }
+
+    pub fn stop(&mut self) {
+        self.recording = None;
+        self.recorded.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(id: &'static str, bindings: Vec<KeyBinding>) -> ActionDef {
+        ActionDef {
+            id: ActionId(id),
+            label: id,
+            menu: "Edit",
+            bindings,
+            prompt: None,
+        }
+    }
+
+    fn strokes(text: &str) -> Vec<KeyStroke> {
+        KeyBinding::parse(text, InputContext::Global).unwrap().strokes
+    }
+
+    #[test]
+    fn bindings_read_back_what_they_print() {
+        for text in ["Ctrl+Shift+P", "3 D", "Shift+MouseMiddle", "Ctrl+K Ctrl+S", "Numpad7"] {
+            let binding = KeyBinding::parse(text, InputContext::Global).unwrap();
+            assert_eq!(binding.to_string(), text);
+        }
+        assert_eq!(KeyStroke::parse("ctrl+z"), Some(KeyStroke::key(KeyCode::KeyZ).with_ctrl()));
+        assert_eq!(KeyStroke::parse("Hyper+Z"), None);
+        assert_eq!(KeyBinding::parse("  ", InputContext::Global), None);
+    }
+
+    #[test]
+    fn chords_wait_for_their_last_stroke() {
+        let mut registry = ActionRegistry::default();
+        registry.register(action("view.isometric", vec![KeyBinding::key(KeyCode::Numpad5)]));
+        let mut keymap = Keymap::default();
+        keymap.set_preset(KeymapPreset::Revit);
+
+        let contexts = [InputContext::Viewport, InputContext::Global];
+        assert_eq!(keymap.lookup(&registry, &strokes("3"), &contexts), KeyLookup::Pending);
+        assert_eq!(
+            keymap.lookup(&registry, &strokes("3 D"), &contexts),
+            KeyLookup::Action(ActionId("view.isometric"))
+        );
+        assert_eq!(keymap.lookup(&registry, &strokes("3 E"), &contexts), KeyLookup::None);
+        // The preset replaced the action's own key, and its chord is only typed in a viewport
+        assert_eq!(keymap.lookup(&registry, &strokes("Numpad5"), &contexts), KeyLookup::None);
+        assert_eq!(keymap.lookup(&registry, &strokes("3"), &[InputContext::Global]), KeyLookup::None);
+    }
+
+    #[test]
+    fn custom_bindings_win_over_the_preset() {
+        let delete = ActionId("edit.delete");
+        let mut registry = ActionRegistry::default();
+        registry.register(action("edit.delete", vec![KeyBinding::key(KeyCode::Delete)]));
+        let mut keymap = Keymap::default();
+        let contexts = [InputContext::Viewport, InputContext::Global];
+        assert_eq!(keymap.lookup(&registry, &strokes("X"), &contexts), KeyLookup::None);
+
+        keymap.set_preset(KeymapPreset::Blender);
+        assert_eq!(keymap.lookup(&registry, &strokes("X"), &contexts), KeyLookup::Action(delete));
+        assert_eq!(keymap.lookup(&registry, &strokes("X"), &[InputContext::Global]), KeyLookup::None);
+
+        keymap.custom.insert(delete, vec![KeyBinding::ctrl(KeyCode::KeyD)]);
+        keymap.set_preset(KeymapPreset::Revit);
+        assert_eq!(keymap.lookup(&registry, &strokes("Ctrl+D"), &contexts), KeyLookup::Action(delete));
+        assert_eq!(keymap.lookup(&registry, &strokes("Delete"), &contexts), KeyLookup::None);
+
+        keymap.reset(KeyTarget::Action(delete));
+        assert_eq!(keymap.lookup(&registry, &strokes("D E"), &contexts), KeyLookup::Action(delete));
+    }
+
+    #[test]
+    fn a_key_that_starts_a_chord_clashes_with_it() {
+        let single = KeyBinding::key(KeyCode::KeyG);
+        let chord = KeyBinding::parse("G R", InputContext::Global).unwrap();
+        assert!(single.clashes_with(&chord));
+        assert!(chord.clashes_with(&single));
+        assert!(!single.clashes_with(&chord.clone().in_context(InputContext::Viewport)));
+        assert!(!chord.clashes_with(&KeyBinding::parse("G T", InputContext::Global).unwrap()));
+
+        let mut registry = ActionRegistry::default();
+        registry.register(action("view.show_grid", vec![single]));
+        registry.register(action("structure.grid_line", vec![chord]));
+        let conflicts = Keymap::default().conflicts(&registry);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].involves(KeyTarget::Action(ActionId("structure.grid_line"))));
+    }
+}
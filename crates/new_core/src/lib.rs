@@ -2,12 +2,38 @@ use bevy::prelude::*;
 use bevy_egui::egui;
 use egui_tiles::{TileId, Tree};
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 
+pub mod action;
+pub mod anchor;
+pub mod asset_library;
+pub mod cad;
+pub mod clash;
+pub mod cost;
+pub mod display;
+pub mod drawing;
 pub mod element;
 pub mod elements;
+pub mod exchange;
+pub mod grid;
+pub mod inspector;
+pub mod keymap;
+pub mod mep;
+pub mod mep_system;
+pub mod model_exchange;
 pub mod placement;
 pub mod pane_kind;
+pub mod phase;
+pub mod preferences;
+pub mod profile;
+pub mod room;
+pub mod schedule;
+pub mod sequence;
+pub mod sheet;
+pub mod structural_grid;
+pub mod tool;
+pub mod units;
 
 use crate::pane_kind::{
     PaneKind
@@ -33,6 +59,46 @@ pub struct UiState {
 #[derive(Resource, Default)]
 pub struct VisibleViewports {
     pub rects: HashMap<u32, egui::Rect>,
+    // Widgets drawn over the viewports, their view bars
+    pub overlays: Vec<egui::Rect>,
+    // A popup or window was under the pointer, or a widget held it, on the last UI pass
+    pub pointer_over_ui: bool,
+}
+
+impl VisibleViewports {
+    // Viewport pane under a window position
+    pub fn pane_at(&self, cursor: Vec2) -> Option<u32> {
+        let cursor = egui::pos2(cursor.x, cursor.y);
+        self.rects
+            .iter()
+            .find(|(_, rect)| rect.contains(cursor))
+            .map(|(&pane_id, _)| pane_id)
+    }
+
+    // Viewport a click at the window position goes to, None when it lands on the UI over it
+    pub fn click_target(&self, cursor: Vec2) -> Option<u32> {
+        let over_overlay = self.overlays.iter().any(|rect| rect.contains(egui::pos2(cursor.x, cursor.y)));
+        if self.pointer_over_ui || over_overlay {
+            return None;
+        }
+        self.pane_at(cursor)
+    }
+}
+
+// Viewport pane the user last interacted with
+#[derive(Resource, Default)]
+pub struct ActiveViewport {
+    pub pane_id: Option<u32>,
+}
+
+impl ActiveViewport {
+    // Viewport actions from the keyboard or a menu apply to, the first one open when the user
+    // has not been in any yet
+    pub fn target(&self, visible_viewports: &VisibleViewports) -> Option<u32> {
+        self.pane_id
+            .filter(|pane_id| visible_viewports.rects.contains_key(pane_id))
+            .or_else(|| visible_viewports.rects.keys().min().copied())
+    }
 }
 
 #[derive(Component, Clone, Copy, Debug)]
@@ -40,3 +106,17 @@ pub struct GameViewportCamera {
     pub pane_id: u32,
 }
 
+
+// Per user settings file, in the platform's config folder under monolith/
+pub fn user_config_path(file_name: &str) -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }?;
+    Some(base.join("monolith").join(file_name))
+}
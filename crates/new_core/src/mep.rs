@@ -0,0 +1,395 @@
+// File: mep.rs
+// Desc: Routed MEP runs. Duct, pipe and cable tray segments with the fittings between them,
+//       joined through ports that know which way the flow goes and what they connect to.
+//       Ports live on their element rather than as IfcDistributionPort elements of their own.
+
+use bevy::prelude::*;
+use std::fmt;
+use strum_macros::{Display, EnumIter};
+
+use crate::element::ElementId;
+use crate::elements::element_kindtype_enums::{
+    CableCarrierFittingType, CableCarrierSegmentType, DistributionSystemEnum, DuctFittingType, DuctSegmentType,
+    PipeFittingType, PipeSegmentType,
+};
+use crate::elements::{ElementKind, ElementKindType};
+use crate::tool::ToolId;
+
+// Clicks along the centerline, confirmed to finish the run
+pub const DUCT_TOOL: ToolId = ToolId("mep.duct");
+pub const PIPE_TOOL: ToolId = ToolId("mep.pipe");
+pub const CABLE_TRAY_TOOL: ToolId = ToolId("mep.cable_tray");
+
+// Points this close to an open port or a centerline join it, meters
+pub const JOIN_RADIUS: f32 = 0.3;
+
+// Parameter written on segments and fittings, as SectionSize writes it
+pub const SIZE_PARAM: &str = "Size";
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MepDomain {
+    Duct,
+    Pipe,
+    #[strum(to_string = "Cable Tray")]
+    CableCarrier,
+}
+
+impl MepDomain {
+    pub fn tool(self) -> ToolId {
+        match self {
+            MepDomain::Duct => DUCT_TOOL,
+            MepDomain::Pipe => PIPE_TOOL,
+            MepDomain::CableCarrier => CABLE_TRAY_TOOL,
+        }
+    }
+
+    pub fn of(kind: ElementKind) -> Option<Self> {
+        match kind {
+            ElementKind::DuctSegment | ElementKind::DuctFitting => Some(MepDomain::Duct),
+            ElementKind::PipeSegment | ElementKind::PipeFitting => Some(MepDomain::Pipe),
+            ElementKind::CableCarrierSegment | ElementKind::CableCarrierFitting => Some(MepDomain::CableCarrier),
+            _ => None,
+        }
+    }
+
+    pub fn segment_kind(self) -> ElementKind {
+        match self {
+            MepDomain::Duct => ElementKind::DuctSegment,
+            MepDomain::Pipe => ElementKind::PipeSegment,
+            MepDomain::CableCarrier => ElementKind::CableCarrierSegment,
+        }
+    }
+
+    pub fn fitting_kind(self) -> ElementKind {
+        match self {
+            MepDomain::Duct => ElementKind::DuctFitting,
+            MepDomain::Pipe => ElementKind::PipeFitting,
+            MepDomain::CableCarrier => ElementKind::CableCarrierFitting,
+        }
+    }
+
+    pub fn segment_type(self) -> ElementKindType {
+        match self {
+            MepDomain::Duct => ElementKindType::DuctSegment(DuctSegmentType::RIGIDSEGMENT),
+            MepDomain::Pipe => ElementKindType::PipeSegment(PipeSegmentType::RIGIDSEGMENT),
+            MepDomain::CableCarrier => ElementKindType::CableCarrierSegment(CableCarrierSegmentType::CABLETRAYSEGMENT),
+        }
+    }
+
+    pub fn fitting_type(self, fitting: FittingKind) -> ElementKindType {
+        match (self, fitting) {
+            (MepDomain::Duct, FittingKind::Elbow) => ElementKindType::DuctFitting(DuctFittingType::BEND),
+            (MepDomain::Duct, FittingKind::Tee) => ElementKindType::DuctFitting(DuctFittingType::JUNCTION),
+            (MepDomain::Duct, FittingKind::Transition) => ElementKindType::DuctFitting(DuctFittingType::TRANSITION),
+            (MepDomain::Pipe, FittingKind::Elbow) => ElementKindType::PipeFitting(PipeFittingType::BEND),
+            (MepDomain::Pipe, FittingKind::Tee) => ElementKindType::PipeFitting(PipeFittingType::JUNCTION),
+            (MepDomain::Pipe, FittingKind::Transition) => ElementKindType::PipeFitting(PipeFittingType::TRANSITION),
+            (MepDomain::CableCarrier, FittingKind::Elbow) => {
+                ElementKindType::CableCarrierFitting(CableCarrierFittingType::BEND)
+            }
+            (MepDomain::CableCarrier, FittingKind::Tee) => {
+                ElementKindType::CableCarrierFitting(CableCarrierFittingType::TEE)
+            }
+            (MepDomain::CableCarrier, FittingKind::Transition) => {
+                ElementKindType::CableCarrierFitting(CableCarrierFittingType::REDUCER)
+            }
+        }
+    }
+
+    // Sizes offered by the routing tools, as SectionSize writes them
+    pub fn sizes(self) -> &'static [&'static str] {
+        match self {
+            MepDomain::Duct => &[
+                "Ø100", "Ø125", "Ø160", "Ø200", "Ø250", "Ø315", "Ø400", "Ø500", "Ø630", "300x200", "400x300",
+                "600x400", "800x400", "1000x500",
+            ],
+            MepDomain::Pipe => &[
+                "Ø15", "Ø22", "Ø28", "Ø35", "Ø42", "Ø54", "Ø76", "Ø108", "Ø159", "Ø219",
+            ],
+            MepDomain::CableCarrier => &["100x60", "200x60", "300x60", "400x100", "600x100"],
+        }
+    }
+
+    pub fn default_size(self) -> &'static str {
+        match self {
+            MepDomain::Duct => "Ø315",
+            MepDomain::Pipe => "Ø54",
+            MepDomain::CableCarrier => "300x60",
+        }
+    }
+
+    // Systems offered for runs of the domain, the first is where new runs start
+    pub fn systems(self) -> &'static [DistributionSystemEnum] {
+        match self {
+            MepDomain::Duct => &[
+                DistributionSystemEnum::VENTILATION,
+                DistributionSystemEnum::AIRCONDITIONING,
+                DistributionSystemEnum::EXHAUST,
+            ],
+            MepDomain::Pipe => &[
+                DistributionSystemEnum::DOMESTICCOLDWATER,
+                DistributionSystemEnum::DOMESTICHOTWATER,
+                DistributionSystemEnum::HEATING,
+                DistributionSystemEnum::CHILLEDWATER,
+                DistributionSystemEnum::FIREPROTECTION,
+                DistributionSystemEnum::GAS,
+                DistributionSystemEnum::DRAINAGE,
+            ],
+            MepDomain::CableCarrier => &[
+                DistributionSystemEnum::ELECTRICAL,
+                DistributionSystemEnum::LIGHTING,
+                DistributionSystemEnum::DATA,
+                DistributionSystemEnum::COMMUNICATION,
+            ],
+        }
+    }
+
+    // Height of the centerline above the work plane runs are drawn at, meters
+    pub fn default_elevation(self) -> f32 {
+        match self {
+            MepDomain::Duct => 2.7,
+            MepDomain::Pipe => 2.4,
+            MepDomain::CableCarrier => 3.0,
+        }
+    }
+}
+
+// Inside of a duct, pipe or tray, meters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SectionSize {
+    Round { diameter: f32 },
+    // Width across, height up
+    Rectangular { width: f32, height: f32 },
+}
+
+impl SectionSize {
+    // Millimeters, Ø250 for round and 300x200 for rectangular
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let millimeters = |text: &str| text.trim().parse::<f32>().ok().filter(|value| *value > 0.0).map(|value| value / 1000.0);
+        if let Some((width, height)) = text.split_once(['x', 'X', '×']) {
+            return Some(SectionSize::Rectangular {
+                width: millimeters(width)?,
+                height: millimeters(height)?,
+            });
+        }
+        let diameter = text.strip_prefix('Ø').or_else(|| text.strip_prefix("DN")).unwrap_or(text);
+        Some(SectionSize::Round {
+            diameter: millimeters(diameter)?,
+        })
+    }
+
+    // Largest dimension across, what fittings are sized by
+    pub fn extent(&self) -> f32 {
+        match *self {
+            SectionSize::Round { diameter } => diameter,
+            SectionSize::Rectangular { width, height } => width.max(height),
+        }
+    }
+
+    // Four times the area over the perimeter, what friction is worked out with
+    pub fn hydraulic_diameter(&self) -> f32 {
+        match *self {
+            SectionSize::Round { diameter } => diameter,
+            SectionSize::Rectangular { width, height } => 2.0 * width * height / (width + height),
+        }
+    }
+
+    // Square meters of free section
+    pub fn area(&self) -> f32 {
+        match *self {
+            SectionSize::Round { diameter } => std::f32::consts::FRAC_PI_4 * diameter * diameter,
+            SectionSize::Rectangular { width, height } => width * height,
+        }
+    }
+}
+
+impl fmt::Display for SectionSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mm = |meters: f32| (meters * 1000.0).round();
+        match *self {
+            SectionSize::Round { diameter } => write!(f, "Ø{}", mm(diameter)),
+            SectionSize::Rectangular { width, height } => write!(f, "{}x{}", mm(width), mm(height)),
+        }
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FittingKind {
+    Elbow,
+    Tee,
+    Transition,
+}
+
+// IfcFlowDirectionEnum, seen from the element the port is on
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum FlowDirection {
+    // Flow leaves the element here
+    #[strum(to_string = "Outlet")]
+    Source,
+    // Flow enters the element here
+    #[strum(to_string = "Inlet")]
+    Sink,
+    #[strum(to_string = "Inlet and Outlet")]
+    SourceAndSink,
+    #[default]
+    #[strum(to_string = "Not Defined")]
+    NotDefined,
+}
+
+impl FlowDirection {
+    // What the port it connects to should be
+    pub fn opposite(self) -> Self {
+        match self {
+            FlowDirection::Source => FlowDirection::Sink,
+            FlowDirection::Sink => FlowDirection::Source,
+            other => other,
+        }
+    }
+}
+
+// Port of another element, by its index in that element's ports
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PortRef {
+    pub element: ElementId,
+    pub port: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Port {
+    // In the element's space, the direction pointing out of the element
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub flow: FlowDirection,
+    pub size: SectionSize,
+    pub connected: Option<PortRef>,
+    // Drawn off here while the port is open, what a terminal would take, cubic meters a second
+    pub design_flow: Option<f32>,
+}
+
+impl Port {
+    pub fn new(position: Vec3, direction: Vec3, flow: FlowDirection, size: SectionSize) -> Self {
+        Self {
+            position,
+            direction: direction.normalize_or_zero(),
+            flow,
+            size,
+            connected: None,
+            design_flow: None,
+        }
+    }
+}
+
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Ports {
+    pub domain: MepDomain,
+    pub system: DistributionSystemEnum,
+    pub ports: Vec<Port>,
+}
+
+impl Ports {
+    // Port 1 (Inlet), as the properties pane lists them
+    pub fn label(&self, index: usize) -> String {
+        let flow = self.ports.get(index).map_or(FlowDirection::NotDefined, |port| port.flow);
+        format!("Port {} ({flow})", index + 1)
+    }
+}
+
+// Straight run from the element's origin to end, in its own space. Port 1 is the inlet at the
+// origin and port 2 the outlet at the end.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct MepSegment {
+    pub size: SectionSize,
+    pub end: Vec3,
+}
+
+impl MepSegment {
+    pub fn ports(&self) -> Vec<Port> {
+        vec![
+            Port::new(Vec3::ZERO, -self.end, FlowDirection::Sink, self.size),
+            Port::new(self.end, self.end, FlowDirection::Source, self.size),
+        ]
+    }
+}
+
+// Fitting shaped by its ports, each reached from the element's origin
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct MepFitting {
+    pub kind: FittingKind,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn offered_sizes_read_back_as_written() {
+        for domain in MepDomain::iter() {
+            for &size in domain.sizes() {
+                let parsed = SectionSize::parse(size).expect(size);
+                assert_eq!(parsed.to_string(), size);
+            }
+        }
+        assert_eq!(SectionSize::parse("DN50"), Some(SectionSize::Round { diameter: 0.05 }));
+        assert_eq!(
+            SectionSize::parse(" 300 × 200 "),
+            Some(SectionSize::Rectangular {
+                width: 0.3,
+                height: 0.2
+            })
+        );
+        assert_eq!(SectionSize::parse("Ø0"), None);
+        assert_eq!(SectionSize::parse("300x"), None);
+    }
+
+    #[test]
+    fn a_rectangular_duct_is_sized_by_its_long_side() {
+        let size = SectionSize::Rectangular {
+            width: 0.6,
+            height: 0.4,
+        };
+        assert_eq!(size.extent(), 0.6);
+        assert!((size.area() - 0.24).abs() < 1e-6);
+        assert!((size.hydraulic_diameter() - 0.48).abs() < 1e-6);
+    }
+
+    #[test]
+    fn flow_enters_a_segment_at_its_origin_and_leaves_at_its_end() {
+        let size = SectionSize::Round { diameter: 0.2 };
+        let segment = MepSegment {
+            size,
+            end: Vec3::new(0.0, 0.0, -4.0),
+        };
+        let [inlet, outlet] = segment.ports().try_into().expect("two ports");
+
+        assert_eq!((inlet.position, inlet.direction, inlet.flow), (Vec3::ZERO, Vec3::Z, FlowDirection::Sink));
+        assert_eq!(
+            (outlet.position, outlet.direction, outlet.flow),
+            (segment.end, Vec3::NEG_Z, FlowDirection::Source)
+        );
+        assert!(inlet.connected.is_none() && outlet.connected.is_none());
+
+        // An outlet meets an inlet, ports without a direction meet anything
+        assert_eq!(outlet.flow.opposite(), inlet.flow);
+        assert_eq!(inlet.flow.opposite(), outlet.flow);
+        assert_eq!(FlowDirection::NotDefined.opposite(), FlowDirection::NotDefined);
+        assert_eq!(FlowDirection::SourceAndSink.opposite(), FlowDirection::SourceAndSink);
+    }
+
+    #[test]
+    fn fittings_are_typed_by_their_domain() {
+        for domain in MepDomain::iter() {
+            for fitting in FittingKind::iter() {
+                let kind = match domain.fitting_type(fitting) {
+                    ElementKindType::DuctFitting(_) => ElementKind::DuctFitting,
+                    ElementKindType::PipeFitting(_) => ElementKind::PipeFitting,
+                    ElementKindType::CableCarrierFitting(_) => ElementKind::CableCarrierFitting,
+                    other => panic!("{other:?} is not a fitting"),
+                };
+                assert_eq!(kind, domain.fitting_kind());
+                assert_eq!(MepDomain::of(kind), Some(domain));
+            }
+        }
+    }
+}
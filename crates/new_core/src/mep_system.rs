@@ -0,0 +1,577 @@
+// File: mep_system.rs
+// Desc: Distribution systems made of connected MEP elements. Checks the connections, finds
+//       open ends, adds up the flow drawn downstream and the pressure lost along the way.
+
+use bevy::prelude::*;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use crate::element::ElementId;
+use crate::elements::element_kindtype_enums::DistributionSystemEnum;
+use crate::mep::{FittingKind, FlowDirection, MepDomain, Port, PortRef};
+
+// Below this Reynolds number flow is laminar
+const LAMINAR: f32 = 2300.0;
+
+pub fn system_name(system: DistributionSystemEnum) -> String {
+    let name = match system {
+        DistributionSystemEnum::VENTILATION => "Ventilation",
+        DistributionSystemEnum::AIRCONDITIONING => "Air Conditioning",
+        DistributionSystemEnum::EXHAUST => "Exhaust",
+        DistributionSystemEnum::DOMESTICCOLDWATER => "Domestic Cold Water",
+        DistributionSystemEnum::DOMESTICHOTWATER => "Domestic Hot Water",
+        DistributionSystemEnum::HEATING => "Heating",
+        DistributionSystemEnum::CHILLEDWATER => "Chilled Water",
+        DistributionSystemEnum::FIREPROTECTION => "Fire Protection",
+        DistributionSystemEnum::GAS => "Gas",
+        DistributionSystemEnum::DRAINAGE => "Drainage",
+        DistributionSystemEnum::ELECTRICAL => "Electrical",
+        DistributionSystemEnum::LIGHTING => "Lighting",
+        DistributionSystemEnum::DATA => "Data",
+        DistributionSystemEnum::COMMUNICATION => "Communication",
+        other => return format!("{other:?}"),
+    };
+    name.to_owned()
+}
+
+// What a system carries, for its pressure drop and sizing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fluid {
+    // kg/m³
+    pub density: f32,
+    // Kinematic, m²/s
+    pub viscosity: f32,
+    // Of the duct or pipe wall, meters
+    pub roughness: f32,
+    // Fastest it should go before the run is sized up, m/s
+    pub max_velocity: f32,
+}
+
+impl Fluid {
+    // Cable trays carry nothing that flows
+    pub fn of(domain: MepDomain, system: DistributionSystemEnum) -> Option<Self> {
+        match (domain, system) {
+            (MepDomain::CableCarrier, _) => None,
+            // Galvanized steel duct
+            (MepDomain::Duct, _) => Some(Fluid {
+                density: 1.2,
+                viscosity: 1.5e-5,
+                roughness: 1.5e-4,
+                max_velocity: 8.0,
+            }),
+            (MepDomain::Pipe, DistributionSystemEnum::GAS) => Some(Fluid {
+                density: 0.8,
+                viscosity: 1.4e-5,
+                roughness: 4.5e-5,
+                max_velocity: 10.0,
+            }),
+            (MepDomain::Pipe, _) => Some(Fluid {
+                density: 998.0,
+                viscosity: 1.0e-6,
+                roughness: 4.5e-5,
+                max_velocity: 2.0,
+            }),
+        }
+    }
+
+    // Darcy friction factor, Swamee-Jain once turbulent
+    pub fn friction_factor(&self, velocity: f32, hydraulic_diameter: f32) -> f32 {
+        let reynolds = velocity * hydraulic_diameter / self.viscosity;
+        if reynolds <= 0.0 {
+            return 0.0;
+        }
+        if reynolds < LAMINAR {
+            return 64.0 / reynolds;
+        }
+        let term = self.roughness / (3.7 * hydraulic_diameter) + 5.74 / reynolds.powf(0.9);
+        0.25 / term.log10().powi(2)
+    }
+
+    // Pascals of ρv²/2
+    pub fn velocity_pressure(&self, velocity: f32) -> f32 {
+        self.density * velocity * velocity / 2.0
+    }
+}
+
+impl FittingKind {
+    // Local loss as a share of the velocity pressure through it
+    pub fn loss_coefficient(self) -> f32 {
+        match self {
+            FittingKind::Elbow => 0.3,
+            FittingKind::Tee => 0.5,
+            FittingKind::Transition => 0.15,
+        }
+    }
+}
+
+// One MEP element as the analysis sees it
+#[derive(Clone, Debug)]
+pub struct SystemNode {
+    pub id: ElementId,
+    pub domain: MepDomain,
+    pub system: DistributionSystemEnum,
+    pub ports: Vec<Port>,
+    // Segments have a length and fittings a kind
+    pub length: Option<f32>,
+    pub fitting: Option<FittingKind>,
+}
+
+// Elements joined through their ports
+#[derive(Clone, Debug)]
+pub struct MepNetwork {
+    pub domain: MepDomain,
+    pub system: DistributionSystemEnum,
+    pub elements: Vec<ElementId>,
+    // Going in at the open inlets, m³/s
+    pub flow: f32,
+    // Largest drop from an inlet to an outlet, the index run, Pa
+    pub index_drop: f32,
+}
+
+// Flow through one element and the pressure it costs
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ElementFlow {
+    // m³/s
+    pub flow: f32,
+    // m/s
+    pub velocity: f32,
+    // Lost across the element, Pa
+    pub pressure_drop: f32,
+    // Lost from the inlet of the network up to the outlet of the element, Pa
+    pub drop_from_source: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MepIssue {
+    pub element: ElementId,
+    pub port: Option<usize>,
+    pub message: String,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct MepAnalysis {
+    pub networks: Vec<MepNetwork>,
+    // Index into networks
+    pub membership: HashMap<ElementId, usize>,
+    pub flows: HashMap<ElementId, ElementFlow>,
+    pub issues: Vec<MepIssue>,
+}
+
+impl MepAnalysis {
+    pub fn network_of(&self, element: ElementId) -> Option<&MepNetwork> {
+        self.networks.get(*self.membership.get(&element)?)
+    }
+
+    pub fn issues_for(&self, element: ElementId) -> impl Iterator<Item = &MepIssue> {
+        self.issues.iter().filter(move |issue| issue.element == element)
+    }
+
+    pub fn analyse(nodes: &[SystemNode]) -> Self {
+        let mut analysis = MepAnalysis::default();
+        let by_id: HashMap<ElementId, &SystemNode> = nodes.iter().map(|node| (node.id, node)).collect();
+        let mut issue = |element: ElementId, port: Option<usize>, message: String| {
+            analysis.issues.push(MepIssue { element, port, message });
+        };
+
+        // Connections both ports agree on, anything else is reported and left out
+        let mut links: HashMap<PortRef, PortRef> = HashMap::new();
+        for node in nodes {
+            for (index, port) in node.ports.iter().enumerate() {
+                let here = PortRef {
+                    element: node.id,
+                    port: index,
+                };
+                let Some(there) = port.connected else {
+                    continue;
+                };
+                let Some(other) = by_id.get(&there.element).and_then(|other| other.ports.get(there.port)) else {
+                    issue(node.id, Some(index), format!("Connected to #{} which is gone", there.element.0));
+                    continue;
+                };
+                if other.connected != Some(here) {
+                    issue(node.id, Some(index), format!("#{} is not connected back", there.element.0));
+                    continue;
+                }
+                // Each pair is checked once, from its lower end
+                if (there.element.0, there.port) > (node.id.0, index) {
+                    if other.size != port.size {
+                        issue(node.id, Some(index), format!("{} meets {} at #{}", port.size, other.size, there.element.0));
+                    }
+                    let clash = matches!(
+                        (port.flow, other.flow),
+                        (FlowDirection::Source, FlowDirection::Source) | (FlowDirection::Sink, FlowDirection::Sink)
+                    );
+                    if clash {
+                        issue(node.id, Some(index), format!("Flow runs against #{}", there.element.0));
+                    }
+                }
+                links.insert(here, there);
+            }
+        }
+
+        // Networks are what the agreed connections reach
+        for node in nodes {
+            if analysis.membership.contains_key(&node.id) {
+                continue;
+            }
+            let network = analysis.networks.len();
+            let mut elements = Vec::new();
+            let mut stack = vec![node.id];
+            analysis.membership.insert(node.id, network);
+            while let Some(id) = stack.pop() {
+                elements.push(id);
+                let Some(current) = by_id.get(&id) else {
+                    continue;
+                };
+                for index in 0..current.ports.len() {
+                    let Some(there) = links.get(&PortRef { element: id, port: index }) else {
+                        continue;
+                    };
+                    if let Entry::Vacant(member) = analysis.membership.entry(there.element) {
+                        member.insert(network);
+                        stack.push(there.element);
+                    }
+                }
+            }
+            analysis.networks.push(MepNetwork {
+                domain: node.domain,
+                system: node.system,
+                elements,
+                flow: 0.0,
+                index_drop: 0.0,
+            });
+        }
+
+        for network in &analysis.networks {
+            let members = network.elements.iter().filter_map(|id| by_id.get(id));
+            if let Some(other) = members.clone().find(|member| member.system != network.system) {
+                analysis.issues.push(MepIssue {
+                    element: other.id,
+                    port: None,
+                    message: format!(
+                        "{} joins a {} network",
+                        system_name(other.system),
+                        system_name(network.system)
+                    ),
+                });
+            }
+            let open_inlets = members.clone().any(|member| {
+                member
+                    .ports
+                    .iter()
+                    .any(|port| port.connected.is_none() && port.flow == FlowDirection::Sink)
+            });
+            if !open_inlets && let Some(first) = network.elements.first() {
+                analysis.issues.push(MepIssue {
+                    element: *first,
+                    port: None,
+                    message: "Nothing feeds the network, it has no open inlet".to_owned(),
+                });
+            }
+            // Cable trays carry nothing, their open ends are never given a flow
+            if Fluid::of(network.domain, network.system).is_none() {
+                continue;
+            }
+            for member in members {
+                for (index, port) in member.ports.iter().enumerate() {
+                    if port.connected.is_none() && port.flow == FlowDirection::Source && port.design_flow.is_none() {
+                        analysis.issues.push(MepIssue {
+                            element: member.id,
+                            port: Some(index),
+                            message: "Open end without a design flow".to_owned(),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Flow through an element is what its outlets pass on downstream
+        let mut flows: HashMap<ElementId, f32> = HashMap::new();
+        let mut looped = HashSet::new();
+        for node in nodes {
+            downstream_flow(node.id, &by_id, &links, &mut flows, &mut Vec::new(), &mut looped);
+        }
+        for element in looped {
+            analysis.issues.push(MepIssue {
+                element,
+                port: None,
+                message: "Flow runs round a loop".to_owned(),
+            });
+        }
+
+        for node in nodes {
+            let flow = flows.get(&node.id).copied().unwrap_or(0.0);
+            let Some(fluid) = Fluid::of(node.domain, node.system) else {
+                continue;
+            };
+            // Fittings are taken at their inlet
+            let Some(size) = node
+                .ports
+                .iter()
+                .find(|port| port.flow == FlowDirection::Sink)
+                .or(node.ports.first())
+                .map(|port| port.size)
+            else {
+                continue;
+            };
+            let velocity = flow / size.area();
+            let velocity_pressure = fluid.velocity_pressure(velocity);
+            let pressure_drop = match (node.length, node.fitting) {
+                (Some(length), _) => {
+                    let diameter = size.hydraulic_diameter();
+                    fluid.friction_factor(velocity, diameter) * length / diameter * velocity_pressure
+                }
+                (None, Some(kind)) => kind.loss_coefficient() * velocity_pressure,
+                (None, None) => 0.0,
+            };
+            if velocity > fluid.max_velocity {
+                analysis.issues.push(MepIssue {
+                    element: node.id,
+                    port: None,
+                    message: format!(
+                        "{velocity:.1} m/s is over the {:.1} m/s limit for {size}",
+                        fluid.max_velocity
+                    ),
+                });
+            }
+            analysis.flows.insert(
+                node.id,
+                ElementFlow {
+                    flow,
+                    velocity,
+                    pressure_drop,
+                    drop_from_source: 0.0,
+                },
+            );
+        }
+
+        // Drops add up from the open inlets downstream, the worst path in wins
+        let own: HashMap<ElementId, f32> = analysis.flows.iter().map(|(id, flow)| (*id, flow.pressure_drop)).collect();
+        let mut drops = HashMap::new();
+        for node in nodes {
+            let drop = source_drop(node.id, &by_id, &links, &own, &mut drops, &mut Vec::new());
+            if let Some(flow) = analysis.flows.get_mut(&node.id) {
+                flow.drop_from_source = drop;
+            }
+        }
+
+        for network in &mut analysis.networks {
+            let members = || network.elements.iter().filter_map(|id| by_id.get(id));
+            network.flow = members()
+                .filter(|member| {
+                    member
+                        .ports
+                        .iter()
+                        .any(|port| port.connected.is_none() && port.flow == FlowDirection::Sink)
+                })
+                .filter_map(|member| flows.get(&member.id))
+                .sum();
+            network.index_drop = network
+                .elements
+                .iter()
+                .filter_map(|id| analysis.flows.get(id))
+                .map(|flow| flow.drop_from_source)
+                .fold(0.0, f32::max);
+        }
+        analysis
+    }
+}
+
+// Pressure lost from the open inlets up to the outlet of an element, along the worst path
+fn source_drop(
+    id: ElementId,
+    by_id: &HashMap<ElementId, &SystemNode>,
+    links: &HashMap<PortRef, PortRef>,
+    own: &HashMap<ElementId, f32>,
+    drops: &mut HashMap<ElementId, f32>,
+    path: &mut Vec<ElementId>,
+) -> f32 {
+    if let Some(drop) = drops.get(&id) {
+        return *drop;
+    }
+    let Some(node) = by_id.get(&id) else {
+        return 0.0;
+    };
+    if path.contains(&id) {
+        return 0.0;
+    }
+
+    path.push(id);
+    let mut upstream: f32 = 0.0;
+    for (index, port) in node.ports.iter().enumerate() {
+        if port.flow != FlowDirection::Sink {
+            continue;
+        }
+        if let Some(there) = links.get(&PortRef { element: id, port: index }) {
+            upstream = upstream.max(source_drop(there.element, by_id, links, own, drops, path));
+        }
+    }
+    path.pop();
+    let drop = upstream + own.get(&id).copied().unwrap_or(0.0);
+    drops.insert(id, drop);
+    drop
+}
+
+// Design flows of the open outlets downstream of an element, remembered once worked out.
+// Elements met again on the way down close a loop and pass nothing on.
+fn downstream_flow(
+    id: ElementId,
+    by_id: &HashMap<ElementId, &SystemNode>,
+    links: &HashMap<PortRef, PortRef>,
+    flows: &mut HashMap<ElementId, f32>,
+    path: &mut Vec<ElementId>,
+    looped: &mut HashSet<ElementId>,
+) -> f32 {
+    if let Some(flow) = flows.get(&id) {
+        return *flow;
+    }
+    if path.contains(&id) {
+        looped.insert(id);
+        return 0.0;
+    }
+    let Some(node) = by_id.get(&id) else {
+        return 0.0;
+    };
+
+    path.push(id);
+    let mut flow = 0.0;
+    for (index, port) in node.ports.iter().enumerate() {
+        if port.flow != FlowDirection::Source {
+            continue;
+        }
+        flow += match links.get(&PortRef { element: id, port: index }) {
+            Some(there) => downstream_flow(there.element, by_id, links, flows, path, looped),
+            None => port.design_flow.unwrap_or(0.0),
+        };
+    }
+    path.pop();
+    flows.insert(id, flow);
+    flow
+}
+
+#[derive(Message, Debug, Clone)]
+pub enum SystemCommand {
+    // Select the element an issue is on
+    Select(ElementId),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mep::SectionSize;
+
+    const SIZE: SectionSize = SectionSize::Round { diameter: 0.4 };
+
+    fn to(element: i64, port: usize) -> Option<PortRef> {
+        Some(PortRef {
+            element: ElementId(element),
+            port,
+        })
+    }
+
+    fn port(flow: FlowDirection, connected: Option<PortRef>, design_flow: Option<f32>) -> Port {
+        Port {
+            connected,
+            design_flow,
+            ..Port::new(Vec3::ZERO, Vec3::X, flow, SIZE)
+        }
+    }
+
+    // Ten meters of duct from its inlet to its outlet
+    fn segment(id: i64, inlet: Option<PortRef>, outlet: Option<PortRef>, design_flow: Option<f32>) -> SystemNode {
+        SystemNode {
+            id: ElementId(id),
+            domain: MepDomain::Duct,
+            system: DistributionSystemEnum::VENTILATION,
+            ports: vec![
+                port(FlowDirection::Sink, inlet, None),
+                port(FlowDirection::Source, outlet, design_flow),
+            ],
+            length: Some(10.0),
+            fitting: None,
+        }
+    }
+
+    fn messages(analysis: &MepAnalysis) -> Vec<&str> {
+        analysis.issues.iter().map(|issue| issue.message.as_str()).collect()
+    }
+
+    #[test]
+    fn flow_adds_up_from_the_open_outlets() {
+        let tee = SystemNode {
+            id: ElementId(2),
+            domain: MepDomain::Duct,
+            system: DistributionSystemEnum::VENTILATION,
+            ports: vec![
+                port(FlowDirection::Sink, to(1, 1), None),
+                port(FlowDirection::Source, to(3, 0), None),
+                port(FlowDirection::Source, to(4, 0), None),
+            ],
+            length: None,
+            fitting: Some(FittingKind::Tee),
+        };
+        let nodes = [
+            segment(1, None, to(2, 0), None),
+            tee,
+            segment(3, to(2, 1), None, Some(0.2)),
+            segment(4, to(2, 2), None, Some(0.3)),
+        ];
+        let analysis = MepAnalysis::analyse(&nodes);
+
+        assert!(analysis.issues.is_empty(), "{:?}", messages(&analysis));
+        assert_eq!(analysis.networks.len(), 1);
+        let network = &analysis.networks[0];
+        assert_eq!(network.elements.len(), 4);
+        assert!((network.flow - 0.5).abs() < 1e-6);
+
+        let flow = |id: i64| analysis.flows[&ElementId(id)];
+        for (id, expected) in [(1, 0.5), (2, 0.5), (3, 0.2), (4, 0.3)] {
+            assert!((flow(id).flow - expected).abs() < 1e-6, "#{id} carries {}", flow(id).flow);
+        }
+        assert!(flow(2).pressure_drop > 0.0);
+        let through_tee = flow(1).pressure_drop + flow(2).pressure_drop;
+        assert!((flow(4).drop_from_source - through_tee - flow(4).pressure_drop).abs() < 1e-3);
+        assert_eq!(network.index_drop, flow(3).drop_from_source.max(flow(4).drop_from_source));
+    }
+
+    #[test]
+    fn outlets_joined_to_outlets_are_reported() {
+        let nodes = [segment(1, None, to(2, 1), None), segment(2, None, to(1, 1), None)];
+        let analysis = MepAnalysis::analyse(&nodes);
+
+        let against = analysis
+            .issues
+            .iter()
+            .find(|issue| issue.message == "Flow runs against #2")
+            .expect("the clash is reported");
+        assert_eq!((against.element, against.port), (ElementId(1), Some(1)));
+    }
+
+    #[test]
+    fn one_sided_connections_are_left_out() {
+        let nodes = [segment(1, None, to(2, 0), None), segment(2, None, None, Some(0.1))];
+        let analysis = MepAnalysis::analyse(&nodes);
+
+        assert_eq!(messages(&analysis), ["#2 is not connected back"]);
+        assert_eq!(analysis.networks.len(), 2);
+        assert_ne!(analysis.membership[&ElementId(1)], analysis.membership[&ElementId(2)]);
+        assert_eq!(analysis.flows[&ElementId(1)].flow, 0.0);
+    }
+
+    #[test]
+    fn open_ends_need_a_design_flow_except_on_cable_trays() {
+        let duct = segment(1, None, None, None);
+        let analysis = MepAnalysis::analyse(std::slice::from_ref(&duct));
+        assert_eq!(messages(&analysis), ["Open end without a design flow"]);
+        assert_eq!(analysis.issues[0].port, Some(1));
+
+        let tray = SystemNode {
+            domain: MepDomain::CableCarrier,
+            system: DistributionSystemEnum::ELECTRICAL,
+            ..duct
+        };
+        let analysis = MepAnalysis::analyse(&[tray]);
+        assert!(analysis.issues.is_empty(), "{:?}", messages(&analysis));
+        assert!(analysis.flows.is_empty());
+    }
+}
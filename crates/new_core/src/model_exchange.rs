@@ -0,0 +1,67 @@
+// File: model_exchange.rs
+// Desc: 3D model exchange with other tools. glTF comes in as element instances and goes out as
+//       GLB with element ids and kinds in each node's extras. IFC goes out with structural grids.
+
+use bevy::prelude::*;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+use crate::elements::ElementKind;
+
+// Keys of the extras object on exported nodes, read back on import
+pub const EXTRAS_ELEMENT_ID: &str = "element_id";
+pub const EXTRAS_KIND: &str = "kind";
+pub const EXTRAS_KIND_TYPE: &str = "kind_type";
+pub const EXTRAS_PARAMS: &str = "params";
+
+// What imported meshes become when the file doesn't say
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ImportAs {
+    #[default]
+    #[strum(to_string = "Proxy")]
+    Proxy,
+    #[strum(to_string = "Furnishing")]
+    Furnishing,
+}
+
+impl ImportAs {
+    pub fn kind(&self) -> ElementKind {
+        match self {
+            ImportAs::Proxy => ElementKind::BuildingElementProxy,
+            ImportAs::Furnishing => ElementKind::FurnishingElement,
+        }
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ExportScope {
+    #[default]
+    #[strum(to_string = "Whole Model")]
+    Model,
+    #[strum(to_string = "Selection")]
+    Selection,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct ModelExchange {
+    pub import_path: String,
+    pub import_as: ImportAs,
+    pub export_path: String,
+    pub scope: ExportScope,
+    pub status: Option<String>,
+}
+
+#[derive(Message, Debug, Clone)]
+pub enum ModelCommand {
+    // .gltf or .glb, every mesh primitive becomes an element
+    ImportGltf { path: PathBuf, import_as: ImportAs },
+    ExportGlb { path: PathBuf, scope: ExportScope },
+    // IFC4 STEP, elements as proxies and structural grids as IfcGrid
+    ExportIfc { path: PathBuf, scope: ExportScope },
+}
+
+// Kind written by an export, matched on its display name
+pub fn kind_from_name(name: &str) -> Option<ElementKind> {
+    ElementKind::iter().find(|kind| kind.to_string() == name)
+}
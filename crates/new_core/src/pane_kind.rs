@@ -23,4 +23,31 @@ pub enum PaneKind {
 
     #[strum(to_string="Branch")]
     Branch,
+
+    #[strum(to_string="Clashes")]
+    Clashes,
+
+    #[strum(to_string="Systems")]
+    Systems,
+
+    #[strum(to_string="Timeline")]
+    Timeline,
+
+    #[strum(to_string="Costs")]
+    Costs,
+
+    #[strum(to_string="Schedules")]
+    Schedules,
+
+    #[strum(to_string="Sheets")]
+    Sheets,
+
+    #[strum(to_string="CAD")]
+    Cad,
+
+    #[strum(to_string="Models")]
+    Models,
+
+    #[strum(to_string="Keymap")]
+    Keymap,
 }
\ No newline at end of file
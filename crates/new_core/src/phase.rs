@@ -0,0 +1,164 @@
+// File: phase.rs
+// Desc: Renovation phasing. Elements know in which phase they were created and demolished,
+//       each view shows one phase through a phase filter.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use strum_macros::{Display, EnumIter};
+
+// Ordered, an element created in a later phase does not exist yet in an earlier one.
+// Same list as mn_core::enums::PhasingTemp.
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Phase {
+    #[default]
+    #[strum(to_string = "Existing")]
+    Existing,
+    #[strum(to_string = "Demolition")]
+    Demolition,
+    #[strum(to_string = "New Construction")]
+    NewConstruction,
+    #[strum(to_string = "Furnishing")]
+    Furnishing,
+    #[strum(to_string = "Hand Over")]
+    HandOver,
+}
+
+// Elements without this component count as existing and never demolished
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct ElementPhasing {
+    pub created: Phase,
+    pub demolished: Option<Phase>,
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhaseStatus {
+    // Created in an earlier phase and still standing
+    Existing,
+    // Created earlier, demolished in the view phase
+    Demolished,
+    // Created in the view phase
+    New,
+    // Created and demolished in the view phase
+    Temporary,
+    // Created in a later phase
+    Future,
+    // Demolished in an earlier phase
+    Gone,
+}
+
+impl ElementPhasing {
+    pub fn status(&self, view_phase: Phase) -> PhaseStatus {
+        if self.created > view_phase {
+            return PhaseStatus::Future;
+        }
+
+        match self.demolished {
+            Some(demolished) if demolished < view_phase => PhaseStatus::Gone,
+            Some(demolished) if demolished == view_phase && self.created == view_phase => {
+                PhaseStatus::Temporary
+            }
+            Some(demolished) if demolished == view_phase => PhaseStatus::Demolished,
+            _ if self.created == view_phase => PhaseStatus::New,
+            _ => PhaseStatus::Existing,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PhaseStyle {
+    Normal,
+    // Existing work, drawn washed out
+    Halftone,
+    // Demolished or temporary work, drawn red and hatched
+    Demolished,
+    Hidden,
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PhaseFilter {
+    // Everything that exists in the phase, no overrides
+    #[default]
+    #[strum(to_string = "None")]
+    NoFilter,
+    #[strum(to_string = "Show All")]
+    ShowAll,
+    #[strum(to_string = "Show Complete")]
+    ShowComplete,
+    #[strum(to_string = "Show New")]
+    ShowNew,
+    #[strum(to_string = "Show Demo + New")]
+    ShowDemoAndNew,
+    #[strum(to_string = "Show Previous + New")]
+    ShowPreviousAndNew,
+    #[strum(to_string = "Show Previous + Demo")]
+    ShowPreviousAndDemo,
+}
+
+impl PhaseFilter {
+    pub fn style(&self, status: PhaseStatus) -> PhaseStyle {
+        use PhaseFilter as F;
+        use PhaseStatus as S;
+        use PhaseStyle::*;
+
+        match (self, status) {
+            (_, S::Future | S::Gone) => Hidden,
+
+            (F::NoFilter, _) => Normal,
+
+            (F::ShowAll, S::New) => Normal,
+            (F::ShowAll, S::Existing) => Halftone,
+            (F::ShowAll, S::Demolished | S::Temporary) => Demolished,
+
+            (F::ShowComplete, S::New | S::Existing) => Normal,
+            (F::ShowComplete, S::Demolished | S::Temporary) => Hidden,
+
+            (F::ShowNew, S::New) => Normal,
+            (F::ShowNew, _) => Hidden,
+
+            (F::ShowDemoAndNew, S::New) => Normal,
+            (F::ShowDemoAndNew, S::Demolished | S::Temporary) => Demolished,
+            (F::ShowDemoAndNew, S::Existing) => Hidden,
+
+            (F::ShowPreviousAndNew, S::New) => Normal,
+            (F::ShowPreviousAndNew, S::Existing) => Halftone,
+            (F::ShowPreviousAndNew, S::Demolished | S::Temporary) => Hidden,
+
+            (F::ShowPreviousAndDemo, S::Existing) => Halftone,
+            (F::ShowPreviousAndDemo, S::Demolished) => Demolished,
+            (F::ShowPreviousAndDemo, S::New | S::Temporary) => Hidden,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ViewPhase {
+    pub phase: Phase,
+    pub filter: PhaseFilter,
+}
+
+impl Default for ViewPhase {
+    fn default() -> Self {
+        Self {
+            phase: Phase::NewConstruction,
+            filter: PhaseFilter::NoFilter,
+        }
+    }
+}
+
+impl ViewPhase {
+    pub fn style(&self, phasing: &ElementPhasing) -> PhaseStyle {
+        self.filter.style(phasing.status(self.phase))
+    }
+}
+
+// Phase settings of each viewport pane, keyed by pane id
+#[derive(Resource, Default, Debug)]
+pub struct ViewPhases {
+    pub views: HashMap<u32, ViewPhase>,
+}
+
+impl ViewPhases {
+    pub fn get(&self, pane_id: u32) -> ViewPhase {
+        self.views.get(&pane_id).copied().unwrap_or_default()
+    }
+}
@@ -1,3 +1,5 @@
+use bevy::prelude::*;
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Point3 {
     pub x: f64,
@@ -5,6 +7,20 @@ pub struct Point3 {
     pub z: f64,
 }
 
+impl Point3 {
+    pub fn from_vec3(point: Vec3) -> Self {
+        Self {
+            x: f64::from(point.x),
+            y: f64::from(point.y),
+            z: f64::from(point.z),
+        }
+    }
+
+    pub fn to_vec3(self) -> Vec3 {
+        Vec3::new(self.x as f32, self.y as f32, self.z as f32)
+    }
+}
+
 pub struct Quart3 {
     pub x: f64,
     pub y: f64,
@@ -55,6 +71,25 @@ impl Curve3 {
 pub struct Profile3 {
     loops: Vec<Curve3>,}
 
+impl Profile3 {
+    // The first loop is the outline, the ones after it are holes in it
+    pub fn new(loops: Vec<Curve3>) -> Self {
+        Self { loops }
+    }
+
+    pub fn outline(&self) -> Option<&Curve3> {
+        self.loops.first()
+    }
+
+    pub fn holes(&self) -> &[Curve3] {
+        self.loops.get(1..).unwrap_or_default()
+    }
+
+    pub fn loops(&self) -> &[Curve3] {
+        &self.loops
+    }
+}
+
 
 #[derive(Clone, Debug)]
 pub enum Placement {
@@ -0,0 +1,126 @@
+// File: preferences.rs
+// Desc: Settings each user keeps for themselves rather than per project, saved to their
+//       config folder. Plugins read the Preferences resource and follow its changes while the
+//       app runs. Fields missing from the file take their defaults, so it survives new ones
+//       being added. The keymap is kept next to it in its own file.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use strum_macros::{Display, EnumIter};
+
+use crate::units::ProjectUnits;
+
+pub const PREFERENCES_FILE: &str = "preferences.toml";
+
+pub const UI_SCALE_RANGE: RangeInclusive<f32> = 0.75..=2.0;
+pub const SENSITIVITY_RANGE: RangeInclusive<f32> = 0.1..=5.0;
+pub const GRID_SPACING_RANGE: RangeInclusive<f32> = 0.001..=100.0;
+
+#[derive(EnumIter, Display, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AppearancePrefs {
+    pub theme: Theme,
+    pub ui_scale: f32,
+}
+
+impl Default for AppearancePrefs {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Dark,
+            ui_scale: 1.0,
+        }
+    }
+}
+
+// Speeds are multipliers on the built in rates, 1 is the speed the app ships with
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct NavigationPrefs {
+    pub orbit_speed: f32,
+    pub pan_speed: f32,
+    pub zoom_speed: f32,
+    pub invert_zoom: bool,
+}
+
+impl Default for NavigationPrefs {
+    fn default() -> Self {
+        Self {
+            orbit_speed: 1.0,
+            pan_speed: 1.0,
+            zoom_speed: 1.0,
+            invert_zoom: false,
+        }
+    }
+}
+
+// What a new view starts with, each view keeps its own after that
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct GridPrefs {
+    pub visible: bool,
+    // Metres between minor lines
+    pub spacing: f32,
+    // Minor lines per major line
+    pub major_every: u32,
+    pub snap: bool,
+}
+
+impl Default for GridPrefs {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            spacing: 1.0,
+            major_every: 10,
+            snap: false,
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct Preferences {
+    pub appearance: AppearancePrefs,
+    pub navigation: NavigationPrefs,
+    pub grid: GridPrefs,
+    // What a new project starts with
+    pub units: ProjectUnits,
+}
+
+impl Preferences {
+    // Pulls values edited by hand in the file back into range
+    pub fn clamp(&mut self) {
+        let clamp = |value: f32, range: &RangeInclusive<f32>| value.clamp(*range.start(), *range.end());
+
+        self.appearance.ui_scale = clamp(self.appearance.ui_scale, &UI_SCALE_RANGE);
+        self.navigation.orbit_speed = clamp(self.navigation.orbit_speed, &SENSITIVITY_RANGE);
+        self.navigation.pan_speed = clamp(self.navigation.pan_speed, &SENSITIVITY_RANGE);
+        self.navigation.zoom_speed = clamp(self.navigation.zoom_speed, &SENSITIVITY_RANGE);
+        self.grid.spacing = clamp(self.grid.spacing, &GRID_SPACING_RANGE);
+        self.grid.major_every = self.grid.major_every.max(1);
+        self.units.clamp();
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PreferenceSection {
+    #[default]
+    Appearance,
+    Navigation,
+    Grid,
+    Units,
+    Keymap,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct PreferencesWindow {
+    pub open: bool,
+    pub section: PreferenceSection,
+}
@@ -0,0 +1,469 @@
+// File: profile.rs
+// Desc: Cross-sections of structural framing and the catalog of standard sizes they are picked
+//       from. Columns and beams sweep one along their axis, and carry its dimensions in their
+//       parameters so schedules and the properties pane can show them.
+
+use bevy::prelude::*;
+use strum_macros::{Display, EnumIter};
+
+use crate::element::{ElementId, ElementParams, ParamKey, ParamValue};
+use crate::placement::Curve3;
+use crate::tool::ToolId;
+
+// One click on a level or a grid intersection, up to the next level or a height
+pub const COLUMN_TOOL: ToolId = ToolId("structure.column");
+// Clicks along the axis, confirmed to finish
+pub const BEAM_TOOL: ToolId = ToolId("structure.beam");
+
+// Sides of the polygon round sections are drawn with
+pub const ROUND_SEGMENTS: usize = 24;
+
+// Parameters written from a profile, lengths in meters
+pub const PROFILE_PARAM: &str = "Profile";
+pub const SECTION_AREA_PARAM: &str = "Section Area";
+pub const LENGTH_PARAMS: [&str; 7] = [
+    "Depth",
+    "Width",
+    "Web Thickness",
+    "Flange Thickness",
+    "Thickness",
+    "Wall Thickness",
+    "Diameter",
+];
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProfileFamily {
+    #[strum(to_string = "I/H")]
+    IShape,
+    #[strum(to_string = "C")]
+    Channel,
+    #[strum(to_string = "L")]
+    Angle,
+    #[strum(to_string = "HSS")]
+    Hollow,
+    #[strum(to_string = "Rectangular")]
+    Rectangular,
+    #[strum(to_string = "Circular")]
+    Circular,
+}
+
+// Section dimensions in meters. Depth runs up the section and width across it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProfileShape {
+    IShape { depth: f32, width: f32, web: f32, flange: f32 },
+    // Web on the left, flanges pointing right
+    Channel { depth: f32, width: f32, web: f32, flange: f32 },
+    // Legs along the left and the bottom
+    Angle { depth: f32, width: f32, thickness: f32 },
+    RectangularHollow { depth: f32, width: f32, wall: f32 },
+    CircularHollow { diameter: f32, wall: f32 },
+    Rectangle { depth: f32, width: f32 },
+    Circle { diameter: f32 },
+}
+
+impl ProfileShape {
+    pub fn family(&self) -> ProfileFamily {
+        match self {
+            ProfileShape::IShape { .. } => ProfileFamily::IShape,
+            ProfileShape::Channel { .. } => ProfileFamily::Channel,
+            ProfileShape::Angle { .. } => ProfileFamily::Angle,
+            ProfileShape::RectangularHollow { .. } | ProfileShape::CircularHollow { .. } => ProfileFamily::Hollow,
+            ProfileShape::Rectangle { .. } => ProfileFamily::Rectangular,
+            ProfileShape::Circle { .. } => ProfileFamily::Circular,
+        }
+    }
+
+    // Width and depth of the box around the section
+    pub fn size(&self) -> Vec2 {
+        match *self {
+            ProfileShape::IShape { depth, width, .. }
+            | ProfileShape::Channel { depth, width, .. }
+            | ProfileShape::Angle { depth, width, .. }
+            | ProfileShape::RectangularHollow { depth, width, .. }
+            | ProfileShape::Rectangle { depth, width } => Vec2::new(width, depth),
+            ProfileShape::CircularHollow { diameter, .. } | ProfileShape::Circle { diameter } => Vec2::splat(diameter),
+        }
+    }
+
+    // Counterclockwise around the outside, centered on the box around the section
+    pub fn outline(&self) -> Vec<Vec2> {
+        let Vec2 { x: w, y: h } = self.size() / 2.0;
+        match *self {
+            ProfileShape::IShape { web, flange, .. } => {
+                let (t, f) = (web / 2.0, flange);
+                vec![
+                    Vec2::new(-w, -h),
+                    Vec2::new(w, -h),
+                    Vec2::new(w, -h + f),
+                    Vec2::new(t, -h + f),
+                    Vec2::new(t, h - f),
+                    Vec2::new(w, h - f),
+                    Vec2::new(w, h),
+                    Vec2::new(-w, h),
+                    Vec2::new(-w, h - f),
+                    Vec2::new(-t, h - f),
+                    Vec2::new(-t, -h + f),
+                    Vec2::new(-w, -h + f),
+                ]
+            }
+            ProfileShape::Channel { web, flange, .. } => vec![
+                Vec2::new(-w, -h),
+                Vec2::new(w, -h),
+                Vec2::new(w, -h + flange),
+                Vec2::new(-w + web, -h + flange),
+                Vec2::new(-w + web, h - flange),
+                Vec2::new(w, h - flange),
+                Vec2::new(w, h),
+                Vec2::new(-w, h),
+            ],
+            ProfileShape::Angle { thickness, .. } => vec![
+                Vec2::new(-w, -h),
+                Vec2::new(w, -h),
+                Vec2::new(w, -h + thickness),
+                Vec2::new(-w + thickness, -h + thickness),
+                Vec2::new(-w + thickness, h),
+                Vec2::new(-w, h),
+            ],
+            ProfileShape::RectangularHollow { .. } | ProfileShape::Rectangle { .. } => rectangle(w, h),
+            ProfileShape::CircularHollow { .. } | ProfileShape::Circle { .. } => round(w),
+        }
+    }
+
+    // Inside of a hollow section, counterclockwise with a point for each point of the outline
+    pub fn hole(&self) -> Option<Vec<Vec2>> {
+        let Vec2 { x: w, y: h } = self.size() / 2.0;
+        match *self {
+            ProfileShape::RectangularHollow { wall, .. } => Some(rectangle(w - wall, h - wall)),
+            ProfileShape::CircularHollow { wall, .. } => Some(round(w - wall)),
+            _ => None,
+        }
+    }
+
+    // Square meters of material
+    pub fn area(&self) -> f32 {
+        let outside = polygon_area(&self.outline());
+        let inside = self.hole().map_or(0.0, |hole| polygon_area(&hole));
+        outside - inside
+    }
+
+    // Named dimensions as the profile's catalog lists them
+    pub fn dimensions(&self) -> Vec<(&'static str, f32)> {
+        match *self {
+            ProfileShape::IShape { depth, width, web, flange } | ProfileShape::Channel { depth, width, web, flange } => vec![
+                ("Depth", depth),
+                ("Width", width),
+                ("Web Thickness", web),
+                ("Flange Thickness", flange),
+            ],
+            ProfileShape::Angle { depth, width, thickness } => {
+                vec![("Depth", depth), ("Width", width), ("Thickness", thickness)]
+            }
+            ProfileShape::RectangularHollow { depth, width, wall } => {
+                vec![("Depth", depth), ("Width", width), ("Wall Thickness", wall)]
+            }
+            ProfileShape::CircularHollow { diameter, wall } => vec![("Diameter", diameter), ("Wall Thickness", wall)],
+            ProfileShape::Rectangle { depth, width } => vec![("Depth", depth), ("Width", width)],
+            ProfileShape::Circle { diameter } => vec![("Diameter", diameter)],
+        }
+    }
+}
+
+fn rectangle(w: f32, h: f32) -> Vec<Vec2> {
+    vec![Vec2::new(-w, -h), Vec2::new(w, -h), Vec2::new(w, h), Vec2::new(-w, h)]
+}
+
+fn round(radius: f32) -> Vec<Vec2> {
+    (0..ROUND_SEGMENTS)
+        .map(|index| Vec2::from_angle(index as f32 / ROUND_SEGMENTS as f32 * std::f32::consts::TAU) * radius)
+        .collect()
+}
+
+fn polygon_area(points: &[Vec2]) -> f32 {
+    let twice: f32 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum();
+    twice.abs() / 2.0
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SectionProfile {
+    pub name: &'static str,
+    pub shape: ProfileShape,
+    // Steel sections, the rest are cast in concrete
+    pub steel: bool,
+}
+
+impl SectionProfile {
+    // What an element swept from the profile carries, the name and every dimension
+    pub fn parameters(&self) -> ElementParams {
+        let mut params = ElementParams::new();
+        params.insert(ParamKey::new(PROFILE_PARAM), ParamValue::Text(self.name.to_owned()));
+        for (key, value) in self.shape.dimensions() {
+            params.insert(ParamKey::new(key), ParamValue::Float(f64::from(value)));
+        }
+        params.insert(ParamKey::new(SECTION_AREA_PARAM), ParamValue::Float(f64::from(self.shape.area())));
+        params
+    }
+}
+
+// Catalog sizes are listed in millimeters
+const fn mm(value: f32) -> f32 {
+    value / 1000.0
+}
+
+const fn i_shape(name: &'static str, depth: f32, width: f32, web: f32, flange: f32) -> SectionProfile {
+    SectionProfile {
+        name,
+        shape: ProfileShape::IShape {
+            depth: mm(depth),
+            width: mm(width),
+            web: mm(web),
+            flange: mm(flange),
+        },
+        steel: true,
+    }
+}
+
+const fn channel(name: &'static str, depth: f32, width: f32, web: f32, flange: f32) -> SectionProfile {
+    SectionProfile {
+        name,
+        shape: ProfileShape::Channel {
+            depth: mm(depth),
+            width: mm(width),
+            web: mm(web),
+            flange: mm(flange),
+        },
+        steel: true,
+    }
+}
+
+const fn angle(name: &'static str, depth: f32, width: f32, thickness: f32) -> SectionProfile {
+    SectionProfile {
+        name,
+        shape: ProfileShape::Angle {
+            depth: mm(depth),
+            width: mm(width),
+            thickness: mm(thickness),
+        },
+        steel: true,
+    }
+}
+
+const fn box_section(name: &'static str, depth: f32, width: f32, wall: f32) -> SectionProfile {
+    SectionProfile {
+        name,
+        shape: ProfileShape::RectangularHollow {
+            depth: mm(depth),
+            width: mm(width),
+            wall: mm(wall),
+        },
+        steel: true,
+    }
+}
+
+const fn tube(name: &'static str, diameter: f32, wall: f32) -> SectionProfile {
+    SectionProfile {
+        name,
+        shape: ProfileShape::CircularHollow {
+            diameter: mm(diameter),
+            wall: mm(wall),
+        },
+        steel: true,
+    }
+}
+
+const fn rectangular(name: &'static str, depth: f32, width: f32) -> SectionProfile {
+    SectionProfile {
+        name,
+        shape: ProfileShape::Rectangle {
+            depth: mm(depth),
+            width: mm(width),
+        },
+        steel: false,
+    }
+}
+
+const fn circular(name: &'static str, diameter: f32) -> SectionProfile {
+    SectionProfile {
+        name,
+        shape: ProfileShape::Circle { diameter: mm(diameter) },
+        steel: false,
+    }
+}
+
+// European and American rolled sections, hollow sections to EN 10219 and common concrete sizes
+pub const PROFILES: &[SectionProfile] = &[
+    i_shape("IPE 200", 200.0, 100.0, 5.6, 8.5),
+    i_shape("IPE 240", 240.0, 120.0, 6.2, 9.8),
+    i_shape("IPE 300", 300.0, 150.0, 7.1, 10.7),
+    i_shape("IPE 360", 360.0, 170.0, 8.0, 12.7),
+    i_shape("IPE 400", 400.0, 180.0, 8.6, 13.5),
+    i_shape("IPE 450", 450.0, 190.0, 9.4, 14.6),
+    i_shape("IPE 500", 500.0, 200.0, 10.2, 16.0),
+    i_shape("HEA 200", 190.0, 200.0, 6.5, 10.0),
+    i_shape("HEA 240", 230.0, 240.0, 7.5, 12.0),
+    i_shape("HEA 300", 290.0, 300.0, 8.5, 14.0),
+    i_shape("HEB 200", 200.0, 200.0, 9.0, 15.0),
+    i_shape("HEB 240", 240.0, 240.0, 10.0, 17.0),
+    i_shape("HEB 300", 300.0, 300.0, 11.0, 19.0),
+    i_shape("W8x31", 203.0, 203.0, 7.2, 11.0),
+    i_shape("W10x49", 253.0, 254.0, 8.6, 14.2),
+    i_shape("W12x65", 308.0, 305.0, 9.9, 15.4),
+    i_shape("W14x90", 356.0, 369.0, 11.2, 18.0),
+    channel("UPN 100", 100.0, 50.0, 6.0, 8.5),
+    channel("UPN 160", 160.0, 65.0, 7.5, 10.5),
+    channel("UPN 200", 200.0, 75.0, 8.5, 11.5),
+    channel("UPN 300", 300.0, 100.0, 10.0, 16.0),
+    angle("L 50x50x5", 50.0, 50.0, 5.0),
+    angle("L 75x75x8", 75.0, 75.0, 8.0),
+    angle("L 100x100x10", 100.0, 100.0, 10.0),
+    angle("L 150x100x10", 150.0, 100.0, 10.0),
+    angle("L 150x150x15", 150.0, 150.0, 15.0),
+    box_section("SHS 100x100x5", 100.0, 100.0, 5.0),
+    box_section("SHS 150x150x8", 150.0, 150.0, 8.0),
+    box_section("SHS 200x200x10", 200.0, 200.0, 10.0),
+    box_section("RHS 200x100x6", 200.0, 100.0, 6.0),
+    box_section("RHS 300x200x10", 300.0, 200.0, 10.0),
+    tube("CHS 114.3x5", 114.3, 5.0),
+    tube("CHS 168.3x6.3", 168.3, 6.3),
+    tube("CHS 219.1x8", 219.1, 8.0),
+    tube("CHS 323.9x10", 323.9, 10.0),
+    rectangular("R 200x400", 400.0, 200.0),
+    rectangular("R 300x300", 300.0, 300.0),
+    rectangular("R 300x600", 600.0, 300.0),
+    rectangular("R 400x400", 400.0, 400.0),
+    rectangular("R 500x500", 500.0, 500.0),
+    circular("D 300", 300.0),
+    circular("D 400", 400.0),
+    circular("D 500", 500.0),
+    circular("D 600", 600.0),
+];
+
+pub fn profile(name: &str) -> Option<&'static SectionProfile> {
+    PROFILES.iter().find(|profile| profile.name == name)
+}
+
+// Where the section sits on the axis
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Justification {
+    // Columns, centered on the axis
+    #[default]
+    Center,
+    // Beams, hanging with their top on the axis so it can be drawn at the level
+    Top,
+}
+
+// Column or beam swept from a catalog profile. The axis is in the element's own space, starting
+// at its origin.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Framing {
+    pub profile: String,
+    pub axis: Curve3,
+    pub justification: Justification,
+}
+
+// What a column's top is held to. Its base stays on the level in its header.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub enum ColumnTop {
+    // Up to the level, following it when it moves
+    Level(ElementId),
+    // This far above the base, meters
+    Height(f32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{PI, TAU};
+
+    // Within half a percent of the table, which lists areas to three figures
+    fn close(value: f32, published: f32) -> bool {
+        (value / published - 1.0).abs() < 5.0e-3
+    }
+
+    fn shape(name: &str) -> ProfileShape {
+        profile(name).expect("in the catalog").shape
+    }
+
+    // Dimensions to a tenth of a millimeter, the way the tables give them
+    fn in_mm(shape: &ProfileShape) -> Vec<(&'static str, f32)> {
+        shape
+            .dimensions()
+            .into_iter()
+            .map(|(name, value)| (name, (value * 1.0e4).round() / 10.0))
+            .collect()
+    }
+
+    // Rolled sections are drawn without the root radius between web and flange, tables count
+    // the four fillets in
+    fn fillets(radius: f32) -> f32 {
+        (4.0 - PI) * radius * radius
+    }
+
+    // Cold formed hollow sections have rounded corners, tables take off what they leave out
+    fn corners(outside: f32, inside: f32) -> f32 {
+        (4.0 - PI) * (outside * outside - inside * inside)
+    }
+
+    #[test]
+    fn i_sections_match_their_tables() {
+        // EN 10365, cm² and root radius
+        let ipe = shape("IPE 300");
+        assert_eq!(
+            in_mm(&ipe),
+            [
+                ("Depth", 300.0),
+                ("Width", 150.0),
+                ("Web Thickness", 7.1),
+                ("Flange Thickness", 10.7)
+            ]
+        );
+        assert!(close(ipe.area() + fillets(0.015), 53.81e-4));
+        assert!(close(shape("HEB 200").area() + fillets(0.018), 78.08e-4));
+
+        // AISC, 14.4 in² with k less the flange as the radius
+        let w = shape("W10x49");
+        assert_eq!(in_mm(&w)[..2], [("Depth", 253.0), ("Width", 254.0)]);
+        assert!(close(w.area() + fillets(0.0127), 14.4 * 0.0254 * 0.0254));
+    }
+
+    #[test]
+    fn hollow_sections_match_en_10219() {
+        // Corner radii of 2t outside and t inside for walls up to 6 mm
+        let shs = shape("SHS 100x100x5");
+        assert_eq!(shs.family(), ProfileFamily::Hollow);
+        assert_eq!(in_mm(&shs), [("Depth", 100.0), ("Width", 100.0), ("Wall Thickness", 5.0)]);
+        assert!(close(shs.area() - corners(0.010, 0.005), 18.36e-4));
+        assert!(close(shape("RHS 200x100x6").area() - corners(0.012, 0.006), 33.6e-4));
+    }
+
+    #[test]
+    fn round_sections_match_their_tables() {
+        // The polygon they are drawn with holds this much of the circle
+        let polygon = ROUND_SEGMENTS as f32 / TAU * (TAU / ROUND_SEGMENTS as f32).sin();
+
+        let chs = shape("CHS 168.3x6.3");
+        assert_eq!(in_mm(&chs), [("Diameter", 168.3), ("Wall Thickness", 6.3)]);
+        assert!(close(chs.area() / polygon, 32.1e-4));
+
+        let column = shape("D 400");
+        assert_eq!(column.family(), ProfileFamily::Circular);
+        assert!(close(column.area() / polygon, PI * 0.2 * 0.2));
+    }
+
+    #[test]
+    fn parameters_carry_the_catalog_area() {
+        let ipe = profile("IPE 300").expect("in the catalog");
+        let params = ipe.parameters();
+        assert_eq!(
+            params.get(&ParamKey::new(PROFILE_PARAM)),
+            Some(&ParamValue::Text("IPE 300".to_owned()))
+        );
+        assert_eq!(
+            params.get(&ParamKey::new(SECTION_AREA_PARAM)),
+            Some(&ParamValue::Float(f64::from(ipe.shape.area())))
+        );
+    }
+}
@@ -0,0 +1,620 @@
+// File: room.rs
+// Desc: Rooms. A click between walls finds the loop their faces close around the point, cut
+//       through a little above the floor. The room keeps that loop as its profile, works out
+//       its areas from it and writes them with its number into parameters, where schedules,
+//       area reports and the doors leading into it read them.
+
+use bevy::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::element::{ElementHeader, ParamKey, ParamValue};
+use crate::elements::ElementKind;
+use crate::placement::{Curve3, Point3, Profile3};
+use crate::structural_grid::plan_to_world;
+use crate::tool::ToolId;
+
+// One click on the floor between the walls
+pub const ROOM_TOOL: ToolId = ToolId("architecture.room");
+
+// Walls are cut this far above the floor for the boundary, meters
+pub const CUT_HEIGHT: f32 = 1.0;
+// Points closer than this are one corner, meters
+const WELD: f32 = 1.0e-3;
+// Walls farther away than this from the point clicked are left out, meters
+const REACH: f32 = 100.0;
+// Faces farther apart than this are not taken for the two sides of one wall, meters
+const MAX_WALL: f32 = 1.0;
+// Sine of the angle under which two faces still count as the sides of one wall
+const PARALLEL: f32 = 0.05;
+
+// Parameters written on rooms, areas in m² and volume in m³
+pub const NUMBER_PARAM: &str = "Number";
+pub const AREA_PARAM: &str = "Area";
+pub const GROSS_AREA_PARAM: &str = "Gross Area";
+pub const PERIMETER_PARAM: &str = "Perimeter";
+pub const VOLUME_PARAM: &str = "Volume";
+// Written on doors, as the rooms are labelled
+pub const FROM_ROOM_PARAM: &str = "From Room";
+pub const TO_ROOM_PARAM: &str = "To Room";
+
+// Elements that close rooms off. Doors and windows fill the openings they stand in, columns
+// standing free in a room come off its area.
+pub fn bounds_rooms(kind: ElementKind) -> bool {
+    matches!(
+        kind,
+        ElementKind::Wall
+            | ElementKind::WallStandardCase
+            | ElementKind::CurtainWall
+            | ElementKind::Column
+            | ElementKind::Door
+            | ElementKind::Window
+    )
+}
+
+// Number and name, like 101 Office
+pub fn room_label(header: &ElementHeader) -> String {
+    let number = header.params.get(&ParamKey::new(NUMBER_PARAM)).map(ToString::to_string);
+    match (number, header.name.as_deref()) {
+        (Some(number), Some(name)) => format!("{number} {name}"),
+        (Some(number), None) => number,
+        (None, Some(name)) => name.to_owned(),
+        (None, None) => format!("#{}", header.id.0),
+    }
+}
+
+// Rooms on a floor are numbered from its hundred, 101 and on for the first one up from the lowest.
+// Past 99 rooms the gaps left are filled, then the last number is split so the next floor's
+// numbers stay free. Numbers taken that are not plain numbers are left alone.
+pub fn next_room_number<'a>(taken: impl IntoIterator<Item = &'a str>, floor: usize) -> String {
+    let first = (floor as u32 + 1) * 100 + 1;
+    let last = first + 98;
+    let taken: Vec<&str> = taken.into_iter().map(str::trim).collect();
+    let numbers: BTreeSet<u32> = taken
+        .iter()
+        .filter_map(|number| number.parse::<u32>().ok())
+        .filter(|number| (first..=last).contains(number))
+        .collect();
+
+    let next = match numbers.last() {
+        None => Some(first),
+        Some(&top) if top < last => Some(top + 1),
+        Some(_) => (first..=last).find(|number| !numbers.contains(number)),
+    };
+    match next {
+        Some(number) => number.to_string(),
+        None => (1..)
+            .map(|part| format!("{last}.{part}"))
+            .find(|number| !taken.contains(&number.as_str()))
+            .unwrap_or_default(),
+    }
+}
+
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct Room {
+    // Floor to ceiling, meters
+    pub height: f32,
+    // Along the wall faces, on the floor around the room's origin
+    pub profile: Profile3,
+    // Inside the wall centerlines, m²
+    pub gross_area: f32,
+    // False once the walls no longer close around it, the last boundary found is kept
+    pub enclosed: bool,
+}
+
+impl Room {
+    // Inside the wall faces, less what stands free in the room, m²
+    pub fn area(&self) -> f32 {
+        let outline = self.profile.outline().map_or(0.0, |outline| polygon_area(&plan(outline)).abs());
+        let holes: f32 = self.profile.holes().iter().map(|hole| polygon_area(&plan(hole)).abs()).sum();
+        (outline - holes).max(0.0)
+    }
+
+    // Length of wall faces around the room, free standing ones included
+    pub fn perimeter(&self) -> f32 {
+        self.profile
+            .loops()
+            .iter()
+            .map(|curve| {
+                let points = plan(curve);
+                (0..points.len())
+                    .map(|i| points[i].distance(points[(i + 1) % points.len()]))
+                    .sum::<f32>()
+            })
+            .sum()
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.area() * self.height
+    }
+
+    // Whether a point in plan, relative to the room's origin, is on its floor
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.profile.outline().is_some_and(|outline| polygon_contains(&plan(outline), point))
+            && !self.profile.holes().iter().any(|hole| polygon_contains(&plan(hole), point))
+    }
+
+    pub fn params(&self) -> [(&'static str, f32); 4] {
+        [
+            (AREA_PARAM, self.area()),
+            (GROSS_AREA_PARAM, self.gross_area),
+            (PERIMETER_PARAM, self.perimeter()),
+            (VOLUME_PARAM, self.volume()),
+        ]
+    }
+
+    pub fn write_params(&self, header: &mut ElementHeader) {
+        for (key, value) in self.params() {
+            header.params.insert(ParamKey::new(key), ParamValue::Float(f64::from(value)));
+        }
+    }
+}
+
+// Profile points lie on the floor, in plan as the grids set them out
+pub fn plan(curve: &Curve3) -> Vec<Vec2> {
+    curve.points.iter().map(|point| Vec2::new(point.x as f32, -point.z as f32)).collect()
+}
+
+// Boundary found around a point, in plan
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoomOutline {
+    // Counterclockwise along the wall faces
+    pub outline: Vec<Vec2>,
+    // Clockwise, around what stands free inside the outline
+    pub holes: Vec<Vec<Vec2>>,
+    // Halfway through the walls around the outline
+    pub centerline: Vec<Vec2>,
+}
+
+impl RoomOutline {
+    // The smallest loop of faces around the point, None when it is not closed in or the point
+    // is inside a wall. Faces are the cut through the walls, each running counterclockwise
+    // around the wall it cuts, in any order and overlapping where walls meet.
+    pub fn find(faces: &[(Vec2, Vec2)], point: Vec2) -> Option<Self> {
+        let near: Vec<(Vec2, Vec2)> = faces
+            .iter()
+            .copied()
+            .filter(|(a, b)| a.min(*b).cmple(point + REACH).all() && a.max(*b).cmpge(point - REACH).all())
+            .collect();
+
+        // From inside a wall the nearest face to the right is the back of one
+        let nearest = near
+            .iter()
+            .filter_map(|&(a, b)| ray_hit(a, b, point).map(|x| (x, b.y - a.y)))
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+        if nearest.is_none_or(|(_, rise)| rise > 0.0) {
+            return None;
+        }
+
+        let graph = Arrangement::new(&near);
+
+        // Along the same ray, the first face of every group of walls met faces the room. Groups
+        // standing free in the room are passed, the first loop around the point is it.
+        let mut hits: Vec<(f32, usize, usize)> = graph
+            .edges
+            .iter()
+            .filter_map(|&(a, b)| ray_hit(graph.nodes[a], graph.nodes[b], point).map(|x| (x, a, b)))
+            .collect();
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut passed = Vec::new();
+        let mut found = None;
+        for (_, a, b) in hits {
+            let group = graph.group(a);
+            if passed.contains(&group) {
+                continue;
+            }
+            passed.push(group);
+
+            let (pa, pb) = (graph.nodes[a], graph.nodes[b]);
+            let start = if (pb - pa).perp_dot(point - pa) > 0.0 { (a, b) } else { (b, a) };
+            let outline = simplify(graph.trace(start).iter().map(|&node| graph.nodes[node]).collect());
+            if polygon_area(&outline) > 0.0 && polygon_contains(&outline, point) {
+                found = Some((outline, group));
+                break;
+            }
+        }
+        let (outline, group) = found?;
+
+        // Whatever stands free inside is a hole, unless it is inside another hole
+        let candidates: Vec<Vec<Vec2>> = graph
+            .groups()
+            .into_iter()
+            .filter(|(other, nodes)| *other != group && polygon_contains(&outline, graph.nodes[nodes[0]]))
+            .filter_map(|(_, nodes)| graph.outer_loop(&nodes))
+            .map(simplify)
+            .filter(|hole| hole.len() >= 3)
+            .collect();
+        let holes = candidates
+            .iter()
+            .filter(|hole| {
+                !candidates
+                    .iter()
+                    .any(|other| other != *hole && polygon_contains(other, hole[0]))
+            })
+            .cloned()
+            .collect();
+
+        let centerline = centerline(&outline, &graph);
+        Some(Self {
+            outline,
+            holes,
+            centerline,
+        })
+    }
+
+    // Room on the floor at the given elevation, placed at origin in plan
+    pub fn room(&self, placed: &GlobalTransform, height: f32) -> Room {
+        let to_local = placed.affine().inverse();
+        let elevation = placed.translation().y;
+        let curve = |points: &Vec<Vec2>| Curve3 {
+            points: points
+                .iter()
+                .map(|point| Point3::from_vec3(to_local.transform_point3(plan_to_world(*point, elevation))))
+                .collect(),
+        };
+
+        Room {
+            height,
+            profile: Profile3::new(std::iter::once(&self.outline).chain(&self.holes).map(curve).collect()),
+            gross_area: polygon_area(&self.centerline).abs(),
+            enclosed: true,
+        }
+    }
+}
+
+// Faces split wherever they cross or touch, as a planar graph
+struct Arrangement {
+    nodes: Vec<Vec2>,
+    edges: Vec<(usize, usize)>,
+    // Neighbours of every node, counterclockwise
+    around: Vec<Vec<usize>>,
+    // Union find over the nodes, walls touching each other end up in one group
+    parents: Vec<usize>,
+}
+
+impl Arrangement {
+    fn new(faces: &[(Vec2, Vec2)]) -> Self {
+        // Where along every face it is split, 0 to 1
+        let mut splits: Vec<Vec<f32>> = faces.iter().map(|_| vec![0.0, 1.0]).collect();
+        for i in 0..faces.len() {
+            for j in i + 1..faces.len() {
+                let ((a, b), (c, d)) = (faces[i], faces[j]);
+                if a.min(b).cmpgt(c.max(d) + WELD).any() || c.min(d).cmpgt(a.max(b) + WELD).any() {
+                    continue;
+                }
+
+                let (r, s) = (b - a, d - c);
+                let denominator = r.perp_dot(s);
+                if denominator.abs() > f32::EPSILON {
+                    let t = (c - a).perp_dot(s) / denominator;
+                    let u = (c - a).perp_dot(r) / denominator;
+                    let (slack_t, slack_u) = (WELD / r.length(), WELD / s.length());
+                    if (-slack_t..=1.0 + slack_t).contains(&t) && (-slack_u..=1.0 + slack_u).contains(&u) {
+                        splits[i].push(t.clamp(0.0, 1.0));
+                        splits[j].push(u.clamp(0.0, 1.0));
+                    }
+                }
+
+                // Ends lying on the other face, where walls overlap or butt against each other
+                for (end, onto, split) in [(c, i, i), (d, i, i), (a, j, j), (b, j, j)] {
+                    let (from, to) = faces[onto];
+                    let along = to - from;
+                    let t = ((end - from).dot(along) / along.length_squared()).clamp(0.0, 1.0);
+                    if (from + along * t).distance(end) < WELD {
+                        splits[split].push(t);
+                    }
+                }
+            }
+        }
+
+        let mut nodes: Vec<Vec2> = Vec::new();
+        let mut welded: HashMap<[i64; 2], usize> = HashMap::new();
+        let mut weld = |point: Vec2| {
+            let key = (point / WELD).round().as_i64vec2().to_array();
+            *welded.entry(key).or_insert_with(|| {
+                nodes.push(point);
+                nodes.len() - 1
+            })
+        };
+
+        let mut edges: BTreeSet<(usize, usize)> = BTreeSet::new();
+        for ((a, b), mut split) in faces.iter().zip(splits) {
+            split.sort_by(f32::total_cmp);
+            let points: Vec<usize> = split.iter().map(|&t| weld(a.lerp(*b, t))).collect();
+            for pair in points.windows(2) {
+                if pair[0] != pair[1] {
+                    edges.insert((pair[0].min(pair[1]), pair[0].max(pair[1])));
+                }
+            }
+        }
+
+        let mut around: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+        let mut parents: Vec<usize> = (0..nodes.len()).collect();
+        for &(a, b) in &edges {
+            around[a].push(b);
+            around[b].push(a);
+            let (root_a, root_b) = (find_root(&mut parents, a), find_root(&mut parents, b));
+            parents[root_a] = root_b;
+        }
+        for (node, neighbours) in around.iter_mut().enumerate() {
+            let at = nodes[node];
+            let angle = |other: &usize| {
+                let to = nodes[*other] - at;
+                to.y.atan2(to.x)
+            };
+            neighbours.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+        }
+
+        Self {
+            nodes,
+            edges: edges.into_iter().collect(),
+            around,
+            parents,
+        }
+    }
+
+    fn group(&self, node: usize) -> usize {
+        let mut node = node;
+        while self.parents[node] != node {
+            node = self.parents[node];
+        }
+        node
+    }
+
+    fn groups(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+        for node in 0..self.nodes.len() {
+            let group = self.group(node);
+            match groups.iter_mut().find(|(other, _)| *other == group) {
+                Some((_, nodes)) => nodes.push(node),
+                None => groups.push((group, vec![node])),
+            }
+        }
+        groups
+    }
+
+    // Nodes around the face on the left of the edge, turning as far right as it can at each node
+    fn trace(&self, start: (usize, usize)) -> Vec<usize> {
+        let mut ring = Vec::new();
+        let (mut from, mut to) = start;
+        for _ in 0..=self.edges.len() * 2 {
+            ring.push(from);
+            let neighbours = &self.around[to];
+            let back = neighbours.iter().position(|&node| node == from).unwrap_or(0);
+            let next = neighbours[(back + neighbours.len() - 1) % neighbours.len()];
+            (from, to) = (to, next);
+            if (from, to) == start {
+                break;
+            }
+        }
+        ring
+    }
+
+    // Clockwise loop around the outside of a group, the one with the most area
+    fn outer_loop(&self, nodes: &[usize]) -> Option<Vec<Vec2>> {
+        nodes
+            .iter()
+            .flat_map(|&node| self.around[node].iter().map(move |&next| (node, next)))
+            .map(|start| self.trace(start).iter().map(|&node| self.nodes[node]).collect::<Vec<Vec2>>())
+            .filter(|ring| polygon_area(ring) < 0.0)
+            .min_by(|a, b| polygon_area(a).total_cmp(&polygon_area(b)))
+    }
+}
+
+fn find_root(parents: &mut [usize], node: usize) -> usize {
+    let mut root = node;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[node] = root;
+    root
+}
+
+// Drops corners that do not turn, along with the out and back of faces running into the room
+fn simplify(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    loop {
+        let count = points.len();
+        if count < 3 {
+            return points;
+        }
+        let straight = (0..count).find(|&i| {
+            let (a, b, c) = (points[(i + count - 1) % count], points[i], points[(i + 1) % count]);
+            (b - a).perp_dot(c - b).abs() <= WELD * (c - a).length().max(WELD)
+        });
+        let Some(i) = straight else {
+            return points;
+        };
+        points.remove(i);
+    }
+}
+
+// Every face of the outline moved out by half the wall behind it. Faces with nothing parallel
+// close behind them stay where they are.
+fn centerline(outline: &[Vec2], graph: &Arrangement) -> Vec<Vec2> {
+    let count = outline.len();
+    let lines: Vec<(Vec2, Vec2)> = (0..count)
+        .map(|i| {
+            let (a, b) = (outline[i], outline[(i + 1) % count]);
+            let along = (b - a).normalize_or_zero();
+            // Right of a counterclockwise outline is out of the room
+            let out = Vec2::new(along.y, -along.x);
+            let middle = (a + b) / 2.0;
+
+            let thickness = graph
+                .edges
+                .iter()
+                .filter_map(|&(c, d)| {
+                    let (c, d) = (graph.nodes[c], graph.nodes[d]);
+                    let face = d - c;
+                    if along.perp_dot(face.normalize_or_zero()).abs() > PARALLEL {
+                        return None;
+                    }
+                    let denominator = out.perp_dot(face);
+                    if denominator.abs() < f32::EPSILON {
+                        return None;
+                    }
+                    let t = (c - middle).perp_dot(face) / denominator;
+                    let u = (c - middle).perp_dot(out) / denominator;
+                    ((0.0..=1.0).contains(&u) && t > WELD && t <= MAX_WALL).then_some(t)
+                })
+                .min_by(f32::total_cmp)
+                .unwrap_or(0.0);
+            (a + out * thickness / 2.0, along)
+        })
+        .collect();
+
+    (0..count)
+        .map(|i| {
+            let (before, before_along) = lines[(i + count - 1) % count];
+            let (at, along) = lines[i];
+            let denominator = before_along.perp_dot(along);
+            if denominator.abs() < PARALLEL * PARALLEL {
+                return at;
+            }
+            before + before_along * (at - before).perp_dot(along) / denominator
+        })
+        .collect()
+}
+
+// Where a ray from the point to the right crosses the face
+fn ray_hit(a: Vec2, b: Vec2, point: Vec2) -> Option<f32> {
+    if (a.y > point.y) == (b.y > point.y) {
+        return None;
+    }
+    let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+    (x > point.x).then_some(x)
+}
+
+// Positive for counterclockwise polygons
+fn polygon_area(points: &[Vec2]) -> f32 {
+    (0..points.len())
+        .map(|i| points[i].perp_dot(points[(i + 1) % points.len()]))
+        .sum::<f32>()
+        / 2.0
+}
+
+fn polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THICKNESS: f32 = 0.2;
+
+    // Faces of a wall along its centerline, running past both ends by half its thickness
+    fn wall(a: Vec2, b: Vec2) -> [(Vec2, Vec2); 4] {
+        let along = (b - a).normalize() * THICKNESS / 2.0;
+        let side = along.perp();
+        let corners = [a - along - side, b + along - side, b + along + side, a - along + side];
+        [0, 1, 2, 3].map(|i| (corners[i], corners[(i + 1) % 4]))
+    }
+
+    fn walls(centerline: &[Vec2], closed: bool) -> Vec<(Vec2, Vec2)> {
+        let count = if closed { centerline.len() } else { centerline.len() - 1 };
+        (0..count)
+            .flat_map(|i| wall(centerline[i], centerline[(i + 1) % centerline.len()]))
+            .collect()
+    }
+
+    fn rectangle(min: Vec2, max: Vec2) -> Vec<Vec2> {
+        vec![min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+    }
+
+    fn close(value: f32, expected: f32) -> bool {
+        (value - expected).abs() < 1.0e-3
+    }
+
+    #[test]
+    fn four_walls_close_around_a_rectangle() {
+        let faces = walls(&rectangle(Vec2::ZERO, Vec2::new(4.0, 3.0)), true);
+        let found = RoomOutline::find(&faces, Vec2::new(2.0, 1.5)).expect("closed in");
+
+        assert_eq!(found.outline.len(), 4);
+        assert!(close(polygon_area(&found.outline), 3.8 * 2.8));
+        assert!(found.holes.is_empty());
+        assert!(close(polygon_area(&found.centerline).abs(), 12.0));
+
+        let room = found.room(&GlobalTransform::IDENTITY, 2.5);
+        assert!(close(room.area(), 3.8 * 2.8));
+        assert!(close(room.perimeter(), 2.0 * (3.8 + 2.8)));
+        assert!(close(room.gross_area, 12.0));
+        assert!(room.contains(Vec2::new(2.0, 1.5)));
+    }
+
+    #[test]
+    fn an_l_shaped_room_keeps_its_inside_corner() {
+        let centerline = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(6.0, 0.0),
+            Vec2::new(6.0, 3.0),
+            Vec2::new(3.0, 3.0),
+            Vec2::new(3.0, 6.0),
+            Vec2::new(0.0, 6.0),
+        ];
+        let faces = walls(&centerline, true);
+        let found = RoomOutline::find(&faces, Vec2::new(1.0, 4.0)).expect("closed in");
+
+        assert_eq!(found.outline.len(), 6);
+        assert!(found.outline.iter().any(|corner| corner.distance(Vec2::new(2.9, 2.9)) < WELD));
+        assert!(close(polygon_area(&found.outline), 5.8 * 2.8 + 2.8 * 3.0));
+        assert!(close(polygon_area(&found.centerline).abs(), 27.0));
+        assert!(!polygon_contains(&found.outline, Vec2::new(4.5, 4.5)));
+    }
+
+    #[test]
+    fn a_free_standing_column_is_a_hole() {
+        let mut faces = walls(&rectangle(Vec2::ZERO, Vec2::new(6.0, 4.0)), true);
+        let column = rectangle(Vec2::new(2.8, 1.8), Vec2::new(3.2, 2.2));
+        faces.extend((0..4).map(|i| (column[i], column[(i + 1) % 4])));
+        let found = RoomOutline::find(&faces, Vec2::new(1.0, 1.0)).expect("closed in");
+
+        assert_eq!(found.holes.len(), 1);
+        assert!(polygon_area(&found.holes[0]) < 0.0);
+        assert!(close(polygon_area(&found.holes[0]).abs(), 0.16));
+
+        let room = found.room(&GlobalTransform::IDENTITY, 2.5);
+        assert!(close(room.area(), 5.8 * 3.8 - 0.16));
+        assert!(close(room.gross_area, 24.0));
+        assert!(room.contains(Vec2::new(1.0, 1.0)));
+        assert!(!room.contains(Vec2::new(3.0, 2.0)));
+    }
+
+    #[test]
+    fn a_click_inside_a_wall_finds_nothing() {
+        let faces = walls(&rectangle(Vec2::ZERO, Vec2::new(4.0, 3.0)), true);
+        assert_eq!(RoomOutline::find(&faces, Vec2::new(0.0, 1.5)), None);
+    }
+
+    #[test]
+    fn an_open_run_of_walls_finds_nothing() {
+        let run = [
+            Vec2::new(0.0, 3.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 0.0),
+            Vec2::new(4.0, 3.0),
+        ];
+        let faces = walls(&run, false);
+        assert_eq!(RoomOutline::find(&faces, Vec2::new(2.0, 1.5)), None);
+    }
+
+    #[test]
+    fn room_numbers_stay_within_the_floor() {
+        assert_eq!(next_room_number([], 0), "101");
+        assert_eq!(next_room_number(["101", "102", "B1", "205"], 0), "103");
+        assert_eq!(next_room_number(["199", "101"], 0), "102");
+
+        let full: Vec<String> = (101..=199).map(|number| number.to_string()).collect();
+        assert_eq!(next_room_number(full.iter().map(String::as_str), 0), "199.1");
+        let gap: Vec<&str> = full.iter().map(String::as_str).filter(|number| *number != "150").collect();
+        assert_eq!(next_room_number(gap, 0), "150");
+    }
+}
@@ -0,0 +1,460 @@
+// File: schedule.rs
+// Desc: Schedules. Definitions pick elements by kind and lay their fields out as a table,
+//       the app keeps the rows current and the pane edits them.
+
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use strum_macros::{Display, EnumIter};
+
+use crate::element::{ElementHeader, ElementId, ParamKey, ParamType, ParamValue};
+use crate::elements::ElementKind;
+use crate::room::{AREA_PARAM, FROM_ROOM_PARAM, GROSS_AREA_PARAM, NUMBER_PARAM, TO_ROOM_PARAM, VOLUME_PARAM};
+use crate::units::{Measure, ProjectUnits};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScheduleId(pub u32);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ScheduleField {
+    Id,
+    Name,
+    Kind,
+    KindType,
+    Level,
+    Spec,
+    Param(ParamKey, ParamType),
+}
+
+impl ScheduleField {
+    pub fn label(&self) -> String {
+        match self {
+            ScheduleField::Id => "Id".to_owned(),
+            ScheduleField::Name => "Name".to_owned(),
+            ScheduleField::Kind => "Kind".to_owned(),
+            ScheduleField::KindType => "Type".to_owned(),
+            ScheduleField::Level => "Level".to_owned(),
+            ScheduleField::Spec => "Spec".to_owned(),
+            ScheduleField::Param(key, _) => key.0.clone(),
+        }
+    }
+
+    // Fields that live on the element and may be written from a schedule
+    pub fn editable(&self) -> bool {
+        matches!(self, ScheduleField::Name | ScheduleField::Param(..))
+    }
+
+    pub fn value_type(&self) -> ParamType {
+        match self {
+            ScheduleField::Id => ParamType::Int,
+            ScheduleField::Level | ScheduleField::Spec => ParamType::ElementRef,
+            ScheduleField::Param(_, param_type) => *param_type,
+            _ => ParamType::Text,
+        }
+    }
+
+    pub fn read(&self, header: &ElementHeader) -> Option<ParamValue> {
+        match self {
+            ScheduleField::Id => Some(ParamValue::Int(header.id.0)),
+            ScheduleField::Name => header.name.clone().map(ParamValue::Text),
+            ScheduleField::Kind => Some(ParamValue::Text(header.kind.to_string())),
+            ScheduleField::KindType => header
+                .kind_type
+                .as_ref()
+                .map(|kind_type| ParamValue::Text(kind_type.to_string())),
+            ScheduleField::Level => header.level_id.map(ParamValue::ElemenentRef),
+            ScheduleField::Spec => header.spec_id.map(ParamValue::ElemenentRef),
+            ScheduleField::Param(key, _) => header.params.get(key).cloned(),
+        }
+    }
+
+    // Returns false when the field can not take the value
+    pub fn write(&self, header: &mut ElementHeader, value: Option<ParamValue>) -> bool {
+        match (self, value) {
+            (ScheduleField::Name, Some(ParamValue::Text(name))) => {
+                header.name = (!name.is_empty()).then_some(name);
+                true
+            }
+            (ScheduleField::Name, None) => {
+                header.name = None;
+                true
+            }
+            (ScheduleField::Param(key, param_type), Some(value)) => {
+                if value.param_type() != *param_type {
+                    return false;
+                }
+                header.params.insert(key.clone(), value);
+                true
+            }
+            (ScheduleField::Param(key, _), None) => {
+                header.params.remove(key);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormatCondition {
+    #[strum(to_string = "Is Empty")]
+    Empty,
+    #[strum(to_string = "Equals")]
+    Equals,
+    #[strum(to_string = "Contains")]
+    Contains,
+    #[strum(to_string = "Greater Than")]
+    GreaterThan,
+    #[strum(to_string = "Less Than")]
+    LessThan,
+}
+
+// Highlights a cell whose value meets the condition
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatRule {
+    pub condition: FormatCondition,
+    pub operand: String,
+    pub color: [u8; 3],
+}
+
+impl FormatRule {
+    // Operands of a measured column are typed in units, like > 2.1m
+    pub fn matches(&self, value: Option<&ParamValue>, measure: Option<Measure>, units: &ProjectUnits) -> bool {
+        let number = || match measure {
+            Some(measure) => units.parse(measure, &self.operand),
+            None => self.operand.trim().parse::<f64>().ok(),
+        };
+
+        match (self.condition, value) {
+            (FormatCondition::Empty, value) => {
+                value.is_none_or(|value| value.to_string().trim().is_empty())
+            }
+            (_, None) => false,
+            (FormatCondition::Equals, Some(value)) => {
+                value.to_string().eq_ignore_ascii_case(self.operand.trim())
+            }
+            (FormatCondition::Contains, Some(value)) => value
+                .to_string()
+                .to_lowercase()
+                .contains(&self.operand.trim().to_lowercase()),
+            (FormatCondition::GreaterThan, Some(value)) => {
+                matches!((value.as_f64(), number()), (Some(v), Some(n)) if v > n)
+            }
+            (FormatCondition::LessThan, Some(value)) => {
+                matches!((value.as_f64(), number()), (Some(v), Some(n)) if v < n)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduleColumn {
+    pub field: ScheduleField,
+    pub heading: String,
+    // Sum the column in group and grand totals
+    pub total: bool,
+    pub formats: Vec<FormatRule>,
+    // Float cells hold SI values shown in project units
+    pub measure: Option<Measure>,
+}
+
+impl ScheduleColumn {
+    pub fn new(field: ScheduleField) -> Self {
+        Self {
+            heading: field.label(),
+            field,
+            total: false,
+            formats: Vec::new(),
+            measure: None,
+        }
+    }
+
+    pub fn totaled(mut self) -> Self {
+        self.total = true;
+        self
+    }
+
+    pub fn measured(mut self, measure: Measure) -> Self {
+        self.measure = Some(measure);
+        self
+    }
+
+    pub fn format(&self, value: Option<&ParamValue>, units: &ProjectUnits) -> Option<[u8; 3]> {
+        self.formats
+            .iter()
+            .find(|rule| rule.matches(value, self.measure, units))
+            .map(|rule| rule.color)
+    }
+
+    // Cell as the table shows it
+    pub fn text(&self, value: Option<&ParamValue>, units: &ProjectUnits) -> String {
+        match (self.measure, value) {
+            (Some(measure), Some(ParamValue::Float(value))) => units.format(measure, *value),
+            (_, value) => value.map_or(String::new(), ToString::to_string),
+        }
+    }
+
+    // Typed text for a cell, None when it does not fit
+    pub fn parse(&self, text: &str, units: &ProjectUnits) -> Option<ParamValue> {
+        match (self.measure, self.field.value_type()) {
+            (Some(measure), ParamType::Float) => units.parse(measure, text).map(ParamValue::Float),
+            (_, param_type) => ParamValue::parse(param_type, text),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduleDefinition {
+    pub id: ScheduleId,
+    pub name: String,
+    // Empty shows every element
+    pub kinds: Vec<ElementKind>,
+    pub columns: Vec<ScheduleColumn>,
+    // Column index and ascending
+    pub sort: Vec<(usize, bool)>,
+    pub group_by: Option<usize>,
+    pub show_totals: bool,
+}
+
+impl ScheduleDefinition {
+    pub fn includes(&self, header: &ElementHeader) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&header.kind)
+    }
+
+    pub fn row(&self, entity: Entity, header: &ElementHeader) -> ScheduleRow {
+        ScheduleRow {
+            entity,
+            element: header.id,
+            cells: self
+                .columns
+                .iter()
+                .map(|column| column.field.read(header))
+                .collect(),
+        }
+    }
+
+    // Sorts and groups the rows. Groups come out in the order of their first row.
+    pub fn arrange<'a>(&self, rows: &'a [ScheduleRow]) -> Vec<ScheduleGroup<'a>> {
+        let mut sorted: Vec<&ScheduleRow> = rows.iter().collect();
+
+        sorted.sort_by(|a, b| {
+            let mut order = Ordering::Equal;
+            if let Some(group) = self.group_by {
+                order = compare_cells(a.cell(group), b.cell(group));
+            }
+            for &(column, ascending) in &self.sort {
+                if order != Ordering::Equal {
+                    break;
+                }
+                order = compare_cells(a.cell(column), b.cell(column));
+                if !ascending {
+                    order = order.reverse();
+                }
+            }
+            order.then(a.element.cmp(&b.element))
+        });
+
+        let mut groups: Vec<ScheduleGroup> = Vec::new();
+        for row in sorted {
+            let label = self
+                .group_by
+                .map(|group| row.cell(group).map_or("(none)".to_owned(), |value| value.to_string()));
+
+            match groups.last_mut() {
+                Some(group) if group.label == label => group.rows.push(row),
+                _ => groups.push(ScheduleGroup {
+                    label,
+                    rows: vec![row],
+                }),
+            }
+        }
+
+        groups
+    }
+
+    // Sums of the totaled columns, None for columns that are not totaled
+    pub fn totals<'a>(&self, rows: impl IntoIterator<Item = &'a ScheduleRow>) -> Vec<Option<f64>> {
+        let mut totals: Vec<Option<f64>> = self
+            .columns
+            .iter()
+            .map(|column| column.total.then_some(0.0))
+            .collect();
+
+        for row in rows {
+            for (total, cell) in totals.iter_mut().zip(&row.cells) {
+                if let (Some(total), Some(value)) = (total.as_mut(), cell.as_ref().and_then(ParamValue::as_f64)) {
+                    *total += value;
+                }
+            }
+        }
+
+        totals
+    }
+}
+
+fn compare_cells(a: Option<&ParamValue>, b: Option<&ParamValue>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        // Empty cells last
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            _ => a.to_string().to_lowercase().cmp(&b.to_string().to_lowercase()),
+        },
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduleRow {
+    pub entity: Entity,
+    pub element: ElementId,
+    pub cells: Vec<Option<ParamValue>>,
+}
+
+impl ScheduleRow {
+    pub fn cell(&self, column: usize) -> Option<&ParamValue> {
+        self.cells.get(column).and_then(Option::as_ref)
+    }
+}
+
+pub struct ScheduleGroup<'a> {
+    pub label: Option<String>,
+    pub rows: Vec<&'a ScheduleRow>,
+}
+
+#[derive(Resource, Debug)]
+pub struct Schedules {
+    pub definitions: Vec<ScheduleDefinition>,
+    pub active: Option<ScheduleId>,
+}
+
+impl Schedules {
+    pub fn get(&self, id: ScheduleId) -> Option<&ScheduleDefinition> {
+        self.definitions.iter().find(|definition| definition.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: ScheduleId) -> Option<&mut ScheduleDefinition> {
+        self.definitions.iter_mut().find(|definition| definition.id == id)
+    }
+
+    pub fn add(&mut self, name: &str, kinds: Vec<ElementKind>, columns: Vec<ScheduleColumn>) -> ScheduleId {
+        let id = ScheduleId(
+            self.definitions
+                .iter()
+                .map(|definition| definition.id.0 + 1)
+                .max()
+                .unwrap_or(1),
+        );
+        self.definitions.push(ScheduleDefinition {
+            id,
+            name: name.to_owned(),
+            kinds,
+            columns,
+            sort: vec![(0, true)],
+            group_by: None,
+            show_totals: true,
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: ScheduleId) {
+        self.definitions.retain(|definition| definition.id != id);
+        if self.active == Some(id) {
+            self.active = self.definitions.first().map(|definition| definition.id);
+        }
+    }
+}
+
+// Rows of every schedule and the params found in the model, kept up to date by the app.
+// Separate from the definitions so filling it does not look like a definition edit.
+#[derive(Resource, Default, Debug)]
+pub struct ScheduleTables {
+    pub rows: HashMap<ScheduleId, Vec<ScheduleRow>>,
+    pub model_params: BTreeMap<ParamKey, ParamType>,
+}
+
+impl Default for Schedules {
+    fn default() -> Self {
+        use ScheduleField as F;
+
+        let param = |key: &str, param_type| F::Param(ParamKey::new(key), param_type);
+
+        let mut schedules = Self {
+            definitions: Vec::new(),
+            active: None,
+        };
+
+        let doors = schedules.add(
+            "Door Schedule",
+            vec![ElementKind::Door],
+            vec![
+                ScheduleColumn::new(param("Mark", ParamType::Text)),
+                ScheduleColumn::new(F::Level),
+                ScheduleColumn::new(F::KindType),
+                ScheduleColumn::new(param("Width", ParamType::Float)).measured(Measure::Length),
+                ScheduleColumn::new(param("Height", ParamType::Float)).measured(Measure::Length),
+                ScheduleColumn::new(param("Fire Rating", ParamType::Text)),
+                ScheduleColumn::new(param(FROM_ROOM_PARAM, ParamType::Text)),
+                ScheduleColumn::new(param(TO_ROOM_PARAM, ParamType::Text)),
+                ScheduleColumn::new(F::Name),
+            ],
+        );
+
+        schedules.add(
+            "Window Schedule",
+            vec![ElementKind::Window],
+            vec![
+                ScheduleColumn::new(param("Mark", ParamType::Text)),
+                ScheduleColumn::new(F::Level),
+                ScheduleColumn::new(F::KindType),
+                ScheduleColumn::new(param("Width", ParamType::Float)).measured(Measure::Length),
+                ScheduleColumn::new(param("Height", ParamType::Float)).measured(Measure::Length),
+                ScheduleColumn::new(param("Sill Height", ParamType::Float)).measured(Measure::Length),
+            ],
+        );
+
+        schedules.add(
+            "Room Schedule",
+            vec![ElementKind::Space],
+            vec![
+                ScheduleColumn::new(param(NUMBER_PARAM, ParamType::Text)),
+                ScheduleColumn::new(F::Name),
+                ScheduleColumn::new(F::Level),
+                ScheduleColumn::new(param(AREA_PARAM, ParamType::Float)).measured(Measure::Area).totaled(),
+                ScheduleColumn::new(param(VOLUME_PARAM, ParamType::Float)).measured(Measure::Volume).totaled(),
+                ScheduleColumn::new(param("Finish Floor", ParamType::Text)),
+                ScheduleColumn::new(param("Finish Wall", ParamType::Text)),
+                ScheduleColumn::new(param("Finish Ceiling", ParamType::Text)),
+            ],
+        );
+
+        // Net and gross floor area of every level
+        let areas = schedules.add(
+            "Area Report",
+            vec![ElementKind::Space],
+            vec![
+                ScheduleColumn::new(F::Level),
+                ScheduleColumn::new(F::KindType),
+                ScheduleColumn::new(param(NUMBER_PARAM, ParamType::Text)),
+                ScheduleColumn::new(F::Name),
+                ScheduleColumn::new(param(AREA_PARAM, ParamType::Float)).measured(Measure::Area).totaled(),
+                ScheduleColumn::new(param(GROSS_AREA_PARAM, ParamType::Float)).measured(Measure::Area).totaled(),
+            ],
+        );
+        if let Some(areas) = schedules.get_mut(areas) {
+            areas.sort = vec![(2, true)];
+            areas.group_by = Some(0);
+        }
+
+        schedules.active = Some(doors);
+        schedules
+    }
+}
+
+#[derive(Message, Debug, Clone)]
+pub struct ScheduleEdit {
+    pub entity: Entity,
+    pub field: ScheduleField,
+    pub value: Option<ParamValue>,
+}
@@ -0,0 +1,303 @@
+// File: sequence.rs
+// Desc: 4D construction sequence. Tasks with dates linked to elements and the timeline playhead.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use strum_macros::{Display, EnumIter};
+
+use crate::display::ElementAppearance;
+use crate::element::ElementId;
+use crate::elements::element_kindtype_enums::TaskType;
+
+// Calendar day, counted in days since 1970-01-01
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date(pub i32);
+
+impl Date {
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Self {
+        // Howard Hinnant's days_from_civil
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let mp = (month as i32 + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + day as i32 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        Self(era * 146_097 + doe - 719_468)
+    }
+
+    pub fn ymd(&self) -> (i32, u32, u32) {
+        // Howard Hinnant's civil_from_days
+        let z = self.0 + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = yoe + era * 400 + i32::from(month <= 2);
+        (year, month, day)
+    }
+
+    pub fn today() -> Self {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self((seconds / 86_400) as i32)
+    }
+
+    // Accepts YYYY-MM-DD
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        let date = Self::from_ymd(year, month, day);
+        // Rejects 2025-02-30 and friends
+        (date.ymd() == (year, month, day)).then_some(date)
+    }
+
+    pub fn add_days(&self, days: i32) -> Self {
+        Self(self.0 + days)
+    }
+
+    pub fn days_until(&self, other: Date) -> i32 {
+        other.0 - self.0
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.ymd();
+        write!(f, "{year:04}-{month:02}-{day:02}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TaskId(pub u32);
+
+// What a task does to the elements linked to it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskEffect {
+    Build,
+    Remove,
+    // Inspections, maintenance, ... leave the element as it is
+    None,
+}
+
+pub fn task_effect(task_type: TaskType) -> TaskEffect {
+    match task_type {
+        TaskType::CONSTRUCTION | TaskType::INSTALLATION => TaskEffect::Build,
+        TaskType::DEMOLITION | TaskType::DISMANTLE | TaskType::DISPOSAL | TaskType::REMOVAL => {
+            TaskEffect::Remove
+        }
+        _ => TaskEffect::None,
+    }
+}
+
+// Task types offered in the timeline pane
+pub const SEQUENCE_TASK_TYPES: [TaskType; 9] = [
+    TaskType::CONSTRUCTION,
+    TaskType::INSTALLATION,
+    TaskType::DEMOLITION,
+    TaskType::DISMANTLE,
+    TaskType::REMOVAL,
+    TaskType::RENOVATION,
+    TaskType::LOGISTIC,
+    TaskType::INSPECTION,
+    TaskType::TESTING,
+];
+
+#[derive(Clone, Debug)]
+pub struct ConstructionTask {
+    pub id: TaskId,
+    pub name: String,
+    pub task_type: TaskType,
+    // Both days inclusive
+    pub start: Date,
+    pub end: Date,
+    pub elements: Vec<ElementId>,
+}
+
+impl ConstructionTask {
+    pub fn duration_days(&self) -> i32 {
+        self.start.days_until(self.end) + 1
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstructionState {
+    // Not linked to any task that builds or removes it
+    Existing,
+    Planned,
+    #[strum(to_string = "Under Construction")]
+    UnderConstruction,
+    Built,
+    #[strum(to_string = "Being Removed")]
+    BeingRemoved,
+    Removed,
+}
+
+impl ConstructionState {
+    // State of an element on the given day from all tasks linked to it
+    pub fn at<'a>(tasks: impl IntoIterator<Item = &'a ConstructionTask>, date: Date) -> Self {
+        let mut build: Option<(Date, Date)> = None;
+        let mut remove: Option<(Date, Date)> = None;
+
+        for task in tasks {
+            let span = match task_effect(task.task_type) {
+                TaskEffect::Build => &mut build,
+                TaskEffect::Remove => &mut remove,
+                TaskEffect::None => continue,
+            };
+            *span = Some(match *span {
+                Some((start, end)) => (start.min(task.start), end.max(task.end)),
+                None => (task.start, task.end),
+            });
+        }
+
+        if let Some((start, end)) = remove {
+            if date > end {
+                return ConstructionState::Removed;
+            }
+            if date >= start {
+                return ConstructionState::BeingRemoved;
+            }
+        }
+
+        match build {
+            Some((start, _)) if date < start => ConstructionState::Planned,
+            Some((_, end)) if date <= end => ConstructionState::UnderConstruction,
+            Some(_) => ConstructionState::Built,
+            None => ConstructionState::Existing,
+        }
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TimelineDisplay {
+    // Only what stands on the day is shown
+    #[default]
+    #[strum(to_string = "Filter")]
+    Filter,
+    // Future and removed work stays visible as a ghost
+    #[strum(to_string = "Ghost")]
+    Ghost,
+    // Work in progress is colored by what is happening to it
+    #[strum(to_string = "Color")]
+    Color,
+}
+
+impl TimelineDisplay {
+    pub fn appearance(&self, state: ConstructionState) -> ElementAppearance {
+        use ConstructionState as S;
+
+        match (self, state) {
+            (_, S::Existing | S::Built) => ElementAppearance::Normal,
+            (TimelineDisplay::Filter, S::Planned | S::Removed) => ElementAppearance::Hidden,
+            (TimelineDisplay::Filter, S::UnderConstruction | S::BeingRemoved) => {
+                ElementAppearance::Normal
+            }
+            (TimelineDisplay::Ghost, S::Planned | S::Removed) => ElementAppearance::Ghost,
+            (TimelineDisplay::Ghost, S::UnderConstruction | S::BeingRemoved) => {
+                ElementAppearance::Normal
+            }
+            (TimelineDisplay::Color, S::Planned) => ElementAppearance::Ghost,
+            (TimelineDisplay::Color, S::Removed) => ElementAppearance::Hidden,
+            (TimelineDisplay::Color, S::UnderConstruction) => {
+                ElementAppearance::Tint(Color::srgb(0.3, 0.8, 0.35))
+            }
+            (TimelineDisplay::Color, S::BeingRemoved) => {
+                ElementAppearance::Tint(Color::srgb(0.9, 0.25, 0.2))
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct ConstructionSequence {
+    pub tasks: Vec<ConstructionTask>,
+}
+
+impl ConstructionSequence {
+    pub fn get(&self, id: TaskId) -> Option<&ConstructionTask> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: TaskId) -> Option<&mut ConstructionTask> {
+        self.tasks.iter_mut().find(|task| task.id == id)
+    }
+
+    pub fn add(&mut self, name: &str, task_type: TaskType, start: Date, end: Date) -> TaskId {
+        let id = TaskId(self.tasks.iter().map(|task| task.id.0 + 1).max().unwrap_or(1));
+        self.tasks.push(ConstructionTask {
+            id,
+            name: name.to_owned(),
+            task_type,
+            start,
+            end: end.max(start),
+            elements: Vec::new(),
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: TaskId) {
+        self.tasks.retain(|task| task.id != id);
+    }
+
+    // First and last day of the whole sequence
+    pub fn span(&self) -> Option<(Date, Date)> {
+        let start = self.tasks.iter().map(|task| task.start).min()?;
+        let end = self.tasks.iter().map(|task| task.end).max()?;
+        Some((start, end))
+    }
+
+    pub fn tasks_by_element(&self) -> HashMap<ElementId, Vec<&ConstructionTask>> {
+        let mut map: HashMap<ElementId, Vec<&ConstructionTask>> = HashMap::new();
+        for task in &self.tasks {
+            for id in &task.elements {
+                map.entry(*id).or_default().push(task);
+            }
+        }
+        map
+    }
+}
+
+// Playback state of the timeline pane
+#[derive(Resource, Debug)]
+pub struct Timeline {
+    // When off the model is shown as is
+    pub enabled: bool,
+    pub playhead: Date,
+    pub display: TimelineDisplay,
+    pub playing: bool,
+    pub days_per_second: f32,
+    pub selected_task: Option<TaskId>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            playhead: Date::today(),
+            display: TimelineDisplay::Filter,
+            playing: false,
+            days_per_second: 7.0,
+            selected_task: None,
+        }
+    }
+}
+
+#[derive(Message, Debug, Clone, Copy)]
+pub enum TimelineCommand {
+    // Link the selected element to the task
+    LinkSelection(TaskId),
+    UnlinkSelection(TaskId),
+}
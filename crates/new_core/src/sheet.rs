@@ -0,0 +1,525 @@
+// File: sheet.rs
+// Desc: Drawing sheets. Views placed at a scale on paper with a title block and revision table.
+//       compose() lays a sheet out in millimeters, the pane preview and the PDF both draw from it.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use strum_macros::{Display, EnumIter};
+
+use crate::drawing::{Annotation, Compass, Drawing, DrawingView, LineWeight, ViewKind};
+use crate::phase::ViewPhase;
+use crate::sequence::Date;
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PaperSize {
+    #[strum(to_string = "A0")]
+    A0,
+    #[strum(to_string = "A1")]
+    A1,
+    #[strum(to_string = "A2")]
+    A2,
+    #[default]
+    #[strum(to_string = "A3")]
+    A3,
+    #[strum(to_string = "A4")]
+    A4,
+    #[strum(to_string = "Letter")]
+    Letter,
+    #[strum(to_string = "Tabloid")]
+    Tabloid,
+    #[strum(to_string = "ARCH D")]
+    ArchD,
+}
+
+impl PaperSize {
+    // Long side first, millimeters
+    pub fn size_mm(&self) -> Vec2 {
+        match self {
+            PaperSize::A0 => Vec2::new(1189.0, 841.0),
+            PaperSize::A1 => Vec2::new(841.0, 594.0),
+            PaperSize::A2 => Vec2::new(594.0, 420.0),
+            PaperSize::A3 => Vec2::new(420.0, 297.0),
+            PaperSize::A4 => Vec2::new(297.0, 210.0),
+            PaperSize::Letter => Vec2::new(279.4, 215.9),
+            PaperSize::Tabloid => Vec2::new(431.8, 279.4),
+            PaperSize::ArchD => Vec2::new(914.4, 609.6),
+        }
+    }
+}
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Orientation {
+    #[default]
+    Landscape,
+    Portrait,
+}
+
+// Denominators offered for view scales, 1:N
+pub const DRAWING_SCALES: [u32; 10] = [1, 5, 10, 20, 25, 50, 100, 200, 500, 1000];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SheetId(pub u32);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewPlacement {
+    pub view: DrawingView,
+    // 1:scale
+    pub scale: u32,
+    // Where the middle of the drawing goes, millimeters from the bottom left of the paper
+    pub center: Vec2,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SheetNote {
+    pub text: String,
+    pub position: Vec2,
+    // Cap height in millimeters
+    pub height: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Revision {
+    pub number: u32,
+    pub date: Date,
+    pub description: String,
+    pub by: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sheet {
+    pub id: SheetId,
+    pub number: String,
+    pub name: String,
+    pub paper: PaperSize,
+    pub orientation: Orientation,
+    pub status: String,
+    pub placements: Vec<ViewPlacement>,
+    pub notes: Vec<SheetNote>,
+    pub revisions: Vec<Revision>,
+}
+
+impl Sheet {
+    pub fn paper_mm(&self) -> Vec2 {
+        let size = self.paper.size_mm();
+        match self.orientation {
+            Orientation::Landscape => size,
+            Orientation::Portrait => Vec2::new(size.y, size.x),
+        }
+    }
+
+    pub fn scale_label(&self) -> String {
+        let mut scales: Vec<u32> = self.placements.iter().map(|placement| placement.scale).collect();
+        scales.sort_unstable();
+        scales.dedup();
+        match scales.as_slice() {
+            [] => "-".to_owned(),
+            [scale] => format!("1:{scale}"),
+            _ => "As indicated".to_owned(),
+        }
+    }
+
+    pub fn add_revision(&mut self, description: &str, by: &str) {
+        let number = self.revisions.iter().map(|revision| revision.number + 1).max().unwrap_or(1);
+        self.revisions.push(Revision {
+            number,
+            date: Date::today(),
+            description: description.to_owned(),
+            by: by.to_owned(),
+        });
+    }
+}
+
+// Every sheet of the project and the title block fields they share
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct SheetSet {
+    pub project: String,
+    pub client: String,
+    pub drawn_by: String,
+    pub checked_by: String,
+    pub sheets: Vec<Sheet>,
+    pub active: Option<SheetId>,
+}
+
+impl SheetSet {
+    pub fn get(&self, id: SheetId) -> Option<&Sheet> {
+        self.sheets.iter().find(|sheet| sheet.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: SheetId) -> Option<&mut Sheet> {
+        self.sheets.iter_mut().find(|sheet| sheet.id == id)
+    }
+
+    pub fn add(&mut self, number: &str, name: &str) -> SheetId {
+        let id = SheetId(self.sheets.iter().map(|sheet| sheet.id.0 + 1).max().unwrap_or(1));
+        self.sheets.push(Sheet {
+            id,
+            number: number.to_owned(),
+            name: name.to_owned(),
+            paper: PaperSize::default(),
+            orientation: Orientation::default(),
+            status: "Preliminary".to_owned(),
+            placements: Vec::new(),
+            notes: Vec::new(),
+            revisions: Vec::new(),
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: SheetId) {
+        self.sheets.retain(|sheet| sheet.id != id);
+        if self.active == Some(id) {
+            self.active = self.sheets.first().map(|sheet| sheet.id);
+        }
+    }
+}
+
+impl Default for SheetSet {
+    fn default() -> Self {
+        let mut set = Self {
+            project: "Monolith Project".to_owned(),
+            client: String::new(),
+            drawn_by: String::new(),
+            checked_by: String::new(),
+            sheets: Vec::new(),
+            active: None,
+        };
+
+        let view = |name: &str, kind| DrawingView {
+            name: name.to_owned(),
+            kind,
+            phase: ViewPhase::default(),
+        };
+
+        let plans = set.add("A-101", "Plans");
+        if let Some(sheet) = set.get_mut(plans) {
+            sheet.placements.push(ViewPlacement {
+                view: view("Ground Floor", ViewKind::Plan { cut_height: 1.2 }),
+                scale: 50,
+                center: Vec2::new(150.0, 170.0),
+            });
+        }
+
+        let elevations = set.add("A-201", "Elevations and Sections");
+        if let Some(sheet) = set.get_mut(elevations) {
+            sheet.placements.push(ViewPlacement {
+                view: view("North Elevation", ViewKind::Elevation { side: Compass::North }),
+                scale: 50,
+                center: Vec2::new(110.0, 200.0),
+            });
+            sheet.placements.push(ViewPlacement {
+                view: view(
+                    "Section A",
+                    ViewKind::Section {
+                        facing: Compass::East,
+                        offset: 0.0,
+                    },
+                ),
+                scale: 50,
+                center: Vec2::new(110.0, 90.0),
+            });
+        }
+
+        set.active = Some(plans);
+        set
+    }
+}
+
+// Linework of every placed view, in placement order, kept up to date by the app
+#[derive(Resource, Default, Debug)]
+pub struct SheetDrawings {
+    pub drawings: HashMap<SheetId, Vec<Drawing>>,
+}
+
+// Publish and sheet set paths and the outcome of the last command
+#[derive(Resource, Default, Debug)]
+pub struct SheetPublish {
+    pub path: String,
+    pub set_path: String,
+    pub status: Option<String>,
+}
+
+#[derive(Message, Debug, Clone)]
+pub enum SheetCommand {
+    // One page per sheet
+    Publish { sheets: Vec<SheetId>, path: PathBuf },
+    // The sheet set as a file next to the model, what headless publishing reads
+    Save { path: PathBuf },
+    Open { path: PathBuf },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SheetLine {
+    pub a: Vec2,
+    pub b: Vec2,
+    pub weight: LineWeight,
+    // 0 is black
+    pub gray: f32,
+    pub dashed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SheetLabel {
+    // Left end of the baseline
+    pub position: Vec2,
+    pub height: f32,
+    pub text: String,
+}
+
+// A sheet laid out on paper, millimeters with the origin at the bottom left
+#[derive(Clone, Debug, Default)]
+pub struct SheetGraphics {
+    pub size: Vec2,
+    pub lines: Vec<SheetLine>,
+    pub labels: Vec<SheetLabel>,
+}
+
+const HALFTONE_GRAY: f32 = 0.55;
+const TITLE_BLOCK_WIDTH: f32 = 170.0;
+const TITLE_ROW: f32 = 9.0;
+const REVISION_ROW: f32 = 5.0;
+// Grid bubbles and dimensions, millimeters on paper
+const BUBBLE_DIAMETER: f32 = 8.0;
+const BUBBLE_GAP: f32 = 12.0;
+const BUBBLE_SEGMENTS: usize = 24;
+const DIMENSION_OFFSET: f32 = 5.0;
+const DIMENSION_TICK: f32 = 1.5;
+const ANNOTATION_TEXT: f32 = 2.5;
+// Rough advance of a character for its cap height, enough to center short labels
+const CHARACTER_WIDTH: f32 = 0.6;
+
+impl SheetGraphics {
+    fn line(&mut self, a: Vec2, b: Vec2, weight: LineWeight) {
+        self.lines.push(SheetLine {
+            a,
+            b,
+            weight,
+            gray: 0.0,
+            dashed: false,
+        });
+    }
+
+    fn rect(&mut self, min: Vec2, max: Vec2, weight: LineWeight) {
+        let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+        for i in 0..4 {
+            self.line(corners[i], corners[(i + 1) % 4], weight);
+        }
+    }
+
+    fn label(&mut self, position: Vec2, height: f32, text: impl Into<String>) {
+        self.labels.push(SheetLabel {
+            position,
+            height,
+            text: text.into(),
+        });
+    }
+
+    fn centered_label(&mut self, center: Vec2, height: f32, text: &str) {
+        let width = text.chars().count() as f32 * height * CHARACTER_WIDTH;
+        self.label(center - Vec2::new(width, height) / 2.0, height, text);
+    }
+
+    fn circle(&mut self, center: Vec2, radius: f32, weight: LineWeight) {
+        let point = |index: usize| {
+            let angle = index as f32 / BUBBLE_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + Vec2::from_angle(angle) * radius
+        };
+        for index in 0..BUBBLE_SEGMENTS {
+            self.line(point(index), point(index + 1), weight);
+        }
+    }
+}
+
+// drawings holds the linework of each placement, missing ones are left blank
+pub fn compose(set: &SheetSet, sheet: &Sheet, drawings: &[Drawing]) -> SheetGraphics {
+    let size = sheet.paper_mm();
+    let mut graphics = SheetGraphics {
+        size,
+        ..default()
+    };
+
+    // ISO 5457 frame, wider margin on the binding edge
+    let frame_min = Vec2::new(20.0, 10.0);
+    let frame_max = size - Vec2::splat(10.0);
+    graphics.rect(frame_min, frame_max, LineWeight::Heavy);
+
+    for (index, placement) in sheet.placements.iter().enumerate() {
+        if let Some(drawing) = drawings.get(index) {
+            place_drawing(&mut graphics, placement, drawing);
+        }
+        view_title(&mut graphics, index, placement, drawings.get(index));
+    }
+
+    for note in &sheet.notes {
+        for (line, text) in note.text.lines().enumerate() {
+            let position = note.position - Vec2::new(0.0, line as f32 * note.height * 1.6);
+            graphics.label(position, note.height, text);
+        }
+    }
+
+    let title_top = title_block(&mut graphics, set, sheet, Vec2::new(frame_max.x, frame_min.y));
+    revision_table(&mut graphics, sheet, Vec2::new(frame_max.x, title_top));
+
+    graphics
+}
+
+fn place_drawing(graphics: &mut SheetGraphics, placement: &ViewPlacement, drawing: &Drawing) {
+    let Some(bounds) = drawing.bounds() else {
+        return;
+    };
+
+    // Meters in the model to millimeters on paper
+    let factor = 1000.0 / placement.scale.max(1) as f32;
+    let to_paper = |point: Vec2| placement.center + (point - bounds.center()) * factor;
+
+    for polyline in &drawing.polylines {
+        let style = polyline.style;
+        for (a, b) in polyline.segments() {
+            graphics.lines.push(SheetLine {
+                a: to_paper(a),
+                b: to_paper(b),
+                weight: style.kind.weight(),
+                gray: if style.halftone { HALFTONE_GRAY } else { 0.0 },
+                dashed: style.dashed,
+            });
+        }
+    }
+
+    for annotation in &drawing.annotations {
+        annotate(graphics, annotation, to_paper);
+    }
+}
+
+// Annotations keep their paper size, only where they sit follows the scale
+fn annotate(graphics: &mut SheetGraphics, annotation: &Annotation, to_paper: impl Fn(Vec2) -> Vec2) {
+    match annotation {
+        Annotation::Bubble { end, outward, tag } => {
+            let outward = outward.normalize_or_zero();
+            let end = to_paper(*end);
+            let center = end + outward * (BUBBLE_GAP + BUBBLE_DIAMETER / 2.0);
+            graphics.line(end, center - outward * BUBBLE_DIAMETER / 2.0, LineWeight::ExtraFine);
+            graphics.circle(center, BUBBLE_DIAMETER / 2.0, LineWeight::Thin);
+            graphics.centered_label(center, BUBBLE_DIAMETER * 0.45, tag);
+        }
+        Annotation::Dimension { a, b, outward, text } => {
+            let outward = outward.normalize_or_zero();
+            let (a, b) = (to_paper(*a), to_paper(*b));
+            let (from, to) = (a + outward * DIMENSION_OFFSET, b + outward * DIMENSION_OFFSET);
+            // Extension lines run from the points a little past the dimension line
+            graphics.line(a, from + outward * DIMENSION_TICK, LineWeight::ExtraFine);
+            graphics.line(b, to + outward * DIMENSION_TICK, LineWeight::ExtraFine);
+            graphics.line(from, to, LineWeight::ExtraFine);
+
+            // Architectural ticks, slanted across each end
+            let along = (to - from).normalize_or_zero();
+            let tick = (along + along.perp()).normalize_or_zero() * DIMENSION_TICK;
+            for point in [from, to] {
+                graphics.line(point - tick, point + tick, LineWeight::Thin);
+            }
+
+            let middle = (from + to) / 2.0 + outward * (ANNOTATION_TEXT * 0.5 + 1.0);
+            graphics.centered_label(middle, ANNOTATION_TEXT, text);
+        }
+    }
+}
+
+fn view_title(graphics: &mut SheetGraphics, index: usize, placement: &ViewPlacement, drawing: Option<&Drawing>) {
+    let factor = 1000.0 / placement.scale.max(1) as f32;
+    let half = drawing
+        .and_then(Drawing::bounds)
+        .map_or(Vec2::splat(10.0), |bounds| bounds.half_size() * factor);
+
+    let left = placement.center.x - half.x;
+    let y = placement.center.y - half.y - 8.0;
+
+    graphics.line(Vec2::new(left, y), Vec2::new(left + 70.0, y), LineWeight::Medium);
+    graphics.label(
+        Vec2::new(left, y + 1.5),
+        3.5,
+        format!("{}  {}", index + 1, placement.view.name),
+    );
+    graphics.label(
+        Vec2::new(left, y - 4.0),
+        2.5,
+        format!("{}  1:{}", placement.view.kind.label(), placement.scale),
+    );
+}
+
+// Returns the top edge of the block
+fn title_block(graphics: &mut SheetGraphics, set: &SheetSet, sheet: &Sheet, bottom_right: Vec2) -> f32 {
+    let left = bottom_right.x - TITLE_BLOCK_WIDTH;
+    let date = sheet
+        .revisions
+        .iter()
+        .max_by_key(|revision| revision.number)
+        .map_or(Date::today(), |revision| revision.date);
+
+    // Bottom up, each row split into captioned cells of the given width share
+    let rows: [&[(&str, String, f32)]; 5] = [
+        &[
+            ("Sheet", sheet.number.clone(), 0.5),
+            ("Scale", sheet.scale_label(), 0.25),
+            ("Status", sheet.status.clone(), 0.25),
+        ],
+        &[
+            ("Drawn", set.drawn_by.clone(), 0.25),
+            ("Checked", set.checked_by.clone(), 0.25),
+            ("Date", date.to_string(), 0.25),
+            ("Paper", format!("{} {}", sheet.paper, sheet.orientation), 0.25),
+        ],
+        &[("Title", sheet.name.clone(), 1.0)],
+        &[("Client", set.client.clone(), 1.0)],
+        &[("Project", set.project.clone(), 1.0)],
+    ];
+
+    let mut y = bottom_right.y;
+    for (row_index, cells) in rows.iter().enumerate() {
+        // The sheet number row is taller
+        let height = if row_index == 0 { TITLE_ROW * 1.5 } else { TITLE_ROW };
+        let mut x = left;
+        for (caption, value, share) in cells.iter() {
+            let width = TITLE_BLOCK_WIDTH * share;
+            graphics.rect(Vec2::new(x, y), Vec2::new(x + width, y + height), LineWeight::Thin);
+            graphics.label(Vec2::new(x + 1.5, y + height - 2.8), 1.8, *caption);
+            let value_height = if row_index == 0 && *caption == "Sheet" { 6.0 } else { 3.0 };
+            graphics.label(Vec2::new(x + 1.5, y + 1.8), value_height, value.clone());
+            x += width;
+        }
+        y += height;
+    }
+
+    graphics.rect(Vec2::new(left, bottom_right.y), Vec2::new(bottom_right.x, y), LineWeight::Medium);
+    y
+}
+
+fn revision_table(graphics: &mut SheetGraphics, sheet: &Sheet, bottom_right: Vec2) {
+    let left = bottom_right.x - TITLE_BLOCK_WIDTH;
+    let columns = [("Rev", 15.0), ("Date", 25.0), ("Description", TITLE_BLOCK_WIDTH - 60.0), ("By", 20.0)];
+
+    let mut revisions: Vec<&Revision> = sheet.revisions.iter().collect();
+    revisions.sort_by_key(|revision| revision.number);
+
+    // Oldest revision sits on the title block, the heading row on top
+    let mut y = bottom_right.y;
+    let rows = revisions
+        .iter()
+        .map(|revision| {
+            [
+                revision.number.to_string(),
+                revision.date.to_string(),
+                revision.description.clone(),
+                revision.by.clone(),
+            ]
+        })
+        .chain(std::iter::once(columns.map(|(heading, _)| heading.to_owned())));
+
+    for cells in rows {
+        let mut x = left;
+        for ((_, width), text) in columns.iter().zip(cells) {
+            graphics.rect(Vec2::new(x, y), Vec2::new(x + width, y + REVISION_ROW), LineWeight::Thin);
+            graphics.label(Vec2::new(x + 1.2, y + 1.4), 2.0, text);
+            x += width;
+        }
+        y += REVISION_ROW;
+    }
+}
@@ -0,0 +1,288 @@
+// File: structural_grid.rs
+// Desc: Structural grids, IfcGrid. Named axes in plan that everything on site is set out
+//       from, spanning a range of heights. Lettered axes run one way and numbered ones the
+//       other, and elements can stand on an intersection and move with it.
+
+use bevy::prelude::*;
+use std::fmt;
+use strum_macros::{Display, EnumIter};
+
+use crate::element::ElementId;
+use crate::elements::ElementKind;
+use crate::tool::ToolId;
+
+// Draws one axis from two picks, named after the ones before it
+pub const GRID_LINE_TOOL: ToolId = ToolId("structure.grid_line");
+
+// Letters that read like digits are left out, as on most drawings
+const AXIS_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+// Axes closer to parallel than this are dimensioned against each other, sine of the angle
+const PARALLEL_TOLERANCE: f32 = 1.0e-3;
+// Intersections this far past the end of an axis still count, metres
+const END_TOLERANCE: f32 = 1.0e-3;
+
+// The two sets of axes of a grid, IfcGrid's UAxes and VAxes
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum AxisFamily {
+    #[default]
+    Letters,
+    Numbers,
+}
+
+impl AxisFamily {
+    // Tag of the nth axis of the family, A to Z then AA, AB and on, or 1, 2, 3
+    pub fn tag(self, index: usize) -> String {
+        match self {
+            AxisFamily::Numbers => (index + 1).to_string(),
+            AxisFamily::Letters => {
+                let count = AXIS_LETTERS.len();
+                let mut tag = Vec::new();
+                let mut rest = index + 1;
+                while rest > 0 {
+                    rest -= 1;
+                    tag.push(AXIS_LETTERS[rest % count]);
+                    rest /= count;
+                }
+                tag.reverse();
+                String::from_utf8(tag).unwrap_or_default()
+            }
+        }
+    }
+
+    // Numbered axes run north to south on the plan and lettered ones east to west
+    pub fn along(direction: Vec2) -> Self {
+        if direction.y.abs() > direction.x.abs() {
+            AxisFamily::Numbers
+        } else {
+            AxisFamily::Letters
+        }
+    }
+}
+
+// One named line of the grid, plan coordinates in metres with north up
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridAxis {
+    pub tag: String,
+    pub family: AxisFamily,
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+impl GridAxis {
+    pub fn direction(&self) -> Option<Vec2> {
+        (self.end - self.start).try_normalize()
+    }
+
+    // Where the lines through both axes meet, None for parallel axes
+    pub fn meets(&self, other: &GridAxis) -> Option<Vec2> {
+        let (d1, d2) = (self.end - self.start, other.end - other.start);
+        let denominator = d1.perp_dot(d2);
+        if denominator.abs() <= PARALLEL_TOLERANCE * d1.length() * d2.length() {
+            return None;
+        }
+        let t = (other.start - self.start).perp_dot(d2) / denominator;
+        Some(self.start + d1 * t)
+    }
+
+    // Distance from the line through the axis, signed by the side
+    pub fn offset(&self, point: Vec2) -> f32 {
+        self.direction().map_or(0.0, |direction| direction.perp_dot(point - self.start))
+    }
+
+    fn reaches(&self, point: Vec2) -> bool {
+        let along = self.end - self.start;
+        let t = (point - self.start).dot(along) / along.length_squared().max(f32::EPSILON);
+        let slack = END_TOLERANCE / along.length().max(f32::EPSILON);
+        (-slack..=1.0 + slack).contains(&t)
+    }
+}
+
+// Plan point to model space, north is -Z
+pub fn plan_to_world(point: Vec2, elevation: f32) -> Vec3 {
+    Vec3::new(point.x, elevation, -point.y)
+}
+
+pub fn world_to_plan(point: Vec3) -> Vec2 {
+    Vec2::new(point.x, -point.z)
+}
+
+// Crossing of two axes, written like A/1
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GridPoint {
+    pub first: String,
+    pub second: String,
+}
+
+impl GridPoint {
+    pub fn new(first: &str, second: &str) -> Self {
+        Self {
+            first: first.to_owned(),
+            second: second.to_owned(),
+        }
+    }
+
+    // Reads what Display writes, spaces around the slash allowed
+    pub fn parse(text: &str) -> Option<Self> {
+        let (first, second) = text.split_once('/')?;
+        let (first, second) = (first.trim(), second.trim());
+        if first.is_empty() || second.is_empty() {
+            return None;
+        }
+        Some(Self::new(first, second))
+    }
+}
+
+impl fmt::Display for GridPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.first, self.second)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridIntersection {
+    pub at: GridPoint,
+    pub point: Vec2,
+}
+
+// Spacing of two neighbouring parallel axes
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridDimension {
+    pub from: String,
+    pub to: String,
+    pub distance: f32,
+}
+
+// Axes live on the grid element, the element header names it and holds its GridType
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct StructuralGrid {
+    pub axes: Vec<GridAxis>,
+    // Heights the axes span, from below the lowest level to above the highest
+    pub bottom: f32,
+    pub top: f32,
+}
+
+impl Default for StructuralGrid {
+    fn default() -> Self {
+        Self {
+            axes: Vec::new(),
+            bottom: -1.0,
+            top: 10.0,
+        }
+    }
+}
+
+impl StructuralGrid {
+    pub fn axis(&self, tag: &str) -> Option<&GridAxis> {
+        self.axes.iter().find(|axis| axis.tag == tag)
+    }
+
+    pub fn family(&self, family: AxisFamily) -> impl Iterator<Item = &GridAxis> {
+        self.axes.iter().filter(move |axis| axis.family == family)
+    }
+
+    // First tag of the family nobody has yet
+    pub fn next_tag(&self, family: AxisFamily) -> String {
+        (0..)
+            .map(|index| family.tag(index))
+            .find(|tag| self.axis(tag).is_none())
+            .unwrap_or_default()
+    }
+
+    // Named after the axes before it, in the family its direction suggests unless one is given
+    pub fn add_axis(&mut self, start: Vec2, end: Vec2, family: Option<AxisFamily>) -> Option<&GridAxis> {
+        let family = family.unwrap_or_else(|| AxisFamily::along(end - start));
+        let axis = GridAxis {
+            tag: self.next_tag(family),
+            family,
+            start,
+            end,
+        };
+        axis.direction()?;
+        self.axes.push(axis);
+        self.axes.last()
+    }
+
+    // Where the two named axes cross, even past their ends
+    pub fn point(&self, at: &GridPoint) -> Option<Vec2> {
+        self.axis(&at.first)?.meets(self.axis(&at.second)?)
+    }
+
+    // Crossings of lettered with numbered axes within both their lengths
+    pub fn intersections(&self) -> Vec<GridIntersection> {
+        let mut intersections = Vec::new();
+        for first in self.family(AxisFamily::Letters) {
+            for second in self.family(AxisFamily::Numbers) {
+                if let Some(point) = first.meets(second)
+                    && first.reaches(point)
+                    && second.reaches(point)
+                {
+                    intersections.push(GridIntersection {
+                        at: GridPoint::new(&first.tag, &second.tag),
+                        point,
+                    });
+                }
+            }
+        }
+        intersections
+    }
+
+    // Intersection within radius of the plan point
+    pub fn snap(&self, point: Vec2, radius: f32) -> Option<GridIntersection> {
+        self.intersections()
+            .into_iter()
+            .map(|intersection| (intersection.point.distance_squared(point), intersection))
+            .filter(|(distance, _)| *distance <= radius * radius)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, intersection)| intersection)
+    }
+
+    // Neighbouring parallel axes of each family, in order across them. Worked out from the axes
+    // every time, so they follow any axis that moves.
+    pub fn dimensions(&self) -> Vec<GridDimension> {
+        let mut dimensions = Vec::new();
+        for family in [AxisFamily::Letters, AxisFamily::Numbers] {
+            let axes: Vec<&GridAxis> = self.family(family).collect();
+            let Some(reference) = axes.first() else {
+                continue;
+            };
+
+            let mut parallel: Vec<(f32, &GridAxis)> = axes
+                .iter()
+                .filter(|axis| reference.meets(axis).is_none())
+                .map(|axis| (reference.offset(axis.start), *axis))
+                .collect();
+            parallel.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            dimensions.extend(parallel.windows(2).map(|pair| GridDimension {
+                from: pair[0].1.tag.clone(),
+                to: pair[1].1.tag.clone(),
+                distance: (pair[1].0 - pair[0].0).abs(),
+            }));
+        }
+        dimensions
+    }
+}
+
+// Element set out from a grid intersection, it moves when the grid does. Elements running
+// between two intersections, like walls, also turn to face the second one.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct GridReference {
+    pub grid: ElementId,
+    pub at: GridPoint,
+    pub towards: Option<GridPoint>,
+}
+
+impl GridReference {
+    pub fn can_reference(kind: ElementKind) -> bool {
+        matches!(
+            kind,
+            ElementKind::Column
+                | ElementKind::Wall
+                | ElementKind::WallStandardCase
+                | ElementKind::Beam
+                | ElementKind::Member
+                | ElementKind::Footing
+                | ElementKind::Pile
+        )
+    }
+}
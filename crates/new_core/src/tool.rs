@@ -0,0 +1,195 @@
+// File: tool.rs
+// Desc: Modeling tools of the new stack. Tools register here from their plugins, the toolbar
+//       is drawn from the registry and talks back through ToolCommand. Each tool is also an
+//       action, so its shortcut and palette entry come from the same definition. Picks are
+//       kept per viewport so a pick started in one pane never continues in another.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::action::ActionId;
+use crate::units::Measure;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ToolId(pub &'static str);
+
+impl ToolId {
+    pub fn action(self) -> ActionId {
+        ActionId(self.0)
+    }
+}
+
+// Actions of the framework itself, for whichever tool is running
+pub const EXIT_TOOL: ActionId = ActionId("tools.exit");
+pub const CANCEL_TOOL: ActionId = ActionId("tools.cancel");
+pub const CONFIRM_TOOL: ActionId = ActionId("tools.confirm");
+
+impl fmt::Display for ToolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ToolDef {
+    pub id: ToolId,
+    pub label: &'static str,
+    pub tooltip: &'static str,
+    // Toolbar section the button goes in
+    pub group: &'static str,
+    // Default key of its action
+    pub shortcut: Option<KeyCode>,
+}
+
+// Every tool in toolbar order
+#[derive(Resource, Default)]
+pub struct ToolRegistry {
+    pub tools: Vec<ToolDef>,
+}
+
+impl ToolRegistry {
+    // A tool registered twice replaces the first one in place
+    pub fn register(&mut self, def: ToolDef) {
+        match self.tools.iter_mut().find(|tool| tool.id == def.id) {
+            Some(tool) => *tool = def,
+            None => self.tools.push(def),
+        }
+    }
+
+    pub fn get(&self, id: ToolId) -> Option<&ToolDef> {
+        self.tools.iter().find(|tool| tool.id == id)
+    }
+
+    // Groups in the order their first tool was registered
+    pub fn groups(&self) -> Vec<&'static str> {
+        let mut groups = Vec::new();
+        for tool in &self.tools {
+            if !groups.contains(&tool.group) {
+                groups.push(tool.group);
+            }
+        }
+        groups
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct ActiveTool {
+    pub id: Option<ToolId>,
+    // Tool that ran before the last switch, so it can tidy up after itself
+    pub previous: Option<ToolId>,
+}
+
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub enum ToolCommand {
+    Activate(ToolId),
+    Exit,
+    // Drops the picks in progress, or leaves the tool when there are none
+    Cancel,
+    // Finishes the picks in progress, for tools that take any number of points
+    Confirm,
+}
+
+// What the framework tells the running tool
+#[derive(Message, Clone, Debug, PartialEq)]
+pub enum ToolEvent {
+    Entered(ToolId),
+    Exited(ToolId),
+    // Left click in a viewport. Tools that need more than one point record it with
+    // ToolInputs::push, single click tools act on it straight away.
+    Picked { tool: ToolId, pane_id: u32, cursor: Vec2 },
+    Confirmed { tool: ToolId, pane_id: u32, points: Vec<Vec3> },
+    Cancelled { tool: ToolId, pane_id: u32 },
+}
+
+// Where the running tool is in its input, in one viewport
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ToolStep {
+    #[default]
+    Idle,
+    Picking { points: Vec<Vec3> },
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct ToolInputs {
+    pub panes: HashMap<u32, ToolStep>,
+}
+
+impl ToolInputs {
+    pub fn step(&self, pane_id: u32) -> &ToolStep {
+        self.panes.get(&pane_id).unwrap_or(&ToolStep::Idle)
+    }
+
+    pub fn points(&self, pane_id: u32) -> &[Vec3] {
+        match self.step(pane_id) {
+            ToolStep::Idle => &[],
+            ToolStep::Picking { points } => points,
+        }
+    }
+
+    pub fn push(&mut self, pane_id: u32, point: Vec3) {
+        match self.panes.entry(pane_id).or_default() {
+            ToolStep::Picking { points } => points.push(point),
+            step => *step = ToolStep::Picking { points: vec![point] },
+        }
+    }
+
+    // Panes with picks in progress
+    pub fn picking(&self) -> impl Iterator<Item = u32> + '_ {
+        self.panes
+            .iter()
+            .filter(|(_, step)| matches!(step, ToolStep::Picking { .. }))
+            .map(|(&pane_id, _)| pane_id)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ToolValue {
+    // Shown and typed in the project's unit for the measure
+    Number { value: f32, measure: Measure },
+    Toggle(bool),
+    Choice { choices: Vec<String>, selected: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToolOption {
+    pub key: &'static str,
+    pub value: ToolValue,
+}
+
+// Options panel of each tool, shown next to the toolbar while the tool runs
+#[derive(Resource, Default, Debug)]
+pub struct ToolOptions {
+    pub tools: HashMap<ToolId, Vec<ToolOption>>,
+}
+
+impl ToolOptions {
+    pub fn get(&self, tool: ToolId, key: &str) -> Option<&ToolValue> {
+        self.tools
+            .get(&tool)?
+            .iter()
+            .find(|option| option.key == key)
+            .map(|option| &option.value)
+    }
+
+    pub fn number(&self, tool: ToolId, key: &str) -> Option<f32> {
+        match self.get(tool, key)? {
+            ToolValue::Number { value, .. } => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn toggle(&self, tool: ToolId, key: &str) -> Option<bool> {
+        match self.get(tool, key)? {
+            ToolValue::Toggle(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn choice(&self, tool: ToolId, key: &str) -> Option<&str> {
+        match self.get(tool, key)? {
+            ToolValue::Choice { choices, selected } => choices.get(*selected).map(String::as_str),
+            _ => None,
+        }
+    }
+}
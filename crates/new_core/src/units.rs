@@ -0,0 +1,544 @@
+// File: units.rs
+// Desc: Project units. Values are kept in SI everywhere (metres, square and cubic metres,
+//       radians, cubic metres per second, pascals, metres per second) and only turned into
+//       the project units where they are shown or typed. Typed text may name any unit of its
+//       measure, so 3' 4 1/2", 1200mm and 1.2m are all read as lengths whatever the project
+//       shows.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+pub const PRECISION_RANGE: RangeInclusive<u8> = 0..=6;
+// Smallest fractions of an inch feet and inches round to
+pub const INCH_FRACTIONS: [u32; 6] = [2, 4, 8, 16, 32, 64];
+
+#[derive(EnumIter, Display, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Measure {
+    Length,
+    Area,
+    Volume,
+    Angle,
+    Flow,
+    Pressure,
+    Velocity,
+}
+
+#[derive(EnumIter, Display, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum UnitSystem {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+// Scale is the SI value of one of the unit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unit {
+    pub measure: Measure,
+    pub symbol: &'static str,
+    pub scale: f64,
+    // Other ways to type it, lower case without spaces or dots
+    aliases: &'static [&'static str],
+}
+
+impl Unit {
+    const fn new(measure: Measure, symbol: &'static str, scale: f64, aliases: &'static [&'static str]) -> Self {
+        Self {
+            measure,
+            symbol,
+            scale,
+            aliases,
+        }
+    }
+
+    // 12.5 mm but 12.5° and 4'
+    fn spaced(&self) -> bool {
+        self.symbol.starts_with(char::is_alphabetic)
+    }
+
+    // Symbol that reads on its own, as in a rate per ft rather than per '
+    pub fn abbreviation(&self) -> &'static str {
+        match self.aliases.first() {
+            Some(alias) if !self.spaced() => alias,
+            _ => self.symbol,
+        }
+    }
+
+    fn matches(&self, symbol: &str) -> bool {
+        normalize(self.symbol) == symbol || self.aliases.contains(&symbol)
+    }
+}
+
+pub const MILLIMETRE: Unit = Unit::new(Measure::Length, "mm", 0.001, &["millimetre", "millimeter", "millimetres", "millimeters"]);
+pub const CENTIMETRE: Unit = Unit::new(Measure::Length, "cm", 0.01, &["centimetre", "centimeter", "centimetres", "centimeters"]);
+pub const METRE: Unit = Unit::new(Measure::Length, "m", 1.0, &["metre", "meter", "metres", "meters"]);
+pub const INCH: Unit = Unit::new(Measure::Length, "\"", 0.0254, &["in", "inch", "inches"]);
+pub const FOOT: Unit = Unit::new(Measure::Length, "'", 0.3048, &["ft", "foot", "feet"]);
+
+pub const SQUARE_MILLIMETRE: Unit = Unit::new(Measure::Area, "mm²", 1e-6, &[]);
+pub const SQUARE_METRE: Unit = Unit::new(Measure::Area, "m²", 1.0, &["sqm"]);
+pub const SQUARE_INCH: Unit = Unit::new(Measure::Area, "in²", 0.00064516, &["sqin"]);
+pub const SQUARE_FOOT: Unit = Unit::new(Measure::Area, "ft²", 0.09290304, &["sqft", "sf"]);
+
+pub const LITRE: Unit = Unit::new(Measure::Volume, "L", 0.001, &["litre", "liter", "litres", "liters"]);
+pub const CUBIC_METRE: Unit = Unit::new(Measure::Volume, "m³", 1.0, &["cum"]);
+pub const US_GALLON: Unit = Unit::new(Measure::Volume, "gal", 0.003785411784, &["gallon", "gallons"]);
+pub const CUBIC_FOOT: Unit = Unit::new(Measure::Volume, "ft³", 0.028316846592, &["cuft", "cf"]);
+pub const CUBIC_YARD: Unit = Unit::new(Measure::Volume, "yd³", 0.764554857984, &["cuyd", "cy"]);
+
+pub const DEGREE: Unit = Unit::new(Measure::Angle, "°", PI / 180.0, &["deg", "degree", "degrees"]);
+pub const ARC_MINUTE: Unit = Unit::new(Measure::Angle, "′", PI / 10_800.0, &[]);
+pub const ARC_SECOND: Unit = Unit::new(Measure::Angle, "″", PI / 648_000.0, &[]);
+pub const RADIAN: Unit = Unit::new(Measure::Angle, "rad", 1.0, &["radian", "radians"]);
+
+pub const LITRES_PER_SECOND: Unit = Unit::new(Measure::Flow, "L/s", 0.001, &["lps"]);
+pub const CUBIC_METRES_PER_HOUR: Unit = Unit::new(Measure::Flow, "m³/h", 1.0 / 3600.0, &["cmh"]);
+pub const CUBIC_METRES_PER_SECOND: Unit = Unit::new(Measure::Flow, "m³/s", 1.0, &[]);
+pub const GALLONS_PER_MINUTE: Unit = Unit::new(Measure::Flow, "gpm", 0.003785411784 / 60.0, &["gal/min", "usgpm"]);
+pub const CUBIC_FEET_PER_MINUTE: Unit = Unit::new(Measure::Flow, "cfm", 0.028316846592 / 60.0, &["ft3/min"]);
+
+pub const PASCAL: Unit = Unit::new(Measure::Pressure, "Pa", 1.0, &["pascal", "pascals"]);
+pub const KILOPASCAL: Unit = Unit::new(Measure::Pressure, "kPa", 1000.0, &[]);
+pub const BAR: Unit = Unit::new(Measure::Pressure, "bar", 100_000.0, &[]);
+pub const PSI: Unit = Unit::new(Measure::Pressure, "psi", 6894.757293168, &["lbf/in2"]);
+pub const INCH_OF_WATER: Unit = Unit::new(Measure::Pressure, "in. wg", 249.08891, &["inwc", "inh2o"]);
+
+pub const METRES_PER_SECOND: Unit = Unit::new(Measure::Velocity, "m/s", 1.0, &["mps"]);
+// Ducts are sized in feet a minute in imperial projects
+pub const FEET_PER_MINUTE: Unit = Unit::new(Measure::Velocity, "fpm", 0.3048 / 60.0, &["ft/min"]);
+
+pub const UNITS: &[Unit] = &[
+    MILLIMETRE,
+    CENTIMETRE,
+    METRE,
+    INCH,
+    FOOT,
+    SQUARE_MILLIMETRE,
+    SQUARE_METRE,
+    SQUARE_INCH,
+    SQUARE_FOOT,
+    LITRE,
+    CUBIC_METRE,
+    US_GALLON,
+    CUBIC_FOOT,
+    CUBIC_YARD,
+    DEGREE,
+    ARC_MINUTE,
+    ARC_SECOND,
+    RADIAN,
+    LITRES_PER_SECOND,
+    CUBIC_METRES_PER_HOUR,
+    CUBIC_METRES_PER_SECOND,
+    GALLONS_PER_MINUTE,
+    CUBIC_FEET_PER_MINUTE,
+    PASCAL,
+    KILOPASCAL,
+    BAR,
+    PSI,
+    INCH_OF_WATER,
+    METRES_PER_SECOND,
+    FEET_PER_MINUTE,
+];
+
+// Units a project can show a measure in, offered by the system they belong to
+pub trait UnitChoice: IntoEnumIterator + std::fmt::Display + Copy + PartialEq {
+    fn unit(self) -> Unit;
+    fn system(self) -> UnitSystem;
+}
+
+#[derive(EnumIter, Display, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthUnit {
+    Millimetres,
+    Centimetres,
+    Metres,
+    // 3' 4 1/2"
+    #[strum(to_string = "Feet and Inches")]
+    FeetAndInches,
+    #[strum(to_string = "Decimal Feet")]
+    Feet,
+    #[strum(to_string = "Decimal Inches")]
+    Inches,
+}
+
+impl UnitChoice for LengthUnit {
+    fn unit(self) -> Unit {
+        match self {
+            LengthUnit::Millimetres => MILLIMETRE,
+            LengthUnit::Centimetres => CENTIMETRE,
+            LengthUnit::Metres => METRE,
+            LengthUnit::FeetAndInches | LengthUnit::Feet => FOOT,
+            LengthUnit::Inches => INCH,
+        }
+    }
+
+    fn system(self) -> UnitSystem {
+        match self {
+            LengthUnit::Millimetres | LengthUnit::Centimetres | LengthUnit::Metres => UnitSystem::Metric,
+            LengthUnit::FeetAndInches | LengthUnit::Feet | LengthUnit::Inches => UnitSystem::Imperial,
+        }
+    }
+}
+
+#[derive(EnumIter, Display, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlowUnit {
+    #[strum(to_string = "L/s")]
+    LitresPerSecond,
+    #[strum(to_string = "m³/h")]
+    CubicMetresPerHour,
+    #[strum(to_string = "gpm")]
+    GallonsPerMinute,
+    #[strum(to_string = "cfm")]
+    CubicFeetPerMinute,
+}
+
+impl UnitChoice for FlowUnit {
+    fn unit(self) -> Unit {
+        match self {
+            FlowUnit::LitresPerSecond => LITRES_PER_SECOND,
+            FlowUnit::CubicMetresPerHour => CUBIC_METRES_PER_HOUR,
+            FlowUnit::GallonsPerMinute => GALLONS_PER_MINUTE,
+            FlowUnit::CubicFeetPerMinute => CUBIC_FEET_PER_MINUTE,
+        }
+    }
+
+    fn system(self) -> UnitSystem {
+        match self {
+            FlowUnit::LitresPerSecond | FlowUnit::CubicMetresPerHour => UnitSystem::Metric,
+            FlowUnit::GallonsPerMinute | FlowUnit::CubicFeetPerMinute => UnitSystem::Imperial,
+        }
+    }
+}
+
+#[derive(EnumIter, Display, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PressureUnit {
+    #[strum(to_string = "Pa")]
+    Pascals,
+    #[strum(to_string = "kPa")]
+    Kilopascals,
+    #[strum(to_string = "psi")]
+    Psi,
+    #[strum(to_string = "in. wg")]
+    InchesOfWater,
+}
+
+impl UnitChoice for PressureUnit {
+    fn unit(self) -> Unit {
+        match self {
+            PressureUnit::Pascals => PASCAL,
+            PressureUnit::Kilopascals => KILOPASCAL,
+            PressureUnit::Psi => PSI,
+            PressureUnit::InchesOfWater => INCH_OF_WATER,
+        }
+    }
+
+    fn system(self) -> UnitSystem {
+        match self {
+            PressureUnit::Pascals | PressureUnit::Kilopascals => UnitSystem::Metric,
+            PressureUnit::Psi | PressureUnit::InchesOfWater => UnitSystem::Imperial,
+        }
+    }
+}
+
+// Area, volume, angle and velocity follow the system, the rest are picked
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct ProjectUnits {
+    pub system: UnitSystem,
+    pub length: LengthUnit,
+    pub flow: FlowUnit,
+    pub pressure: PressureUnit,
+    // Decimal places shown
+    pub precision: u8,
+    // Feet and inches round to 1/inch_fraction of an inch
+    pub inch_fraction: u32,
+}
+
+impl Default for ProjectUnits {
+    fn default() -> Self {
+        Self::metric()
+    }
+}
+
+impl ProjectUnits {
+    pub fn metric() -> Self {
+        Self {
+            system: UnitSystem::Metric,
+            length: LengthUnit::Metres,
+            flow: FlowUnit::LitresPerSecond,
+            pressure: PressureUnit::Kilopascals,
+            precision: 3,
+            inch_fraction: 16,
+        }
+    }
+
+    pub fn imperial() -> Self {
+        Self {
+            system: UnitSystem::Imperial,
+            length: LengthUnit::FeetAndInches,
+            flow: FlowUnit::GallonsPerMinute,
+            pressure: PressureUnit::Psi,
+            precision: 2,
+            inch_fraction: 16,
+        }
+    }
+
+    // Every unit goes to the usual one of the system, the rounding stays
+    pub fn set_system(&mut self, system: UnitSystem) {
+        let precision = self.precision;
+        let inch_fraction = self.inch_fraction;
+        *self = match system {
+            UnitSystem::Metric => Self::metric(),
+            UnitSystem::Imperial => Self::imperial(),
+        };
+        self.precision = precision;
+        self.inch_fraction = inch_fraction;
+    }
+
+    // Pulls values edited by hand in a file back into range
+    pub fn clamp(&mut self) {
+        self.precision = self.precision.min(*PRECISION_RANGE.end());
+        if !INCH_FRACTIONS.contains(&self.inch_fraction) {
+            self.inch_fraction = 16;
+        }
+    }
+
+    pub fn unit(&self, measure: Measure) -> Unit {
+        match (measure, self.system) {
+            (Measure::Length, _) => self.length.unit(),
+            (Measure::Area, UnitSystem::Metric) => SQUARE_METRE,
+            (Measure::Area, UnitSystem::Imperial) => SQUARE_FOOT,
+            (Measure::Volume, UnitSystem::Metric) => CUBIC_METRE,
+            (Measure::Volume, UnitSystem::Imperial) => CUBIC_FOOT,
+            (Measure::Angle, _) => DEGREE,
+            (Measure::Flow, _) => self.flow.unit(),
+            (Measure::Pressure, _) => self.pressure.unit(),
+            (Measure::Velocity, UnitSystem::Metric) => METRES_PER_SECOND,
+            (Measure::Velocity, UnitSystem::Imperial) => FEET_PER_MINUTE,
+        }
+    }
+
+    // SI value as the project shows it, with its symbol
+    pub fn format(&self, measure: Measure, value: f64) -> String {
+        if measure == Measure::Length && self.length == LengthUnit::FeetAndInches {
+            return feet_and_inches(value, self.inch_fraction);
+        }
+
+        let unit = self.unit(measure);
+        let precision = usize::from(self.precision);
+        let mut shown = value / unit.scale;
+        // No -0.000
+        if shown.abs() < 0.5 * 10f64.powi(-i32::from(self.precision)) {
+            shown = 0.0;
+        }
+        if unit.spaced() {
+            format!("{shown:.precision$} {}", unit.symbol)
+        } else {
+            format!("{shown:.precision$}{}", unit.symbol)
+        }
+    }
+
+    // Typed text to an SI value. Terms add up, so 3' 4 1/2" and 1m 20cm work, and a number
+    // without a unit is in the project unit.
+    pub fn parse(&self, measure: Measure, text: &str) -> Option<f64> {
+        let text = text.trim();
+        let (sign, mut rest) = match text.strip_prefix('-') {
+            Some(rest) => (-1.0, rest),
+            None => (1.0, text.strip_prefix('+').unwrap_or(text)),
+        };
+
+        let mut total = 0.0;
+        let mut previous: Option<Unit> = None;
+        loop {
+            rest = rest.trim_start();
+            // 3'-4"
+            if previous.is_some() {
+                rest = rest.strip_prefix('-').unwrap_or(rest).trim_start();
+            }
+            if rest.is_empty() {
+                break;
+            }
+
+            let (number, after) = take_number(rest)?;
+            let (symbol, after) = take_symbol(after);
+            let unit = if symbol.is_empty() {
+                self.implied_unit(measure, previous)?
+            } else {
+                UNITS
+                    .iter()
+                    .find(|unit| unit.measure == measure && unit.matches(&symbol))
+                    .copied()?
+            };
+            total += number * unit.scale;
+            previous = Some(unit);
+            rest = after;
+        }
+
+        previous.map(|_| sign * total)
+    }
+
+    // The first bare number is in the project unit, one after feet is inches as in 3' 4
+    fn implied_unit(&self, measure: Measure, previous: Option<Unit>) -> Option<Unit> {
+        match previous {
+            None => Some(self.unit(measure)),
+            Some(unit) if unit == FOOT => Some(INCH),
+            Some(_) => None,
+        }
+    }
+}
+
+fn feet_and_inches(metres: f64, inch_fraction: u32) -> String {
+    let fraction = u64::from(inch_fraction.max(1));
+    // Counted in the smallest fraction so rounding carries into inches and feet
+    let steps = (metres.abs() / INCH.scale * fraction as f64).round() as u64;
+    let sign = if metres < 0.0 && steps > 0 { "-" } else { "" };
+
+    let feet = steps / (12 * fraction);
+    let inches = steps % (12 * fraction) / fraction;
+    let numerator = steps % fraction;
+    if numerator == 0 {
+        return format!("{sign}{feet}' {inches}\"");
+    }
+    let divisor = gcd(numerator, fraction);
+    format!("{sign}{feet}' {inches} {}/{}\"", numerator / divisor, fraction / divisor)
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// Lower case without spaces or dots, with superscripts and primes typed as they are on a
+// keyboard
+fn normalize(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '.')
+        .map(|c| match c {
+            '²' => '2',
+            '³' => '3',
+            '′' | '’' => '\'',
+            '″' | '”' => '"',
+            c => c,
+        })
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .replace("''", "\"")
+}
+
+fn take_decimal(text: &str) -> Option<(f64, &str)> {
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let value = text[..end].parse().ok()?;
+    Some((value, &text[end..]))
+}
+
+// 1.5, 1/2 or 4 1/2
+fn take_number(text: &str) -> Option<(f64, &str)> {
+    let (whole, rest) = take_decimal(text)?;
+    if let Some(rest) = rest.strip_prefix('/') {
+        let (denominator, rest) = take_decimal(rest)?;
+        return (denominator != 0.0).then_some((whole / denominator, rest));
+    }
+
+    if whole.fract() == 0.0
+        && let Some((numerator, after)) = take_decimal(rest.trim_start())
+        && let Some(after) = after.strip_prefix('/')
+        && let Some((denominator, after)) = take_decimal(after)
+        && denominator != 0.0
+    {
+        return Some((whole + numerator / denominator, after));
+    }
+    Some((whole, rest))
+}
+
+// The unit after a number, up to the next number. Digits that follow a letter stay in it,
+// as in m3/h.
+fn take_symbol(text: &str) -> (String, &str) {
+    let mut end = text.len();
+    let mut previous = ' ';
+    for (index, c) in text.char_indices() {
+        let starts_number = c.is_ascii_digit() && !(previous.is_alphabetic() || previous == '/');
+        if c == '-' || starts_number {
+            end = index;
+            break;
+        }
+        previous = c;
+    }
+    (normalize(&text[..end]), &text[end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    fn assert_parses(units: &ProjectUnits, measure: Measure, text: &str, expected: f64) {
+        let value = units.parse(measure, text).unwrap_or_else(|| panic!("{text} did not parse"));
+        assert!((value - expected).abs() < 1e-9, "{text} read as {value}, not {expected}");
+    }
+
+    #[test]
+    fn formats_in_the_project_units() {
+        let metric = ProjectUnits::metric();
+        assert_eq!(metric.format(Measure::Length, 1.25), "1.250 m");
+        assert_eq!(metric.format(Measure::Length, -0.0001), "0.000 m");
+        assert_eq!(metric.format(Measure::Angle, FRAC_PI_2), "90.000°");
+        assert_eq!(metric.format(Measure::Flow, 0.0015), "1.500 L/s");
+        assert_eq!(metric.format(Measure::Velocity, 2.5), "2.500 m/s");
+
+        let imperial = ProjectUnits::imperial();
+        assert_eq!(imperial.format(Measure::Length, 1.0), "3' 3 3/8\"");
+        assert_eq!(imperial.format(Measure::Length, -FOOT.scale), "-1' 0\"");
+        assert_eq!(imperial.format(Measure::Area, SQUARE_FOOT.scale), "1.00 ft²");
+        assert_eq!(imperial.format(Measure::Velocity, 2.032), "400.00 fpm");
+        let decimal_feet = ProjectUnits {
+            length: LengthUnit::Feet,
+            ..imperial
+        };
+        assert_eq!(decimal_feet.format(Measure::Length, 0.762), "2.50'");
+    }
+
+    #[test]
+    fn parses_terms_that_add_up() {
+        let metric = ProjectUnits::metric();
+        assert_parses(&metric, Measure::Length, "1200mm", 1.2);
+        assert_parses(&metric, Measure::Length, "1m 20cm", 1.2);
+        assert_parses(&metric, Measure::Length, " 2 ", 2.0);
+        assert_parses(&metric, Measure::Length, "-3 ft", -0.9144);
+        assert_parses(&metric, Measure::Length, "3' 4 1/2\"", 1.0287);
+        assert_parses(&metric, Measure::Length, "3'-4\"", 1.016);
+        assert_parses(&metric, Measure::Length, "3' 4", 1.016);
+        assert_parses(&metric, Measure::Flow, "1 m3/h", 1.0 / 3600.0);
+        assert_parses(&metric, Measure::Pressure, "2 in. wg", 2.0 * INCH_OF_WATER.scale);
+        assert_parses(&metric, Measure::Angle, "45deg", FRAC_PI_4);
+
+        let imperial = ProjectUnits::imperial();
+        assert_parses(&imperial, Measure::Length, "10", 10.0 * FOOT.scale);
+        assert_parses(&imperial, Measure::Flow, "60", 60.0 * GALLONS_PER_MINUTE.scale);
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_read() {
+        let metric = ProjectUnits::metric();
+        for text in ["", "abc", "1 kg", "1m 2", "3/0", "2 psi"] {
+            assert_eq!(metric.parse(Measure::Length, text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn formatted_values_read_back() {
+        for units in [ProjectUnits::metric(), ProjectUnits::imperial()] {
+            for value in [0.0, 0.3, 2.745, -12.5] {
+                let text = units.format(Measure::Length, value);
+                let read = units.parse(Measure::Length, &text).unwrap();
+                assert!((read - value).abs() < 0.002, "{value} printed as {text} read back as {read}");
+            }
+        }
+    }
+}
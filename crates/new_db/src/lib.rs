@@ -1,8 +1,10 @@
 use bevy::prelude::*;
 
 use crate::db::MonoDb;
+use crate::library::LibraryDb;
 
 pub mod db;
+pub mod library;
 pub mod repo;
 
 pub struct DbPlugin;
@@ -18,4 +20,7 @@ impl Plugin for DbPlugin {
 fn setup_db(world: &mut World) {
     let db = MonoDb::open("monolith.sqlite").expect("failed to open monolith.sqlite");
     world.insert_non_send_resource(db);
+
+    let library = LibraryDb::open("library.sqlite").expect("failed to open library.sqlite");
+    world.insert_non_send_resource(library);
 }
\ No newline at end of file
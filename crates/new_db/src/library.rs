@@ -0,0 +1,201 @@
+use rusqlite::{Connection, OptionalExtension, Result, params};
+
+use new_core::asset_library::{LibraryAssetId, LibraryAssetSummary, Thumbnail};
+use new_core::elements::ElementKind;
+use new_core::model_exchange::kind_from_name;
+
+// Shared asset library, kept apart from the model so every project can use it
+pub struct LibraryDb {
+    pub conn: Connection,
+}
+
+impl LibraryDb {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS assets (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                category TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                thumbnail BLOB NOT NULL,
+                geometry BLOB NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS assets_by_category ON assets (category, name);
+            ",
+        )?;
+
+        Ok(Self { conn })
+    }
+}
+
+pub struct NewAsset<'a> {
+    pub name: &'a str,
+    pub category: &'a str,
+    pub kind: ElementKind,
+    pub thumbnail: &'a Thumbnail,
+    // GLB with the default parameters in the node extras
+    pub geometry: &'a [u8],
+}
+
+// Name, kind and geometry of an asset about to be placed
+pub struct AssetDefinition {
+    pub name: String,
+    pub kind: ElementKind,
+    pub geometry: Vec<u8>,
+}
+
+pub fn insert_asset(db: &LibraryDb, asset: &NewAsset) -> Result<LibraryAssetId> {
+    db.conn.execute(
+        "
+        INSERT INTO assets (name, category, kind, thumbnail, geometry)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        ",
+        params![
+            asset.name,
+            asset.category,
+            asset.kind.to_string(),
+            asset.thumbnail.to_bytes(),
+            asset.geometry,
+        ],
+    )?;
+
+    Ok(LibraryAssetId(db.conn.last_insert_rowid()))
+}
+
+pub fn delete_asset(db: &LibraryDb, id: LibraryAssetId) -> Result<()> {
+    db.conn.execute("DELETE FROM assets WHERE id = ?1", params![id.0])?;
+    Ok(())
+}
+
+pub fn asset_categories(db: &LibraryDb) -> Result<Vec<String>> {
+    let mut statement = db
+        .conn
+        .prepare("SELECT DISTINCT category FROM assets ORDER BY category")?;
+    statement.query_map([], |row| row.get(0))?.collect()
+}
+
+// Assets whose name, category or kind contain the search, at most limit of them, and how many
+// matched in all
+pub fn search_assets(
+    db: &LibraryDb,
+    search: &str,
+    category: Option<&str>,
+    limit: usize,
+) -> Result<(Vec<LibraryAssetSummary>, usize)> {
+    let escaped = search
+        .trim()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let pattern = format!("%{escaped}%");
+    let filter = "
+        (name LIKE ?1 ESCAPE '\\' OR category LIKE ?1 ESCAPE '\\' OR kind LIKE ?1 ESCAPE '\\')
+        AND (?2 IS NULL OR category = ?2)
+    ";
+
+    let total: i64 = db.conn.query_row(
+        &format!("SELECT COUNT(*) FROM assets WHERE {filter}"),
+        params![pattern, category],
+        |row| row.get(0),
+    )?;
+
+    let mut statement = db.conn.prepare(&format!(
+        "
+        SELECT id, name, category, kind, thumbnail FROM assets
+        WHERE {filter}
+        ORDER BY category, name
+        LIMIT ?3
+        "
+    ))?;
+    let assets = statement
+        .query_map(params![pattern, category, limit as i64], |row| {
+            let kind: String = row.get(3)?;
+            let thumbnail: Vec<u8> = row.get(4)?;
+            Ok(LibraryAssetSummary {
+                id: LibraryAssetId(row.get(0)?),
+                name: row.get(1)?,
+                category: row.get(2)?,
+                kind: kind_from_name(&kind).unwrap_or(ElementKind::BuildingElementProxy),
+                thumbnail: Thumbnail::from_bytes(&thumbnail),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((assets, total as usize))
+}
+
+pub fn asset_definition(db: &LibraryDb, id: LibraryAssetId) -> Result<Option<AssetDefinition>> {
+    db.conn
+        .query_row(
+            "SELECT name, kind, geometry FROM assets WHERE id = ?1",
+            params![id.0],
+            |row| {
+                let kind: String = row.get(1)?;
+                Ok(AssetDefinition {
+                    name: row.get(0)?,
+                    kind: kind_from_name(&kind).unwrap_or(ElementKind::BuildingElementProxy),
+                    geometry: row.get(2)?,
+                })
+            },
+        )
+        .optional()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(assets: &[(&str, &str)]) -> LibraryDb {
+        let db = LibraryDb::open(":memory:").expect("opened");
+        for (name, category) in assets {
+            let asset = NewAsset {
+                name,
+                category,
+                kind: ElementKind::Furniture,
+                thumbnail: &Thumbnail::default(),
+                geometry: &[],
+            };
+            insert_asset(&db, &asset).expect("inserted");
+        }
+        db
+    }
+
+    fn names(db: &LibraryDb, search: &str, category: Option<&str>) -> Vec<String> {
+        let (assets, total) = search_assets(db, search, category, 10).expect("searched");
+        assert_eq!(total, assets.len());
+        assets.into_iter().map(|asset| asset.name).collect()
+    }
+
+    #[test]
+    fn wildcards_in_the_search_are_taken_literally() {
+        let db = library(&[
+            ("Desk 50% off", "Office"),
+            ("Desk 500", "Office"),
+            ("Door_single", "Doors"),
+            ("Door single", "Doors"),
+            ("Shelf A\\B", "Storage"),
+            ("Shelf AB", "Storage"),
+        ]);
+
+        assert_eq!(names(&db, "50%", None), ["Desk 50% off"]);
+        assert_eq!(names(&db, "r_s", None), ["Door_single"]);
+        assert_eq!(names(&db, "A\\B", None), ["Shelf A\\B"]);
+        assert_eq!(names(&db, "%", None), ["Desk 50% off"]);
+        assert_eq!(names(&db, "", None).len(), 6);
+    }
+
+    #[test]
+    fn a_category_narrows_the_search() {
+        let db = library(&[("Chair", "Office"), ("Chair", "Dining"), ("Table", "Dining")]);
+
+        assert_eq!(names(&db, "chair", None).len(), 2);
+        assert_eq!(names(&db, "chair", Some("Dining")), ["Chair"]);
+        assert_eq!(names(&db, "", Some("Dining")), ["Chair", "Table"]);
+        assert!(names(&db, "", Some("Outdoor")).is_empty());
+        // The search still reaches the category name
+        assert_eq!(names(&db, "dini", None), ["Chair", "Table"]);
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use egui_tiles::{Tile, Tree};
+use strum::IntoEnumIterator;
+
+use new_core::action::{
+    ActionAppExt, ActionDef, ActionId, ActionInvoked, ActionPrompt, ActionRegistry, COMMAND_PALETTE,
+    CommandPalette,
+};
+use new_core::keymap::KeyBinding;
+use new_core::pane_kind::PaneKind;
+use new_core::preferences::{PreferenceSection, PreferencesWindow};
+use new_core::{DockTree, Pane};
+
+pub const OPEN_PANE: ActionId = ActionId("window.open_pane");
+pub const EDIT_KEYMAP: ActionId = ActionId("edit.keymap");
+pub const EDIT_PREFERENCES: ActionId = ActionId("edit.preferences");
+pub const PROJECT_UNITS: ActionId = ActionId("file.project_units");
+
+pub fn register_ui_actions(app: &mut App) {
+    app.register_action(ActionDef {
+        id: COMMAND_PALETTE,
+        label: "Command Palette",
+        menu: "Edit",
+        bindings: vec![KeyBinding::key(KeyCode::Space), KeyBinding::ctrl(KeyCode::KeyP)],
+        prompt: None,
+    })
+    .register_action(ActionDef {
+        id: EDIT_PREFERENCES,
+        label: "Preferences",
+        menu: "Edit",
+        bindings: vec![KeyBinding::ctrl(KeyCode::Comma)],
+        prompt: None,
+    })
+    .register_action(ActionDef {
+        id: EDIT_KEYMAP,
+        label: "Keymap",
+        menu: "Edit",
+        bindings: Vec::new(),
+        prompt: None,
+    })
+    .register_action(ActionDef {
+        id: PROJECT_UNITS,
+        label: "Project Units",
+        menu: "File",
+        bindings: Vec::new(),
+        prompt: None,
+    })
+    .register_action(ActionDef {
+        id: OPEN_PANE,
+        label: "Open Pane",
+        menu: "Window",
+        bindings: Vec::new(),
+        prompt: Some(ActionPrompt {
+            label: "Pane",
+            choices: PaneKind::iter().map(|kind| kind.to_string()).collect(),
+        }),
+    });
+}
+
+// Opens the palette, asks for missing arguments, and carries out the actions on the dock
+pub fn apply_ui_actions(
+    mut invoked: MessageReader<ActionInvoked>,
+    registry: Res<ActionRegistry>,
+    mut palette: ResMut<CommandPalette>,
+    mut preferences_window: ResMut<PreferencesWindow>,
+    mut dock: ResMut<DockTree>,
+) {
+    for action in invoked.read() {
+        let Some(def) = registry.get(action.id) else {
+            continue;
+        };
+
+        if action.id == COMMAND_PALETTE {
+            palette.show(None);
+        } else if def.prompt.is_some() && action.arg.is_none() {
+            palette.show(Some(action.id));
+        } else if action.id == OPEN_PANE
+            && let Some(kind) = PaneKind::iter().find(|kind| Some(kind.to_string()) == action.arg)
+        {
+            open_pane(&mut dock.tree, kind);
+        } else if action.id == EDIT_PREFERENCES {
+            preferences_window.open = true;
+        } else if action.id == PROJECT_UNITS {
+            preferences_window.open = true;
+            preferences_window.section = PreferenceSection::Units;
+        } else if action.id == EDIT_KEYMAP && !has_pane(&dock.tree, PaneKind::Keymap) {
+            open_pane(&mut dock.tree, PaneKind::Keymap);
+        }
+    }
+}
+
+fn has_pane(tree: &Tree<Pane>, kind: PaneKind) -> bool {
+    tree.tiles
+        .iter()
+        .any(|(_, tile)| matches!(tile, Tile::Pane(pane) if pane.kind == kind))
+}
+
+// New pane next to the others in the root container
+fn open_pane(tree: &mut Tree<Pane>, kind: PaneKind) {
+    let id = tree
+        .tiles
+        .iter()
+        .filter_map(|(_, tile)| match tile {
+            Tile::Pane(pane) => Some(pane.id),
+            Tile::Container(_) => None,
+        })
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let tile = tree.tiles.insert_pane(Pane { id, kind });
+
+    match tree.root {
+        Some(root) => match tree.tiles.get_mut(root) {
+            Some(Tile::Container(container)) => container.add_child(tile),
+            _ => {
+                let both = tree.tiles.insert_horizontal_tile(vec![root, tile]);
+                tree.root = Some(both);
+            }
+        },
+        None => tree.root = Some(tile),
+    }
+}
@@ -1,11 +1,32 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 use egui_tiles::{Container, Tile, TileId, Tiles, Tree};
 use std::collections::HashMap;
 
+use new_core::action::{ActionHistory, ActionInvoked, ActionRegistry, CommandPalette};
+use new_core::anchor::PlacementSettings;
+use new_core::asset_library::{AssetBrowser, AssetCommand, AssetResults};
+use new_core::cad::{CadCommand, CadExchange, Underlays};
+use new_core::clash::{ClashCommand, ClashResults, ClashReview, ClashRules};
+use new_core::cost::{CostEstimate, CostSchedule, CostView};
+use new_core::exchange::{ExchangeCommand, SheetExchange};
+use new_core::grid::ViewGrids;
+use new_core::inspector::{ElementEdit, InspectedElement};
+use new_core::keymap::{Keymap, KeymapEditor};
+use new_core::mep_system::{MepAnalysis, SystemCommand};
+use new_core::model_exchange::{ModelCommand, ModelExchange};
 use new_core::pane_kind::PaneKind;
+use new_core::phase::ViewPhases;
+use new_core::preferences::{Preferences, PreferencesWindow};
+use new_core::schedule::{ScheduleEdit, ScheduleTables, Schedules};
+use new_core::sheet::{SheetCommand, SheetDrawings, SheetPublish, SheetSet};
+use new_core::sequence::{ConstructionSequence, Timeline, TimelineCommand};
+use new_core::tool::{ActiveTool, ToolCommand, ToolOptions, ToolRegistry};
+use new_core::units::ProjectUnits;
 use new_core::{DockTree, Pane, UiState, VisibleViewports};
 
+use crate::pane::pane_viewport::view_bar_rect;
 use crate::tree::TreeBehavior;
 
 pub fn setup_dock(mut commands: Commands) {
@@ -46,10 +67,118 @@ pub fn setup_dock(mut commands: Commands) {
     commands.insert_resource(VisibleViewports::default());
 }
 
+// Resources behind each group of panes, kept apart so the dock system stays readable
+#[derive(SystemParam)]
+pub struct ElementPaneParams<'w> {
+    inspected: Res<'w, InspectedElement>,
+    edits: MessageWriter<'w, ElementEdit>,
+    view_phases: ResMut<'w, ViewPhases>,
+    view_grids: ResMut<'w, ViewGrids>,
+    mep_analysis: Res<'w, MepAnalysis>,
+    system_commands: MessageWriter<'w, SystemCommand>,
+}
+
+#[derive(SystemParam)]
+pub struct ClashPaneParams<'w> {
+    results: ResMut<'w, ClashResults>,
+    rules: Res<'w, ClashRules>,
+    review: ResMut<'w, ClashReview>,
+    commands: MessageWriter<'w, ClashCommand>,
+}
+
+#[derive(SystemParam)]
+pub struct TimelinePaneParams<'w> {
+    sequence: ResMut<'w, ConstructionSequence>,
+    timeline: ResMut<'w, Timeline>,
+    commands: MessageWriter<'w, TimelineCommand>,
+}
+
+#[derive(SystemParam)]
+pub struct CostPaneParams<'w> {
+    estimate: Res<'w, CostEstimate>,
+    schedule: ResMut<'w, CostSchedule>,
+    view: ResMut<'w, CostView>,
+}
+
+#[derive(SystemParam)]
+pub struct SchedulePaneParams<'w> {
+    schedules: ResMut<'w, Schedules>,
+    tables: Res<'w, ScheduleTables>,
+    edits: MessageWriter<'w, ScheduleEdit>,
+    exchange: ResMut<'w, SheetExchange>,
+    exchange_commands: MessageWriter<'w, ExchangeCommand>,
+}
+
+#[derive(SystemParam)]
+pub struct SheetPaneParams<'w> {
+    sheets: ResMut<'w, SheetSet>,
+    drawings: Res<'w, SheetDrawings>,
+    publish: ResMut<'w, SheetPublish>,
+    commands: MessageWriter<'w, SheetCommand>,
+}
+
+#[derive(SystemParam)]
+pub struct CadPaneParams<'w> {
+    underlays: ResMut<'w, Underlays>,
+    exchange: ResMut<'w, CadExchange>,
+    commands: MessageWriter<'w, CadCommand>,
+}
+
+#[derive(SystemParam)]
+pub struct ModelPaneParams<'w> {
+    exchange: ResMut<'w, ModelExchange>,
+    commands: MessageWriter<'w, ModelCommand>,
+}
+
+#[derive(SystemParam)]
+pub struct AssetPaneParams<'w> {
+    browser: ResMut<'w, AssetBrowser>,
+    results: Res<'w, AssetResults>,
+    placement: ResMut<'w, PlacementSettings>,
+    commands: MessageWriter<'w, AssetCommand>,
+}
+
+#[derive(SystemParam)]
+pub struct ToolbarParams<'w> {
+    registry: Res<'w, ToolRegistry>,
+    active: Res<'w, ActiveTool>,
+    options: ResMut<'w, ToolOptions>,
+    commands: MessageWriter<'w, ToolCommand>,
+}
+
+#[derive(SystemParam)]
+pub struct ActionParams<'w> {
+    registry: Res<'w, ActionRegistry>,
+    history: Res<'w, ActionHistory>,
+    palette: ResMut<'w, CommandPalette>,
+    invoked: MessageWriter<'w, ActionInvoked>,
+    keymap: ResMut<'w, Keymap>,
+    keymap_editor: ResMut<'w, KeymapEditor>,
+}
+
+#[derive(SystemParam)]
+pub struct PreferenceParams<'w> {
+    preferences: ResMut<'w, Preferences>,
+    window: ResMut<'w, PreferencesWindow>,
+    units: ResMut<'w, ProjectUnits>,
+}
+
 pub fn dock_ui_system(
     mut contexts: EguiContexts,
     mut dock: ResMut<DockTree>,
     mut visible_viewports: ResMut<VisibleViewports>,
+    mut elements: ElementPaneParams,
+    mut clash: ClashPaneParams,
+    mut timeline: TimelinePaneParams,
+    mut cost: CostPaneParams,
+    mut schedule: SchedulePaneParams,
+    mut sheet: SheetPaneParams,
+    mut cad: CadPaneParams,
+    mut model: ModelPaneParams,
+    mut asset: AssetPaneParams,
+    mut tools: ToolbarParams,
+    mut actions: ActionParams,
+    mut preferences: PreferenceParams,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else {
         return;
@@ -57,13 +186,186 @@ pub fn dock_ui_system(
 
     let pointer_busy = ctx.input(|i| i.pointer.any_down() || i.pointer.any_released());
 
+    let mut element_edits = Vec::new();
+    let mut system_commands = Vec::new();
+    let mut clash_commands = Vec::new();
+    let mut sequence_edited = false;
+    let mut timeline_edited = false;
+    let mut timeline_commands = Vec::new();
+    let mut cost_schedule_edited = false;
+    let mut schedule_edits = Vec::new();
+    let mut schedules_edited = false;
+    let mut exchange_commands = Vec::new();
+    let mut sheets_edited = false;
+    let mut sheet_commands = Vec::new();
+    let mut underlays_edited = false;
+    let mut cad_commands = Vec::new();
+    let mut model_commands = Vec::new();
+    let mut asset_browser_edited = false;
+    let mut asset_commands = Vec::new();
+    let mut tool_options_edited = false;
+    let mut tool_commands = Vec::new();
+    let mut invoked_actions = Vec::new();
+    let mut keymap_edited = false;
+    let mut preferences_edited = false;
+    let mut units_edited = false;
+
+    egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+        crate::menubar::show(ui, &actions.registry, &actions.keymap, &mut invoked_actions);
+    });
+
+    egui::TopBottomPanel::top("tool_bar").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            crate::toolbar::show(
+                ui,
+                &tools.registry,
+                &tools.active,
+                &actions.registry,
+                &actions.keymap,
+                &mut tool_commands,
+            );
+            tool_options_edited = crate::toolbar::options(
+                ui,
+                &tools.active,
+                &actions.registry,
+                &actions.keymap,
+                // Tools rebuild what depends on their options, only flag real edits
+                tools.options.bypass_change_detection(),
+                &preferences.units,
+            );
+        });
+    });
+
     egui::CentralPanel::default()
         .frame(egui::Frame::NONE)
         .show(ctx, |ui| {
-            let mut behavior = TreeBehavior;
+            let mut behavior = TreeBehavior {
+                inspected: &elements.inspected,
+                element_edits: &mut element_edits,
+                view_phases: &mut elements.view_phases,
+                view_grids: &mut elements.view_grids,
+                mep_analysis: &elements.mep_analysis,
+                system_commands: &mut system_commands,
+                clash_results: &mut clash.results,
+                clash_rules: &clash.rules,
+                clash_review: &mut clash.review,
+                clash_commands: &mut clash_commands,
+                // Construction state is reapplied to every element when either changes
+                sequence: timeline.sequence.bypass_change_detection(),
+                sequence_edited: &mut sequence_edited,
+                timeline: timeline.timeline.bypass_change_detection(),
+                timeline_edited: &mut timeline_edited,
+                timeline_commands: &mut timeline_commands,
+                cost_estimate: &cost.estimate,
+                // Repricing walks every element, only flag the schedule when it was edited
+                cost_schedule: cost.schedule.bypass_change_detection(),
+                cost_schedule_edited: &mut cost_schedule_edited,
+                cost_view: &mut cost.view,
+                // Same for schedules, a changed definition rebuilds every table
+                schedules: schedule.schedules.bypass_change_detection(),
+                schedules_edited: &mut schedules_edited,
+                schedule_tables: &schedule.tables,
+                schedule_edits: &mut schedule_edits,
+                sheet_exchange: &mut schedule.exchange,
+                exchange_commands: &mut exchange_commands,
+                // Redrawing projects every element again
+                sheets: sheet.sheets.bypass_change_detection(),
+                sheets_edited: &mut sheets_edited,
+                sheet_drawings: &sheet.drawings,
+                sheet_publish: &mut sheet.publish,
+                sheet_commands: &mut sheet_commands,
+                // Underlay meshes only follow edits
+                underlays: cad.underlays.bypass_change_detection(),
+                underlays_edited: &mut underlays_edited,
+                cad_exchange: &mut cad.exchange,
+                cad_commands: &mut cad_commands,
+                model_exchange: &mut model.exchange,
+                model_commands: &mut model_commands,
+                // Every keystroke in the search would query the library otherwise
+                asset_browser: asset.browser.bypass_change_detection(),
+                asset_browser_edited: &mut asset_browser_edited,
+                asset_results: &asset.results,
+                placement: &mut asset.placement,
+                asset_commands: &mut asset_commands,
+                tool_commands: &mut tool_commands,
+                action_registry: &actions.registry,
+                // Saved to disk on every change
+                keymap: actions.keymap.bypass_change_detection(),
+                keymap_edited: &mut keymap_edited,
+                keymap_editor: &mut actions.keymap_editor,
+                units: &preferences.units,
+            };
             dock.tree.ui(&mut behavior, ui);
         });
 
+    crate::palette::show(
+        ctx,
+        &mut actions.palette,
+        &actions.registry,
+        &actions.history,
+        &actions.keymap,
+        &mut invoked_actions,
+    );
+
+    if preferences.window.open {
+        preferences_edited = crate::preferences::show(
+            ctx,
+            &mut preferences.window,
+            // Saved to disk and followed by plugins on every change
+            preferences.preferences.bypass_change_detection(),
+            // Everything showing a measure redraws with them
+            preferences.units.bypass_change_detection(),
+            &mut units_edited,
+            &mut invoked_actions,
+        );
+    }
+
+    elements.edits.write_batch(element_edits);
+    elements.system_commands.write_batch(system_commands);
+    clash.commands.write_batch(clash_commands);
+    timeline.commands.write_batch(timeline_commands);
+    if sequence_edited {
+        timeline.sequence.set_changed();
+    }
+    if timeline_edited {
+        timeline.timeline.set_changed();
+    }
+    if cost_schedule_edited {
+        cost.schedule.set_changed();
+    }
+    schedule.edits.write_batch(schedule_edits);
+    schedule.exchange_commands.write_batch(exchange_commands);
+    if schedules_edited {
+        schedule.schedules.set_changed();
+    }
+    sheet.commands.write_batch(sheet_commands);
+    if sheets_edited {
+        sheet.sheets.set_changed();
+    }
+    cad.commands.write_batch(cad_commands);
+    if underlays_edited {
+        cad.underlays.set_changed();
+    }
+    model.commands.write_batch(model_commands);
+    asset.commands.write_batch(asset_commands);
+    if asset_browser_edited {
+        asset.browser.set_changed();
+    }
+    tools.commands.write_batch(tool_commands);
+    actions.invoked.write_batch(invoked_actions);
+    if keymap_edited {
+        actions.keymap.set_changed();
+    }
+    if preferences_edited {
+        preferences.preferences.set_changed();
+    }
+    if units_edited {
+        preferences.units.set_changed();
+    }
+    if tool_options_edited {
+        tools.options.set_changed();
+    }
+
     let flattened = flatten_tab_stacks(&mut dock.tree);
 
     if flattened {
@@ -75,9 +377,20 @@ pub fn dock_ui_system(
     let new_viewports = collect_visible_viewports(&dock.tree);
 
     if visible_viewports.rects != new_viewports {
+        visible_viewports.overlays = new_viewports.values().copied().map(view_bar_rect).collect();
         visible_viewports.rects = new_viewports;
     }
 
+    // Panels are on the background layer with the viewports, popups and windows are above it
+    let pointer_over_ui = ctx.is_using_pointer()
+        || ctx
+            .pointer_latest_pos()
+            .and_then(|pos| ctx.layer_id_at(pos))
+            .is_some_and(|layer| layer.order != egui::Order::Background);
+    if visible_viewports.pointer_over_ui != pointer_over_ui {
+        visible_viewports.pointer_over_ui = pointer_over_ui;
+    }
+
     if pointer_busy {
         ctx.request_repaint();
     }
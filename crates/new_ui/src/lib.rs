@@ -1,17 +1,33 @@
 use bevy::prelude::*;
 use bevy_egui::{EguiPrimaryContextPass, EguiStartupSet};
 
+use new_core::preferences::{Preferences, PreferencesWindow};
+use new_core::units::ProjectUnits;
+
+use crate::actions::{apply_ui_actions, register_ui_actions};
 use crate::dock::{dock_ui_system, setup_dock };
+use crate::preferences::apply_appearance;
 pub struct UIPlugin;
 
+pub mod actions;
 pub mod dock;
+pub mod menubar;
+pub mod palette;
 pub mod pane;
+pub mod preferences;
 pub mod utils;
+pub mod toolbar;
 pub mod tree;
+pub mod units;
 
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_dock)
-           .add_systems(EguiPrimaryContextPass, dock_ui_system);
+        register_ui_actions(app);
+        app.init_resource::<Preferences>()
+           .init_resource::<PreferencesWindow>()
+           .init_resource::<ProjectUnits>()
+           .add_systems(Startup, setup_dock)
+           .add_systems(Update, apply_ui_actions)
+           .add_systems(EguiPrimaryContextPass, (apply_appearance, dock_ui_system).chain());
     }
 }
\ No newline at end of file
@@ -0,0 +1,28 @@
+use bevy_egui::egui;
+
+use new_core::action::{ActionInvoked, ActionRegistry};
+use new_core::keymap::Keymap;
+
+// One menu per action menu, entries with a prompt ask for it in the palette
+pub fn show(ui: &mut egui::Ui, registry: &ActionRegistry, keymap: &Keymap, invoked: &mut Vec<ActionInvoked>) {
+    egui::MenuBar::new().ui(ui, |ui| {
+        for menu in registry.menus() {
+            ui.menu_button(menu, |ui| {
+                for action in registry.actions.iter().filter(|action| action.menu == menu) {
+                    let label = match action.prompt {
+                        Some(_) => format!("{}…", action.label),
+                        None => action.label.to_owned(),
+                    };
+                    let mut button = egui::Button::new(label);
+                    if let Some(binding) = keymap.bindings(action).first() {
+                        button = button.shortcut_text(binding.to_string());
+                    }
+                    if ui.add(button).clicked() {
+                        invoked.push(ActionInvoked::new(action.id));
+                        ui.close();
+                    }
+                }
+            });
+        }
+    });
+}
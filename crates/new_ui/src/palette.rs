@@ -0,0 +1,206 @@
+use bevy_egui::egui;
+
+use new_core::action::{ActionDef, ActionHistory, ActionInvoked, ActionRegistry, CommandPalette, fuzzy_score};
+use new_core::keymap::Keymap;
+
+const MAX_ROWS: usize = 12;
+// Score added for the most recent action, one less for each older one
+const RECENT_BONUS: i32 = 10;
+
+enum Row<'a> {
+    Action(&'a ActionDef),
+    Choice(&'a str),
+}
+
+// Searchable list of every action. An action with a prompt asks for its argument in the same
+// box before it runs.
+pub fn show(
+    ctx: &egui::Context,
+    palette: &mut CommandPalette,
+    registry: &ActionRegistry,
+    history: &ActionHistory,
+    keymap: &Keymap,
+    invoked: &mut Vec<ActionInvoked>,
+) {
+    if !palette.open {
+        return;
+    }
+
+    let prompting = palette.prompting.and_then(|id| registry.get(id));
+    let prompt = prompting.and_then(|def| def.prompt.as_ref());
+    // The key that opened the palette may have typed into it already
+    palette.query = palette.query.trim_start().to_owned();
+
+    let rows: Vec<Row> = match prompt {
+        Some(prompt) => ranked(&palette.query, prompt.choices.iter(), String::clone, |_| None)
+            .into_iter()
+            .map(|choice| Row::Choice(choice))
+            .collect(),
+        None => ranked(
+            &palette.query,
+            registry.actions.iter(),
+            |action| format!("{} {}", action.menu, action.label),
+            |action| history.rank(action.id),
+        )
+        .into_iter()
+        .map(Row::Action)
+        .collect(),
+    };
+    // Free text prompts take whatever was typed
+    let free_text = prompt.is_some_and(|prompt| prompt.choices.is_empty());
+
+    let (down, up, enter, escape) = ctx.input_mut(|input| {
+        (
+            input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            input.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            input.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        )
+    });
+    let last = rows.len().min(MAX_ROWS).saturating_sub(1);
+    if down {
+        palette.selected += 1;
+    }
+    if up {
+        palette.selected = palette.selected.saturating_sub(1);
+    }
+    palette.selected = palette.selected.min(last);
+
+    let mut chosen = enter.then_some(palette.selected);
+    let mut close = escape;
+
+    let window = egui::Window::new("Command Palette")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .fixed_size(egui::vec2(480.0, 0.0))
+        .show(ctx, |ui| {
+            if let (Some(def), Some(prompt)) = (prompting, prompt) {
+                ui.label(format!("{}: {}", def.label, prompt.label));
+            }
+            let hint = match prompt {
+                Some(_) if free_text => "Type and press Enter",
+                Some(_) => "🔍 Search choices",
+                None => "🔍 Search commands",
+            };
+            let search = ui.add(
+                egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text(hint)
+                    .desired_width(f32::INFINITY),
+            );
+            search.request_focus();
+            if search.changed() {
+                palette.selected = 0;
+            }
+            ui.separator();
+
+            for (index, row) in rows.iter().enumerate().take(MAX_ROWS) {
+                if row_ui(ui, row, index == palette.selected, history, keymap).clicked() {
+                    chosen = Some(index);
+                }
+            }
+            if rows.is_empty() && !free_text {
+                ui.weak("Nothing matches");
+            }
+        });
+
+    // Clicking anywhere else closes it
+    if let Some(window) = window
+        && ctx.input(|input| input.pointer.any_pressed())
+        && ctx
+            .pointer_interact_pos()
+            .is_some_and(|pointer| !window.response.rect.contains(pointer))
+    {
+        close = true;
+    }
+
+    if free_text && enter {
+        let text = palette.query.trim().to_owned();
+        if let Some(def) = prompting
+            && !text.is_empty()
+        {
+            invoked.push(ActionInvoked {
+                id: def.id,
+                arg: Some(text),
+            });
+        }
+        close = true;
+    } else if let Some(row) = chosen.and_then(|index| rows.get(index)) {
+        match row {
+            // Asks for the argument next, in the same box
+            Row::Action(def) if def.prompt.is_some() => palette.show(Some(def.id)),
+            Row::Action(def) => {
+                invoked.push(ActionInvoked::new(def.id));
+                close = true;
+            }
+            Row::Choice(choice) => {
+                if let Some(def) = prompting {
+                    invoked.push(ActionInvoked {
+                        id: def.id,
+                        arg: Some((*choice).to_owned()),
+                    });
+                }
+                close = true;
+            }
+        }
+    }
+
+    if close {
+        *palette = CommandPalette::default();
+    }
+}
+
+// Best match first. Without a query recently used come first, the rest in registry order.
+fn ranked<'a, T>(
+    query: &str,
+    items: impl Iterator<Item = &'a T>,
+    text: impl Fn(&T) -> String,
+    recent: impl Fn(&T) -> Option<usize>,
+) -> Vec<&'a T> {
+    let mut scored: Vec<(i32, usize, &T)> = items
+        .enumerate()
+        .filter_map(|(order, item)| {
+            let score = fuzzy_score(query, &text(item))?;
+            let bonus = recent(item).map_or(0, |rank| RECENT_BONUS - rank as i32);
+            Some((score + bonus, order, item))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, _, item)| item).collect()
+}
+
+fn row_ui(ui: &mut egui::Ui, row: &Row, selected: bool, history: &ActionHistory, keymap: &Keymap) -> egui::Response {
+    let (label, detail, binding) = match row {
+        Row::Action(def) => {
+            let label = match def.prompt {
+                Some(_) => format!("{}…", def.label),
+                None => def.label.to_owned(),
+            };
+            let detail = match history.rank(def.id) {
+                Some(_) => format!("{} · recent", def.menu),
+                None => def.menu.to_owned(),
+            };
+            let binding = keymap
+                .bindings(def)
+                .iter()
+                .map(|binding| binding.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            (label, detail, binding)
+        }
+        Row::Choice(choice) => ((*choice).to_owned(), String::new(), String::new()),
+    };
+
+    ui.horizontal(|ui| {
+        let response = ui.selectable_label(selected, label);
+        if selected {
+            response.scroll_to_me(None);
+        }
+        ui.weak(detail);
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.weak(binding);
+        });
+        response
+    })
+    .inner
+}
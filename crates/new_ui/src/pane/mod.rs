@@ -1,3 +1,13 @@
 pub mod pane_properties;
 pub mod pane_console;
-pub mod pane_viewport;
\ No newline at end of file
+pub mod pane_viewport;
+pub mod pane_assets;
+pub mod pane_cad;
+pub mod pane_models;
+pub mod pane_keymap;
+pub mod pane_clashes;
+pub mod pane_systems;
+pub mod pane_timeline;
+pub mod pane_costs;
+pub mod pane_schedules;
+pub mod pane_sheets;
\ No newline at end of file
@@ -0,0 +1,202 @@
+use bevy_egui::egui;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+use new_core::anchor::{AnchorStrategy, PlacementSettings};
+use new_core::asset_library::{AssetBrowser, AssetCommand, AssetResults, LibraryAssetSummary};
+use new_core::model_exchange::ImportAs;
+
+use crate::utils::paint_opaque_pane_background;
+
+const TILE_SIZE: f32 = 84.0;
+
+// Returns true when the search changed so the results get fetched again
+pub fn show(
+    ui: &mut egui::Ui,
+    browser: &mut AssetBrowser,
+    results: &AssetResults,
+    placement: &mut PlacementSettings,
+    commands: &mut Vec<AssetCommand>,
+) -> bool {
+    paint_opaque_pane_background(ui);
+
+    let edited = search_bar(ui, browser, results);
+    placement_bar(ui, placement);
+    ui.separator();
+
+    egui::ScrollArea::vertical()
+        .id_salt("assets_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            add_section(ui, browser, commands);
+            if let Some(status) = &browser.status {
+                ui.label(status);
+            }
+            ui.separator();
+
+            if results.assets.is_empty() {
+                ui.label("No assets found. Add selected elements or import a glTF file.");
+            } else if results.total > results.assets.len() {
+                ui.weak(format!(
+                    "Showing {} of {} assets, narrow the search to see the rest",
+                    results.assets.len(),
+                    results.total
+                ));
+            }
+
+            ui.horizontal_wrapped(|ui| {
+                for asset in &results.assets {
+                    asset_tile(ui, asset, commands);
+                }
+            });
+        });
+
+    edited
+}
+
+fn search_bar(ui: &mut egui::Ui, browser: &mut AssetBrowser, results: &AssetResults) -> bool {
+    let mut edited = false;
+
+    ui.horizontal(|ui| {
+        edited |= ui
+            .add(
+                egui::TextEdit::singleline(&mut browser.search)
+                    .hint_text("🔍 Search")
+                    .desired_width(180.0),
+            )
+            .changed();
+
+        egui::ComboBox::from_id_salt("assets_category")
+            .selected_text(browser.category.as_deref().unwrap_or("All categories"))
+            .show_ui(ui, |ui| {
+                edited |= ui
+                    .selectable_value(&mut browser.category, None, "All categories")
+                    .changed();
+                for category in &results.categories {
+                    edited |= ui
+                        .selectable_value(&mut browser.category, Some(category.clone()), category)
+                        .changed();
+                }
+            });
+    });
+
+    edited
+}
+
+fn placement_bar(ui: &mut egui::Ui, placement: &mut PlacementSettings) {
+    ui.horizontal(|ui| {
+        ui.label("Place with");
+        egui::ComboBox::from_id_salt("assets_anchor")
+            .selected_text(placement.strategy.to_string())
+            .show_ui(ui, |ui| {
+                for strategy in AnchorStrategy::iter() {
+                    ui.selectable_value(&mut placement.strategy, strategy, strategy.to_string())
+                        .on_hover_text(strategy.description());
+                }
+            })
+            .response
+            .on_hover_text(placement.strategy.description());
+        ui.weak("Drag an asset onto a viewport");
+    });
+}
+
+fn add_section(ui: &mut egui::Ui, browser: &mut AssetBrowser, commands: &mut Vec<AssetCommand>) {
+    egui::CollapsingHeader::new("Add to Library")
+        .id_salt("assets_add")
+        .show(ui, |ui| {
+            egui::Grid::new("assets_add_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Category");
+                ui.add(
+                    egui::TextEdit::singleline(&mut browser.new_category)
+                        .hint_text("Furniture")
+                        .desired_width(200.0),
+                );
+                ui.end_row();
+
+                ui.label("glTF");
+                ui.add(
+                    egui::TextEdit::singleline(&mut browser.import_path)
+                        .hint_text("chair.glb")
+                        .desired_width(200.0),
+                );
+                ui.end_row();
+
+                ui.label("As");
+                egui::ComboBox::from_id_salt("assets_import_as")
+                    .selected_text(browser.import_as.to_string())
+                    .show_ui(ui, |ui| {
+                        for import_as in ImportAs::iter() {
+                            ui.selectable_value(&mut browser.import_as, import_as, import_as.to_string());
+                        }
+                    });
+                ui.end_row();
+            });
+
+            let category = browser.new_category.trim();
+            let category = if category.is_empty() { "General" } else { category }.to_owned();
+
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Add Selection")
+                    .on_hover_text("Every selected element becomes an asset")
+                    .clicked()
+                {
+                    commands.push(AssetCommand::AddSelection {
+                        category: category.clone(),
+                    });
+                }
+
+                let path = PathBuf::from(browser.import_path.trim());
+                let is_gltf = path
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| {
+                        extension.eq_ignore_ascii_case("gltf") || extension.eq_ignore_ascii_case("glb")
+                    });
+                if ui.add_enabled(is_gltf, egui::Button::new("Import glTF")).clicked() {
+                    commands.push(AssetCommand::ImportGltf {
+                        path,
+                        category,
+                        import_as: browser.import_as,
+                    });
+                }
+            });
+        });
+}
+
+// Drag the tile onto a viewport to place an instance
+fn asset_tile(ui: &mut egui::Ui, asset: &LibraryAssetSummary, commands: &mut Vec<AssetCommand>) {
+    let response = ui
+        .dnd_drag_source(egui::Id::new(("asset_tile", asset.id.0)), asset.id, |ui| {
+            ui.vertical(|ui| {
+                ui.set_width(TILE_SIZE);
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(TILE_SIZE, TILE_SIZE), egui::Sense::hover());
+                paint_thumbnail(ui, rect, asset);
+                ui.add(egui::Label::new(egui::RichText::new(&asset.name).small()).truncate());
+            });
+        })
+        .response
+        .on_hover_text(format!("{}\n{} · {}", asset.name, asset.category, asset.kind));
+
+    response.context_menu(|ui| {
+        if ui.button("Delete from Library").clicked() {
+            commands.push(AssetCommand::Delete(asset.id));
+            ui.close();
+        }
+    });
+}
+
+fn paint_thumbnail(ui: &egui::Ui, rect: egui::Rect, asset: &LibraryAssetSummary) {
+    let painter = ui.painter();
+    painter.rect_filled(rect, 3.0, ui.visuals().extreme_bg_color);
+
+    let inner = rect.shrink(6.0);
+    let stroke = egui::Stroke::new(1.0, ui.visuals().text_color());
+    for polyline in &asset.thumbnail.polylines {
+        let points = polyline
+            .iter()
+            .map(|point| egui::pos2(inner.left() + point.x * inner.width(), inner.bottom() - point.y * inner.height()))
+            .collect();
+        painter.add(egui::Shape::line(points, stroke));
+    }
+}
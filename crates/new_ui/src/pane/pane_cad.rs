@@ -0,0 +1,224 @@
+use bevy_egui::egui;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+use new_core::cad::{CadCommand, CadExchange, CadUnits, Underlay, Underlays};
+use new_core::element::ElementId;
+use new_core::phase::{Phase, PhaseFilter};
+use new_core::units::{Measure, ProjectUnits};
+
+use crate::units::measure_drag;
+use crate::utils::paint_opaque_pane_background;
+
+// Returns true when an underlay was edited so its meshes get updated
+pub fn show(
+    ui: &mut egui::Ui,
+    underlays: &mut Underlays,
+    exchange: &mut CadExchange,
+    commands: &mut Vec<CadCommand>,
+    units: &ProjectUnits,
+) -> bool {
+    paint_opaque_pane_background(ui);
+
+    let mut edited = false;
+
+    egui::ScrollArea::vertical()
+        .id_salt("cad_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            import_section(ui, exchange, commands, units);
+            export_section(ui, exchange, commands, units);
+
+            if let Some(status) = &exchange.status {
+                ui.label(status);
+            }
+            ui.separator();
+
+            if underlays.underlays.is_empty() {
+                ui.label("No underlays. Import a DXF to trace over it.");
+            }
+            for underlay in &mut underlays.underlays {
+                ui.push_id(("underlay", underlay.id.0), |ui| {
+                    edited |= underlay_editor(ui, underlay, commands, units);
+                });
+            }
+        });
+
+    edited
+}
+
+fn dxf_path(text: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(text.trim());
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("dxf"))
+        .then_some(path)
+}
+
+fn import_section(ui: &mut egui::Ui, exchange: &mut CadExchange, commands: &mut Vec<CadCommand>, units: &ProjectUnits) {
+    egui::CollapsingHeader::new("Import Underlay")
+        .id_salt("cad_import")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("cad_import_grid").num_columns(2).show(ui, |ui| {
+                ui.label("DXF");
+                ui.add(
+                    egui::TextEdit::singleline(&mut exchange.import_path)
+                        .hint_text("survey.dxf")
+                        .desired_width(200.0),
+                );
+                ui.end_row();
+
+                ui.label("Units");
+                egui::ComboBox::from_id_salt("cad_units")
+                    .selected_text(exchange.units.map_or("From file".to_owned(), |units| units.to_string()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut exchange.units, None, "From file");
+                        for units in CadUnits::iter() {
+                            ui.selectable_value(&mut exchange.units, Some(units), units.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Level");
+                ui.horizontal(|ui| {
+                    let mut on_level = exchange.level_id.is_some();
+                    if ui.checkbox(&mut on_level, "").changed() {
+                        exchange.level_id = on_level.then_some(ElementId(0));
+                    }
+                    if let Some(level) = &mut exchange.level_id {
+                        ui.add(egui::DragValue::new(&mut level.0).prefix("#"));
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Elevation");
+                ui.add(measure_drag(&mut exchange.elevation, Measure::Length, units).speed(0.05));
+                ui.end_row();
+            });
+
+            let path = dxf_path(&exchange.import_path);
+            if ui.add_enabled(path.is_some(), egui::Button::new("Import")).clicked()
+                && let Some(path) = path
+            {
+                commands.push(CadCommand::Import {
+                    path,
+                    units: exchange.units,
+                    level_id: exchange.level_id,
+                    elevation: exchange.elevation,
+                });
+            }
+        });
+}
+
+fn export_section(ui: &mut egui::Ui, exchange: &mut CadExchange, commands: &mut Vec<CadCommand>, units: &ProjectUnits) {
+    egui::CollapsingHeader::new("Export Plan")
+        .id_salt("cad_export")
+        .show(ui, |ui| {
+            egui::Grid::new("cad_export_grid").num_columns(2).show(ui, |ui| {
+                ui.label("DXF");
+                ui.add(
+                    egui::TextEdit::singleline(&mut exchange.export_path)
+                        .hint_text("plan.dxf")
+                        .desired_width(200.0),
+                );
+                ui.end_row();
+
+                ui.label("Cut");
+                ui.add(measure_drag(&mut exchange.cut_height, Measure::Length, units).speed(0.05));
+                ui.end_row();
+
+                ui.label("Phase");
+                ui.horizontal(|ui| {
+                    let phase = &mut exchange.phase;
+                    egui::ComboBox::from_id_salt("cad_phase")
+                        .selected_text(phase.phase.to_string())
+                        .show_ui(ui, |ui| {
+                            for option in Phase::iter() {
+                                ui.selectable_value(&mut phase.phase, option, option.to_string());
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("cad_phase_filter")
+                        .selected_text(phase.filter.to_string())
+                        .show_ui(ui, |ui| {
+                            for filter in PhaseFilter::iter() {
+                                ui.selectable_value(&mut phase.filter, filter, filter.to_string());
+                            }
+                        });
+                });
+                ui.end_row();
+            });
+
+            let path = dxf_path(&exchange.export_path);
+            if ui.add_enabled(path.is_some(), egui::Button::new("Export")).clicked()
+                && let Some(path) = path
+            {
+                commands.push(CadCommand::ExportPlan {
+                    path,
+                    cut_height: exchange.cut_height,
+                    phase: exchange.phase,
+                });
+            }
+        });
+}
+
+// Returns true when the underlay was edited
+fn underlay_editor(
+    ui: &mut egui::Ui,
+    underlay: &mut Underlay,
+    commands: &mut Vec<CadCommand>,
+    units: &ProjectUnits,
+) -> bool {
+    let mut edited = false;
+
+    ui.horizontal(|ui| {
+        edited |= ui.checkbox(&mut underlay.visible, "").changed();
+        ui.strong(&underlay.name);
+        if let Some(level) = underlay.level_id {
+            ui.label(format!("Level #{}", level.0));
+        }
+        edited |= ui
+            .add(measure_drag(&mut underlay.elevation, Measure::Length, units).speed(0.05))
+            .changed();
+        ui.label("🔒").on_hover_text("Underlays are locked, they can be snapped to but not selected");
+        if ui.small_button("✖").clicked() {
+            commands.push(CadCommand::Remove(underlay.id));
+        }
+    });
+
+    egui::CollapsingHeader::new(format!("Layers ({})", underlay.drawing.layers.len()))
+        .id_salt("underlay_layers")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for (label, visible) in [("All", true), ("None", false)] {
+                    if ui.small_button(label).clicked() {
+                        for layer in &mut underlay.drawing.layers {
+                            layer.visible = visible;
+                        }
+                        edited = true;
+                    }
+                }
+            });
+
+            let mut counts = vec![0; underlay.drawing.layers.len()];
+            for item in &underlay.drawing.items {
+                counts[item.layer] += 1;
+            }
+
+            egui::Grid::new("underlay_layers_grid").num_columns(3).show(ui, |ui| {
+                for (layer, count) in underlay.drawing.layers.iter_mut().zip(counts) {
+                    let [r, g, b] = layer.color;
+                    edited |= ui.checkbox(&mut layer.visible, "").changed();
+                    ui.horizontal(|ui| {
+                        let (swatch, _) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                        ui.painter().rect_filled(swatch, 1.0, egui::Color32::from_rgb(r, g, b));
+                        ui.label(&layer.name);
+                    });
+                    ui.weak(count.to_string());
+                    ui.end_row();
+                }
+            });
+        });
+
+    ui.separator();
+    edited
+}
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use bevy_egui::egui;
+use strum::IntoEnumIterator;
+
+use new_core::clash::{
+    Clash, ClashCommand, ClashComment, ClashGrouping, ClashKey, ClashResults, ClashReview,
+    ClashRules, ClashStatus, ClashTest,
+};
+use new_core::units::{Measure, ProjectUnits};
+
+use crate::utils::paint_opaque_pane_background;
+
+pub fn show(
+    ui: &mut egui::Ui,
+    results: &mut ClashResults,
+    rules: &ClashRules,
+    review: &mut ClashReview,
+    commands: &mut Vec<ClashCommand>,
+    units: &ProjectUnits,
+) {
+    paint_opaque_pane_background(ui);
+
+    ui.heading("Clashes");
+    ui.horizontal(|ui| {
+        for status in ClashStatus::iter() {
+            ui.colored_label(
+                status_color(status),
+                format!("{status}: {}", results.count(status)),
+            );
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Group by:");
+        egui::ComboBox::from_id_salt("clash_grouping")
+            .selected_text(review.grouping.to_string())
+            .show_ui(ui, |ui| {
+                for grouping in ClashGrouping::iter() {
+                    ui.selectable_value(&mut review.grouping, grouping, grouping.to_string());
+                }
+            });
+
+        ui.checkbox(&mut review.show_new, "New");
+        ui.checkbox(&mut review.show_active, "Active");
+        ui.checkbox(&mut review.show_resolved, "Resolved");
+
+        if ui.button("Clear Isolation").clicked() {
+            commands.push(ClashCommand::ClearIsolation);
+        }
+    });
+
+    ui.separator();
+
+    let groups = group_clashes(results, rules, review);
+    let list_height = if review.selected.is_some() {
+        ui.available_height() * 0.55
+    } else {
+        ui.available_height()
+    };
+
+    egui::ScrollArea::vertical()
+        .id_salt("clash_list")
+        .max_height(list_height)
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            if groups.is_empty() {
+                ui.label("No clashes match the current filter.");
+            }
+
+            for (group, keys) in &groups {
+                egui::CollapsingHeader::new(format!("{group} ({})", keys.len()))
+                    .id_salt(("clash_group", group))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for key in keys {
+                            let clash = &results.clashes[key];
+                            let text = egui::RichText::new(clash_label(clash, units))
+                                .color(status_color(clash.status));
+
+                            if ui
+                                .selectable_label(review.selected == Some(*key), text)
+                                .clicked()
+                            {
+                                review.selected = Some(*key);
+                                commands.push(ClashCommand::Focus(*key));
+                            }
+                        }
+                    });
+            }
+        });
+
+    if let Some(key) = review.selected {
+        ui.separator();
+        details(ui, results, rules, review, commands, key, units);
+    }
+}
+
+fn details(
+    ui: &mut egui::Ui,
+    results: &mut ClashResults,
+    rules: &ClashRules,
+    review: &mut ClashReview,
+    commands: &mut Vec<ClashCommand>,
+    key: ClashKey,
+    units: &ProjectUnits,
+) {
+    let Some(clash) = results.clashes.get_mut(&key) else {
+        review.selected = None;
+        return;
+    };
+
+    ui.strong(clash_label(clash, units));
+
+    egui::Grid::new("clash_details")
+        .num_columns(2)
+        .spacing([8.0, 2.0])
+        .show(ui, |ui| {
+            ui.label("Rule");
+            ui.label(rule_name(rules, key));
+            ui.end_row();
+
+            ui.label("Test");
+            ui.label(match clash.test {
+                ClashTest::Hard => "Hard",
+                ClashTest::Clearance => "Clearance",
+            });
+            ui.end_row();
+
+            ui.label(match clash.test {
+                ClashTest::Hard => "Penetration",
+                ClashTest::Clearance => "Gap",
+            });
+            ui.label(units.format(Measure::Length, f64::from(clash.distance)));
+            ui.end_row();
+
+            ui.label("Location");
+            ui.label(
+                clash
+                    .point
+                    .to_array()
+                    .map(|coordinate| units.format(Measure::Length, f64::from(coordinate)))
+                    .join(", "),
+            );
+            ui.end_row();
+
+            ui.label("Status");
+            egui::ComboBox::from_id_salt("clash_status")
+                .selected_text(clash.status.to_string())
+                .show_ui(ui, |ui| {
+                    for status in ClashStatus::iter() {
+                        if ui.selectable_value(&mut clash.status, status, status.to_string()).clicked() {
+                            clash.reviewed = true;
+                        }
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Assignee");
+            let assignee = clash.assignee.get_or_insert_with(String::new);
+            ui.text_edit_singleline(assignee);
+            if assignee.trim().is_empty() {
+                clash.assignee = None;
+            }
+            ui.end_row();
+        });
+
+    ui.horizontal(|ui| {
+        if ui.button("Zoom To").clicked() {
+            commands.push(ClashCommand::Focus(key));
+        }
+        if clash.status != ClashStatus::Resolved && ui.button("Resolve").clicked() {
+            clash.status = ClashStatus::Resolved;
+            clash.reviewed = true;
+        }
+    });
+
+    ui.label("Comments");
+    egui::ScrollArea::vertical()
+        .id_salt("clash_comments")
+        .max_height(120.0)
+        .show(ui, |ui| {
+            for comment in &clash.comments {
+                ui.horizontal_wrapped(|ui| {
+                    ui.strong(&comment.author);
+                    ui.label(&comment.text);
+                });
+            }
+        });
+
+    ui.horizontal(|ui| {
+        let response = ui.text_edit_singleline(&mut review.comment_draft);
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+        if (ui.button("Add").clicked() || submitted) && !review.comment_draft.trim().is_empty() {
+            clash.comments.push(ClashComment {
+                author: review.reviewer.clone(),
+                text: std::mem::take(&mut review.comment_draft).trim().to_owned(),
+            });
+        }
+    });
+}
+
+fn group_clashes(
+    results: &ClashResults,
+    rules: &ClashRules,
+    review: &ClashReview,
+) -> BTreeMap<String, Vec<ClashKey>> {
+    let mut groups: BTreeMap<String, Vec<ClashKey>> = BTreeMap::new();
+
+    for clash in results.clashes.values() {
+        if !review.shows(clash.status) {
+            continue;
+        }
+
+        let group = match review.grouping {
+            ClashGrouping::Rule => rule_name(rules, clash.key),
+            ClashGrouping::Level => match clash.level_id {
+                Some(level) => format!("Level #{}", level.0),
+                None => "No Level".to_owned(),
+            },
+            ClashGrouping::ElementPair => format!("#{} ↔ #{}", clash.a_id.0, clash.b_id.0),
+        };
+
+        groups.entry(group).or_default().push(clash.key);
+    }
+
+    // Most severe first, unresolved before resolved
+    for keys in groups.values_mut() {
+        keys.sort_by(|a, b| {
+            let (a, b) = (&results.clashes[a], &results.clashes[b]);
+            status_rank(a.status)
+                .cmp(&status_rank(b.status))
+                .then(b.distance.total_cmp(&a.distance))
+        });
+    }
+
+    groups
+}
+
+fn clash_label(clash: &Clash, units: &ProjectUnits) -> String {
+    format!(
+        "#{} ↔ #{}   {}   {}",
+        clash.a_id.0,
+        clash.b_id.0,
+        units.format(Measure::Length, f64::from(clash.distance)),
+        clash.assignee.as_deref().unwrap_or("")
+    )
+}
+
+fn rule_name(rules: &ClashRules, key: ClashKey) -> String {
+    rules
+        .get(key.rule)
+        .map(|rule| rule.name.clone())
+        .unwrap_or_else(|| format!("Rule {}", key.rule.0))
+}
+
+fn status_rank(status: ClashStatus) -> u8 {
+    match status {
+        ClashStatus::New => 0,
+        ClashStatus::Active => 1,
+        ClashStatus::Resolved => 2,
+    }
+}
+
+fn status_color(status: ClashStatus) -> egui::Color32 {
+    match status {
+        ClashStatus::New => egui::Color32::from_rgb(230, 90, 80),
+        ClashStatus::Active => egui::Color32::from_rgb(235, 170, 60),
+        ClashStatus::Resolved => egui::Color32::from_rgb(110, 190, 110),
+    }
+}
@@ -0,0 +1,215 @@
+use bevy_egui::egui;
+use strum::IntoEnumIterator;
+
+use new_core::cost::{CostEstimate, CostGrouping, CostSchedule, CostView, QuantityBasis};
+use new_core::element::ElementId;
+use new_core::elements::ElementKind;
+use new_core::units::ProjectUnits;
+
+use crate::utils::paint_opaque_pane_background;
+
+// Returns true when the schedule was edited so the estimate gets repriced
+pub fn show(
+    ui: &mut egui::Ui,
+    estimate: &CostEstimate,
+    schedule: &mut CostSchedule,
+    view: &mut CostView,
+    units: &ProjectUnits,
+) -> bool {
+    paint_opaque_pane_background(ui);
+
+    ui.heading(&schedule.name);
+    ui.horizontal(|ui| {
+        ui.strong(format!("Total: {}", money(estimate.total(), &schedule.currency)));
+        ui.label(format!("{} elements", estimate.lines.len()));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Group by:");
+        egui::ComboBox::from_id_salt("cost_grouping")
+            .selected_text(view.grouping.to_string())
+            .show_ui(ui, |ui| {
+                for grouping in CostGrouping::iter() {
+                    ui.selectable_value(&mut view.grouping, grouping, grouping.to_string());
+                }
+            });
+    });
+
+    ui.separator();
+
+    egui::ScrollArea::vertical()
+        .id_salt("cost_rollup")
+        .max_height(ui.available_height() * 0.5)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            egui::Grid::new("cost_rollup_grid")
+                .num_columns(4)
+                .striped(true)
+                .spacing([12.0, 2.0])
+                .show(ui, |ui| {
+                    ui.strong("Group");
+                    ui.strong("Elements");
+                    ui.strong("Quantity");
+                    ui.strong("Cost");
+                    ui.end_row();
+
+                    for (group, rollup) in estimate.rollup(schedule, view.grouping) {
+                        ui.label(group);
+                        ui.label(rollup.elements.to_string());
+                        ui.label(match rollup.quantity() {
+                            Some((quantity, basis)) => match basis.measure() {
+                                Some(measure) => units.format(measure, quantity),
+                                None => format!("{quantity:.0} ea"),
+                            },
+                            None => "-".to_owned(),
+                        });
+                        ui.label(money(rollup.cost, &schedule.currency));
+                        ui.end_row();
+                    }
+                });
+        });
+
+    ui.separator();
+    cost_items(ui, estimate, schedule, units)
+}
+
+fn cost_items(ui: &mut egui::Ui, estimate: &CostEstimate, schedule: &mut CostSchedule, units: &ProjectUnits) -> bool {
+    let mut edited = false;
+
+    // Kinds in the model plus the ones already priced
+    let mut kinds: Vec<ElementKind> = estimate
+        .lines
+        .values()
+        .map(|line| line.kind)
+        .chain(schedule.items.iter().filter_map(|item| item.kind))
+        .collect();
+    kinds.sort_by_key(|kind| kind.to_string());
+    kinds.dedup();
+
+    // Same for specs, an item bound to a spec prices it ahead of its kind
+    let mut specs: Vec<ElementId> = estimate
+        .lines
+        .values()
+        .filter_map(|line| line.spec_id)
+        .chain(schedule.items.iter().filter_map(|item| item.spec))
+        .collect();
+    specs.sort();
+    specs.dedup();
+
+    ui.horizontal(|ui| {
+        ui.strong("Cost Items");
+        if ui.button("Add Item").clicked() {
+            schedule.add("", "New item", QuantityBasis::Count, 0.0, None);
+            edited = true;
+        }
+    });
+
+    let mut removed = None;
+
+    egui::ScrollArea::vertical()
+        .id_salt("cost_items")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("cost_items_grid")
+                .num_columns(7)
+                .striped(true)
+                .spacing([8.0, 2.0])
+                .show(ui, |ui| {
+                    ui.strong("Code");
+                    ui.strong("Description");
+                    ui.strong("Basis");
+                    ui.strong("Rate");
+                    ui.strong("Applies To");
+                    ui.strong("Spec");
+                    ui.end_row();
+
+                    for item in schedule.items.iter_mut() {
+                        edited |= ui
+                            .add(egui::TextEdit::singleline(&mut item.code).desired_width(60.0))
+                            .changed();
+                        edited |= ui
+                            .add(egui::TextEdit::singleline(&mut item.description).desired_width(140.0))
+                            .changed();
+
+                        let basis = item.basis;
+                        egui::ComboBox::from_id_salt(("cost_basis", item.id.0))
+                            .selected_text(item.basis.to_string())
+                            .show_ui(ui, |ui| {
+                                for basis in QuantityBasis::iter() {
+                                    ui.selectable_value(&mut item.basis, basis, basis.to_string());
+                                }
+                            });
+                        edited |= basis != item.basis;
+
+                        // Rates are kept per SI unit and edited per project unit
+                        let unit = item.basis.measure().map(|measure| units.unit(measure));
+                        let scale = unit.map_or(1.0, |unit| unit.scale);
+                        let mut rate = item.unit_rate * scale;
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut rate)
+                                    .range(0.0..=f64::MAX)
+                                    .speed(1.0)
+                                    .suffix(format!(" /{}", unit.map_or("ea", |unit| unit.abbreviation()))),
+                            )
+                            .changed()
+                        {
+                            item.unit_rate = rate / scale;
+                            edited = true;
+                        }
+
+                        let kind = item.kind;
+                        egui::ComboBox::from_id_salt(("cost_kind", item.id.0))
+                            .selected_text(item.kind.map_or("-".to_owned(), |kind| kind.to_string()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut item.kind, None, "-");
+                                for kind in &kinds {
+                                    ui.selectable_value(&mut item.kind, Some(*kind), kind.to_string());
+                                }
+                            });
+                        edited |= kind != item.kind;
+
+                        let spec = item.spec;
+                        egui::ComboBox::from_id_salt(("cost_spec", item.id.0))
+                            .selected_text(item.spec.map_or("-".to_owned(), |spec| format!("#{}", spec.0)))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut item.spec, None, "-");
+                                for spec in &specs {
+                                    ui.selectable_value(&mut item.spec, Some(*spec), format!("#{}", spec.0));
+                                }
+                            });
+                        edited |= spec != item.spec;
+
+                        if ui.small_button("✖").clicked() {
+                            removed = Some(item.id);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if let Some(id) = removed {
+        schedule.remove(id);
+        edited = true;
+    }
+
+    edited
+}
+
+pub fn money(amount: f64, currency: &str) -> String {
+    let whole = amount.abs().trunc() as u64;
+    let cents = ((amount.abs().fract() * 100.0).round() as u64).min(99);
+
+    // Thousands separators
+    let digits = whole.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(' ');
+        }
+        grouped.push(digit);
+    }
+
+    let sign = if amount < 0.0 { "-" } else { "" };
+    format!("{sign}{grouped}.{cents:02} {currency}")
+}
@@ -0,0 +1,254 @@
+use bevy_egui::egui;
+use strum::IntoEnumIterator;
+
+use new_core::action::{ActionDef, ActionRegistry};
+use new_core::keymap::{InputContext, KeyBinding, KeyConflict, KeyTarget, Keymap, KeymapEditor, KeymapPreset, Navigation};
+
+use crate::utils::paint_opaque_pane_background;
+
+// Preset, recorder, conflicts, then every binding by menu. Returns true when the keymap was
+// edited.
+pub fn show(ui: &mut egui::Ui, registry: &ActionRegistry, keymap: &mut Keymap, editor: &mut KeymapEditor) -> bool {
+    paint_opaque_pane_background(ui);
+    let mut edited = false;
+    let conflicts = keymap.conflicts(registry);
+
+    egui::ScrollArea::vertical()
+        .id_salt("keymap_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Preset");
+                egui::ComboBox::from_id_salt("keymap_preset")
+                    .selected_text(keymap.preset.to_string())
+                    .show_ui(ui, |ui| {
+                        for preset in KeymapPreset::iter() {
+                            if ui.selectable_label(keymap.preset == preset, preset.to_string()).clicked() {
+                                keymap.set_preset(preset);
+                                edited = true;
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text("Your own bindings stay when the preset changes");
+                ui.add(
+                    egui::TextEdit::singleline(&mut editor.filter)
+                        .hint_text("🔍 Search")
+                        .desired_width(160.0),
+                );
+            });
+
+            if editor.recording.is_some() {
+                edited |= recorder(ui, registry, keymap, editor);
+            }
+            conflict_list(ui, registry, &conflicts);
+            ui.separator();
+
+            edited |= navigation_section(ui, keymap, editor, &conflicts);
+            let filter = editor.filter.to_lowercase();
+            for menu in registry.menus() {
+                let actions: Vec<&ActionDef> = registry
+                    .actions
+                    .iter()
+                    .filter(|def| def.menu == menu && def.label.to_lowercase().contains(&filter))
+                    .collect();
+                if !actions.is_empty() {
+                    edited |= menu_section(ui, menu, &actions, keymap, editor, &conflicts);
+                }
+            }
+        });
+
+    edited
+}
+
+fn target_label(registry: &ActionRegistry, target: KeyTarget) -> String {
+    match target {
+        KeyTarget::Action(id) => registry.get(id).map_or_else(|| id.to_string(), |def| def.label.to_owned()),
+        KeyTarget::Navigation(navigation) => navigation.to_string(),
+    }
+}
+
+// Strokes land here from the app while it records, so they run nothing
+fn recorder(ui: &mut egui::Ui, registry: &ActionRegistry, keymap: &mut Keymap, editor: &mut KeymapEditor) -> bool {
+    let Some(target) = editor.recording else {
+        return false;
+    };
+    let mut edited = false;
+
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.label(format!("Press the keys for {}", target_label(registry, target)));
+        let recorded = KeyBinding::chord(editor.recorded.iter().copied());
+        match target {
+            KeyTarget::Action(_) if !editor.recorded.is_empty() => {
+                ui.strong(recorded.to_string());
+            }
+            // Navigation is held, only the last button counts
+            KeyTarget::Navigation(_) if !editor.recorded.is_empty() => {
+                ui.strong(editor.recorded.last().map(ToString::to_string).unwrap_or_default());
+            }
+            _ => {
+                ui.weak("Waiting for a key or mouse button…");
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if matches!(target, KeyTarget::Action(_)) {
+                egui::ComboBox::from_id_salt("keymap_record_context")
+                    .selected_text(editor.context.to_string())
+                    .show_ui(ui, |ui| {
+                        for context in InputContext::iter() {
+                            ui.selectable_value(&mut editor.context, context, context.to_string());
+                        }
+                    });
+            }
+            if ui.add_enabled(!editor.recorded.is_empty(), egui::Button::new("Keep")).clicked() {
+                match target {
+                    KeyTarget::Action(id) => {
+                        if let Some(def) = registry.get(id) {
+                            let mut bindings = keymap.bindings(def).to_vec();
+                            bindings.push(recorded.in_context(editor.context));
+                            keymap.custom.insert(id, bindings);
+                        }
+                    }
+                    KeyTarget::Navigation(navigation) => {
+                        if let Some(&stroke) = editor.recorded.last() {
+                            keymap.custom_navigation.insert(navigation, stroke);
+                        }
+                    }
+                }
+                editor.stop();
+                edited = true;
+            }
+            if ui.button("Again").clicked() {
+                editor.recorded.clear();
+            }
+            if ui.button("Cancel").clicked() {
+                editor.stop();
+            }
+        });
+    });
+
+    edited
+}
+
+fn conflict_list(ui: &mut egui::Ui, registry: &ActionRegistry, conflicts: &[KeyConflict]) {
+    if conflicts.is_empty() {
+        return;
+    }
+    let color = ui.visuals().error_fg_color;
+    egui::CollapsingHeader::new(egui::RichText::new(format!("⚠ {} conflicts", conflicts.len())).color(color))
+        .id_salt("keymap_conflicts")
+        .default_open(true)
+        .show(ui, |ui| {
+            for conflict in conflicts {
+                ui.label(format!(
+                    "{} ({}): {} and {}",
+                    conflict.binding,
+                    conflict.binding.context,
+                    target_label(registry, conflict.first),
+                    target_label(registry, conflict.second),
+                ));
+            }
+        });
+}
+
+fn row_label(ui: &mut egui::Ui, label: &str, target: KeyTarget, conflicts: &[KeyConflict]) {
+    if conflicts.iter().any(|conflict| conflict.involves(target)) {
+        ui.colored_label(ui.visuals().error_fg_color, label);
+    } else {
+        ui.label(label);
+    }
+}
+
+fn navigation_section(
+    ui: &mut egui::Ui,
+    keymap: &mut Keymap,
+    editor: &mut KeymapEditor,
+    conflicts: &[KeyConflict],
+) -> bool {
+    let mut edited = false;
+
+    egui::CollapsingHeader::new("Viewport Navigation")
+        .id_salt("keymap_navigation")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("keymap_navigation_grid").num_columns(3).striped(true).show(ui, |ui| {
+                for navigation in Navigation::iter() {
+                    let target = KeyTarget::Navigation(navigation);
+                    row_label(ui, &navigation.to_string(), target, conflicts);
+                    ui.monospace(keymap.navigation(navigation).to_string());
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Change").clicked() {
+                            editor.record(target, InputContext::Viewport);
+                        }
+                        if keymap.is_custom(target) && ui.small_button("Reset").clicked() {
+                            keymap.reset(target);
+                            edited = true;
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+
+    edited
+}
+
+fn menu_section(
+    ui: &mut egui::Ui,
+    menu: &str,
+    actions: &[&ActionDef],
+    keymap: &mut Keymap,
+    editor: &mut KeymapEditor,
+    conflicts: &[KeyConflict],
+) -> bool {
+    let mut edited = false;
+
+    egui::CollapsingHeader::new(menu)
+        .id_salt(("keymap_menu", menu))
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new(("keymap_menu_grid", menu)).num_columns(3).striped(true).show(ui, |ui| {
+                for def in actions {
+                    let target = KeyTarget::Action(def.id);
+                    row_label(ui, def.label, target, conflicts);
+
+                    let bindings = keymap.bindings(def).to_vec();
+                    let mut removed = None;
+                    ui.horizontal_wrapped(|ui| {
+                        for (index, binding) in bindings.iter().enumerate() {
+                            let text = match binding.context {
+                                InputContext::Global => binding.to_string(),
+                                context => format!("{binding} · {context}"),
+                            };
+                            if ui.small_button(text).on_hover_text("Click to remove").clicked() {
+                                removed = Some(index);
+                            }
+                        }
+                        if bindings.is_empty() {
+                            ui.weak("None");
+                        }
+                    });
+                    if let Some(index) = removed {
+                        let mut kept = bindings;
+                        kept.remove(index);
+                        keymap.custom.insert(def.id, kept);
+                        edited = true;
+                    }
+
+                    ui.horizontal(|ui| {
+                        if ui.small_button("+").on_hover_text("Add a binding").clicked() {
+                            editor.record(target, InputContext::Global);
+                        }
+                        if keymap.is_custom(target) && ui.small_button("Reset").clicked() {
+                            keymap.reset(target);
+                            edited = true;
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+
+    edited
+}
@@ -0,0 +1,108 @@
+use bevy_egui::egui;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+use new_core::model_exchange::{ExportScope, ImportAs, ModelCommand, ModelExchange};
+
+use crate::utils::paint_opaque_pane_background;
+
+pub fn show(ui: &mut egui::Ui, exchange: &mut ModelExchange, commands: &mut Vec<ModelCommand>) {
+    paint_opaque_pane_background(ui);
+
+    egui::ScrollArea::vertical()
+        .id_salt("models_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            import_section(ui, exchange, commands);
+            export_section(ui, exchange, commands);
+
+            if let Some(status) = &exchange.status {
+                ui.separator();
+                ui.label(status);
+            }
+        });
+}
+
+fn model_path(text: &str, extensions: &[&str]) -> Option<PathBuf> {
+    let path = PathBuf::from(text.trim());
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extensions.iter().any(|allowed| extension.eq_ignore_ascii_case(allowed)))
+        .then_some(path)
+}
+
+fn import_section(ui: &mut egui::Ui, exchange: &mut ModelExchange, commands: &mut Vec<ModelCommand>) {
+    egui::CollapsingHeader::new("Import glTF")
+        .id_salt("models_import")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("models_import_grid").num_columns(2).show(ui, |ui| {
+                ui.label("File");
+                ui.add(
+                    egui::TextEdit::singleline(&mut exchange.import_path)
+                        .hint_text("chair.glb")
+                        .desired_width(200.0),
+                );
+                ui.end_row();
+
+                ui.label("As");
+                egui::ComboBox::from_id_salt("models_import_as")
+                    .selected_text(exchange.import_as.to_string())
+                    .show_ui(ui, |ui| {
+                        for import_as in ImportAs::iter() {
+                            ui.selectable_value(&mut exchange.import_as, import_as, import_as.to_string());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Meshes exported from here keep their own kind");
+                ui.end_row();
+            });
+
+            let path = model_path(&exchange.import_path, &["gltf", "glb"]);
+            if ui.add_enabled(path.is_some(), egui::Button::new("Import")).clicked()
+                && let Some(path) = path
+            {
+                commands.push(ModelCommand::ImportGltf {
+                    path,
+                    import_as: exchange.import_as,
+                });
+            }
+        });
+}
+
+fn export_section(ui: &mut egui::Ui, exchange: &mut ModelExchange, commands: &mut Vec<ModelCommand>) {
+    egui::CollapsingHeader::new("Export")
+        .id_salt("models_export")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("models_export_grid").num_columns(2).show(ui, |ui| {
+                ui.label("File");
+                ui.add(
+                    egui::TextEdit::singleline(&mut exchange.export_path)
+                        .hint_text("model.glb or model.ifc")
+                        .desired_width(200.0),
+                );
+                ui.end_row();
+
+                ui.label("Scope");
+                ui.horizontal(|ui| {
+                    for scope in ExportScope::iter() {
+                        ui.radio_value(&mut exchange.scope, scope, scope.to_string());
+                    }
+                });
+                ui.end_row();
+            });
+
+            // The extension picks the format
+            let path = model_path(&exchange.export_path, &["glb", "ifc"]);
+            if ui.add_enabled(path.is_some(), egui::Button::new("Export")).clicked()
+                && let Some(path) = path
+            {
+                let scope = exchange.scope;
+                commands.push(match model_path(&exchange.export_path, &["ifc"]) {
+                    Some(_) => ModelCommand::ExportIfc { path, scope },
+                    None => ModelCommand::ExportGlb { path, scope },
+                });
+            }
+        });
+}
@@ -1,13 +1,352 @@
 use bevy_egui::egui;
+use strum::IntoEnumIterator;
+
+use new_core::anchor::AnchorStrategy;
+use new_core::cost::{CostEstimate, CostSchedule};
+use new_core::inspector::{ElementEdit, InspectedElement};
+use new_core::element::ParamValue;
+use new_core::mep::FlowDirection;
+use new_core::mep_system::system_name;
+use new_core::phase::Phase;
+use new_core::profile::{LENGTH_PARAMS, SECTION_AREA_PARAM};
+use new_core::room::{AREA_PARAM, GROSS_AREA_PARAM, PERIMETER_PARAM, VOLUME_PARAM};
+use new_core::structural_grid::{GridPoint, GridReference};
+use new_core::units::{Measure, ProjectUnits};
+
+use crate::pane::pane_costs::money;
+use crate::units::measure_drag;
 use crate::utils::paint_opaque_pane_background;
 
 pub fn show(
-    ui: &mut egui::Ui
+    ui: &mut egui::Ui,
+    inspected: &InspectedElement,
+    edits: &mut Vec<ElementEdit>,
+    estimate: &CostEstimate,
+    schedule: &CostSchedule,
+    units: &ProjectUnits,
 ) {
     paint_opaque_pane_background(ui);
 
     ui.heading("Properties");
-    ui.horizontal(|ui| {
-        ui.label("Filter:");
-    });
-}
\ No newline at end of file
+
+    let (Some(entity), Some(header)) = (inspected.entity, inspected.header.as_ref()) else {
+        ui.label("Nothing selected.");
+        return;
+    };
+
+    egui::Grid::new("properties_identity")
+        .num_columns(2)
+        .spacing([8.0, 2.0])
+        .show(ui, |ui| {
+            ui.label("Id");
+            ui.label(format!("#{}", header.id.0));
+            ui.end_row();
+
+            ui.label("Name");
+            ui.label(header.name.as_deref().unwrap_or("-"));
+            ui.end_row();
+
+            ui.label("Kind");
+            ui.label(header.kind.to_string());
+            ui.end_row();
+        });
+
+    ui.separator();
+    ui.strong("Phasing");
+
+    let mut phasing = inspected.phasing;
+
+    egui::Grid::new("properties_phasing")
+        .num_columns(2)
+        .spacing([8.0, 2.0])
+        .show(ui, |ui| {
+            ui.label("Phase Created");
+            egui::ComboBox::from_id_salt("phase_created")
+                .selected_text(phasing.created.to_string())
+                .show_ui(ui, |ui| {
+                    for phase in Phase::iter() {
+                        ui.selectable_value(&mut phasing.created, phase, phase.to_string());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Phase Demolished");
+            egui::ComboBox::from_id_salt("phase_demolished")
+                .selected_text(phasing.demolished.map_or("None".to_owned(), |phase| phase.to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut phasing.demolished, None, "None");
+                    // Can not be demolished before it is built
+                    for phase in Phase::iter().filter(|phase| *phase >= phasing.created) {
+                        ui.selectable_value(&mut phasing.demolished, Some(phase), phase.to_string());
+                    }
+                });
+            ui.end_row();
+        });
+
+    if phasing.demolished.is_some_and(|demolished| demolished < phasing.created) {
+        phasing.demolished = Some(phasing.created);
+    }
+
+    if phasing != inspected.phasing {
+        edits.push(ElementEdit::SetPhasing(entity, phasing));
+    }
+
+    if let Some(room) = &inspected.room {
+        ui.separator();
+        ui.strong("Room");
+
+        egui::Grid::new("properties_room")
+            .num_columns(2)
+            .spacing([8.0, 2.0])
+            .show(ui, |ui| {
+                ui.label("Number");
+                if let Some(number) = text_edit(ui, ("room_number", entity), &room.number, "None") {
+                    edits.push(ElementEdit::SetRoomNumber(entity, number));
+                }
+                ui.end_row();
+
+                ui.label("Name");
+                if let Some(name) = text_edit(ui, ("room_name", entity), header.name.as_deref().unwrap_or(""), "None") {
+                    edits.push(ElementEdit::SetRoomName(entity, name));
+                }
+                ui.end_row();
+
+                ui.label("Height").on_hover_text("Floor to ceiling, for the volume");
+                let mut height = room.height;
+                if ui.add(measure_drag(&mut height, Measure::Length, units).speed(0.01)).changed() {
+                    edits.push(ElementEdit::SetRoomHeight(entity, height));
+                }
+                ui.end_row();
+            });
+
+        if !room.enclosed {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "Not enclosed, the walls around it have moved. Its last boundary is kept.",
+            );
+        }
+    }
+
+    ui.separator();
+    ui.strong("Placement");
+
+    egui::Grid::new("properties_placement")
+        .num_columns(2)
+        .spacing([8.0, 2.0])
+        .show(ui, |ui| {
+            let current = inspected.anchor.map(|anchor| anchor.strategy);
+            ui.label("Anchor Strategy");
+            egui::ComboBox::from_id_salt("anchor_strategy")
+                .selected_text(current.map_or("Not anchored".to_owned(), |strategy| strategy.to_string()))
+                .show_ui(ui, |ui| {
+                    for strategy in AnchorStrategy::iter() {
+                        let picked = ui
+                            .selectable_label(current == Some(strategy), strategy.to_string())
+                            .on_hover_text(strategy.description());
+                        if picked.clicked() && current != Some(strategy) {
+                            edits.push(ElementEdit::SetAnchor(entity, strategy));
+                        }
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Host");
+            ui.label(
+                inspected
+                    .anchor
+                    .and_then(|anchor| anchor.host)
+                    .map_or("None".to_owned(), |host| format!("#{}", host.0)),
+            );
+            ui.end_row();
+
+            if GridReference::can_reference(header.kind) {
+                let reference = inspected.grid_reference.as_ref();
+                let at = reference.map(|reference| &reference.at);
+                let towards = reference.and_then(|reference| reference.towards.as_ref());
+
+                ui.label("Grid Point").on_hover_text("Intersection it stands on, like A/1");
+                if let Some(at) = grid_point_edit(ui, ("grid_point", entity), at) {
+                    let towards = at.as_ref().and(towards.cloned());
+                    edits.push(ElementEdit::SetGridReference(entity, at, towards));
+                }
+                ui.end_row();
+
+                ui.label("Facing").on_hover_text("Intersection it runs towards, for walls and beams");
+                if let Some(towards) = grid_point_edit(ui, ("grid_facing", entity), towards)
+                    && let Some(at) = at
+                {
+                    edits.push(ElementEdit::SetGridReference(entity, Some(at.clone()), towards));
+                }
+                ui.end_row();
+            }
+        });
+
+    if !header.params.is_empty() {
+        ui.separator();
+        ui.strong("Parameters");
+
+        egui::Grid::new("properties_params")
+            .num_columns(2)
+            .spacing([8.0, 2.0])
+            .show(ui, |ui| {
+                for (key, value) in &header.params {
+                    ui.label(&key.0);
+                    // Section dimensions are kept in meters and shown in the project units
+                    let shown = match value {
+                        ParamValue::Float(value) if LENGTH_PARAMS.contains(&key.0.as_str()) => {
+                            units.format(Measure::Length, *value)
+                        }
+                        ParamValue::Float(value) if key.0 == SECTION_AREA_PARAM => units.format(Measure::Area, *value),
+                        // Room measures, from its boundary
+                        ParamValue::Float(value) if [AREA_PARAM, GROSS_AREA_PARAM].contains(&key.0.as_str()) => {
+                            units.format(Measure::Area, *value)
+                        }
+                        ParamValue::Float(value) if key.0 == PERIMETER_PARAM => units.format(Measure::Length, *value),
+                        ParamValue::Float(value) if key.0 == VOLUME_PARAM => units.format(Measure::Volume, *value),
+                        value => value.to_string(),
+                    };
+                    ui.label(shown);
+                    ui.end_row();
+                }
+            });
+    }
+
+    if !inspected.connections.is_empty() {
+        ui.separator();
+        ui.strong("Connections");
+
+        egui::Grid::new("properties_connections")
+            .num_columns(2)
+            .spacing([8.0, 2.0])
+            .show(ui, |ui| {
+                for (port, connection) in inspected.connections.iter().enumerate() {
+                    ui.label(&connection.label).on_hover_text(format!("Size {}", connection.size));
+                    ui.label(match &connection.connected {
+                        Some((id, Some(name))) => format!("#{} {name}", id.0),
+                        Some((id, None)) => format!("#{}", id.0),
+                        None => "Open".to_owned(),
+                    });
+                    ui.end_row();
+
+                    // Open outlets stand in for the terminals that will go there
+                    let carries_flow = inspected.system.as_ref().is_some_and(|system| system.flow.is_some());
+                    if carries_flow && connection.connected.is_none() && connection.flow == FlowDirection::Source {
+                        ui.label("Design Flow").on_hover_text("Drawn off this open end");
+                        let mut flow = connection.design_flow.unwrap_or(0.0);
+                        if ui.add(measure_drag(&mut flow, Measure::Flow, units).speed(0.001)).changed() {
+                            edits.push(ElementEdit::SetDesignFlow(entity, port, Some(flow)));
+                        }
+                        ui.end_row();
+                    }
+                }
+            });
+    }
+
+    if let Some(system) = &inspected.system {
+        ui.separator();
+        ui.strong("System");
+
+        egui::Grid::new("properties_system")
+            .num_columns(2)
+            .spacing([8.0, 2.0])
+            .show(ui, |ui| {
+                ui.label("System");
+                egui::ComboBox::from_id_salt(("properties_system", entity))
+                    .selected_text(system_name(system.system))
+                    .show_ui(ui, |ui| {
+                        for &choice in system.domain.systems() {
+                            if ui.selectable_label(choice == system.system, system_name(choice)).clicked()
+                                && choice != system.system
+                            {
+                                edits.push(ElementEdit::SetSystem(entity, choice));
+                            }
+                        }
+                    });
+                ui.end_row();
+
+                if let Some(flow) = &system.flow {
+                    ui.label("Flow Rate");
+                    ui.label(units.format(Measure::Flow, f64::from(flow.flow)));
+                    ui.end_row();
+
+                    ui.label("Velocity");
+                    ui.label(units.format(Measure::Velocity, f64::from(flow.velocity)));
+                    ui.end_row();
+
+                    ui.label("Pressure Drop");
+                    ui.label(units.format(Measure::Pressure, f64::from(flow.pressure_drop)));
+                    ui.end_row();
+
+                    ui.label("From Source").on_hover_text("Lost from the open inlet up to this element's outlet");
+                    ui.label(units.format(Measure::Pressure, f64::from(flow.drop_from_source)));
+                    ui.end_row();
+                }
+            });
+
+        for issue in &system.issues {
+            ui.colored_label(ui.visuals().warn_fg_color, issue);
+        }
+    }
+
+    ui.separator();
+    ui.strong("Cost");
+
+    let Some(line) = estimate.lines.get(&entity) else {
+        ui.label("No quantities yet.");
+        return;
+    };
+    let item = line.item.and_then(|id| schedule.get(id));
+
+    egui::Grid::new("properties_cost")
+        .num_columns(2)
+        .spacing([8.0, 2.0])
+        .show(ui, |ui| {
+            ui.label("Length");
+            ui.label(units.format(Measure::Length, line.quantities.length));
+            ui.end_row();
+
+            ui.label("Area");
+            ui.label(units.format(Measure::Area, line.quantities.area));
+            ui.end_row();
+
+            ui.label("Volume");
+            ui.label(units.format(Measure::Volume, line.quantities.volume));
+            ui.end_row();
+
+            ui.label("Cost Item");
+            ui.label(item.map_or("Unpriced".to_owned(), |item| {
+                format!("{} {}", item.code, item.description)
+            }));
+            ui.end_row();
+
+            ui.label("Cost");
+            ui.label(money(line.cost, &schedule.currency));
+            ui.end_row();
+        });
+}
+
+// Typed like A/1, applied when the field is left. Some(None) clears it, None leaves it alone.
+fn grid_point_edit(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, current: Option<&GridPoint>) -> Option<Option<GridPoint>> {
+    let shown = current.map(GridPoint::to_string).unwrap_or_default();
+    let text = text_edit(ui, id_salt, &shown, "None")?;
+    if text.is_empty() {
+        return Some(None);
+    }
+    GridPoint::parse(&text).map(Some)
+}
+
+// Text applied when the field is left, trimmed. None while typing or when it did not change.
+fn text_edit(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, shown: &str, hint: &str) -> Option<String> {
+    let id = ui.make_persistent_id(id_salt);
+    let mut text = ui.data_mut(|data| data.get_temp::<String>(id)).unwrap_or_else(|| shown.to_owned());
+
+    let response = ui.add(egui::TextEdit::singleline(&mut text).id(id).hint_text(hint).desired_width(80.0));
+    if response.has_focus() {
+        ui.data_mut(|data| data.insert_temp(id, text.clone()));
+    }
+    if !response.lost_focus() {
+        return None;
+    }
+    ui.data_mut(|data| data.remove::<String>(id));
+    (text.trim() != shown).then(|| text.trim().to_owned())
+}
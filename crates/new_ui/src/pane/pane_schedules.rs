@@ -0,0 +1,607 @@
+use bevy_egui::egui;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+use new_core::element::{ParamKey, ParamType, ParamValue};
+use new_core::elements::ElementKind;
+use new_core::exchange::{ExchangeCommand, ImportPreview, IssueLevel, SheetExchange, SheetFormat};
+use new_core::schedule::{
+    FormatCondition, FormatRule, ScheduleColumn, ScheduleDefinition, ScheduleEdit, ScheduleField,
+    ScheduleId, ScheduleRow, ScheduleTables, Schedules,
+};
+use new_core::units::{Measure, ProjectUnits};
+
+use crate::utils::paint_opaque_pane_background;
+
+const CELL_WIDTH: f32 = 90.0;
+
+// Returns true when a definition was edited so the tables get rebuilt
+pub fn show(
+    ui: &mut egui::Ui,
+    schedules: &mut Schedules,
+    tables: &ScheduleTables,
+    edits: &mut Vec<ScheduleEdit>,
+    exchange: &mut SheetExchange,
+    exchange_commands: &mut Vec<ExchangeCommand>,
+    units: &ProjectUnits,
+) -> bool {
+    paint_opaque_pane_background(ui);
+
+    let mut edited = false;
+
+    ui.horizontal(|ui| {
+        let active_name = schedules
+            .active
+            .and_then(|id| schedules.get(id))
+            .map_or("-".to_owned(), |definition| definition.name.clone());
+
+        egui::ComboBox::from_id_salt("schedule_active")
+            .selected_text(active_name)
+            .width(180.0)
+            .show_ui(ui, |ui| {
+                for definition in &schedules.definitions {
+                    ui.selectable_value(&mut schedules.active, Some(definition.id), &definition.name);
+                }
+            });
+
+        if ui.button("New").clicked() {
+            let id = schedules.add(
+                "New Schedule",
+                Vec::new(),
+                vec![
+                    ScheduleColumn::new(ScheduleField::Id),
+                    ScheduleColumn::new(ScheduleField::Kind),
+                    ScheduleColumn::new(ScheduleField::Name),
+                ],
+            );
+            schedules.active = Some(id);
+            edited = true;
+        }
+
+        if let Some(id) = schedules.active
+            && ui.button("Delete").clicked()
+        {
+            schedules.remove(id);
+            edited = true;
+        }
+    });
+
+    let Some(definition) = schedules.active.and_then(|id| schedules.get_mut(id)) else {
+        ui.label("No schedule selected.");
+        return edited;
+    };
+
+    egui::CollapsingHeader::new("Definition")
+        .id_salt(("schedule_definition", definition.id.0))
+        .show(ui, |ui| {
+            edited |= definition_editor(ui, definition, tables);
+        });
+
+    egui::CollapsingHeader::new("Spreadsheet")
+        .id_salt("schedule_exchange")
+        .show(ui, |ui| {
+            exchange_controls(ui, definition.id, exchange, exchange_commands);
+        });
+
+    if let Some(preview) = &exchange.preview {
+        import_preview(ui, preview, exchange_commands);
+    }
+
+    ui.separator();
+
+    let rows = tables
+        .rows
+        .get(&definition.id)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    egui::ScrollArea::both()
+        .id_salt(("schedule_table", definition.id.0))
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            edited |= table(ui, definition, rows, edits, units);
+        });
+
+    edited
+}
+
+fn table(
+    ui: &mut egui::Ui,
+    definition: &mut ScheduleDefinition,
+    rows: &[ScheduleRow],
+    edits: &mut Vec<ScheduleEdit>,
+    units: &ProjectUnits,
+) -> bool {
+    let mut edited = false;
+    let groups = definition.arrange(rows);
+    let has_totals = definition.show_totals && definition.columns.iter().any(|column| column.total);
+
+    egui::Grid::new(("schedule_grid", definition.id.0))
+        .num_columns(definition.columns.len())
+        .striped(true)
+        .spacing([6.0, 2.0])
+        .show(ui, |ui| {
+            // Clicking a heading sorts by it, clicking again flips the direction
+            for (index, column) in definition.columns.iter().enumerate() {
+                let arrow = match definition.sort.first() {
+                    Some(&(sorted, true)) if sorted == index => " ⏶",
+                    Some(&(sorted, false)) if sorted == index => " ⏷",
+                    _ => "",
+                };
+                if ui
+                    .add(egui::Button::new(egui::RichText::new(format!("{}{arrow}", column.heading)).strong()).frame(false))
+                    .clicked()
+                {
+                    let ascending = !matches!(definition.sort.first(), Some(&(sorted, true)) if sorted == index);
+                    definition.sort.retain(|(sorted, _)| *sorted != index);
+                    definition.sort.insert(0, (index, ascending));
+                    edited = true;
+                }
+            }
+            ui.end_row();
+
+            for group in &groups {
+                if let Some(label) = &group.label {
+                    ui.strong(format!("{label} ({})", group.rows.len()));
+                    ui.end_row();
+                }
+
+                for row in &group.rows {
+                    for (index, column) in definition.columns.iter().enumerate() {
+                        let value = row.cell(index);
+                        let color = column
+                            .format(value, units)
+                            .map(|[r, g, b]| egui::Color32::from_rgb(r, g, b));
+
+                        if !column.field.editable() {
+                            let mut text = egui::RichText::new(column.text(value, units));
+                            if let Some(color) = color {
+                                text = text.color(color);
+                            }
+                            ui.label(text);
+                            continue;
+                        }
+
+                        let id = ui.make_persistent_id(("schedule_cell", row.entity, index));
+                        if let Some(value) = cell_edit(ui, id, value, column, units, color) {
+                            edits.push(ScheduleEdit {
+                                entity: row.entity,
+                                field: column.field.clone(),
+                                value,
+                            });
+                        }
+                    }
+                    ui.end_row();
+                }
+
+                if has_totals && group.label.is_some() {
+                    totals_row(ui, definition, definition.totals(group.rows.iter().copied()), "Subtotal", units);
+                }
+            }
+
+            if has_totals {
+                totals_row(ui, definition, definition.totals(rows), "Total", units);
+            }
+        });
+
+    ui.label(format!("{} elements", rows.len()));
+
+    edited
+}
+
+fn totals_row(
+    ui: &mut egui::Ui,
+    definition: &ScheduleDefinition,
+    totals: Vec<Option<f64>>,
+    label: &str,
+    units: &ProjectUnits,
+) {
+    for (index, (total, column)) in totals.iter().zip(&definition.columns).enumerate() {
+        match (total, column.measure) {
+            (Some(total), Some(measure)) => ui.strong(units.format(measure, *total)),
+            (Some(total), None) => ui.strong(format!("{total:.2}")),
+            (None, _) if index == 0 => ui.strong(label),
+            (None, _) => ui.label(""),
+        };
+    }
+    if definition.columns.is_empty() {
+        ui.strong(label);
+    }
+    ui.end_row();
+}
+
+// Edits a cell and hands back the new value once the edit is committed.
+// The text lives in egui memory while the cell has focus so half typed numbers survive.
+fn cell_edit(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    value: Option<&ParamValue>,
+    column: &ScheduleColumn,
+    units: &ProjectUnits,
+    color: Option<egui::Color32>,
+) -> Option<Option<ParamValue>> {
+    if column.field.value_type() == ParamType::Bool {
+        let mut checked = matches!(value, Some(ParamValue::Bool(true)));
+        return ui
+            .checkbox(&mut checked, "")
+            .changed()
+            .then_some(Some(ParamValue::Bool(checked)));
+    }
+
+    let mut text = ui
+        .data_mut(|data| data.get_temp::<String>(id))
+        .unwrap_or_else(|| column.text(value, units));
+
+    let mut edit = egui::TextEdit::singleline(&mut text)
+        .id(id)
+        .desired_width(CELL_WIDTH);
+    if let Some(color) = color {
+        edit = edit.text_color(color);
+    }
+    let response = ui.add(edit);
+
+    if response.has_focus() {
+        ui.data_mut(|data| data.insert_temp(id, text.clone()));
+    }
+    if !response.lost_focus() {
+        return None;
+    }
+    ui.data_mut(|data| data.remove::<String>(id));
+    // Rounded for display, reading it back would lose the digits hidden by the precision
+    if text == column.text(value, units) {
+        return None;
+    }
+
+    let new = if text.trim().is_empty() {
+        None
+    } else {
+        // Text that does not fit the type falls back to the old value
+        Some(column.parse(&text, units)?)
+    };
+
+    (new.as_ref() != value).then_some(new)
+}
+
+fn definition_editor(ui: &mut egui::Ui, definition: &mut ScheduleDefinition, tables: &ScheduleTables) -> bool {
+    let before = definition.clone();
+
+    ui.horizontal(|ui| {
+        ui.label("Name");
+        ui.text_edit_singleline(&mut definition.name);
+    });
+
+    // Kind filter
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Kinds");
+        if definition.kinds.is_empty() {
+            ui.weak("all");
+        }
+        let mut removed = None;
+        for (index, kind) in definition.kinds.iter().enumerate() {
+            if ui.small_button(format!("{kind} ✖")).clicked() {
+                removed = Some(index);
+            }
+        }
+        if let Some(index) = removed {
+            definition.kinds.remove(index);
+        }
+
+        let mut added: Option<ElementKind> = None;
+        egui::ComboBox::from_id_salt(("schedule_add_kind", definition.id.0))
+            .selected_text("Add kind")
+            .show_ui(ui, |ui| {
+                for kind in ElementKind::iter().filter(|kind| !definition.kinds.contains(kind)) {
+                    ui.selectable_value(&mut added, Some(kind), kind.to_string());
+                }
+            });
+        if let Some(kind) = added {
+            definition.kinds.push(kind);
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Group by");
+        let group_text = definition
+            .group_by
+            .and_then(|index| definition.columns.get(index))
+            .map_or("None".to_owned(), |column| column.heading.clone());
+        egui::ComboBox::from_id_salt(("schedule_group", definition.id.0))
+            .selected_text(group_text)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut definition.group_by, None, "None");
+                for (index, column) in definition.columns.iter().enumerate() {
+                    ui.selectable_value(&mut definition.group_by, Some(index), &column.heading);
+                }
+            });
+
+        ui.checkbox(&mut definition.show_totals, "Totals");
+    });
+
+    ui.label("Columns");
+    let mut action: Option<(usize, ColumnAction)> = None;
+
+    for (index, column) in definition.columns.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut column.heading).desired_width(110.0));
+            ui.weak(column.field.label());
+            ui.checkbox(&mut column.total, "Sum");
+            if column.field.value_type() == ParamType::Float {
+                measure_combo(ui, (definition.id.0, index), &mut column.measure);
+            }
+
+            if ui.small_button("⏶").clicked() {
+                action = Some((index, ColumnAction::Up));
+            }
+            if ui.small_button("⏷").clicked() {
+                action = Some((index, ColumnAction::Down));
+            }
+            if ui.small_button("✖").clicked() {
+                action = Some((index, ColumnAction::Remove));
+            }
+            if ui.small_button("+ Format").clicked() {
+                column.formats.push(FormatRule {
+                    condition: FormatCondition::Empty,
+                    operand: String::new(),
+                    color: [230, 90, 80],
+                });
+            }
+        });
+
+        let mut removed = None;
+        for (rule_index, rule) in column.formats.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add_space(16.0);
+                egui::ComboBox::from_id_salt(("schedule_format", definition.id.0, index, rule_index))
+                    .selected_text(rule.condition.to_string())
+                    .show_ui(ui, |ui| {
+                        for condition in FormatCondition::iter() {
+                            ui.selectable_value(&mut rule.condition, condition, condition.to_string());
+                        }
+                    });
+                if rule.condition != FormatCondition::Empty {
+                    ui.add(egui::TextEdit::singleline(&mut rule.operand).desired_width(70.0));
+                }
+                ui.color_edit_button_srgb(&mut rule.color);
+                if ui.small_button("✖").clicked() {
+                    removed = Some(rule_index);
+                }
+            });
+        }
+        if let Some(rule_index) = removed {
+            column.formats.remove(rule_index);
+        }
+    }
+
+    if let Some((index, action)) = action {
+        apply_column_action(definition, index, action);
+    }
+
+    add_column(ui, definition, tables);
+
+    *definition != before
+}
+
+fn measure_combo(ui: &mut egui::Ui, id_salt: (u32, usize), measure: &mut Option<Measure>) {
+    egui::ComboBox::from_id_salt(("schedule_measure", id_salt))
+        .selected_text(measure.map_or("Number".to_owned(), |measure| measure.to_string()))
+        .width(80.0)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(measure, None, "Number");
+            for choice in Measure::iter() {
+                ui.selectable_value(measure, Some(choice), choice.to_string());
+            }
+        });
+}
+
+#[derive(Clone, Copy)]
+enum ColumnAction {
+    Up,
+    Down,
+    Remove,
+}
+
+// Keeps sort and group indices pointing at the same columns
+fn apply_column_action(definition: &mut ScheduleDefinition, index: usize, action: ColumnAction) {
+    let remap = |definition: &mut ScheduleDefinition, map: &dyn Fn(usize) -> Option<usize>| {
+        definition.sort = definition
+            .sort
+            .iter()
+            .filter_map(|&(column, ascending)| map(column).map(|column| (column, ascending)))
+            .collect();
+        definition.group_by = definition.group_by.and_then(map);
+    };
+
+    match action {
+        ColumnAction::Up | ColumnAction::Down => {
+            let other = match action {
+                ColumnAction::Up if index > 0 => index - 1,
+                ColumnAction::Down if index + 1 < definition.columns.len() => index + 1,
+                _ => return,
+            };
+            definition.columns.swap(index, other);
+            remap(definition, &|column| {
+                Some(if column == index {
+                    other
+                } else if column == other {
+                    index
+                } else {
+                    column
+                })
+            });
+        }
+        ColumnAction::Remove => {
+            definition.columns.remove(index);
+            remap(definition, &|column| match column.cmp(&index) {
+                std::cmp::Ordering::Less => Some(column),
+                std::cmp::Ordering::Equal => None,
+                std::cmp::Ordering::Greater => Some(column - 1),
+            });
+        }
+    }
+}
+
+fn add_column(ui: &mut egui::Ui, definition: &mut ScheduleDefinition, tables: &ScheduleTables) {
+    let new_param_id = ui.make_persistent_id(("schedule_new_param", definition.id.0));
+    let (mut new_key, mut new_type) = ui
+        .data_mut(|data| data.get_temp::<(String, ParamType)>(new_param_id))
+        .unwrap_or_else(|| (String::new(), ParamType::Text));
+
+    ui.horizontal(|ui| {
+        let mut added: Option<ScheduleField> = None;
+
+        egui::ComboBox::from_id_salt(("schedule_add_column", definition.id.0))
+            .selected_text("Add column")
+            .show_ui(ui, |ui| {
+                let fields = [
+                    ScheduleField::Id,
+                    ScheduleField::Name,
+                    ScheduleField::Kind,
+                    ScheduleField::KindType,
+                    ScheduleField::Level,
+                    ScheduleField::Spec,
+                ]
+                .into_iter()
+                .chain(
+                    tables
+                        .model_params
+                        .iter()
+                        .map(|(key, param_type)| ScheduleField::Param(key.clone(), *param_type)),
+                );
+
+                for field in fields {
+                    let label = field.label();
+                    ui.selectable_value(&mut added, Some(field), label);
+                }
+            });
+
+        ui.separator();
+        ui.label("Param");
+        ui.add(egui::TextEdit::singleline(&mut new_key).desired_width(90.0));
+        egui::ComboBox::from_id_salt(("schedule_new_param_type", definition.id.0))
+            .selected_text(new_type.to_string())
+            .show_ui(ui, |ui| {
+                for param_type in ParamType::iter() {
+                    ui.selectable_value(&mut new_type, param_type, param_type.to_string());
+                }
+            });
+        if ui.button("Add").clicked() && !new_key.trim().is_empty() {
+            added = Some(ScheduleField::Param(ParamKey::new(new_key.trim()), new_type));
+            new_key.clear();
+        }
+
+        if let Some(field) = added {
+            definition.columns.push(ScheduleColumn::new(field));
+        }
+    });
+
+    ui.data_mut(|data| data.insert_temp(new_param_id, (new_key, new_type)));
+}
+
+fn exchange_controls(
+    ui: &mut egui::Ui,
+    schedule: ScheduleId,
+    exchange: &mut SheetExchange,
+    commands: &mut Vec<ExchangeCommand>,
+) {
+    ui.horizontal(|ui| {
+        ui.label("File");
+        ui.add(
+            egui::TextEdit::singleline(&mut exchange.path)
+                .hint_text("schedule.xlsx or .csv")
+                .desired_width(220.0),
+        );
+    });
+
+    let path = PathBuf::from(exchange.path.trim());
+    let known = SheetFormat::from_path(&path).is_some();
+
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(known, |ui| {
+            if ui.button("Export Schedule").clicked() {
+                commands.push(ExchangeCommand::ExportSchedule {
+                    schedule,
+                    path: path.clone(),
+                });
+            }
+            if ui.button("Export Parameters").clicked() {
+                commands.push(ExchangeCommand::ExportParameters { path: path.clone() });
+            }
+            if ui.button("Import").clicked() {
+                commands.push(ExchangeCommand::Import {
+                    schedule: Some(schedule),
+                    path: path.clone(),
+                });
+            }
+        });
+    });
+
+    if !known && !exchange.path.trim().is_empty() {
+        ui.weak("Use a .csv or .xlsx file");
+    }
+    if let Some(status) = &exchange.status {
+        ui.label(status);
+    }
+}
+
+fn import_preview(ui: &mut egui::Ui, preview: &ImportPreview, commands: &mut Vec<ExchangeCommand>) {
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.strong(format!("Import {}", preview.source.display()));
+            ui.label(format!(
+                "{} rows, {} changes, {} unchanged",
+                preview.rows,
+                preview.changes.len(),
+                preview.unchanged
+            ));
+        });
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(preview.can_apply(), egui::Button::new("Apply"))
+                .on_disabled_hover_text("Fix the errors in the sheet and import again")
+                .clicked()
+            {
+                commands.push(ExchangeCommand::ApplyImport);
+            }
+            if ui.button("Discard").clicked() {
+                commands.push(ExchangeCommand::DiscardImport);
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .id_salt("schedule_import_preview")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for issue in &preview.issues {
+                    let color = match issue.level {
+                        IssueLevel::Error => ui.visuals().error_fg_color,
+                        IssueLevel::Warning => ui.visuals().warn_fg_color,
+                    };
+                    let row = issue.row.map_or(String::new(), |row| format!("Row {row} "));
+                    let column = issue.column.as_deref().map_or(String::new(), |column| format!("[{column}] "));
+                    ui.colored_label(color, format!("{row}{column}{}", issue.message));
+                }
+
+                egui::Grid::new("schedule_import_changes")
+                    .num_columns(4)
+                    .striped(true)
+                    .spacing([12.0, 2.0])
+                    .show(ui, |ui| {
+                        ui.strong("Element");
+                        ui.strong("Field");
+                        ui.strong("Current");
+                        ui.strong("Imported");
+                        ui.end_row();
+
+                        let text = |value: &Option<ParamValue>| value.as_ref().map_or("-".to_owned(), ToString::to_string);
+                        for change in &preview.changes {
+                            ui.label(format!("#{}", change.element.0));
+                            ui.label(change.field.label());
+                            ui.label(text(&change.old));
+                            ui.label(text(&change.new));
+                            ui.end_row();
+                        }
+                    });
+            });
+    });
+}
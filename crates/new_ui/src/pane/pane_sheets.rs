@@ -0,0 +1,438 @@
+use bevy::math::Vec2;
+use bevy_egui::egui;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+use new_core::drawing::{Compass, DrawingView, ViewKind};
+use new_core::phase::{Phase, PhaseFilter, ViewPhase};
+use new_core::sheet::{
+    DRAWING_SCALES, Orientation, PaperSize, Sheet, SheetCommand, SheetDrawings, SheetGraphics,
+    SheetNote, SheetPublish, SheetSet, ViewPlacement, compose,
+};
+use new_core::units::{Measure, ProjectUnits};
+
+use crate::pane::pane_timeline::date_edit;
+use crate::units::measure_drag;
+use crate::utils::paint_opaque_pane_background;
+
+const EDITOR_WIDTH: f32 = 300.0;
+
+// Returns true when the sheet set was edited so the drawings get redone
+pub fn show(
+    ui: &mut egui::Ui,
+    set: &mut SheetSet,
+    drawings: &SheetDrawings,
+    publish: &mut SheetPublish,
+    commands: &mut Vec<SheetCommand>,
+    units: &ProjectUnits,
+) -> bool {
+    paint_opaque_pane_background(ui);
+
+    let before = set.clone();
+
+    ui.horizontal(|ui| {
+        let active_name = set
+            .active
+            .and_then(|id| set.get(id))
+            .map_or("-".to_owned(), |sheet| format!("{} {}", sheet.number, sheet.name));
+
+        egui::ComboBox::from_id_salt("sheet_active")
+            .selected_text(active_name)
+            .width(200.0)
+            .show_ui(ui, |ui| {
+                for sheet in &set.sheets {
+                    ui.selectable_value(&mut set.active, Some(sheet.id), format!("{} {}", sheet.number, sheet.name));
+                }
+            });
+
+        if ui.button("New").clicked() {
+            let number = format!("A-{}", 101 + set.sheets.len());
+            set.active = Some(set.add(&number, "New Sheet"));
+        }
+
+        if let Some(id) = set.active
+            && ui.button("Delete").clicked()
+        {
+            set.remove(id);
+        }
+    });
+
+    publish_bar(ui, set, publish, commands);
+    ui.separator();
+
+    let Some(id) = set.active else {
+        ui.label("No sheet selected.");
+        return *set != before;
+    };
+
+    ui.horizontal_top(|ui| {
+        ui.vertical(|ui| {
+            ui.set_width(EDITOR_WIDTH);
+            egui::ScrollArea::vertical()
+                .id_salt("sheet_editor")
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    project_editor(ui, set);
+                    if let Some(sheet) = set.get_mut(id) {
+                        sheet_editor(ui, sheet, units);
+                    }
+                });
+        });
+
+        ui.separator();
+
+        if let Some(sheet) = set.get(id) {
+            let views = drawings.drawings.get(&id).map(Vec::as_slice).unwrap_or_default();
+            preview(ui, &compose(set, sheet, views));
+        }
+    });
+
+    *set != before
+}
+
+fn publish_bar(ui: &mut egui::Ui, set: &SheetSet, publish: &mut SheetPublish, commands: &mut Vec<SheetCommand>) {
+    ui.horizontal(|ui| {
+        ui.label("PDF");
+        ui.add(
+            egui::TextEdit::singleline(&mut publish.path)
+                .hint_text("sheets.pdf")
+                .desired_width(200.0),
+        );
+
+        let path = PathBuf::from(publish.path.trim());
+        let valid = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+
+        ui.add_enabled_ui(valid, |ui| {
+            if let Some(id) = set.active
+                && ui.button("Publish Sheet").clicked()
+            {
+                commands.push(SheetCommand::Publish {
+                    sheets: vec![id],
+                    path: path.clone(),
+                });
+            }
+            if ui.button("Publish All").clicked() {
+                commands.push(SheetCommand::Publish {
+                    sheets: set.sheets.iter().map(|sheet| sheet.id).collect(),
+                    path: path.clone(),
+                });
+            }
+        });
+
+        ui.separator();
+        ui.label("Sheet set");
+        ui.add(
+            egui::TextEdit::singleline(&mut publish.set_path)
+                .hint_text("sheets.toml")
+                .desired_width(160.0),
+        );
+
+        let set_path = PathBuf::from(publish.set_path.trim());
+        let valid = set_path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+        ui.add_enabled_ui(valid, |ui| {
+            if ui.button("Save").clicked() {
+                commands.push(SheetCommand::Save { path: set_path.clone() });
+            }
+            if ui.button("Open").clicked() {
+                commands.push(SheetCommand::Open { path: set_path.clone() });
+            }
+        });
+
+        if let Some(status) = &publish.status {
+            ui.label(status);
+        }
+    });
+}
+
+fn project_editor(ui: &mut egui::Ui, set: &mut SheetSet) {
+    egui::CollapsingHeader::new("Project")
+        .id_salt("sheet_project")
+        .show(ui, |ui| {
+            egui::Grid::new("sheet_project_grid").num_columns(2).show(ui, |ui| {
+                for (label, value) in [
+                    ("Project", &mut set.project),
+                    ("Client", &mut set.client),
+                    ("Drawn by", &mut set.drawn_by),
+                    ("Checked by", &mut set.checked_by),
+                ] {
+                    ui.label(label);
+                    ui.text_edit_singleline(value);
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+fn sheet_editor(ui: &mut egui::Ui, sheet: &mut Sheet, units: &ProjectUnits) {
+    egui::CollapsingHeader::new("Sheet")
+        .id_salt("sheet_fields")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("sheet_fields_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Number");
+                ui.text_edit_singleline(&mut sheet.number);
+                ui.end_row();
+
+                ui.label("Name");
+                ui.text_edit_singleline(&mut sheet.name);
+                ui.end_row();
+
+                ui.label("Status");
+                ui.text_edit_singleline(&mut sheet.status);
+                ui.end_row();
+
+                ui.label("Paper");
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("sheet_paper")
+                        .selected_text(sheet.paper.to_string())
+                        .show_ui(ui, |ui| {
+                            for paper in PaperSize::iter() {
+                                ui.selectable_value(&mut sheet.paper, paper, paper.to_string());
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("sheet_orientation")
+                        .selected_text(sheet.orientation.to_string())
+                        .show_ui(ui, |ui| {
+                            for orientation in Orientation::iter() {
+                                ui.selectable_value(&mut sheet.orientation, orientation, orientation.to_string());
+                            }
+                        });
+                });
+                ui.end_row();
+            });
+        });
+
+    egui::CollapsingHeader::new("Views")
+        .id_salt("sheet_views")
+        .default_open(true)
+        .show(ui, |ui| {
+            let mut removed = None;
+            for (index, placement) in sheet.placements.iter_mut().enumerate() {
+                ui.push_id(("sheet_view", index), |ui| {
+                    if placement_editor(ui, index, placement, units) {
+                        removed = Some(index);
+                    }
+                });
+                ui.separator();
+            }
+            if let Some(index) = removed {
+                sheet.placements.remove(index);
+            }
+
+            if ui.button("Add View").clicked() {
+                let center = sheet.paper_mm() * 0.4;
+                sheet.placements.push(ViewPlacement {
+                    view: DrawingView {
+                        name: format!("View {}", sheet.placements.len() + 1),
+                        kind: ViewKind::Plan { cut_height: 1.2 },
+                        phase: ViewPhase::default(),
+                    },
+                    scale: 50,
+                    center,
+                });
+            }
+        });
+
+    egui::CollapsingHeader::new("Notes")
+        .id_salt("sheet_notes")
+        .show(ui, |ui| {
+            let mut removed = None;
+            for (index, note) in sheet.notes.iter_mut().enumerate() {
+                ui.push_id(("sheet_note", index), |ui| {
+                    ui.add(egui::TextEdit::multiline(&mut note.text).desired_rows(2));
+                    ui.horizontal(|ui| {
+                        position_edit(ui, &mut note.position);
+                        ui.add(egui::DragValue::new(&mut note.height).range(1.0..=20.0).speed(0.1).suffix(" mm"));
+                        if ui.small_button("✖").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                });
+            }
+            if let Some(index) = removed {
+                sheet.notes.remove(index);
+            }
+
+            if ui.button("Add Note").clicked() {
+                sheet.notes.push(SheetNote {
+                    text: "Note".to_owned(),
+                    position: Vec2::new(30.0, 40.0),
+                    height: 2.5,
+                });
+            }
+        });
+
+    egui::CollapsingHeader::new("Revisions")
+        .id_salt("sheet_revisions")
+        .show(ui, |ui| {
+            let mut removed = None;
+            egui::Grid::new("sheet_revisions_grid").num_columns(5).show(ui, |ui| {
+                for (index, revision) in sheet.revisions.iter_mut().enumerate() {
+                    ui.label(revision.number.to_string());
+                    date_edit(ui, &mut revision.date);
+                    ui.add(egui::TextEdit::singleline(&mut revision.description).desired_width(110.0));
+                    ui.add(egui::TextEdit::singleline(&mut revision.by).desired_width(30.0));
+                    if ui.small_button("✖").clicked() {
+                        removed = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(index) = removed {
+                sheet.revisions.remove(index);
+            }
+
+            if ui.button("Add Revision").clicked() {
+                sheet.add_revision("", "");
+            }
+        });
+}
+
+// Returns true when the placement should be removed
+fn placement_editor(ui: &mut egui::Ui, index: usize, placement: &mut ViewPlacement, units: &ProjectUnits) -> bool {
+    let mut remove = false;
+
+    ui.horizontal(|ui| {
+        ui.strong((index + 1).to_string());
+        ui.add(egui::TextEdit::singleline(&mut placement.view.name).desired_width(140.0));
+        remove = ui.small_button("✖").clicked();
+    });
+
+    let kinds = [
+        ViewKind::Plan { cut_height: 1.2 },
+        ViewKind::Section {
+            facing: Compass::East,
+            offset: 0.0,
+        },
+        ViewKind::Elevation { side: Compass::North },
+    ];
+
+    ui.horizontal(|ui| {
+        let current = placement.view.kind.label();
+        egui::ComboBox::from_id_salt("view_kind")
+            .selected_text(current)
+            .show_ui(ui, |ui| {
+                for kind in kinds {
+                    if ui.selectable_label(current == kind.label(), kind.label()).clicked() && current != kind.label() {
+                        placement.view.kind = kind;
+                    }
+                }
+            });
+
+        match &mut placement.view.kind {
+            ViewKind::Plan { cut_height } => {
+                ui.label("Cut");
+                ui.add(measure_drag(cut_height, Measure::Length, units).speed(0.05));
+            }
+            ViewKind::Section { facing, offset } => {
+                compass_combo(ui, "Facing", facing);
+                ui.add(measure_drag(offset, Measure::Length, units).speed(0.05));
+            }
+            ViewKind::Elevation { side } => compass_combo(ui, "Side", side),
+        }
+    });
+
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("view_scale")
+            .selected_text(format!("1:{}", placement.scale))
+            .width(70.0)
+            .show_ui(ui, |ui| {
+                for scale in DRAWING_SCALES {
+                    ui.selectable_value(&mut placement.scale, scale, format!("1:{scale}"));
+                }
+            });
+        position_edit(ui, &mut placement.center);
+    });
+
+    ui.horizontal(|ui| {
+        let phase = &mut placement.view.phase;
+        egui::ComboBox::from_id_salt("view_phase")
+            .selected_text(phase.phase.to_string())
+            .show_ui(ui, |ui| {
+                for option in Phase::iter() {
+                    ui.selectable_value(&mut phase.phase, option, option.to_string());
+                }
+            });
+        egui::ComboBox::from_id_salt("view_phase_filter")
+            .selected_text(phase.filter.to_string())
+            .show_ui(ui, |ui| {
+                for filter in PhaseFilter::iter() {
+                    ui.selectable_value(&mut phase.filter, filter, filter.to_string());
+                }
+            });
+    });
+
+    remove
+}
+
+fn compass_combo(ui: &mut egui::Ui, label: &str, compass: &mut Compass) {
+    ui.label(label);
+    egui::ComboBox::from_id_salt(label)
+        .selected_text(compass.to_string())
+        .width(70.0)
+        .show_ui(ui, |ui| {
+            for option in Compass::iter() {
+                ui.selectable_value(compass, option, option.to_string());
+            }
+        });
+}
+
+fn position_edit(ui: &mut egui::Ui, position: &mut Vec2) {
+    ui.add(egui::DragValue::new(&mut position.x).speed(1.0).prefix("x ").suffix(" mm"));
+    ui.add(egui::DragValue::new(&mut position.y).speed(1.0).prefix("y ").suffix(" mm"));
+}
+
+// Paper fitted into the remaining space
+fn preview(ui: &mut egui::Ui, graphics: &SheetGraphics) {
+    let (rect, _) = ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+    if graphics.size.x <= 0.0 || graphics.size.y <= 0.0 {
+        return;
+    }
+
+    let margin = 12.0;
+    let scale = ((rect.width() - margin * 2.0) / graphics.size.x)
+        .min((rect.height() - margin * 2.0) / graphics.size.y)
+        .max(0.01);
+    let paper = egui::Rect::from_center_size(
+        rect.center(),
+        egui::vec2(graphics.size.x * scale, graphics.size.y * scale),
+    );
+
+    // Sheet y points up, screen y down
+    let to_screen = |point: Vec2| egui::pos2(paper.left() + point.x * scale, paper.bottom() - point.y * scale);
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(paper, 0.0, egui::Color32::WHITE);
+
+    for line in &graphics.lines {
+        let gray = (line.gray * 255.0) as u8;
+        let stroke = egui::Stroke::new(
+            (line.weight.mm() * scale).max(0.6),
+            egui::Color32::from_gray(gray),
+        );
+        let points = [to_screen(line.a), to_screen(line.b)];
+        if line.dashed {
+            painter.extend(egui::Shape::dashed_line(&points, stroke, 2.0 * scale, scale));
+        } else {
+            painter.line_segment(points, stroke);
+        }
+    }
+
+    for label in &graphics.labels {
+        let size = label.height / 0.72 * scale;
+        if size < 3.0 {
+            continue;
+        }
+        painter.text(
+            to_screen(label.position),
+            egui::Align2::LEFT_BOTTOM,
+            &label.text,
+            egui::FontId::proportional(size),
+            egui::Color32::BLACK,
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use bevy_egui::egui;
+
+use new_core::mep_system::{MepAnalysis, SystemCommand, system_name};
+use new_core::units::{Measure, ProjectUnits};
+
+use crate::utils::paint_opaque_pane_background;
+
+pub fn show(ui: &mut egui::Ui, analysis: &MepAnalysis, commands: &mut Vec<SystemCommand>, units: &ProjectUnits) {
+    paint_opaque_pane_background(ui);
+
+    ui.heading("Systems");
+    ui.label(format!(
+        "{} networks, {} issues",
+        analysis.networks.len(),
+        analysis.issues.len()
+    ));
+    ui.separator();
+
+    egui::ScrollArea::vertical()
+        .id_salt("system_list")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            if analysis.networks.is_empty() {
+                ui.label("No ducts, pipes or cable trays yet.");
+            }
+
+            for (index, network) in analysis.networks.iter().enumerate() {
+                let issues: Vec<_> = analysis
+                    .issues
+                    .iter()
+                    .filter(|issue| network.elements.contains(&issue.element))
+                    .collect();
+                let title = format!(
+                    "{} {} ({} elements)",
+                    system_name(network.system),
+                    network.domain,
+                    network.elements.len()
+                );
+                let title = match issues.len() {
+                    0 => egui::RichText::new(title),
+                    count => egui::RichText::new(format!("{title}, {count} issues")).color(ui.visuals().warn_fg_color),
+                };
+
+                egui::CollapsingHeader::new(title)
+                    .id_salt(("system_network", index))
+                    .default_open(!issues.is_empty())
+                    .show(ui, |ui| {
+                        if analysis.flows.contains_key(&network.elements[0]) {
+                            ui.label(format!(
+                                "Flow {}, index run {}",
+                                units.format(Measure::Flow, f64::from(network.flow)),
+                                units.format(Measure::Pressure, f64::from(network.index_drop)),
+                            ));
+                        }
+                        for issue in issues {
+                            let text = format!("#{} {}", issue.element.0, issue.message);
+                            if ui.selectable_label(false, text).on_hover_text("Select the element").clicked() {
+                                commands.push(SystemCommand::Select(issue.element));
+                            }
+                        }
+                    });
+            }
+        });
+}
@@ -0,0 +1,351 @@
+use bevy_egui::egui;
+use strum::IntoEnumIterator;
+
+use new_core::elements::element_kindtype_enums::TaskType;
+use new_core::sequence::{
+    ConstructionSequence, ConstructionTask, Date, SEQUENCE_TASK_TYPES, TaskEffect, Timeline,
+    TimelineCommand, TimelineDisplay, task_effect,
+};
+
+use crate::utils::paint_opaque_pane_background;
+
+const RULER_HEIGHT: f32 = 22.0;
+const ROW_HEIGHT: f32 = 20.0;
+const LABEL_WIDTH: f32 = 160.0;
+// Days shown before the first and after the last task
+const MARGIN_DAYS: i32 = 3;
+
+// Returns true when the timeline was edited, sequence edits are flagged in `sequence_edited`
+pub fn show(
+    ui: &mut egui::Ui,
+    timeline: &mut Timeline,
+    sequence: &mut ConstructionSequence,
+    sequence_edited: &mut bool,
+    commands: &mut Vec<TimelineCommand>,
+) -> bool {
+    paint_opaque_pane_background(ui);
+
+    let mut edited = toolbar(ui, sequence, timeline);
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        if ui.button("Add Task").clicked() {
+            let start = timeline.playhead;
+            let id = sequence.add(
+                &format!("Task {}", sequence.tasks.len() + 1),
+                TaskType::CONSTRUCTION,
+                start,
+                start.add_days(6),
+            );
+            timeline.selected_task = Some(id);
+            *sequence_edited = true;
+            edited = true;
+        }
+
+        let Some(selected) = timeline.selected_task else {
+            return;
+        };
+
+        if ui.button("Remove Task").clicked() {
+            sequence.remove(selected);
+            timeline.selected_task = None;
+            *sequence_edited = true;
+            edited = true;
+            return;
+        }
+        if ui.button("Link Selection").clicked() {
+            commands.push(TimelineCommand::LinkSelection(selected));
+        }
+        if ui.button("Unlink Selection").clicked() {
+            commands.push(TimelineCommand::UnlinkSelection(selected));
+        }
+    });
+
+    let chart_height = if timeline.selected_task.is_some() {
+        ui.available_height() * 0.6
+    } else {
+        ui.available_height()
+    };
+
+    egui::ScrollArea::vertical()
+        .id_salt("timeline_gantt")
+        .max_height(chart_height)
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            if sequence.tasks.is_empty() {
+                ui.label("No tasks yet. Add a task and link the selected element to it.");
+                return;
+            }
+            edited |= gantt(ui, sequence, timeline);
+        });
+
+    if let Some(id) = timeline.selected_task {
+        ui.separator();
+        match sequence.get_mut(id) {
+            Some(task) => *sequence_edited |= details(ui, task),
+            None => {
+                timeline.selected_task = None;
+                edited = true;
+            }
+        }
+    }
+
+    edited
+}
+
+// Returns true when a toolbar widget changed the timeline
+fn toolbar(ui: &mut egui::Ui, sequence: &ConstructionSequence, timeline: &mut Timeline) -> bool {
+    ui.horizontal(|ui| {
+        let mut edited = ui.checkbox(&mut timeline.enabled, "4D").changed();
+
+        let span = sequence.span();
+
+        if ui.button("⏮").on_hover_text("Start of sequence").clicked()
+            && let Some((start, _)) = span
+        {
+            timeline.playhead = start;
+            edited = true;
+        }
+        if ui.button("◀").on_hover_text("Previous day").clicked() {
+            timeline.playhead = timeline.playhead.add_days(-1);
+            edited = true;
+        }
+
+        let play_label = if timeline.playing { "⏸" } else { "▶" };
+        if ui.button(play_label).clicked() {
+            timeline.playing = !timeline.playing;
+            timeline.enabled |= timeline.playing;
+            edited = true;
+
+            // Playing from the end starts over
+            if timeline.playing
+                && let Some((start, end)) = span
+                && timeline.playhead >= end
+            {
+                timeline.playhead = start;
+            }
+        }
+
+        if ui.button("▶|").on_hover_text("Next day").clicked() {
+            timeline.playhead = timeline.playhead.add_days(1);
+            edited = true;
+        }
+        if ui.button("⏭").on_hover_text("End of sequence").clicked()
+            && let Some((_, end)) = span
+        {
+            timeline.playhead = end;
+            edited = true;
+        }
+
+        edited |= date_edit(ui, &mut timeline.playhead).changed();
+
+        edited |= ui
+            .add(
+                egui::DragValue::new(&mut timeline.days_per_second)
+                    .range(0.5..=365.0)
+                    .speed(0.5)
+                    .suffix(" days/s"),
+            )
+            .changed();
+
+        egui::ComboBox::from_id_salt("timeline_display")
+            .selected_text(timeline.display.to_string())
+            .show_ui(ui, |ui| {
+                for display in TimelineDisplay::iter() {
+                    edited |= ui
+                        .selectable_value(&mut timeline.display, display, display.to_string())
+                        .changed();
+                }
+            });
+
+        edited
+    })
+    .inner
+}
+
+// Returns true when the playhead was scrubbed or a task selected
+fn gantt(ui: &mut egui::Ui, sequence: &ConstructionSequence, timeline: &mut Timeline) -> bool {
+    let Some((start, end)) = sequence.span() else {
+        return false;
+    };
+
+    let first = start.min(timeline.playhead).add_days(-MARGIN_DAYS);
+    let last = end.max(timeline.playhead).add_days(MARGIN_DAYS);
+    let days = (first.days_until(last) + 1) as f32;
+
+    let height = RULER_HEIGHT + ROW_HEIGHT * sequence.tasks.len() as f32;
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), height),
+        egui::Sense::click_and_drag(),
+    );
+
+    let chart = egui::Rect::from_min_max(egui::pos2(rect.left() + LABEL_WIDTH, rect.top()), rect.max);
+    let day_width = chart.width() / days;
+    let x_of = |date: Date| chart.left() + first.days_until(date) as f32 * day_width;
+    let date_at = |x: f32| first.add_days(((x - chart.left()) / day_width).floor() as i32);
+
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    let font = egui::TextStyle::Small.resolve(ui.style());
+
+    // Ruler
+    let ruler = egui::Rect::from_min_max(chart.min, egui::pos2(chart.right(), chart.top() + RULER_HEIGHT));
+    painter.rect_filled(ruler, 0.0, visuals.extreme_bg_color);
+
+    let step = [1, 7, 14, 30, 91, 365]
+        .into_iter()
+        .find(|step| *step as f32 * day_width >= 80.0)
+        .unwrap_or(365);
+    let mut tick = first;
+    while tick <= last {
+        let x = x_of(tick);
+        painter.line_segment(
+            [egui::pos2(x, ruler.top()), egui::pos2(x, rect.bottom())],
+            egui::Stroke::new(1.0, visuals.faint_bg_color),
+        );
+        painter.text(
+            egui::pos2(x + 2.0, ruler.center().y),
+            egui::Align2::LEFT_CENTER,
+            tick.to_string(),
+            font.clone(),
+            visuals.weak_text_color(),
+        );
+        tick = tick.add_days(step);
+    }
+
+    // Task rows
+    for (row, task) in sequence.tasks.iter().enumerate() {
+        let top = chart.top() + RULER_HEIGHT + row as f32 * ROW_HEIGHT;
+        let selected = timeline.selected_task == Some(task.id);
+
+        if selected {
+            painter.rect_filled(
+                egui::Rect::from_min_max(egui::pos2(rect.left(), top), egui::pos2(rect.right(), top + ROW_HEIGHT)),
+                0.0,
+                visuals.selection.bg_fill.gamma_multiply(0.4),
+            );
+        }
+
+        painter.text(
+            egui::pos2(rect.left() + 4.0, top + ROW_HEIGHT * 0.5),
+            egui::Align2::LEFT_CENTER,
+            format!("{} ({})", task.name, task.elements.len()),
+            font.clone(),
+            visuals.text_color(),
+        );
+
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(x_of(task.start), top + 3.0),
+            egui::pos2(x_of(task.end.add_days(1)), top + ROW_HEIGHT - 3.0),
+        );
+        painter.rect_filled(bar, 3.0, effect_color(task_effect(task.task_type)));
+        if selected {
+            painter.rect_stroke(bar, 3.0, visuals.selection.stroke, egui::StrokeKind::Outside);
+        }
+    }
+
+    // Playhead sits in the middle of its day
+    let playhead_x = x_of(timeline.playhead) + day_width * 0.5;
+    painter.line_segment(
+        [egui::pos2(playhead_x, rect.top()), egui::pos2(playhead_x, rect.bottom())],
+        egui::Stroke::new(2.0, egui::Color32::from_rgb(230, 70, 60)),
+    );
+    painter.text(
+        egui::pos2(playhead_x + 3.0, rect.top()),
+        egui::Align2::LEFT_TOP,
+        timeline.playhead.to_string(),
+        font,
+        egui::Color32::from_rgb(230, 70, 60),
+    );
+
+    let Some(pointer) = response.interact_pointer_pos() else {
+        return false;
+    };
+
+    // Dragging anywhere or clicking the ruler scrubs, clicking a row selects its task
+    if response.dragged() || (response.clicked() && pointer.y < ruler.bottom()) {
+        let day = date_at(pointer.x);
+        if pointer.x >= chart.left() && timeline.playhead != day {
+            timeline.playhead = day;
+            return true;
+        }
+    } else if response.clicked() {
+        let row = ((pointer.y - ruler.bottom()) / ROW_HEIGHT).floor() as usize;
+        timeline.selected_task = sequence.tasks.get(row).map(|task| task.id);
+        return true;
+    }
+
+    false
+}
+
+// Returns true when the task was edited
+fn details(ui: &mut egui::Ui, task: &mut ConstructionTask) -> bool {
+    egui::Grid::new("timeline_task")
+        .num_columns(2)
+        .spacing([8.0, 2.0])
+        .show(ui, |ui| {
+            ui.label("Name");
+            let mut edited = ui.text_edit_singleline(&mut task.name).changed();
+            ui.end_row();
+
+            ui.label("Type");
+            egui::ComboBox::from_id_salt("timeline_task_type")
+                .selected_text(format!("{:?}", task.task_type))
+                .show_ui(ui, |ui| {
+                    for task_type in SEQUENCE_TASK_TYPES {
+                        edited |= ui
+                            .selectable_value(&mut task.task_type, task_type, format!("{task_type:?}"))
+                            .changed();
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Start");
+            if date_edit(ui, &mut task.start).changed() {
+                task.end = task.end.max(task.start);
+                edited = true;
+            }
+            ui.end_row();
+
+            ui.label("End");
+            if date_edit(ui, &mut task.end).changed() {
+                task.start = task.start.min(task.end);
+                edited = true;
+            }
+            ui.end_row();
+
+            ui.label("Duration");
+            ui.label(format!("{} days", task.duration_days()));
+            ui.end_row();
+
+            ui.label("Elements");
+            ui.label(
+                task.elements
+                    .iter()
+                    .map(|id| format!("#{}", id.0))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+            ui.end_row();
+
+            edited
+        })
+        .inner
+}
+
+pub fn date_edit(ui: &mut egui::Ui, date: &mut Date) -> egui::Response {
+    ui.add(
+        egui::DragValue::new(&mut date.0)
+            .speed(0.2)
+            .custom_formatter(|days, _| Date(days as i32).to_string())
+            .custom_parser(|text| Date::parse(text).map(|date| date.0 as f64)),
+    )
+}
+
+fn effect_color(effect: TaskEffect) -> egui::Color32 {
+    match effect {
+        TaskEffect::Build => egui::Color32::from_rgb(90, 170, 100),
+        TaskEffect::Remove => egui::Color32::from_rgb(210, 90, 80),
+        TaskEffect::None => egui::Color32::from_rgb(110, 140, 190),
+    }
+}
@@ -1,10 +1,26 @@
-use std::collections::HashMap;
+use bevy::math::Vec2;
 use bevy_egui::egui;
+use strum::IntoEnumIterator;
+
 use new_core::Pane;
+use new_core::asset_library::{AssetCommand, LibraryAssetId};
+use new_core::element::ElementId;
+use new_core::grid::{PICK_WORK_PLANE, ViewGrid, ViewGrids};
+use new_core::phase::{Phase, PhaseFilter, ViewPhases};
+use new_core::preferences::GRID_SPACING_RANGE;
+use new_core::tool::ToolCommand;
+use new_core::units::{Measure, ProjectUnits};
+
+use crate::units::measure_drag;
 
 pub fn show(
     ui: &mut egui::Ui,
-    pane: &mut Pane
+    pane: &mut Pane,
+    view_phases: &mut ViewPhases,
+    view_grids: &mut ViewGrids,
+    units: &ProjectUnits,
+    asset_commands: &mut Vec<AssetCommand>,
+    tool_commands: &mut Vec<ToolCommand>,
 ) {
     let rect = ui.max_rect();
 
@@ -17,5 +33,131 @@ pub fn show(
 
     // let rect = ui.max_rect();
     // visible_viewports.insert(pane.id, rect);
-    let _ = ui.allocate_rect(rect, egui::Sense::hover());
-}
\ No newline at end of file
+    let response = ui.allocate_rect(rect, egui::Sense::hover());
+
+    // An asset dragged from the library places an instance where it is let go
+    if response.dnd_hover_payload::<LibraryAssetId>().is_some() {
+        ui.painter().rect_stroke(
+            rect.shrink(1.0),
+            0.0,
+            ui.visuals().selection.stroke,
+            egui::StrokeKind::Inside,
+        );
+    }
+    if let Some(asset) = response.dnd_release_payload::<LibraryAssetId>()
+        && let Some(cursor) = ui.ctx().pointer_interact_pos()
+    {
+        asset_commands.push(AssetCommand::Place {
+            asset: *asset,
+            pane_id: pane.id,
+            cursor: Vec2::new(cursor.x, cursor.y),
+        });
+    }
+
+    view_bar(ui, rect, pane, view_phases, view_grids, units, tool_commands);
+}
+
+// Where the view bar sits in a viewport, clicks there are not picks
+pub fn view_bar_rect(viewport: egui::Rect) -> egui::Rect {
+    egui::Rect::from_min_size(viewport.min + egui::vec2(6.0, 6.0), egui::vec2(380.0, 22.0))
+}
+
+// Phase, phase filter and grid of this view, top left over the 3d image
+fn view_bar(
+    ui: &mut egui::Ui,
+    rect: egui::Rect,
+    pane: &Pane,
+    view_phases: &mut ViewPhases,
+    view_grids: &mut ViewGrids,
+    units: &ProjectUnits,
+    tool_commands: &mut Vec<ToolCommand>,
+) {
+    let bar = view_bar_rect(rect);
+    let mut view = view_phases.get(pane.id);
+    let mut grid = view_grids.get(pane.id);
+
+    ui.scope_builder(egui::UiBuilder::new().max_rect(bar), |ui| {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt(("view_phase", pane.id))
+                .selected_text(view.phase.to_string())
+                .width(140.0)
+                .show_ui(ui, |ui| {
+                    for phase in Phase::iter() {
+                        ui.selectable_value(&mut view.phase, phase, phase.to_string());
+                    }
+                });
+
+            egui::ComboBox::from_id_salt(("view_phase_filter", pane.id))
+                .selected_text(view.filter.to_string())
+                .width(150.0)
+                .show_ui(ui, |ui| {
+                    for filter in PhaseFilter::iter() {
+                        ui.selectable_value(&mut view.filter, filter, filter.to_string());
+                    }
+                });
+
+            ui.menu_button("Grid", |ui| grid_menu(ui, &mut grid, units, tool_commands));
+        });
+    });
+
+    if view != view_phases.get(pane.id) {
+        view_phases.views.insert(pane.id, view);
+    }
+    if grid != view_grids.get(pane.id) {
+        view_grids.views.insert(pane.id, grid);
+    }
+}
+
+fn grid_menu(ui: &mut egui::Ui, grid: &mut ViewGrid, units: &ProjectUnits, tool_commands: &mut Vec<ToolCommand>) {
+    ui.checkbox(&mut grid.visible, "Show Grid");
+    ui.checkbox(&mut grid.snap, "Snap to Grid");
+    ui.separator();
+
+    egui::Grid::new("view_grid_spacing").num_columns(2).show(ui, |ui| {
+        ui.label("Spacing");
+        ui.add(
+            measure_drag(&mut grid.spacing, Measure::Length, units)
+                .range(GRID_SPACING_RANGE)
+                .speed(0.01),
+        );
+        ui.end_row();
+
+        ui.label("Major line every");
+        ui.add(egui::DragValue::new(&mut grid.major_every).range(1..=100));
+        ui.end_row();
+    });
+    ui.separator();
+
+    ui.label("Work plane");
+    if grid.face.is_some() {
+        ui.horizontal(|ui| {
+            ui.weak("On a picked face");
+            if ui.button("Back to Level").clicked() {
+                grid.face = None;
+            }
+        });
+    } else {
+        egui::Grid::new("view_grid_level").num_columns(2).show(ui, |ui| {
+            ui.label("Level");
+            ui.horizontal(|ui| {
+                let mut on_level = grid.level_id.is_some();
+                if ui.checkbox(&mut on_level, "").changed() {
+                    grid.level_id = on_level.then_some(ElementId(0));
+                }
+                if let Some(level) = &mut grid.level_id {
+                    ui.add(egui::DragValue::new(&mut level.0).prefix("#"));
+                }
+            });
+            ui.end_row();
+
+            // Read from the level while the grid is on one
+            ui.label("Elevation");
+            ui.add_enabled(grid.level_id.is_none(), measure_drag(&mut grid.elevation, Measure::Length, units));
+            ui.end_row();
+        });
+    }
+    if ui.button("Pick Face…").clicked() {
+        tool_commands.push(ToolCommand::Activate(PICK_WORK_PLANE));
+        ui.close();
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use strum::IntoEnumIterator;
+
+use new_core::action::ActionInvoked;
+use new_core::preferences::{
+    AppearancePrefs, GRID_SPACING_RANGE, GridPrefs, NavigationPrefs, PreferenceSection, Preferences, PreferencesWindow,
+    SENSITIVITY_RANGE, Theme, UI_SCALE_RANGE,
+};
+use new_core::units::{Measure, ProjectUnits};
+
+use crate::actions::EDIT_KEYMAP;
+use crate::units::units_editor;
+
+// Theme and scale go to egui whenever they change, the rest is read where it is used
+pub fn apply_appearance(
+    mut contexts: EguiContexts,
+    preferences: Res<Preferences>,
+    mut applied: Local<Option<AppearancePrefs>>,
+) {
+    if applied.as_ref() == Some(&preferences.appearance) {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    ctx.set_visuals(match preferences.appearance.theme {
+        Theme::Dark => egui::Visuals::dark(),
+        Theme::Light => egui::Visuals::light(),
+    });
+    ctx.set_zoom_factor(preferences.appearance.ui_scale);
+    *applied = Some(preferences.appearance.clone());
+}
+
+// Sections down the side, the chosen one next to them. Changes apply as they are made.
+// Returns true when a preference was edited, the units of the open project are flagged apart.
+pub fn show(
+    ctx: &egui::Context,
+    window: &mut PreferencesWindow,
+    preferences: &mut Preferences,
+    project_units: &mut ProjectUnits,
+    project_units_edited: &mut bool,
+    invoked: &mut Vec<ActionInvoked>,
+) -> bool {
+    let mut edited = false;
+    let mut open = window.open;
+
+    egui::Window::new("Preferences")
+        .open(&mut open)
+        .collapsible(false)
+        .default_size(egui::vec2(460.0, 280.0))
+        .show(ctx, |ui| {
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(110.0);
+                    for section in PreferenceSection::iter() {
+                        ui.selectable_value(&mut window.section, section, section.to_string());
+                    }
+                });
+                ui.separator();
+                ui.vertical(|ui| {
+                    edited |= match window.section {
+                        PreferenceSection::Appearance => appearance_section(ui, &mut preferences.appearance),
+                        PreferenceSection::Navigation => navigation_section(ui, &mut preferences.navigation),
+                        PreferenceSection::Grid => grid_section(ui, &mut preferences.grid, project_units),
+                        PreferenceSection::Units => {
+                            units_section(ui, &mut preferences.units, project_units, project_units_edited)
+                        }
+                        PreferenceSection::Keymap => {
+                            keymap_section(ui, invoked);
+                            false
+                        }
+                    };
+                });
+            });
+        });
+
+    window.open = open;
+    edited
+}
+
+// Resets the section it is drawn in
+fn defaults_button<T: Default + PartialEq>(ui: &mut egui::Ui, prefs: &mut T) -> bool {
+    ui.separator();
+    let differs = *prefs != T::default();
+    if ui.add_enabled(differs, egui::Button::new("Restore Defaults")).clicked() {
+        *prefs = T::default();
+        return true;
+    }
+    false
+}
+
+fn appearance_section(ui: &mut egui::Ui, prefs: &mut AppearancePrefs) -> bool {
+    let mut edited = false;
+
+    egui::Grid::new("preferences_appearance").num_columns(2).show(ui, |ui| {
+        ui.label("Theme");
+        ui.horizontal(|ui| {
+            for theme in Theme::iter() {
+                edited |= ui.radio_value(&mut prefs.theme, theme, theme.to_string()).changed();
+            }
+        });
+        ui.end_row();
+
+        ui.label("UI scale");
+        edited |= ui
+            .add(egui::Slider::new(&mut prefs.ui_scale, UI_SCALE_RANGE).step_by(0.05))
+            .changed();
+        ui.end_row();
+    });
+
+    edited | defaults_button(ui, prefs)
+}
+
+fn navigation_section(ui: &mut egui::Ui, prefs: &mut NavigationPrefs) -> bool {
+    let mut edited = false;
+
+    egui::Grid::new("preferences_navigation").num_columns(2).show(ui, |ui| {
+        for (label, speed) in [
+            ("Orbit speed", &mut prefs.orbit_speed),
+            ("Pan speed", &mut prefs.pan_speed),
+            ("Zoom speed", &mut prefs.zoom_speed),
+        ] {
+            ui.label(label);
+            edited |= ui
+                .add(egui::Slider::new(speed, SENSITIVITY_RANGE).logarithmic(true).suffix("×"))
+                .changed();
+            ui.end_row();
+        }
+
+        ui.label("Invert zoom");
+        edited |= ui.checkbox(&mut prefs.invert_zoom, "Scroll up zooms out").changed();
+        ui.end_row();
+    });
+    ui.weak("Buttons to orbit and pan are set in the keymap");
+
+    edited | defaults_button(ui, prefs)
+}
+
+fn grid_section(ui: &mut egui::Ui, prefs: &mut GridPrefs, units: &ProjectUnits) -> bool {
+    let mut edited = false;
+
+    egui::Grid::new("preferences_grid").num_columns(2).show(ui, |ui| {
+        ui.label("Show grid");
+        edited |= ui.checkbox(&mut prefs.visible, "").changed();
+        ui.end_row();
+
+        ui.label("Spacing");
+        edited |= ui
+            .add(
+                egui::Slider::new(&mut prefs.spacing, GRID_SPACING_RANGE)
+                    .logarithmic(true)
+                    .custom_formatter(|value, _| units.format(Measure::Length, value))
+                    .custom_parser(|text| units.parse(Measure::Length, text)),
+            )
+            .changed();
+        ui.end_row();
+
+        ui.label("Major line every");
+        edited |= ui.add(egui::DragValue::new(&mut prefs.major_every).range(1..=100)).changed();
+        ui.end_row();
+
+        ui.label("Snap to grid");
+        edited |= ui.checkbox(&mut prefs.snap, "").changed();
+        ui.end_row();
+    });
+    ui.weak("New views start with these, each view keeps its own after that");
+
+    edited | defaults_button(ui, prefs)
+}
+
+// The open project and what new ones start with
+fn units_section(
+    ui: &mut egui::Ui,
+    prefs: &mut ProjectUnits,
+    project_units: &mut ProjectUnits,
+    project_units_edited: &mut bool,
+) -> bool {
+    ui.strong("This project");
+    *project_units_edited |= units_editor(ui, "project_units", project_units);
+    if ui
+        .add_enabled(*project_units != *prefs, egui::Button::new("Use for New Projects"))
+        .clicked()
+    {
+        *prefs = *project_units;
+        return true;
+    }
+    ui.separator();
+
+    ui.strong("New projects");
+    units_editor(ui, "preferences_units", prefs) | defaults_button(ui, prefs)
+}
+
+fn keymap_section(ui: &mut egui::Ui, invoked: &mut Vec<ActionInvoked>) {
+    ui.label("Key and mouse bindings, with presets for other programs, are edited in the Keymap pane.");
+    if ui.button("Open Keymap").clicked() {
+        invoked.push(ActionInvoked::new(EDIT_KEYMAP));
+    }
+}
@@ -0,0 +1,100 @@
+use bevy_egui::egui;
+
+use new_core::action::{ActionId, ActionRegistry};
+use new_core::keymap::Keymap;
+use new_core::tool::{
+    ActiveTool, CANCEL_TOOL, CONFIRM_TOOL, ToolCommand, ToolDef, ToolOption, ToolOptions, ToolRegistry, ToolValue,
+};
+use new_core::units::ProjectUnits;
+
+use crate::units::measure_drag;
+
+// Buttons for every registered tool
+pub fn show(
+    ui: &mut egui::Ui,
+    registry: &ToolRegistry,
+    active: &ActiveTool,
+    actions: &ActionRegistry,
+    keymap: &Keymap,
+    commands: &mut Vec<ToolCommand>,
+) {
+    for group in registry.groups() {
+        ui.weak(group);
+        for tool in registry.tools.iter().filter(|tool| tool.group == group) {
+            tool_button(ui, tool, active, key_text(actions, keymap, tool.id.action()), commands);
+        }
+        ui.separator();
+    }
+}
+
+// Options of the running tool, drawn after its buttons. Returns true when one was edited.
+pub fn options(
+    ui: &mut egui::Ui,
+    active: &ActiveTool,
+    actions: &ActionRegistry,
+    keymap: &Keymap,
+    options: &mut ToolOptions,
+    units: &ProjectUnits,
+) -> bool {
+    let Some(tool) = active.id else {
+        ui.weak("No tool, clicks select");
+        return false;
+    };
+
+    let mut edited = false;
+    if let Some(tool_options) = options.tools.get_mut(&tool) {
+        for option in tool_options {
+            edited |= option_editor(ui, option, units);
+        }
+    }
+    let hint: Vec<String> = [(CANCEL_TOOL, "cancels"), (CONFIRM_TOOL, "confirms")]
+        .into_iter()
+        .filter_map(|(id, does)| Some(format!("{} {does}", key_text(actions, keymap, id)?)))
+        .collect();
+    ui.weak(hint.join(" · "));
+
+    edited
+}
+
+// First key of an action as the keymap has it
+fn key_text(actions: &ActionRegistry, keymap: &Keymap, id: ActionId) -> Option<String> {
+    let def = actions.get(id)?;
+    keymap.bindings(def).first().map(ToString::to_string)
+}
+
+fn tool_button(
+    ui: &mut egui::Ui,
+    tool: &ToolDef,
+    active: &ActiveTool,
+    key: Option<String>,
+    commands: &mut Vec<ToolCommand>,
+) {
+    let running = active.id == Some(tool.id);
+    let tooltip = match key {
+        Some(key) => format!("{} ({key})", tool.tooltip),
+        None => tool.tooltip.to_owned(),
+    };
+
+    if ui.selectable_label(running, tool.label).on_hover_text(tooltip).clicked() {
+        commands.push(if running { ToolCommand::Exit } else { ToolCommand::Activate(tool.id) });
+    }
+}
+
+fn option_editor(ui: &mut egui::Ui, option: &mut ToolOption, units: &ProjectUnits) -> bool {
+    ui.label(option.key);
+    match &mut option.value {
+        ToolValue::Number { value, measure } => ui.add(measure_drag(value, *measure, units).speed(0.01)).changed(),
+        ToolValue::Toggle(value) => ui.checkbox(value, "").changed(),
+        ToolValue::Choice { choices, selected } => {
+            let mut changed = false;
+            egui::ComboBox::from_id_salt(("tool_option", option.key))
+                .selected_text(choices.get(*selected).map_or("", String::as_str))
+                .show_ui(ui, |ui| {
+                    for (index, choice) in choices.iter().enumerate() {
+                        changed |= ui.selectable_value(selected, index, choice).changed();
+                    }
+                });
+            changed
+        }
+    }
+}
@@ -1,29 +1,87 @@
 use bevy_egui::egui;
 use egui_tiles::{Behavior, TileId, UiResponse};
+use strum::IntoEnumIterator;
 
+use new_core::action::ActionRegistry;
+use new_core::anchor::PlacementSettings;
+use new_core::asset_library::{AssetBrowser, AssetCommand, AssetResults};
+use new_core::cad::{CadCommand, CadExchange, Underlays};
+use new_core::clash::{ClashCommand, ClashResults, ClashReview, ClashRules};
+use new_core::cost::{CostEstimate, CostSchedule, CostView};
+use new_core::exchange::{ExchangeCommand, SheetExchange};
+use new_core::grid::ViewGrids;
+use new_core::inspector::{ElementEdit, InspectedElement};
+use new_core::keymap::{Keymap, KeymapEditor};
+use new_core::mep_system::{MepAnalysis, SystemCommand};
+use new_core::model_exchange::{ModelCommand, ModelExchange};
 use new_core::pane_kind::PaneKind;
+use new_core::phase::ViewPhases;
+use new_core::schedule::{ScheduleEdit, ScheduleTables, Schedules};
+use new_core::sheet::{SheetCommand, SheetDrawings, SheetPublish, SheetSet};
+use new_core::sequence::{ConstructionSequence, Timeline, TimelineCommand};
+use new_core::tool::ToolCommand;
+use new_core::units::ProjectUnits;
 use new_core::Pane;
 
-pub struct TreeBehavior;
+// Everything the panes read or write during one dock pass.
+// Commands are collected here and sent by the dock system afterwards.
+pub struct TreeBehavior<'a> {
+    pub inspected: &'a InspectedElement,
+    pub element_edits: &'a mut Vec<ElementEdit>,
+    pub view_phases: &'a mut ViewPhases,
+    pub view_grids: &'a mut ViewGrids,
+    pub mep_analysis: &'a MepAnalysis,
+    pub system_commands: &'a mut Vec<SystemCommand>,
+    pub clash_results: &'a mut ClashResults,
+    pub clash_rules: &'a ClashRules,
+    pub clash_review: &'a mut ClashReview,
+    pub clash_commands: &'a mut Vec<ClashCommand>,
+    pub sequence: &'a mut ConstructionSequence,
+    pub sequence_edited: &'a mut bool,
+    pub timeline: &'a mut Timeline,
+    pub timeline_edited: &'a mut bool,
+    pub timeline_commands: &'a mut Vec<TimelineCommand>,
+    pub cost_estimate: &'a CostEstimate,
+    pub cost_schedule: &'a mut CostSchedule,
+    pub cost_schedule_edited: &'a mut bool,
+    pub cost_view: &'a mut CostView,
+    pub schedules: &'a mut Schedules,
+    pub schedules_edited: &'a mut bool,
+    pub schedule_tables: &'a ScheduleTables,
+    pub schedule_edits: &'a mut Vec<ScheduleEdit>,
+    pub sheet_exchange: &'a mut SheetExchange,
+    pub exchange_commands: &'a mut Vec<ExchangeCommand>,
+    pub sheets: &'a mut SheetSet,
+    pub sheets_edited: &'a mut bool,
+    pub sheet_drawings: &'a SheetDrawings,
+    pub sheet_publish: &'a mut SheetPublish,
+    pub sheet_commands: &'a mut Vec<SheetCommand>,
+    pub underlays: &'a mut Underlays,
+    pub underlays_edited: &'a mut bool,
+    pub cad_exchange: &'a mut CadExchange,
+    pub cad_commands: &'a mut Vec<CadCommand>,
+    pub model_exchange: &'a mut ModelExchange,
+    pub model_commands: &'a mut Vec<ModelCommand>,
+    pub asset_browser: &'a mut AssetBrowser,
+    pub asset_browser_edited: &'a mut bool,
+    pub asset_results: &'a AssetResults,
+    pub placement: &'a mut PlacementSettings,
+    pub asset_commands: &'a mut Vec<AssetCommand>,
+    pub tool_commands: &'a mut Vec<ToolCommand>,
+    pub action_registry: &'a ActionRegistry,
+    pub keymap: &'a mut Keymap,
+    pub keymap_edited: &'a mut bool,
+    pub keymap_editor: &'a mut KeymapEditor,
+    pub units: &'a ProjectUnits,
+}
 
-impl Behavior<Pane> for TreeBehavior {
+impl Behavior<Pane> for TreeBehavior<'_> {
     fn tab_title_for_pane(&mut self, pane: &Pane) -> egui::WidgetText {
-        match pane.kind {
-            PaneKind::Viewport => "Viewport".into(),
-            PaneKind::Properties => "Properties".into(),
-            PaneKind::Console => "Console".into(),
-            _ => "Pane".into(),
-        }
+        pane.kind.to_string().into()
     }
 
     fn pane_ui(&mut self, ui: &mut egui::Ui, _tile_id: TileId, pane: &mut Pane) -> UiResponse {
-        let title = match pane.kind {
-            PaneKind::Viewport => "Viewport",
-            PaneKind::Properties => "Properties",
-            PaneKind::Console => "Console",
-            _ => "Pane",
-        };
-        if pane_header(ui, title) {
+        if pane_header(ui, pane) {
             return UiResponse::DragStarted;
         }
 
@@ -31,8 +89,105 @@ impl Behavior<Pane> for TreeBehavior {
 
         match pane.kind {
             PaneKind::Console => crate::pane::pane_console::show(ui),
-            PaneKind::Properties => crate::pane::pane_properties::show(ui),
-            PaneKind::Viewport => crate::pane::pane_viewport::show(ui, pane),
+            PaneKind::Properties => crate::pane::pane_properties::show(
+                ui,
+                self.inspected,
+                self.element_edits,
+                self.cost_estimate,
+                self.cost_schedule,
+                self.units,
+            ),
+            PaneKind::Viewport => crate::pane::pane_viewport::show(
+                ui,
+                pane,
+                self.view_phases,
+                self.view_grids,
+                self.units,
+                self.asset_commands,
+                self.tool_commands,
+            ),
+            PaneKind::Clashes => crate::pane::pane_clashes::show(
+                ui,
+                self.clash_results,
+                self.clash_rules,
+                self.clash_review,
+                self.clash_commands,
+                self.units,
+            ),
+            PaneKind::Systems => crate::pane::pane_systems::show(
+                ui,
+                self.mep_analysis,
+                self.system_commands,
+                self.units,
+            ),
+            PaneKind::Timeline => {
+                *self.timeline_edited |= crate::pane::pane_timeline::show(
+                    ui,
+                    self.timeline,
+                    self.sequence,
+                    self.sequence_edited,
+                    self.timeline_commands,
+                );
+            }
+            PaneKind::Costs => {
+                *self.cost_schedule_edited |= crate::pane::pane_costs::show(
+                    ui,
+                    self.cost_estimate,
+                    self.cost_schedule,
+                    self.cost_view,
+                    self.units,
+                );
+            }
+            PaneKind::Schedules => {
+                *self.schedules_edited |= crate::pane::pane_schedules::show(
+                    ui,
+                    self.schedules,
+                    self.schedule_tables,
+                    self.schedule_edits,
+                    self.sheet_exchange,
+                    self.exchange_commands,
+                    self.units,
+                );
+            }
+            PaneKind::Sheets => {
+                *self.sheets_edited |= crate::pane::pane_sheets::show(
+                    ui,
+                    self.sheets,
+                    self.sheet_drawings,
+                    self.sheet_publish,
+                    self.sheet_commands,
+                    self.units,
+                );
+            }
+            PaneKind::Cad => {
+                *self.underlays_edited |= crate::pane::pane_cad::show(
+                    ui,
+                    self.underlays,
+                    self.cad_exchange,
+                    self.cad_commands,
+                    self.units,
+                );
+            }
+            PaneKind::Assets => {
+                *self.asset_browser_edited |= crate::pane::pane_assets::show(
+                    ui,
+                    self.asset_browser,
+                    self.asset_results,
+                    self.placement,
+                    self.asset_commands,
+                );
+            }
+            PaneKind::Models => {
+                crate::pane::pane_models::show(ui, self.model_exchange, self.model_commands);
+            }
+            PaneKind::Keymap => {
+                *self.keymap_edited |= crate::pane::pane_keymap::show(
+                    ui,
+                    self.action_registry,
+                    self.keymap,
+                    self.keymap_editor,
+                );
+            }
             _ => {}
         }
 
@@ -63,7 +218,7 @@ impl Behavior<Pane> for TreeBehavior {
     }
 }
 
-fn pane_header(ui: &mut egui::Ui, title: &str) -> bool {
+fn pane_header(ui: &mut egui::Ui, pane: &mut Pane) -> bool {
     let height = 24.0;
     let width = ui.available_width();
     let (rect, response) =
@@ -74,11 +229,28 @@ fn pane_header(ui: &mut egui::Ui, title: &str) -> bool {
     ui.painter().text(
         rect.left_center() + egui::vec2(8.0, 0.0),
         egui::Align2::LEFT_CENTER,
-        title,
+        pane.kind.to_string(),
         egui::TextStyle::Button.resolve(ui.style()),
         ui.visuals().text_color(),
     );
 
+    // Pane kind switcher, drawn over the drag handle so it gets the click
+    let picker_width = 110.0;
+    let picker_rect = egui::Rect::from_min_size(
+        rect.right_top() - egui::vec2(picker_width + 4.0, -2.0),
+        egui::vec2(picker_width, height - 4.0),
+    );
+    ui.scope_builder(egui::UiBuilder::new().max_rect(picker_rect), |ui| {
+        egui::ComboBox::from_id_salt(("pane_kind", pane.id))
+            .selected_text(pane.kind.to_string())
+            .width(picker_width)
+            .show_ui(ui, |ui| {
+                for kind in PaneKind::iter() {
+                    ui.selectable_value(&mut pane.kind, kind, kind.to_string());
+                }
+            });
+    });
+
     if response.hovered() {
         ui.ctx().set_cursor_icon(if response.dragged() {
             egui::CursorIcon::Grabbing
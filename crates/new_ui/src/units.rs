@@ -0,0 +1,83 @@
+use bevy_egui::egui;
+use strum::IntoEnumIterator;
+
+use new_core::units::{INCH_FRACTIONS, LengthUnit, Measure, PRECISION_RANGE, ProjectUnits, UnitChoice, UnitSystem};
+
+// Drag value over an SI value shown in project units. Typing parses, so 3' 4" works in a
+// metric project too.
+pub fn measure_drag<'a, T: egui::emath::Numeric>(
+    value: &'a mut T,
+    measure: Measure,
+    units: &ProjectUnits,
+) -> egui::DragValue<'a> {
+    let units = *units;
+    egui::DragValue::new(value)
+        .custom_formatter(move |value, _| units.format(measure, value))
+        .custom_parser(move |text| units.parse(measure, text))
+}
+
+// System, units per measure and rounding. Returns true when something changed.
+pub fn units_editor(ui: &mut egui::Ui, id_salt: &str, units: &mut ProjectUnits) -> bool {
+    let before = *units;
+
+    egui::Grid::new((id_salt, "units")).num_columns(2).show(ui, |ui| {
+        ui.label("System");
+        ui.horizontal(|ui| {
+            for system in UnitSystem::iter() {
+                if ui.radio(units.system == system, system.to_string()).clicked() && units.system != system {
+                    units.set_system(system);
+                }
+            }
+        });
+        ui.end_row();
+
+        ui.label("Length");
+        unit_combo(ui, (id_salt, Measure::Length), &mut units.length, units.system);
+        ui.end_row();
+
+        ui.label("Flow");
+        unit_combo(ui, (id_salt, Measure::Flow), &mut units.flow, units.system);
+        ui.end_row();
+
+        ui.label("Pressure");
+        unit_combo(ui, (id_salt, Measure::Pressure), &mut units.pressure, units.system);
+        ui.end_row();
+
+        ui.label("Decimal places");
+        ui.add(egui::Slider::new(&mut units.precision, PRECISION_RANGE));
+        ui.end_row();
+
+        if units.length == LengthUnit::FeetAndInches {
+            ui.label("Round inches to");
+            egui::ComboBox::from_id_salt((id_salt, "inch_fraction"))
+                .selected_text(format!("1/{}\"", units.inch_fraction))
+                .show_ui(ui, |ui| {
+                    for fraction in INCH_FRACTIONS {
+                        ui.selectable_value(&mut units.inch_fraction, fraction, format!("1/{fraction}\""));
+                    }
+                });
+            ui.end_row();
+        }
+
+        ui.label("Example");
+        ui.weak(format!(
+            "{}   {}   {}",
+            units.format(Measure::Length, 1.0143),
+            units.format(Measure::Area, 12.5),
+            units.format(Measure::Volume, 3.2),
+        ));
+        ui.end_row();
+    });
+
+    *units != before
+}
+
+fn unit_combo<T: UnitChoice>(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, value: &mut T, system: UnitSystem) {
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(value.to_string())
+        .show_ui(ui, |ui| {
+            for choice in T::iter().filter(|choice| choice.system() == system) {
+                ui.selectable_value(value, choice, choice.to_string());
+            }
+        });
+}